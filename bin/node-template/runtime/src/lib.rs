@@ -171,14 +171,18 @@ impl grandpa::Trait for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	pub const IndexDeposit: Balance = 1_000;
+}
+
 impl indices::Trait for Runtime {
 	/// The type for recording indexing into the account enumeration. If this ever overflows, there
 	/// will be problems!
 	type AccountIndex = AccountIndex;
-	/// Use the standard means of resolving an index hint from an id.
-	type ResolveHint = indices::SimpleResolveHint<Self::AccountId, Self::AccountIndex>;
-	/// Determine whether an account is dead.
-	type IsDeadAccount = Balances;
+	/// The currency in which the deposit for an index is held.
+	type Currency = Balances;
+	/// The deposit needed for claiming an index.
+	type Deposit = IndexDeposit;
 	/// The ubiquitous event type.
 	type Event = Event;
 }
@@ -208,7 +212,7 @@ impl balances::Trait for Runtime {
 	/// What to do if an account is fully reaped from the system.
 	type OnReapAccount = System;
 	/// What to do if a new account is created.
-	type OnNewAccount = Indices;
+	type OnNewAccount = ();
 	/// The ubiquitous event type.
 	type Event = Event;
 	type DustRemoval = ();
@@ -36,6 +36,7 @@ use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 use sp_runtime::{Perbill, traits::{Verify, IdentifyAccount}};
 
 pub use node_primitives::{AccountId, Balance, Signature};
+use node_primitives::AccountIndex;
 pub use node_runtime::GenesisConfig;
 
 type AccountPublic = <Signature as Verify>::Signer;
@@ -237,11 +238,12 @@ pub fn testnet_genesis(
 				.map(|k| (k, ENDOWMENT))
 				.chain(initial_authorities.iter().map(|x| (x.0.clone(), STASH)))
 				.collect(),
-			vesting: vec![],
 		}),
 		pallet_indices: Some(IndicesConfig {
-			ids: endowed_accounts.iter().cloned()
+			indices: endowed_accounts.iter().cloned()
 				.chain(initial_authorities.iter().map(|x| x.0.clone()))
+				.enumerate()
+				.map(|(index, account)| (index as AccountIndex, account))
 				.collect::<Vec<_>>(),
 		}),
 		pallet_session: Some(SessionConfig {
@@ -299,7 +301,8 @@ pub fn testnet_genesis(
 			members: endowed_accounts[0..3].to_vec(),
 			pot: 0,
 			max_members: 999,
-		})
+		}),
+		pallet_vesting: Some(Default::default()),
 	}
 }
 
@@ -19,7 +19,7 @@ use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
 use node_executor::Executor;
 use node_primitives::{BlockNumber, Hash};
 use node_runtime::{
-	Block, BuildStorage, Call, CheckedExtrinsic, GenesisConfig, Header, UncheckedExtrinsic,
+	Block, BuildStorage, Call, CheckedExtrinsic, GenesisConfig, Header, Runtime, UncheckedExtrinsic,
 };
 use node_runtime::constants::currency::*;
 use node_testing::keyring::*;
@@ -27,10 +27,12 @@ use sp_core::{Blake2Hasher, NativeOrEncoded, NeverNativeValue};
 use sp_core::storage::well_known_keys;
 use sp_core::traits::CodeExecutor;
 use	frame_support::Hashable;
+use sp_runtime::traits::Hash as HashT;
 use sp_state_machine::TestExternalities as CoreTestExternalities;
-use sc_executor::{NativeExecutor, RuntimeInfo, WasmExecutionMethod, Externalities};
+use sc_executor::{NativeExecutor, RuntimeInfo, SandboxBackend, WasmExecutionMethod, Externalities};
+use pallet_contracts::ContractAddressFor;
 
-criterion_group!(benches, bench_execute_block);
+criterion_group!(benches, bench_execute_block, bench_execute_contract_calls);
 criterion_main!(benches);
 
 /// The wasm runtime code.
@@ -44,6 +46,14 @@ const HEAP_PAGES: u64 = 20;
 
 type TestExternalities<H> = CoreTestExternalities<H, u64>;
 
+/// The `pallet-contracts` fixture hashing its input with the hash function selected by the first
+/// byte, so executing it stresses both the sandboxed code and the calls back into the supervisor.
+const CODE_CRYPTO_HASHES: &str =
+	include_str!("../../../../frame/contracts/fixtures/crypto_hashes.wat");
+
+/// The size of the data hashed by each contract call.
+const HASHED_INPUT_LEN: usize = 1024;
+
 #[derive(Debug)]
 enum ExecutionMethod {
 	Native,
@@ -194,3 +204,115 @@ fn bench_execute_block(c: &mut Criterion) {
 		],
 	);
 }
+
+fn contract_blocks(genesis_config: &GenesisConfig, executor: &NativeExecutor<Executor>)
+	-> Vec<(Vec<u8>, Hash)>
+{
+	let code = wabt::wat2wasm(CODE_CRYPTO_HASHES).unwrap();
+	let code_hash = <Runtime as frame_system::Trait>::Hashing::hash(&code);
+	let addr = <Runtime as pallet_contracts::Trait>::DetermineContractAddress::contract_address_for(
+		&code_hash,
+		&[],
+		&charlie(),
+	);
+
+	let mut test_ext = new_test_ext(genesis_config);
+	let block1 = construct_block(
+		executor,
+		&mut test_ext.ext(),
+		1,
+		GENESIS_HASH.into(),
+		vec![
+			CheckedExtrinsic {
+				signed: None,
+				function: Call::Timestamp(pallet_timestamp::Call::set(42 * 1000)),
+			},
+			CheckedExtrinsic {
+				signed: Some((charlie(), signed_extra(0, 0))),
+				function: Call::Contracts(pallet_contracts::Call::put_code::<Runtime>(100_000, code)),
+			},
+			CheckedExtrinsic {
+				signed: Some((charlie(), signed_extra(1, 0))),
+				function: Call::Contracts(
+					pallet_contracts::Call::instantiate::<Runtime>(1 * DOLLARS, 100_000, code_hash, Vec::new())
+				),
+			},
+		],
+	);
+
+	let mut block2_extrinsics = vec![
+		CheckedExtrinsic {
+			signed: None,
+			function: Call::Timestamp(pallet_timestamp::Call::set(52 * 1000)),
+		},
+	];
+	block2_extrinsics.extend((0..20).map(|i| {
+		// Cycle through the four hash functions supported by the fixture.
+		let mut input = vec![(i % 4) as u8];
+		input.extend_from_slice(&[0xaa; HASHED_INPUT_LEN]);
+		CheckedExtrinsic {
+			signed: Some((charlie(), signed_extra(i + 2, 0))),
+			function: Call::Contracts(
+				pallet_contracts::Call::call::<Runtime>(
+					pallet_indices::address::Address::Id(addr.clone()),
+					0,
+					1_000_000,
+					input,
+				)
+			),
+		}
+	}));
+	let block2 = construct_block(
+		executor,
+		&mut test_ext.ext(),
+		2,
+		block1.1,
+		block2_extrinsics,
+	);
+
+	vec![block1, block2]
+}
+
+fn bench_execute_contract_calls(c: &mut Criterion) {
+	c.bench_function_over_inputs(
+		"execute contract calls",
+		|b, (wasm_method, sandbox_backend)| {
+			let genesis_config = node_testing::genesis::config(false, Some(COMPACT_CODE));
+			let executor = NativeExecutor::new(*wasm_method, None)
+				.with_sandbox_backend(*sandbox_backend);
+
+			// Get the runtime version to initialize the runtimes cache.
+			{
+				let mut test_ext = new_test_ext(&genesis_config);
+				executor.runtime_version(&mut test_ext.ext());
+			}
+
+			let blocks = contract_blocks(&genesis_config, &executor);
+
+			b.iter_batched_ref(
+				|| new_test_ext(&genesis_config),
+				|test_ext| {
+					for block in blocks.iter() {
+						executor.call::<_, NeverNativeValue, fn() -> _>(
+							&mut test_ext.ext(),
+							"Core_execute_block",
+							&block.0,
+							false,
+							None,
+						).0.unwrap();
+					}
+				},
+				BatchSize::LargeInput,
+			);
+		},
+		vec![
+			(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmi),
+			#[cfg(feature = "wasmtime")]
+			(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmtime),
+			#[cfg(feature = "wasmtime")]
+			(WasmExecutionMethod::Compiled, SandboxBackend::Wasmi),
+			#[cfg(feature = "wasmtime")]
+			(WasmExecutionMethod::Compiled, SandboxBackend::Wasmtime),
+		],
+	);
+}
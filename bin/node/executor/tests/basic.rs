@@ -170,9 +170,6 @@ fn panic_execution_with_foreign_code_gives_error() {
 			<pallet_balances::TotalIssuance<Runtime>>::hashed_key().to_vec() => {
 				69_u128.encode()
 			},
-			<pallet_indices::NextEnumSet<Runtime>>::hashed_key().to_vec() => {
-				0_u128.encode()
-			},
 			<frame_system::BlockHash<Runtime>>::hashed_key_for(0) => {
 				vec![0u8; 32]
 			}
@@ -209,9 +206,6 @@ fn bad_extrinsic_with_native_equivalent_code_gives_error() {
 			<pallet_balances::TotalIssuance<Runtime>>::hashed_key().to_vec() => {
 				69_u128.encode()
 			},
-			<pallet_indices::NextEnumSet<Runtime>>::hashed_key().to_vec() => {
-				0_u128.encode()
-			},
 			<frame_system::BlockHash<Runtime>>::hashed_key_for(0) => {
 				vec![0u8; 32]
 			}
@@ -248,7 +242,6 @@ fn successful_execution_with_native_equivalent_code_gives_ok() {
 			<pallet_balances::TotalIssuance<Runtime>>::hashed_key().to_vec() => {
 				(111 * DOLLARS).encode()
 			},
-			<pallet_indices::NextEnumSet<Runtime>>::hashed_key().to_vec() => vec![0u8; 16],
 			<frame_system::BlockHash<Runtime>>::hashed_key_for(0) => vec![0u8; 32]
 		],
 		children: map![],
@@ -290,7 +283,6 @@ fn successful_execution_with_foreign_code_gives_ok() {
 			<pallet_balances::TotalIssuance<Runtime>>::hashed_key().to_vec() => {
 				(111 * DOLLARS).encode()
 			},
-			<pallet_indices::NextEnumSet<Runtime>>::hashed_key().to_vec() => vec![0u8; 16],
 			<frame_system::BlockHash<Runtime>>::hashed_key_for(0) => vec![0u8; 32]
 		],
 		children: map![],
@@ -716,7 +708,6 @@ fn panic_execution_gives_error() {
 			<pallet_balances::TotalIssuance<Runtime>>::hashed_key().to_vec() => {
 				0_u128.encode()
 			},
-			<pallet_indices::NextEnumSet<Runtime>>::hashed_key().to_vec() => vec![0u8; 16],
 			<frame_system::BlockHash<Runtime>>::hashed_key_for(0) => vec![0u8; 32]
 		],
 		children: map![],
@@ -751,7 +742,6 @@ fn successful_execution_gives_ok() {
 			<pallet_balances::TotalIssuance<Runtime>>::hashed_key().to_vec() => {
 				(111 * DOLLARS).encode()
 			},
-			<pallet_indices::NextEnumSet<Runtime>>::hashed_key().to_vec() => vec![0u8; 16],
 			<frame_system::BlockHash<Runtime>>::hashed_key_for(0) => vec![0u8; 32]
 		],
 		children: map![],
@@ -24,30 +24,36 @@ use sp_std::prelude::*;
 use frame_support::{
 	construct_runtime, parameter_types, debug,
 	weights::Weight,
-	traits::{SplitTwoWays, Currency, Randomness},
+	traits::{SplitTwoWays, Currency, Randomness, InstanceFilter, Filter},
 };
+use codec::{Encode, Decode};
 use sp_core::u32_trait::{_1, _2, _3, _4};
 use node_primitives::{AccountId, AccountIndex, Balance, BlockNumber, Hash, Index, Moment, Signature};
 use sp_api::impl_runtime_apis;
 use sp_runtime::{
-	Permill, Perbill, Percent, ApplyExtrinsicResult, impl_opaque_keys, generic, create_runtime_str
+	Permill, Perbill, Percent, ApplyExtrinsicResult, impl_opaque_keys, generic, create_runtime_str,
+	RuntimeDebug, DispatchError,
 };
 use sp_runtime::curve::PiecewiseLinear;
 use sp_runtime::transaction_validity::TransactionValidity;
 use sp_runtime::traits::{
 	self, BlakeTwo256, Block as BlockT, StaticLookup, SaturatedConversion,
-	OpaqueKeys,
+	OpaqueKeys, ConvertInto,
 };
 use sp_version::RuntimeVersion;
 #[cfg(any(feature = "std", test))]
 use sp_version::NativeVersion;
-use sp_core::OpaqueMetadata;
+use sp_core::{OpaqueMetadata, H160, H256, U256};
 use pallet_grandpa::AuthorityList as GrandpaAuthorityList;
 use pallet_grandpa::fg_primitives;
 use pallet_im_online::sr25519::{AuthorityId as ImOnlineId};
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 use pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo;
-use pallet_contracts_rpc_runtime_api::ContractExecResult;
+use pallet_contracts_rpc_runtime_api::{
+	CallKind, CallTrace, Code, ContractDebugExecResult, ContractExecResult,
+	ContractInstantiateResult,
+};
+use pallet_evm_rpc_runtime_api::ExecutionInfo as EvmExecutionInfo;
 use frame_system::offchain::TransactionSubmitter;
 use sp_inherents::{InherentData, CheckInherentsResult};
 
@@ -80,8 +86,8 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// and set impl_version to equal spec_version. If only runtime
 	// implementation changes and behavior does not, then leave spec_version as
 	// is and increment impl_version.
-	spec_version: 204,
-	impl_version: 204,
+	spec_version: 239,
+	impl_version: 239,
 	apis: RUNTIME_API_VERSIONS,
 };
 
@@ -145,6 +151,89 @@ impl pallet_utility::Trait for Runtime {
 	type MultisigDepositBase = MultisigDepositBase;
 	type MultisigDepositFactor = MultisigDepositFactor;
 	type MaxSignatories = MaxSignatories;
+	type IsCallable = IsCallable;
+}
+
+/// Calls which may be dispatched through the utility module by a non-root origin.
+pub struct IsCallable;
+impl Filter<Call> for IsCallable {
+	fn filter(c: &Call) -> bool {
+		match c {
+			// The gas limit of these is only checked against the block by `CheckBlockGasLimit`
+			// when they are the outermost call of an extrinsic.
+			Call::Contracts(pallet_contracts::Call::put_code(..))
+			| Call::Contracts(pallet_contracts::Call::call(..))
+			| Call::Contracts(pallet_contracts::Call::instantiate(..)) => false,
+			_ => true,
+		}
+	}
+}
+
+parameter_types! {
+	// One storage item; key size 32, value size 16 for the deposit.
+	pub const ProxyDepositBase: Balance = 20 * CENTS;
+	// Additional storage item size of 33 bytes.
+	pub const ProxyDepositFactor: Balance = 3 * CENTS;
+	pub const MaxProxies: u16 = 32;
+}
+
+/// The type used to represent the kinds of proxying allowed.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug)]
+pub enum ProxyType {
+	/// Any call may be proxied.
+	Any,
+	/// Any call which can not move funds may be proxied.
+	NonTransfer,
+	/// Only governance calls may be proxied.
+	Governance,
+	/// Only staking calls may be proxied.
+	Staking,
+}
+
+impl Default for ProxyType { fn default() -> Self { Self::Any } }
+
+impl InstanceFilter<Call> for ProxyType {
+	fn filter(&self, c: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			ProxyType::NonTransfer => match c {
+				Call::Balances(..) | Call::Assets(..) | Call::Contracts(..) | Call::Utility(..)
+				| Call::Proxy(..) | Call::Recovery(..) | Call::Sudo(..)
+				| Call::Indices(pallet_indices::Call::transfer(..))
+				| Call::Vesting(pallet_vesting::Call::vested_transfer(..)) => false,
+				_ => true,
+			},
+			ProxyType::Governance => match c {
+				Call::Democracy(..) | Call::Council(..) | Call::TechnicalCommittee(..)
+				| Call::Elections(..) | Call::Treasury(..) => true,
+				_ => false,
+			},
+			ProxyType::Staking => match c {
+				Call::Staking(..) | Call::Session(..) => true,
+				_ => false,
+			},
+		}
+	}
+
+	fn is_superset(&self, o: &Self) -> bool {
+		match (self, o) {
+			(x, y) if x == y => true,
+			(ProxyType::Any, _) => true,
+			(_, ProxyType::Any) => false,
+			(ProxyType::NonTransfer, _) => true,
+			_ => false,
+		}
+	}
+}
+
+impl pallet_proxy::Trait for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = MaxProxies;
 }
 
 parameter_types! {
@@ -158,10 +247,14 @@ impl pallet_babe::Trait for Runtime {
 	type EpochChangeTrigger = pallet_babe::ExternalTrigger;
 }
 
+parameter_types! {
+	pub const IndexDeposit: Balance = 1 * DOLLARS;
+}
+
 impl pallet_indices::Trait for Runtime {
 	type AccountIndex = AccountIndex;
-	type IsDeadAccount = Balances;
-	type ResolveHint = pallet_indices::SimpleResolveHint<Self::AccountId, Self::AccountIndex>;
+	type Currency = Balances;
+	type Deposit = IndexDeposit;
 	type Event = Event;
 }
 
@@ -175,7 +268,7 @@ impl pallet_balances::Trait for Runtime {
 	type Balance = Balance;
 	type OnFreeBalanceZero = ((Staking, Contracts), Session);
 	type OnReapAccount = (System, Recovery);
-	type OnNewAccount = Indices;
+	type OnNewAccount = ();
 	type Event = Event;
 	type DustRemoval = ();
 	type TransferPayment = ();
@@ -326,16 +419,22 @@ impl pallet_democracy::Trait for Runtime {
 	type Slash = Treasury;
 }
 
+parameter_types! {
+	pub const CouncilMotionDuration: BlockNumber = 5 * DAYS;
+}
+
 type CouncilCollective = pallet_collective::Instance1;
 impl pallet_collective::Trait<CouncilCollective> for Runtime {
 	type Origin = Origin;
 	type Proposal = Call;
 	type Event = Event;
+	type MotionDuration = CouncilMotionDuration;
 }
 
 parameter_types! {
 	pub const CandidacyBond: Balance = 10 * DOLLARS;
 	pub const VotingBond: Balance = 1 * DOLLARS;
+	pub const VotingBondFactor: Balance = 10 * CENTS;
 	pub const TermDuration: BlockNumber = 7 * DAYS;
 	pub const DesiredMembers: u32 = 13;
 	pub const DesiredRunnersUp: u32 = 7;
@@ -347,6 +446,7 @@ impl pallet_elections_phragmen::Trait for Runtime {
 	type CurrencyToVote = CurrencyToVoteHandler;
 	type CandidacyBond = CandidacyBond;
 	type VotingBond = VotingBond;
+	type VotingBondFactor = VotingBondFactor;
 	type TermDuration = TermDuration;
 	type DesiredMembers = DesiredMembers;
 	type DesiredRunnersUp = DesiredRunnersUp;
@@ -356,11 +456,16 @@ impl pallet_elections_phragmen::Trait for Runtime {
 	type ChangeMembers = Council;
 }
 
+parameter_types! {
+	pub const TechnicalMotionDuration: BlockNumber = 5 * DAYS;
+}
+
 type TechnicalCollective = pallet_collective::Instance2;
 impl pallet_collective::Trait<TechnicalCollective> for Runtime {
 	type Origin = Origin;
 	type Proposal = Call;
 	type Event = Event;
+	type MotionDuration = TechnicalMotionDuration;
 }
 
 impl pallet_membership::Trait<pallet_membership::Instance1> for Runtime {
@@ -369,6 +474,7 @@ impl pallet_membership::Trait<pallet_membership::Instance1> for Runtime {
 	type RemoveOrigin = pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>;
 	type SwapOrigin = pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>;
 	type ResetOrigin = pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>;
+	type PrimeOrigin = pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>;
 	type MembershipInitialized = TechnicalCommittee;
 	type MembershipChanged = TechnicalCommittee;
 }
@@ -382,6 +488,12 @@ parameter_types! {
 	pub const TipFindersFee: Percent = Percent::from_percent(20);
 	pub const TipReportDepositBase: Balance = 1 * DOLLARS;
 	pub const TipReportDepositPerByte: Balance = 1 * CENTS;
+	pub const BountyDepositBase: Balance = 1 * DOLLARS;
+	pub const BountyDepositPerByte: Balance = 1 * CENTS;
+	pub const BountyDepositPayoutDelay: BlockNumber = 8 * DAYS;
+	pub const BountyUpdatePeriod: BlockNumber = 90 * DAYS;
+	pub const BountyCuratorDeposit: Permill = Permill::from_percent(50);
+	pub const BountyValueMinimum: Balance = 10 * DOLLARS;
 }
 
 impl pallet_treasury::Trait for Runtime {
@@ -399,6 +511,12 @@ impl pallet_treasury::Trait for Runtime {
 	type TipFindersFee = TipFindersFee;
 	type TipReportDepositBase = TipReportDepositBase;
 	type TipReportDepositPerByte = TipReportDepositPerByte;
+	type BountyDepositBase = BountyDepositBase;
+	type BountyDepositPerByte = BountyDepositPerByte;
+	type BountyDepositPayoutDelay = BountyDepositPayoutDelay;
+	type BountyUpdatePeriod = BountyUpdatePeriod;
+	type BountyCuratorDeposit = BountyCuratorDeposit;
+	type BountyValueMinimum = BountyValueMinimum;
 }
 
 parameter_types! {
@@ -407,6 +525,7 @@ parameter_types! {
 	pub const ContractTransactionBaseFee: Balance = 1 * CENTS;
 	pub const ContractTransactionByteFee: Balance = 10 * MILLICENTS;
 	pub const ContractFee: Balance = 1 * CENTS;
+	pub const ContractCodeDepositPerByte: Balance = 1 * MILLICENTS;
 	pub const TombstoneDeposit: Balance = 1 * DOLLARS;
 	pub const RentByteFee: Balance = 1 * DOLLARS;
 	pub const RentDepositOffset: Balance = 1000 * DOLLARS;
@@ -435,11 +554,13 @@ impl pallet_contracts::Trait for Runtime {
 	type TransactionBaseFee = ContractTransactionBaseFee;
 	type TransactionByteFee = ContractTransactionByteFee;
 	type ContractFee = ContractFee;
+	type CodeDepositPerByte = ContractCodeDepositPerByte;
 	type CallBaseFee = pallet_contracts::DefaultCallBaseFee;
 	type InstantiateBaseFee = pallet_contracts::DefaultInstantiateBaseFee;
 	type MaxDepth = pallet_contracts::DefaultMaxDepth;
 	type MaxValueSize = pallet_contracts::DefaultMaxValueSize;
 	type BlockGasLimit = pallet_contracts::DefaultBlockGasLimit;
+	type ChainExtension = ();
 }
 
 impl pallet_sudo::Trait for Runtime {
@@ -588,6 +709,54 @@ impl pallet_society::Trait for Runtime {
 	type ChallengePeriod = ChallengePeriod;
 }
 
+parameter_types! {
+	pub const AssetDeposit: Balance = 100 * DOLLARS;
+	pub const AssetMetadataDepositBase: Balance = 10 * DOLLARS;
+	pub const AssetMetadataDepositPerByte: Balance = 1 * DOLLARS;
+	pub const AssetStringLimit: u32 = 50;
+	pub const AssetApprovalDeposit: Balance = 1 * DOLLARS;
+}
+
+impl pallet_assets::Trait for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = u32;
+	type Currency = Balances;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = AssetMetadataDepositBase;
+	type MetadataDepositPerByte = AssetMetadataDepositPerByte;
+	type StringLimit = AssetStringLimit;
+	type ApprovalDeposit = AssetApprovalDeposit;
+}
+
+parameter_types! {
+	pub const MinVestedTransfer: Balance = 100 * DOLLARS;
+	pub const MaxVestingSchedules: u32 = 28;
+}
+
+impl pallet_vesting::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type BlockNumberToBalance = ConvertInto;
+	type MinVestedTransfer = MinVestedTransfer;
+	type MaxVestingSchedules = MaxVestingSchedules;
+}
+
+parameter_types! {
+	pub const EvmChainId: u64 = 42;
+}
+
+impl pallet_evm::Trait for Runtime {
+	type FeeCalculator = ();
+	type ConvertAccountId = pallet_evm::HashTruncateConvertAccountId<sp_core::Blake2Hasher>;
+	type Currency = Balances;
+	type Event = Event;
+	type Precompiles = pallet_evm::StandardPrecompiles;
+	type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Babe>;
+	type GasPayment = DealWithFees;
+	type ChainId = EvmChainId;
+}
+
 construct_runtime!(
 	pub enum Runtime where
 		Block = Block,
@@ -621,6 +790,10 @@ construct_runtime!(
 		Identity: pallet_identity::{Module, Call, Storage, Event<T>},
 		Society: pallet_society::{Module, Call, Storage, Event<T>, Config<T>},
 		Recovery: pallet_recovery::{Module, Call, Storage, Event<T>},
+		Assets: pallet_assets::{Module, Call, Storage, Event<T>},
+		Proxy: pallet_proxy::{Module, Call, Storage, Event<T>},
+		Vesting: pallet_vesting::{Module, Call, Storage, Event<T>, Config<T>},
+		Evm: pallet_evm::{Module, Call, Storage, Event, ValidateUnsigned},
 	}
 );
 
@@ -653,6 +826,30 @@ pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExt
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<Runtime, Block, frame_system::ChainContext<Runtime>, Runtime, AllModules>;
 
+/// Map the exit reason of an EVM execution into the RPC layer status.
+fn evm_exit_status(reason: pallet_evm::ExitReason) -> pallet_evm_rpc_runtime_api::ExitStatus {
+	use pallet_evm::ExitReason;
+	use pallet_evm_rpc_runtime_api::ExitStatus;
+	match reason {
+		ExitReason::Succeed(_) => ExitStatus::Succeed,
+		ExitReason::Revert(_) => ExitStatus::Revert,
+		ExitReason::Error(_) => ExitStatus::Error,
+		ExitReason::Fatal(_) => ExitStatus::Fatal,
+	}
+}
+
+/// Map the contract access error into the RPC layer error.
+fn contract_access_error(
+	err: pallet_contracts::ContractAccessError,
+) -> pallet_contracts_rpc_runtime_api::ContractAccessError {
+	use pallet_contracts::ContractAccessError;
+	use pallet_contracts_rpc_runtime_api::ContractAccessError as RpcContractAccessError;
+	match err {
+		ContractAccessError::ContractDoesntExist => RpcContractAccessError::ContractDoesntExist,
+		ContractAccessError::IsTombstone => RpcContractAccessError::IsTombstone,
+	}
+}
+
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -744,7 +941,23 @@ impl_runtime_apis! {
 		}
 	}
 
-	impl pallet_contracts_rpc_runtime_api::ContractsApi<Block, AccountId, Balance> for Runtime {
+	impl pallet_assets_rpc_runtime_api::AssetsApi<Block, AccountId, u32, Balance> for Runtime {
+		fn balance(id: u32, who: AccountId) -> Balance {
+			Assets::balance(id, who)
+		}
+
+		fn total_supply(id: u32) -> Balance {
+			Assets::total_supply(id)
+		}
+	}
+
+	impl pallet_contracts_rpc_runtime_api::ContractsApi<
+		Block,
+		AccountId,
+		Balance,
+		BlockNumber,
+		Hash,
+	> for Runtime {
 		fn call(
 			origin: AccountId,
 			dest: AccountId,
@@ -768,19 +981,160 @@ impl_runtime_apis! {
 			}
 		}
 
+		fn call_with_debug(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			input_data: Vec<u8>,
+		) -> ContractDebugExecResult<AccountId, Balance> {
+			let (exec_result, debug) = Contracts::bare_call_with_debug(
+				origin,
+				dest.into(),
+				value,
+				gas_limit,
+				input_data,
+			);
+			let result = match exec_result {
+				Ok(v) => ContractExecResult::Success {
+					status: v.status,
+					data: v.data,
+				},
+				Err(_) => ContractExecResult::Error,
+			};
+			let trace = debug.calls.into_iter().map(|call| CallTrace {
+				kind: match call.kind {
+					pallet_contracts::CallKind::Call => CallKind::Call,
+					pallet_contracts::CallKind::Instantiate => CallKind::Instantiate,
+				},
+				depth: call.depth,
+				caller: call.caller,
+				callee: call.callee,
+				value: call.value,
+				gas_used: call.gas_used,
+				status: call.status,
+				output: call.output,
+				trap_reason: call.trap_reason.map(|reason| reason.as_bytes().to_vec()),
+			}).collect();
+
+			ContractDebugExecResult {
+				result,
+				debug_message: debug.message,
+				trace,
+			}
+		}
+
+		fn instantiate(
+			origin: AccountId,
+			endowment: Balance,
+			gas_limit: u64,
+			code: Code<Hash>,
+			data: Vec<u8>,
+		) -> ContractInstantiateResult<AccountId> {
+			let code = match code {
+				Code::Upload(code) => pallet_contracts::Code::Upload(code),
+				Code::Existing(hash) => pallet_contracts::Code::Existing(hash),
+			};
+			let outcome = Contracts::bare_instantiate(origin, endowment, gas_limit, code, data);
+			let (address, result) = match outcome.result {
+				Ok((address, v)) => (Some(address), ContractExecResult::Success {
+					status: v.status,
+					data: v.data,
+				}),
+				Err(_) => (None, ContractExecResult::Error),
+			};
+
+			ContractInstantiateResult {
+				address,
+				result,
+				gas_consumed: outcome.gas_consumed,
+				events: outcome.events.iter().map(Encode::encode).collect(),
+			}
+		}
+
 		fn get_storage(
 			address: AccountId,
 			key: [u8; 32],
 		) -> pallet_contracts_rpc_runtime_api::GetStorageResult {
-			Contracts::get_storage(address, key).map_err(|rpc_err| {
-				use pallet_contracts::GetStorageError;
-				use pallet_contracts_rpc_runtime_api::{GetStorageError as RpcGetStorageError};
-				/// Map the contract error into the RPC layer error.
-				match rpc_err {
-					GetStorageError::ContractDoesntExist => RpcGetStorageError::ContractDoesntExist,
-					GetStorageError::IsTombstone => RpcGetStorageError::IsTombstone,
-				}
-			})
+			Contracts::get_storage(address, key).map_err(contract_access_error)
+		}
+
+		fn rent_projection(
+			address: AccountId,
+		) -> pallet_contracts_rpc_runtime_api::RentProjectionResult<BlockNumber> {
+			use pallet_contracts::RentProjection;
+			use pallet_contracts_rpc_runtime_api::RentProjection as RpcRentProjection;
+
+			Contracts::rent_projection(address)
+				.map(|projection| match projection {
+					RentProjection::EvictionAt(block) => RpcRentProjection::EvictionAt(block),
+					RentProjection::NoEviction => RpcRentProjection::NoEviction,
+				})
+				.map_err(contract_access_error)
+		}
+	}
+
+	impl pallet_evm_rpc_runtime_api::EvmApi<Block> for Runtime {
+		fn chain_id() -> u64 {
+			EvmChainId::get()
+		}
+
+		fn account_basic(address: H160) -> pallet_evm_rpc_runtime_api::Account {
+			let account = Evm::accounts(address);
+			pallet_evm_rpc_runtime_api::Account {
+				nonce: account.nonce,
+				balance: account.balance,
+			}
+		}
+
+		fn account_code_at(address: H160) -> Vec<u8> {
+			Evm::account_codes(address)
+		}
+
+		fn storage_at(address: H160, index: U256) -> H256 {
+			let mut key = [0u8; 32];
+			index.to_big_endian(&mut key);
+			Evm::account_storages(address, H256::from(key))
+		}
+
+		fn block_info() -> pallet_evm_rpc_runtime_api::BlockInfo {
+			pallet_evm_rpc_runtime_api::BlockInfo {
+				author: Evm::coinbase(),
+				timestamp: Timestamp::now(),
+			}
+		}
+
+		fn call(
+			from: H160,
+			to: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			gas_price: Option<U256>,
+		) -> Result<EvmExecutionInfo<Vec<u8>>, DispatchError> {
+			Evm::dry_run_call(from, to, data, value, gas_limit, gas_price)
+				.map(|(reason, value, used_gas)| EvmExecutionInfo {
+					exit_status: evm_exit_status(reason),
+					value,
+					used_gas,
+				})
+				.map_err(Into::into)
+		}
+
+		fn create(
+			from: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			gas_price: Option<U256>,
+		) -> Result<EvmExecutionInfo<H160>, DispatchError> {
+			Evm::dry_run_create(from, data, value, gas_limit, gas_price)
+				.map(|(reason, address, used_gas)| EvmExecutionInfo {
+					exit_status: evm_exit_status(reason),
+					value: address,
+					used_gas,
+				})
+				.map_err(Into::into)
 		}
 	}
 
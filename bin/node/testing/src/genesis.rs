@@ -38,7 +38,11 @@ pub fn config(support_changes_trie: bool, code: Option<&[u8]>) -> GenesisConfig
 			code: code.map(|x| x.to_vec()).unwrap_or_else(|| WASM_BINARY.to_vec()),
 		}),
 		pallet_indices: Some(IndicesConfig {
-			ids: vec![alice(), bob(), charlie(), dave(), eve(), ferdie()],
+			indices: vec![alice(), bob(), charlie(), dave(), eve(), ferdie()]
+				.into_iter()
+				.enumerate()
+				.map(|(index, account)| (index as u32, account))
+				.collect(),
 		}),
 		pallet_balances: Some(BalancesConfig {
 			balances: vec![
@@ -49,7 +53,6 @@ pub fn config(support_changes_trie: bool, code: Option<&[u8]>) -> GenesisConfig
 				(eve(), 101 * DOLLARS),
 				(ferdie(), 100 * DOLLARS),
 			],
-			vesting: vec![],
 		}),
 		pallet_session: Some(SessionConfig {
 			keys: vec![
@@ -101,5 +104,6 @@ pub fn config(support_changes_trie: bool, code: Option<&[u8]>) -> GenesisConfig
 			pot: 0,
 			max_members: 999,
 		}),
+		pallet_vesting: Some(Default::default()),
 	}
 }
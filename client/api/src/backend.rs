@@ -106,6 +106,107 @@ impl NewBlockState {
 	}
 }
 
+/// A problem found while checking the integrity of a backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityIssue<Block: BlockT> {
+	/// The canonical chain has no block with the given number.
+	MissingCanonicalBlock(NumberFor<Block>),
+	/// The header of a block is missing.
+	MissingHeader(Block::Hash),
+	/// The body of a block is missing.
+	MissingBody {
+		/// Number of the block.
+		number: NumberFor<Block>,
+		/// Hash of the block.
+		hash: Block::Hash,
+	},
+	/// The parent hash of a canonical block does not point to the previous canonical block.
+	BrokenParentLink {
+		/// Number of the block.
+		number: NumberFor<Block>,
+		/// Hash of the block.
+		hash: Block::Hash,
+		/// Parent hash stored in the header.
+		parent_hash: Block::Hash,
+		/// Hash of the previous canonical block.
+		expected: Block::Hash,
+	},
+	/// The extrinsics root of a header does not match its body.
+	ExtrinsicsRootMismatch {
+		/// Number of the block.
+		number: NumberFor<Block>,
+		/// Hash of the block.
+		hash: Block::Hash,
+	},
+	/// The state of a block that should be kept can not be fully read.
+	IncompleteState {
+		/// Number of the block.
+		number: NumberFor<Block>,
+		/// Hash of the block.
+		hash: Block::Hash,
+		/// The error returned while walking the state trie.
+		error: String,
+	},
+	/// The state database journals are invalid.
+	InvalidStateJournal(String),
+	/// The leaf set is invalid.
+	InvalidLeaves(String),
+	/// The chain metadata is invalid.
+	InvalidMeta(String),
+}
+
+impl<Block: BlockT> std::fmt::Display for IntegrityIssue<Block> {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			IntegrityIssue::MissingCanonicalBlock(number) =>
+				write!(f, "No canonical block #{}", number),
+			IntegrityIssue::MissingHeader(hash) => write!(f, "Missing header of block {}", hash),
+			IntegrityIssue::MissingBody { number, hash } =>
+				write!(f, "Missing body of block #{} ({})", number, hash),
+			IntegrityIssue::BrokenParentLink { number, hash, parent_hash, expected } => write!(
+				f,
+				"Block #{} ({}) has parent {}, expected {}",
+				number, hash, parent_hash, expected,
+			),
+			IntegrityIssue::ExtrinsicsRootMismatch { number, hash } =>
+				write!(f, "Extrinsics root of block #{} ({}) does not match its body", number, hash),
+			IntegrityIssue::IncompleteState { number, hash, error } =>
+				write!(f, "Incomplete state of block #{} ({}): {}", number, hash, error),
+			IntegrityIssue::InvalidStateJournal(e) => write!(f, "Invalid state database journal: {}", e),
+			IntegrityIssue::InvalidLeaves(e) => write!(f, "Invalid leaf set: {}", e),
+			IntegrityIssue::InvalidMeta(e) => write!(f, "Invalid chain metadata: {}", e),
+		}
+	}
+}
+
+/// Result of checking the integrity of a backend.
+#[derive(Debug, Clone)]
+pub struct IntegrityReport<Block: BlockT> {
+	/// Number of canonical blocks that were checked.
+	pub checked_blocks: u64,
+	/// Number of block states that were walked.
+	pub checked_states: u64,
+	/// Problems that were found.
+	pub issues: Vec<IntegrityIssue<Block>>,
+}
+
+impl<Block: BlockT> Default for IntegrityReport<Block> {
+	fn default() -> Self {
+		IntegrityReport {
+			checked_blocks: 0,
+			checked_states: 0,
+			issues: Vec::new(),
+		}
+	}
+}
+
+impl<Block: BlockT> IntegrityReport<Block> {
+	/// Returns `true` if no problems were found.
+	pub fn is_ok(&self) -> bool {
+		self.issues.is_empty()
+	}
+}
+
 /// Block insertion operation.
 ///
 /// Keeps hold if the inserted block state and data.
@@ -324,6 +425,20 @@ pub trait Backend<Block: BlockT>: AuxStore + Send + Sync {
 		AuxStore::get_aux(self, key)
 	}
 
+	/// Check the integrity of the stored chain.
+	///
+	/// Walks the canonical chain from block `from` up to block `to` (or the best block), checking
+	/// the headers, bodies and the state of every block that is kept, as well as any metadata
+	/// the backend maintains. Problems are collected in the returned report; an error is only
+	/// returned if the check could not be performed at all.
+	fn check_integrity(
+		&self,
+		_from: NumberFor<Block>,
+		_to: Option<NumberFor<Block>>,
+	) -> sp_blockchain::Result<IntegrityReport<Block>> {
+		Err(sp_blockchain::Error::Backend("Integrity check is not supported by this backend".into()))
+	}
+
 	/// Gain access to the import lock around this backend.
 	///
 	/// _Note_ Backend isn't expected to acquire the lock by itself ever. Rather
@@ -31,7 +31,10 @@ use sp_runtime::{
 	generic::BlockId,
 	traits,
 };
-use sp_state_machine::{ExecutionStrategy, ExecutionManager, DefaultHandler};
+use sp_state_machine::{
+	ExecutionStrategy, ExecutionManager, DefaultHandler, ExecutionDivergence, DivergenceReporter,
+	DivergenceReporterExt,
+};
 use sp_externalities::Extensions;
 use parking_lot::RwLock;
 
@@ -74,6 +77,17 @@ impl ExtensionsFactory for () {
 	}
 }
 
+/// A sink for native/wasm execution divergences.
+///
+/// Divergences are only detected when calls are executed with `ExecutionStrategy::Both`.
+pub trait DivergenceLog<Block: traits::Block>: Send + Sync {
+	/// Record a divergence that happened while executing a call on top of the block `at`.
+	fn record(&self, at: &BlockId<Block>, divergence: ExecutionDivergence);
+
+	/// The number of divergences recorded so far.
+	fn count(&self) -> u64;
+}
+
 /// A producer of execution extensions for offchain calls.
 ///
 /// This crate aggregates extensions available for the offchain calls
@@ -86,6 +100,7 @@ pub struct ExecutionExtensions<Block: traits::Block> {
 	//        remove when fixed.
 	transaction_pool: RwLock<Option<Weak<dyn sp_transaction_pool::OffchainSubmitTransaction<Block>>>>,
 	extensions_factory: RwLock<Box<dyn ExtensionsFactory>>,
+	divergence_log: RwLock<Option<Arc<dyn DivergenceLog<Block>>>>,
 }
 
 impl<Block: traits::Block> Default for ExecutionExtensions<Block> {
@@ -95,6 +110,7 @@ impl<Block: traits::Block> Default for ExecutionExtensions<Block> {
 			keystore: None,
 			transaction_pool: RwLock::new(None),
 			extensions_factory: RwLock::new(Box::new(())),
+			divergence_log: RwLock::new(None),
 		}
 	}
 }
@@ -107,7 +123,13 @@ impl<Block: traits::Block> ExecutionExtensions<Block> {
 	) -> Self {
 		let transaction_pool = RwLock::new(None);
 		let extensions_factory = Box::new(());
-		Self { strategies, keystore, extensions_factory: RwLock::new(extensions_factory), transaction_pool }
+		Self {
+			strategies,
+			keystore,
+			extensions_factory: RwLock::new(extensions_factory),
+			transaction_pool,
+			divergence_log: RwLock::new(None),
+		}
 	}
 
 	/// Get a reference to the execution strategies.
@@ -120,6 +142,19 @@ impl<Block: traits::Block> ExecutionExtensions<Block> {
 		*self.extensions_factory.write() = maker;
 	}
 
+	/// Set the log that receives native/wasm execution divergences.
+	///
+	/// When set, calls executed with `ExecutionStrategy::Both` report any discrepancy to the log
+	/// and continue with the wasm result instead of failing.
+	pub fn set_divergence_log(&self, log: Arc<dyn DivergenceLog<Block>>) {
+		*self.divergence_log.write() = Some(log);
+	}
+
+	/// Get the log that receives native/wasm execution divergences, if any.
+	pub fn divergence_log(&self) -> Option<Arc<dyn DivergenceLog<Block>>> {
+		self.divergence_log.read().clone()
+	}
+
 	/// Register transaction pool extension.
 	///
 	/// To break retain cycle between `Client` and `TransactionPool` we require this
@@ -174,6 +209,13 @@ impl<Block: traits::Block> ExecutionExtensions<Block> {
 			}
 		}
 
+		if let Some(log) = self.divergence_log.read().as_ref() {
+			extensions.register(DivergenceReporterExt::new(DivergenceLogAdapter {
+				at: *at,
+				log: log.clone(),
+			}));
+		}
+
 		if let ExecutionContext::OffchainCall(Some(ext)) = context {
 			extensions.register(
 				OffchainExt::new(offchain::LimitedExternalities::new(capabilities, ext.0))
@@ -184,6 +226,18 @@ impl<Block: traits::Block> ExecutionExtensions<Block> {
 	}
 }
 
+/// A wrapper type to pass `BlockId` to the actual divergence log.
+struct DivergenceLogAdapter<Block: traits::Block> {
+	at: BlockId<Block>,
+	log: Arc<dyn DivergenceLog<Block>>,
+}
+
+impl<Block: traits::Block> DivergenceReporter for DivergenceLogAdapter<Block> {
+	fn report(&self, divergence: ExecutionDivergence) {
+		self.log.record(&self.at, divergence)
+	}
+}
+
 /// A wrapper type to pass `BlockId` to the actual transaction pool.
 struct TransactionPoolAdapter<Block: traits::Block> {
 	at: BlockId<Block>,
@@ -40,7 +40,10 @@ pub fn build(service: &impl AbstractService) -> impl futures::Future<Output = ()
 			} else {
 				trace!(target: "usage", "Usage statistics not displayed as backend does not provide it")
 			}
-			display.display(&info, net_status);
+			let divergences = client.execution_extensions()
+				.divergence_log()
+				.map(|log| log.count());
+			display.display(&info, net_status, divergences);
 			future::ready(())
 		});
 
@@ -53,7 +53,15 @@ impl<B: BlockT> InformantDisplay<B> {
 	}
 
 	/// Displays the informant by calling `info!`.
-	pub fn display(&mut self, info: &ClientInfo<B>, net_status: NetworkStatus<B>) {
+	///
+	/// `divergences` is the number of native/wasm execution divergences recorded so far, if
+	/// divergence logging is enabled.
+	pub fn display(
+		&mut self,
+		info: &ClientInfo<B>,
+		net_status: NetworkStatus<B>,
+		divergences: Option<u64>,
+	) {
 		let best_number = info.chain.best_number;
 		let best_hash = info.chain.best_hash;
 		let speed = speed::<B>(best_number, self.last_number, self.last_update);
@@ -66,9 +74,14 @@ impl<B: BlockT> InformantDisplay<B> {
 			(SyncState::Downloading, Some(n)) => (format!("Syncing{}", speed), format!(", target=#{}", n)),
 		};
 
+		let divergences = match divergences {
+			Some(n) => format!(", {} divergences", Colour::Yellow.paint(format!("{}", n))),
+			None => String::new(),
+		};
+
 		info!(
 			target: "substrate",
-			"{}{} ({} peers), best: #{} ({}), finalized #{} ({}), ⬇ {} ⬆ {}",
+			"{}{} ({} peers), best: #{} ({}), finalized #{} ({}), ⬇ {} ⬆ {}{}",
 			Colour::White.bold().paint(&status),
 			target,
 			Colour::White.bold().paint(format!("{}", net_status.num_connected_peers)),
@@ -78,6 +91,7 @@ impl<B: BlockT> InformantDisplay<B> {
 			info.chain.finalized_hash,
 			TransferRateFormat(net_status.average_download_per_sec),
 			TransferRateFormat(net_status.average_upload_per_sec),
+			divergences,
 		);
 	}
 }
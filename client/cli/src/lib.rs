@@ -55,7 +55,7 @@ pub use structopt::clap::App;
 use params::{
 	RunCmd, PurgeChainCmd, RevertCmd, ImportBlocksCmd, ExportBlocksCmd, BuildSpecCmd,
 	NetworkConfigurationParams, MergeParameters, TransactionPoolParams,
-	NodeKeyParams, NodeKeyType, Cors, CheckBlockCmd,
+	NodeKeyParams, NodeKeyType, Cors, CheckBlockCmd, CheckDbCmd,
 };
 pub use params::{NoCustom, CoreParams, SharedParams, ImportParams, ExecutionStrategy};
 pub use traits::GetSharedParams;
@@ -65,7 +65,7 @@ use lazy_static::lazy_static;
 use futures::{Future, executor::block_on};
 use sc_telemetry::TelemetryEndpoints;
 use sp_runtime::generic::BlockId;
-use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, Zero};
 
 /// default sub directory to store network config
 const DEFAULT_NETWORK_CONFIG_PATH : &'static str = "network";
@@ -241,6 +241,9 @@ where
 		params::CoreParams::CheckBlock(params) => ParseAndPrepare::CheckBlock(
 			CheckBlock { params, version }
 		),
+		params::CoreParams::CheckDb(params) => ParseAndPrepare::CheckDb(
+			ParseAndPrepareCheckDb { params, version }
+		),
 		params::CoreParams::PurgeChain(params) => ParseAndPrepare::PurgeChain(
 			ParseAndPreparePurge { params, version }
 		),
@@ -277,6 +280,8 @@ pub enum ParseAndPrepare<'a, CC, RP> {
 	ImportBlocks(ParseAndPrepareImport<'a>),
 	/// Command to check a block.
 	CheckBlock(CheckBlock<'a>),
+	/// Command ready to check the integrity of the database.
+	CheckDb(ParseAndPrepareCheckDb<'a>),
 	/// Command ready to purge the chain.
 	PurgeChain(ParseAndPreparePurge<'a>),
 	/// Command ready to revert the chain.
@@ -294,6 +299,7 @@ impl<'a, CC, RP> ParseAndPrepare<'a, CC, RP> where CC: GetSharedParams {
 			ParseAndPrepare::ExportBlocks(c) => Some(&c.params.shared_params),
 			ParseAndPrepare::ImportBlocks(c) => Some(&c.params.shared_params),
 			ParseAndPrepare::CheckBlock(c) => Some(&c.params.shared_params),
+			ParseAndPrepare::CheckDb(c) => Some(&c.params.shared_params),
 			ParseAndPrepare::PurgeChain(c) => Some(&c.params.shared_params),
 			ParseAndPrepare::RevertChain(c) => Some(&c.params.shared_params),
 			ParseAndPrepare::CustomCommand(c) => c.shared_params(),
@@ -354,6 +360,13 @@ impl<'a, CC, RP> ParseAndPrepare<'a, CC, RP> {
 					c.version,
 					default_base_path,
 				)).transpose(),
+			ParseAndPrepare::CheckDb(c) =>
+				Some(create_config_with_db_path(
+					spec_factory,
+					&c.params.shared_params,
+					c.version,
+					default_base_path,
+				)).transpose(),
 			ParseAndPrepare::PurgeChain(c) =>
 				Some(create_config_with_db_path(
 					spec_factory,
@@ -653,6 +666,67 @@ impl<'a> CheckBlock<'a> {
 	}
 }
 
+/// Command ready to check the integrity of the database.
+pub struct ParseAndPrepareCheckDb<'a> {
+	params: CheckDbCmd,
+	version: &'a VersionInfo,
+}
+
+impl<'a> ParseAndPrepareCheckDb<'a> {
+	/// Runs the command and checks the database.
+	///
+	/// Every problem found is logged and an error is returned if there was any, so that the
+	/// process exits with a non-zero code.
+	pub fn run_with_builder<C, G, E, F, B, S>(
+		self,
+		builder: F,
+		spec_factory: S,
+	) -> error::Result<()> where
+		S: FnOnce(&str) -> Result<Option<ChainSpec<G, E>>, String>,
+		F: FnOnce(Configuration<C, G, E>) -> Result<B, error::Error>,
+		B: ServiceBuilderCommand,
+		<<<<B as ServiceBuilderCommand>::Block as BlockT>::Header as HeaderT>
+			::Number as FromStr>::Err: Debug,
+		C: Default,
+		G: RuntimeGenesis,
+		E: ChainSpecExtension,
+	{
+		let mut config = create_config_with_db_path(
+			spec_factory,
+			&self.params.shared_params,
+			self.version,
+			None,
+		)?;
+		fill_import_params(
+			&mut config,
+			&self.params.import_params,
+			sc_service::Roles::FULL,
+			self.params.shared_params.dev,
+		)?;
+		fill_config_keystore_in_memory(&mut config)?;
+
+		if let DatabaseConfig::Path { ref path, .. } = &config.database {
+			info!("DB path: {}", path.display());
+		}
+		let from = match self.params.from {
+			Some(from) => from.parse()?,
+			None => Zero::zero(),
+		};
+		let to = self.params.to.map(|to| to.parse()).transpose()?;
+
+		let report = builder(config)?.check_db(from, to)?;
+		for issue in &report.issues {
+			log::warn!("{}", issue);
+		}
+
+		if report.is_ok() {
+			Ok(())
+		} else {
+			Err(error::Error::Other(format!("Found {} database integrity issues", report.issues.len())))
+		}
+	}
+}
+
 /// Command ready to purge the chain.
 pub struct ParseAndPreparePurge<'a> {
 	params: PurgeChainCmd,
@@ -953,6 +1027,8 @@ pub fn fill_import_params<C, G, E>(
 	};
 
 	config.wasm_method = cli.wasm_method.into();
+	config.max_stack_height = cli.max_stack_height;
+	config.sandbox_backend = cli.sandbox_backend.into();
 
 	let exec = &cli.execution_strategies;
 	let exec_all_or = |strat: ExecutionStrategy, default: ExecutionStrategy| {
@@ -972,6 +1048,7 @@ pub fn fill_import_params<C, G, E>(
 			exec_all_or(exec.execution_offchain_worker, DEFAULT_EXECUTION_OFFCHAIN_WORKER),
 		other: exec_all_or(exec.execution_other, DEFAULT_EXECUTION_OTHER),
 	};
+	config.execution_divergence_log = exec.execution_divergence_log.clone();
 	Ok(())
 }
 
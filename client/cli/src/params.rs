@@ -70,6 +70,43 @@ impl Into<sc_service::config::WasmExecutionMethod> for WasmExecutionMethod {
 	}
 }
 
+arg_enum! {
+	/// How to execute the modules instantiated by the Wasm runtime through the sandbox
+	#[allow(missing_docs)]
+	#[derive(Debug, Clone, Copy)]
+	pub enum SandboxBackend {
+		// Uses an interpreter.
+		Wasmi,
+		// Compiles the modules with wasmtime.
+		Wasmtime,
+	}
+}
+
+impl SandboxBackend {
+	/// Returns list of variants that are not disabled by feature flags.
+	fn enabled_variants() -> Vec<&'static str> {
+		Self::variants()
+			.iter()
+			.cloned()
+			.filter(|&name| cfg!(feature = "wasmtime") || name != "Wasmtime")
+			.collect()
+	}
+}
+
+impl Into<sc_service::config::SandboxBackend> for SandboxBackend {
+	fn into(self) -> sc_service::config::SandboxBackend {
+		match self {
+			SandboxBackend::Wasmi => sc_service::config::SandboxBackend::Wasmi,
+			#[cfg(feature = "wasmtime")]
+			SandboxBackend::Wasmtime => sc_service::config::SandboxBackend::Wasmtime,
+			#[cfg(not(feature = "wasmtime"))]
+			SandboxBackend::Wasmtime => panic!(
+				"Substrate must be compiled with \"wasmtime\" feature for the wasmtime sandbox"
+			),
+		}
+	}
+}
+
 arg_enum! {
 	/// Whether off-chain workers are enabled.
 	#[allow(missing_docs)]
@@ -130,6 +167,23 @@ pub struct ImportParams {
 	)]
 	pub wasm_method: WasmExecutionMethod,
 
+	/// Limit the stack height of the Wasm runtime.
+	///
+	/// The runtime code is instrumented so that exceeding the limit traps deterministically,
+	/// whatever the execution method. Disabled if unspecified.
+	#[structopt(long = "wasm-max-stack-height", value_name = "HEIGHT")]
+	pub max_stack_height: Option<u32>,
+
+	/// Backend for executing the Wasm modules instantiated by the runtime through the sandbox.
+	#[structopt(
+		long = "wasm-sandbox-backend",
+		value_name = "BACKEND",
+		possible_values = &SandboxBackend::enabled_variants(),
+		case_insensitive = true,
+		default_value = "Wasmi"
+	)]
+	pub sandbox_backend: SandboxBackend,
+
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub execution_strategies: ExecutionStrategies,
@@ -381,6 +435,13 @@ pub struct ExecutionStrategies {
 		]
 	)]
 	pub execution: Option<ExecutionStrategy>,
+
+	/// Log native/wasm execution divergences to the given file instead of failing.
+	///
+	/// Every call executed with the `Both` strategy whose native and wasm results or storage
+	/// changes differ is appended as a JSON line, and execution continues with the wasm result.
+	#[structopt(long = "execution-divergence-log", value_name = "PATH", parse(from_os_str))]
+	pub execution_divergence_log: Option<PathBuf>,
 }
 
 /// The `run` command used to run a node.
@@ -830,6 +891,30 @@ pub struct CheckBlockCmd {
 	pub import_params: ImportParams,
 }
 
+/// The `check-db` command used to verify the integrity of the database.
+#[derive(Debug, StructOpt, Clone)]
+pub struct CheckDbCmd {
+	/// Specify starting block number.
+	///
+	/// Default is 0.
+	#[structopt(long = "from", value_name = "BLOCK")]
+	pub from: Option<BlockNumber>,
+
+	/// Specify last block number.
+	///
+	/// Default is best block.
+	#[structopt(long = "to", value_name = "BLOCK")]
+	pub to: Option<BlockNumber>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
 /// The `revert` command used revert the chain to a previous state.
 #[derive(Debug, StructOpt, Clone)]
 pub struct RevertCmd {
@@ -876,6 +961,9 @@ pub enum CoreParams<CC, RP> {
 	/// Validte a single block.
 	CheckBlock(CheckBlockCmd),
 
+	/// Check the integrity of the database.
+	CheckDb(CheckDbCmd),
+
 	/// Revert chain to the previous state.
 	Revert(RevertCmd),
 
@@ -913,6 +1001,10 @@ impl<CC, RP> StructOpt for CoreParams<CC, RP> where
 			CheckBlockCmd::augment_clap(SubCommand::with_name("check-block"))
 				.about("Re-validate a known block.")
 		)
+		.subcommand(
+			CheckDbCmd::augment_clap(SubCommand::with_name("check-db"))
+				.about("Check the integrity of the database.")
+		)
 		.subcommand(
 			RevertCmd::augment_clap(SubCommand::with_name("revert"))
 				.about("Revert chain to the previous state.")
@@ -933,6 +1025,8 @@ impl<CC, RP> StructOpt for CoreParams<CC, RP> where
 				CoreParams::ImportBlocks(ImportBlocksCmd::from_clap(matches)),
 			("check-block", Some(matches)) =>
 				CoreParams::CheckBlock(CheckBlockCmd::from_clap(matches)),
+			("check-db", Some(matches)) =>
+				CoreParams::CheckDb(CheckDbCmd::from_clap(matches)),
 			("revert", Some(matches)) => CoreParams::Revert(RevertCmd::from_clap(matches)),
 			("purge-chain", Some(matches)) =>
 				CoreParams::PurgeChain(PurgeChainCmd::from_clap(matches)),
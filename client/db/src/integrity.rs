@@ -0,0 +1,486 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Integrity check of the full database.
+//!
+//! Walks the canonical chain and verifies the header links, the extrinsics root of every body
+//! and that the state trie of every block that is kept, child tries included, can be read
+//! completely. The state database journals, the leaf set and the chain metadata are re-read
+//! from disk and validated against the chain.
+
+use codec::Encode;
+use kvdb::KeyValueDB;
+use sc_client::blockchain::Backend as _;
+use sc_client::leaves::LeafSet;
+use sc_client_api::backend::{IntegrityIssue, IntegrityReport};
+use sc_state_db::StateDb;
+use sp_blockchain::Result as ClientResult;
+use sp_core::storage::{ChildInfo, well_known_keys};
+use sp_runtime::generic::BlockId;
+use sp_runtime::traits::{
+	Block as BlockT, Header as HeaderT, Hash as HashT, NumberFor, One, SaturatedConversion,
+};
+use crate::{Backend, DbState, StateMetaDb, children, columns};
+use crate::utils::{self, meta_keys, read_genesis_hash};
+
+/// Prefix of the storage keys of the child tries using the default child info.
+const DEFAULT_CHILD_STORAGE_KEY_PREFIX: &[u8] = b":child_storage:default:";
+
+/// Check the integrity of the database behind `backend`, see
+/// [`check_integrity`](sc_client_api::backend::Backend::check_integrity).
+pub(crate) fn check_integrity<Block: BlockT>(
+	backend: &Backend<Block>,
+	from: NumberFor<Block>,
+	to: Option<NumberFor<Block>>,
+) -> ClientResult<IntegrityReport<Block>> {
+	let db = &*backend.storage.db;
+	let mut report = IntegrityReport::default();
+
+	check_meta(db, &mut report)?;
+	check_leaves(db, &mut report)?;
+	check_state_journals(backend, &mut report);
+
+	let to = to.unwrap_or_else(|| backend.blockchain.meta.read().best_number);
+	let mut parent_hash = None;
+	let mut number = from;
+	while number <= to {
+		match check_block(backend, number, parent_hash, &mut report)? {
+			Some(hash) => parent_hash = Some(hash),
+			None => {
+				report.issues.push(IntegrityIssue::MissingCanonicalBlock(number));
+				parent_hash = None;
+			},
+		}
+		report.checked_blocks += 1;
+		number = match number.checked_add(&One::one()) {
+			Some(next) => next,
+			None => break,
+		};
+	}
+
+	Ok(report)
+}
+
+/// Check the canonical block `number`, returning its hash if it exists.
+fn check_block<Block: BlockT>(
+	backend: &Backend<Block>,
+	number: NumberFor<Block>,
+	parent_hash: Option<Block::Hash>,
+	report: &mut IntegrityReport<Block>,
+) -> ClientResult<Option<Block::Hash>> {
+	let header = match utils::read_header::<Block>(
+		&*backend.storage.db,
+		columns::KEY_LOOKUP,
+		columns::HEADER,
+		BlockId::Number(number),
+	)? {
+		Some(header) => header,
+		None => return Ok(None),
+	};
+	let hash = header.hash();
+
+	if let Some(expected) = parent_hash {
+		if *header.parent_hash() != expected {
+			report.issues.push(IntegrityIssue::BrokenParentLink {
+				number,
+				hash,
+				parent_hash: *header.parent_hash(),
+				expected,
+			});
+		}
+	}
+
+	match backend.blockchain.body(BlockId::Hash(hash))? {
+		Some(body) => {
+			let extrinsics_root = <<Block::Header as HeaderT>::Hashing as HashT>::ordered_trie_root(
+				body.iter().map(Encode::encode).collect(),
+			);
+			if extrinsics_root != *header.extrinsics_root() {
+				report.issues.push(IntegrityIssue::ExtrinsicsRootMismatch { number, hash });
+			}
+		},
+		None => report.issues.push(IntegrityIssue::MissingBody { number, hash }),
+	}
+
+	if !backend.storage.state_db.is_pruned(&hash, number.saturated_into::<u64>()) {
+		if let Err(error) = walk_state(backend, *header.state_root()) {
+			report.issues.push(IntegrityIssue::IncompleteState { number, hash, error });
+		}
+		report.checked_states += 1;
+	}
+
+	Ok(Some(hash))
+}
+
+/// Read every key and value of the state trie with the given `root` and of its child tries.
+///
+/// The child info of a child trie is not stored in the state. Child tries are assumed to use the
+/// default child info with the storage key stripped of `:child_storage:default:` as unique id,
+/// like the ones created by the runtime modules.
+fn walk_state<Block: BlockT>(backend: &Backend<Block>, root: Block::Hash) -> Result<(), String> {
+	let state = DbState::<Block>::new(backend.storage.clone(), root);
+	let mut key = Vec::new();
+	while let Some(next) = state.essence().next_storage_key(&key)? {
+		state.essence().storage(&next)?;
+		if well_known_keys::is_child_trie_key_valid(&next) {
+			walk_child_state::<Block>(&state, &next)?;
+		}
+		key = next;
+	}
+	Ok(())
+}
+
+/// Read every key and value of the child trie stored under `storage_key`.
+fn walk_child_state<Block: BlockT>(
+	state: &DbState<Block>,
+	storage_key: &[u8],
+) -> Result<(), String> {
+	let child_info = ChildInfo::new_default(&storage_key[DEFAULT_CHILD_STORAGE_KEY_PREFIX.len()..]);
+	let mut key = Vec::new();
+	while let Some(next) = state.essence().next_child_storage_key(storage_key, child_info, &key)? {
+		state.essence().child_storage(storage_key, child_info, &next)?;
+		key = next;
+	}
+	Ok(())
+}
+
+/// Check that the genesis, best and finalized block entries point to known canonical blocks.
+fn check_meta<Block: BlockT>(
+	db: &dyn KeyValueDB,
+	report: &mut IntegrityReport<Block>,
+) -> ClientResult<()> {
+	let genesis_hash = match read_genesis_hash::<Block::Hash>(db) {
+		Ok(Some(genesis_hash)) => genesis_hash,
+		Ok(None) => {
+			report.issues.push(IntegrityIssue::InvalidMeta("Genesis hash is missing".into()));
+			return Ok(());
+		},
+		Err(e) => {
+			report.issues.push(IntegrityIssue::InvalidMeta(e.to_string()));
+			return Ok(());
+		},
+	};
+
+	match canonical_hash::<Block>(db, Default::default())? {
+		Some(hash) if hash == genesis_hash => {},
+		_ => report.issues.push(IntegrityIssue::InvalidMeta(
+			format!("Genesis hash {} is not the canonical block #0", genesis_hash),
+		)),
+	}
+
+	let mut best_number = None;
+	let meta_blocks = [("Best", &meta_keys::BEST_BLOCK[..]), ("Finalized", &meta_keys::FINALIZED_BLOCK[..])];
+	for (desc, key) in &meta_blocks {
+		let lookup_key = match db.get(columns::META, key).map_err(utils::db_err)? {
+			Some(lookup_key) => lookup_key,
+			None => {
+				report.issues.push(IntegrityIssue::InvalidMeta(format!("{} block is missing", desc)));
+				continue;
+			},
+		};
+		let header = match db.get(columns::HEADER, &lookup_key).map_err(utils::db_err)? {
+			Some(header) => header,
+			None => {
+				report.issues.push(
+					IntegrityIssue::InvalidMeta(format!("{} block header is missing", desc)),
+				);
+				continue;
+			},
+		};
+		let header = match <Block::Header as codec::Decode>::decode(&mut &header[..]) {
+			Ok(header) => header,
+			Err(e) => {
+				report.issues.push(IntegrityIssue::InvalidMeta(
+					format!("{} block header can not be decoded: {}", desc, e.what()),
+				));
+				continue;
+			},
+		};
+
+		let hash = header.hash();
+		if canonical_hash::<Block>(db, *header.number())? != Some(hash) {
+			report.issues.push(IntegrityIssue::InvalidMeta(
+				format!("{} block #{} ({}) is not canonical", desc, header.number(), hash),
+			));
+		}
+
+		if key == &&meta_keys::BEST_BLOCK[..] {
+			best_number = Some(*header.number());
+		} else if best_number.map_or(false, |best_number| *header.number() > best_number) {
+			report.issues.push(IntegrityIssue::InvalidMeta(format!(
+				"Finalized block #{} is above the best block #{:?}",
+				header.number(),
+				best_number,
+			)));
+		}
+	}
+
+	Ok(())
+}
+
+/// Check that every leaf is a known block without children and that the best block is a leaf.
+fn check_leaves<Block: BlockT>(
+	db: &dyn KeyValueDB,
+	report: &mut IntegrityReport<Block>,
+) -> ClientResult<()> {
+	let leaves = match LeafSet::<Block::Hash, NumberFor<Block>>::read_from_db(
+		db,
+		columns::META,
+		meta_keys::LEAF_PREFIX,
+	) {
+		Ok(leaves) => leaves.hashes(),
+		Err(e) => {
+			report.issues.push(IntegrityIssue::InvalidLeaves(e.to_string()));
+			return Ok(());
+		},
+	};
+
+	for leaf in &leaves {
+		if utils::read_header::<Block>(db, columns::KEY_LOOKUP, columns::HEADER, BlockId::Hash(*leaf))?
+			.is_none()
+		{
+			report.issues.push(IntegrityIssue::MissingHeader(*leaf));
+			continue;
+		}
+
+		match children::read_children::<_, Block::Hash>(db, columns::META, meta_keys::CHILDREN_PREFIX, *leaf) {
+			Ok(ref children) if children.is_empty() => {},
+			Ok(_) => report.issues.push(IntegrityIssue::InvalidLeaves(
+				format!("Leaf {} has children", leaf),
+			)),
+			Err(e) => report.issues.push(IntegrityIssue::InvalidLeaves(e.to_string())),
+		}
+	}
+
+	let best_hash = utils::read_meta::<Block>(db, columns::HEADER)?.best_hash;
+	if !leaves.is_empty() && !leaves.contains(&best_hash) {
+		report.issues.push(IntegrityIssue::InvalidLeaves(
+			format!("Best block {} is not a leaf", best_hash),
+		));
+	}
+
+	Ok(())
+}
+
+/// Re-read the state database journals from disk and compare them to the open state database
+/// and the chain.
+fn check_state_journals<Block: BlockT>(backend: &Backend<Block>, report: &mut IntegrityReport<Block>) {
+	let state_db = &backend.storage.state_db;
+	match StateDb::<Block::Hash, Vec<u8>>::new(state_db.pruning_mode(), &StateMetaDb(&*backend.storage.db)) {
+		Ok(journals) => {
+			if journals.best_canonical() != state_db.best_canonical() {
+				report.issues.push(IntegrityIssue::InvalidStateJournal(format!(
+					"Last canonicalized block on disk is {:?}, expected {:?}",
+					journals.best_canonical(),
+					state_db.best_canonical(),
+				)));
+			}
+
+			let best_number = backend.blockchain.meta.read().best_number.saturated_into::<u64>();
+			if journals.best_canonical().map_or(false, |canonical| canonical > best_number) {
+				report.issues.push(IntegrityIssue::InvalidStateJournal(format!(
+					"Block #{:?} is canonicalized but the best block is #{}",
+					journals.best_canonical(),
+					best_number,
+				)));
+			}
+
+			let journaled_blocks = journals.journaled_blocks();
+			let expected_blocks = state_db.journaled_blocks();
+			if journaled_blocks != expected_blocks {
+				report.issues.push(IntegrityIssue::InvalidStateJournal(format!(
+					"Journaled blocks on disk are {:?}, expected {:?}",
+					journaled_blocks,
+					expected_blocks,
+				)));
+			}
+
+			let best_canonical = journals.best_canonical();
+			for (hash, number) in journaled_blocks {
+				if let Err(e) = check_journaled_block(backend, hash, number, best_canonical) {
+					report.issues.push(IntegrityIssue::InvalidStateJournal(e));
+				}
+			}
+		},
+		Err(e) => report.issues.push(IntegrityIssue::InvalidStateJournal(format!("{:?}", e))),
+	}
+}
+
+/// Check that the journaled block `hash` is a known block with the journaled `number` and that it
+/// is canonical if it is at or below the last canonicalized block.
+fn check_journaled_block<Block: BlockT>(
+	backend: &Backend<Block>,
+	hash: Block::Hash,
+	number: u64,
+	best_canonical: Option<u64>,
+) -> Result<(), String> {
+	let db = &*backend.storage.db;
+	let header = utils::read_header::<Block>(
+		db,
+		columns::KEY_LOOKUP,
+		columns::HEADER,
+		BlockId::Hash(hash),
+	).map_err(|e| e.to_string())?.ok_or_else(|| format!("Journaled block {} is unknown", hash))?;
+
+	if header.number().saturated_into::<u64>() != number {
+		return Err(format!(
+			"Block {} is journaled as #{} but its header is #{}",
+			hash,
+			number,
+			header.number(),
+		));
+	}
+
+	if best_canonical.map_or(false, |canonical| number <= canonical)
+		&& canonical_hash::<Block>(db, *header.number()).map_err(|e| e.to_string())? != Some(hash)
+	{
+		return Err(format!("Block #{} ({}) is journaled as canonical but is not", number, hash));
+	}
+
+	Ok(())
+}
+
+fn canonical_hash<Block: BlockT>(
+	db: &dyn KeyValueDB,
+	number: NumberFor<Block>,
+) -> ClientResult<Option<Block::Hash>> {
+	Ok(utils::read_header::<Block>(db, columns::KEY_LOOKUP, columns::HEADER, BlockId::Number(number))?
+		.map(|header| header.hash()))
+}
+
+#[cfg(test)]
+mod tests {
+	use sc_client_api::backend::{
+		Backend as _, BlockImportOperation as _, IntegrityIssue, NewBlockState,
+	};
+	use sp_core::H256;
+	use sp_core::storage::{OwnedChildInfo, Storage, StorageChild};
+	use sp_runtime::generic::BlockId;
+	use sp_runtime::testing::Header;
+	use sp_runtime::traits::{BlakeTwo256, Hash as _, Header as _};
+	use crate::columns;
+	use crate::tests::{Block, insert_header};
+
+	fn empty_extrinsics_root() -> H256 {
+		BlakeTwo256::ordered_trie_root(Vec::new())
+	}
+
+	/// Insert a genesis block whose state has a child trie under `:child_storage:default:child`
+	/// with the given `unique_id`.
+	fn insert_genesis_with_child_trie(backend: &crate::Backend<Block>, unique_id: &[u8]) -> H256 {
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, BlockId::Hash(Default::default())).unwrap();
+		let state_root = op.reset_storage(Storage {
+			top: vec![(vec![1, 2, 3], vec![4, 5, 6])].into_iter().collect(),
+			children: vec![(
+				b":child_storage:default:child".to_vec(),
+				StorageChild {
+					data: vec![(vec![7, 8, 9], vec![10, 11, 12])].into_iter().collect(),
+					child_info: OwnedChildInfo::new_default(unique_id.to_vec()),
+				},
+			)].into_iter().collect(),
+		}).unwrap();
+		let header = Header {
+			number: 0,
+			parent_hash: Default::default(),
+			state_root,
+			digest: Default::default(),
+			extrinsics_root: empty_extrinsics_root(),
+		};
+		let hash = header.hash();
+		op.set_block_data(header, Some(Vec::new()), None, NewBlockState::Best).unwrap();
+		backend.commit_operation(op).unwrap();
+		hash
+	}
+
+	#[test]
+	fn healthy_database_has_no_issues() {
+		let backend = crate::Backend::<Block>::new_test(16, 0);
+		let mut parent = Default::default();
+		for number in 0..4 {
+			parent = insert_header(&backend, number, parent, None, empty_extrinsics_root());
+		}
+
+		let report = backend.check_integrity(0, None).unwrap();
+		assert!(report.is_ok(), "{:?}", report.issues);
+		assert_eq!(report.checked_blocks, 4);
+	}
+
+	#[test]
+	fn missing_body_is_reported() {
+		let backend = crate::Backend::<Block>::new_test(16, 0);
+		let mut hashes = Vec::new();
+		let mut parent = Default::default();
+		for number in 0..3 {
+			parent = insert_header(&backend, number, parent, None, empty_extrinsics_root());
+			hashes.push(parent);
+		}
+
+		let lookup_key = crate::utils::number_and_hash_to_lookup_key(1, hashes[1]).unwrap();
+		let mut tx = kvdb::DBTransaction::new();
+		tx.delete(columns::BODY, &lookup_key);
+		backend.storage.db.write(tx).unwrap();
+
+		let report = backend.check_integrity(0, None).unwrap();
+		assert_eq!(report.issues, vec![IntegrityIssue::MissingBody { number: 1, hash: hashes[1] }]);
+	}
+
+	#[test]
+	fn journaled_block_without_header_is_reported() {
+		let backend = crate::Backend::<Block>::new_test(16, 0);
+		let mut hashes = Vec::new();
+		let mut parent = Default::default();
+		for number in 0..3 {
+			parent = insert_header(&backend, number, parent, None, empty_extrinsics_root());
+			hashes.push(parent);
+		}
+
+		let lookup_key = crate::utils::number_and_hash_to_lookup_key(1, hashes[1]).unwrap();
+		let mut tx = kvdb::DBTransaction::new();
+		tx.delete(columns::HEADER, &lookup_key);
+		backend.storage.db.write(tx).unwrap();
+
+		let report = backend.check_integrity(0, None).unwrap();
+		assert!(report.issues.contains(&IntegrityIssue::MissingCanonicalBlock(1)));
+		assert!(report.issues.contains(&IntegrityIssue::InvalidStateJournal(
+			format!("Journaled block {} is unknown", hashes[1]),
+		)));
+	}
+
+	#[test]
+	fn child_tries_are_walked() {
+		let backend = crate::Backend::<Block>::new_test(16, 0);
+		insert_genesis_with_child_trie(&backend, b"child");
+
+		let report = backend.check_integrity(0, None).unwrap();
+		assert!(report.is_ok(), "{:?}", report.issues);
+		assert_eq!(report.checked_states, 1);
+	}
+
+	#[test]
+	fn unreadable_child_trie_is_reported() {
+		// The child trie nodes are stored under a keyspace that doesn't match its storage key.
+		let backend = crate::Backend::<Block>::new_test(16, 0);
+		let hash = insert_genesis_with_child_trie(&backend, b"elsewhere");
+
+		let report = backend.check_integrity(0, None).unwrap();
+		match &report.issues[..] {
+			[IntegrityIssue::IncompleteState { number: 0, hash: issue_hash, .. }] =>
+				assert_eq!(*issue_hash, hash),
+			issues => panic!("Unexpected issues: {:?}", issues),
+		}
+	}
+}
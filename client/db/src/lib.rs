@@ -30,6 +30,7 @@ pub mod light;
 pub mod offchain;
 
 mod children;
+mod integrity;
 mod cache;
 mod changes_tries_storage;
 mod storage_cache;
@@ -44,7 +45,7 @@ use std::io;
 use std::collections::HashMap;
 
 use sc_client_api::{execution_extensions::ExecutionExtensions, ForkBlocks, UsageInfo, MemoryInfo, BadBlocks, IoInfo};
-use sc_client_api::backend::NewBlockState;
+use sc_client_api::backend::{NewBlockState, IntegrityReport};
 use sc_client_api::backend::PrunableStateChangesTrieStorage;
 use sp_blockchain::{
 	Result as ClientResult, Error as ClientError,
@@ -1628,6 +1629,14 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		Ok(())
 	}
 
+	fn check_integrity(
+		&self,
+		from: NumberFor<Block>,
+		to: Option<NumberFor<Block>>,
+	) -> ClientResult<IntegrityReport<Block>> {
+		integrity::check_integrity(self, from, to)
+	}
+
 	fn get_import_lock(&self) -> &RwLock<()> {
 		&self.import_lock
 	}
@@ -22,3 +22,5 @@ pub mod sandbox;
 pub mod allocator;
 pub mod error;
 pub mod wasm_runtime;
+#[cfg(feature = "wasmtime")]
+pub mod trampoline;
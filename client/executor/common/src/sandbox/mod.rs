@@ -16,8 +16,12 @@
 
 //! This module implements sandboxing support in the runtime.
 //!
-//! Sandboxing is baked by wasmi at the moment. In future, however, we would like to add/switch to
-//! a compiled execution engine.
+//! Sandboxed guest modules are either interpreted by wasmi or, with the `wasmtime` feature,
+//! compiled to native code by wasmtime. The backend is chosen per [`Store`], see
+//! [`SandboxBackend`].
+
+#[cfg(feature = "wasmtime")]
+mod wasmtime_backend;
 
 use crate::error::{Result, Error};
 use std::{collections::HashMap, rc::Rc};
@@ -25,10 +29,56 @@ use codec::{Decode, Encode};
 use sp_core::sandbox as sandbox_primitives;
 use wasmi::{
 	Externals, ImportResolver, MemoryInstance, MemoryRef, Module, ModuleInstance,
-	ModuleRef, RuntimeArgs, RuntimeValue, Trap, TrapKind, memory_units::Pages,
+	ModuleRef, RuntimeArgs, RuntimeValue, Trap, memory_units::Pages,
 };
 use sp_wasm_interface::{Pointer, WordSize};
 
+/// The engine used to execute sandboxed guest modules.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SandboxBackend {
+	/// Interpret guest modules with wasmi.
+	Wasmi,
+	/// Compile guest modules to native code with wasmtime.
+	#[cfg(feature = "wasmtime")]
+	Wasmtime,
+}
+
+impl Default for SandboxBackend {
+	fn default() -> Self {
+		SandboxBackend::Wasmi
+	}
+}
+
+/// A linear memory that can be imported by sandboxed guest modules.
+#[derive(Clone)]
+pub enum Memory {
+	/// Memory created for the wasmi backend.
+	Wasmi(MemoryRef),
+	/// Memory created for the wasmtime backend.
+	#[cfg(feature = "wasmtime")]
+	Wasmtime(wasmtime_backend::Memory),
+}
+
+impl Memory {
+	/// Provides direct access to the underlying memory buffer.
+	pub fn with_direct_access<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
+		match self {
+			Memory::Wasmi(memory) => memory.with_direct_access(f),
+			#[cfg(feature = "wasmtime")]
+			Memory::Wasmtime(memory) => memory.with_direct_access(f),
+		}
+	}
+
+	/// Provides direct mutable access to the underlying memory buffer.
+	pub fn with_direct_access_mut<R, F: FnOnce(&mut [u8]) -> R>(&self, f: F) -> R {
+		match self {
+			Memory::Wasmi(memory) => memory.with_direct_access_mut(f),
+			#[cfg(feature = "wasmtime")]
+			Memory::Wasmtime(memory) => memory.with_direct_access_mut(f),
+		}
+	}
+}
+
 /// Index of a function inside the supervisor.
 ///
 /// This is a typically an index in the default table of the supervisor, however
@@ -71,7 +121,7 @@ impl GuestToSupervisorFunctionMapping {
 
 struct Imports {
 	func_map: HashMap<(Vec<u8>, Vec<u8>), GuestFuncIndex>,
-	memories_map: HashMap<(Vec<u8>, Vec<u8>), MemoryRef>,
+	memories_map: HashMap<(Vec<u8>, Vec<u8>), Memory>,
 }
 
 impl ImportResolver for Imports {
@@ -104,16 +154,13 @@ impl ImportResolver for Imports {
 			module_name.as_bytes().to_vec(),
 			field_name.as_bytes().to_vec(),
 		);
-		let mem = self.memories_map
-			.get(&key)
-			.ok_or_else(|| {
-				wasmi::Error::Instantiation(format!(
-					"Export {}:{} not found",
-					module_name, field_name
-				))
-			})?
-			.clone();
-		Ok(mem)
+		match self.memories_map.get(&key) {
+			Some(Memory::Wasmi(mem)) => Ok(mem.clone()),
+			_ => Err(wasmi::Error::Instantiation(format!(
+				"Export {}:{} not found",
+				module_name, field_name
+			))),
+		}
 	}
 
 	fn resolve_global(
@@ -215,54 +262,73 @@ pub trait SandboxCapabilities {
 /// [`Externals`]: ../wasmi/trait.Externals.html
 pub struct GuestExternals<'a, FE: SandboxCapabilities + 'a> {
 	supervisor_externals: &'a mut FE,
-	sandbox_instance: &'a SandboxInstance<FE::SupervisorFuncRef>,
+	dispatch_thunk: &'a FE::SupervisorFuncRef,
+	guest_to_supervisor_mapping: &'a GuestToSupervisorFunctionMapping,
 	state: u32,
 }
 
-fn trap(msg: &'static str) -> Trap {
-	TrapKind::Host(Box::new(Error::Other(msg.into()))).into()
-}
-
-fn deserialize_result(serialized_result: &[u8]) -> std::result::Result<Option<RuntimeValue>, Trap> {
+fn deserialize_result(
+	serialized_result: &[u8],
+) -> Result<Option<sandbox_primitives::TypedValue>> {
 	use self::sandbox_primitives::{HostError, ReturnValue};
 	let result_val = std::result::Result::<ReturnValue, HostError>::decode(&mut &serialized_result[..])
-		.map_err(|_| trap("Decoding Result<ReturnValue, HostError> failed!"))?;
+		.map_err(|_| Error::Other("Decoding Result<ReturnValue, HostError> failed!".into()))?;
 
 	match result_val {
 		Ok(return_value) => Ok(match return_value {
 			ReturnValue::Unit => None,
-			ReturnValue::Value(typed_value) => Some(RuntimeValue::from(typed_value)),
+			ReturnValue::Value(typed_value) => Some(typed_value),
 		}),
-		Err(HostError) => Err(trap("Supervisor function returned sandbox::HostError")),
+		Err(HostError) => Err(Error::Other("Supervisor function returned sandbox::HostError".into())),
 	}
 }
 
+/// Dispatches calls of a guest module to the functions imported from the supervisor.
+trait SupervisorDispatch {
+	/// Call the supervisor function registered under the given guest `index`.
+	fn dispatch(
+		&mut self,
+		index: GuestFuncIndex,
+		args: Vec<sandbox_primitives::TypedValue>,
+	) -> Result<Option<sandbox_primitives::TypedValue>>;
+}
+
 impl<'a, FE: SandboxCapabilities + 'a> Externals for GuestExternals<'a, FE> {
 	fn invoke_index(
 		&mut self,
 		index: usize,
 		args: RuntimeArgs,
 	) -> std::result::Result<Option<RuntimeValue>, Trap> {
+		let args = args.as_ref()
+			.iter()
+			.cloned()
+			.map(sandbox_primitives::TypedValue::from)
+			.collect();
+
 		// Make `index` typesafe again.
-		let index = GuestFuncIndex(index);
+		self.dispatch(GuestFuncIndex(index), args)
+			.map(|result| result.map(RuntimeValue::from))
+			.map_err(Into::into)
+	}
+}
 
-		let func_idx = self.sandbox_instance
-			.guest_to_supervisor_mapping
+impl<'a, FE: SandboxCapabilities + 'a> SupervisorDispatch for GuestExternals<'a, FE> {
+	fn dispatch(
+		&mut self,
+		index: GuestFuncIndex,
+		args: Vec<sandbox_primitives::TypedValue>,
+	) -> Result<Option<sandbox_primitives::TypedValue>> {
+		let func_idx = self.guest_to_supervisor_mapping
 			.func_by_guest_index(index)
 			.expect(
-				"`invoke_index` is called with indexes registered via `FuncInstance::alloc_host`;
-					`FuncInstance::alloc_host` is called with indexes that was obtained from `guest_to_supervisor_mapping`;
+				"`dispatch` is called with indexes registered for the imports of the guest module;
+					these indexes were obtained from `guest_to_supervisor_mapping`;
 					`func_by_guest_index` called with `index` can't return `None`;
 					qed"
 			);
 
 		// Serialize arguments into a byte vector.
-		let invoke_args_data: Vec<u8> = args.as_ref()
-			.iter()
-			.cloned()
-			.map(sandbox_primitives::TypedValue::from)
-			.collect::<Vec<_>>()
-			.encode();
+		let invoke_args_data: Vec<u8> = args.encode();
 
 		let state = self.state;
 
@@ -272,7 +338,7 @@ impl<'a, FE: SandboxCapabilities + 'a> Externals for GuestExternals<'a, FE> {
 		let invoke_args_ptr = self.supervisor_externals.allocate(invoke_args_len)?;
 		self.supervisor_externals.write_memory(invoke_args_ptr, &invoke_args_data)?;
 		let result = self.supervisor_externals.invoke(
-			&self.sandbox_instance.dispatch_thunk,
+			self.dispatch_thunk,
 			invoke_args_ptr,
 			invoke_args_len,
 			state,
@@ -301,7 +367,8 @@ impl<'a, FE: SandboxCapabilities + 'a> Externals for GuestExternals<'a, FE> {
 
 fn with_guest_externals<FE, R, F>(
 	supervisor_externals: &mut FE,
-	sandbox_instance: &SandboxInstance<FE::SupervisorFuncRef>,
+	dispatch_thunk: &FE::SupervisorFuncRef,
+	guest_to_supervisor_mapping: &GuestToSupervisorFunctionMapping,
 	state: u32,
 	f: F,
 ) -> R
@@ -311,12 +378,22 @@ where
 {
 	let mut guest_externals = GuestExternals {
 		supervisor_externals,
-		sandbox_instance,
+		dispatch_thunk,
+		guest_to_supervisor_mapping,
 		state,
 	};
 	f(&mut guest_externals)
 }
 
+/// A guest module instantiated by one of the [`SandboxBackend`]s.
+enum BackendInstance {
+	/// Instance of the wasmi backend.
+	Wasmi(ModuleRef),
+	/// Instance of the wasmtime backend.
+	#[cfg(feature = "wasmtime")]
+	Wasmtime(wasmtime_backend::Instance),
+}
+
 /// Sandboxed instance of a wasm module.
 ///
 /// It's primary purpose is to [`invoke`] exported functions on it.
@@ -332,7 +409,7 @@ where
 ///
 /// [`invoke`]: #method.invoke
 pub struct SandboxInstance<FR> {
-	instance: ModuleRef,
+	backend_instance: BackendInstance,
 	dispatch_thunk: FR,
 	guest_to_supervisor_mapping: GuestToSupervisorFunctionMapping,
 }
@@ -354,11 +431,15 @@ impl<FR> SandboxInstance<FR> {
 	) -> std::result::Result<Option<wasmi::RuntimeValue>, wasmi::Error> {
 		with_guest_externals(
 			supervisor_externals,
-			self,
+			&self.dispatch_thunk,
+			&self.guest_to_supervisor_mapping,
 			state,
-			|guest_externals| {
-				self.instance
-					.invoke_export(export_name, args, guest_externals)
+			|guest_externals| match &self.backend_instance {
+				BackendInstance::Wasmi(instance) =>
+					instance.invoke_export(export_name, args, guest_externals),
+				#[cfg(feature = "wasmtime")]
+				BackendInstance::Wasmtime(instance) =>
+					instance.invoke(export_name, args, guest_externals),
 			},
 		)
 	}
@@ -381,7 +462,7 @@ pub enum InstantiationError {
 
 fn decode_environment_definition(
 	raw_env_def: &[u8],
-	memories: &[Option<MemoryRef>],
+	memories: &[Option<Memory>],
 ) -> std::result::Result<(Imports, GuestToSupervisorFunctionMapping), InstantiationError> {
 	let env_def = sandbox_primitives::EnvironmentDefinition::decode(&mut &raw_env_def[..])
 		.map_err(|_| InstantiationError::EnvironmentDefinitionCorrupted)?;
@@ -444,29 +525,45 @@ pub fn instantiate<FE: SandboxCapabilities>(
 	let (imports, guest_to_supervisor_mapping) =
 		decode_environment_definition(raw_env_def, &supervisor_externals.store().memories)?;
 
-	let module = Module::from_buffer(wasm).map_err(|_| InstantiationError::ModuleDecoding)?;
-	let instance = ModuleInstance::new(&module, &imports).map_err(|_| InstantiationError::Instantiation)?;
+	let backend_instance = match supervisor_externals.store().backend {
+		SandboxBackend::Wasmi => {
+			let module = Module::from_buffer(wasm)
+				.map_err(|_| InstantiationError::ModuleDecoding)?;
+			let instance = ModuleInstance::new(&module, &imports)
+				.map_err(|_| InstantiationError::Instantiation)?;
+
+			let instance = with_guest_externals(
+				supervisor_externals,
+				&dispatch_thunk,
+				&guest_to_supervisor_mapping,
+				state,
+				|guest_externals| {
+					instance
+						.run_start(guest_externals)
+						.map_err(|_| InstantiationError::StartTrapped)
+				},
+			)?;
+			BackendInstance::Wasmi(instance)
+		}
+		#[cfg(feature = "wasmtime")]
+		SandboxBackend::Wasmtime => {
+			let instance = with_guest_externals(
+				supervisor_externals,
+				&dispatch_thunk,
+				&guest_to_supervisor_mapping,
+				state,
+				|guest_externals| wasmtime_backend::Instance::new(wasm, &imports, guest_externals),
+			)?;
+			BackendInstance::Wasmtime(instance)
+		}
+	};
 
 	let sandbox_instance = Rc::new(SandboxInstance {
-		// In general, it's not a very good idea to use `.not_started_instance()` for anything
-		// but for extracting memory and tables. But in this particular case, we are extracting
-		// for the purpose of running `start` function which should be ok.
-		instance: instance.not_started_instance().clone(),
+		backend_instance,
 		dispatch_thunk,
 		guest_to_supervisor_mapping,
 	});
 
-	with_guest_externals(
-		supervisor_externals,
-		&sandbox_instance,
-		state,
-		|guest_externals| {
-			instance
-				.run_start(guest_externals)
-				.map_err(|_| InstantiationError::StartTrapped)
-		},
-	)?;
-
 	// At last, register the instance.
 	let instance_idx = supervisor_externals
 		.store_mut()
@@ -480,18 +577,25 @@ pub fn instantiate<FE: SandboxCapabilities>(
 pub struct Store<FR> {
 	// Memories and instances are `Some` untill torndown.
 	instances: Vec<Option<Rc<SandboxInstance<FR>>>>,
-	memories: Vec<Option<MemoryRef>>,
+	memories: Vec<Option<Memory>>,
+	backend: SandboxBackend,
 }
 
 impl<FR> Store<FR> {
-	/// Create a new empty sandbox store.
-	pub fn new() -> Self {
+	/// Create a new empty sandbox store that executes guest modules with the given `backend`.
+	pub fn new(backend: SandboxBackend) -> Self {
 		Store {
 			instances: Vec::new(),
 			memories: Vec::new(),
+			backend,
 		}
 	}
 
+	/// Returns the backend used to execute the guest modules of this store.
+	pub fn backend(&self) -> SandboxBackend {
+		self.backend
+	}
+
 	/// Create a new memory instance and return it's index.
 	///
 	/// # Errors
@@ -501,14 +605,21 @@ impl<FR> Store<FR> {
 	pub fn new_memory(&mut self, initial: u32, maximum: u32) -> Result<u32> {
 		let maximum = match maximum {
 			sandbox_primitives::MEM_UNLIMITED => None,
-			specified_limit => Some(Pages(specified_limit as usize)),
+			specified_limit => Some(specified_limit),
 		};
 
-		let mem =
-			MemoryInstance::alloc(
-				Pages(initial as usize),
-				maximum,
-			)?;
+		let mem = match self.backend {
+			SandboxBackend::Wasmi => Memory::Wasmi(
+				MemoryInstance::alloc(
+					Pages(initial as usize),
+					maximum.map(|maximum| Pages(maximum as usize)),
+				)?
+			),
+			#[cfg(feature = "wasmtime")]
+			SandboxBackend::Wasmtime => Memory::Wasmtime(
+				wasmtime_backend::Memory::new(initial, maximum)?
+			),
+		};
 
 		let mem_idx = self.memories.len();
 		self.memories.push(Some(mem));
@@ -535,7 +646,7 @@ impl<FR> Store<FR> {
 	///
 	/// Returns `Err` If `memory_idx` isn't a valid index of an memory or
 	/// if memory has been torn down.
-	pub fn memory(&self, memory_idx: u32) -> Result<MemoryRef> {
+	pub fn memory(&self, memory_idx: u32) -> Result<Memory> {
 		self.memories
 			.get(memory_idx as usize)
 			.cloned()
@@ -0,0 +1,537 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Sandbox backend that compiles guest modules to native code with wasmtime.
+//!
+//! The guest code is compiled as is, so any instrumentation applied by the caller before
+//! instantiation (e.g. gas metering or stack height limiting done by `pallet-contracts`) keeps
+//! working unchanged: the injected calls simply end up as calls to supervisor functions.
+//!
+//! Every function imported by a guest module is backed by a trampoline of a per instance "env"
+//! module. The trampolines forward the call to the [`SupervisorDispatch`] that is registered on
+//! the "env" module for the duration of the instantiation or invocation.
+
+use super::{GuestFuncIndex, Imports, InstantiationError, Memory as SandboxMemory, SupervisorDispatch};
+use crate::error::{Error, Result};
+use crate::trampoline::make_trampoline;
+
+use sp_core::sandbox::TypedValue;
+use std::{
+	any::Any, cell::RefCell, collections::HashMap, mem, ptr, rc::Rc,
+	panic::{self, AssertUnwindSafe},
+};
+
+use cranelift_codegen::{ir, isa::TargetIsa};
+use cranelift_entity::PrimaryMap;
+use cranelift_frontend::FunctionBuilderContext;
+use cranelift_wasm::{DefinedFuncIndex, FuncIndex};
+use parity_wasm::elements::{External, Module as RawModule, Type, ValueType};
+use wasmtime_environ::{MemoryPlan, Module, translate_signature};
+use wasmtime_jit::{CodeMemory, CompilationStrategy, Compiler, SetupError, target_tunables};
+use wasmtime_runtime::{
+	Export, Imports as RuntimeImports, InstanceHandle, VMContext, VMFunctionBody,
+	VMInvokeArgument, VMMemoryDefinition, wasmtime_call_trampoline,
+};
+
+const CALL_SUCCESS: u32 = 0;
+const CALL_FAILED_WITH_ERROR: u32 = 1;
+const CALL_WITH_BAD_HOST_STATE: u32 = 2;
+
+/// A linear memory of the wasmtime backend.
+///
+/// The memory is owned by a tiny instance that does nothing but export it, so that it can be
+/// imported by guest modules.
+#[derive(Clone)]
+pub struct Memory {
+	// The instance exporting the memory must be kept around to prevent the memory from being
+	// dropped.
+	#[allow(dead_code)]
+	instance: InstanceHandle,
+	/// The export of the memory, used when linking guest modules.
+	export: Export,
+	definition: *mut VMMemoryDefinition,
+}
+
+impl Memory {
+	/// Create a new memory with the given `initial` and `maximum` number of pages.
+	pub fn new(initial: u32, maximum: Option<u32>) -> Result<Self> {
+		if maximum.map_or(false, |maximum| initial > maximum) {
+			return Err(Error::Other(
+				"initial number of memory pages exceeds the maximum".into()
+			));
+		}
+
+		// The memory must be laid out the same way the compiler expects imported memories to be.
+		let tunables = target_tunables(target_isa()?.triple());
+		let mut module = Module::new();
+		let plan = MemoryPlan::for_memory(
+			cranelift_wasm::Memory { minimum: initial, maximum, shared: false },
+			&tunables,
+		);
+		let memory_index = module.memory_plans.push(plan);
+		module.exports.insert(
+			"memory".to_string(),
+			wasmtime_environ::Export::Memory(memory_index),
+		);
+
+		let mut instance = InstanceHandle::new(
+			Rc::new(module),
+			Rc::new(RefCell::new(HashMap::new())),
+			PrimaryMap::new().into_boxed_slice(),
+			RuntimeImports::none(),
+			&[],
+			PrimaryMap::new().into_boxed_slice(),
+			None,
+			Box::new(()),
+		).map_err(|e| Error::Other(format!("cannot allocate sandbox memory: {}", e)))?;
+
+		let export = instance.lookup("memory").ok_or(Error::InvalidMemoryReference)?;
+		let definition = match export {
+			Export::Memory { definition, .. } => definition,
+			_ => return Err(Error::InvalidMemoryReference),
+		};
+		Ok(Memory { instance, export, definition })
+	}
+
+	/// Provides direct access to the underlying memory buffer.
+	pub fn with_direct_access<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
+		// This is safe as:
+		// - the definition is owned by `self.instance` which is kept alive by `self`
+		// - the base and length are read on every access, so growing the memory is accounted for
+		let memory = unsafe {
+			std::slice::from_raw_parts((*self.definition).base, (*self.definition).current_length)
+		};
+		f(memory)
+	}
+
+	/// Provides direct mutable access to the underlying memory buffer.
+	pub fn with_direct_access_mut<R, F: FnOnce(&mut [u8]) -> R>(&self, f: F) -> R {
+		// See `with_direct_access`. The sandbox is single threaded and the buffer is only borrowed
+		// for the duration of `f`, during which no guest code of this memory is running.
+		let memory = unsafe {
+			std::slice::from_raw_parts_mut((*self.definition).base, (*self.definition).current_length)
+		};
+		f(memory)
+	}
+}
+
+/// The signature of a function imported by a guest module.
+struct HostFunc {
+	guest_index: GuestFuncIndex,
+	params: Vec<ValueType>,
+	result: Option<ValueType>,
+}
+
+/// The host state of the "env" module of a guest instance.
+struct EnvState {
+	host_funcs: Vec<HostFunc>,
+	// The code memory must be kept around on the state to prevent it from being dropped.
+	#[allow(dead_code)]
+	code_memory: CodeMemory,
+	/// The dispatcher of the instantiation or invocation that is currently in progress.
+	///
+	/// The lifetime is erased; the pointer is only set while the dispatcher is borrowed by
+	/// [`with_dispatcher`].
+	dispatcher: Option<*mut dyn SupervisorDispatch>,
+	trap: Option<Error>,
+}
+
+/// A guest module instance of the wasmtime backend.
+pub struct Instance {
+	instance: InstanceHandle,
+	env: InstanceHandle,
+	compiler: RefCell<Compiler>,
+}
+
+impl Instance {
+	/// Compile and instantiate the guest module in `wasm`, running its start function.
+	pub(super) fn new(
+		wasm: &[u8],
+		imports: &Imports,
+		dispatcher: &mut dyn SupervisorDispatch,
+	) -> std::result::Result<Self, InstantiationError> {
+		let raw_module = parity_wasm::deserialize_buffer::<RawModule>(wasm)
+			.map_err(|_| InstantiationError::ModuleDecoding)?;
+
+		let isa = target_isa().map_err(|_| InstantiationError::Instantiation)?;
+		let (mut env, func_exports) = instantiate_env_module(isa.as_ref(), &raw_module, imports)?;
+
+		let mut compiler = Compiler::new(
+			target_isa().map_err(|_| InstantiationError::Instantiation)?,
+			CompilationStrategy::Cranelift,
+		);
+
+		let mut dispatch_env = env.clone();
+		let instance = {
+			let mut resolver = Resolver {
+				env: &mut env,
+				func_exports: &func_exports,
+				imports,
+			};
+			let global_exports = Rc::new(RefCell::new(HashMap::new()));
+
+			with_dispatcher(&mut dispatch_env, dispatcher, || {
+				wasmtime_jit::instantiate(&mut compiler, wasm, &mut resolver, global_exports, false)
+			}).map_err(|e| match e {
+				SetupError::Validate(_) | SetupError::Compile(_) => InstantiationError::ModuleDecoding,
+				SetupError::Instantiate(wasmtime_runtime::InstantiationError::StartTrap(_)) =>
+					InstantiationError::StartTrapped,
+				_ => InstantiationError::Instantiation,
+			})?
+		};
+
+		Ok(Instance {
+			instance,
+			env,
+			compiler: RefCell::new(compiler),
+		})
+	}
+
+	/// Invoke the exported function `export_name` with the given `args`.
+	pub(super) fn invoke(
+		&self,
+		export_name: &str,
+		args: &[wasmi::RuntimeValue],
+		dispatcher: &mut dyn SupervisorDispatch,
+	) -> std::result::Result<Option<wasmi::RuntimeValue>, wasmi::Error> {
+		let (address, signature, vmctx) = match self.instance.clone().lookup(export_name) {
+			Some(Export::Function { address, signature, vmctx }) => (address, signature, vmctx),
+			_ => return Err(wasmi::Error::Function(
+				format!("Module doesn't have export {}", export_name)
+			)),
+		};
+
+		// The first parameter of the signature is the `VMContext`.
+		let params = &signature.params[1..];
+		let args_match = params.len() == args.len() && params.iter()
+			.zip(args)
+			.all(|(param, arg)| param.value_type == cranelift_ir_type(arg.value_type()));
+		if !args_match {
+			return Err(wasmi::Error::Function(
+				format!("Export {} called with mismatching arguments", export_name)
+			));
+		}
+
+		// The following code is based on the wasmtime_jit::Context::invoke.
+		let value_size = mem::size_of::<VMInvokeArgument>();
+		let mut values_vec = vec![
+			VMInvokeArgument::new();
+			std::cmp::max(params.len(), signature.returns.len())
+		];
+		for (index, arg) in args.iter().enumerate() {
+			unsafe {
+				let ptr = values_vec.as_mut_ptr().add(index);
+				match *arg {
+					wasmi::RuntimeValue::I32(v) => ptr::write(ptr as *mut i32, v),
+					wasmi::RuntimeValue::I64(v) => ptr::write(ptr as *mut i64, v),
+					wasmi::RuntimeValue::F32(v) => ptr::write(ptr as *mut u32, v.to_bits()),
+					wasmi::RuntimeValue::F64(v) => ptr::write(ptr as *mut u64, v.to_bits()),
+				}
+			}
+		}
+
+		// Get the trampoline to call for this function. The compiler is not borrowed during the
+		// call, so the supervisor is free to invoke this instance again.
+		let exec_code_buf = self.compiler
+			.try_borrow_mut()
+			.map_err(|_| wasmi::Error::Function("Sandbox compiler is busy".into()))?
+			.get_published_trampoline(address, &signature, value_size)
+			.map_err(|e| wasmi::Error::Function(e.to_string()))?;
+
+		let mut env = self.env.clone();
+		let outcome = with_dispatcher(&mut env, dispatcher, || unsafe {
+			wasmtime_call_trampoline(vmctx, exec_code_buf, values_vec.as_mut_ptr() as *mut u8)
+		});
+		let trap = env_state(&mut env).and_then(|state| state.trap.take());
+
+		if let Err(message) = outcome {
+			let error = trap.unwrap_or_else(
+				|| Error::Other(format!("Sandboxed module trapped: {}", message))
+			);
+			return Err(wasmi::Error::Trap(error.into()));
+		}
+
+		// Load the return value out of `values_vec`.
+		let result = signature.returns.first().map(|abi_param| unsafe {
+			let ptr = values_vec.as_ptr();
+			match abi_param.value_type {
+				ir::types::I32 => wasmi::RuntimeValue::I32(ptr::read(ptr as *const i32)),
+				ir::types::I64 => wasmi::RuntimeValue::I64(ptr::read(ptr as *const i64)),
+				ir::types::F32 => wasmi::RuntimeValue::F32(
+					wasmi::nan_preserving_float::F32::from_bits(ptr::read(ptr as *const u32))
+				),
+				ir::types::F64 => wasmi::RuntimeValue::F64(
+					wasmi::nan_preserving_float::F64::from_bits(ptr::read(ptr as *const u64))
+				),
+				other => unreachable!(
+					"guest modules are validated to only use MVP value types; found {}; qed",
+					other,
+				),
+			}
+		});
+		Ok(result)
+	}
+}
+
+/// Resolves the imports of a guest module to the trampolines of its "env" module and to the
+/// memories of the sandbox store.
+struct Resolver<'a> {
+	env: &'a mut InstanceHandle,
+	func_exports: &'a HashMap<(String, String), FuncIndex>,
+	imports: &'a Imports,
+}
+
+impl<'a> wasmtime_jit::Resolver for Resolver<'a> {
+	fn resolve(&mut self, module: &str, field: &str) -> Option<Export> {
+		let key = (module.to_string(), field.to_string());
+		if let Some(func_index) = self.func_exports.get(&key) {
+			return Some(self.env.lookup_by_declaration(
+				&wasmtime_environ::Export::Function(*func_index)
+			));
+		}
+
+		let key = (module.as_bytes().to_vec(), field.as_bytes().to_vec());
+		match self.imports.memories_map.get(&key) {
+			Some(SandboxMemory::Wasmtime(memory)) => Some(memory.export.clone()),
+			_ => None,
+		}
+	}
+}
+
+/// Register `dispatcher` on the "env" module while `f` is running.
+fn with_dispatcher<R>(
+	env: &mut InstanceHandle,
+	dispatcher: &mut dyn SupervisorDispatch,
+	f: impl FnOnce() -> R,
+) -> R {
+	// Erase the lifetime of the dispatcher. This is safe as the pointer is removed from the
+	// state again before this function returns and thus before `dispatcher` goes out of scope.
+	let dispatcher: *mut (dyn SupervisorDispatch + '_) = dispatcher;
+	let dispatcher: *mut (dyn SupervisorDispatch + 'static) = unsafe { mem::transmute(dispatcher) };
+
+	let previous = env_state(env).map(|state| mem::replace(&mut state.dispatcher, Some(dispatcher)));
+	let result = f();
+	if let (Some(state), Some(previous)) = (env_state(env), previous) {
+		state.dispatcher = previous;
+	}
+	result
+}
+
+fn env_state(env: &mut InstanceHandle) -> Option<&mut EnvState> {
+	env.host_state().downcast_mut::<EnvState>()
+}
+
+/// Create the "env" module providing a trampoline for every function imported by the guest
+/// module.
+///
+/// Returns the instance along with the index of the trampoline of each imported function.
+fn instantiate_env_module(
+	isa: &dyn TargetIsa,
+	raw_module: &RawModule,
+	imports: &Imports,
+) -> std::result::Result<(InstanceHandle, HashMap<(String, String), FuncIndex>), InstantiationError> {
+	let types = raw_module.type_section().map(|section| section.types()).unwrap_or(&[]);
+	let import_entries = raw_module.import_section().map(|section| section.entries()).unwrap_or(&[]);
+
+	let pointer_type = isa.pointer_type();
+	let call_conv = isa.default_call_conv();
+
+	let mut fn_builder_ctx = FunctionBuilderContext::new();
+	let mut module = Module::new();
+	let mut finished_functions = <PrimaryMap<DefinedFuncIndex, *const VMFunctionBody>>::new();
+	let mut code_memory = CodeMemory::new();
+	let mut host_funcs = Vec::new();
+	let mut func_exports = HashMap::new();
+
+	for entry in import_entries {
+		let type_index = match entry.external() {
+			External::Function(type_index) => *type_index,
+			_ => continue,
+		};
+		let func_type = match types.get(type_index as usize) {
+			Some(Type::Function(func_type)) => func_type,
+			None => return Err(InstantiationError::ModuleDecoding),
+		};
+		let key = (entry.module().as_bytes().to_vec(), entry.field().as_bytes().to_vec());
+		let guest_index = *imports.func_map.get(&key)
+			.ok_or(InstantiationError::Instantiation)?;
+
+		let mut sig = ir::Signature::new(call_conv);
+		for param in func_type.params() {
+			sig.params.push(ir::AbiParam::new(cranelift_ir_type(wasmi_value_type(*param)?)));
+		}
+		if let Some(result) = func_type.return_type() {
+			sig.returns.push(ir::AbiParam::new(cranelift_ir_type(wasmi_value_type(result)?)));
+		}
+		let sig = translate_signature(sig, pointer_type);
+
+		let sig_id = module.signatures.push(sig.clone());
+		let func_id = module.functions.push(sig_id);
+		let trampoline = make_trampoline(
+			isa,
+			&mut code_memory,
+			&mut fn_builder_ctx,
+			host_funcs.len() as u32,
+			&sig,
+			stub_fn,
+		).map_err(|_| InstantiationError::Instantiation)?;
+		finished_functions.push(trampoline);
+
+		host_funcs.push(HostFunc {
+			guest_index,
+			params: func_type.params().to_vec(),
+			result: func_type.return_type(),
+		});
+		func_exports.insert((entry.module().to_string(), entry.field().to_string()), func_id);
+	}
+
+	code_memory.publish();
+
+	let env_state = EnvState {
+		host_funcs,
+		code_memory,
+		dispatcher: None,
+		trap: None,
+	};
+	let env = InstanceHandle::new(
+		Rc::new(module),
+		Rc::new(RefCell::new(HashMap::new())),
+		finished_functions.into_boxed_slice(),
+		RuntimeImports::none(),
+		&[],
+		PrimaryMap::new().into_boxed_slice(),
+		None,
+		Box::new(env_state) as Box<dyn Any>,
+	).map_err(|_| InstantiationError::Instantiation)?;
+
+	Ok((env, func_exports))
+}
+
+/// This is called by the dynamically generated trampoline taking the function index and reference
+/// to the call arguments on the stack as arguments. Returns zero on success and a non-zero value
+/// on failure.
+unsafe extern "C" fn stub_fn(vmctx: *mut VMContext, func_index: u32, values_vec: *mut i64) -> u32 {
+	if let Some(state) = (*vmctx).host_state().downcast_mut::<EnvState>() {
+		let host_funcs = &state.host_funcs;
+		let dispatcher = state.dispatcher;
+		let result = panic::catch_unwind(AssertUnwindSafe(|| {
+			stub_fn_inner(host_funcs, dispatcher, func_index, values_vec)
+		}));
+
+		match result {
+			Ok(Ok(())) => CALL_SUCCESS,
+			Ok(Err(err)) => {
+				state.trap = Some(err);
+				CALL_FAILED_WITH_ERROR
+			}
+			Err(_) => {
+				state.trap = Some(Error::Other("Supervisor function panicked".into()));
+				CALL_FAILED_WITH_ERROR
+			}
+		}
+	} else {
+		// Well, we can't even set a trap message, so we'll just exit without one.
+		CALL_WITH_BAD_HOST_STATE
+	}
+}
+
+/// Implements most of the logic in `stub_fn` but returning a `Result` instead of an integer error
+/// for the sake of readability.
+unsafe fn stub_fn_inner(
+	host_funcs: &[HostFunc],
+	dispatcher: Option<*mut dyn SupervisorDispatch>,
+	func_index: u32,
+	values_vec: *mut i64,
+) -> Result<()> {
+	let func = host_funcs.get(func_index as usize)
+		.ok_or_else(|| Error::Other(format!("call to undefined sandbox function {}", func_index)))?;
+	let dispatcher = dispatcher
+		.ok_or_else(|| Error::Other("sandbox function called outside of an invocation".into()))?;
+
+	let args = func.params.iter()
+		.enumerate()
+		.map(|(i, param_type)| read_value_from(values_vec.add(i), *param_type))
+		.collect();
+
+	match ((*dispatcher).dispatch(func.guest_index, args)?, func.result) {
+		(None, None) => Ok(()),
+		(Some(value), Some(result_type)) if value_type_matches(&value, result_type) => {
+			write_value_to(values_vec, value);
+			Ok(())
+		}
+		_ => Err(Error::Other("Supervisor function returned a value of unexpected type".into())),
+	}
+}
+
+/// Build a new TargetIsa for the host machine.
+fn target_isa() -> Result<Box<dyn TargetIsa>> {
+	let isa_builder = cranelift_native::builder()
+		.map_err(|e| Error::Other(format!("missing compiler support: {}", e)))?;
+	let flag_builder = cranelift_codegen::settings::builder();
+	Ok(isa_builder.finish(cranelift_codegen::settings::Flags::new(flag_builder)))
+}
+
+fn wasmi_value_type(
+	value_type: ValueType,
+) -> std::result::Result<wasmi::ValueType, InstantiationError> {
+	match value_type {
+		ValueType::I32 => Ok(wasmi::ValueType::I32),
+		ValueType::I64 => Ok(wasmi::ValueType::I64),
+		ValueType::F32 => Ok(wasmi::ValueType::F32),
+		ValueType::F64 => Ok(wasmi::ValueType::F64),
+		#[allow(unreachable_patterns)]
+		_ => Err(InstantiationError::ModuleDecoding),
+	}
+}
+
+fn cranelift_ir_type(value_type: wasmi::ValueType) -> ir::types::Type {
+	match value_type {
+		wasmi::ValueType::I32 => ir::types::I32,
+		wasmi::ValueType::I64 => ir::types::I64,
+		wasmi::ValueType::F32 => ir::types::F32,
+		wasmi::ValueType::F64 => ir::types::F64,
+	}
+}
+
+fn value_type_matches(value: &TypedValue, value_type: ValueType) -> bool {
+	match (value, value_type) {
+		(TypedValue::I32(_), ValueType::I32) |
+		(TypedValue::I64(_), ValueType::I64) |
+		(TypedValue::F32(_), ValueType::F32) |
+		(TypedValue::F64(_), ValueType::F64) => true,
+		_ => false,
+	}
+}
+
+unsafe fn write_value_to(p: *mut i64, value: TypedValue) {
+	match value {
+		TypedValue::I32(v) => ptr::write(p as *mut i32, v),
+		TypedValue::I64(v) => ptr::write(p as *mut i64, v),
+		TypedValue::F32(v) => ptr::write(p as *mut i32, v),
+		TypedValue::F64(v) => ptr::write(p as *mut i64, v),
+	}
+}
+
+unsafe fn read_value_from(p: *const i64, value_type: ValueType) -> TypedValue {
+	match value_type {
+		ValueType::I32 => TypedValue::I32(ptr::read(p as *const i32)),
+		ValueType::I64 => TypedValue::I64(ptr::read(p as *const i64)),
+		ValueType::F32 => TypedValue::F32(ptr::read(p as *const i32)),
+		ValueType::F64 => TypedValue::F64(ptr::read(p as *const i64)),
+		#[allow(unreachable_patterns)]
+		_ => unreachable!("the imported functions are checked to only use MVP value types; qed"),
+	}
+}
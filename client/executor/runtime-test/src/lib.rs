@@ -58,6 +58,10 @@ sp_core::wasm_export_functions! {
 
 	fn test_exhaust_heap() -> Vec<u8> { Vec::with_capacity(16777216) }
 
+	fn test_recursion(depth: u64) -> u64 {
+		recurse(depth)
+	}
+
 	fn test_panic() { panic!("test panic") }
 
 	fn test_conditional_panic(input: Vec<u8>) -> Vec<u8> {
@@ -214,6 +218,17 @@ sp_core::wasm_export_functions! {
 	}
  }
 
+#[cfg(not(feature = "std"))]
+#[inline(never)]
+fn recurse(depth: u64) -> u64 {
+	// Not a tail call, so every level of recursion needs its own stack frame.
+	if depth == 0 {
+		0
+	} else {
+		recurse(depth - 1).wrapping_mul(31) ^ depth
+	}
+}
+
 #[cfg(not(feature = "std"))]
 fn execute_sandboxed(
 	code: &[u8],
@@ -94,6 +94,30 @@ fn call_in_wasm<E: Externalities>(
 	)
 }
 
+fn call_in_wasm_with_sandbox_backend<E: Externalities>(
+	function: &str,
+	call_data: &[u8],
+	execution_method: WasmExecutionMethod,
+	sandbox_backend: crate::SandboxBackend,
+	ext: &mut E,
+	code: &[u8],
+	heap_pages: u64,
+) -> crate::error::Result<Vec<u8>> {
+	use sp_wasm_interface::HostFunctions as _;
+
+	crate::call_in_wasm_with_host_functions(
+		function,
+		call_data,
+		execution_method,
+		ext,
+		code,
+		heap_pages,
+		HostFunctions::host_functions(),
+		true,
+		sandbox_backend,
+	)
+}
+
 #[test_case(WasmExecutionMethod::Interpreted)]
 #[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
 fn returning_should_work(wasm_method: WasmExecutionMethod) {
@@ -580,3 +604,55 @@ fn offchain_http_should_work(wasm_method: WasmExecutionMethod) {
 		true.encode(),
 	);
 }
+
+/// Stack height limit used for the recursion tests.
+const MAX_STACK_HEIGHT: u32 = 16 * 1024;
+
+fn call_recursion(wasm_method: WasmExecutionMethod, depth: u64) -> crate::error::Result<Vec<u8>> {
+	let mut ext = TestExternalities::default();
+	let mut ext = ext.ext();
+	let test_code = crate::inject_stack_height_limiter(&WASM_BINARY, MAX_STACK_HEIGHT).unwrap();
+	call_in_wasm(
+		"test_recursion",
+		&depth.encode(),
+		wasm_method,
+		&mut ext,
+		&test_code[..],
+		8,
+	)
+}
+
+#[test_case(WasmExecutionMethod::Interpreted)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
+fn shallow_recursion_within_stack_height_limit_should_work(wasm_method: WasmExecutionMethod) {
+	let expected = (1..=100u64).fold(0u64, |acc, depth| acc.wrapping_mul(31) ^ depth);
+	assert_eq!(call_recursion(wasm_method, 100).unwrap(), expected.encode());
+}
+
+#[test_case(WasmExecutionMethod::Interpreted)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
+fn deep_recursion_exceeding_stack_height_limit_should_trap(wasm_method: WasmExecutionMethod) {
+	assert!(call_recursion(wasm_method, 1_000_000).is_err());
+}
+
+#[cfg(feature = "wasmtime")]
+#[test]
+fn stack_height_limit_is_identical_for_all_backends() {
+	// Returns the deepest recursion that still succeeds.
+	fn max_depth(wasm_method: WasmExecutionMethod) -> u64 {
+		let (mut lo, mut hi) = (0, 1_000_000);
+		while hi - lo > 1 {
+			let mid = lo + (hi - lo) / 2;
+			match call_recursion(wasm_method, mid) {
+				Ok(_) => lo = mid,
+				Err(_) => hi = mid,
+			}
+		}
+		lo
+	}
+
+	assert_eq!(
+		max_depth(WasmExecutionMethod::Interpreted),
+		max_depth(WasmExecutionMethod::Compiled),
+	);
+}
@@ -14,17 +14,19 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-use super::{TestExternalities, call_in_wasm};
-use crate::WasmExecutionMethod;
+use super::{TestExternalities, call_in_wasm_with_sandbox_backend};
+use crate::{WasmExecutionMethod, SandboxBackend};
 
 use codec::Encode;
 use sc_runtime_test::WASM_BINARY;
 use test_case::test_case;
 use wabt;
 
-#[test_case(WasmExecutionMethod::Interpreted)]
-#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
-fn sandbox_should_work(wasm_method: WasmExecutionMethod) {
+#[test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmi)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmi))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmtime))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmtime))]
+fn sandbox_should_work(wasm_method: WasmExecutionMethod, sandbox_backend: SandboxBackend) {
 	let mut ext = TestExternalities::default();
 	let mut ext = ext.ext();
 	let test_code = WASM_BINARY;
@@ -51,10 +53,11 @@ fn sandbox_should_work(wasm_method: WasmExecutionMethod) {
 		"#).unwrap().encode();
 
 	assert_eq!(
-		call_in_wasm(
+		call_in_wasm_with_sandbox_backend(
 			"test_sandbox",
 			&code,
 			wasm_method,
+			sandbox_backend,
 			&mut ext,
 			&test_code[..],
 			8,
@@ -63,9 +66,11 @@ fn sandbox_should_work(wasm_method: WasmExecutionMethod) {
 	);
 }
 
-#[test_case(WasmExecutionMethod::Interpreted)]
-#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
-fn sandbox_trap(wasm_method: WasmExecutionMethod) {
+#[test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmi)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmi))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmtime))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmtime))]
+fn sandbox_trap(wasm_method: WasmExecutionMethod, sandbox_backend: SandboxBackend) {
 	let mut ext = TestExternalities::default();
 	let mut ext = ext.ext();
 	let test_code = WASM_BINARY;
@@ -81,10 +86,11 @@ fn sandbox_trap(wasm_method: WasmExecutionMethod) {
 		"#).unwrap();
 
 	assert_eq!(
-		call_in_wasm(
+		call_in_wasm_with_sandbox_backend(
 			"test_sandbox",
 			&code,
 			wasm_method,
+			sandbox_backend,
 			&mut ext,
 			&test_code[..],
 			8,
@@ -93,10 +99,12 @@ fn sandbox_trap(wasm_method: WasmExecutionMethod) {
 	);
 }
 
-#[test_case(WasmExecutionMethod::Interpreted)]
-#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
+#[test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmi)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmi))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmtime))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmtime))]
 #[should_panic(expected = "Allocator ran out of space")]
-fn sandbox_should_trap_when_heap_exhausted(wasm_method: WasmExecutionMethod) {
+fn sandbox_should_trap_when_heap_exhausted(wasm_method: WasmExecutionMethod, sandbox_backend: SandboxBackend) {
 	let mut ext = TestExternalities::default();
 	let mut ext = ext.ext();
 	let test_code = WASM_BINARY;
@@ -111,19 +119,22 @@ fn sandbox_should_trap_when_heap_exhausted(wasm_method: WasmExecutionMethod) {
 		)
 		"#).unwrap().encode();
 
-	call_in_wasm(
+	call_in_wasm_with_sandbox_backend(
 		"test_exhaust_heap",
 		&code,
 		wasm_method,
+		sandbox_backend,
 		&mut ext,
 		&test_code[..],
 		8,
 	).unwrap();
 }
 
-#[test_case(WasmExecutionMethod::Interpreted)]
-#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
-fn start_called(wasm_method: WasmExecutionMethod) {
+#[test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmi)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmi))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmtime))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmtime))]
+fn start_called(wasm_method: WasmExecutionMethod, sandbox_backend: SandboxBackend) {
 	let mut ext = TestExternalities::default();
 	let mut ext = ext.ext();
 	let test_code = WASM_BINARY;
@@ -156,10 +167,11 @@ fn start_called(wasm_method: WasmExecutionMethod) {
 		"#).unwrap().encode();
 
 	assert_eq!(
-		call_in_wasm(
+		call_in_wasm_with_sandbox_backend(
 			"test_sandbox",
 			&code,
 			wasm_method,
+			sandbox_backend,
 			&mut ext,
 			&test_code[..],
 			8,
@@ -168,9 +180,11 @@ fn start_called(wasm_method: WasmExecutionMethod) {
 	);
 }
 
-#[test_case(WasmExecutionMethod::Interpreted)]
-#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
-fn invoke_args(wasm_method: WasmExecutionMethod) {
+#[test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmi)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmi))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmtime))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmtime))]
+fn invoke_args(wasm_method: WasmExecutionMethod, sandbox_backend: SandboxBackend) {
 	let mut ext = TestExternalities::default();
 	let mut ext = ext.ext();
 	let test_code = WASM_BINARY;
@@ -199,10 +213,11 @@ fn invoke_args(wasm_method: WasmExecutionMethod) {
 		"#).unwrap().encode();
 
 	assert_eq!(
-		call_in_wasm(
+		call_in_wasm_with_sandbox_backend(
 			"test_sandbox_args",
 			&code,
 			wasm_method,
+			sandbox_backend,
 			&mut ext,
 			&test_code[..],
 			8,
@@ -211,9 +226,11 @@ fn invoke_args(wasm_method: WasmExecutionMethod) {
 	);
 }
 
-#[test_case(WasmExecutionMethod::Interpreted)]
-#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
-fn return_val(wasm_method: WasmExecutionMethod) {
+#[test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmi)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmi))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmtime))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmtime))]
+fn return_val(wasm_method: WasmExecutionMethod, sandbox_backend: SandboxBackend) {
 	let mut ext = TestExternalities::default();
 	let mut ext = ext.ext();
 	let test_code = WASM_BINARY;
@@ -230,10 +247,11 @@ fn return_val(wasm_method: WasmExecutionMethod) {
 		"#).unwrap().encode();
 
 	assert_eq!(
-		call_in_wasm(
+		call_in_wasm_with_sandbox_backend(
 			"test_sandbox_return_val",
 			&code,
 			wasm_method,
+			sandbox_backend,
 			&mut ext,
 			&test_code[..],
 			8,
@@ -242,9 +260,11 @@ fn return_val(wasm_method: WasmExecutionMethod) {
 	);
 }
 
-#[test_case(WasmExecutionMethod::Interpreted)]
-#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
-fn unlinkable_module(wasm_method: WasmExecutionMethod) {
+#[test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmi)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmi))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmtime))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmtime))]
+fn unlinkable_module(wasm_method: WasmExecutionMethod, sandbox_backend: SandboxBackend) {
 	let mut ext = TestExternalities::default();
 	let mut ext = ext.ext();
 	let test_code = WASM_BINARY;
@@ -259,10 +279,11 @@ fn unlinkable_module(wasm_method: WasmExecutionMethod) {
 		"#).unwrap().encode();
 
 	assert_eq!(
-		call_in_wasm(
+		call_in_wasm_with_sandbox_backend(
 			"test_sandbox_instantiate",
 			&code,
 			wasm_method,
+			sandbox_backend,
 			&mut ext,
 			&test_code[..],
 			8,
@@ -271,9 +292,11 @@ fn unlinkable_module(wasm_method: WasmExecutionMethod) {
 	);
 }
 
-#[test_case(WasmExecutionMethod::Interpreted)]
-#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
-fn corrupted_module(wasm_method: WasmExecutionMethod) {
+#[test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmi)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmi))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmtime))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmtime))]
+fn corrupted_module(wasm_method: WasmExecutionMethod, sandbox_backend: SandboxBackend) {
 	let mut ext = TestExternalities::default();
 	let mut ext = ext.ext();
 	let test_code = WASM_BINARY;
@@ -282,10 +305,11 @@ fn corrupted_module(wasm_method: WasmExecutionMethod) {
 	let code = vec![0u8, 0, 0, 0, 1, 0, 0, 0].encode();
 
 	assert_eq!(
-		call_in_wasm(
+		call_in_wasm_with_sandbox_backend(
 			"test_sandbox_instantiate",
 			&code,
 			wasm_method,
+			sandbox_backend,
 			&mut ext,
 			&test_code[..],
 			8,
@@ -294,9 +318,11 @@ fn corrupted_module(wasm_method: WasmExecutionMethod) {
 	);
 }
 
-#[test_case(WasmExecutionMethod::Interpreted)]
-#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
-fn start_fn_ok(wasm_method: WasmExecutionMethod) {
+#[test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmi)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmi))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmtime))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmtime))]
+fn start_fn_ok(wasm_method: WasmExecutionMethod, sandbox_backend: SandboxBackend) {
 	let mut ext = TestExternalities::default();
 	let mut ext = ext.ext();
 	let test_code = WASM_BINARY;
@@ -314,10 +340,11 @@ fn start_fn_ok(wasm_method: WasmExecutionMethod) {
 		"#).unwrap().encode();
 
 	assert_eq!(
-		call_in_wasm(
+		call_in_wasm_with_sandbox_backend(
 			"test_sandbox_instantiate",
 			&code,
 			wasm_method,
+			sandbox_backend,
 			&mut ext,
 			&test_code[..],
 			8,
@@ -326,9 +353,11 @@ fn start_fn_ok(wasm_method: WasmExecutionMethod) {
 	);
 }
 
-#[test_case(WasmExecutionMethod::Interpreted)]
-#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
-fn start_fn_traps(wasm_method: WasmExecutionMethod) {
+#[test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmi)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmi))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Interpreted, SandboxBackend::Wasmtime))]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled, SandboxBackend::Wasmtime))]
+fn start_fn_traps(wasm_method: WasmExecutionMethod, sandbox_backend: SandboxBackend) {
 	let mut ext = TestExternalities::default();
 	let mut ext = ext.ext();
 	let test_code = WASM_BINARY;
@@ -347,10 +376,11 @@ fn start_fn_traps(wasm_method: WasmExecutionMethod) {
 		"#).unwrap().encode();
 
 	assert_eq!(
-		call_in_wasm(
+		call_in_wasm_with_sandbox_backend(
 			"test_sandbox_instantiate",
 			&code,
 			wasm_method,
+			sandbox_backend,
 			&mut ext,
 			&test_code[..],
 			8,
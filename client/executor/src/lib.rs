@@ -46,9 +46,10 @@ pub use codec::Codec;
 pub use sp_core::traits::Externalities;
 #[doc(hidden)]
 pub use sp_wasm_interface;
-pub use wasm_runtime::WasmExecutionMethod;
+pub use wasm_runtime::{WasmExecutionMethod, enable_compilation_cache, inject_stack_height_limiter};
 
 pub use sc_executor_common::{error, allocator, sandbox};
+pub use sc_executor_common::sandbox::SandboxBackend;
 
 /// Call the given `function` in the given wasm `code`.
 ///
@@ -79,10 +80,12 @@ pub fn call_in_wasm<HF: sp_wasm_interface::HostFunctions>(
 		heap_pages,
 		HF::host_functions(),
 		allow_missing_imports,
+		SandboxBackend::default(),
 	)
 }
 
 /// Non-generic version of [`call_in_wasm`] that takes the `host_functions` as parameter.
+/// Modules instantiated through `sp-sandbox` are executed with the given `sandbox_backend`.
 /// For more information please see [`call_in_wasm`].
 pub fn call_in_wasm_with_host_functions(
 	function: &str,
@@ -93,6 +96,7 @@ pub fn call_in_wasm_with_host_functions(
 	heap_pages: u64,
 	host_functions: Vec<&'static dyn sp_wasm_interface::Function>,
 	allow_missing_imports: bool,
+	sandbox_backend: SandboxBackend,
 ) -> error::Result<Vec<u8>> {
 	let instance = wasm_runtime::create_wasm_runtime_with_code(
 		execution_method,
@@ -100,6 +104,7 @@ pub fn call_in_wasm_with_host_functions(
 		code,
 		host_functions,
 		allow_missing_imports,
+		sandbox_backend,
 	)?;
 
 	// It is safe, as we delete the instance afterwards.
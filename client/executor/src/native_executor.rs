@@ -24,7 +24,7 @@ use sp_core::{NativeOrEncoded, traits::{CodeExecutor, Externalities}};
 use log::trace;
 use std::{result, cell::RefCell, panic::{UnwindSafe, AssertUnwindSafe}, sync::Arc};
 use sp_wasm_interface::{HostFunctions, Function};
-use sc_executor_common::wasm_runtime::WasmRuntime;
+use sc_executor_common::{sandbox::SandboxBackend, wasm_runtime::WasmRuntime};
 
 thread_local! {
 	static RUNTIMES_CACHE: RefCell<RuntimesCache> = RefCell::new(RuntimesCache::new());
@@ -86,6 +86,10 @@ pub struct NativeExecutor<D> {
 	native_version: NativeVersion,
 	/// The number of 64KB pages to allocate for Wasm execution.
 	default_heap_pages: u64,
+	/// The stack height limit injected into the runtime code. `None` if disabled.
+	max_stack_height: Option<u32>,
+	/// The backend executing the modules instantiated through `sp-sandbox`.
+	sandbox_backend: SandboxBackend,
 	/// The host functions registered with this instance.
 	host_functions: Arc<Vec<&'static dyn Function>>,
 }
@@ -114,10 +118,31 @@ impl<D: NativeExecutionDispatch> NativeExecutor<D> {
 			fallback_method,
 			native_version: D::native_version(),
 			default_heap_pages: default_heap_pages.unwrap_or(DEFAULT_HEAP_PAGES),
+			max_stack_height: None,
+			sandbox_backend: SandboxBackend::default(),
 			host_functions: Arc::new(host_functions),
 		}
 	}
 
+	/// Limit the stack height of the runtime to `max_stack_height`.
+	///
+	/// The runtime code is instrumented before instantiation so that exceeding the limit traps
+	/// deterministically, independent of the backend and the native stack size. Runtime calls in
+	/// native are not affected.
+	pub fn with_max_stack_height(mut self, max_stack_height: Option<u32>) -> Self {
+		self.max_stack_height = max_stack_height;
+		self
+	}
+
+	/// Execute the modules the runtime instantiates through `sp-sandbox` with `sandbox_backend`.
+	///
+	/// This only affects the Wasm execution of the runtime, the native runtime always uses the
+	/// Wasmi based sandbox.
+	pub fn with_sandbox_backend(mut self, sandbox_backend: SandboxBackend) -> Self {
+		self.sandbox_backend = sandbox_backend;
+		self
+	}
+
 	/// Execute the given closure `f` with the latest runtime (based on the `CODE` key in `ext`).
 	///
 	/// The closure `f` is expected to return `Err(_)` when there happened a `panic!` in native code
@@ -146,6 +171,8 @@ impl<D: NativeExecutionDispatch> NativeExecutor<D> {
 				ext,
 				self.fallback_method,
 				self.default_heap_pages,
+				self.max_stack_height,
+				self.sandbox_backend,
 				&*self.host_functions,
 			)?;
 
@@ -170,6 +197,8 @@ impl<D: NativeExecutionDispatch> Clone for NativeExecutor<D> {
 			fallback_method: self.fallback_method,
 			native_version: D::native_version(),
 			default_heap_pages: self.default_heap_pages,
+			max_stack_height: self.max_stack_height,
+			sandbox_backend: self.sandbox_backend,
 			host_functions: self.host_functions.clone(),
 		}
 	}
@@ -272,6 +301,16 @@ impl<D: NativeExecutionDispatch> sp_core::traits::CallInWasm for NativeExecutor<
 		call_data: &[u8],
 		ext: &mut dyn Externalities,
 	) -> std::result::Result<Vec<u8>, String> {
+		let instrumented;
+		let wasm_blob = match self.max_stack_height {
+			Some(max_stack_height) => {
+				instrumented = crate::inject_stack_height_limiter(wasm_blob, max_stack_height)
+					.map_err(|e| e.to_string())?;
+				&instrumented[..]
+			},
+			None => wasm_blob,
+		};
+
 		crate::call_in_wasm_with_host_functions(
 			method,
 			call_data,
@@ -281,6 +320,7 @@ impl<D: NativeExecutionDispatch> sp_core::traits::CallInWasm for NativeExecutor<
 			self.default_heap_pages,
 			(*self.host_functions).clone(),
 			false,
+			self.sandbox_backend,
 		).map_err(|e| e.to_string())
 	}
 }
@@ -24,8 +24,9 @@ use log::{trace, warn};
 use codec::Decode;
 use sp_core::{storage::well_known_keys, traits::Externalities};
 use sp_version::RuntimeVersion;
-use std::{collections::hash_map::{Entry, HashMap}, panic::AssertUnwindSafe};
-use sc_executor_common::wasm_runtime::WasmRuntime;
+use std::panic::AssertUnwindSafe;
+use lru::LruCache;
+use sc_executor_common::{sandbox::SandboxBackend, wasm_runtime::WasmRuntime};
 
 use sp_wasm_interface::Function;
 
@@ -46,6 +47,19 @@ struct VersionedRuntime {
 	version: RuntimeVersion,
 }
 
+/// The maximum number of runtime instances kept in the [`RuntimesCache`].
+pub const MAX_RUNTIMES: usize = 8;
+
+/// The key of a runtime instance in the [`RuntimesCache`].
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+struct RuntimeKey {
+	wasm_method: WasmExecutionMethod,
+	code_hash: Vec<u8>,
+	heap_pages: u64,
+	max_stack_height: Option<u32>,
+	sandbox_backend: SandboxBackend,
+}
+
 /// Cache for the runtimes.
 ///
 /// When an instance is requested for the first time it is added to this cache. Metadata is kept
@@ -56,20 +70,28 @@ struct VersionedRuntime {
 /// the memory reset to the initial memory. So, one runtime instance is reused for every fetch
 /// request.
 ///
-/// For now the cache grows indefinitely, but that should be fine for now since runtimes can only be
-/// upgraded rarely and there are no other ways to make the node to execute some other runtime.
+/// Instances are keyed by the Wasm execution method, the hash of their code, the number of heap
+/// pages, the stack height limit and the sandbox backend. At most [`MAX_RUNTIMES`] instances are
+/// kept, evicting the least recently used one. This allows switching between runtimes (e.g. when
+/// executing calls on blocks before and after a runtime upgrade) without recompiling the code
+/// every time.
 pub struct RuntimesCache {
 	/// A cache of runtime instances along with metadata, ready to be reused.
-	///
-	/// Instances are keyed by the Wasm execution method and the hash of their code.
-	instances: HashMap<(WasmExecutionMethod, Vec<u8>), Result<VersionedRuntime, WasmError>>,
+	instances: LruCache<RuntimeKey, Result<VersionedRuntime, WasmError>>,
 }
 
 impl RuntimesCache {
-	/// Creates a new instance of a runtimes cache.
+	/// Creates a new instance of a runtimes cache holding at most [`MAX_RUNTIMES`] instances.
 	pub fn new() -> RuntimesCache {
+		RuntimesCache::with_capacity(MAX_RUNTIMES)
+	}
+
+	/// Creates a new instance of a runtimes cache holding at most `capacity` instances.
+	///
+	/// A `capacity` of 0 is treated as 1, since the runtime being fetched is always cached.
+	pub fn with_capacity(capacity: usize) -> RuntimesCache {
 		RuntimesCache {
-			instances: HashMap::new(),
+			instances: LruCache::new(capacity.max(1)),
 		}
 	}
 
@@ -89,6 +111,11 @@ impl RuntimesCache {
 	///
 	/// `default_heap_pages` - Number of 64KB pages to allocate for Wasm execution.
 	///
+	/// `max_stack_height` - The deterministic stack height limit injected into the runtime code,
+	/// see [`inject_stack_height_limiter`]. `None` leaves the code uninstrumented.
+	///
+	/// `sandbox_backend` - The backend executing the modules instantiated through `sp-sandbox`.
+	///
 	/// `host_functions` - The host functions that should be registered for the Wasm runtime.
 	///
 	/// # Return value
@@ -107,6 +134,8 @@ impl RuntimesCache {
 		ext: &mut E,
 		wasm_method: WasmExecutionMethod,
 		default_heap_pages: u64,
+		max_stack_height: Option<u32>,
+		sandbox_backend: SandboxBackend,
 		host_functions: &[&'static dyn Function],
 	) -> Result<(&mut (dyn WasmRuntime + 'static), &RuntimeVersion, Vec<u8>), Error> {
 		let code_hash = ext
@@ -118,59 +147,57 @@ impl RuntimesCache {
 			.and_then(|pages| u64::decode(&mut &pages[..]).ok())
 			.unwrap_or(default_heap_pages);
 
-		let result = match self.instances.entry((wasm_method, code_hash.clone())) {
-			Entry::Occupied(o) => {
-				let result = o.into_mut();
-				if let Ok(ref mut cached_runtime) = result {
-					let heap_pages_changed = !cached_runtime.runtime.update_heap_pages(heap_pages);
-					let host_functions_changed = cached_runtime.runtime.host_functions()
-						!= host_functions;
-					if heap_pages_changed || host_functions_changed {
-						let changed = if heap_pages_changed {
-							"heap_pages"
-						} else {
-							"host functions"
-						};
-
-						trace!(
-							target: "runtimes_cache",
-							"{} were changed. Reinstantiating the instance",
-							changed,
-						);
-						*result = create_versioned_wasm_runtime(
-							ext,
-							wasm_method,
-							heap_pages,
-							host_functions.into(),
-						);
-						if let Err(ref err) = result {
-							warn!(target: "runtimes_cache", "cannot create a runtime: {:?}", err);
-						}
-					}
+		let key = RuntimeKey {
+			wasm_method,
+			code_hash: code_hash.clone(),
+			heap_pages,
+			max_stack_height,
+			sandbox_backend,
+		};
+
+		let reinstantiate = match self.instances.get_mut(&key) {
+			Some(Ok(cached_runtime)) => {
+				let host_functions_changed = cached_runtime.runtime.host_functions()
+					!= host_functions;
+				if host_functions_changed {
+					trace!(
+						target: "runtimes_cache",
+						"host functions were changed. Reinstantiating the instance",
+					);
 				}
-				result
+				host_functions_changed
 			},
-			Entry::Vacant(v) => {
+			Some(Err(_)) => false,
+			None => {
 				trace!(target: "runtimes_cache", "no instance found in cache, creating now.");
-				let result = create_versioned_wasm_runtime(
-					ext,
-					wasm_method,
-					heap_pages,
-					host_functions.into(),
-				);
-				if let Err(ref err) = result {
-					warn!(target: "runtimes_cache", "cannot create a runtime: {:?}", err);
-				}
-				v.insert(result)
-			}
+				true
+			},
 		};
 
+		if reinstantiate {
+			let result = create_versioned_wasm_runtime(
+				ext,
+				wasm_method,
+				heap_pages,
+				max_stack_height,
+				sandbox_backend,
+				host_functions.into(),
+			);
+			if let Err(ref err) = result {
+				warn!(target: "runtimes_cache", "cannot create a runtime: {:?}", err);
+			}
+			self.instances.put(key.clone(), result);
+		}
+
+		let result = self.instances.get_mut(&key)
+			.expect("the runtime was either found or just inserted into the cache; qed");
+
 		result.as_mut()
 			.map(|entry| (entry.runtime.as_mut(), &entry.version, code_hash))
 			.map_err(|ref e| Error::InvalidCode(format!("{:?}", e)))
 	}
 
-	/// Invalidate the runtime for the given `wasm_method` and `code_hash`.
+	/// Invalidate the runtimes for the given `wasm_method` and `code_hash`.
 	///
 	/// Invalidation of a runtime is useful when there was a `panic!` in native while executing it.
 	/// The `panic!` maybe have brought the runtime into a poisoned state and so, it is better to
@@ -180,8 +207,34 @@ impl RuntimesCache {
 		wasm_method: WasmExecutionMethod,
 		code_hash: Vec<u8>,
 	) {
-		// Just remove the instance, it will be re-created the next time it is requested.
-		self.instances.remove(&(wasm_method, code_hash));
+		let keys = self.instances.iter()
+			.map(|(key, _)| key)
+			.filter(|key| key.wasm_method == wasm_method && key.code_hash == code_hash)
+			.cloned()
+			.collect::<Vec<_>>();
+
+		// Just remove the instances, they will be re-created the next time they are requested.
+		for key in keys {
+			self.instances.pop(&key);
+		}
+	}
+}
+
+/// Enable the on-disk cache of compiled runtimes for the given `wasm_method`, storing artifacts in
+/// `directory`.
+///
+/// Only the compiled execution method has compilation artifacts worth caching, for the others this
+/// is a no-op. The cache needs to be enabled before the first runtime is created.
+#[cfg_attr(not(feature = "wasmtime"), allow(unused_variables))]
+pub fn enable_compilation_cache(
+	wasm_method: WasmExecutionMethod,
+	directory: &std::path::Path,
+) -> Result<(), WasmError> {
+	match wasm_method {
+		WasmExecutionMethod::Interpreted => Ok(()),
+		#[cfg(feature = "wasmtime")]
+		WasmExecutionMethod::Compiled =>
+			sc_executor_wasmtime::enable_compilation_cache(directory),
 	}
 }
 
@@ -192,28 +245,61 @@ pub fn create_wasm_runtime_with_code(
 	code: &[u8],
 	host_functions: Vec<&'static dyn Function>,
 	allow_missing_imports: bool,
+	sandbox_backend: SandboxBackend,
 ) -> Result<Box<dyn WasmRuntime>, WasmError> {
 	match wasm_method {
 		WasmExecutionMethod::Interpreted =>
-			sc_executor_wasmi::create_instance(code, heap_pages, host_functions, allow_missing_imports)
-				.map(|runtime| -> Box<dyn WasmRuntime> { Box::new(runtime) }),
+			sc_executor_wasmi::create_instance(
+				code,
+				heap_pages,
+				host_functions,
+				allow_missing_imports,
+				sandbox_backend,
+			).map(|runtime| -> Box<dyn WasmRuntime> { Box::new(runtime) }),
 		#[cfg(feature = "wasmtime")]
 		WasmExecutionMethod::Compiled =>
-			sc_executor_wasmtime::create_instance(code, heap_pages, host_functions)
+			sc_executor_wasmtime::create_instance(code, heap_pages, host_functions, sandbox_backend)
 				.map(|runtime| -> Box<dyn WasmRuntime> { Box::new(runtime) }),
 	}
 }
 
+/// Instrument the given wasm `code` with a deterministic stack height limit.
+///
+/// The injected code keeps track of the stack height in a global and traps as soon as
+/// `max_stack_height` is exceeded. The stack height of a function is the number of its locals
+/// plus the maximal height of its value stack, so the limit does not depend on the native stack
+/// size of the thread executing the code nor on the Wasm backend used.
+pub fn inject_stack_height_limiter(code: &[u8], max_stack_height: u32) -> Result<Vec<u8>, WasmError> {
+	let module = parity_wasm::deserialize_buffer::<parity_wasm::elements::Module>(code)
+		.map_err(|_| WasmError::CantDeserializeWasm)?;
+	let module = pwasm_utils::stack_height::inject_limiter(module, max_stack_height)
+		.map_err(|e| WasmError::Other(format!("cannot inject stack height limiter: {:?}", e)))?;
+	parity_wasm::serialize(module)
+		.map_err(|e| WasmError::Other(format!("cannot serialize instrumented module: {}", e)))
+}
+
 fn create_versioned_wasm_runtime<E: Externalities>(
 	ext: &mut E,
 	wasm_method: WasmExecutionMethod,
 	heap_pages: u64,
+	max_stack_height: Option<u32>,
+	sandbox_backend: SandboxBackend,
 	host_functions: Vec<&'static dyn Function>,
 ) -> Result<VersionedRuntime, WasmError> {
-	let code = ext
+	let mut code = ext
 		.original_storage(well_known_keys::CODE)
 		.ok_or(WasmError::CodeNotFound)?;
-	let mut runtime = create_wasm_runtime_with_code(wasm_method, heap_pages, &code, host_functions, false)?;
+	if let Some(max_stack_height) = max_stack_height {
+		code = inject_stack_height_limiter(&code, max_stack_height)?;
+	}
+	let mut runtime = create_wasm_runtime_with_code(
+		wasm_method,
+		heap_pages,
+		&code,
+		host_functions,
+		false,
+		sandbox_backend,
+	)?;
 
 	// Call to determine runtime version.
 	let version_result = {
@@ -241,7 +327,86 @@ fn create_versioned_wasm_runtime<E: Externalities>(
 
 #[cfg(test)]
 mod tests {
+	use super::*;
 	use sp_wasm_interface::HostFunctions;
+	use sp_core::Blake2Hasher;
+	use codec::Encode;
+
+	type TestExternalities = sp_state_machine::TestExternalities<Blake2Hasher, u64>;
+
+	fn ext_with_heap_pages(heap_pages: u64) -> TestExternalities {
+		let mut ext = TestExternalities::default();
+		ext.insert(well_known_keys::CODE.to_vec(), substrate_test_runtime::WASM_BINARY.to_vec());
+		ext.insert(well_known_keys::HEAP_PAGES.to_vec(), heap_pages.encode());
+		ext
+	}
+
+	#[test]
+	fn runtimes_are_cached_per_heap_pages_and_evicted() {
+		let host_functions = sp_io::SubstrateHostFunctions::host_functions();
+		let mut cache = RuntimesCache::with_capacity(2);
+
+		for heap_pages in &[8, 16, 8, 32] {
+			let mut ext = ext_with_heap_pages(*heap_pages);
+			let mut ext = ext.ext();
+			cache.fetch_runtime(
+				&mut ext,
+				WasmExecutionMethod::Interpreted,
+				1024,
+				None,
+				SandboxBackend::Wasmi,
+				&host_functions,
+			).expect("creates runtime");
+		}
+
+		let mut heap_pages = cache.instances.iter()
+			.map(|(key, _)| key.heap_pages)
+			.collect::<Vec<_>>();
+		heap_pages.sort();
+		assert_eq!(heap_pages, vec![8, 32]);
+	}
+
+	#[test]
+	fn zero_capacity_cache_keeps_the_fetched_runtime() {
+		let host_functions = sp_io::SubstrateHostFunctions::host_functions();
+		let mut cache = RuntimesCache::with_capacity(0);
+
+		let mut ext = ext_with_heap_pages(8);
+		let mut ext = ext.ext();
+		cache.fetch_runtime(
+			&mut ext,
+			WasmExecutionMethod::Interpreted,
+			1024,
+			None,
+			SandboxBackend::Wasmi,
+			&host_functions,
+		).expect("creates runtime");
+		assert_eq!(cache.instances.len(), 1);
+	}
+
+	#[test]
+	fn invalidate_runtime_removes_all_heap_pages_variants() {
+		let host_functions = sp_io::SubstrateHostFunctions::host_functions();
+		let mut cache = RuntimesCache::new();
+
+		let mut code_hash = Vec::new();
+		for heap_pages in &[8, 16] {
+			let mut ext = ext_with_heap_pages(*heap_pages);
+			let mut ext = ext.ext();
+			code_hash = cache.fetch_runtime(
+				&mut ext,
+				WasmExecutionMethod::Interpreted,
+				1024,
+				None,
+				SandboxBackend::Wasmi,
+				&host_functions,
+			).expect("creates runtime").2;
+		}
+		assert_eq!(cache.instances.len(), 2);
+
+		cache.invalidate_runtime(WasmExecutionMethod::Interpreted, code_hash);
+		assert!(cache.instances.is_empty());
+	}
 
 	#[test]
 	fn host_functions_are_equal() {
@@ -21,9 +21,9 @@ use sc_executor_common::{
 	sandbox,
 	allocator,
 };
-use std::{str, mem, cell::RefCell};
+use std::{str, mem, cell::RefCell, ops::Range};
 use wasmi::{
-	Module, ModuleInstance, MemoryInstance, MemoryRef, TableRef, ImportsBuilder, ModuleRef,
+	Module, ModuleInstance, MemoryRef, TableRef, ImportsBuilder, ModuleRef,
 	memory_units::Pages, RuntimeValue::{I32, I64, self},
 };
 use codec::{Encode, Decode};
@@ -54,9 +54,10 @@ impl<'a> FunctionExecutor<'a> {
 		host_functions: &'a [&'static dyn Function],
 		allow_missing_imports: bool,
 		missing_functions: &'a [String],
+		sandbox_backend: sandbox::SandboxBackend,
 	) -> Result<Self, Error> {
 		Ok(FunctionExecutor {
-			sandbox_store: sandbox::Store::new(),
+			sandbox_store: sandbox::Store::new(sandbox_backend),
 			heap: allocator::FreeingBumpHeapAllocator::new(heap_base),
 			memory: m,
 			table: t,
@@ -159,17 +160,18 @@ impl<'a> Sandbox for FunctionExecutor<'a> {
 		buf_len: WordSize,
 	) -> WResult<u32> {
 		let sandboxed_memory = self.sandbox_store.memory(memory_id).map_err(|e| e.to_string())?;
+		let supervisor_memory = &self.memory;
 
-		match MemoryInstance::transfer(
-			&sandboxed_memory,
-			offset as usize,
-			&self.memory,
-			buf_ptr.into(),
-			buf_len as usize,
-		) {
-			Ok(()) => Ok(sandbox_primitives::ERR_OK),
-			Err(_) => Ok(sandbox_primitives::ERR_OUT_OF_BOUNDS),
-		}
+		sandboxed_memory.with_direct_access(|sandboxed_memory| {
+			let data = match checked_range(offset as usize, buf_len as usize, sandboxed_memory.len()) {
+				Some(range) => &sandboxed_memory[range],
+				None => return Ok(sandbox_primitives::ERR_OUT_OF_BOUNDS),
+			};
+			match supervisor_memory.set(buf_ptr.into(), data) {
+				Ok(()) => Ok(sandbox_primitives::ERR_OK),
+				Err(_) => Ok(sandbox_primitives::ERR_OUT_OF_BOUNDS),
+			}
+		})
 	}
 
 	fn memory_set(
@@ -181,16 +183,19 @@ impl<'a> Sandbox for FunctionExecutor<'a> {
 	) -> WResult<u32> {
 		let sandboxed_memory = self.sandbox_store.memory(memory_id).map_err(|e| e.to_string())?;
 
-		match MemoryInstance::transfer(
-			&self.memory,
-			val_ptr.into(),
-			&sandboxed_memory,
-			offset as usize,
-			val_len as usize,
-		) {
-			Ok(()) => Ok(sandbox_primitives::ERR_OK),
-			Err(_) => Ok(sandbox_primitives::ERR_OUT_OF_BOUNDS),
-		}
+		let data = match self.memory.get(val_ptr.into(), val_len as usize) {
+			Ok(data) => data,
+			Err(_) => return Ok(sandbox_primitives::ERR_OUT_OF_BOUNDS),
+		};
+		sandboxed_memory.with_direct_access_mut(|sandboxed_memory| {
+			match checked_range(offset as usize, data.len(), sandboxed_memory.len()) {
+				Some(range) => {
+					sandboxed_memory[range].copy_from_slice(&data);
+					Ok(sandbox_primitives::ERR_OK)
+				}
+				None => Ok(sandbox_primitives::ERR_OUT_OF_BOUNDS),
+			}
+		})
 	}
 
 	fn memory_teardown(&mut self, memory_id: MemoryId) -> WResult<()> {
@@ -387,6 +392,7 @@ fn call_in_wasm_module(
 	host_functions: &[&'static dyn Function],
 	allow_missing_imports: bool,
 	missing_functions: &Vec<String>,
+	sandbox_backend: sandbox::SandboxBackend,
 ) -> Result<Vec<u8>, Error> {
 	// extract a reference to a linear memory, optional reference to a table
 	// and then initialize FunctionExecutor.
@@ -403,6 +409,7 @@ fn call_in_wasm_module(
 		host_functions,
 		allow_missing_imports,
 		missing_functions,
+		sandbox_backend,
 	)?;
 
 	// Write the call data
@@ -582,6 +589,8 @@ pub struct WasmiRuntime {
 	allow_missing_imports: bool,
 	/// List of missing functions detected during function resolution
 	missing_functions: Vec<String>,
+	/// The backend used to execute sandboxed guest modules.
+	sandbox_backend: sandbox::SandboxBackend,
 }
 
 impl WasmRuntime for WasmiRuntime {
@@ -613,6 +622,7 @@ impl WasmRuntime for WasmiRuntime {
 			&self.host_functions,
 			self.allow_missing_imports,
 			&self.missing_functions,
+			self.sandbox_backend,
 		)
 	}
 }
@@ -622,6 +632,7 @@ pub fn create_instance(
 	heap_pages: u64,
 	host_functions: Vec<&'static dyn Function>,
 	allow_missing_imports: bool,
+	sandbox_backend: sandbox::SandboxBackend,
 ) -> Result<WasmiRuntime, WasmError> {
 	let module = Module::from_buffer(&code).map_err(|_| WasmError::InvalidModule)?;
 
@@ -654,9 +665,21 @@ pub fn create_instance(
 		host_functions,
 		allow_missing_imports,
 		missing_functions,
+		sandbox_backend,
 	})
 }
 
+/// Construct a range from an offset to a data length after the offset.
+/// Returns None if the end of the range would exceed some maximum offset.
+fn checked_range(offset: usize, len: usize, max: usize) -> Option<Range<usize>> {
+	let end = offset.checked_add(len)?;
+	if end <= max {
+		Some(offset..end)
+	} else {
+		None
+	}
+}
+
 /// Extract the data segments from the given wasm code.
 ///
 /// Returns `Err` if the given wasm code cannot be deserialized.
@@ -54,9 +54,9 @@ pub struct FunctionExecutorState {
 
 impl FunctionExecutorState {
 	/// Constructs a new `FunctionExecutorState`.
-	pub fn new(heap_base: u32) -> Self {
+	pub fn new(heap_base: u32, sandbox_backend: sandbox::SandboxBackend) -> Self {
 		FunctionExecutorState {
-			sandbox_store: sandbox::Store::new(),
+			sandbox_store: sandbox::Store::new(sandbox_backend),
 			heap: FreeingBumpHeapAllocator::new(heap_base),
 		}
 	}
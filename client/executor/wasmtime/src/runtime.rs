@@ -17,20 +17,26 @@
 //! Defines the compiled Wasm runtime that uses Wasmtime internally.
 
 use crate::function_executor::FunctionExecutorState;
-use crate::trampoline::{EnvState, make_trampoline};
+use crate::trampoline::{EnvState, stub_fn};
 use crate::util::{cranelift_ir_signature, read_memory_into, write_memory_from};
 
 use sc_executor_common::{
 	error::{Error, Result, WasmError},
+	sandbox::SandboxBackend,
+	trampoline::make_trampoline,
 	wasm_runtime::WasmRuntime,
 };
 use sp_wasm_interface::{Pointer, WordSize, Function};
 use sp_runtime_interface::unpack_ptr_and_len;
+use lazy_static::lazy_static;
 
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
 use std::rc::Rc;
+use std::sync::Mutex;
 
 use cranelift_codegen::ir;
 use cranelift_codegen::isa::TargetIsa;
@@ -52,6 +58,8 @@ pub struct WasmtimeRuntime {
 	heap_pages: u32,
 	/// The host functions registered for this instance.
 	host_functions: Vec<&'static dyn Function>,
+	/// The backend used to execute sandboxed guest modules.
+	sandbox_backend: SandboxBackend,
 }
 
 impl WasmRuntime for WasmtimeRuntime {
@@ -76,6 +84,7 @@ impl WasmRuntime for WasmtimeRuntime {
 			method,
 			data,
 			self.heap_pages,
+			self.sandbox_backend,
 		)
 	}
 }
@@ -86,6 +95,7 @@ pub fn create_instance(
 	code: &[u8],
 	heap_pages: u64,
 	host_functions: Vec<&'static dyn Function>,
+	sandbox_backend: SandboxBackend,
 ) -> std::result::Result<WasmtimeRuntime, WasmError> {
 	let (compiled_module, context) = create_compiled_unit(code, &host_functions)?;
 
@@ -112,9 +122,62 @@ pub fn create_instance(
 		max_heap_pages,
 		heap_pages,
 		host_functions,
+		sandbox_backend,
 	})
 }
 
+/// Enable wasmtime's on-disk cache of compiled modules, storing artifacts in `directory`.
+///
+/// The cache is process wide and can only be set up once, before the first module is compiled.
+/// Subsequent calls don't change it and return the result of the first one.
+pub fn enable_compilation_cache(directory: &Path) -> std::result::Result<(), WasmError> {
+	lazy_static! {
+		static ref INIT_RESULT: Mutex<Option<std::result::Result<(), String>>> = Mutex::new(None);
+	}
+
+	let mut init_result = INIT_RESULT.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+	init_result
+		.get_or_insert_with(|| init_compilation_cache(directory).map_err(|e| e.to_string()))
+		.clone()
+		.map_err(WasmError::Other)
+}
+
+fn init_compilation_cache(directory: &Path) -> std::result::Result<(), WasmError> {
+	fs::create_dir_all(directory)
+		.map_err(|e| WasmError::Other(format!("cannot create compilation cache directory: {}", e)))?;
+
+	let config_path = directory.join("cache-config.toml");
+	let config = format!(
+		"[cache]\nenabled = true\ndirectory = {}\n",
+		toml_string(&directory.to_string_lossy()),
+	);
+	fs::write(&config_path, config)
+		.map_err(|e| WasmError::Other(format!("cannot write compilation cache config: {}", e)))?;
+
+	let errors = wasmtime_environ::cache_init(true, Some(&config_path), None);
+	if errors.is_empty() {
+		Ok(())
+	} else {
+		Err(WasmError::Other(format!("cannot initialize compilation cache: {}", errors.join(", "))))
+	}
+}
+
+/// Formats `value` as a TOML basic string, escaping the characters TOML requires to be escaped.
+fn toml_string(value: &str) -> String {
+	let mut escaped = String::with_capacity(value.len() + 2);
+	escaped.push('"');
+	for c in value.chars() {
+		match c {
+			'"' => escaped.push_str("\\\""),
+			'\\' => escaped.push_str("\\\\"),
+			c if c.is_control() => escaped.push_str(&format!("\\u{:04X}", c as u32)),
+			c => escaped.push(c),
+		}
+	}
+	escaped.push('"');
+	escaped
+}
+
 fn create_compiled_unit(
 	code: &[u8],
 	host_functions: &[&'static dyn Function],
@@ -147,6 +210,7 @@ fn call_method(
 	method: &str,
 	data: &[u8],
 	heap_pages: u32,
+	sandbox_backend: SandboxBackend,
 ) -> Result<Vec<u8>> {
 	// Old exports get clobbered in `InstanceHandle::new` if we don't explicitly remove them first.
 	//
@@ -165,7 +229,7 @@ fn call_method(
 
 	// Initialize the function executor state.
 	let heap_base = get_heap_base(&instance)?;
-	let executor_state = FunctionExecutorState::new(heap_base);
+	let executor_state = FunctionExecutorState::new(heap_base, sandbox_backend);
 	reset_env_state_and_take_trap(context, Some(executor_state))?;
 
 	// Write the input data into guest memory.
@@ -227,6 +291,7 @@ fn instantiate_env_module(
 			&mut fn_builder_ctx,
 			func_id.index() as u32,
 			&sig,
+			stub_fn,
 		)?;
 		finished_functions.push(trampoline);
 	}
@@ -366,3 +431,15 @@ fn heap_pages_valid(heap_pages: u64, max_heap_pages: Option<u32>)
 	}
 	Some(heap_pages)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::toml_string;
+
+	#[test]
+	fn toml_strings_are_escaped() {
+		assert_eq!(toml_string("/tmp/cache"), r#""/tmp/cache""#);
+		assert_eq!(toml_string(r#"C:\a "b""#), r#""C:\\a \"b\"""#);
+		assert_eq!(toml_string("a\nb\u{7f}"), r#""a\u000Ab\u007F""#);
+	}
+}
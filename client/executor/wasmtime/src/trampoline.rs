@@ -14,35 +14,22 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-//! The trampoline is the dynamically generated entry point to a runtime host call.
+//! The host state and stub function behind the trampolines of the runtime's host calls.
 //!
-//! This code is based on and large parts are copied from wasmtime's
-//! wasmtime-api/src/trampoline/func.rs.
+//! The trampolines themselves are generated by [`sc_executor_common::trampoline`].
 
 use crate::function_executor::{FunctionExecutorState, FunctionExecutor};
-use sc_executor_common::error::{Error, WasmError};
+use sc_executor_common::error::Error;
 
-use cranelift_codegen::{Context, binemit, ir, isa};
-use cranelift_codegen::ir::{InstBuilder, StackSlotData, StackSlotKind, TrapCode};
-use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
-use cranelift_codegen::print_errors::pretty_error;
 use wasmtime_jit::{CodeMemory, Compiler};
-use wasmtime_environ::CompiledFunction;
-use wasmtime_runtime::{VMContext, VMFunctionBody};
+use wasmtime_runtime::VMContext;
 use sp_wasm_interface::{Function, Value, ValueType};
-use std::{cmp, panic::{self, AssertUnwindSafe}, ptr};
+use std::{panic::{self, AssertUnwindSafe}, ptr};
 
 const CALL_SUCCESS: u32 = 0;
 const CALL_FAILED_WITH_ERROR: u32 = 1;
 const CALL_WITH_BAD_HOST_STATE: u32 = 2;
 
-/// A code to trap with that indicates a host call error.
-const TRAP_USER_CODE: u16 = 0;
-
-/// The only Wasm types allowed in host function signatures (I32, I64, F32, F64) are all
-/// represented in at most 8 bytes.
-const MAX_WASM_TYPE_SIZE: usize = 8;
-
 /// The top-level host state of the "env" module. This state is used by the trampoline function to
 /// construct a `FunctionExecutor` which can execute the host call.
 pub struct EnvState {
@@ -82,7 +69,7 @@ impl EnvState {
 /// This is called by the dynamically generated trampoline taking the function index and reference
 /// to the call arguments on the stack as arguments. Returns zero on success and a non-zero value
 /// on failure.
-unsafe extern "C" fn stub_fn(vmctx: *mut VMContext, func_index: u32, values_vec: *mut i64) -> u32 {
+pub unsafe extern "C" fn stub_fn(vmctx: *mut VMContext, func_index: u32, values_vec: *mut i64) -> u32 {
 	if let Some(state) = (*vmctx).host_state().downcast_mut::<EnvState>() {
 			match stub_fn_inner(
 				vmctx,
@@ -158,190 +145,6 @@ unsafe fn stub_fn_inner(
 	}
 }
 
-/// Create a trampoline for invoking a host function.
-///
-/// The trampoline is a dynamically generated entry point to a runtime host call. The function is
-/// generated by manually constructing Cranelift IR and using the Cranelift compiler. The
-/// trampoline embeds the function index as a constant and delegates to a stub function in Rust,
-/// which takes the function index and a memory reference to the stack arguments and return value
-/// slots.
-///
-/// This code is of modified copy of wasmtime's wasmtime-api/src/trampoline/func.rs.
-pub fn make_trampoline(
-	isa: &dyn isa::TargetIsa,
-	code_memory: &mut CodeMemory,
-	fn_builder_ctx: &mut FunctionBuilderContext,
-	func_index: u32,
-	signature: &ir::Signature,
-) -> Result<*const VMFunctionBody, WasmError> {
-	// Mostly reverse copy of the similar method from wasmtime's
-	// wasmtime-jit/src/compiler.rs.
-	let pointer_type = isa.pointer_type();
-	let mut stub_sig = ir::Signature::new(isa.frontend_config().default_call_conv);
-
-	// Ensure that the first parameter of the generated function is the `VMContext` pointer.
-	assert_eq!(
-		signature.params[0],
-		ir::AbiParam::special(pointer_type, ir::ArgumentPurpose::VMContext)
-	);
-
-	// Add the `vmctx` parameter.
-	stub_sig.params.push(ir::AbiParam::special(
-		pointer_type,
-		ir::ArgumentPurpose::VMContext,
-	));
-
-	// Add the `func_index` parameter.
-	stub_sig.params.push(ir::AbiParam::new(ir::types::I32));
-
-	// Add the `values_vec` parameter.
-	stub_sig.params.push(ir::AbiParam::new(pointer_type));
-
-	// Add error/trap return.
-	stub_sig.returns.push(ir::AbiParam::new(ir::types::I32));
-
-	// Each parameter and return value gets a 64-bit (8-byte) wide slot on the stack, as that is
-	// large enough to fit all Wasm primitive types that can be used in host function signatures.
-	// The `VMContext` pointer, which is a parameter of the function signature, is excluded as it
-	// is passed directly to the stub function rather than being looked up on the caller stack from
-	// the `values_vec` pointer.
-	let values_vec_len = cmp::max(signature.params.len() - 1, signature.returns.len());
-	let values_vec_size = (MAX_WASM_TYPE_SIZE * values_vec_len) as u32;
-
-	let mut context = Context::new();
-	context.func =
-		ir::Function::with_name_signature(ir::ExternalName::user(0, 0), signature.clone());
-
-	let ss = context.func.create_stack_slot(StackSlotData::new(
-		StackSlotKind::ExplicitSlot,
-		values_vec_size,
-	));
-
-	{
-		let mut builder = FunctionBuilder::new(&mut context.func, fn_builder_ctx);
-		let block0 = builder.create_ebb();
-
-		builder.append_ebb_params_for_function_params(block0);
-		builder.switch_to_block(block0);
-		builder.seal_block(block0);
-
-		let values_vec_ptr_val = builder.ins().stack_addr(pointer_type, ss, 0);
-		let mflags = ir::MemFlags::trusted();
-		for i in 1..signature.params.len() {
-			let val = builder.func.dfg.ebb_params(block0)[i];
-			builder.ins().store(
-				mflags,
-				val,
-				values_vec_ptr_val,
-				((i - 1) * MAX_WASM_TYPE_SIZE) as i32,
-			);
-		}
-
-		let vmctx_ptr_val = builder.func.dfg.ebb_params(block0)[0];
-		let func_index_val = builder.ins().iconst(ir::types::I32, func_index as i64);
-
-		let callee_args = vec![vmctx_ptr_val, func_index_val, values_vec_ptr_val];
-
-		let new_sig = builder.import_signature(stub_sig.clone());
-
-		let callee_value = builder
-			.ins()
-			.iconst(pointer_type, stub_fn as *const VMFunctionBody as i64);
-		let call = builder
-			.ins()
-			.call_indirect(new_sig, callee_value, &callee_args);
-
-		let call_result = builder.func.dfg.inst_results(call)[0];
-		builder.ins().trapnz(call_result, TrapCode::User(TRAP_USER_CODE));
-
-		let mflags = ir::MemFlags::trusted();
-		let mut results = Vec::new();
-		for (i, r) in signature.returns.iter().enumerate() {
-			let load = builder.ins().load(
-				r.value_type,
-				mflags,
-				values_vec_ptr_val,
-				(i * MAX_WASM_TYPE_SIZE) as i32,
-			);
-			results.push(load);
-		}
-		builder.ins().return_(&results);
-		builder.finalize()
-	}
-
-	let mut code_buf: Vec<u8> = Vec::new();
-	let mut reloc_sink = RelocSink;
-	let mut trap_sink = binemit::NullTrapSink {};
-	let mut stackmap_sink = binemit::NullStackmapSink {};
-	context
-		.compile_and_emit(
-			isa,
-			&mut code_buf,
-			&mut reloc_sink,
-			&mut trap_sink,
-			&mut stackmap_sink,
-		)
-		.map_err(|e| {
-			WasmError::Instantiation(format!(
-				"failed to compile trampoline: {}",
-				pretty_error(&context.func, Some(isa), e)
-			))
-		})?;
-
-	let mut unwind_info = Vec::new();
-	context.emit_unwind_info(isa, &mut unwind_info);
-
-	let func_ref = code_memory
-		.allocate_for_function(&CompiledFunction {
-			body: code_buf,
-			jt_offsets: context.func.jt_offsets,
-			unwind_info,
-		})
-		.map_err(|e| WasmError::Instantiation(format!("failed to allocate code memory: {}", e)))?;
-
-	Ok(func_ref.as_ptr())
-}
-
-/// We don't expect trampoline compilation to produce any relocations, so
-/// this `RelocSink` just asserts that it doesn't recieve any.
-struct RelocSink;
-
-impl binemit::RelocSink for RelocSink {
-	fn reloc_ebb(
-		&mut self,
-		_offset: binemit::CodeOffset,
-		_reloc: binemit::Reloc,
-		_ebb_offset: binemit::CodeOffset,
-	) {
-		panic!("trampoline compilation should not produce ebb relocs");
-	}
-	fn reloc_external(
-		&mut self,
-		_offset: binemit::CodeOffset,
-		_reloc: binemit::Reloc,
-		_name: &ir::ExternalName,
-		_addend: binemit::Addend,
-	) {
-		panic!("trampoline compilation should not produce external symbol relocs");
-	}
-	fn reloc_constant(
-		&mut self,
-		_code_offset: binemit::CodeOffset,
-		_reloc: binemit::Reloc,
-		_constant_offset: ir::ConstantOffset,
-	) {
-		panic!("trampoline compilation should not produce constant relocs");
-	}
-	fn reloc_jt(
-		&mut self,
-		_offset: binemit::CodeOffset,
-		_reloc: binemit::Reloc,
-		_jt: ir::JumpTable,
-	) {
-		panic!("trampoline compilation should not produce jump table relocs");
-	}
-}
-
 unsafe fn write_value_to(p: *mut i64, val: Value) {
 	match val {
 		Value::I32(i) => ptr::write(p as *mut i32, i),
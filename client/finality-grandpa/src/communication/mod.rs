@@ -72,6 +72,9 @@ mod tests;
 
 pub use sp_finality_grandpa::GRANDPA_ENGINE_ID;
 
+/// Name of the notifications protocol used by GRANDPA.
+pub const GRANDPA_PROTOCOL_NAME: &str = "/paritytech/grandpa/1";
+
 // cost scalars for reporting peers.
 mod cost {
 	use sc_network::ReputationChange as Rep;
@@ -192,7 +195,13 @@ impl<B: BlockT, N: Network<B>> NetworkBridge<B, N> {
 		);
 
 		let validator = Arc::new(validator);
-		let gossip_engine = GossipEngine::new(service.clone(), executor, GRANDPA_ENGINE_ID, validator.clone());
+		let gossip_engine = GossipEngine::new(
+			service.clone(),
+			executor,
+			GRANDPA_ENGINE_ID,
+			GRANDPA_PROTOCOL_NAME,
+			validator.clone(),
+		);
 
 		{
 			// register all previous votes with the gossip service so that they're
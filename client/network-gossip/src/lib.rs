@@ -33,8 +33,11 @@
 //! - Implement the `Network` trait, representing the low-level networking primitives. It is
 //!   already implemented on `sc_network::NetworkService`.
 //! - Implement the `Validator` trait. See the section below.
-//! - Decide on a `ConsensusEngineId`. Each gossiping protocol should have a different one.
-//! - Build a `GossipEngine` using these three elements.
+//! - Decide on a `ConsensusEngineId` and on a protocol name, for example
+//!   `/paritytech/grandpa/1`. Each gossiping protocol should have a different one of each.
+//!   Messages are exchanged on a dedicated substream negotiated with the protocol name, or as
+//!   consensus messages on the legacy substream for peers that don't support it.
+//! - Build a `GossipEngine` using these four elements.
 //! - Use the methods of the `GossipEngine` in order to send out messages and receive incoming
 //!   messages.
 //!
@@ -61,7 +64,7 @@ pub use self::validator::{DiscardAll, MessageIntent, Validator, ValidatorContext
 use futures::prelude::*;
 use sc_network::{specialization::NetworkSpecialization, Event, ExHashT, NetworkService, PeerId, ReputationChange};
 use sp_runtime::{traits::Block as BlockT, ConsensusEngineId};
-use std::sync::Arc;
+use std::{borrow::Cow, sync::Arc};
 
 mod bridge;
 mod state_machine;
@@ -86,7 +89,8 @@ pub trait Network<B: BlockT> {
 	/// See the documentation of [`NetworkService:register_notifications_protocol`] for more information.
 	fn register_notifications_protocol(
 		&self,
-		engine_id: ConsensusEngineId
+		engine_id: ConsensusEngineId,
+		protocol_name: Cow<'static, str>,
 	);
 
 	/// Notify everyone we're connected to that we have the given block.
@@ -116,8 +120,9 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Network<B> for Arc<Netw
 	fn register_notifications_protocol(
 		&self,
 		engine_id: ConsensusEngineId,
+		protocol_name: Cow<'static, str>,
 	) {
-		NetworkService::register_notifications_protocol(self, engine_id)
+		NetworkService::register_notifications_protocol(self, engine_id, protocol_name)
 	}
 
 	fn announce(&self, block: B::Hash, associated_data: Vec<u8>) {
@@ -19,7 +19,9 @@ use crate::{
 	Event, protocol::event::DhtEvent
 };
 use crate::{ExHashT, specialization::NetworkSpecialization};
-use crate::protocol::{CustomMessageOutcome, Protocol};
+use crate::notifications::{self, NotificationsBehaviour};
+use crate::protocol::{CustomMessageOutcome, NotificationsTransport, Protocol};
+use crate::request_responses::{self, RequestFailure, RequestId, RequestResponsesBehaviour};
 use libp2p::NetworkBehaviour;
 use libp2p::core::{Multiaddr, PeerId, PublicKey};
 use libp2p::kad::record;
@@ -27,8 +29,8 @@ use libp2p::swarm::{NetworkBehaviourAction, NetworkBehaviourEventProcess};
 use libp2p::core::{nodes::Substream, muxing::StreamMuxerBox};
 use log::{debug, warn};
 use sp_consensus::{BlockOrigin, import_queue::{IncomingBlock, Origin}};
-use sp_runtime::{traits::{Block as BlockT, NumberFor}, Justification};
-use std::{iter, task::Context, task::Poll};
+use sp_runtime::{traits::{Block as BlockT, NumberFor}, ConsensusEngineId, Justification};
+use std::{borrow::Cow, collections::HashMap, iter, task::Context, task::Poll};
 use void;
 
 /// General behaviour of the network. Combines all protocols together.
@@ -42,10 +44,18 @@ pub struct Behaviour<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> {
 	debug_info: debug_info::DebugInfoBehaviour<Substream<StreamMuxerBox>>,
 	/// Discovers nodes of the network.
 	discovery: DiscoveryBehaviour<Substream<StreamMuxerBox>>,
+	/// Block and light client requests, with one substream per request.
+	request_responses: RequestResponsesBehaviour<Substream<StreamMuxerBox>>,
+	/// Notifications protocols, with their own substreams.
+	notifications: NotificationsBehaviour<Substream<StreamMuxerBox>>,
 
 	/// Queue of events to produce for the outside.
 	#[behaviour(ignore)]
 	events: Vec<BehaviourOut<B>>,
+	/// Requests sent on the request-response protocols, kept to be sent again on the legacy
+	/// substream if the remote doesn't support these protocols.
+	#[behaviour(ignore)]
+	pending_requests: HashMap<RequestId, Vec<u8>>,
 }
 
 /// Event generated by `Behaviour`.
@@ -66,6 +76,9 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Behaviour<B, S, H> {
 		enable_mdns: bool,
 		allow_private_ipv4: bool,
 	) -> Self {
+		let request_responses =
+			RequestResponsesBehaviour::new(substrate.request_response_protocols());
+		let notifications = NotificationsBehaviour::new(substrate.notifications_handshake());
 		Behaviour {
 			substrate,
 			debug_info: debug_info::DebugInfoBehaviour::new(user_agent, local_public_key.clone()),
@@ -75,7 +88,10 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Behaviour<B, S, H> {
 				enable_mdns,
 				allow_private_ipv4
 			).await,
+			request_responses,
+			notifications,
 			events: Vec::new(),
+			pending_requests: HashMap::new(),
 		}
 	}
 
@@ -117,6 +133,43 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Behaviour<B, S, H> {
 	pub fn put_value(&mut self, key: record::Key, value: Vec<u8>) {
 		self.discovery.put_value(key, value);
 	}
+
+	/// Registers a new notifications protocol with the given name.
+	///
+	/// Returns the events to report for the peers we're already connected to.
+	pub fn register_notifications_protocol(
+		&mut self,
+		engine_id: ConsensusEngineId,
+		protocol_name: Cow<'static, str>,
+	) -> Vec<Event> {
+		let events = self.substrate.register_notifications_protocol(engine_id);
+		self.notifications.register_protocol(engine_id, protocol_name);
+		events
+	}
+
+	/// Sends a notification to a peer, on the substream of its protocol if it is open and on the
+	/// legacy substream if the peer doesn't support it.
+	///
+	/// Does nothing if the protocol hasn't been reported as open with that peer yet.
+	pub fn write_notification(
+		&mut self,
+		target: PeerId,
+		engine_id: ConsensusEngineId,
+		message: Vec<u8>,
+	) {
+		match self.substrate.notifications_transport(&target, engine_id) {
+			Some(NotificationsTransport::Substream) =>
+				self.notifications.write_notification(&target, engine_id, message),
+			Some(NotificationsTransport::Legacy) =>
+				self.substrate.write_notification(target, engine_id, message),
+			None => debug!(
+				target: "sub-libp2p",
+				"Dropping notification to {}: protocol {:?} isn't open",
+				target,
+				engine_id,
+			),
+		}
+	}
 }
 
 impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> NetworkBehaviourEventProcess<void::Void> for
@@ -136,6 +189,8 @@ Behaviour<B, S, H> {
 				self.events.push(BehaviourOut::JustificationImport(origin, hash, nb, justification)),
 			CustomMessageOutcome::FinalityProofImport(origin, hash, nb, proof) =>
 				self.events.push(BehaviourOut::FinalityProofImport(origin, hash, nb, proof)),
+			CustomMessageOutcome::OpenNotificationsSubstreams { remote } =>
+				self.notifications.open(&remote),
 			CustomMessageOutcome::NotificationStreamOpened { remote, protocols, roles } =>
 				for engine_id in protocols {
 					self.events.push(BehaviourOut::Event(Event::NotificationStreamOpened {
@@ -144,13 +199,15 @@ Behaviour<B, S, H> {
 						roles,
 					}));
 				},
-			CustomMessageOutcome::NotificationsStreamClosed { remote, protocols } =>
+			CustomMessageOutcome::NotificationsStreamClosed { remote, protocols } => {
+				self.notifications.close(&remote);
 				for engine_id in protocols {
 					self.events.push(BehaviourOut::Event(Event::NotificationsStreamClosed {
 						remote: remote.clone(),
 						engine_id,
 					}));
-				},
+				}
+			},
 			CustomMessageOutcome::NotificationsReceived { remote, messages } => {
 				let ev = Event::NotificationsReceived { remote, messages };
 				self.events.push(BehaviourOut::Event(ev));
@@ -210,8 +267,66 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> NetworkBehaviourEventPr
 	}
 }
 
+impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT>
+	NetworkBehaviourEventProcess<request_responses::Event> for Behaviour<B, S, H> {
+	fn inject_event(&mut self, event: request_responses::Event) {
+		match event {
+			request_responses::Event::InboundRequest {
+				peer, protocol, payload, pending_response,
+			} => {
+				if let Some(response) = self.substrate.on_request(peer, &protocol, &payload) {
+					// The request may have timed out in the meantime.
+					let _ = pending_response.send(response);
+				}
+			},
+			request_responses::Event::RequestFinished { peer, request_id, result } => {
+				let request = self.pending_requests.remove(&request_id);
+				match result {
+					Ok(response) => {
+						let outcome = self.substrate.on_request_response(peer, response);
+						NetworkBehaviourEventProcess::<CustomMessageOutcome<B>>::inject_event(
+							self,
+							outcome,
+						);
+					},
+					Err(RequestFailure::UnsupportedProtocol) => if let Some(request) = request {
+						self.substrate.on_request_unsupported(peer, request);
+					},
+					Err(err) => if let Some(request) = request {
+						self.substrate.on_request_failed(peer, request, err);
+					},
+				}
+			},
+		}
+	}
+}
+
+impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT>
+	NetworkBehaviourEventProcess<notifications::Event> for Behaviour<B, S, H> {
+	fn inject_event(&mut self, event: notifications::Event) {
+		let outcome = match event {
+			notifications::Event::Opened { peer, engine_id, handshake } =>
+				self.substrate.on_notifications_substream_open(peer, engine_id, &handshake),
+			notifications::Event::OpenFailed { peer, engine_id } =>
+				self.substrate.on_notifications_substream_unsupported(peer, engine_id),
+			notifications::Event::Closed { peer, engine_id } => {
+				self.substrate.on_notifications_substream_closed(&peer, engine_id);
+				CustomMessageOutcome::None
+			},
+			notifications::Event::Notification { peer, engine_id, message } =>
+				self.substrate.on_notification(peer, engine_id, message),
+		};
+		NetworkBehaviourEventProcess::<CustomMessageOutcome<B>>::inject_event(self, outcome);
+	}
+}
+
 impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Behaviour<B, S, H> {
 	fn poll<TEv>(&mut self, _: &mut Context) -> Poll<NetworkBehaviourAction<TEv, BehaviourOut<B>>> {
+		for (peer, protocol, request) in self.substrate.take_outbound_requests() {
+			let request_id = self.request_responses.send_request(&peer, &protocol, request.clone());
+			self.pending_requests.insert(request_id, request);
+		}
+
 		if !self.events.is_empty() {
 			return Poll::Ready(NetworkBehaviourAction::GenerateEvent(self.events.remove(0)))
 		}
@@ -109,6 +109,17 @@
 //! protocol negotiated is based on the *protocol ID* passed as part of the network configuration.
 //! This protocol ID should be unique for each chain and prevents nodes from different chains from
 //! connecting to each other. More information below.
+//! - We open an ephemeral substream for each block or finality proof request, whose protocol name
+//! is `/substrate/<protocol-id>/sync/1`, and for each light client request, whose protocol name
+//! is `/substrate/<protocol-id>/light/1`. The request and then the response, encoded as the
+//! corresponding messages of the Substrate substream, are sent on it. Nodes that don't support
+//! these protocols receive the requests on the Substrate substream instead.
+//! - For each notifications protocol registered with
+//! [`NetworkService::register_notifications_protocol`], for example the GRANDPA gossiping, each
+//! side opens a substream whose protocol name is the name of the protocol, and sends its
+//! notifications on it after a handshake containing its roles. Each protocol has its own queue of
+//! notifications, so that a slow protocol doesn't slow down the others. Nodes that don't support
+//! a protocol receive its notifications as consensus messages on the Substrate substream instead.
 //!
 //! ## The Substrate substream
 //!
@@ -139,7 +150,7 @@
 //! - Syncing. Blocks are announced and requested from other nodes.
 //! - Light-client requests. When a light client requires information, a random node we have a
 //! substream open with is chosen, and the information is requested from it.
-//! - Gossiping. Used for example by grandpa.
+//! - Gossiping with the nodes that don't support the notifications protocols.
 //! - Network specialization. The network protocol can be specialized through a template parameter
 //! of the network service. This specialization is free to send and receive messages with the
 //! remote. This is meant to be used by the chain that is being built on top of Substrate
@@ -172,8 +183,10 @@ mod behaviour;
 mod chain;
 mod debug_info;
 mod discovery;
+mod notifications;
 mod on_demand_layer;
 mod protocol;
+mod request_responses;
 mod service;
 mod transport;
 mod utils;
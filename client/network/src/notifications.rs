@@ -0,0 +1,806 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Notifications protocols.
+//!
+//! A notifications protocol lets the two sides of a connection send each other messages, called
+//! notifications, that are never answered. Each protocol has its own name, for example
+//! `/paritytech/grandpa/1`, and is identified in the API by the engine id of the component using
+//! it. A protocol uses two substreams per connection, one opened by each side, on which the opener
+//! sends its notifications. The first message of each substream is a handshake, which is the same
+//! for all the protocols. Messages are prefixed with their length as an unsigned varint.
+//!
+//! A protocol is open with a peer once both substreams are established. If that takes too long,
+//! the protocol is reported as failed to open, so that its notifications are sent on the legacy
+//! substream until both substreams are established. Each protocol has its own bounded queue of
+//! notifications waiting to be sent, and notifications are dropped when that queue is full, so
+//! that a slow protocol never delays the others.
+
+use bytes::Bytes;
+use futures::{future::BoxFuture, prelude::*};
+use futures_codec::Framed;
+use futures_timer::Delay;
+use libp2p::core::{ConnectedPoint, Multiaddr, PeerId};
+use libp2p::core::upgrade::{InboundUpgrade, Negotiated, OutboundUpgrade, ProtocolName, UpgradeInfo};
+use libp2p::swarm::{
+	KeepAlive, NetworkBehaviour, NetworkBehaviourAction, PollParameters, ProtocolsHandler,
+	ProtocolsHandlerEvent, ProtocolsHandlerUpgrErr, SubstreamProtocol,
+};
+use log::debug;
+use parking_lot::RwLock;
+use sp_runtime::ConsensusEngineId;
+use std::{borrow::Cow, collections::{HashSet, VecDeque}, io, iter, marker::PhantomData};
+use std::{pin::Pin, sync::Arc, time::Duration};
+use std::task::{Context, Poll, Waker};
+use unsigned_varint::codec::UviBytes;
+use wasm_timer::Instant;
+
+/// Maximal number of notifications of a protocol waiting to be sent to a peer. Further
+/// notifications are dropped.
+const MAX_PENDING_NOTIFICATIONS: usize = 1024;
+
+/// Maximal size, in bytes, of a handshake or of a notification.
+const MAX_NOTIFICATION_SIZE: usize = 16 * 1024 * 1024;
+
+/// Duration for which a new connection is kept alive, so that the protocols have time to be
+/// opened.
+const INITIAL_KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+/// Duration after which a protocol whose substreams aren't both established is reported as
+/// failed to open.
+const OPEN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Registered protocols, shared between the behaviour and the handlers so that the protocols
+/// registered after a connection has been established can still be used on it. Handlers refer to
+/// the protocols by their index, which never changes as protocols can't be unregistered.
+type Protocols = Arc<RwLock<Vec<(ConsensusEngineId, Cow<'static, str>)>>>;
+
+/// Event generated by the [`NotificationsBehaviour`].
+#[derive(Debug)]
+pub enum Event {
+	/// Both substreams of a protocol are open with a peer.
+	Opened {
+		/// Peer the protocol is open with.
+		peer: PeerId,
+		/// The protocol.
+		engine_id: ConsensusEngineId,
+		/// Handshake sent by the peer.
+		handshake: Vec<u8>,
+	},
+
+	/// The substreams of a protocol with a peer have been closed by the peer or because of an
+	/// error. Always matches a previous `Opened` event.
+	Closed {
+		/// Peer the protocol was open with.
+		peer: PeerId,
+		/// The protocol.
+		engine_id: ConsensusEngineId,
+	},
+
+	/// We failed to open the substreams of a protocol with a peer in time, most likely because
+	/// the peer doesn't support the protocol. The protocol can still be opened later.
+	OpenFailed {
+		/// The peer.
+		peer: PeerId,
+		/// The protocol.
+		engine_id: ConsensusEngineId,
+	},
+
+	/// A peer sent a notification.
+	Notification {
+		/// Peer which sent the notification.
+		peer: PeerId,
+		/// The protocol of the notification.
+		engine_id: ConsensusEngineId,
+		/// The notification.
+		message: Bytes,
+	},
+}
+
+/// Implementation of `NetworkBehaviour` for the notifications protocols.
+///
+/// Substreams are only opened with the peers passed to [`NotificationsBehaviour::open`], but the
+/// substreams opened by any peer are accepted.
+pub struct NotificationsBehaviour<TSubstream> {
+	/// The registered protocols.
+	protocols: Protocols,
+	/// Handshake sent on all our substreams.
+	handshake: Bytes,
+	/// Peers with which the protocols must be opened.
+	open_peers: HashSet<PeerId>,
+	/// Actions to return from `poll`.
+	actions: VecDeque<NetworkBehaviourAction<HandlerIn, Event>>,
+	/// Waker of the task polling the behaviour, woken when an action is queued.
+	waker: Option<Waker>,
+	/// Marker to pin the generics.
+	marker: PhantomData<TSubstream>,
+}
+
+impl<TSubstream> NotificationsBehaviour<TSubstream> {
+	/// Builds a new `NotificationsBehaviour` sending the given handshake on all its substreams.
+	pub fn new(handshake: Vec<u8>) -> Self {
+		NotificationsBehaviour {
+			protocols: Arc::new(RwLock::new(Vec::new())),
+			handshake: Bytes::from(handshake),
+			open_peers: HashSet::new(),
+			actions: VecDeque::new(),
+			waker: None,
+			marker: PhantomData,
+		}
+	}
+
+	/// Registers a new protocol. Does nothing if a protocol with the same engine id has already
+	/// been registered.
+	///
+	/// The protocol is also opened with the peers passed to `open` earlier.
+	pub fn register_protocol(&mut self, engine_id: ConsensusEngineId, name: Cow<'static, str>) {
+		{
+			let mut protocols = self.protocols.write();
+			if protocols.iter().any(|(id, _)| *id == engine_id) {
+				return;
+			}
+			protocols.push((engine_id, name));
+		}
+
+		let peers = self.open_peers.iter().cloned().collect::<Vec<_>>();
+		for peer in peers {
+			self.send_to_handler(peer, HandlerIn::Open);
+		}
+	}
+
+	/// Opens all the protocols with the given peer we are connected to.
+	pub fn open(&mut self, peer: &PeerId) {
+		self.open_peers.insert(peer.clone());
+		self.send_to_handler(peer.clone(), HandlerIn::Open);
+	}
+
+	/// Closes all the protocols with the given peer. No `Closed` event is generated.
+	pub fn close(&mut self, peer: &PeerId) {
+		if self.open_peers.remove(peer) {
+			self.send_to_handler(peer.clone(), HandlerIn::Close);
+		}
+	}
+
+	/// Sends a notification to a peer on the substream of the given protocol.
+	///
+	/// Does nothing if the protocol isn't open with that peer, or if too many notifications are
+	/// already waiting to be sent on it.
+	pub fn write_notification(
+		&mut self,
+		peer: &PeerId,
+		engine_id: ConsensusEngineId,
+		message: Vec<u8>,
+	) {
+		let index = self.protocols.read().iter().position(|(id, _)| *id == engine_id);
+		if let Some(index) = index {
+			let message = Bytes::from(message);
+			self.send_to_handler(peer.clone(), HandlerIn::Send { index, message });
+		}
+	}
+
+	/// Queues an event for the handler of the connection with the given peer.
+	fn send_to_handler(&mut self, peer_id: PeerId, event: HandlerIn) {
+		self.actions.push_back(NetworkBehaviourAction::SendEvent { peer_id, event });
+		if let Some(waker) = self.waker.take() {
+			waker.wake();
+		}
+	}
+}
+
+impl<TSubstream> NetworkBehaviour for NotificationsBehaviour<TSubstream>
+where
+	TSubstream: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+	type ProtocolsHandler = NotificationsHandler<TSubstream>;
+	type OutEvent = Event;
+
+	fn new_handler(&mut self) -> Self::ProtocolsHandler {
+		NotificationsHandler::new(self.protocols.clone(), self.handshake.clone())
+	}
+
+	fn addresses_of_peer(&mut self, _: &PeerId) -> Vec<Multiaddr> {
+		Vec::new()
+	}
+
+	fn inject_connected(&mut self, _: PeerId, _: ConnectedPoint) {}
+
+	fn inject_disconnected(&mut self, peer_id: &PeerId, _: ConnectedPoint) {
+		self.open_peers.remove(peer_id);
+	}
+
+	fn inject_node_event(&mut self, peer: PeerId, event: HandlerOut) {
+		let index = match event {
+			HandlerOut::Opened { index, .. } | HandlerOut::Closed { index } |
+			HandlerOut::OpenFailed { index } | HandlerOut::Notification { index, .. } => index,
+		};
+		let engine_id = match self.protocols.read().get(index) {
+			Some((engine_id, _)) => *engine_id,
+			None => return,
+		};
+
+		let event = match event {
+			HandlerOut::Opened { handshake, .. } => Event::Opened { peer, engine_id, handshake },
+			HandlerOut::Closed { .. } => Event::Closed { peer, engine_id },
+			HandlerOut::OpenFailed { .. } => Event::OpenFailed { peer, engine_id },
+			HandlerOut::Notification { message, .. } =>
+				Event::Notification { peer, engine_id, message },
+		};
+		self.actions.push_back(NetworkBehaviourAction::GenerateEvent(event));
+	}
+
+	fn poll(
+		&mut self,
+		cx: &mut Context,
+		_: &mut impl PollParameters,
+	) -> Poll<NetworkBehaviourAction<HandlerIn, Event>> {
+		if let Some(action) = self.actions.pop_front() {
+			return Poll::Ready(action);
+		}
+
+		self.waker = Some(cx.waker().clone());
+		Poll::Pending
+	}
+}
+
+/// Event sent by the behaviour to a [`NotificationsHandler`].
+#[derive(Debug)]
+pub enum HandlerIn {
+	/// Open all the registered protocols, including the ones registered later.
+	Open,
+	/// Close the substreams of all the protocols.
+	Close,
+	/// Send a notification on the substream of the protocol with the given index.
+	Send { index: usize, message: Bytes },
+}
+
+/// Event generated by a [`NotificationsHandler`]. Protocols are designated by their index.
+#[derive(Debug)]
+pub enum HandlerOut {
+	/// Both substreams of a protocol are open.
+	Opened { index: usize, handshake: Vec<u8> },
+	/// The substreams of an open protocol have been closed.
+	Closed { index: usize },
+	/// Failed to open the substreams of a protocol in time.
+	OpenFailed { index: usize },
+	/// Received a notification.
+	Notification { index: usize, message: Bytes },
+}
+
+/// Substream of a notifications protocol, after the handshake.
+type NotificationsSubstream<TSubstream> = Framed<Negotiated<TSubstream>, UviBytes<Bytes>>;
+
+/// Implementation of `ProtocolsHandler` for the notifications protocols.
+pub struct NotificationsHandler<TSubstream> {
+	/// The registered protocols.
+	protocols: Protocols,
+	/// Handshake sent on all our substreams.
+	handshake: Bytes,
+	/// Whether the behaviour has asked for the protocols to be opened.
+	enabled: bool,
+	/// State of each protocol, by index.
+	states: Vec<ProtocolState<TSubstream>>,
+	/// Events to report to the behaviour.
+	events: VecDeque<HandlerOut>,
+	/// Time the handler was created.
+	created: Instant,
+}
+
+/// State of a protocol of a [`NotificationsHandler`].
+struct ProtocolState<TSubstream> {
+	/// Substream opened by the remote.
+	inbound: Option<NotificationsSubstream<TSubstream>>,
+	/// Handshake received on the inbound substream, until `Opened` is reported.
+	handshake: Option<Vec<u8>>,
+	/// Substream we opened.
+	outbound: Option<NotificationsSubstream<TSubstream>>,
+	/// Whether we have tried to open our substream since the protocols have been enabled.
+	outbound_requested: bool,
+	/// Fires if the protocol isn't open in time, until it is open or `OpenFailed` is reported.
+	open_timeout: Option<Delay>,
+	/// Whether `Opened` has been reported and not followed by `Closed`.
+	open: bool,
+	/// Notifications waiting to be sent on the outbound substream.
+	pending: VecDeque<Bytes>,
+	/// Whether the outbound substream needs to be flushed.
+	needs_flush: bool,
+}
+
+impl<TSubstream> Default for ProtocolState<TSubstream> {
+	fn default() -> Self {
+		ProtocolState {
+			inbound: None,
+			handshake: None,
+			outbound: None,
+			outbound_requested: false,
+			open_timeout: None,
+			open: false,
+			pending: VecDeque::new(),
+			needs_flush: false,
+		}
+	}
+}
+
+impl<TSubstream> ProtocolState<TSubstream> {
+	/// Drops the substreams and returns whether `Closed` must be reported. Our substream is opened
+	/// again if the protocols are still enabled.
+	fn close(&mut self) -> bool {
+		self.inbound = None;
+		self.handshake = None;
+		self.outbound = None;
+		self.outbound_requested = false;
+		self.open_timeout = None;
+		self.pending.clear();
+		self.needs_flush = false;
+		let was_open = self.open;
+		self.open = false;
+		was_open
+	}
+}
+
+impl<TSubstream> NotificationsHandler<TSubstream> {
+	/// Builds a new handler.
+	fn new(protocols: Protocols, handshake: Bytes) -> Self {
+		NotificationsHandler {
+			protocols,
+			handshake,
+			enabled: false,
+			states: Vec::new(),
+			events: VecDeque::new(),
+			created: Instant::now(),
+		}
+	}
+
+	/// Returns the state of the protocol with the given index, creating it if necessary.
+	fn state_mut(&mut self, index: usize) -> &mut ProtocolState<TSubstream> {
+		while self.states.len() <= index {
+			self.states.push(ProtocolState::default());
+		}
+		&mut self.states[index]
+	}
+}
+
+impl<TSubstream> ProtocolsHandler for NotificationsHandler<TSubstream>
+where
+	TSubstream: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+	type InEvent = HandlerIn;
+	type OutEvent = HandlerOut;
+	type Error = void::Void;
+	type Substream = TSubstream;
+	type InboundProtocol = InboundNotificationsUpgrade;
+	type OutboundProtocol = OutboundNotificationsUpgrade;
+	type OutboundOpenInfo = usize;
+
+	fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol> {
+		let protocols = self.protocols.read().iter()
+			.enumerate()
+			.map(|(index, (_, name))| NotificationsProtocolName { index, name: name.clone() })
+			.collect();
+		SubstreamProtocol::new(InboundNotificationsUpgrade { protocols })
+	}
+
+	fn inject_fully_negotiated_inbound(&mut self, inbound: InboundNotifications<TSubstream>) {
+		let InboundNotifications { index, handshake, substream } = inbound;
+		let state = self.state_mut(index);
+		state.inbound = Some(substream);
+		state.handshake = Some(handshake);
+	}
+
+	fn inject_fully_negotiated_outbound(
+		&mut self,
+		substream: NotificationsSubstream<TSubstream>,
+		index: usize,
+	) {
+		if self.enabled {
+			self.state_mut(index).outbound = Some(substream);
+		}
+	}
+
+	fn inject_event(&mut self, event: HandlerIn) {
+		match event {
+			HandlerIn::Open => self.enabled = true,
+			HandlerIn::Close => {
+				self.enabled = false;
+				self.states.clear();
+			},
+			HandlerIn::Send { index, message } => match self.states.get_mut(index) {
+				Some(state) if state.outbound.is_some() => {
+					if state.pending.len() >= MAX_PENDING_NOTIFICATIONS {
+						debug!(
+							target: "sub-libp2p",
+							"Dropping notification: too many notifications are queued",
+						);
+					} else {
+						state.pending.push_back(message);
+					}
+				},
+				_ => {},
+			},
+		}
+	}
+
+	fn inject_dial_upgrade_error(
+		&mut self,
+		index: usize,
+		error: ProtocolsHandlerUpgrErr<io::Error>,
+	) {
+		debug!(target: "sub-libp2p", "Failed to open notifications substream: {:?}", error);
+		// `OpenFailed` may already have been reported because of the timeout.
+		if self.enabled && self.state_mut(index).open_timeout.take().is_some() {
+			self.events.push_back(HandlerOut::OpenFailed { index });
+		}
+	}
+
+	fn connection_keep_alive(&self) -> KeepAlive {
+		let has_substreams = self.states.iter()
+			.any(|state| state.inbound.is_some() || state.outbound.is_some());
+		if self.enabled || has_substreams {
+			KeepAlive::Yes
+		} else {
+			KeepAlive::Until(self.created + INITIAL_KEEP_ALIVE)
+		}
+	}
+
+	fn poll(
+		&mut self,
+		cx: &mut Context,
+	) -> Poll<
+		ProtocolsHandlerEvent<OutboundNotificationsUpgrade, usize, HandlerOut, Self::Error>
+	> {
+		if let Some(event) = self.events.pop_front() {
+			return Poll::Ready(ProtocolsHandlerEvent::Custom(event));
+		}
+
+		if self.enabled {
+			// Open the protocols registered since the last poll.
+			let num_protocols = self.protocols.read().len();
+			if num_protocols > 0 {
+				self.state_mut(num_protocols - 1);
+			}
+		}
+
+		for (index, state) in self.states.iter_mut().enumerate() {
+			if self.enabled && !state.outbound_requested {
+				state.outbound_requested = true;
+				state.open_timeout = Some(Delay::new(OPEN_TIMEOUT));
+				let upgrade = OutboundNotificationsUpgrade {
+					name: self.protocols.read()[index].1.clone(),
+					handshake: self.handshake.clone(),
+				};
+				return Poll::Ready(ProtocolsHandlerEvent::OutboundSubstreamRequest {
+					protocol: SubstreamProtocol::new(upgrade),
+					info: index,
+				});
+			}
+
+			if self.enabled && !state.open && state.outbound.is_some() {
+				if let Some(handshake) = state.handshake.take() {
+					state.open = true;
+					state.open_timeout = None;
+					let event = HandlerOut::Opened { index, handshake };
+					return Poll::Ready(ProtocolsHandlerEvent::Custom(event));
+				}
+			}
+
+			if let Some(timeout) = state.open_timeout.as_mut() {
+				if let Poll::Ready(_) = Pin::new(timeout).poll(cx) {
+					state.open_timeout = None;
+					debug!(target: "sub-libp2p", "Timeout while opening notifications substreams");
+					let event = HandlerOut::OpenFailed { index };
+					return Poll::Ready(ProtocolsHandlerEvent::Custom(event));
+				}
+			}
+
+			let mut failed = false;
+
+			if let Some(outbound) = state.outbound.as_mut() {
+				while !state.pending.is_empty() {
+					match Pin::new(&mut *outbound).poll_ready(cx) {
+						Poll::Ready(Ok(())) => {
+							let message = state.pending.pop_front()
+								.expect("Checked that the queue isn't empty; qed");
+							failed |= Pin::new(&mut *outbound).start_send(message).is_err();
+							state.needs_flush = true;
+						},
+						Poll::Ready(Err(_)) => failed = true,
+						Poll::Pending => break,
+					}
+					if failed {
+						break;
+					}
+				}
+				if state.needs_flush && !failed {
+					match Pin::new(&mut *outbound).poll_flush(cx) {
+						Poll::Ready(Ok(())) => state.needs_flush = false,
+						Poll::Ready(Err(_)) => failed = true,
+						Poll::Pending => {},
+					}
+				}
+			}
+
+			if let Some(inbound) = state.inbound.as_mut() {
+				match Pin::new(inbound).poll_next(cx) {
+					Poll::Ready(Some(Ok(message))) => {
+						let event = HandlerOut::Notification { index, message: message.freeze() };
+						return Poll::Ready(ProtocolsHandlerEvent::Custom(event));
+					},
+					Poll::Ready(Some(Err(_))) | Poll::Ready(None) => failed = true,
+					Poll::Pending => {},
+				}
+			}
+
+			if failed {
+				// Make sure that our substream is opened again.
+				cx.waker().wake_by_ref();
+				if state.close() {
+					return Poll::Ready(ProtocolsHandlerEvent::Custom(HandlerOut::Closed { index }));
+				}
+			}
+		}
+
+		Poll::Pending
+	}
+}
+
+/// Builds the codec of the substreams.
+fn codec() -> UviBytes<Bytes> {
+	let mut codec = UviBytes::default();
+	codec.set_max_len(MAX_NOTIFICATION_SIZE);
+	codec
+}
+
+/// Name of a notifications protocol, as negotiated on a substream, and its index.
+#[derive(Debug, Clone)]
+pub struct NotificationsProtocolName {
+	index: usize,
+	name: Cow<'static, str>,
+}
+
+impl ProtocolName for NotificationsProtocolName {
+	fn protocol_name(&self) -> &[u8] {
+		self.name.as_bytes()
+	}
+}
+
+/// An inbound substream, after the handshake has been received.
+pub struct InboundNotifications<TSubstream> {
+	/// Index of the protocol of the substream.
+	index: usize,
+	/// Handshake sent by the remote.
+	handshake: Vec<u8>,
+	/// The substream.
+	substream: NotificationsSubstream<TSubstream>,
+}
+
+/// Upgrade for inbound substreams, which accepts all the protocols and reads the handshake.
+#[derive(Debug, Clone)]
+pub struct InboundNotificationsUpgrade {
+	protocols: Vec<NotificationsProtocolName>,
+}
+
+impl UpgradeInfo for InboundNotificationsUpgrade {
+	type Info = NotificationsProtocolName;
+	type InfoIter = Vec<NotificationsProtocolName>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		self.protocols.clone()
+	}
+}
+
+impl<TSubstream> InboundUpgrade<TSubstream> for InboundNotificationsUpgrade
+where
+	TSubstream: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+	type Output = InboundNotifications<TSubstream>;
+	type Error = io::Error;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_inbound(
+		self,
+		substream: Negotiated<TSubstream>,
+		protocol: NotificationsProtocolName,
+	) -> Self::Future {
+		async move {
+			let mut substream = Framed::new(substream, codec());
+			let handshake = match substream.next().await {
+				Some(handshake) => handshake?.to_vec(),
+				None => return Err(io::ErrorKind::UnexpectedEof.into()),
+			};
+			Ok(InboundNotifications { index: protocol.index, handshake, substream })
+		}.boxed()
+	}
+}
+
+/// Upgrade for outbound substreams, which sends the handshake.
+#[derive(Debug)]
+pub struct OutboundNotificationsUpgrade {
+	name: Cow<'static, str>,
+	handshake: Bytes,
+}
+
+impl UpgradeInfo for OutboundNotificationsUpgrade {
+	type Info = NotificationsProtocolName;
+	type InfoIter = iter::Once<NotificationsProtocolName>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		iter::once(NotificationsProtocolName { index: 0, name: self.name.clone() })
+	}
+}
+
+impl<TSubstream> OutboundUpgrade<TSubstream> for OutboundNotificationsUpgrade
+where
+	TSubstream: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+	type Output = NotificationsSubstream<TSubstream>;
+	type Error = io::Error;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_outbound(
+		self,
+		substream: Negotiated<TSubstream>,
+		_: NotificationsProtocolName,
+	) -> Self::Future {
+		let handshake = self.handshake;
+		async move {
+			let mut substream = Framed::new(substream, codec());
+			substream.send(handshake).await?;
+			Ok(substream)
+		}.boxed()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::{executor::block_on, future, prelude::*};
+	use libp2p::core::{nodes::Substream, transport::{boxed::Boxed, MemoryTransport}, upgrade};
+	use libp2p::core::muxing::StreamMuxerBox;
+	use libp2p::swarm::Swarm;
+	use libp2p::{identity::Keypair, Multiaddr, PeerId, Transport};
+	use std::{borrow::Cow, io, task::Poll, time::Duration};
+	use super::{Event, NotificationsBehaviour};
+
+	type TestSwarm = Swarm<
+		Boxed<(PeerId, StreamMuxerBox), io::Error>,
+		NotificationsBehaviour<Substream<StreamMuxerBox>>,
+	>;
+
+	/// Builds a node with the given protocols and handshake, listening on a random memory address.
+	fn build_node(
+		protocols: &[(&'static str, [u8; 4])],
+		handshake: &[u8],
+	) -> (TestSwarm, Multiaddr) {
+		let keypair = Keypair::generate_ed25519();
+		let local_peer_id = keypair.public().into_peer_id();
+		let transport = MemoryTransport
+			.and_then(move |out, endpoint| {
+				let secio = libp2p::secio::SecioConfig::new(keypair);
+				upgrade::apply(out, secio, endpoint, upgrade::Version::V1)
+			})
+			.and_then(|(peer_id, stream), endpoint| {
+				let yamux = libp2p::yamux::Config::default();
+				upgrade::apply(stream, yamux, endpoint, upgrade::Version::V1)
+					.map_ok(|muxer| (peer_id, StreamMuxerBox::new(muxer)))
+			})
+			.timeout(Duration::from_secs(20))
+			.map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+			.boxed();
+
+		let mut behaviour = NotificationsBehaviour::new(handshake.to_vec());
+		for (name, engine_id) in protocols {
+			behaviour.register_protocol(*engine_id, Cow::Borrowed(name));
+		}
+		let mut swarm = Swarm::new(transport, behaviour, local_peer_id);
+		let addr: Multiaddr = format!("/memory/{}", rand::random::<u64>()).parse().unwrap();
+		Swarm::listen_on(&mut swarm, addr.clone()).unwrap();
+		(swarm, addr)
+	}
+
+	/// Connects two nodes, opens the protocols on the second side and, if `first_opens` is true,
+	/// on the first side, and returns the events generated by the second node once `until`
+	/// returns true for them.
+	fn run(
+		first_protocols: &[(&'static str, [u8; 4])],
+		second_protocols: &[(&'static str, [u8; 4])],
+		first_opens: bool,
+		until: fn(&[Event]) -> bool,
+	) -> Vec<Event> {
+		let (mut first, _) = build_node(first_protocols, b"first");
+		let (mut second, second_addr) = build_node(second_protocols, b"second");
+		let first_id = Swarm::local_peer_id(&first).clone();
+		let second_id = Swarm::local_peer_id(&second).clone();
+		Swarm::dial_addr(&mut first, second_addr).unwrap();
+
+		let mut first_open = !first_opens;
+		let mut second_open = false;
+		let mut events = Vec::new();
+		block_on(future::poll_fn(move |cx| {
+			// The first node sends a notification on each protocol it opens.
+			while let Poll::Ready(Some(event)) = first.poll_next_unpin(cx) {
+				if let Event::Opened { peer, engine_id, .. } = event {
+					first.write_notification(&peer, engine_id, b"hello".to_vec());
+				}
+			}
+			if !first_open && Swarm::connection_info(&mut first, &second_id).is_some() {
+				first.open(&second_id);
+				first_open = true;
+			}
+
+			loop {
+				match second.poll_next_unpin(cx) {
+					Poll::Ready(Some(event)) => {
+						events.push(event);
+						if until(&events) {
+							return Poll::Ready(std::mem::replace(&mut events, Vec::new()));
+						}
+					},
+					Poll::Ready(None) => panic!(),
+					Poll::Pending => break,
+				}
+			}
+			if !second_open && Swarm::connection_info(&mut second, &first_id).is_some() {
+				second.open(&first_id);
+				second_open = true;
+				cx.waker().wake_by_ref();
+			}
+			Poll::Pending
+		}))
+	}
+
+	#[test]
+	fn opens_protocols_and_receives_notifications() {
+		let protocols = [("/a", *b"aaaa"), ("/b", *b"bbbb")];
+		let events = run(&protocols, &protocols, true, |events| events.len() == 4);
+
+		for engine in &[b"aaaa", b"bbbb"] {
+			assert!(events.iter().any(|event| match event {
+				Event::Opened { engine_id, handshake, .. } =>
+					engine_id == *engine && handshake == b"first",
+				_ => false,
+			}));
+			assert!(events.iter().any(|event| match event {
+				Event::Notification { engine_id, message, .. } =>
+					engine_id == *engine && &message[..] == b"hello",
+				_ => false,
+			}));
+		}
+	}
+
+	#[test]
+	fn unsupported_protocols_fail_to_open() {
+		let first_protocols = [("/a", *b"aaaa")];
+		let second_protocols = [("/a", *b"aaaa"), ("/b", *b"bbbb")];
+		let events = run(&first_protocols, &second_protocols, true, |events| match events.last() {
+			Some(Event::OpenFailed { .. }) => true,
+			_ => false,
+		});
+
+		match events.last() {
+			Some(Event::OpenFailed { engine_id, .. }) => assert_eq!(engine_id, b"bbbb"),
+			_ => panic!(),
+		}
+	}
+
+	#[test]
+	fn protocols_not_opened_by_the_remote_time_out() {
+		let protocols = [("/a", *b"aaaa")];
+		let events = run(&protocols, &protocols, false, |events| !events.is_empty());
+
+		match &events[..] {
+			[Event::OpenFailed { engine_id, .. }] => assert_eq!(engine_id, b"aaaa"),
+			_ => panic!(),
+		}
+	}
+}
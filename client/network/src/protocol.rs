@@ -14,7 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::{DiscoveryNetBehaviour, config::ProtocolId};
+use crate::{DiscoveryNetBehaviour, config::ProtocolId, request_responses};
+use crate::request_responses::RequestFailure;
 use legacy_proto::{LegacyProto, LegacyProtoOut};
 use crate::utils::interval;
 use bytes::{Bytes, BytesMut};
@@ -43,10 +44,11 @@ use sync::{ChainSync, SyncState};
 use crate::service::{TransactionPool, ExHashT};
 use crate::config::{BoxFinalityProofRequestBuilder, Roles};
 use rustc_hex::ToHex;
+use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 use std::fmt::Write;
-use std::{cmp, num::NonZeroUsize, pin::Pin, task::Poll, time};
+use std::{cmp, mem, num::NonZeroUsize, pin::Pin, task::Poll, time};
 use log::{log, Level, trace, debug, warn, error};
 use crate::chain::{Client, FinalityProofProvider};
 use sc_client_api::{FetchChecker, ChangesProof, StorageProof};
@@ -143,6 +145,8 @@ pub struct Protocol<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> {
 	finality_proof_provider: Option<Arc<dyn FinalityProofProvider<B>>>,
 	/// Handles opening the unique substream and sending and receiving raw messages.
 	behaviour: LegacyProto<Substream<StreamMuxerBox>>,
+	/// Requests to send on the request-response protocols.
+	outbound_requests: OutboundRequests,
 	/// List of notification protocols that have been registered.
 	registered_notif_protocols: HashSet<ConsensusEngineId>,
 }
@@ -175,6 +179,17 @@ struct Peer<B: BlockT, H: ExHashT> {
 	known_blocks: LruHashSet<B::Hash>,
 	/// Request counter,
 	next_request_id: message::RequestId,
+	/// Notifications protocols open with this peer, and how their notifications are sent.
+	notifications: HashMap<ConsensusEngineId, NotificationsTransport>,
+}
+
+/// How the notifications of a notifications protocol are sent to a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationsTransport {
+	/// On the substreams of the protocol.
+	Substream,
+	/// As consensus messages on the legacy substream, for peers that don't support the protocol.
+	Legacy,
 }
 
 /// Info about a peer's known state.
@@ -190,11 +205,81 @@ pub struct PeerInfo<B: BlockT> {
 	pub best_number: <B::Header as HeaderT>::Number,
 }
 
+/// Routes the requests we send either to the request-response protocols or, for the peers that
+/// don't support them, to the legacy substream.
+struct OutboundRequests {
+	/// Name of the protocol of block and finality proof requests.
+	sync_protocol: Cow<'static, str>,
+	/// Name of the protocol of light client requests.
+	light_protocol: Cow<'static, str>,
+	/// Peers that don't support the request-response protocols.
+	legacy_peers: HashSet<PeerId>,
+	/// Encoded requests to send on the request-response protocols, and their targets.
+	queue: Vec<(PeerId, Cow<'static, str>, Vec<u8>)>,
+}
+
+impl OutboundRequests {
+	fn new(protocol_id: &ProtocolId) -> Self {
+		let protocol_id = String::from_utf8_lossy(protocol_id.as_bytes());
+		OutboundRequests {
+			sync_protocol: Cow::Owned(format!("/substrate/{}/sync/1", protocol_id)),
+			light_protocol: Cow::Owned(format!("/substrate/{}/light/1", protocol_id)),
+			legacy_peers: HashSet::new(),
+			queue: Vec::new(),
+		}
+	}
+
+	/// Returns the request-response protocol `message` is sent on, or `None` if `message` isn't
+	/// a request.
+	fn protocol_of<B: BlockT>(&self, message: &Message<B>) -> Option<&Cow<'static, str>> {
+		match message {
+			GenericMessage::BlockRequest(_) | GenericMessage::FinalityProofRequest(_) =>
+				Some(&self.sync_protocol),
+			GenericMessage::RemoteCallRequest(_) |
+			GenericMessage::RemoteReadRequest(_) |
+			GenericMessage::RemoteReadChildRequest(_) |
+			GenericMessage::RemoteHeaderRequest(_) |
+			GenericMessage::RemoteChangesRequest(_) => Some(&self.light_protocol),
+			_ => None,
+		}
+	}
+
+	/// Queues `message` to be sent to `who` on a request-response protocol. Returns the message
+	/// back if it must be sent on the legacy substream instead.
+	fn route<B: BlockT>(&mut self, who: &PeerId, message: Message<B>) -> Option<Message<B>> {
+		if self.legacy_peers.contains(who) {
+			return Some(message);
+		}
+		match self.protocol_of(&message).cloned() {
+			Some(protocol) => {
+				self.queue.push((who.clone(), protocol, message.encode()));
+				None
+			},
+			None => Some(message),
+		}
+	}
+
+	/// Sends all the next requests to `who` on the legacy substream, because it doesn't support
+	/// the request-response protocols.
+	fn fall_back(&mut self, who: PeerId) {
+		self.legacy_peers.insert(who);
+	}
+}
+
 struct LightDispatchIn<'a> {
 	behaviour: &'a mut LegacyProto<Substream<StreamMuxerBox>>,
+	requests: &'a mut OutboundRequests,
 	peerset: sc_peerset::PeersetHandle,
 }
 
+impl<'a> LightDispatchIn<'a> {
+	fn send_request<B: BlockT>(&mut self, who: &PeerId, message: Message<B>) {
+		if let Some(message) = self.requests.route(who, message) {
+			self.behaviour.send_packet(who, message.encode())
+		}
+	}
+}
+
 impl<'a, B: BlockT> LightDispatchNetwork<B> for LightDispatchIn<'a> {
 	fn report_peer(&mut self, who: &PeerId, reputation: sc_peerset::ReputationChange) {
 		self.peerset.report_peer(who.clone(), reputation)
@@ -210,7 +295,7 @@ impl<'a, B: BlockT> LightDispatchNetwork<B> for LightDispatchIn<'a> {
 			block,
 		});
 
-		self.behaviour.send_packet(who, message.encode())
+		self.send_request(who, message)
 	}
 
 	fn send_read_request(
@@ -226,7 +311,7 @@ impl<'a, B: BlockT> LightDispatchNetwork<B> for LightDispatchIn<'a> {
 			keys,
 		});
 
-		self.behaviour.send_packet(who, message.encode())
+		self.send_request(who, message)
 	}
 
 	fn send_read_child_request(
@@ -248,7 +333,7 @@ impl<'a, B: BlockT> LightDispatchNetwork<B> for LightDispatchIn<'a> {
 			keys,
 		});
 
-		self.behaviour.send_packet(who, message.encode())
+		self.send_request(who, message)
 	}
 
 	fn send_call_request(
@@ -266,7 +351,7 @@ impl<'a, B: BlockT> LightDispatchNetwork<B> for LightDispatchIn<'a> {
 			data,
 		});
 
-		self.behaviour.send_packet(who, message.encode())
+		self.send_request(who, message)
 	}
 
 	fn send_changes_request(
@@ -290,7 +375,7 @@ impl<'a, B: BlockT> LightDispatchNetwork<B> for LightDispatchIn<'a> {
 			key,
 		});
 
-		self.behaviour.send_packet(who, message.encode())
+		self.send_request(who, message)
 	}
 
 	fn send_body_request(
@@ -312,7 +397,7 @@ impl<'a, B: BlockT> LightDispatchNetwork<B> for LightDispatchIn<'a> {
 			max,
 		});
 
-		self.behaviour.send_packet(who, message.encode())
+		self.send_request(who, message)
 	}
 }
 
@@ -426,6 +511,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 
 		let (peerset, peerset_handle) = sc_peerset::Peerset::from_config(peerset_config);
 		let versions = &((MIN_VERSION as u8)..=(CURRENT_VERSION as u8)).collect::<Vec<u8>>();
+		let outbound_requests = OutboundRequests::new(&protocol_id);
 		let behaviour = LegacyProto::new(protocol_id, versions, peerset);
 
 		let protocol = Protocol {
@@ -447,6 +533,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 			finality_proof_provider,
 			peerset_handle: peerset_handle.clone(),
 			behaviour,
+			outbound_requests,
 			registered_notif_protocols: HashSet::new(),
 		};
 
@@ -523,6 +610,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 	pub(crate) fn add_light_client_request(&mut self, rq: RequestData<B>) {
 		self.light_dispatch.add_request(LightDispatchIn {
 			behaviour: &mut self.behaviour,
+			requests: &mut self.outbound_requests,
 			peerset: self.peerset_handle.clone(),
 		}, rq);
 	}
@@ -586,9 +674,23 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		stats.bytes_in += data.len() as u64;
 		stats.count_in += 1;
 
+		self.on_message(who, message)
+	}
+
+	fn on_message(&mut self, who: PeerId, message: Message<B>) -> CustomMessageOutcome<B> {
 		match message {
 			GenericMessage::Status(s) => return self.on_status_message(who, s),
-			GenericMessage::BlockRequest(r) => self.on_block_request(who, r),
+			GenericMessage::BlockRequest(_) |
+			GenericMessage::RemoteCallRequest(_) |
+			GenericMessage::RemoteReadRequest(_) |
+			GenericMessage::RemoteReadChildRequest(_) |
+			GenericMessage::RemoteHeaderRequest(_) |
+			GenericMessage::RemoteChangesRequest(_) |
+			GenericMessage::FinalityProofRequest(_) => {
+				if let Some(response) = self.on_request_message(who.clone(), message) {
+					self.send_message(&who, response);
+				}
+			},
 			GenericMessage::BlockResponse(r) => {
 				// Note, this is safe because only `ordinary bodies` and `remote bodies` are received in this matter.
 				if self.is_light_response(&who, r.id) {
@@ -608,27 +710,16 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 			},
 			GenericMessage::Transactions(m) =>
 				self.on_extrinsics(who, m),
-			GenericMessage::RemoteCallRequest(request) => self.on_remote_call_request(who, request),
 			GenericMessage::RemoteCallResponse(response) =>
 				self.on_remote_call_response(who, response),
-			GenericMessage::RemoteReadRequest(request) =>
-				self.on_remote_read_request(who, request),
 			GenericMessage::RemoteReadResponse(response) =>
 				self.on_remote_read_response(who, response),
-			GenericMessage::RemoteHeaderRequest(request) =>
-				self.on_remote_header_request(who, request),
 			GenericMessage::RemoteHeaderResponse(response) =>
 				self.on_remote_header_response(who, response),
-			GenericMessage::RemoteChangesRequest(request) =>
-				self.on_remote_changes_request(who, request),
 			GenericMessage::RemoteChangesResponse(response) =>
 				self.on_remote_changes_response(who, response),
-			GenericMessage::FinalityProofRequest(request) =>
-				self.on_finality_proof_request(who, request),
 			GenericMessage::FinalityProofResponse(response) =>
 				return self.on_finality_proof_response(who, response),
-			GenericMessage::RemoteReadChildRequest(request) =>
-				self.on_remote_read_child_request(who, request),
 			GenericMessage::Consensus(msg) =>
 				return if self.registered_notif_protocols.contains(&msg.engine_id) {
 					CustomMessageOutcome::NotificationsReceived {
@@ -671,11 +762,189 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		CustomMessageOutcome::None
 	}
 
+	/// Answers a request sent by `who`, either on the legacy substream or on a request-response
+	/// protocol. Returns `None` if the request is refused or if `message` isn't a request.
+	fn on_request_message(&mut self, who: PeerId, message: Message<B>) -> Option<Message<B>> {
+		match message {
+			GenericMessage::BlockRequest(r) => self.on_block_request(who, r),
+			GenericMessage::RemoteCallRequest(r) => self.on_remote_call_request(who, r),
+			GenericMessage::RemoteReadRequest(r) => self.on_remote_read_request(who, r),
+			GenericMessage::RemoteReadChildRequest(r) => self.on_remote_read_child_request(who, r),
+			GenericMessage::RemoteHeaderRequest(r) => self.on_remote_header_request(who, r),
+			GenericMessage::RemoteChangesRequest(r) => self.on_remote_changes_request(who, r),
+			GenericMessage::FinalityProofRequest(r) => self.on_finality_proof_request(who, r),
+			_ => None,
+		}
+	}
+
+	/// Returns the configuration of the request-response protocols, which carry the SCALE
+	/// encoded request and response messages of the legacy substream.
+	///
+	/// A peer has at most one block request, one finality proof request and one light client
+	/// request in flight with us, so the limits of concurrent inbound requests per peer leave
+	/// room for a few obsolete requests only.
+	pub fn request_response_protocols(&self) -> Vec<request_responses::ProtocolConfig> {
+		let sync = request_responses::ProtocolConfig {
+			name: self.outbound_requests.sync_protocol.clone(),
+			max_request_size: 1024 * 1024,
+			max_response_size: 16 * 1024 * 1024,
+			request_timeout: time::Duration::from_secs(REQUEST_TIMEOUT_SEC),
+			max_concurrent_inbound: 4,
+		};
+		let light = request_responses::ProtocolConfig {
+			name: self.outbound_requests.light_protocol.clone(),
+			max_request_size: 1024 * 1024,
+			max_response_size: 16 * 1024 * 1024,
+			request_timeout: light_dispatch::REQUEST_TIMEOUT,
+			max_concurrent_inbound: 4,
+		};
+		vec![sync, light]
+	}
+
+	/// Returns the requests to send on the request-response protocols, with their targets.
+	pub fn take_outbound_requests(&mut self) -> Vec<(PeerId, Cow<'static, str>, Vec<u8>)> {
+		mem::replace(&mut self.outbound_requests.queue, Vec::new())
+	}
+
+	/// Answers a request received on the request-response protocol `protocol`.
+	///
+	/// Returns the encoded response, or `None` if the request is refused.
+	pub fn on_request(&mut self, who: PeerId, protocol: &str, request: &[u8]) -> Option<Vec<u8>> {
+		let message = match <Message<B> as Decode>::decode(&mut &request[..]) {
+			Ok(message) => message,
+			Err(err) => {
+				debug!(target: "sync", "Couldn't decode request sent by {}: {}", who, err.what());
+				self.peerset_handle.report_peer(who, rep::BAD_MESSAGE);
+				return None;
+			}
+		};
+		if self.outbound_requests.protocol_of(&message).map_or(true, |p| p != protocol) {
+			debug!(
+				target: "sync",
+				"Unexpected {} request on {} sent by {}",
+				message.id(),
+				protocol,
+				who,
+			);
+			self.peerset_handle.report_peer(who, rep::BAD_MESSAGE);
+			return None;
+		}
+
+		let stats = self.context_data.stats.entry(message.id()).or_default();
+		stats.bytes_in += request.len() as u64;
+		stats.count_in += 1;
+
+		let response = self.on_request_message(who, message)?;
+		let encoded = response.encode();
+		let stats = self.context_data.stats.entry(response.id()).or_default();
+		stats.bytes_out += encoded.len() as u64;
+		stats.count_out += 1;
+		Some(encoded)
+	}
+
+	/// Must be called with the response to a request sent on a request-response protocol.
+	pub fn on_request_response(
+		&mut self,
+		who: PeerId,
+		response: Vec<u8>,
+	) -> CustomMessageOutcome<B> {
+		let message = match <Message<B> as Decode>::decode(&mut &response[..]) {
+			Ok(message) => message,
+			Err(err) => {
+				debug!(target: "sync", "Couldn't decode response sent by {}: {}", who, err.what());
+				self.peerset_handle.report_peer(who, rep::BAD_MESSAGE);
+				return CustomMessageOutcome::None;
+			}
+		};
+		match message {
+			GenericMessage::BlockResponse(_) |
+			GenericMessage::RemoteCallResponse(_) |
+			GenericMessage::RemoteReadResponse(_) |
+			GenericMessage::RemoteHeaderResponse(_) |
+			GenericMessage::RemoteChangesResponse(_) |
+			GenericMessage::FinalityProofResponse(_) => {},
+			_ => {
+				debug!(target: "sync", "Unexpected {} response sent by {}", message.id(), who);
+				self.peerset_handle.report_peer(who, rep::BAD_MESSAGE);
+				return CustomMessageOutcome::None;
+			}
+		}
+
+		let stats = self.context_data.stats.entry(message.id()).or_default();
+		stats.bytes_in += response.len() as u64;
+		stats.count_in += 1;
+
+		self.on_message(who, message)
+	}
+
+	/// Must be called when `who` doesn't support the protocol of a request sent on a
+	/// request-response protocol. The request, and all the next ones, are sent on the legacy
+	/// substream instead.
+	pub fn on_request_unsupported(&mut self, who: PeerId, request: Vec<u8>) {
+		debug!(target: "sync", "Falling back to the legacy substream for requests to {}", who);
+		self.behaviour.send_packet(&who, request);
+		self.outbound_requests.fall_back(who);
+	}
+
+	/// Must be called when a request sent on a request-response protocol has failed for another
+	/// reason than the remote not supporting the protocol.
+	///
+	/// A request that couldn't be sent because the connection was lost is sent on the legacy
+	/// substream instead, if it is still open. Any other failure is handled right away as a
+	/// timeout of the request would be: the peer is disconnected, after which the sync and the
+	/// light client send their requests to other peers.
+	pub fn on_request_failed(&mut self, who: PeerId, request: Vec<u8>, failure: RequestFailure) {
+		if let RequestFailure::NotConnected | RequestFailure::ConnectionClosed = failure {
+			if self.context_data.peers.contains_key(&who) {
+				debug!(target: "sync", "Sending failed request to {} on the legacy substream", who);
+				self.behaviour.send_packet(&who, request);
+			}
+			return;
+		}
+
+		// We encoded the request ourselves.
+		let message = match <Message<B> as Decode>::decode(&mut &request[..]) {
+			Ok(message) => message,
+			Err(_) => return,
+		};
+		let light_request_id = match message {
+			GenericMessage::BlockRequest(r) => {
+				let obsolete = self.context_data.peers.get_mut(&who)
+					.map_or(true, |peer| peer.obsolete_requests.remove(&r.id).is_some());
+				if obsolete {
+					return;
+				}
+				None
+			},
+			GenericMessage::FinalityProofRequest(_) => None,
+			GenericMessage::RemoteCallRequest(r) => Some(r.id),
+			GenericMessage::RemoteReadRequest(r) => Some(r.id),
+			GenericMessage::RemoteReadChildRequest(r) => Some(r.id),
+			GenericMessage::RemoteHeaderRequest(r) => Some(r.id),
+			GenericMessage::RemoteChangesRequest(r) => Some(r.id),
+			_ => return,
+		};
+
+		debug!(target: "sync", "Request to {} failed: {}", who, failure);
+		match light_request_id {
+			Some(id) => self.light_dispatch.on_request_failed(LightDispatchIn {
+				behaviour: &mut self.behaviour,
+				requests: &mut self.outbound_requests,
+				peerset: self.peerset_handle.clone(),
+			}, who, id),
+			None => {
+				self.behaviour.disconnect_peer(&who);
+				self.peerset_handle.report_peer(who, rep::TIMEOUT);
+			},
+		}
+	}
+
 	fn send_request(&mut self, who: &PeerId, message: Message<B>) {
 		send_request::<B, H>(
 			&mut self.behaviour,
 			&mut self.context_data.stats,
 			&mut self.context_data.peers,
+			&mut self.outbound_requests,
 			who,
 			message,
 		);
@@ -716,6 +985,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		// lock all the the peer lists so that add/remove peer events are in order
 		let removed = {
 			self.handshaking_peers.remove(&peer);
+			self.outbound_requests.legacy_peers.remove(&peer);
 			self.context_data.peers.remove(&peer)
 		};
 		if let Some(_peer_data) = removed {
@@ -724,6 +994,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 			self.specialization.on_disconnect(&mut context, peer.clone());
 			self.light_dispatch.on_disconnect(LightDispatchIn {
 				behaviour: &mut self.behaviour,
+				requests: &mut self.outbound_requests,
 				peerset: self.peerset_handle.clone(),
 			}, peer);
 		}
@@ -749,7 +1020,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		&mut self,
 		peer: PeerId,
 		request: message::BlockRequest<B>
-	) {
+	) -> Option<Message<B>> {
 		trace!(target: "sync", "BlockRequest {} from {}: from {:?} to {:?} max {:?}",
 			request.id,
 			peer,
@@ -762,7 +1033,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 			trace!(target: "sync", "Peer {} is trying to sync from the light node", peer);
 			self.behaviour.disconnect_peer(&peer);
 			self.peerset_handle.report_peer(peer, rep::UNEXPECTED_REQUEST);
-			return;
+			return None;
 		}
 
 		let mut blocks = Vec::new();
@@ -819,7 +1090,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 			blocks: blocks,
 		};
 		trace!(target: "sync", "Sending BlockResponse with {} blocks", response.blocks.len());
-		self.send_message(&peer, GenericMessage::BlockResponse(response))
+		Some(GenericMessage::BlockResponse(response))
 	}
 
 	/// Adjusts the reputation of a node.
@@ -885,6 +1156,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		self.maintain_peers();
 		self.light_dispatch.maintain_peers(LightDispatchIn {
 			behaviour: &mut self.behaviour,
+			requests: &mut self.outbound_requests,
 			peerset: self.peerset_handle.clone(),
 		});
 	}
@@ -1017,6 +1289,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 					.expect("Constant is nonzero")),
 				next_request_id: 0,
 				obsolete_requests: HashMap::new(),
+				notifications: HashMap::new(),
 			};
 			self.context_data.peers.insert(who.clone(), peer);
 
@@ -1027,6 +1300,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		let info = self.context_data.peers.get(&who).expect("We just inserted above; QED").info.clone();
 		self.light_dispatch.on_connect(LightDispatchIn {
 			behaviour: &mut self.behaviour,
+			requests: &mut self.outbound_requests,
 			peerset: self.peerset_handle.clone(),
 		}, who.clone(), status.roles, status.best_number);
 		if info.roles.is_full() {
@@ -1043,18 +1317,16 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		let mut context = ProtocolContext::new(&mut self.context_data, &mut self.behaviour, &self.peerset_handle);
 		self.specialization.on_connect(&mut context, who.clone(), status);
 
-		// Notify all the notification protocols as open.
-		CustomMessageOutcome::NotificationStreamOpened {
-			remote: who,
-			protocols: self.registered_notif_protocols.iter().cloned().collect(),
-			roles: info.roles,
-		}
+		// The notifications protocols are reported as open once their substreams are open, or
+		// once we know that the remote doesn't support them.
+		CustomMessageOutcome::OpenNotificationsSubstreams { remote: who }
 	}
 
-	/// Send a notification to the given peer we're connected to.
+	/// Send a notification to the given peer we're connected to, as a consensus message on the
+	/// legacy substream.
 	///
-	/// Doesn't do anything if we don't have a notifications substream for that protocol with that
-	/// peer.
+	/// Notifications should only be sent this way if `notifications_transport` returns
+	/// `NotificationsTransport::Legacy`.
 	pub fn write_notification(
 		&mut self,
 		target: PeerId,
@@ -1077,28 +1349,137 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 
 	/// Registers a new notifications protocol.
 	///
-	/// You are very strongly encouraged to call this method very early on. Any connection open
-	/// will retain the protocols that were registered then, and not any new one.
+	/// You are very strongly encouraged to call this method very early on. The protocol is
+	/// immediately reported as open with the peers we are already connected to, and its
+	/// notifications are sent to them on the legacy substream until its substreams are open.
 	pub fn register_notifications_protocol(
 		&mut self,
 		engine_id: ConsensusEngineId,
 	) -> Vec<event::Event> {
 		if !self.registered_notif_protocols.insert(engine_id) {
 			error!(target: "sub-libp2p", "Notifications protocol already registered: {:?}", engine_id);
+			return Vec::new();
 		}
 
-		// Registering a protocol while we already have open connections isn't great, but for now
-		// we handle it by notifying that we opened channels with everyone.
-		self.context_data.peers.iter()
-			.map(|(peer_id, peer)|
+		self.context_data.peers.iter_mut()
+			.map(|(peer_id, peer)| {
+				peer.notifications.insert(engine_id, NotificationsTransport::Legacy);
 				event::Event::NotificationStreamOpened {
 					remote: peer_id.clone(),
 					engine_id,
 					roles: peer.info.roles,
-				})
+				}
+			})
 			.collect()
 	}
 
+	/// Returns the handshake sent on the substreams of the notifications protocols, which is our
+	/// encoded roles.
+	pub fn notifications_handshake(&self) -> Vec<u8> {
+		self.config.roles.encode()
+	}
+
+	/// Returns how notifications of the given protocol are sent to the given peer, or `None` if
+	/// the protocol isn't open with that peer.
+	pub fn notifications_transport(
+		&self,
+		who: &PeerId,
+		engine_id: ConsensusEngineId,
+	) -> Option<NotificationsTransport> {
+		self.context_data.peers.get(who)?.notifications.get(&engine_id).cloned()
+	}
+
+	/// Must be called when both substreams of a notifications protocol are open with a peer.
+	pub fn on_notifications_substream_open(
+		&mut self,
+		who: PeerId,
+		engine_id: ConsensusEngineId,
+		handshake: &[u8],
+	) -> CustomMessageOutcome<B> {
+		let (transport, roles) = match Roles::decode(&mut &handshake[..]) {
+			Ok(roles) => (NotificationsTransport::Substream, Some(roles)),
+			Err(err) => {
+				debug!(target: "sync", "Invalid notifications handshake from {}: {}", who, err);
+				(NotificationsTransport::Legacy, None)
+			},
+		};
+		self.open_notifications(who, engine_id, transport, roles)
+	}
+
+	/// Must be called when the substreams of a notifications protocol couldn't be opened in time
+	/// with a peer. Its notifications are then sent on the legacy substream.
+	pub fn on_notifications_substream_unsupported(
+		&mut self,
+		who: PeerId,
+		engine_id: ConsensusEngineId,
+	) -> CustomMessageOutcome<B> {
+		self.open_notifications(who, engine_id, NotificationsTransport::Legacy, None)
+	}
+
+	/// Must be called when the substreams of a notifications protocol with a peer have been
+	/// closed. Its notifications are then sent on the legacy substream.
+	pub fn on_notifications_substream_closed(
+		&mut self,
+		who: &PeerId,
+		engine_id: ConsensusEngineId,
+	) {
+		let transport = self.context_data.peers.get_mut(who)
+			.and_then(|peer| peer.notifications.get_mut(&engine_id));
+		if let Some(transport) = transport {
+			*transport = NotificationsTransport::Legacy;
+		}
+	}
+
+	/// Must be called when a peer sent a notification on the substream of a notifications
+	/// protocol.
+	pub fn on_notification(
+		&mut self,
+		who: PeerId,
+		engine_id: ConsensusEngineId,
+		message: Bytes,
+	) -> CustomMessageOutcome<B> {
+		if self.registered_notif_protocols.contains(&engine_id) {
+			CustomMessageOutcome::NotificationsReceived {
+				remote: who,
+				messages: vec![(engine_id, message)],
+			}
+		} else {
+			warn!(
+				target: "sync",
+				"Received notification on non-registered protocol: {:?}",
+				engine_id,
+			);
+			CustomMessageOutcome::None
+		}
+	}
+
+	/// Records how the notifications of a protocol are sent to a peer, and reports the protocol as
+	/// open if it wasn't already. `roles` defaults to the roles of the peer's status.
+	fn open_notifications(
+		&mut self,
+		who: PeerId,
+		engine_id: ConsensusEngineId,
+		transport: NotificationsTransport,
+		roles: Option<Roles>,
+	) -> CustomMessageOutcome<B> {
+		if !self.registered_notif_protocols.contains(&engine_id) {
+			return CustomMessageOutcome::None;
+		}
+		let peer = match self.context_data.peers.get_mut(&who) {
+			Some(peer) => peer,
+			None => return CustomMessageOutcome::None,
+		};
+		if peer.notifications.insert(engine_id, transport).is_some() {
+			return CustomMessageOutcome::None;
+		}
+
+		CustomMessageOutcome::NotificationStreamOpened {
+			remote: who,
+			protocols: vec![engine_id],
+			roles: roles.unwrap_or(peer.info.roles),
+		}
+	}
+
 	/// Called when peer sends us new extrinsics
 	fn on_extrinsics(
 		&mut self,
@@ -1265,6 +1646,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		}
 		self.light_dispatch.update_best_number(LightDispatchIn {
 			behaviour: &mut self.behaviour,
+			requests: &mut self.outbound_requests,
 			peerset: self.peerset_handle.clone(),
 		}, who.clone(), *announce.header.number());
 
@@ -1350,7 +1732,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		&mut self,
 		who: PeerId,
 		request: message::RemoteCallRequest<B::Hash>,
-	) {
+	) -> Option<Message<B>> {
 		trace!(target: "sync", "Remote call request {} from {} ({} at {})",
 			request.id,
 			who,
@@ -1376,13 +1758,10 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 			}
 		};
 
-		self.send_message(
-			&who,
-			GenericMessage::RemoteCallResponse(message::RemoteCallResponse {
-				id: request.id,
-				proof,
-			}),
-		);
+		Some(GenericMessage::RemoteCallResponse(message::RemoteCallResponse {
+			id: request.id,
+			proof,
+		}))
 	}
 
 	/// Request a justification for the given block.
@@ -1422,6 +1801,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 						&mut self.behaviour,
 						&mut self.context_data.stats,
 						&mut self.context_data.peers,
+						&mut self.outbound_requests,
 						&id,
 						msg
 					)
@@ -1463,6 +1843,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		trace!(target: "sync", "Remote call response {} from {}", response.id, who);
 		self.light_dispatch.on_remote_call_response(LightDispatchIn {
 			behaviour: &mut self.behaviour,
+			requests: &mut self.outbound_requests,
 			peerset: self.peerset_handle.clone(),
 		}, who, response);
 	}
@@ -1471,12 +1852,12 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		&mut self,
 		who: PeerId,
 		request: message::RemoteReadRequest<B::Hash>,
-	) {
+	) -> Option<Message<B>> {
 		if request.keys.is_empty() {
 			debug!(target: "sync", "Invalid remote read request sent by {}", who);
 			self.behaviour.disconnect_peer(&who);
 			self.peerset_handle.report_peer(who, rep::BAD_MESSAGE);
-			return;
+			return None;
 		}
 
 		let keys_str = || match request.keys.len() {
@@ -1503,25 +1884,22 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 				StorageProof::empty()
 			}
 		};
-		self.send_message(
-			&who,
-			GenericMessage::RemoteReadResponse(message::RemoteReadResponse {
-				id: request.id,
-				proof,
-			}),
-		);
+		Some(GenericMessage::RemoteReadResponse(message::RemoteReadResponse {
+			id: request.id,
+			proof,
+		}))
 	}
 
 	fn on_remote_read_child_request(
 		&mut self,
 		who: PeerId,
 		request: message::RemoteReadChildRequest<B::Hash>,
-	) {
+	) -> Option<Message<B>> {
 		if request.keys.is_empty() {
 			debug!(target: "sync", "Invalid remote child read request sent by {}", who);
 			self.behaviour.disconnect_peer(&who);
 			self.peerset_handle.report_peer(who, rep::BAD_MESSAGE);
-			return;
+			return None;
 		}
 
 		let keys_str = || match request.keys.len() {
@@ -1567,13 +1945,10 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 
 			StorageProof::empty()
 		};
-		self.send_message(
-			&who,
-			GenericMessage::RemoteReadResponse(message::RemoteReadResponse {
-				id: request.id,
-				proof,
-			}),
-		);
+		Some(GenericMessage::RemoteReadResponse(message::RemoteReadResponse {
+			id: request.id,
+			proof,
+		}))
 	}
 
 	fn on_remote_read_response(
@@ -1584,6 +1959,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		trace!(target: "sync", "Remote read response {} from {}", response.id, who);
 		self.light_dispatch.on_remote_read_response(LightDispatchIn {
 			behaviour: &mut self.behaviour,
+			requests: &mut self.outbound_requests,
 			peerset: self.peerset_handle.clone(),
 		}, who, response);
 	}
@@ -1592,7 +1968,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		&mut self,
 		who: PeerId,
 		request: message::RemoteHeaderRequest<NumberFor<B>>,
-	) {
+	) -> Option<Message<B>> {
 		trace!(target: "sync", "Remote header proof request {} from {} ({})",
 			request.id, who, request.block);
 		let (header, proof) = match self.context_data.chain.header_proof(request.block) {
@@ -1607,14 +1983,11 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 				(Default::default(), StorageProof::empty())
 			}
 		};
-		self.send_message(
-			&who,
-			GenericMessage::RemoteHeaderResponse(message::RemoteHeaderResponse {
-				id: request.id,
-				header,
-				proof,
-			}),
-		);
+		Some(GenericMessage::RemoteHeaderResponse(message::RemoteHeaderResponse {
+			id: request.id,
+			header,
+			proof,
+		}))
 	}
 
 	fn on_remote_header_response(
@@ -1625,6 +1998,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		trace!(target: "sync", "Remote header proof response {} from {}", response.id, who);
 		self.light_dispatch.on_remote_header_response(LightDispatchIn {
 			behaviour: &mut self.behaviour,
+			requests: &mut self.outbound_requests,
 			peerset: self.peerset_handle.clone(),
 		}, who, response);
 	}
@@ -1633,7 +2007,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		&mut self,
 		who: PeerId,
 		request: message::RemoteChangesRequest<B::Hash>,
-	) {
+	) -> Option<Message<B>> {
 		trace!(target: "sync", "Remote changes proof request {} from {} for key {} ({}..{})",
 			request.id,
 			who,
@@ -1677,16 +2051,13 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 				}
 			}
 		};
-		self.send_message(
-			&who,
-			GenericMessage::RemoteChangesResponse(message::RemoteChangesResponse {
-				id: request.id,
-				max: proof.max_block,
-				proof: proof.proof,
-				roots: proof.roots.into_iter().collect(),
-				roots_proof: proof.roots_proof,
-			}),
-		);
+		Some(GenericMessage::RemoteChangesResponse(message::RemoteChangesResponse {
+			id: request.id,
+			max: proof.max_block,
+			proof: proof.proof,
+			roots: proof.roots.into_iter().collect(),
+			roots_proof: proof.roots_proof,
+		}))
 	}
 
 	fn on_remote_changes_response(
@@ -1701,6 +2072,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		);
 		self.light_dispatch.on_remote_changes_response(LightDispatchIn {
 			behaviour: &mut self.behaviour,
+			requests: &mut self.outbound_requests,
 			peerset: self.peerset_handle.clone(),
 		}, who, response);
 	}
@@ -1709,7 +2081,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 		&mut self,
 		who: PeerId,
 		request: message::FinalityProofRequest<B::Hash>,
-	) {
+	) -> Option<Message<B>> {
 		trace!(target: "sync", "Finality proof request from {} for {}", who, request.block);
 		let finality_proof = self.finality_proof_provider.as_ref()
 			.ok_or_else(|| String::from("Finality provider is not configured"))
@@ -1727,14 +2099,11 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 				None
 			},
 		};
-		self.send_message(
-			&who,
-			GenericMessage::FinalityProofResponse(message::FinalityProofResponse {
-				id: 0,
-				block: request.block,
-				proof: finality_proof,
-			}),
-		);
+		Some(GenericMessage::FinalityProofResponse(message::FinalityProofResponse {
+			id: 0,
+			block: request.block,
+			proof: finality_proof,
+		}))
 	}
 
 	fn on_finality_proof_response(
@@ -1762,6 +2131,7 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Protocol<B, S, H> {
 	) {
 		self.light_dispatch.on_remote_body_response(LightDispatchIn {
 			behaviour: &mut self.behaviour,
+			requests: &mut self.outbound_requests,
 			peerset: self.peerset_handle.clone(),
 		}, peer, response);
 	}
@@ -1789,9 +2159,12 @@ pub enum CustomMessageOutcome<B: BlockT> {
 	BlockImport(BlockOrigin, Vec<IncomingBlock<B>>),
 	JustificationImport(Origin, B::Hash, NumberFor<B>, Justification),
 	FinalityProofImport(Origin, B::Hash, NumberFor<B>, Vec<u8>),
+	/// The substreams of the notification protocols must be opened with a remote.
+	OpenNotificationsSubstreams { remote: PeerId },
 	/// Notification protocols have been opened with a remote.
 	NotificationStreamOpened { remote: PeerId, protocols: Vec<ConsensusEngineId>, roles: Roles },
-	/// Notification protocols have been closed with a remote.
+	/// Notification protocols have been closed with a remote, whose notifications substreams
+	/// must be closed.
 	NotificationsStreamClosed { remote: PeerId, protocols: Vec<ConsensusEngineId> },
 	/// Messages have been received on one or more notifications protocols.
 	NotificationsReceived { remote: PeerId, messages: Vec<(ConsensusEngineId, Bytes)> },
@@ -1802,6 +2175,7 @@ fn send_request<B: BlockT, H: ExHashT>(
 	behaviour: &mut LegacyProto<Substream<StreamMuxerBox>>,
 	stats: &mut HashMap<&'static str, PacketStats>,
 	peers: &mut HashMap<PeerId, Peer<B, H>>,
+	requests: &mut OutboundRequests,
 	who: &PeerId,
 	mut message: Message<B>,
 ) {
@@ -1816,7 +2190,9 @@ fn send_request<B: BlockT, H: ExHashT>(
 			peer.block_request = Some((time::Instant::now(), r.clone()));
 		}
 	}
-	send_message::<B>(behaviour, stats, who, message)
+	if let Some(message) = requests.route(who, message) {
+		send_message::<B>(behaviour, stats, who, message)
+	}
 }
 
 fn send_message<B: BlockT>(
@@ -1884,6 +2260,7 @@ Protocol<B, S, H> {
 				&mut self.behaviour,
 				&mut self.context_data.stats,
 				&mut self.context_data.peers,
+				&mut self.outbound_requests,
 				&id,
 				GenericMessage::BlockRequest(r)
 			)
@@ -1893,6 +2270,7 @@ Protocol<B, S, H> {
 				&mut self.behaviour,
 				&mut self.context_data.stats,
 				&mut self.context_data.peers,
+				&mut self.outbound_requests,
 				&id,
 				GenericMessage::BlockRequest(r)
 			)
@@ -1902,6 +2280,7 @@ Protocol<B, S, H> {
 				&mut self.behaviour,
 				&mut self.context_data.stats,
 				&mut self.context_data.peers,
+				&mut self.outbound_requests,
 				&id,
 				GenericMessage::FinalityProofRequest(r))
 		}
@@ -1929,12 +2308,12 @@ Protocol<B, S, H> {
 				CustomMessageOutcome::None
 			}
 			LegacyProtoOut::CustomProtocolClosed { peer_id, .. } => {
+				let protocols = self.context_data.peers.get(&peer_id)
+					.map(|peer| peer.notifications.keys().cloned().collect())
+					.unwrap_or_default();
 				self.on_peer_disconnected(peer_id.clone());
-				// Notify all the notification protocols as closed.
-				CustomMessageOutcome::NotificationsStreamClosed {
-					remote: peer_id,
-					protocols: self.registered_notif_protocols.iter().cloned().collect(),
-				}
+				// Notify the notification protocols that were open as closed.
+				CustomMessageOutcome::NotificationsStreamClosed { remote: peer_id, protocols }
 			},
 			LegacyProtoOut::CustomMessage { peer_id, message } =>
 				self.on_custom_message(peer_id, message),
@@ -1997,3 +2376,66 @@ impl<B: BlockT, S: NetworkSpecialization<B>, H: ExHashT> Drop for Protocol<B, S,
 		debug!(target: "sync", "Network stats:\n{}", self.format_stats());
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use codec::Encode;
+	use libp2p::PeerId;
+	use sp_test_primitives::Block;
+	use std::borrow::Cow;
+	use super::{BlockAttributes, Direction, FromBlock, GenericMessage, Message, OutboundRequests};
+	use super::message::{BlockRequest, RemoteCallRequest};
+
+	fn block_request() -> Message<Block> {
+		GenericMessage::BlockRequest(BlockRequest {
+			id: 0,
+			fields: BlockAttributes::HEADER,
+			from: FromBlock::Number(1),
+			to: None,
+			direction: Direction::Ascending,
+			max: None,
+		})
+	}
+
+	fn call_request() -> Message<Block> {
+		GenericMessage::RemoteCallRequest(RemoteCallRequest {
+			id: 0,
+			block: Default::default(),
+			method: "test".into(),
+			data: vec![],
+		})
+	}
+
+	#[test]
+	fn routes_requests_to_their_protocol() {
+		let mut requests = OutboundRequests::new(&(&b"test"[..]).into());
+		let peer = PeerId::random();
+
+		assert_eq!(requests.route(&peer, block_request()), None);
+		assert_eq!(requests.route(&peer, call_request()), None);
+		assert_eq!(requests.queue, vec![
+			(peer.clone(), Cow::Borrowed("/substrate/test/sync/1"), block_request().encode()),
+			(peer.clone(), Cow::Borrowed("/substrate/test/light/1"), call_request().encode()),
+		]);
+
+		// Messages that aren't requests are always sent on the legacy substream.
+		let transactions = GenericMessage::Transactions(vec![]);
+		assert_eq!(requests.route(&peer, transactions.clone()), Some(transactions));
+		assert_eq!(requests.queue.len(), 2);
+	}
+
+	#[test]
+	fn falls_back_to_the_legacy_substream() {
+		let mut requests = OutboundRequests::new(&(&b"test"[..]).into());
+		let legacy_peer = PeerId::random();
+		let peer = PeerId::random();
+		requests.fall_back(legacy_peer.clone());
+
+		assert_eq!(requests.route(&legacy_peer, block_request()), Some(block_request()));
+		assert_eq!(requests.route(&legacy_peer, call_request()), Some(call_request()));
+		assert!(requests.queue.is_empty());
+
+		assert_eq!(requests.route(&peer, block_request()), None);
+		assert_eq!(requests.queue.len(), 1);
+	}
+}
@@ -36,7 +36,7 @@ use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
 use sc_peerset::ReputationChange;
 
 /// Remote request timeout.
-const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+pub(crate) const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
 /// Default request retry count.
 const RETRY_COUNT: usize = 1;
 /// Reputation change for a peer when a request timed out.
@@ -360,6 +360,26 @@ impl<B: BlockT> LightDispatch<B> where
 		self.dispatch(network);
 	}
 
+	/// Call this when the request `request_id` sent to `peer` has failed without a response, for
+	/// example because it timed out. The peer is handled as if the request had timed out in
+	/// `maintain_peers`, and the request is sent to another peer.
+	pub fn on_request_failed(
+		&mut self,
+		mut network: impl LightDispatchNetwork<B>,
+		peer: PeerId,
+		request_id: u64,
+	) {
+		if !self.is_light_response(&peer, request_id) {
+			return;
+		}
+
+		let reputation = ReputationChange::new(TIMEOUT_REPUTATION_CHANGE, "Light request failed");
+		network.report_peer(&peer, reputation);
+		network.disconnect_peer(&peer);
+		self.remove_peer(peer);
+		self.dispatch(network);
+	}
+
 	/// Handles a remote header response message from on the network.
 	pub fn on_remote_header_response(
 		&mut self,
@@ -883,6 +903,40 @@ pub mod tests {
 		assert_disconnected_peer(&network_interface);
 	}
 
+	#[test]
+	fn disconnects_from_peer_on_failed_request() {
+		let mut light_dispatch = dummy(true);
+		let mut network_interface = DummyNetwork::default();
+		let peer0 = PeerId::random();
+		let peer1 = PeerId::random();
+		light_dispatch.on_connect(&mut network_interface, peer0.clone(), Roles::FULL, 1000);
+		light_dispatch.on_connect(&mut network_interface, peer1.clone(), Roles::FULL, 1000);
+
+		let request = RemoteCallRequest {
+			block: Default::default(),
+			header: dummy_header(),
+			method: "test".into(),
+			call_data: vec![],
+			retry_count: None,
+		};
+		let data = RequestData::RemoteCall(request, oneshot::channel().0);
+		light_dispatch.add_request(&mut network_interface, data);
+		let active_peers = |light_dispatch: &LightDispatch<Block>| {
+			light_dispatch.active_peers.keys().cloned().collect::<Vec<_>>()
+		};
+		assert_eq!(vec![peer0.clone()], active_peers(&light_dispatch));
+
+		// Failures of requests that aren't active are ignored.
+		light_dispatch.on_request_failed(&mut network_interface, peer0.clone(), 1);
+		assert_eq!(vec![peer0.clone()], active_peers(&light_dispatch));
+		assert!(network_interface.disconnected_peers.is_empty());
+
+		light_dispatch.on_request_failed(&mut network_interface, peer0.clone(), 0);
+		assert!(light_dispatch.idle_peers.is_empty());
+		assert_eq!(vec![peer1.clone()], active_peers(&light_dispatch));
+		assert_disconnected_peer(&network_interface);
+	}
+
 	#[test]
 	fn disconnects_from_peer_on_response_with_wrong_id() {
 		let mut light_dispatch = dummy(true);
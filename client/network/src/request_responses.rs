@@ -0,0 +1,737 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generic request-response protocols.
+//!
+//! Each request-response protocol has its own name, for example `/dot/sync/2`. A request is sent
+//! by opening a new substream with the name of the protocol, writing the request and reading the
+//! response, after which the substream is closed. Requests and responses are prefixed with their
+//! length as an unsigned varint.
+//!
+//! The [`RequestResponsesBehaviour`] handles all the protocols it is built with and enforces the
+//! maximal sizes, the timeout and the number of concurrent inbound requests per peer configured
+//! for each of them. Inbound requests are reported as [`Event::InboundRequest`] and answered by
+//! sending the response on the channel of the event. Dropping the channel refuses the request,
+//! which closes the substream without writing anything. Responses are therefore never empty.
+
+use futures::{channel::oneshot, future::{self, BoxFuture, Either}, prelude::*};
+use futures::stream::FuturesUnordered;
+use futures_timer::Delay;
+use libp2p::core::{ConnectedPoint, Multiaddr, PeerId};
+use libp2p::core::upgrade::{
+	self, InboundUpgrade, Negotiated, NegotiationError, OutboundUpgrade, ProtocolName,
+	ReadOneError, UpgradeError, UpgradeInfo,
+};
+use libp2p::swarm::{
+	KeepAlive, NetworkBehaviour, NetworkBehaviourAction, PollParameters, ProtocolsHandler,
+	ProtocolsHandlerEvent, ProtocolsHandlerUpgrErr, SubstreamProtocol,
+};
+use log::debug;
+use std::{borrow::Cow, error, io, iter};
+use std::collections::{HashMap, HashSet, VecDeque, hash_map::Entry};
+use std::{marker::PhantomData, sync::Arc, time::Duration};
+use std::task::{Context, Poll, Waker};
+use wasm_timer::Instant;
+
+/// Configuration of a request-response protocol.
+#[derive(Debug, Clone)]
+pub struct ProtocolConfig {
+	/// Name of the protocol on the wire, for example `/dot/sync/2`.
+	pub name: Cow<'static, str>,
+	/// Maximal size, in bytes, of a request.
+	pub max_request_size: usize,
+	/// Maximal size, in bytes, of a response.
+	pub max_response_size: usize,
+	/// Duration after which an outbound request fails if no response has been received, and
+	/// after which an inbound request is dropped if it hasn't been answered.
+	pub request_timeout: Duration,
+	/// Maximal number of inbound requests of a peer that are being answered at the same time.
+	/// Further requests of that peer are refused until one of them has been answered.
+	pub max_concurrent_inbound: usize,
+}
+
+/// Identifier of an outbound request, unique within a [`RequestResponsesBehaviour`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RequestId(u64);
+
+/// Event generated by the [`RequestResponsesBehaviour`].
+#[derive(Debug)]
+pub enum Event {
+	/// A remote sent a request.
+	InboundRequest {
+		/// Peer which sent the request.
+		peer: PeerId,
+		/// Name of the protocol of the request.
+		protocol: Cow<'static, str>,
+		/// The request.
+		payload: Vec<u8>,
+		/// Channel to send the response on. Dropping it refuses the request.
+		pending_response: oneshot::Sender<Vec<u8>>,
+	},
+
+	/// An outbound request started with [`RequestResponsesBehaviour::send_request`] has finished.
+	RequestFinished {
+		/// Peer the request was sent to.
+		peer: PeerId,
+		/// Identifier returned by [`RequestResponsesBehaviour::send_request`].
+		request_id: RequestId,
+		/// The response, or the reason why there is none.
+		result: Result<Vec<u8>, RequestFailure>,
+	},
+}
+
+/// Reason why an outbound request has failed.
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
+pub enum RequestFailure {
+	/// We are not connected to the peer.
+	#[display(fmt = "Not connected to the peer")]
+	NotConnected,
+	/// The protocol of the request isn't one of the protocols of the behaviour.
+	#[display(fmt = "Unknown request-response protocol")]
+	UnknownProtocol,
+	/// The remote doesn't support the protocol of the request.
+	#[display(fmt = "The remote doesn't support the protocol")]
+	UnsupportedProtocol,
+	/// The remote refused the request or closed the substream without answering.
+	#[display(fmt = "The remote refused the request")]
+	Refused,
+	/// The response was larger than the maximal size of the protocol.
+	#[display(fmt = "The response was too large")]
+	ResponseTooLarge,
+	/// No response was received in time.
+	#[display(fmt = "The request timed out")]
+	Timeout,
+	/// The connection to the remote was closed before the response was received.
+	#[display(fmt = "The connection was closed")]
+	ConnectionClosed,
+}
+
+impl error::Error for RequestFailure {}
+
+/// Implementation of `NetworkBehaviour` for the request-response protocols.
+pub struct RequestResponsesBehaviour<TSubstream> {
+	/// The protocols, by name.
+	protocols: HashMap<Cow<'static, str>, ProtocolState>,
+	/// Upgrade for inbound substreams, which accepts all the protocols.
+	inbound_upgrade: InboundRequestsUpgrade,
+	/// Duration for which connections are kept alive after their last request.
+	keep_alive_timeout: Duration,
+	/// Peers we are connected to.
+	connected: HashSet<PeerId>,
+	/// Outbound requests that haven't finished yet, and the peers they were sent to.
+	pending_requests: HashMap<RequestId, PeerId>,
+	/// Inbound requests waiting for their response to be sent. Each future yields the name of
+	/// the protocol of its request and the peer which sent it.
+	pending_responses: FuturesUnordered<BoxFuture<'static, (Cow<'static, str>, PeerId)>>,
+	/// Actions to return from `poll`.
+	actions: VecDeque<NetworkBehaviourAction<OutboundRequest, Event>>,
+	/// Identifier of the next outbound request.
+	next_request_id: u64,
+	/// Waker of the task polling the behaviour, woken when a request is sent.
+	waker: Option<Waker>,
+	/// Marker to pin the generics.
+	marker: PhantomData<TSubstream>,
+}
+
+/// State of a protocol of the [`RequestResponsesBehaviour`].
+struct ProtocolState {
+	/// The configuration of the protocol.
+	config: ProtocolConfig,
+	/// Number of inbound requests that are being answered, by peer.
+	inbound_requests: HashMap<PeerId, usize>,
+}
+
+impl<TSubstream> RequestResponsesBehaviour<TSubstream> {
+	/// Builds a new `RequestResponsesBehaviour` handling the given protocols.
+	pub fn new(protocols: impl IntoIterator<Item = ProtocolConfig>) -> Self {
+		let protocols = protocols.into_iter()
+			.map(|config| {
+				let state = ProtocolState { config, inbound_requests: HashMap::new() };
+				(state.config.name.clone(), state)
+			})
+			.collect::<HashMap<_, _>>();
+		let inbound_upgrade = InboundRequestsUpgrade {
+			protocols: Arc::new(protocols.values()
+				.map(|state| (state.config.name.clone(), state.config.max_request_size))
+				.collect()),
+		};
+		let keep_alive_timeout = protocols.values()
+			.map(|state| state.config.request_timeout)
+			.max()
+			.unwrap_or_default();
+
+		RequestResponsesBehaviour {
+			protocols,
+			inbound_upgrade,
+			keep_alive_timeout,
+			connected: HashSet::new(),
+			pending_requests: HashMap::new(),
+			pending_responses: FuturesUnordered::new(),
+			actions: VecDeque::new(),
+			next_request_id: 0,
+			waker: None,
+			marker: PhantomData,
+		}
+	}
+
+	/// Sends a request to `target` with the given protocol.
+	///
+	/// An [`Event::RequestFinished`] with the returned identifier is always generated later,
+	/// including when the request can't be sent.
+	pub fn send_request(&mut self, target: &PeerId, protocol: &str, payload: Vec<u8>) -> RequestId {
+		let request_id = RequestId(self.next_request_id);
+		self.next_request_id += 1;
+
+		let action = match self.protocols.get(protocol) {
+			Some(_) if !self.connected.contains(target) =>
+				Self::request_failed(target, request_id, RequestFailure::NotConnected),
+			Some(state) => {
+				self.pending_requests.insert(request_id, target.clone());
+				NetworkBehaviourAction::SendEvent {
+					peer_id: target.clone(),
+					event: OutboundRequest {
+						request_id,
+						protocol: state.config.name.clone(),
+						payload,
+						max_response_size: state.config.max_response_size,
+						timeout: state.config.request_timeout,
+					},
+				}
+			},
+			None => Self::request_failed(target, request_id, RequestFailure::UnknownProtocol),
+		};
+		self.actions.push_back(action);
+
+		if let Some(waker) = self.waker.take() {
+			waker.wake();
+		}
+
+		request_id
+	}
+
+	/// Builds the action reporting that a request has failed.
+	fn request_failed(
+		peer: &PeerId,
+		request_id: RequestId,
+		failure: RequestFailure,
+	) -> NetworkBehaviourAction<OutboundRequest, Event> {
+		NetworkBehaviourAction::GenerateEvent(Event::RequestFinished {
+			peer: peer.clone(),
+			request_id,
+			result: Err(failure),
+		})
+	}
+}
+
+impl<TSubstream> NetworkBehaviour for RequestResponsesBehaviour<TSubstream>
+where
+	TSubstream: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+	type ProtocolsHandler = RequestResponsesHandler<TSubstream>;
+	type OutEvent = Event;
+
+	fn new_handler(&mut self) -> Self::ProtocolsHandler {
+		RequestResponsesHandler::new(self.inbound_upgrade.clone(), self.keep_alive_timeout)
+	}
+
+	fn addresses_of_peer(&mut self, _: &PeerId) -> Vec<Multiaddr> {
+		Vec::new()
+	}
+
+	fn inject_connected(&mut self, peer_id: PeerId, _: ConnectedPoint) {
+		self.connected.insert(peer_id);
+	}
+
+	fn inject_disconnected(&mut self, peer_id: &PeerId, _: ConnectedPoint) {
+		self.connected.remove(peer_id);
+
+		let failed = self.pending_requests.iter()
+			.filter(|(_, peer)| *peer == peer_id)
+			.map(|(request_id, _)| *request_id)
+			.collect::<Vec<_>>();
+		for request_id in failed {
+			self.pending_requests.remove(&request_id);
+			self.actions.push_back(
+				Self::request_failed(peer_id, request_id, RequestFailure::ConnectionClosed)
+			);
+		}
+	}
+
+	fn inject_node_event(&mut self, peer: PeerId, event: HandlerEvent<TSubstream>) {
+		match event {
+			HandlerEvent::Response { request_id, result } => {
+				if self.pending_requests.remove(&request_id).is_some() {
+					self.actions.push_back(NetworkBehaviourAction::GenerateEvent(
+						Event::RequestFinished { peer, request_id, result }
+					));
+				}
+			},
+			HandlerEvent::InboundRequest(InboundRequest { protocol, payload, mut substream }) => {
+				let state = match self.protocols.get_mut(&protocol) {
+					Some(state) => state,
+					None => return,
+				};
+				let inbound_requests = state.inbound_requests.entry(peer.clone()).or_default();
+				if *inbound_requests >= state.config.max_concurrent_inbound {
+					debug!(
+						target: "sub-libp2p",
+						"Refusing {} request from {}: too many concurrent requests",
+						protocol,
+						peer,
+					);
+					return;
+				}
+				*inbound_requests += 1;
+
+				let (pending_response, response) = oneshot::channel();
+				let respond = async move {
+					match response.await {
+						Ok(response) => upgrade::write_one(&mut substream, response).await,
+						// The request was refused: close the substream without answering.
+						Err(_) => substream.close().await,
+					}
+				};
+				let timeout = Delay::new(state.config.request_timeout);
+				let name = protocol.clone();
+				let remote = peer.clone();
+				self.pending_responses.push(async move {
+					match future::select(Box::pin(respond), timeout).await {
+						Either::Left((Ok(()), _)) => {},
+						Either::Left((Err(err), _)) => debug!(
+							target: "sub-libp2p",
+							"Failed to send {} response to {}: {}",
+							name,
+							remote,
+							err,
+						),
+						Either::Right(_) => debug!(
+							target: "sub-libp2p",
+							"{} request from {} wasn't answered in time",
+							name,
+							remote,
+						),
+					}
+					(name, remote)
+				}.boxed());
+
+				self.actions.push_back(NetworkBehaviourAction::GenerateEvent(
+					Event::InboundRequest { peer, protocol, payload, pending_response }
+				));
+			},
+		}
+	}
+
+	fn poll(
+		&mut self,
+		cx: &mut Context,
+		_: &mut impl PollParameters,
+	) -> Poll<NetworkBehaviourAction<OutboundRequest, Event>> {
+		while let Poll::Ready(Some((protocol, peer))) = self.pending_responses.poll_next_unpin(cx) {
+			let state = match self.protocols.get_mut(&protocol) {
+				Some(state) => state,
+				None => continue,
+			};
+			if let Entry::Occupied(mut entry) = state.inbound_requests.entry(peer) {
+				*entry.get_mut() -= 1;
+				if *entry.get() == 0 {
+					entry.remove();
+				}
+			}
+		}
+
+		if let Some(action) = self.actions.pop_front() {
+			return Poll::Ready(action);
+		}
+
+		self.waker = Some(cx.waker().clone());
+		Poll::Pending
+	}
+}
+
+/// Request sent by the behaviour to the handler of the connection with the target.
+#[derive(Debug)]
+pub struct OutboundRequest {
+	request_id: RequestId,
+	protocol: Cow<'static, str>,
+	payload: Vec<u8>,
+	max_response_size: usize,
+	timeout: Duration,
+}
+
+/// Event generated by the [`RequestResponsesHandler`].
+pub enum HandlerEvent<TSubstream> {
+	/// A remote sent a request.
+	InboundRequest(InboundRequest<TSubstream>),
+	/// An outbound request has finished.
+	Response {
+		request_id: RequestId,
+		result: Result<Vec<u8>, RequestFailure>,
+	},
+}
+
+/// Implementation of `ProtocolsHandler` for the request-response protocols.
+///
+/// Every request opens its own substream, so that a request that fails or times out never
+/// affects the other protocols of the connection.
+pub struct RequestResponsesHandler<TSubstream> {
+	/// Upgrade for inbound substreams.
+	inbound_upgrade: InboundRequestsUpgrade,
+	/// Requests for which a substream has yet to be opened.
+	queued_requests: VecDeque<OutboundRequest>,
+	/// Number of outbound requests waiting for their response.
+	active_requests: usize,
+	/// Events to report to the behaviour.
+	events: VecDeque<HandlerEvent<TSubstream>>,
+	/// Duration for which the connection is kept alive after the last request.
+	keep_alive_timeout: Duration,
+	/// Time of the last request.
+	last_activity: Instant,
+}
+
+impl<TSubstream> RequestResponsesHandler<TSubstream> {
+	/// Builds a new handler.
+	fn new(inbound_upgrade: InboundRequestsUpgrade, keep_alive_timeout: Duration) -> Self {
+		RequestResponsesHandler {
+			inbound_upgrade,
+			queued_requests: VecDeque::new(),
+			active_requests: 0,
+			events: VecDeque::new(),
+			keep_alive_timeout,
+			last_activity: Instant::now(),
+		}
+	}
+}
+
+impl<TSubstream> ProtocolsHandler for RequestResponsesHandler<TSubstream>
+where
+	TSubstream: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+	type InEvent = OutboundRequest;
+	type OutEvent = HandlerEvent<TSubstream>;
+	type Error = void::Void;
+	type Substream = TSubstream;
+	type InboundProtocol = InboundRequestsUpgrade;
+	type OutboundProtocol = OutboundRequestUpgrade;
+	type OutboundOpenInfo = RequestId;
+
+	fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol> {
+		SubstreamProtocol::new(self.inbound_upgrade.clone())
+	}
+
+	fn inject_fully_negotiated_inbound(&mut self, request: InboundRequest<TSubstream>) {
+		self.last_activity = Instant::now();
+		self.events.push_back(HandlerEvent::InboundRequest(request));
+	}
+
+	fn inject_fully_negotiated_outbound(&mut self, response: Vec<u8>, request_id: RequestId) {
+		self.active_requests -= 1;
+		self.last_activity = Instant::now();
+		self.events.push_back(HandlerEvent::Response { request_id, result: Ok(response) });
+	}
+
+	fn inject_event(&mut self, request: OutboundRequest) {
+		self.queued_requests.push_back(request);
+	}
+
+	fn inject_dial_upgrade_error(
+		&mut self,
+		request_id: RequestId,
+		error: ProtocolsHandlerUpgrErr<ReadOneError>,
+	) {
+		self.active_requests -= 1;
+		self.last_activity = Instant::now();
+
+		let failure = match error {
+			ProtocolsHandlerUpgrErr::Timeout | ProtocolsHandlerUpgrErr::Timer =>
+				RequestFailure::Timeout,
+			ProtocolsHandlerUpgrErr::Upgrade(UpgradeError::Select(NegotiationError::Failed)) =>
+				RequestFailure::UnsupportedProtocol,
+			ProtocolsHandlerUpgrErr::Upgrade(UpgradeError::Apply(ReadOneError::TooLarge { .. })) =>
+				RequestFailure::ResponseTooLarge,
+			ProtocolsHandlerUpgrErr::Upgrade(_) => RequestFailure::Refused,
+		};
+		self.events.push_back(HandlerEvent::Response { request_id, result: Err(failure) });
+	}
+
+	fn connection_keep_alive(&self) -> KeepAlive {
+		if self.active_requests > 0 || !self.queued_requests.is_empty() {
+			KeepAlive::Yes
+		} else {
+			KeepAlive::Until(self.last_activity + self.keep_alive_timeout)
+		}
+	}
+
+	fn poll(
+		&mut self,
+		_: &mut Context,
+	) -> Poll<
+		ProtocolsHandlerEvent<OutboundRequestUpgrade, RequestId, Self::OutEvent, Self::Error>
+	> {
+		if let Some(event) = self.events.pop_front() {
+			return Poll::Ready(ProtocolsHandlerEvent::Custom(event));
+		}
+
+		if let Some(request) = self.queued_requests.pop_front() {
+			self.active_requests += 1;
+			let OutboundRequest { request_id, protocol, payload, max_response_size, timeout } =
+				request;
+			let upgrade = OutboundRequestUpgrade { protocol, payload, max_response_size };
+			return Poll::Ready(ProtocolsHandlerEvent::OutboundSubstreamRequest {
+				protocol: SubstreamProtocol::new(upgrade).with_timeout(timeout),
+				info: request_id,
+			});
+		}
+
+		Poll::Pending
+	}
+}
+
+/// Name of a request-response protocol, as negotiated on a substream.
+#[derive(Debug, Clone)]
+pub struct RequestProtocolName(Cow<'static, str>);
+
+impl ProtocolName for RequestProtocolName {
+	fn protocol_name(&self) -> &[u8] {
+		self.0.as_bytes()
+	}
+}
+
+/// A request read from an inbound substream.
+pub struct InboundRequest<TSubstream> {
+	/// Name of the protocol of the substream.
+	protocol: Cow<'static, str>,
+	/// The request.
+	payload: Vec<u8>,
+	/// The substream to write the response to.
+	substream: Negotiated<TSubstream>,
+}
+
+/// Upgrade for inbound substreams, which accepts all the protocols and reads the request.
+#[derive(Debug, Clone)]
+pub struct InboundRequestsUpgrade {
+	/// Names of the protocols and maximal sizes of their requests.
+	protocols: Arc<HashMap<Cow<'static, str>, usize>>,
+}
+
+impl UpgradeInfo for InboundRequestsUpgrade {
+	type Info = RequestProtocolName;
+	type InfoIter = Vec<RequestProtocolName>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		self.protocols.keys().cloned().map(RequestProtocolName).collect()
+	}
+}
+
+impl<TSubstream> InboundUpgrade<TSubstream> for InboundRequestsUpgrade
+where
+	TSubstream: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+	type Output = InboundRequest<TSubstream>;
+	type Error = ReadOneError;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_inbound(
+		self,
+		mut substream: Negotiated<TSubstream>,
+		protocol: RequestProtocolName,
+	) -> Self::Future {
+		let max_request_size = self.protocols.get(&protocol.0).cloned().unwrap_or(0);
+		async move {
+			let payload = upgrade::read_one(&mut substream, max_request_size).await?;
+			Ok(InboundRequest { protocol: protocol.0, payload, substream })
+		}.boxed()
+	}
+}
+
+/// Upgrade for outbound substreams, which writes the request and reads the response.
+#[derive(Debug)]
+pub struct OutboundRequestUpgrade {
+	protocol: Cow<'static, str>,
+	payload: Vec<u8>,
+	max_response_size: usize,
+}
+
+impl UpgradeInfo for OutboundRequestUpgrade {
+	type Info = RequestProtocolName;
+	type InfoIter = iter::Once<RequestProtocolName>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		iter::once(RequestProtocolName(self.protocol.clone()))
+	}
+}
+
+impl<TSubstream> OutboundUpgrade<TSubstream> for OutboundRequestUpgrade
+where
+	TSubstream: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+	type Output = Vec<u8>;
+	type Error = ReadOneError;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_outbound(
+		self,
+		mut substream: Negotiated<TSubstream>,
+		_: RequestProtocolName,
+	) -> Self::Future {
+		let OutboundRequestUpgrade { payload, max_response_size, .. } = self;
+		async move {
+			upgrade::write_one(&mut substream, payload).await?;
+			let response = upgrade::read_one(&mut substream, max_response_size).await?;
+			if response.is_empty() {
+				// The remote closed the substream without answering.
+				return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+			}
+			Ok(response)
+		}.boxed()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::{executor::block_on, future::{self, Either}, prelude::*};
+	use libp2p::core::{nodes::Substream, transport::{boxed::Boxed, MemoryTransport}, upgrade};
+	use libp2p::core::muxing::StreamMuxerBox;
+	use libp2p::swarm::Swarm;
+	use libp2p::{identity::Keypair, Multiaddr, PeerId, Transport};
+	use std::{borrow::Cow, io, task::Poll, time::Duration};
+	use super::{Event, ProtocolConfig, RequestFailure, RequestResponsesBehaviour};
+
+	type TestSwarm = Swarm<
+		Boxed<(PeerId, StreamMuxerBox), io::Error>,
+		RequestResponsesBehaviour<Substream<StreamMuxerBox>>,
+	>;
+
+	fn protocol(name: &'static str) -> ProtocolConfig {
+		ProtocolConfig {
+			name: Cow::Borrowed(name),
+			max_request_size: 1024,
+			max_response_size: 1024,
+			request_timeout: Duration::from_secs(10),
+			max_concurrent_inbound: 8,
+		}
+	}
+
+	/// Builds a node handling the given protocols and listening on a random memory address.
+	fn build_node(protocols: Vec<ProtocolConfig>) -> (TestSwarm, Multiaddr) {
+		let keypair = Keypair::generate_ed25519();
+		let local_peer_id = keypair.public().into_peer_id();
+		let transport = MemoryTransport
+			.and_then(move |out, endpoint| {
+				let secio = libp2p::secio::SecioConfig::new(keypair);
+				upgrade::apply(out, secio, endpoint, upgrade::Version::V1)
+			})
+			.and_then(|(peer_id, stream), endpoint| {
+				let yamux = libp2p::yamux::Config::default();
+				upgrade::apply(stream, yamux, endpoint, upgrade::Version::V1)
+					.map_ok(|muxer| (peer_id, StreamMuxerBox::new(muxer)))
+			})
+			.timeout(Duration::from_secs(20))
+			.map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+			.boxed();
+
+		let behaviour = RequestResponsesBehaviour::new(protocols);
+		let mut swarm = Swarm::new(transport, behaviour, local_peer_id);
+		let addr: Multiaddr = format!("/memory/{}", rand::random::<u64>()).parse().unwrap();
+		Swarm::listen_on(&mut swarm, addr.clone()).unwrap();
+		(swarm, addr)
+	}
+
+	/// Connects two nodes and sends a request with `protocol` from the first one to the second
+	/// one, which answers with the result of `respond` or refuses the request if it is `None`.
+	fn request(
+		requester_protocols: Vec<ProtocolConfig>,
+		responder_protocols: Vec<ProtocolConfig>,
+		protocol: &'static str,
+		respond: fn(Vec<u8>) -> Option<Vec<u8>>,
+	) -> Result<Vec<u8>, RequestFailure> {
+		let (mut requester, _) = build_node(requester_protocols);
+		let (mut responder, responder_addr) = build_node(responder_protocols);
+		let responder_id = Swarm::local_peer_id(&responder).clone();
+		Swarm::dial_addr(&mut requester, responder_addr).unwrap();
+
+		let responder_task = future::poll_fn(move |cx| -> Poll<()> {
+			loop {
+				match futures::ready!(responder.poll_next_unpin(cx)) {
+					Some(Event::InboundRequest { payload, pending_response, .. }) => {
+						if let Some(response) = respond(payload) {
+							let _ = pending_response.send(response);
+						}
+					},
+					_ => panic!(),
+				}
+			}
+		});
+
+		let mut sent = false;
+		let requester_task = future::poll_fn(move |cx| {
+			loop {
+				match requester.poll_next_unpin(cx) {
+					Poll::Ready(Some(Event::RequestFinished { result, .. })) =>
+						return Poll::Ready(result),
+					Poll::Ready(_) => panic!(),
+					Poll::Pending => {
+						// Only send the request once the nodes are connected.
+						if sent || Swarm::connection_info(&mut requester, &responder_id).is_none() {
+							return Poll::Pending;
+						}
+						requester.send_request(&responder_id, protocol, b"ping".to_vec());
+						sent = true;
+					},
+				}
+			}
+		});
+
+		match block_on(future::select(responder_task, requester_task)) {
+			Either::Left(_) => unreachable!(),
+			Either::Right((result, _)) => result,
+		}
+	}
+
+	#[test]
+	fn answers_requests() {
+		let result = request(vec![protocol("/a")], vec![protocol("/a")], "/a", |payload| {
+			Some(payload.into_iter().rev().collect())
+		});
+		assert_eq!(result, Ok(b"gnip".to_vec()));
+	}
+
+	#[test]
+	fn refused_requests_fail() {
+		let result = request(vec![protocol("/a")], vec![protocol("/a")], "/a", |_| None);
+		assert_eq!(result, Err(RequestFailure::Refused));
+	}
+
+	#[test]
+	fn too_large_responses_fail() {
+		let result = request(vec![protocol("/a")], vec![protocol("/a")], "/a", |_| {
+			Some(vec![0; 2048])
+		});
+		assert_eq!(result, Err(RequestFailure::ResponseTooLarge));
+	}
+
+	#[test]
+	fn unsupported_protocols_fail() {
+		let protocols = vec![protocol("/a"), protocol("/b")];
+		let result = request(protocols, vec![protocol("/b")], "/a", Some);
+		assert_eq!(result, Err(RequestFailure::UnsupportedProtocol));
+	}
+
+	#[test]
+	fn unknown_protocols_fail() {
+		let result = request(vec![protocol("/a")], vec![protocol("/a")], "/b", Some);
+		assert_eq!(result, Err(RequestFailure::UnknownProtocol));
+	}
+}
@@ -25,7 +25,7 @@
 //! The methods of the [`NetworkService`] are implemented by sending a message over a channel,
 //! which is then processed by [`NetworkWorker::poll`].
 
-use std::{collections::{HashMap, HashSet}, fs, marker::PhantomData, io, path::Path};
+use std::{borrow::Cow, collections::{HashMap, HashSet}, fs, marker::PhantomData, io, path::Path};
 use std::sync::{Arc, atomic::{AtomicBool, AtomicUsize, Ordering}};
 use std::pin::Pin;
 use std::task::Poll;
@@ -437,6 +437,9 @@ impl<B: BlockT + 'static, S: NetworkSpecialization<B>, H: ExHashT> NetworkServic
 	/// >			between the remote voluntarily closing a substream or a network error
 	/// >			preventing the message from being delivered.
 	///
+	/// For the same reason, the message is dropped if too many notifications of this protocol
+	/// are already waiting to be sent to the target.
+	///
 	/// The protocol must have been registered with `register_notifications_protocol`.
 	///
 	pub fn write_notification(&self, target: PeerId, engine_id: ConsensusEngineId, message: Vec<u8>) {
@@ -463,17 +466,26 @@ impl<B: BlockT + 'static, S: NetworkSpecialization<B>, H: ExHashT> NetworkServic
 	///
 	/// After that, you can call `write_notifications`.
 	///
+	/// The notifications of the protocol are sent on their own substream, negotiated with the
+	/// given protocol name, for example `/paritytech/grandpa/1`. Each side of a connection sends
+	/// a handshake containing its roles when opening its substream. Events are generated when the
+	/// protocol is opened and closed with a peer, and when a peer sends notifications. Peers that
+	/// don't support the protocol exchange its notifications on the legacy substream instead.
+	///
 	/// Please call `event_stream` before registering a protocol, otherwise you may miss events
 	/// about the protocol that you have registered.
 	///
-	/// You are very strongly encouraged to call this method very early on. Any connection open
-	/// will retain the protocols that were registered then, and not any new one.
+	/// You are very strongly encouraged to call this method very early on. The protocol is
+	/// reported as open with the peers we're already connected to, and its notifications are sent
+	/// to them on the legacy substream until its substream is open.
 	pub fn register_notifications_protocol(
 		&self,
 		engine_id: ConsensusEngineId,
+		protocol_name: impl Into<Cow<'static, str>>,
 	) {
 		let _ = self.to_worker.unbounded_send(ServiceToWorkerMsg::RegisterNotifProtocol {
 			engine_id,
+			protocol_name: protocol_name.into(),
 		});
 	}
 
@@ -682,6 +694,7 @@ enum ServiceToWorkerMsg<B: BlockT, S: NetworkSpecialization<B>> {
 	},
 	RegisterNotifProtocol {
 		engine_id: ConsensusEngineId,
+		protocol_name: Cow<'static, str>,
 	},
 	DisconnectPeer(PeerId),
 }
@@ -760,9 +773,10 @@ impl<B: BlockT + 'static, S: NetworkSpecialization<B>, H: ExHashT> Future for Ne
 				ServiceToWorkerMsg::EventStream(sender) =>
 					this.event_streams.push(sender),
 				ServiceToWorkerMsg::WriteNotification { message, engine_id, target } =>
-					this.network_service.user_protocol_mut().write_notification(target, engine_id, message),
-				ServiceToWorkerMsg::RegisterNotifProtocol { engine_id } => {
-					let events = this.network_service.user_protocol_mut().register_notifications_protocol(engine_id);
+					this.network_service.write_notification(target, engine_id, message),
+				ServiceToWorkerMsg::RegisterNotifProtocol { engine_id, protocol_name } => {
+					let events = this.network_service
+						.register_notifications_protocol(engine_id, protocol_name);
 					for event in events {
 						this.event_streams.retain(|sender| sender.unbounded_send(event.clone()).is_ok());
 					}
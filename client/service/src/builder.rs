@@ -17,11 +17,12 @@
 use crate::{Service, NetworkStatus, NetworkState, error::Error, DEFAULT_PROTOCOL_ID};
 use crate::{SpawnTaskHandle, start_rpc_servers, build_network_future, TransactionPoolAdapter};
 use crate::status_sinks;
+use crate::divergence_log::FileDivergenceLog;
 use crate::config::{Configuration, DatabaseConfig, KeystoreConfig};
 use sc_client_api::{
 	self,
 	BlockchainEvents,
-	backend::RemoteBackend, light::RemoteBlockchain,
+	backend::{RemoteBackend, IntegrityReport}, light::RemoteBlockchain,
 	execution_extensions::ExtensionsFactory,
 };
 use sc_client::Client;
@@ -154,6 +155,18 @@ pub fn new_full_client<TBl, TRtApi, TExecDisp, TCfg, TGen, TCSExt>(
 	new_full_parts(config).map(|parts| parts.0)
 }
 
+/// Sub-directory of the chain config dir compiled runtimes are cached in.
+const DEFAULT_WASM_CACHE_PATH: &str = "wasm-cache";
+
+/// Set up the on-disk cache of compiled runtimes, if the config has a base path.
+fn enable_compilation_cache<TCfg, TGen, TCSExt>(config: &Configuration<TCfg, TGen, TCSExt>) {
+	if let Some(path) = config.in_chain_config_dir(DEFAULT_WASM_CACHE_PATH) {
+		if let Err(e) = sc_executor::enable_compilation_cache(config.wasm_method, &path) {
+			warn!("Compiled runtimes will not be cached on disk: {}", e);
+		}
+	}
+}
+
 fn new_full_parts<TBl, TRtApi, TExecDisp, TCfg, TGen, TCSExt>(
 	config: &Configuration<TCfg, TGen, TCSExt>,
 ) -> Result<TFullParts<TBl, TRtApi, TExecDisp>,	Error> where
@@ -171,10 +184,13 @@ fn new_full_parts<TBl, TRtApi, TExecDisp, TCfg, TGen, TCSExt>(
 		KeystoreConfig::None => return Err("No keystore config provided!".into()),
 	};
 
+	enable_compilation_cache(config);
+
 	let executor = NativeExecutor::<TExecDisp>::new(
 		config.wasm_method,
 		config.default_heap_pages,
-	);
+	).with_max_stack_height(config.max_stack_height)
+	.with_sandbox_backend(config.sandbox_backend);
 
 	let fork_blocks = config.chain_spec
 		.extensions()
@@ -210,6 +226,10 @@ fn new_full_parts<TBl, TRtApi, TExecDisp, TCfg, TGen, TCSExt>(
 			Some(keystore.clone()),
 		);
 
+		if let Some(path) = &config.execution_divergence_log {
+			extensions.set_divergence_log(Arc::new(FileDivergenceLog::open(path)?));
+		}
+
 		sc_client_db::new_client(
 			db_config,
 			executor,
@@ -296,10 +316,13 @@ where TGen: RuntimeGenesis, TCSExt: Extension {
 			KeystoreConfig::None => return Err("No keystore config provided!".into()),
 		};
 
+		enable_compilation_cache(&config);
+
 		let executor = NativeExecutor::<TExecDisp>::new(
 			config.wasm_method,
 			config.default_heap_pages,
-		);
+		).with_max_stack_height(config.max_stack_height)
+		.with_sandbox_backend(config.sandbox_backend);
 
 		let db_storage = {
 			let db_settings = sc_client_db::DatabaseSettings {
@@ -702,6 +725,13 @@ pub trait ServiceBuilderCommand {
 		self,
 		block: BlockId<Self::Block>
 	) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+	/// Check the integrity of the database from block `from` up to block `to` (default: best).
+	fn check_db(
+		&self,
+		from: NumberFor<Self::Block>,
+		to: Option<NumberFor<Self::Block>>,
+	) -> Result<IntegrityReport<Self::Block>, Error>;
 }
 
 impl<TBl, TRtApi, TCfg, TGen, TCSExt, TBackend, TExec, TSc, TImpQu, TNetP, TExPool, TRpc>
@@ -28,6 +28,7 @@ use sp_runtime::traits::{
 use sp_runtime::generic::{BlockId, SignedBlock};
 use codec::{Decode, Encode, IoReader};
 use sc_client::Client;
+use sc_client_api::backend::IntegrityReport;
 use sp_consensus::import_queue::{IncomingBlock, Link, BlockImportError, BlockImportResult, ImportQueue};
 use sp_consensus::BlockOrigin;
 
@@ -303,4 +304,22 @@ impl<
 			Err(e) => Box::pin(future::err(format!("Error reading block: {:?}", e).into())),
 		}
 	}
+
+	fn check_db(
+		&self,
+		from: NumberFor<TBl>,
+		to: Option<NumberFor<TBl>>,
+	) -> Result<IntegrityReport<TBl>, Error> {
+		info!("Checking database integrity from block #{}", from);
+		let start = std::time::Instant::now();
+		let report = self.client.check_integrity(from, to)?;
+		info!(
+			"Checked {} blocks and {} states in {} ms, found {} issues.",
+			report.checked_blocks,
+			report.checked_states,
+			start.elapsed().as_millis(),
+			report.issues.len(),
+		);
+		Ok(report)
+	}
 }
@@ -19,7 +19,7 @@
 pub use sc_client::ExecutionStrategies;
 pub use sc_client_db::{kvdb::KeyValueDB, PruningMode};
 pub use sc_network::config::{ExtTransport, NetworkConfiguration, Roles};
-pub use sc_executor::WasmExecutionMethod;
+pub use sc_executor::{WasmExecutionMethod, SandboxBackend};
 
 use std::{future::Future, path::{PathBuf, Path}, pin::Pin, net::SocketAddr, sync::Arc};
 pub use sc_transaction_pool::txpool::Options as TransactionPoolOptions;
@@ -66,6 +66,10 @@ pub struct Configuration<C, G, E = NoExtension> {
 	pub wasm_method: WasmExecutionMethod,
 	/// Execution strategies.
 	pub execution_strategies: ExecutionStrategies,
+	/// Path of the file native/wasm execution divergences are logged to. `None` if disabled.
+	///
+	/// Only has an effect for execution contexts using the `Both` strategy.
+	pub execution_divergence_log: Option<PathBuf>,
 	/// RPC over HTTP binding address. `None` if disabled.
 	pub rpc_http: Option<SocketAddr>,
 	/// RPC over Websockets binding address. `None` if disabled.
@@ -83,6 +87,10 @@ pub struct Configuration<C, G, E = NoExtension> {
 	pub telemetry_external_transport: Option<ExtTransport>,
 	/// The default number of 64KB pages to allocate for Wasm execution
 	pub default_heap_pages: Option<u64>,
+	/// The deterministic stack height limit injected into the Wasm runtime. `None` if disabled.
+	pub max_stack_height: Option<u32>,
+	/// The backend executing the modules instantiated by the Wasm runtime through `sp-sandbox`.
+	pub sandbox_backend: SandboxBackend,
 	/// Should offchain workers be executed.
 	pub offchain_worker: bool,
 	/// Sentry mode is enabled, the node's role is AUTHORITY but it should not
@@ -175,6 +183,7 @@ impl<C, G, E> Configuration<C, G, E> where
 			pruning: PruningMode::default(),
 			wasm_method: WasmExecutionMethod::Interpreted,
 			execution_strategies: Default::default(),
+			execution_divergence_log: None,
 			rpc_http: None,
 			rpc_ws: None,
 			rpc_ws_max_connections: None,
@@ -183,6 +192,8 @@ impl<C, G, E> Configuration<C, G, E> where
 			telemetry_endpoints: None,
 			telemetry_external_transport: None,
 			default_heap_pages: None,
+			max_stack_height: None,
+			sandbox_backend: Default::default(),
 			offchain_worker: Default::default(),
 			sentry_mode: false,
 			force_authoring: false,
@@ -0,0 +1,103 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A file backed log of native/wasm execution divergences.
+
+use std::{fs::{File, OpenOptions}, io::{self, Write}, path::Path};
+use std::sync::atomic::{AtomicU64, Ordering};
+use parking_lot::Mutex;
+use log::warn;
+use serde_json::json;
+use sc_client_api::execution_extensions::DivergenceLog;
+use sp_core::hexdisplay::HexDisplay;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use sp_state_machine::{ExecutionDivergence, StorageCollection, ChildStorageCollection};
+
+/// Appends every divergence as one JSON object per line to a file.
+pub struct FileDivergenceLog {
+	file: Mutex<File>,
+	count: AtomicU64,
+}
+
+impl FileDivergenceLog {
+	/// Open the log at the given path, appending to it if it already exists.
+	pub fn open(path: &Path) -> io::Result<Self> {
+		if let Some(parent) = path.parent() {
+			std::fs::create_dir_all(parent)?;
+		}
+
+		let file = OpenOptions::new().create(true).append(true).open(path)?;
+		Ok(FileDivergenceLog {
+			file: Mutex::new(file),
+			count: AtomicU64::new(0),
+		})
+	}
+}
+
+impl<Block: BlockT> DivergenceLog<Block> for FileDivergenceLog {
+	fn record(&self, at: &BlockId<Block>, divergence: ExecutionDivergence) {
+		self.count.fetch_add(1, Ordering::Relaxed);
+
+		let (block_hash, block_number) = match at {
+			BlockId::Hash(hash) => (Some(hash.to_string()), None),
+			BlockId::Number(number) => (None, Some(number.to_string())),
+		};
+
+		let entry = json!({
+			"block_hash": block_hash,
+			"block_number": block_number,
+			"method": divergence.method,
+			"call_data": hex(&divergence.call_data),
+			"native_result": result_to_json(&divergence.native_result),
+			"wasm_result": result_to_json(&divergence.wasm_result),
+			"native_changes": changes_to_json(&divergence.native_changes),
+			"wasm_changes": changes_to_json(&divergence.wasm_changes),
+			"native_child_changes": child_changes_to_json(&divergence.native_child_changes),
+			"wasm_child_changes": child_changes_to_json(&divergence.wasm_child_changes),
+		});
+
+		if let Err(e) = writeln!(self.file.lock(), "{}", entry) {
+			warn!("Failed to write execution divergence to log: {:?}", e);
+		}
+	}
+
+	fn count(&self) -> u64 {
+		self.count.load(Ordering::Relaxed)
+	}
+}
+
+fn hex(data: &[u8]) -> String {
+	format!("0x{}", HexDisplay::from(&data))
+}
+
+fn result_to_json(result: &Result<Vec<u8>, String>) -> serde_json::Value {
+	match result {
+		Ok(data) => json!({ "ok": hex(data) }),
+		Err(e) => json!({ "err": e }),
+	}
+}
+
+fn changes_to_json(changes: &StorageCollection) -> serde_json::Value {
+	changes.iter()
+		.map(|(k, v)| json!([hex(k), v.as_ref().map(|v| hex(v))]))
+		.collect()
+}
+
+fn child_changes_to_json(changes: &ChildStorageCollection) -> serde_json::Value {
+	changes.iter()
+		.map(|(sk, changes)| json!([hex(sk), changes_to_json(changes)]))
+		.collect()
+}
@@ -25,6 +25,7 @@ pub mod chain_ops;
 pub mod error;
 
 mod builder;
+mod divergence_log;
 mod status_sinks;
 
 use std::{io, pin::Pin};
@@ -60,6 +61,7 @@ pub use self::builder::{
 	TFullCallExecutor, TLightCallExecutor,
 };
 pub use config::{Configuration, Roles, PruningMode};
+pub use divergence_log::FileDivergenceLog;
 pub use sc_chain_spec::{ChainSpec, Properties, RuntimeGenesis, Extension as ChainSpecExtension};
 pub use sp_transaction_pool::{TransactionPool, TransactionPoolMaintainer, InPoolTransaction, error::IntoPoolError};
 pub use sc_transaction_pool::txpool::Options as TransactionPoolOptions;
@@ -195,6 +195,7 @@ fn node_config<G, E: Clone> (
 		name: format!("Node {}", index),
 		wasm_method: sc_service::config::WasmExecutionMethod::Interpreted,
 		execution_strategies: Default::default(),
+		execution_divergence_log: None,
 		rpc_http: None,
 		rpc_ws: None,
 		rpc_ws_max_connections: None,
@@ -203,6 +204,8 @@ fn node_config<G, E: Clone> (
 		telemetry_endpoints: None,
 		telemetry_external_transport: None,
 		default_heap_pages: None,
+		max_stack_height: None,
+		sandbox_backend: Default::default(),
 		offchain_worker: false,
 		sentry_mode: false,
 		force_authoring: false,
@@ -65,7 +65,7 @@ use sc_block_builder::BlockBuilderApi;
 pub use sc_client_api::{
 	backend::{
 		self, BlockImportOperation, PrunableStateChangesTrieStorage,
-		ClientImportOperation, Finalizer, ImportSummary, NewBlockState,
+		ClientImportOperation, Finalizer, ImportSummary, NewBlockState, IntegrityReport,
 		changes_tries_state_at_block,
 	},
 	client::{
@@ -1213,6 +1213,16 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		Ok(self.backend.revert(n, true)?)
 	}
 
+	/// Checks the integrity of the stored chain from block `from` up to block `to`, or the best
+	/// block if `to` is `None`. See [`Backend::check_integrity`](backend::Backend::check_integrity).
+	pub fn check_integrity(
+		&self,
+		from: NumberFor<Block>,
+		to: Option<NumberFor<Block>>,
+	) -> sp_blockchain::Result<IntegrityReport<Block>> {
+		self.backend.check_integrity(from, to)
+	}
+
 	/// Get usage info about current client.
 	pub fn usage_info(&self) -> ClientInfo<Block> {
 		ClientInfo {
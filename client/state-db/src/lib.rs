@@ -284,6 +284,13 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 		return self.non_canonical.last_canonicalized_block_number()
 	}
 
+	pub fn journaled_blocks(&self) -> Vec<(BlockHash, u64)> {
+		let mut blocks = self.pruning.as_ref()
+			.map_or_else(Vec::new, |pruning| pruning.journaled_blocks());
+		blocks.extend(self.non_canonical.journaled_blocks());
+		blocks
+	}
+
 	pub fn is_pruned(&self, hash: &BlockHash, number: u64) -> bool {
 		match self.mode {
 			PruningMode::ArchiveAll => false,
@@ -457,6 +464,17 @@ impl<BlockHash: Hash, Key: Hash> StateDb<BlockHash, Key> {
 		return self.db.read().is_pruned(hash, number)
 	}
 
+	/// Returns the hash and number of every block kept in the pruning window or in the
+	/// non-canonical overlay.
+	pub fn journaled_blocks(&self) -> Vec<(BlockHash, u64)> {
+		self.db.read().journaled_blocks()
+	}
+
+	/// Returns the pruning mode the database was opened with.
+	pub fn pruning_mode(&self) -> PruningMode {
+		self.db.read().mode.clone()
+	}
+
 	/// Apply all pending changes
 	pub fn apply_pending(&self) {
 		self.db.write().apply_pending();
@@ -589,6 +607,21 @@ mod tests {
 		assert!(db.data_eq(&make_db(&[1, 21, 3, 921, 922, 93, 94])));
 	}
 
+	#[test]
+	fn journaled_blocks_are_read_back() {
+		let (db, sdb) = make_test_db(PruningMode::Constrained(Constraints {
+			max_blocks: Some(2),
+			max_mem: None,
+		}));
+		let blocks = sdb.journaled_blocks();
+		assert_eq!(
+			blocks.iter().map(|(hash, _)| *hash).collect::<Vec<_>>(),
+			vec![H256::from_low_u64_be(21), H256::from_low_u64_be(3), H256::from_low_u64_be(4)],
+		);
+		let reopened = StateDb::<H256, H256>::new(sdb.pruning_mode(), &db).unwrap();
+		assert_eq!(reopened.journaled_blocks(), blocks);
+	}
+
 	#[test]
 	fn detects_incompatible_mode() {
 		let mut db = make_db(&[]);
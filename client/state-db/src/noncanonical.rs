@@ -286,6 +286,20 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 			.unwrap_or_default()
 	}
 
+	/// Returns the hash and number of every block in the overlay, canonicalizations that are
+	/// still pending excluded.
+	pub fn journaled_blocks(&self) -> Vec<(BlockHash, u64)> {
+		let front = self.front_block_number();
+		self.levels
+			.iter()
+			.enumerate()
+			.skip(self.pending_canonicalizations.len())
+			.flat_map(|(index, level)| {
+				level.iter().map(move |overlay| (overlay.hash.clone(), front + index as u64))
+			})
+			.collect()
+	}
+
 	/// Select a top-level root and canonicalized it. Discards all sibling subtrees and the root.
 	/// Returns a set of changes that need to be added to the DB.
 	pub fn canonicalize<E: fmt::Debug>(
@@ -139,6 +139,17 @@ impl<BlockHash: Hash, Key: Hash> RefWindow<BlockHash, Key> {
 		self.death_rows.iter().skip(self.pending_prunings).any(|r| r.hash == *hash)
 	}
 
+	/// Returns the hash and number of every block in the pruning window, pending prunings
+	/// excluded.
+	pub fn journaled_blocks(&self) -> Vec<(BlockHash, u64)> {
+		self.death_rows
+			.iter()
+			.enumerate()
+			.skip(self.pending_prunings)
+			.map(|(index, row)| (row.hash.clone(), self.pending_number + index as u64))
+			.collect()
+	}
+
 	/// Prune next block. Expects at least one block in the window. Adds changes to `commit`.
 	pub fn prune_one(&mut self, commit: &mut CommitSet<Key>) {
 		if let Some(pruned) = self.death_rows.get(self.pending_prunings) {
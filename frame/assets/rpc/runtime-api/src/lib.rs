@@ -0,0 +1,37 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API definition for the assets module.
+//!
+//! This API should be imported and implemented by the runtime
+//! of a node that wants to expose the balances of its assets.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+sp_api::decl_runtime_apis! {
+	/// The API to query the balances of fungible assets.
+	pub trait AssetsApi<AccountId, AssetId, Balance> where
+		AccountId: codec::Codec,
+		AssetId: codec::Codec,
+		Balance: codec::Codec,
+	{
+		/// Get the balance of asset `id` held by `who`.
+		fn balance(id: AssetId, who: AccountId) -> Balance;
+
+		/// Get the total supply of asset `id`.
+		fn total_supply(id: AssetId) -> Balance;
+	}
+}
@@ -21,10 +21,13 @@
 //! ## Overview
 //!
 //! The Assets module provides functionality for asset management of fungible asset classes
-//! with a fixed supply, including:
+//! with a managed supply, including:
 //!
-//! * Asset Issuance
-//! * Asset Transfer
+//! * Asset Creation
+//! * Asset Issuance and Burning
+//! * Asset Transfer, directly or through an approved delegate
+//! * Asset and Account Freezing
+//! * Asset Metadata
 //! * Asset Destruction
 //!
 //! To use it in your runtime, you need to implement the assets [`Trait`](./trait.Trait.html).
@@ -33,31 +36,64 @@
 //!
 //! ### Terminology
 //!
-//! * **Asset issuance:** The creation of a new asset, whose total supply will belong to the
-//!   account that issues the asset.
+//! * **Asset creation:** The creation of a new asset class. The creator becomes its owner and
+//!   has to reserve a deposit for it.
+//! * **Asset issuance:** The minting of new units of an asset into an account by the asset's
+//!   issuer, increasing the total supply.
+//! * **Asset burning:** The removal of units of an asset from an account by the asset's issuer,
+//!   decreasing the total supply.
 //! * **Asset transfer:** The action of transferring assets from one account to another.
-//! * **Asset destruction:** The process of an account removing its entire holding of an asset.
+//! * **Approval:** An allowance given by an account holder to a delegate, which the delegate may
+//!   then transfer from the holder's account to any other account. The holder reserves a deposit
+//!   for each delegate it approves.
+//! * **Freezing:** Preventing permissionless transfers out of an account, or out of every account
+//!   of an asset.
+//! * **Minimum balance:** The smallest balance an account may hold of an asset. Accounts whose
+//!   balance would drop below it are reaped and the remaining dust is moved or burned with it.
+//! * **Asset destruction:** The removal of an asset class without outstanding supply by its
+//!   owner, returning its deposits.
 //! * **Fungible asset:** An asset whose units are interchangeable.
 //! * **Non-fungible asset:** An asset for which each unit has unique characteristics.
 //!
+//! ### Roles
+//!
+//! Every asset class has a team of accounts with different privileges:
+//!
+//! * **Owner:** Can change the team, transfer ownership, set the metadata and destroy the asset.
+//! * **Issuer:** Can mint and burn units of the asset.
+//! * **Admin:** Can thaw accounts and the asset and force transfers between any accounts.
+//! * **Freezer:** Can freeze accounts and the asset.
+//!
 //! ### Goals
 //!
 //! The assets system in Substrate is designed to make the following possible:
 //!
-//! * Issue a unique asset to its creator's account.
-//! * Move assets between accounts.
-//! * Remove an account's balance of an asset when requested by that account's owner and update
-//!   the asset's total supply.
+//! * Create a unique asset class managed by a team of accounts.
+//! * Issue and burn units of an asset.
+//! * Move assets between accounts, either directly or through an approved delegate.
+//! * Keep accounts from holding dust balances of an asset.
+//! * Describe an asset through a name, a symbol and a number of decimals.
 //!
 //! ## Interface
 //!
 //! ### Dispatchable Functions
 //!
-//! * `issue` - Issues the total supply of a new fungible asset to the account of the caller of the function.
+//! * `create` - Creates a new asset class owned by the caller, with a given admin and minimum
+//! balance.
+//! * `destroy` - Destroys an asset class that has no supply left.
+//! * `mint` - Issues new units of an asset into an account; called by the issuer.
+//! * `burn` - Removes units of an asset from an account; called by the issuer.
 //! * `transfer` - Transfers an `amount` of units of fungible asset `id` from the balance of
 //! the function caller's account (`origin`) to a `target` account.
-//! * `destroy` - Destroys the entire holding of a fungible asset `id` associated with the account
-//! that called the function.
+//! * `force_transfer` - Transfers units of an asset between any two accounts; called by the admin.
+//! * `freeze` / `thaw` - Freezes or thaws transfers out of a single account.
+//! * `freeze_asset` / `thaw_asset` - Freezes or thaws transfers of the whole asset.
+//! * `transfer_ownership` - Hands the ownership of an asset to another account.
+//! * `set_team` - Changes the issuer, admin and freezer of an asset.
+//! * `set_metadata` - Sets or clears the name, symbol and decimals of an asset.
+//! * `approve_transfer` - Allows a delegate to transfer units out of the caller's account.
+//! * `cancel_approval` - Revokes an approval given to a delegate.
+//! * `transfer_approved` - Transfers units of an asset out of an account that approved the caller.
 //!
 //! Please refer to the [`Call`](./enum.Call.html) enum and its associated variants for documentation on each function.
 //!
@@ -66,6 +102,7 @@
 //!
 //! * `balance` - Get the asset `id` balance of `who`.
 //! * `total_supply` - Get the total supply of an asset `id`.
+//! * `allowance` - Get the amount of an asset `id` a delegate may still transfer for an owner.
 //!
 //! Please refer to the [`Module`](./struct.Module.html) struct for details on publicly available functions.
 //!
@@ -73,7 +110,6 @@
 //!
 //! The following example shows how to use the Assets module in your runtime by exposing public functions to:
 //!
-//! * Issue a new fungible asset for a token distribution event (airdrop).
 //! * Query the fungible asset holding balance of an account.
 //! * Query the total supply of a fungible asset that has been issued.
 //!
@@ -92,24 +128,10 @@
 //!
 //! decl_module! {
 //! 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
-//! 		pub fn issue_token_airdrop(origin) -> dispatch::DispatchResult {
-//! 			let sender = ensure_signed(origin).map_err(|e| e.as_str())?;
-//!
-//! 			const ACCOUNT_ALICE: u64 = 1;
-//! 			const ACCOUNT_BOB: u64 = 2;
-//! 			const COUNT_AIRDROP_RECIPIENTS: u64 = 2;
-//! 			const TOKENS_FIXED_SUPPLY: u64 = 100;
+//! 		pub fn ensure_holder(origin, id: T::AssetId) -> dispatch::DispatchResult {
+//! 			let sender = ensure_signed(origin)?;
 //!
-//! 			ensure!(!COUNT_AIRDROP_RECIPIENTS.is_zero(), "Divide by zero error.");
-//!
-//! 			let asset_id = Self::next_asset_id();
-//!
-//! 			<NextAssetId<T>>::mutate(|asset_id| *asset_id += 1);
-//! 			<Balances<T>>::insert((asset_id, &ACCOUNT_ALICE), TOKENS_FIXED_SUPPLY / COUNT_AIRDROP_RECIPIENTS);
-//! 			<Balances<T>>::insert((asset_id, &ACCOUNT_BOB), TOKENS_FIXED_SUPPLY / COUNT_AIRDROP_RECIPIENTS);
-//! 			<TotalSupply<T>>::insert(asset_id, TOKENS_FIXED_SUPPLY);
-//!
-//! 			Self::deposit_event(RawEvent::Issued(asset_id, sender, TOKENS_FIXED_SUPPLY));
+//! 			ensure!(!<assets::Module<T>>::balance(id, sender).is_zero(), "Not a holder.");
 //! 			Ok(())
 //! 		}
 //! 	}
@@ -132,10 +154,20 @@
 // Ensure we're `no_std` when compiling for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::{Parameter, decl_module, decl_event, decl_storage, decl_error, ensure};
-use sp_runtime::traits::{Member, SimpleArithmetic, Zero, StaticLookup};
+use sp_std::prelude::*;
+use codec::{Encode, Decode};
+use frame_support::{
+	Parameter, decl_module, decl_event, decl_storage, decl_error, ensure,
+	dispatch::DispatchError, weights::SimpleDispatchInfo,
+	traits::{Currency, ReservableCurrency, Get},
+};
+use sp_runtime::RuntimeDebug;
+use sp_runtime::traits::{
+	Member, SimpleArithmetic, Zero, StaticLookup, One, Saturating, CheckedAdd,
+};
 use frame_system::{self as system, ensure_signed};
-use sp_runtime::traits::One;
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
 
 /// The module configuration trait.
 pub trait Trait: frame_system::Trait {
@@ -147,6 +179,78 @@ pub trait Trait: frame_system::Trait {
 
 	/// The arithmetic type of asset identifier.
 	type AssetId: Parameter + SimpleArithmetic + Default + Copy;
+
+	/// The currency mechanism, used for the deposits of assets and their metadata.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The amount of currency reserved by the owner of an asset class.
+	type AssetDeposit: Get<BalanceOf<Self>>;
+
+	/// The base amount of currency reserved for setting the metadata of an asset.
+	type MetadataDepositBase: Get<BalanceOf<Self>>;
+
+	/// The additional amount of currency reserved per byte of the name and symbol of an asset.
+	type MetadataDepositPerByte: Get<BalanceOf<Self>>;
+
+	/// The maximum length of the name or the symbol of an asset.
+	type StringLimit: Get<u32>;
+
+	/// The amount of currency reserved by an account holder for each approval of a delegate.
+	type ApprovalDeposit: Get<BalanceOf<Self>>;
+}
+
+/// The details of an asset class.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug)]
+pub struct AssetDetails<Balance, AccountId, DepositBalance> {
+	/// Can change `owner`, `issuer`, `admin` and `freezer`, set the metadata and destroy the asset.
+	pub owner: AccountId,
+	/// Can mint and burn units of the asset.
+	pub issuer: AccountId,
+	/// Can thaw accounts and the asset, and force transfers between any accounts.
+	pub admin: AccountId,
+	/// Can freeze accounts and the asset.
+	pub freezer: AccountId,
+	/// The total supply across all accounts.
+	pub supply: Balance,
+	/// The amount of currency reserved by the owner for this asset.
+	pub deposit: DepositBalance,
+	/// The smallest balance an account may hold; accounts below it are reaped.
+	pub min_balance: Balance,
+	/// The number of accounts holding a balance of this asset.
+	pub accounts: u32,
+	/// Whether transfers of this asset are frozen.
+	pub is_frozen: bool,
+}
+
+/// The balance of an account for a given asset.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Default, RuntimeDebug)]
+pub struct AssetBalance<Balance> {
+	/// The balance of the account.
+	pub balance: Balance,
+	/// Whether transfers out of the account are frozen.
+	pub is_frozen: bool,
+}
+
+/// An approval of a delegate to transfer assets out of the account of an owner.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Default, RuntimeDebug)]
+pub struct Approval<Balance, DepositBalance> {
+	/// The amount of the asset the delegate may still transfer.
+	pub amount: Balance,
+	/// The amount of currency reserved by the owner for this approval.
+	pub deposit: DepositBalance,
+}
+
+/// The metadata of an asset.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Default, RuntimeDebug)]
+pub struct AssetMetadata<DepositBalance> {
+	/// The amount of currency reserved by the owner for this metadata.
+	pub deposit: DepositBalance,
+	/// The user friendly name of the asset.
+	pub name: Vec<u8>,
+	/// The ticker symbol of the asset.
+	pub symbol: Vec<u8>,
+	/// The number of decimals the asset uses to represent one unit.
+	pub decimals: u8,
 }
 
 decl_module! {
@@ -154,47 +258,394 @@ decl_module! {
 		type Error = Error<T>;
 
 		fn deposit_event() = default;
-		/// Issue a new class of fungible assets. There are, and will only ever be, `total`
-		/// such assets and they'll all belong to the `origin` initially. It will have an
-		/// identifier `AssetId` instance: this will be specified in the `Issued` event.
-		fn issue(origin, #[compact] total: T::Balance) {
-			let origin = ensure_signed(origin)?;
+
+		/// Create a new class of fungible assets owned by `origin`, with `admin` as its issuer,
+		/// admin and freezer. It will have an identifier `AssetId` instance: this will be
+		/// specified in the `Created` event.
+		///
+		/// `AssetDeposit` is reserved from the owner. Accounts may not hold less than
+		/// `min_balance` of the asset, which must be non-zero.
+		fn create(origin,
+			admin: <T::Lookup as StaticLookup>::Source,
+			#[compact] min_balance: T::Balance
+		) {
+			let owner = ensure_signed(origin)?;
+			let admin = T::Lookup::lookup(admin)?;
+			ensure!(!min_balance.is_zero(), Error::<T>::MinBalanceZero);
+
+			let deposit = T::AssetDeposit::get();
+			T::Currency::reserve(&owner, deposit)?;
 
 			let id = Self::next_asset_id();
 			<NextAssetId<T>>::mutate(|id| *id += One::one());
 
-			<Balances<T>>::insert((id, &origin), total);
-			<TotalSupply<T>>::insert(id, total);
+			<Asset<T>>::insert(id, AssetDetails {
+				owner: owner.clone(),
+				issuer: admin.clone(),
+				admin: admin.clone(),
+				freezer: admin.clone(),
+				supply: Zero::zero(),
+				deposit,
+				min_balance,
+				accounts: 0,
+				is_frozen: false,
+			});
+
+			Self::deposit_event(RawEvent::Created(id, owner, admin));
+		}
+
+		/// Destroy the asset class `id`, which must not have any supply left, returning the
+		/// deposits to its owner.
+		///
+		/// Approvals of the asset are kept, so that their owners can reclaim the deposits with
+		/// `cancel_approval`.
+		///
+		/// Origin must be the owner of the asset.
+		fn destroy(origin, #[compact] id: T::AssetId) {
+			let origin = ensure_signed(origin)?;
+			let details = <Asset<T>>::get(id).ok_or(Error::<T>::Unknown)?;
+			ensure!(origin == details.owner, Error::<T>::NoPermission);
+			ensure!(details.supply.is_zero(), Error::<T>::InUse);
+
+			let metadata = <Metadata<T>>::take(id);
+			T::Currency::unreserve(&details.owner, details.deposit.saturating_add(metadata.deposit));
+
+			<Asset<T>>::remove(id);
+
+			Self::deposit_event(RawEvent::Destroyed(id));
+		}
+
+		/// Mint `amount` new units of asset `id` into the account of `beneficiary`.
+		///
+		/// Origin must be the issuer of the asset.
+		fn mint(origin,
+			#[compact] id: T::AssetId,
+			beneficiary: <T::Lookup as StaticLookup>::Source,
+			#[compact] amount: T::Balance
+		) {
+			let origin = ensure_signed(origin)?;
+			let beneficiary = T::Lookup::lookup(beneficiary)?;
+			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+
+			let mut details = <Asset<T>>::get(id).ok_or(Error::<T>::Unknown)?;
+			ensure!(origin == details.issuer, Error::<T>::NoPermission);
+			details.supply = details.supply.checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+
+			let mut account = <Account<T>>::get(id, &beneficiary);
+			if account.balance.is_zero() {
+				ensure!(amount >= details.min_balance, Error::<T>::BelowMinimum);
+				details.accounts = details.accounts.saturating_add(1);
+			}
+			account.balance = account.balance.checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+
+			<Account<T>>::insert(id, &beneficiary, account);
+			<Asset<T>>::insert(id, details);
 
-			Self::deposit_event(RawEvent::Issued(id, origin, total));
+			Self::deposit_event(RawEvent::Issued(id, beneficiary, amount));
 		}
 
-		/// Move some assets from one holder to another.
+		/// Burn up to `amount` units of asset `id` from the account of `who`. If the remaining
+		/// balance would be below the minimum balance, the whole balance is burned.
+		///
+		/// Origin must be the issuer of the asset.
+		fn burn(origin,
+			#[compact] id: T::AssetId,
+			who: <T::Lookup as StaticLookup>::Source,
+			#[compact] amount: T::Balance
+		) {
+			let origin = ensure_signed(origin)?;
+			let who = T::Lookup::lookup(who)?;
+
+			let mut details = <Asset<T>>::get(id).ok_or(Error::<T>::Unknown)?;
+			ensure!(origin == details.issuer, Error::<T>::NoPermission);
+
+			let account = <Account<T>>::get(id, &who);
+			ensure!(!account.balance.is_zero(), Error::<T>::BalanceZero);
+
+			let mut burned = amount.min(account.balance);
+			let remaining = account.balance - burned;
+			if remaining < details.min_balance {
+				burned = account.balance;
+			}
+
+			details.supply = details.supply.saturating_sub(burned);
+			let balance = account.balance - burned;
+			Self::set_account_balance(id, &who, account, balance, &mut details);
+			<Asset<T>>::insert(id, details);
+
+			Self::deposit_event(RawEvent::Burned(id, who, burned));
+		}
+
+		/// Move some assets from one holder to another. If the remaining balance of the sender
+		/// would be below the minimum balance, the whole balance is transferred.
+		#[weight = SimpleDispatchInfo::FixedNormal(1_000_000)]
 		fn transfer(origin,
 			#[compact] id: T::AssetId,
 			target: <T::Lookup as StaticLookup>::Source,
 			#[compact] amount: T::Balance
 		) {
 			let origin = ensure_signed(origin)?;
-			let origin_account = (id, origin.clone());
-			let origin_balance = <Balances<T>>::get(&origin_account);
 			let target = T::Lookup::lookup(target)?;
-			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
-			ensure!(origin_balance >= amount, Error::<T>::BalanceLow);
 
-			Self::deposit_event(RawEvent::Transferred(id, origin, target.clone(), amount));
-			<Balances<T>>::insert(origin_account, origin_balance - amount);
-			<Balances<T>>::mutate((id, target), |balance| *balance += amount);
+			let transferred = Self::do_transfer(id, &origin, &target, amount, true, true)?;
+			Self::deposit_event(RawEvent::Transferred(id, origin, target, transferred));
 		}
 
-		/// Destroy any assets of `id` owned by `origin`.
-		fn destroy(origin, #[compact] id: T::AssetId) {
+		/// Move some assets from `source` to `dest`, regardless of any freezing.
+		///
+		/// Origin must be the admin of the asset.
+		fn force_transfer(origin,
+			#[compact] id: T::AssetId,
+			source: <T::Lookup as StaticLookup>::Source,
+			dest: <T::Lookup as StaticLookup>::Source,
+			#[compact] amount: T::Balance
+		) {
+			let origin = ensure_signed(origin)?;
+			let source = T::Lookup::lookup(source)?;
+			let dest = T::Lookup::lookup(dest)?;
+
+			let details = <Asset<T>>::get(id).ok_or(Error::<T>::Unknown)?;
+			ensure!(origin == details.admin, Error::<T>::NoPermission);
+
+			let transferred = Self::do_transfer(id, &source, &dest, amount, false, true)?;
+			Self::deposit_event(RawEvent::ForceTransferred(id, source, dest, transferred));
+		}
+
+		/// Disallow further permissionless transfers out of the account of `who`.
+		///
+		/// Origin must be the freezer of the asset.
+		fn freeze(origin, #[compact] id: T::AssetId, who: <T::Lookup as StaticLookup>::Source) {
 			let origin = ensure_signed(origin)?;
-			let balance = <Balances<T>>::take((id, &origin));
-			ensure!(!balance.is_zero(), Error::<T>::BalanceZero);
+			let who = T::Lookup::lookup(who)?;
+
+			let details = <Asset<T>>::get(id).ok_or(Error::<T>::Unknown)?;
+			ensure!(origin == details.freezer, Error::<T>::NoPermission);
+			ensure!(<Account<T>>::exists(id, &who), Error::<T>::BalanceZero);
+
+			<Account<T>>::mutate(id, &who, |account| account.is_frozen = true);
+
+			Self::deposit_event(RawEvent::Frozen(id, who));
+		}
+
+		/// Allow permissionless transfers out of the account of `who` again.
+		///
+		/// Origin must be the admin of the asset.
+		fn thaw(origin, #[compact] id: T::AssetId, who: <T::Lookup as StaticLookup>::Source) {
+			let origin = ensure_signed(origin)?;
+			let who = T::Lookup::lookup(who)?;
+
+			let details = <Asset<T>>::get(id).ok_or(Error::<T>::Unknown)?;
+			ensure!(origin == details.admin, Error::<T>::NoPermission);
+			ensure!(<Account<T>>::exists(id, &who), Error::<T>::BalanceZero);
+
+			<Account<T>>::mutate(id, &who, |account| account.is_frozen = false);
+
+			Self::deposit_event(RawEvent::Thawed(id, who));
+		}
+
+		/// Disallow further permissionless transfers of asset `id` out of any account.
+		///
+		/// Origin must be the freezer of the asset.
+		fn freeze_asset(origin, #[compact] id: T::AssetId) {
+			let origin = ensure_signed(origin)?;
+
+			let mut details = <Asset<T>>::get(id).ok_or(Error::<T>::Unknown)?;
+			ensure!(origin == details.freezer, Error::<T>::NoPermission);
+			details.is_frozen = true;
+			<Asset<T>>::insert(id, details);
+
+			Self::deposit_event(RawEvent::AssetFrozen(id));
+		}
+
+		/// Allow permissionless transfers of asset `id` again.
+		///
+		/// Origin must be the admin of the asset.
+		fn thaw_asset(origin, #[compact] id: T::AssetId) {
+			let origin = ensure_signed(origin)?;
+
+			let mut details = <Asset<T>>::get(id).ok_or(Error::<T>::Unknown)?;
+			ensure!(origin == details.admin, Error::<T>::NoPermission);
+			details.is_frozen = false;
+			<Asset<T>>::insert(id, details);
+
+			Self::deposit_event(RawEvent::AssetThawed(id));
+		}
+
+		/// Hand the ownership of asset `id` to `owner`, moving the reserved deposits with it.
+		///
+		/// Origin must be the owner of the asset.
+		fn transfer_ownership(origin,
+			#[compact] id: T::AssetId,
+			owner: <T::Lookup as StaticLookup>::Source
+		) {
+			let origin = ensure_signed(origin)?;
+			let owner = T::Lookup::lookup(owner)?;
+
+			let mut details = <Asset<T>>::get(id).ok_or(Error::<T>::Unknown)?;
+			ensure!(origin == details.owner, Error::<T>::NoPermission);
+			if details.owner == owner { return Ok(()) }
+
+			let deposit = details.deposit.saturating_add(<Metadata<T>>::get(id).deposit);
+			T::Currency::reserve(&owner, deposit)?;
+			T::Currency::unreserve(&details.owner, deposit);
+
+			details.owner = owner.clone();
+			<Asset<T>>::insert(id, details);
+
+			Self::deposit_event(RawEvent::OwnerChanged(id, owner));
+		}
+
+		/// Change the issuer, admin and freezer of asset `id`.
+		///
+		/// Origin must be the owner of the asset.
+		fn set_team(origin,
+			#[compact] id: T::AssetId,
+			issuer: <T::Lookup as StaticLookup>::Source,
+			admin: <T::Lookup as StaticLookup>::Source,
+			freezer: <T::Lookup as StaticLookup>::Source
+		) {
+			let origin = ensure_signed(origin)?;
+			let issuer = T::Lookup::lookup(issuer)?;
+			let admin = T::Lookup::lookup(admin)?;
+			let freezer = T::Lookup::lookup(freezer)?;
+
+			let mut details = <Asset<T>>::get(id).ok_or(Error::<T>::Unknown)?;
+			ensure!(origin == details.owner, Error::<T>::NoPermission);
+
+			details.issuer = issuer.clone();
+			details.admin = admin.clone();
+			details.freezer = freezer.clone();
+			<Asset<T>>::insert(id, details);
+
+			Self::deposit_event(RawEvent::TeamChanged(id, issuer, admin, freezer));
+		}
+
+		/// Set the `name`, `symbol` and `decimals` of asset `id`. The deposit reserved from the
+		/// owner is adjusted to `MetadataDepositBase` plus `MetadataDepositPerByte` for every
+		/// byte of `name` and `symbol`. If both are empty, the metadata is cleared and its
+		/// deposit returned.
+		///
+		/// Origin must be the owner of the asset.
+		fn set_metadata(origin,
+			#[compact] id: T::AssetId,
+			name: Vec<u8>,
+			symbol: Vec<u8>,
+			decimals: u8
+		) {
+			let origin = ensure_signed(origin)?;
+
+			let limit = T::StringLimit::get() as usize;
+			ensure!(name.len() <= limit && symbol.len() <= limit, Error::<T>::BadMetadata);
+
+			let details = <Asset<T>>::get(id).ok_or(Error::<T>::Unknown)?;
+			ensure!(origin == details.owner, Error::<T>::NoPermission);
+
+			let old_deposit = <Metadata<T>>::get(id).deposit;
+			if name.is_empty() && symbol.is_empty() {
+				T::Currency::unreserve(&origin, old_deposit);
+				<Metadata<T>>::remove(id);
+				Self::deposit_event(RawEvent::MetadataCleared(id));
+				return Ok(())
+			}
+
+			let bytes = (name.len() + symbol.len()) as u32;
+			let new_deposit = T::MetadataDepositPerByte::get()
+				.saturating_mul(bytes.into())
+				.saturating_add(T::MetadataDepositBase::get());
+			if new_deposit > old_deposit {
+				T::Currency::reserve(&origin, new_deposit - old_deposit)?;
+			} else {
+				T::Currency::unreserve(&origin, old_deposit - new_deposit);
+			}
+
+			<Metadata<T>>::insert(id, AssetMetadata {
+				deposit: new_deposit,
+				name: name.clone(),
+				symbol: symbol.clone(),
+				decimals,
+			});
+
+			Self::deposit_event(RawEvent::MetadataSet(id, name, symbol, decimals));
+		}
+
+		/// Approve `delegate` to transfer a further `amount` of asset `id` out of the account of
+		/// `origin`.
+		///
+		/// `ApprovalDeposit` is reserved from `origin` unless it already approved `delegate`.
+		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
+		fn approve_transfer(origin,
+			#[compact] id: T::AssetId,
+			delegate: <T::Lookup as StaticLookup>::Source,
+			#[compact] amount: T::Balance
+		) {
+			let owner = ensure_signed(origin)?;
+			let delegate = T::Lookup::lookup(delegate)?;
+			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+			ensure!(<Asset<T>>::exists(id), Error::<T>::Unknown);
+
+			let mut approval = <Approvals<T>>::get(id, (&owner, &delegate));
+			if approval.deposit.is_zero() {
+				let deposit = T::ApprovalDeposit::get();
+				T::Currency::reserve(&owner, deposit)?;
+				approval.deposit = deposit;
+			}
+			approval.amount = approval.amount.saturating_add(amount);
+			<Approvals<T>>::insert(id, (&owner, &delegate), &approval);
 
-			<TotalSupply<T>>::mutate(id, |total_supply| *total_supply -= balance);
-			Self::deposit_event(RawEvent::Destroyed(id, origin, balance));
+			Self::deposit_event(RawEvent::ApprovedTransfer(id, owner, delegate, approval.amount));
+		}
+
+		/// Revoke any approval of `delegate` to transfer asset `id` out of the account of `origin`,
+		/// returning its deposit.
+		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
+		fn cancel_approval(origin,
+			#[compact] id: T::AssetId,
+			delegate: <T::Lookup as StaticLookup>::Source
+		) {
+			let owner = ensure_signed(origin)?;
+			let delegate = T::Lookup::lookup(delegate)?;
+			ensure!(<Approvals<T>>::exists(id, (&owner, &delegate)), Error::<T>::Unapproved);
+
+			let approval = <Approvals<T>>::take(id, (&owner, &delegate));
+			T::Currency::unreserve(&owner, approval.deposit);
+
+			Self::deposit_event(RawEvent::ApprovalCancelled(id, owner, delegate));
+		}
+
+		/// Transfer `amount` of asset `id` out of the account of `owner`, which must have
+		/// approved `origin` to do so, into the account of `destination`.
+		///
+		/// Unlike `transfer`, the remaining balance of `owner` is never swept along: the transfer
+		/// fails if it would leave `owner` with a non-zero balance below the minimum balance.
+		/// The deposit of the approval is returned once it is used up.
+		#[weight = SimpleDispatchInfo::FixedNormal(1_000_000)]
+		fn transfer_approved(origin,
+			#[compact] id: T::AssetId,
+			owner: <T::Lookup as StaticLookup>::Source,
+			destination: <T::Lookup as StaticLookup>::Source,
+			#[compact] amount: T::Balance
+		) {
+			let delegate = ensure_signed(origin)?;
+			let owner = T::Lookup::lookup(owner)?;
+			let destination = T::Lookup::lookup(destination)?;
+
+			let mut approval = <Approvals<T>>::get(id, (&owner, &delegate));
+			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+			ensure!(approval.amount >= amount, Error::<T>::Unapproved);
+
+			let transferred = Self::do_transfer(id, &owner, &destination, amount, true, false)?;
+
+			approval.amount = approval.amount.saturating_sub(transferred);
+			if approval.amount.is_zero() {
+				<Approvals<T>>::remove(id, (&owner, &delegate));
+				T::Currency::unreserve(&owner, approval.deposit);
+			} else {
+				<Approvals<T>>::insert(id, (&owner, &delegate), approval);
+			}
+
+			Self::deposit_event(
+				RawEvent::TransferredApproved(id, owner, delegate, destination, transferred)
+			);
 		}
 	}
 }
@@ -205,12 +656,42 @@ decl_event! {
 		<T as Trait>::Balance,
 		<T as Trait>::AssetId,
 	{
+		/// Some asset class was created. (asset_id, owner, admin)
+		Created(AssetId, AccountId, AccountId),
 		/// Some assets were issued.
 		Issued(AssetId, AccountId, Balance),
 		/// Some assets were transferred.
 		Transferred(AssetId, AccountId, AccountId, Balance),
-		/// Some assets were destroyed.
-		Destroyed(AssetId, AccountId, Balance),
+		/// Some assets were burned.
+		Burned(AssetId, AccountId, Balance),
+		/// Some assets were transferred by the admin.
+		ForceTransferred(AssetId, AccountId, AccountId, Balance),
+		/// The team of an asset changed. (asset_id, issuer, admin, freezer)
+		TeamChanged(AssetId, AccountId, AccountId, AccountId),
+		/// The owner of an asset changed.
+		OwnerChanged(AssetId, AccountId),
+		/// Some account was frozen.
+		Frozen(AssetId, AccountId),
+		/// Some account was thawed.
+		Thawed(AssetId, AccountId),
+		/// Some asset was frozen.
+		AssetFrozen(AssetId),
+		/// Some asset was thawed.
+		AssetThawed(AssetId),
+		/// An asset class was destroyed.
+		Destroyed(AssetId),
+		/// The metadata of an asset was set. (asset_id, name, symbol, decimals)
+		MetadataSet(AssetId, Vec<u8>, Vec<u8>, u8),
+		/// The metadata of an asset was cleared.
+		MetadataCleared(AssetId),
+		/// A delegate was approved to transfer assets of an owner. (asset_id, owner, delegate,
+		/// total approved amount)
+		ApprovedTransfer(AssetId, AccountId, AccountId, Balance),
+		/// An approval was cancelled. (asset_id, owner, delegate)
+		ApprovalCancelled(AssetId, AccountId, AccountId),
+		/// A delegate transferred assets of an owner. (asset_id, owner, delegate, destination,
+		/// amount)
+		TransferredApproved(AssetId, AccountId, AccountId, AccountId, Balance),
 	}
 }
 
@@ -222,17 +703,40 @@ decl_error! {
 		BalanceLow,
 		/// Balance should be non-zero
 		BalanceZero,
+		/// The signing account has no permission to do the operation
+		NoPermission,
+		/// The given asset ID is unknown
+		Unknown,
+		/// The account or the asset is frozen
+		Frozen,
+		/// The asset still has some supply
+		InUse,
+		/// The resulting balance would be below the minimum balance of the asset
+		BelowMinimum,
+		/// The minimum balance of an asset should be non-zero
+		MinBalanceZero,
+		/// The supply or a balance would overflow
+		Overflow,
+		/// The name or symbol is too long
+		BadMetadata,
+		/// No approval exists that would allow the transfer
+		Unapproved,
 	}
 }
 
 decl_storage! {
 	trait Store for Module<T: Trait> as Assets {
-		/// The number of units of assets held by any given account.
-		Balances: map (T::AssetId, T::AccountId) => T::Balance;
+		/// Details of an asset.
+		Asset get(fn asset): map T::AssetId => Option<AssetDetails<T::Balance, T::AccountId, BalanceOf<T>>>;
+		/// The holdings of an account for a given asset.
+		Account: double_map T::AssetId, hasher(blake2_256) T::AccountId => AssetBalance<T::Balance>;
+		/// The approvals of delegates to transfer assets, keyed by asset and (owner, delegate).
+		Approvals: double_map T::AssetId, hasher(blake2_256) (T::AccountId, T::AccountId)
+			=> Approval<T::Balance, BalanceOf<T>>;
+		/// Metadata of an asset.
+		Metadata get(fn metadata): map T::AssetId => AssetMetadata<BalanceOf<T>>;
 		/// The next asset identifier up for grabs.
 		NextAssetId get(fn next_asset_id): T::AssetId;
-		/// The total unit supply of an asset.
-		TotalSupply: map T::AssetId => T::Balance;
 	}
 }
 
@@ -242,12 +746,86 @@ impl<T: Trait> Module<T> {
 
 	/// Get the asset `id` balance of `who`.
 	pub fn balance(id: T::AssetId, who: T::AccountId) -> T::Balance {
-		<Balances<T>>::get((id, who))
+		<Account<T>>::get(id, who).balance
 	}
 
 	/// Get the total supply of an asset `id`.
 	pub fn total_supply(id: T::AssetId) -> T::Balance {
-		<TotalSupply<T>>::get(id)
+		<Asset<T>>::get(id).map(|details| details.supply).unwrap_or_default()
+	}
+
+	/// Get the amount of asset `id` that `delegate` may still transfer out of the account of
+	/// `owner`.
+	pub fn allowance(id: T::AssetId, owner: T::AccountId, delegate: T::AccountId) -> T::Balance {
+		<Approvals<T>>::get(id, (owner, delegate)).amount
+	}
+
+	/// Move `amount` of asset `id` from `source` to `dest`, returning the amount actually moved.
+	///
+	/// If the balance of `source` would drop below the minimum balance, its whole balance is
+	/// moved and the account is reaped if `sweep_dust` is set; otherwise the transfer fails.
+	/// Freezing is only respected if `check_frozen` is set.
+	fn do_transfer(
+		id: T::AssetId,
+		source: &T::AccountId,
+		dest: &T::AccountId,
+		amount: T::Balance,
+		check_frozen: bool,
+		sweep_dust: bool,
+	) -> Result<T::Balance, DispatchError> {
+		ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+
+		let mut details = <Asset<T>>::get(id).ok_or(Error::<T>::Unknown)?;
+		let source_account = <Account<T>>::get(id, source);
+		if check_frozen {
+			ensure!(!details.is_frozen && !source_account.is_frozen, Error::<T>::Frozen);
+		}
+		ensure!(source_account.balance >= amount, Error::<T>::BalanceLow);
+
+		if source == dest {
+			return Ok(amount)
+		}
+
+		let mut amount = amount;
+		let remainder = source_account.balance - amount;
+		if !remainder.is_zero() && remainder < details.min_balance {
+			ensure!(sweep_dust, Error::<T>::BelowMinimum);
+			amount = source_account.balance;
+		}
+
+		let dest_account = <Account<T>>::get(id, dest);
+		let dest_balance = dest_account.balance.checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+		ensure!(dest_balance >= details.min_balance, Error::<T>::BelowMinimum);
+
+		let source_balance = source_account.balance - amount;
+		Self::set_account_balance(id, source, source_account, source_balance, &mut details);
+		Self::set_account_balance(id, dest, dest_account, dest_balance, &mut details);
+		<Asset<T>>::insert(id, details);
+
+		Ok(amount)
+	}
+
+	/// Set the balance of `who` to `balance`, reaping the account if it is zero and keeping the
+	/// number of accounts in `details` up to date.
+	fn set_account_balance(
+		id: T::AssetId,
+		who: &T::AccountId,
+		mut account: AssetBalance<T::Balance>,
+		balance: T::Balance,
+		details: &mut AssetDetails<T::Balance, T::AccountId, BalanceOf<T>>,
+	) {
+		if account.balance.is_zero() && !balance.is_zero() {
+			details.accounts = details.accounts.saturating_add(1);
+		}
+		if balance.is_zero() {
+			if !account.balance.is_zero() {
+				details.accounts = details.accounts.saturating_sub(1);
+			}
+			<Account<T>>::remove(id, who);
+		} else {
+			account.balance = balance;
+			<Account<T>>::insert(id, who, account);
+		}
 	}
 }
 
@@ -294,32 +872,113 @@ mod tests {
 		type Version = ();
 		type ModuleToIndex = ();
 	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 0;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+	}
+	impl pallet_balances::Trait for Test {
+		type Balance = u64;
+		type OnFreeBalanceZero = ();
+		type OnReapAccount = System;
+		type OnNewAccount = ();
+		type Event = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+	}
+	parameter_types! {
+		pub const AssetDeposit: u64 = 1;
+		pub const MetadataDepositBase: u64 = 1;
+		pub const MetadataDepositPerByte: u64 = 1;
+		pub const StringLimit: u32 = 8;
+		pub const ApprovalDeposit: u64 = 1;
+	}
 	impl Trait for Test {
 		type Event = ();
 		type Balance = u64;
 		type AssetId = u32;
+		type Currency = Balances;
+		type AssetDeposit = AssetDeposit;
+		type MetadataDepositBase = MetadataDepositBase;
+		type MetadataDepositPerByte = MetadataDepositPerByte;
+		type StringLimit = StringLimit;
+		type ApprovalDeposit = ApprovalDeposit;
 	}
+	type System = frame_system::Module<Test>;
+	type Balances = pallet_balances::Module<Test>;
 	type Assets = Module<Test>;
 
 	// This function basically just builds a genesis storage key/value store according to
 	// our desired mockup.
 	fn new_test_ext() -> sp_io::TestExternalities {
-		frame_system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+		let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		pallet_balances::GenesisConfig::<Test> {
+			balances: vec![
+				(1, 100),
+				(2, 100),
+			],
+		}.assimilate_storage(&mut t).unwrap();
+		t.into()
+	}
+
+	/// Create asset 0 owned by and administered by account 1, and mint 100 units into it.
+	fn create_and_mint(min_balance: u64) {
+		assert_ok!(Assets::create(Origin::signed(1), 1, min_balance));
+		assert_ok!(Assets::mint(Origin::signed(1), 0, 1, 100));
 	}
 
 	#[test]
 	fn issuing_asset_units_to_issuer_should_work() {
 		new_test_ext().execute_with(|| {
-			assert_ok!(Assets::issue(Origin::signed(1), 100));
+			create_and_mint(1);
 			assert_eq!(Assets::balance(0, 1), 100);
+			assert_eq!(Assets::total_supply(0), 100);
+			assert_eq!(Balances::reserved_balance(&1), 1);
+		});
+	}
+
+	#[test]
+	fn creating_asset_with_zero_min_balance_should_not_work() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(Assets::create(Origin::signed(1), 1, 0), Error::<Test>::MinBalanceZero);
+		});
+	}
+
+	#[test]
+	fn minting_and_burning_requires_issuer() {
+		new_test_ext().execute_with(|| {
+			create_and_mint(1);
+			assert_noop!(Assets::mint(Origin::signed(2), 0, 2, 100), Error::<Test>::NoPermission);
+			assert_noop!(Assets::burn(Origin::signed(2), 0, 1, 100), Error::<Test>::NoPermission);
+
+			assert_ok!(Assets::set_team(Origin::signed(1), 0, 2, 1, 1));
+			assert_ok!(Assets::mint(Origin::signed(2), 0, 2, 100));
+			assert_ok!(Assets::burn(Origin::signed(2), 0, 1, 40));
+			assert_eq!(Assets::balance(0, 1), 60);
+			assert_eq!(Assets::balance(0, 2), 100);
+			assert_eq!(Assets::total_supply(0), 160);
+		});
+	}
+
+	#[test]
+	fn burning_below_min_balance_reaps_account() {
+		new_test_ext().execute_with(|| {
+			create_and_mint(10);
+			assert_ok!(Assets::burn(Origin::signed(1), 0, 1, 95));
+			assert_eq!(Assets::balance(0, 1), 0);
+			assert_eq!(Assets::total_supply(0), 0);
+			assert_eq!(Assets::asset(0).unwrap().accounts, 0);
+			assert_noop!(Assets::burn(Origin::signed(1), 0, 1, 1), Error::<Test>::BalanceZero);
 		});
 	}
 
 	#[test]
 	fn querying_total_supply_should_work() {
 		new_test_ext().execute_with(|| {
-			assert_ok!(Assets::issue(Origin::signed(1), 100));
-			assert_eq!(Assets::balance(0, 1), 100);
+			create_and_mint(1);
 			assert_ok!(Assets::transfer(Origin::signed(1), 0, 2, 50));
 			assert_eq!(Assets::balance(0, 1), 50);
 			assert_eq!(Assets::balance(0, 2), 50);
@@ -327,31 +986,20 @@ mod tests {
 			assert_eq!(Assets::balance(0, 1), 50);
 			assert_eq!(Assets::balance(0, 2), 19);
 			assert_eq!(Assets::balance(0, 3), 31);
-			assert_ok!(Assets::destroy(Origin::signed(3), 0));
+			assert_ok!(Assets::burn(Origin::signed(1), 0, 3, 31));
 			assert_eq!(Assets::total_supply(0), 69);
-		});
-	}
-
-	#[test]
-	fn transferring_amount_above_available_balance_should_work() {
-		new_test_ext().execute_with(|| {
-			assert_ok!(Assets::issue(Origin::signed(1), 100));
-			assert_eq!(Assets::balance(0, 1), 100);
-			assert_ok!(Assets::transfer(Origin::signed(1), 0, 2, 50));
-			assert_eq!(Assets::balance(0, 1), 50);
-			assert_eq!(Assets::balance(0, 2), 50);
+			assert_eq!(Assets::asset(0).unwrap().accounts, 2);
 		});
 	}
 
 	#[test]
 	fn transferring_amount_more_than_available_balance_should_not_work() {
 		new_test_ext().execute_with(|| {
-			assert_ok!(Assets::issue(Origin::signed(1), 100));
-			assert_eq!(Assets::balance(0, 1), 100);
+			create_and_mint(1);
 			assert_ok!(Assets::transfer(Origin::signed(1), 0, 2, 50));
 			assert_eq!(Assets::balance(0, 1), 50);
 			assert_eq!(Assets::balance(0, 2), 50);
-			assert_ok!(Assets::destroy(Origin::signed(1), 0));
+			assert_ok!(Assets::burn(Origin::signed(1), 0, 1, 50));
 			assert_eq!(Assets::balance(0, 1), 0);
 			assert_noop!(Assets::transfer(Origin::signed(1), 0, 1, 50), Error::<Test>::BalanceLow);
 		});
@@ -360,8 +1008,7 @@ mod tests {
 	#[test]
 	fn transferring_less_than_one_unit_should_not_work() {
 		new_test_ext().execute_with(|| {
-			assert_ok!(Assets::issue(Origin::signed(1), 100));
-			assert_eq!(Assets::balance(0, 1), 100);
+			create_and_mint(1);
 			assert_noop!(Assets::transfer(Origin::signed(1), 0, 2, 0), Error::<Test>::AmountZero);
 		});
 	}
@@ -369,27 +1016,160 @@ mod tests {
 	#[test]
 	fn transferring_more_units_than_total_supply_should_not_work() {
 		new_test_ext().execute_with(|| {
-			assert_ok!(Assets::issue(Origin::signed(1), 100));
-			assert_eq!(Assets::balance(0, 1), 100);
+			create_and_mint(1);
 			assert_noop!(Assets::transfer(Origin::signed(1), 0, 2, 101), Error::<Test>::BalanceLow);
 		});
 	}
 
 	#[test]
-	fn destroying_asset_balance_with_positive_balance_should_work() {
+	fn transferring_below_min_balance_should_not_work_or_moves_dust() {
 		new_test_ext().execute_with(|| {
-			assert_ok!(Assets::issue(Origin::signed(1), 100));
-			assert_eq!(Assets::balance(0, 1), 100);
+			create_and_mint(10);
+			assert_noop!(Assets::transfer(Origin::signed(1), 0, 2, 9), Error::<Test>::BelowMinimum);
+			assert_ok!(Assets::transfer(Origin::signed(1), 0, 2, 91));
+			assert_eq!(Assets::balance(0, 1), 0);
+			assert_eq!(Assets::balance(0, 2), 100);
+			assert_eq!(Assets::asset(0).unwrap().accounts, 1);
+		});
+	}
+
+	#[test]
+	fn frozen_accounts_and_assets_cannot_transfer() {
+		new_test_ext().execute_with(|| {
+			create_and_mint(1);
+			assert_ok!(Assets::transfer(Origin::signed(1), 0, 2, 50));
+			assert_noop!(Assets::freeze(Origin::signed(2), 0, 1), Error::<Test>::NoPermission);
+			assert_noop!(Assets::freeze(Origin::signed(1), 0, 3), Error::<Test>::BalanceZero);
+
+			assert_ok!(Assets::freeze(Origin::signed(1), 0, 1));
+			assert_noop!(Assets::transfer(Origin::signed(1), 0, 2, 10), Error::<Test>::Frozen);
+			assert_ok!(Assets::transfer(Origin::signed(2), 0, 1, 10));
+			assert_ok!(Assets::thaw(Origin::signed(1), 0, 1));
+			assert_ok!(Assets::transfer(Origin::signed(1), 0, 2, 10));
+
+			assert_ok!(Assets::freeze_asset(Origin::signed(1), 0));
+			assert_noop!(Assets::transfer(Origin::signed(2), 0, 1, 10), Error::<Test>::Frozen);
+			assert_ok!(Assets::force_transfer(Origin::signed(1), 0, 2, 1, 10));
+			assert_ok!(Assets::thaw_asset(Origin::signed(1), 0));
+			assert_ok!(Assets::transfer(Origin::signed(2), 0, 1, 10));
+			assert_eq!(Assets::balance(0, 1), 70);
+			assert_eq!(Assets::balance(0, 2), 30);
+		});
+	}
+
+	#[test]
+	fn approved_transfers_should_work() {
+		new_test_ext().execute_with(|| {
+			create_and_mint(1);
+			assert_noop!(
+				Assets::transfer_approved(Origin::signed(2), 0, 1, 3, 10),
+				Error::<Test>::Unapproved,
+			);
+			assert_ok!(Assets::approve_transfer(Origin::signed(1), 0, 2, 30));
+			assert_eq!(Balances::reserved_balance(&1), 2);
+			assert_noop!(
+				Assets::transfer_approved(Origin::signed(2), 0, 1, 3, 31),
+				Error::<Test>::Unapproved,
+			);
+			assert_ok!(Assets::transfer_approved(Origin::signed(2), 0, 1, 3, 20));
+			assert_eq!(Assets::balance(0, 1), 80);
+			assert_eq!(Assets::balance(0, 3), 20);
+			assert_eq!(Assets::allowance(0, 1, 2), 10);
+
+			assert_ok!(Assets::cancel_approval(Origin::signed(1), 0, 2));
+			assert_eq!(Assets::allowance(0, 1, 2), 0);
+			assert_eq!(Balances::reserved_balance(&1), 1);
+			assert_noop!(Assets::cancel_approval(Origin::signed(1), 0, 2), Error::<Test>::Unapproved);
+		});
+	}
+
+	#[test]
+	fn approval_deposits_should_work() {
+		new_test_ext().execute_with(|| {
+			create_and_mint(1);
+			assert_ok!(Assets::approve_transfer(Origin::signed(1), 0, 2, 10));
+			assert_ok!(Assets::approve_transfer(Origin::signed(1), 0, 2, 10));
+			assert_eq!(Assets::allowance(0, 1, 2), 20);
+			assert_eq!(Balances::reserved_balance(&1), 2);
+			assert_ok!(Assets::approve_transfer(Origin::signed(1), 0, 3, 10));
+			assert_eq!(Balances::reserved_balance(&1), 3);
+
+			// Using up an approval returns its deposit.
+			assert_ok!(Assets::transfer_approved(Origin::signed(2), 0, 1, 4, 20));
+			assert_eq!(Assets::allowance(0, 1, 2), 0);
+			assert_eq!(Balances::reserved_balance(&1), 2);
+
+			// Approvals outlive their asset, so that their deposits can be reclaimed.
+			assert_ok!(Assets::burn(Origin::signed(1), 0, 1, 80));
+			assert_ok!(Assets::burn(Origin::signed(1), 0, 4, 20));
 			assert_ok!(Assets::destroy(Origin::signed(1), 0));
+			assert_eq!(Balances::reserved_balance(&1), 1);
+			assert_ok!(Assets::cancel_approval(Origin::signed(1), 0, 3));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+		});
+	}
+
+	#[test]
+	fn approved_transfers_do_not_move_dust() {
+		new_test_ext().execute_with(|| {
+			create_and_mint(10);
+			assert_ok!(Assets::approve_transfer(Origin::signed(1), 0, 2, 95));
+
+			// The remaining 5 units of the owner are below the minimum balance.
+			assert_noop!(
+				Assets::transfer_approved(Origin::signed(2), 0, 1, 3, 95),
+				Error::<Test>::BelowMinimum,
+			);
+			assert_eq!(Assets::allowance(0, 1, 2), 95);
+
+			assert_ok!(Assets::transfer_approved(Origin::signed(2), 0, 1, 3, 90));
+			assert_eq!(Assets::balance(0, 1), 10);
+			assert_eq!(Assets::balance(0, 3), 90);
+			assert_eq!(Assets::allowance(0, 1, 2), 5);
+		});
+	}
+
+	#[test]
+	fn metadata_deposits_should_work() {
+		new_test_ext().execute_with(|| {
+			create_and_mint(1);
+			assert_noop!(
+				Assets::set_metadata(Origin::signed(2), 0, b"Token".to_vec(), b"TKN".to_vec(), 12),
+				Error::<Test>::NoPermission,
+			);
+			assert_noop!(
+				Assets::set_metadata(Origin::signed(1), 0, b"Long token".to_vec(), b"TKN".to_vec(), 12),
+				Error::<Test>::BadMetadata,
+			);
+			assert_ok!(Assets::set_metadata(Origin::signed(1), 0, b"Token".to_vec(), b"TKN".to_vec(), 12));
+			assert_eq!(Assets::metadata(0).decimals, 12);
+			assert_eq!(Balances::reserved_balance(&1), 10);
+
+			assert_ok!(Assets::set_metadata(Origin::signed(1), 0, b"Tok".to_vec(), b"T".to_vec(), 12));
+			assert_eq!(Balances::reserved_balance(&1), 6);
+
+			assert_ok!(Assets::set_metadata(Origin::signed(1), 0, vec![], vec![], 0));
+			assert_eq!(Assets::metadata(0), Default::default());
+			assert_eq!(Balances::reserved_balance(&1), 1);
 		});
 	}
 
 	#[test]
-	fn destroying_asset_balance_with_zero_balance_should_not_work() {
+	fn ownership_and_destruction_should_work() {
 		new_test_ext().execute_with(|| {
-			assert_ok!(Assets::issue(Origin::signed(1), 100));
-			assert_eq!(Assets::balance(0, 2), 0);
-			assert_noop!(Assets::destroy(Origin::signed(2), 0), Error::<Test>::BalanceZero);
+			create_and_mint(1);
+			assert_ok!(Assets::set_metadata(Origin::signed(1), 0, b"Token".to_vec(), b"TKN".to_vec(), 12));
+			assert_noop!(Assets::transfer_ownership(Origin::signed(2), 0, 2), Error::<Test>::NoPermission);
+			assert_ok!(Assets::transfer_ownership(Origin::signed(1), 0, 2));
+			assert_eq!(Balances::reserved_balance(&1), 0);
+			assert_eq!(Balances::reserved_balance(&2), 10);
+
+			assert_noop!(Assets::destroy(Origin::signed(1), 0), Error::<Test>::NoPermission);
+			assert_noop!(Assets::destroy(Origin::signed(2), 0), Error::<Test>::InUse);
+			assert_ok!(Assets::burn(Origin::signed(1), 0, 1, 100));
+			assert_ok!(Assets::destroy(Origin::signed(2), 0));
+			assert_eq!(Balances::reserved_balance(&2), 0);
+			assert!(Assets::asset(0).is_none());
 		});
 	}
 }
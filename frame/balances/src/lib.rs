@@ -67,8 +67,6 @@
 //! simply dropped, it should automatically maintain any book-keeping such as total issuance.)
 //! - **Lock:** A freeze on a specified amount of an account's free balance until a specified block number. Multiple
 //! locks always operate over the same funds, so they "overlay" rather than "stack".
-//! - **Vesting:** Similar to a lock, this is another, but independent, liquidity restriction that reduces linearly
-//! over time.
 //!
 //! ### Implementations
 //!
@@ -94,10 +92,6 @@
 //! - `transfer` - Transfer some liquid free balance to another account.
 //! - `set_balance` - Set the balances of a given account. The origin of this call must be root.
 //!
-//! ### Public Functions
-//!
-//! - `vesting_balance` - Get the amount that is currently being vested and cannot be transferred out of this account.
-//!
 //! ## Usage
 //!
 //! The following examples show how to use the Balances module in your custom module.
@@ -164,10 +158,11 @@ use sp_std::{cmp, result, mem, fmt::Debug};
 use codec::{Codec, Encode, Decode};
 use frame_support::{
 	StorageValue, Parameter, decl_event, decl_storage, decl_module, decl_error,
+	storage::migration::{StorageIterator, get_storage_value, put_storage_value},
 	traits::{
 		UpdateBalanceOutcome, Currency, OnFreeBalanceZero, OnReapAccount, OnUnbalanced, TryDrop,
 		WithdrawReason, WithdrawReasons, LockIdentifier, LockableCurrency, ExistenceRequirement,
-		Imbalance, SignedImbalance, ReservableCurrency, Get, VestingCurrency,
+		Imbalance, SignedImbalance, ReservableCurrency, Get,
 	},
 	weights::SimpleDispatchInfo,
 };
@@ -187,6 +182,10 @@ mod tests;
 
 pub use self::imbalances::{PositiveImbalance, NegativeImbalance};
 
+/// The lock identifier of the vesting module, under which `on_runtime_upgrade` locks the vesting
+/// schedules of the previous layout.
+const VESTING_ID: LockIdentifier = *b"vesting ";
+
 pub trait Subtrait<I: Instance = DefaultInstance>: frame_system::Trait {
 	/// The balance of an account.
 	type Balance: Parameter + Member + SimpleArithmetic + Codec + Default + Copy +
@@ -287,8 +286,6 @@ decl_event!(
 
 decl_error! {
 	pub enum Error for Module<T: Trait<I>, I: Instance> {
-		/// Vesting balance too high to send value
-		VestingBalance,
 		/// Account liquidity restrictions prevent withdrawal
 		LiquidityRestrictions,
 		/// Got an overflow after adding
@@ -299,16 +296,17 @@ decl_error! {
 		ExistentialDeposit,
 		/// Transfer/payment would kill account
 		KeepAlive,
-		/// A vesting schedule already exists for this account
-		ExistingVestingSchedule,
 		/// Beneficiary account must pre-exist
 		DeadAccount,
+		/// Vesting balance too high to send value
+		VestingBalance,
 	}
 }
 
-/// Struct to encode the vesting schedule of an individual account.
+/// A vesting schedule of the previous storage layout, which `on_runtime_upgrade` moves into the
+/// vesting module's storage. It shares its encoding with the vesting module's schedules.
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
-pub struct VestingSchedule<Balance, BlockNumber> {
+pub struct LegacyVestingSchedule<Balance, BlockNumber> {
 	/// Locked amount at genesis.
 	pub locked: Balance,
 	/// Amount that gets unlocked every block after `starting_block`.
@@ -317,7 +315,9 @@ pub struct VestingSchedule<Balance, BlockNumber> {
 	pub starting_block: BlockNumber,
 }
 
-impl<Balance: SimpleArithmetic + Copy, BlockNumber: SimpleArithmetic + Copy> VestingSchedule<Balance, BlockNumber> {
+impl<Balance: SimpleArithmetic + Copy, BlockNumber: SimpleArithmetic + Copy>
+	LegacyVestingSchedule<Balance, BlockNumber>
+{
 	/// Amount locked at block `n`.
 	pub fn locked_at(&self, n: BlockNumber) -> Balance
 		where Balance: From<BlockNumber>
@@ -349,33 +349,6 @@ decl_storage! {
 			config.balances.iter().fold(Zero::zero(), |acc: T::Balance, &(_, n)| acc + n)
 		}): T::Balance;
 
-		/// Information regarding the vesting of a given account.
-		pub Vesting get(fn vesting) build(|config: &GenesisConfig<T, I>| {
-			// Generate initial vesting configuration
-			// * who - Account which we are generating vesting configuration for
-			// * begin - Block when the account will start to vest
-			// * length - Number of blocks from `begin` until fully vested
-			// * liquid - Number of units which can be spent before vesting begins
-			config.vesting.iter().filter_map(|&(ref who, begin, length, liquid)| {
-				let length = <T::Balance as From<T::BlockNumber>>::from(length);
-
-				config.balances.iter()
-					.find(|&&(ref w, _)| w == who)
-					.map(|&(_, balance)| {
-						// Total genesis `balance` minus `liquid` equals funds locked for vesting
-						let locked = balance.saturating_sub(liquid);
-						// Number of units unlocked per block after `begin`
-						let per_block = locked / length.max(sp_runtime::traits::One::one());
-
-						(who.clone(), VestingSchedule {
-							locked: locked,
-							per_block: per_block,
-							starting_block: begin
-						})
-					})
-			}).collect::<Vec<_>>()
-		}): map T::AccountId => Option<VestingSchedule<T::Balance, T::BlockNumber>>;
-
 		/// The 'free' balance of a given account.
 		///
 		/// This is the only balance that matters in terms of most operations on tokens. It
@@ -406,11 +379,13 @@ decl_storage! {
 
 		/// Any liquidity locks on some account balances.
 		pub Locks get(fn locks): map T::AccountId => Vec<BalanceLock<T::Balance, T::BlockNumber>>;
+
+		/// Whether the vesting schedules of the previous layout have been moved into the vesting
+		/// module. Always true for chains started with this layout.
+		IsUpgraded build(|_: &GenesisConfig<T, I>| true): bool;
 	}
 	add_extra_genesis {
 		config(balances): Vec<(T::AccountId, T::Balance)>;
-		config(vesting): Vec<(T::AccountId, T::BlockNumber, T::BlockNumber, T::Balance)>;
-		// ^^ begin, length, amount liquid at genesis
 		build(|config: &GenesisConfig<T, I>| {
 			for (_, balance) in &config.balances {
 				assert!(
@@ -437,6 +412,10 @@ decl_module! {
 
 		fn deposit_event() = default;
 
+		fn on_runtime_upgrade() {
+			Self::migrate_vesting();
+		}
+
 		/// Transfer some liquid free balance to another account.
 		///
 		/// `transfer` will set the `FreeBalance` of the sender and receiver.
@@ -557,6 +536,54 @@ decl_module! {
 impl<T: Trait<I>, I: Instance> Module<T, I> {
 	// PRIVATE MUTABLES
 
+	/// Move the vesting schedules of the previous layout into the `Vesting` map of the vesting
+	/// module and lock what they still lock under the vesting module's lock identifier. Fully
+	/// vested schedules are dropped. No-op once done.
+	///
+	/// Both maps are keyed by the `blake2_256` hash of the account, which is all that is known of
+	/// it here, so the items are moved by the hash of their key.
+	fn migrate_vesting() {
+		if IsUpgraded::<I>::get() {
+			return
+		}
+
+		let now = <frame_system::Module<T>>::block_number();
+		let prefix = I::PREFIX.as_bytes();
+		let schedules = StorageIterator::<LegacyVestingSchedule<T::Balance, T::BlockNumber>>::new(
+			prefix,
+			b"Vesting",
+		).drain();
+		for (hash, schedule) in schedules {
+			let locked = schedule.locked_at(now);
+			if locked.is_zero() {
+				continue
+			}
+
+			let mut vesting: Vec<LegacyVestingSchedule<_, _>> =
+				get_storage_value(b"Vesting", b"Vesting", &hash).unwrap_or_default();
+			vesting.insert(0, schedule);
+			put_storage_value(b"Vesting", b"Vesting", &hash, vesting);
+
+			let mut locks = get_storage_value::<Vec<BalanceLock<T::Balance, T::BlockNumber>>>(
+				prefix,
+				b"Locks",
+				&hash,
+			).unwrap_or_default();
+			match locks.iter_mut().find(|lock| lock.id == VESTING_ID) {
+				Some(lock) => lock.amount = lock.amount.saturating_add(locked),
+				None => locks.push(BalanceLock {
+					id: VESTING_ID,
+					amount: locked,
+					until: T::BlockNumber::max_value(),
+					reasons: WithdrawReason::Transfer | WithdrawReason::Reserve,
+				}),
+			}
+			put_storage_value(prefix, b"Locks", &hash, locks);
+		}
+
+		IsUpgraded::<I>::put(true);
+	}
+
 	/// Set the reserved balance of an account to some new value. Will enforce `ExistentialDeposit`
 	/// law, annulling the account as needed.
 	///
@@ -932,7 +959,7 @@ where
 	}
 
 	// Ensure that an account can withdraw from their free balance given any existing withdrawal
-	// restrictions like locks and vesting balance.
+	// restrictions like locks.
 	// Is a no-op if amount to be withdrawn is zero.
 	//
 	// # <weight>
@@ -946,11 +973,6 @@ where
 		new_balance: T::Balance,
 	) -> DispatchResult {
 		if amount.is_zero() { return Ok(()) }
-		if reasons.intersects(WithdrawReason::Reserve | WithdrawReason::Transfer)
-			&& Self::vesting_balance(who) > new_balance
-		{
-			Err(Error::<T, I>::VestingBalance)?
-		}
 		let locks = Self::locks(who);
 		if locks.is_empty() {
 			return Ok(())
@@ -1309,52 +1331,6 @@ where
 	}
 }
 
-impl<T: Trait<I>, I: Instance> VestingCurrency<T::AccountId> for Module<T, I>
-where
-	T::Balance: MaybeSerializeDeserialize + Debug
-{
-	type Moment = T::BlockNumber;
-
-	/// Get the amount that is currently being vested and cannot be transferred out of this account.
-	fn vesting_balance(who: &T::AccountId) -> T::Balance {
-		if let Some(v) = Self::vesting(who) {
-			Self::free_balance(who)
-				.min(v.locked_at(<frame_system::Module<T>>::block_number()))
-		} else {
-			Zero::zero()
-		}
-	}
-
-	/// Adds a vesting schedule to a given account.
-	///
-	/// If there already exists a vesting schedule for the given account, an `Err` is returned
-	/// and nothing is updated.
-	/// Is a no-op if the amount to be vested is zero.
-	fn add_vesting_schedule(
-		who: &T::AccountId,
-		locked: T::Balance,
-		per_block: T::Balance,
-		starting_block: T::BlockNumber
-	) -> DispatchResult {
-		if locked.is_zero() { return Ok(()) }
-		if <Vesting<T, I>>::exists(who) {
-			Err(Error::<T, I>::ExistingVestingSchedule)?
-		}
-		let vesting_schedule = VestingSchedule {
-			locked,
-			per_block,
-			starting_block
-		};
-		<Vesting<T, I>>::insert(who, vesting_schedule);
-		Ok(())
-	}
-
-	/// Remove a vesting schedule for a given account.
-	fn remove_vesting_schedule(who: &T::AccountId) {
-		<Vesting<T, I>>::remove(who);
-	}
-}
-
 impl<T: Trait<I>, I: Instance> IsDeadAccount<T::AccountId> for Module<T, I>
 where
 	T::Balance: MaybeSerializeDeserialize + Debug
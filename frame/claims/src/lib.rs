@@ -0,0 +1,646 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Claims Module
+//!
+//! - [`claims::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! A module for distributing tokens to the holders of a token on Ethereum, e.g. an ERC-20 whose
+//! balances are migrated to this chain. Each Ethereum address is allotted an amount, either at
+//! genesis or later through `mint_claim`, and optionally a vesting schedule which is applied to
+//! the funds once they are claimed.
+//!
+//! The holder of an Ethereum address claims their allotment by signing the message
+//!
+//! ```nocompile
+//! "\x19Ethereum Signed Message:\n" ++ len(Prefix ++ hex(dest)) ++ Prefix ++ hex(dest)
+//! ```
+//!
+//! with their Ethereum key, where `Prefix` is the `Prefix` configured for the module and
+//! `hex(dest)` is the lower-case, unprefixed hex of the SCALE encoded destination account. This is
+//! the format used by the `eth_sign` family of Ethereum RPCs, so existing wallets can produce it.
+//!
+//! Claimants usually have no funds on this chain yet, hence `claim` is dispatched as an unsigned
+//! transaction. The signature is checked in `ValidateUnsigned` before the transaction enters the
+//! pool.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `claim` - Claim the allotment of an Ethereum address to a given account.
+//! - `mint_claim` - Allot some funds, and optionally a vesting schedule, to an Ethereum address.
+//!
+//! [`Call`]: ./enum.Call.html
+//! [`Trait`]: ./trait.Trait.html
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_std::prelude::*;
+use sp_io::{hashing::keccak_256, crypto::secp256k1_ecdsa_recover};
+use frame_support::{decl_event, decl_storage, decl_module, decl_error};
+use frame_support::weights::SimpleDispatchInfo;
+use frame_support::traits::{Currency, Get, VestingSchedule};
+use frame_system::{self as system, ensure_root, ensure_none};
+use codec::{Encode, Decode};
+#[cfg(feature = "std")]
+use serde::{self, Serialize, Deserialize, Serializer, Deserializer};
+use sp_runtime::{
+	RuntimeDebug, traits::{Zero, CheckedSub, Saturating},
+	transaction_validity::{
+		TransactionLongevity, TransactionValidity, ValidTransaction, InvalidTransaction,
+	},
+};
+
+type CurrencyOf<T> = <<T as Trait>::VestingSchedule as VestingSchedule<
+	<T as frame_system::Trait>::AccountId
+>>::Currency;
+type BalanceOf<T> = <CurrencyOf<T> as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+pub trait Trait: frame_system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The vesting schedules applied to claimed funds. Its currency is the one that is paid out.
+	type VestingSchedule: VestingSchedule<Self::AccountId, Moment=Self::BlockNumber>;
+
+	/// The text which goes in front of the destination account in the signed message, e.g.
+	/// `b"Pay DOTs to the Polkadot account:"`.
+	type Prefix: Get<&'static [u8]>;
+}
+
+/// An Ethereum address (i.e. 20 bytes, used to represent an Ethereum account).
+///
+/// This gets serialized to the 0x-prefixed hex representation.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, Default, RuntimeDebug)]
+pub struct EthereumAddress(pub [u8; 20]);
+
+#[cfg(feature = "std")]
+impl Serialize for EthereumAddress {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+		let hex: String = rustc_hex::ToHex::to_hex(&self.0[..]);
+		serializer.serialize_str(&format!("0x{}", hex))
+	}
+}
+
+#[cfg(feature = "std")]
+impl<'de> Deserialize<'de> for EthereumAddress {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+		let base_string = String::deserialize(deserializer)?;
+		let offset = if base_string.starts_with("0x") { 2 } else { 0 };
+		let s = &base_string[offset..];
+		if s.len() != 40 {
+			Err(serde::de::Error::custom(
+				"Bad length of Ethereum address (should be 42 including '0x')"
+			))?;
+		}
+		let raw: Vec<u8> = rustc_hex::FromHex::from_hex(s)
+			.map_err(|e| serde::de::Error::custom(format!("{:?}", e)))?;
+		let mut r = Self::default();
+		r.0.copy_from_slice(&raw);
+		Ok(r)
+	}
+}
+
+/// A recoverable ECDSA signature over secp256k1, as produced by Ethereum wallets: `r ++ s ++ v`.
+#[derive(Encode, Decode, Clone)]
+pub struct EcdsaSignature(pub [u8; 65]);
+
+impl PartialEq for EcdsaSignature {
+	fn eq(&self, other: &Self) -> bool {
+		&self.0[..] == &other.0[..]
+	}
+}
+
+impl sp_std::fmt::Debug for EcdsaSignature {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter<'_>) -> sp_std::fmt::Result {
+		write!(f, "EcdsaSignature({:?})", &self.0[..])
+	}
+}
+
+/// Custom validity errors used while validating unsigned `claim` transactions.
+#[repr(u8)]
+pub enum ValidityError {
+	/// The Ethereum signature is invalid.
+	InvalidEthereumSignature = 0,
+	/// The signer has no claim.
+	SignerHasNoClaim = 1,
+}
+
+impl From<ValidityError> for u8 {
+	fn from(err: ValidityError) -> Self {
+		err as u8
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		Balance = BalanceOf<T>,
+		AccountId = <T as frame_system::Trait>::AccountId
+	{
+		/// Someone claimed some funds: destination account, Ethereum address and amount.
+		Claimed(AccountId, EthereumAddress, Balance),
+	}
+);
+
+decl_error! {
+	/// Error for the claims module.
+	pub enum Error for Module<T: Trait> {
+		/// Invalid Ethereum signature.
+		InvalidEthereumSignature,
+		/// Ethereum address has no claim.
+		SignerHasNoClaim,
+		/// The destination is already vesting and cannot be the target of a further vesting claim.
+		DestinationVesting,
+		/// There's not enough in the pot to pay out some unvested amount. Generally implies a
+		/// logic error.
+		PotUnderflow,
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Claims {
+		/// The amount yet to be claimed by each Ethereum address.
+		Claims get(fn claims) build(|config: &GenesisConfig<T>| {
+			config.claims.iter().map(|(a, b)| (a.clone(), b.clone())).collect::<Vec<_>>()
+		}): map hasher(blake2_256) EthereumAddress => Option<BalanceOf<T>>;
+		/// The total amount yet to be claimed.
+		Total get(fn total) build(|config: &GenesisConfig<T>| {
+			config.claims.iter()
+				.fold(Zero::zero(), |acc: BalanceOf<T>, &(_, n)| acc.saturating_add(n))
+		}): BalanceOf<T>;
+		/// Vesting schedule for a claim.
+		/// First balance is the total amount that should be held for vesting.
+		/// Second balance is how much should be unlocked per block.
+		/// The block number is when the vesting should start.
+		Vesting get(fn vesting) config():
+			map hasher(blake2_256) EthereumAddress
+			=> Option<(BalanceOf<T>, BalanceOf<T>, T::BlockNumber)>;
+	}
+	add_extra_genesis {
+		config(claims): Vec<(EthereumAddress, BalanceOf<T>)>;
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		/// The Prefix that is used in signed Ethereum messages for this network.
+		const Prefix: &[u8] = T::Prefix::get();
+
+		fn deposit_event() = default;
+
+		/// Make a claim to collect the allotment of the Ethereum address that signed `dest`.
+		///
+		/// The dispatch origin for this call must be _None_.
+		///
+		/// Unsigned validation ensures that the signature is valid and that the signer has a claim,
+		/// so no fees are charged for this call.
+		///
+		/// Parameters:
+		/// - `dest`: The account which receives the claimed funds.
+		/// - `ethereum_signature`: The signature of an Ethereum signed message matching the format
+		///   described in the module documentation.
+		///
+		/// If the claim has a vesting schedule it is applied to `dest`, which fails the claim if
+		/// `dest` cannot take any further vesting schedule.
+		///
+		/// # <weight>
+		/// - One signature recovery and two `keccak_256` hashes.
+		/// - Up to five storage reads and writes, one event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(1_000_000)]
+		fn claim(origin, dest: T::AccountId, ethereum_signature: EcdsaSignature) {
+			ensure_none(origin)?;
+
+			let data = dest.using_encoded(to_ascii_hex);
+			let signer = Self::eth_recover(&ethereum_signature, &data)
+				.ok_or(Error::<T>::InvalidEthereumSignature)?;
+
+			let balance_due = <Claims<T>>::get(&signer)
+				.ok_or(Error::<T>::SignerHasNoClaim)?;
+
+			let new_total = Self::total().checked_sub(&balance_due)
+				.ok_or(Error::<T>::PotUnderflow)?;
+
+			// check if this claim should have a vesting schedule.
+			if let Some(vs) = <Vesting<T>>::get(&signer) {
+				// if this fails, destination account already has as many vesting schedules as it
+				// may, and this claim should not be processed.
+				T::VestingSchedule::add_vesting_schedule(&dest, vs.0, vs.1, vs.2)
+					.map_err(|_| Error::<T>::DestinationVesting)?;
+			}
+
+			CurrencyOf::<T>::deposit_creating(&dest, balance_due);
+			<Total<T>>::put(new_total);
+			<Claims<T>>::remove(&signer);
+			<Vesting<T>>::remove(&signer);
+
+			// let's deposit an event to let the outside world know this happened.
+			Self::deposit_event(RawEvent::Claimed(dest, signer, balance_due));
+		}
+
+		/// Add a new claim, if you are root.
+		///
+		/// An existing claim of `who` is replaced, and so is its vesting schedule unless
+		/// `vesting_schedule` is `None`.
+		///
+		/// # <weight>
+		/// - Up to three storage reads and writes.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(30_000)]
+		fn mint_claim(origin,
+			who: EthereumAddress,
+			value: BalanceOf<T>,
+			vesting_schedule: Option<(BalanceOf<T>, BalanceOf<T>, T::BlockNumber)>,
+		) {
+			ensure_root(origin)?;
+
+			let previous = <Claims<T>>::get(&who).unwrap_or_else(Zero::zero);
+			<Total<T>>::mutate(|t| *t = t.saturating_sub(previous).saturating_add(value));
+			<Claims<T>>::insert(who, value);
+			if let Some(vs) = vesting_schedule {
+				<Vesting<T>>::insert(who, vs);
+			}
+		}
+	}
+}
+
+/// Converts the given binary data into lower-case ASCII-encoded hex, without a `0x` prefix.
+fn to_ascii_hex(data: &[u8]) -> Vec<u8> {
+	let mut r = Vec::with_capacity(data.len() * 2);
+	let mut push_nibble = |n| r.push(if n < 10 { b'0' + n } else { b'a' - 10 + n });
+	for &b in data.iter() {
+		push_nibble(b / 16);
+		push_nibble(b % 16);
+	}
+	r
+}
+
+impl<T: Trait> Module<T> {
+	/// Constructs the message that Ethereum RPC's `personal_sign` and `eth_sign` would sign.
+	fn ethereum_signable_message(what: &[u8]) -> Vec<u8> {
+		let prefix = T::Prefix::get();
+		let mut l = prefix.len() + what.len();
+		let mut rev = Vec::new();
+		while l > 0 {
+			rev.push(b'0' + (l % 10) as u8);
+			l /= 10;
+		}
+		let mut v = b"\x19Ethereum Signed Message:\n".to_vec();
+		v.extend(rev.into_iter().rev());
+		v.extend_from_slice(&prefix[..]);
+		v.extend_from_slice(what);
+		v
+	}
+
+	/// Attempts to recover the Ethereum address from a message signature signed by using
+	/// the Ethereum RPC's `personal_sign` and `eth_sign`.
+	fn eth_recover(s: &EcdsaSignature, what: &[u8]) -> Option<EthereumAddress> {
+		let msg = keccak_256(&Self::ethereum_signable_message(what));
+		let mut res = EthereumAddress::default();
+		res.0.copy_from_slice(&keccak_256(&secp256k1_ecdsa_recover(&s.0, &msg).ok()?[..])[12..]);
+		Some(res)
+	}
+}
+
+impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
+	type Call = Call<T>;
+
+	fn validate_unsigned(call: &Self::Call) -> TransactionValidity {
+		const PRIORITY: u64 = 100;
+
+		if let Call::claim(account, ethereum_signature) = call {
+			let data = account.using_encoded(to_ascii_hex);
+			let signer = match Self::eth_recover(&ethereum_signature, &data) {
+				Some(s) => s,
+				None => return InvalidTransaction::Custom(
+					ValidityError::InvalidEthereumSignature.into()
+				).into(),
+			};
+
+			if !<Claims<T>>::exists(&signer) {
+				return InvalidTransaction::Custom(ValidityError::SignerHasNoClaim.into()).into();
+			}
+
+			Ok(ValidTransaction {
+				priority: PRIORITY,
+				requires: vec![],
+				provides: vec![("claims", signer).encode()],
+				longevity: TransactionLongevity::max_value(),
+				propagate: true,
+			})
+		} else {
+			InvalidTransaction::Call.into()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use secp256k1;
+	use frame_support::{
+		assert_ok, assert_noop, impl_outer_origin, parameter_types, weights::Weight,
+		unsigned::ValidateUnsigned,
+	};
+	use sp_core::H256;
+	use sp_runtime::{
+		Perbill, testing::Header, traits::{BlakeTwo256, IdentityLookup, ConvertInto, BadOrigin},
+	};
+
+	impl_outer_origin! {
+		pub enum Origin for Test  where system = frame_system {}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+	impl frame_system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Call = ();
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+		type ModuleToIndex = ();
+	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 1;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+	}
+	impl pallet_balances::Trait for Test {
+		type Balance = u64;
+		type OnFreeBalanceZero = ();
+		type OnReapAccount = System;
+		type OnNewAccount = ();
+		type Event = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+	}
+	parameter_types! {
+		pub const MinVestedTransfer: u64 = 0;
+		pub const MaxVestingSchedules: u32 = 1;
+	}
+	impl pallet_vesting::Trait for Test {
+		type Event = ();
+		type Currency = Balances;
+		type BlockNumberToBalance = ConvertInto;
+		type MinVestedTransfer = MinVestedTransfer;
+		type MaxVestingSchedules = MaxVestingSchedules;
+	}
+	parameter_types! {
+		pub const Prefix: &'static [u8] = b"Pay RUSTs to the TEST account:";
+	}
+	impl Trait for Test {
+		type Event = ();
+		type VestingSchedule = Vesting;
+		type Prefix = Prefix;
+	}
+	type System = frame_system::Module<Test>;
+	type Balances = pallet_balances::Module<Test>;
+	type Vesting = pallet_vesting::Module<Test>;
+	type Claims = Module<Test>;
+
+	fn alice() -> secp256k1::SecretKey {
+		secp256k1::SecretKey::parse(&keccak_256(b"Alice")).unwrap()
+	}
+	fn bob() -> secp256k1::SecretKey {
+		secp256k1::SecretKey::parse(&keccak_256(b"Bob")).unwrap()
+	}
+	fn public(secret: &secp256k1::SecretKey) -> secp256k1::PublicKey {
+		secp256k1::PublicKey::from_secret_key(secret)
+	}
+	fn eth(secret: &secp256k1::SecretKey) -> EthereumAddress {
+		let mut res = EthereumAddress::default();
+		res.0.copy_from_slice(&keccak_256(&public(secret).serialize()[1..65])[12..]);
+		res
+	}
+	fn sig(secret: &secp256k1::SecretKey, what: &[u8]) -> EcdsaSignature {
+		let msg = keccak_256(&Claims::ethereum_signable_message(&to_ascii_hex(what)[..]));
+		let (sig, recovery_id) = secp256k1::sign(&secp256k1::Message::parse(&msg), secret);
+		let mut r = [0u8; 65];
+		r[0..64].copy_from_slice(&sig.serialize()[..]);
+		r[64] = recovery_id.serialize();
+		EcdsaSignature(r)
+	}
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		// we use default for brevity, but you can configure as desired if needed.
+		pallet_balances::GenesisConfig::<Test>::default().assimilate_storage(&mut t).unwrap();
+		GenesisConfig::<Test>{
+			claims: vec![(eth(&alice()), 100)],
+			vesting: vec![],
+		}.assimilate_storage(&mut t).unwrap();
+		t.into()
+	}
+
+	#[test]
+	fn basic_setup_works() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Claims::total(), 100);
+			assert_eq!(Claims::claims(&eth(&alice())), Some(100));
+			assert_eq!(Claims::claims(&eth(&bob())), None);
+			assert_eq!(Claims::vesting(&eth(&alice())), None);
+		});
+	}
+
+	#[test]
+	fn signable_message_has_ethereum_prefix_and_length() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(to_ascii_hex(&[0x01, 0xab, 0xf0]), b"01abf0".to_vec());
+			assert_eq!(
+				Claims::ethereum_signable_message(b"01abf0"),
+				b"\x19Ethereum Signed Message:\n36Pay RUSTs to the TEST account:01abf0".to_vec(),
+			);
+		});
+	}
+
+	#[test]
+	fn claiming_works() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Balances::free_balance(42), 0);
+			assert_ok!(Claims::claim(Origin::NONE, 42, sig(&alice(), &42u64.encode())));
+			assert_eq!(Balances::free_balance(&42), 100);
+			assert_eq!(Claims::total(), 0);
+			assert_eq!(Claims::claims(&eth(&alice())), None);
+		});
+	}
+
+	#[test]
+	fn add_claim_works() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				Claims::mint_claim(Origin::signed(42), eth(&bob()), 200, None),
+				BadOrigin,
+			);
+			assert_eq!(Balances::free_balance(42), 0);
+			assert_noop!(
+				Claims::claim(Origin::NONE, 69, sig(&bob(), &69u64.encode())),
+				Error::<Test>::SignerHasNoClaim,
+			);
+			assert_ok!(Claims::mint_claim(Origin::ROOT, eth(&bob()), 200, None));
+			assert_eq!(Claims::total(), 300);
+			assert_ok!(Claims::claim(Origin::NONE, 69, sig(&bob(), &69u64.encode())));
+			assert_eq!(Balances::free_balance(&69), 200);
+			assert_eq!(Vesting::vesting_balance(&69), None);
+			assert_eq!(Claims::total(), 100);
+		});
+	}
+
+	#[test]
+	fn replacing_a_claim_keeps_the_total() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Claims::mint_claim(Origin::ROOT, eth(&alice()), 150, None));
+			assert_eq!(Claims::total(), 150);
+			assert_ok!(Claims::claim(Origin::NONE, 42, sig(&alice(), &42u64.encode())));
+			assert_eq!(Balances::free_balance(&42), 150);
+			assert_eq!(Claims::total(), 0);
+		});
+	}
+
+	#[test]
+	fn add_claim_with_vesting_works() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				Claims::mint_claim(Origin::signed(42), eth(&bob()), 200, Some((50, 10, 1))),
+				BadOrigin,
+			);
+			assert_eq!(Balances::free_balance(42), 0);
+			assert_ok!(Claims::mint_claim(Origin::ROOT, eth(&bob()), 200, Some((50, 10, 1))));
+			assert_ok!(Claims::claim(Origin::NONE, 69, sig(&bob(), &69u64.encode())));
+			assert_eq!(Balances::free_balance(&69), 200);
+			assert_eq!(Vesting::vesting_balance(&69), Some(50));
+			assert_eq!(Claims::vesting(&eth(&bob())), None);
+		});
+	}
+
+	#[test]
+	fn origin_signed_claiming_fail() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Balances::free_balance(42), 0);
+			assert_noop!(
+				Claims::claim(Origin::signed(42), 42, sig(&alice(), &42u64.encode())),
+				BadOrigin,
+			);
+		});
+	}
+
+	#[test]
+	fn double_claiming_doesnt_work() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Balances::free_balance(42), 0);
+			assert_ok!(Claims::claim(Origin::NONE, 42, sig(&alice(), &42u64.encode())));
+			assert_noop!(
+				Claims::claim(Origin::NONE, 42, sig(&alice(), &42u64.encode())),
+				Error::<Test>::SignerHasNoClaim,
+			);
+		});
+	}
+
+	#[test]
+	fn claiming_while_vested_doesnt_work() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Claims::mint_claim(Origin::ROOT, eth(&alice()), 100, Some((50, 10, 1))));
+			assert_ok!(Claims::mint_claim(Origin::ROOT, eth(&bob()), 200, Some((50, 10, 1))));
+			assert_ok!(Claims::claim(Origin::NONE, 69, sig(&bob(), &69u64.encode())));
+			assert_eq!(Claims::total(), 100);
+
+			// the destination already has as many vesting schedules as it may.
+
+			assert_noop!(
+				Claims::claim(Origin::NONE, 69, sig(&alice(), &69u64.encode())),
+				Error::<Test>::DestinationVesting,
+			);
+			assert_eq!(Claims::total(), 100);
+			assert_eq!(Claims::claims(&eth(&alice())), Some(100));
+		});
+	}
+
+	#[test]
+	fn non_sender_sig_doesnt_work() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Balances::free_balance(42), 0);
+			assert_noop!(
+				Claims::claim(Origin::NONE, 42, sig(&alice(), &69u64.encode())),
+				Error::<Test>::SignerHasNoClaim,
+			);
+		});
+	}
+
+	#[test]
+	fn non_claimant_doesnt_work() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Balances::free_balance(42), 0);
+			assert_noop!(
+				Claims::claim(Origin::NONE, 42, sig(&bob(), &69u64.encode())),
+				Error::<Test>::SignerHasNoClaim,
+			);
+		});
+	}
+
+	#[test]
+	fn validate_unsigned_works() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(
+				<Module<Test>>::validate_unsigned(&Call::claim(1, sig(&alice(), &1u64.encode()))),
+				Ok(ValidTransaction {
+					priority: 100,
+					requires: vec![],
+					provides: vec![("claims", eth(&alice())).encode()],
+					longevity: TransactionLongevity::max_value(),
+					propagate: true,
+				})
+			);
+			assert_eq!(
+				<Module<Test>>::validate_unsigned(&Call::claim(0, EcdsaSignature([0; 65]))),
+				InvalidTransaction::Custom(ValidityError::InvalidEthereumSignature.into()).into(),
+			);
+			assert_eq!(
+				<Module<Test>>::validate_unsigned(&Call::claim(1, sig(&bob(), &1u64.encode()))),
+				InvalidTransaction::Custom(ValidityError::SignerHasNoClaim.into()).into(),
+			);
+			assert_eq!(
+				<Module<Test>>::validate_unsigned(&Call::claim(0, sig(&bob(), &1u64.encode()))),
+				InvalidTransaction::Custom(ValidityError::SignerHasNoClaim.into()).into(),
+			);
+		});
+	}
+}
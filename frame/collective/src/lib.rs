@@ -18,7 +18,21 @@
 //! through dispatched calls from one of two specialised origins.
 //!
 //! The membership can be provided in one of two ways: either directly, using the Root-dispatchable
-//! function `set_members`, or indirectly, through implementing the `ChangeMembers`
+//! function `set_members`, or indirectly, through implementing the `ChangeMembers`.
+//!
+//! A "prime" member may be set allowing their vote to act as the default vote in case of any
+//! abstentions after the voting period.
+//!
+//! Voting happens through motions comprising a proposal (i.e. a curried dispatchable) plus a
+//! number of approvals required for it to pass and be called. Motions are open for members to
+//! vote on for a minimum period given by `MotionDuration`. As soon as the needed number of
+//! approvals is given, the motion is closed and executed. If the number of approvals is not
+//! reached during the voting period, then `close` may be called by any member in order to force
+//! the end of the motion explicitly. If a prime member is defined then their vote is used in place of
+//! any abstentions and the proposal is executed if there are enough approvals counting the new
+//! votes.
+//!
+//! If there are not, or if no prime is set, then the motion is dropped without being executed.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![recursion_limit="128"]
@@ -30,8 +44,8 @@ use sp_runtime::traits::{Hash, EnsureOrigin};
 use frame_support::weights::SimpleDispatchInfo;
 use frame_support::{
 	dispatch::{Dispatchable, Parameter}, codec::{Encode, Decode},
-	traits::{ChangeMembers, InitializeMembers}, decl_module, decl_event,
-	decl_storage, decl_error, ensure,
+	traits::{ChangeMembers, InitializeMembers, Get}, decl_module, decl_event,
+	decl_storage, decl_error, ensure, storage::unhashed,
 };
 use frame_system::{self as system, ensure_signed, ensure_root};
 
@@ -53,6 +67,9 @@ pub trait Trait<I=DefaultInstance>: frame_system::Trait {
 
 	/// The outer event type.
 	type Event: From<Event<Self, I>> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The time-out for council motions.
+	type MotionDuration: Get<Self::BlockNumber>;
 }
 
 /// Origin for the collective module.
@@ -71,7 +88,7 @@ pub type Origin<T, I=DefaultInstance> = RawOrigin<<T as frame_system::Trait>::Ac
 
 #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
 /// Info for keeping track of a motion being voted on.
-pub struct Votes<AccountId> {
+pub struct Votes<AccountId, BlockNumber> {
 	/// The proposal's unique index.
 	index: ProposalIndex,
 	/// The number of approval votes that are needed to pass the motion.
@@ -80,6 +97,8 @@ pub struct Votes<AccountId> {
 	ayes: Vec<AccountId>,
 	/// The current set of voters that rejected it.
 	nays: Vec<AccountId>,
+	/// The hard end time of this vote.
+	end: BlockNumber,
 }
 
 decl_storage! {
@@ -89,11 +108,14 @@ decl_storage! {
 		/// Actual proposal for a given hash, if it's current.
 		pub ProposalOf get(fn proposal_of): map T::Hash => Option<<T as Trait<I>>::Proposal>;
 		/// Votes on a given proposal, if it is ongoing.
-		pub Voting get(fn voting): map T::Hash => Option<Votes<T::AccountId>>;
+		pub Voting get(fn voting): map T::Hash => Option<Votes<T::AccountId, T::BlockNumber>>;
 		/// Proposals so far.
 		pub ProposalCount get(fn proposal_count): u32;
 		/// The current members of the collective. This is stored sorted (just by value).
 		pub Members get(fn members): Vec<T::AccountId>;
+		/// The member who provides the default vote for any other members that do not vote before
+		/// the timeout. If None, then no member has that privilege.
+		pub Prime get(fn prime): Option<T::AccountId>;
 	}
 	add_extra_genesis {
 		config(phantom): sp_std::marker::PhantomData<I>;
@@ -121,6 +143,8 @@ decl_event! {
 		Executed(Hash, bool),
 		/// A single member did some action; `bool` is true if returned without error.
 		MemberExecuted(Hash, bool),
+		/// A proposal was closed after its duration was up.
+		Closed(Hash, MemberCount, MemberCount),
 	}
 }
 
@@ -138,6 +162,10 @@ decl_error! {
 		DuplicateVote,
 		/// Members are already initialized!
 		AlreadyInitialized,
+		/// The close call is made too early, before the end of the voting.
+		TooEarly,
+		/// The prime is not one of the members.
+		PrimeNotMember,
 	}
 }
 
@@ -150,17 +178,26 @@ decl_module! {
 
 		fn deposit_event() = default;
 
+		fn on_runtime_upgrade() {
+			Self::migrate_voting();
+		}
+
 		/// Set the collective's membership manually to `new_members`. Be nice to the chain and
-		/// provide it pre-sorted.
+		/// provide it pre-sorted. The `prime` is set as the prime member; it must be one of
+		/// `new_members`.
 		///
 		/// Requires root origin.
 		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
-		fn set_members(origin, new_members: Vec<T::AccountId>) {
+		fn set_members(origin, new_members: Vec<T::AccountId>, prime: Option<T::AccountId>) {
 			ensure_root(origin)?;
 			let mut new_members = new_members;
 			new_members.sort();
+			if let Some(prime) = &prime {
+				ensure!(new_members.binary_search(prime).is_ok(), Error::<T, I>::PrimeNotMember);
+			}
 			<Members<T, I>>::mutate(|m| {
 				<Self as ChangeMembers<T::AccountId>>::set_members_sorted(&new_members[..], m);
+				<Self as ChangeMembers<T::AccountId>>::set_prime(prime);
 				*m = new_members;
 			});
 		}
@@ -200,7 +237,8 @@ decl_module! {
 				<ProposalCount<I>>::mutate(|i| *i += 1);
 				<Proposals<T, I>>::mutate(|proposals| proposals.push(proposal_hash));
 				<ProposalOf<T, I>>::insert(proposal_hash, *proposal);
-				let votes = Votes { index, threshold, ayes: vec![who.clone()], nays: vec![] };
+				let end = system::Module::<T>::block_number() + T::MotionDuration::get();
+				let votes = Votes { index, threshold, ayes: vec![who.clone()], nays: vec![], end };
 				<Voting<T, I>>::insert(proposal_hash, votes);
 
 				Self::deposit_event(RawEvent::Proposed(who, index, proposal_hash, threshold));
@@ -250,40 +288,134 @@ decl_module! {
 			let approved = yes_votes >= voting.threshold;
 			let disapproved = seats.saturating_sub(no_votes) < voting.threshold;
 			if approved || disapproved {
-				if approved {
-					Self::deposit_event(RawEvent::Approved(proposal));
-
-					// execute motion, assuming it exists.
-					if let Some(p) = <ProposalOf<T, I>>::take(&proposal) {
-						let origin = RawOrigin::Members(voting.threshold, seats).into();
-						let ok = p.dispatch(origin).is_ok();
-						Self::deposit_event(RawEvent::Executed(proposal, ok));
-					}
-				} else {
-					// disapproved
-					<ProposalOf<T, I>>::remove(&proposal);
-					Self::deposit_event(RawEvent::Disapproved(proposal));
-				}
-
-				// remove vote
-				<Voting<T, I>>::remove(&proposal);
-				<Proposals<T, I>>::mutate(|proposals| proposals.retain(|h| h != &proposal));
+				Self::finalize_proposal(approved, seats, voting, proposal);
 			} else {
 				// update voting
 				<Voting<T, I>>::insert(&proposal, voting);
 			}
 		}
+
+		/// Close a vote that is either approved, disapproved or whose voting period has ended.
+		///
+		/// Once the voting period has ended, any member that did not vote is counted as voting
+		/// the same way as the prime member did; if there is no prime or the prime did not vote
+		/// in favour, abstentions count as votes against.
+		///
+		/// Origin must be a member of the collective.
+		///
+		/// # <weight>
+		/// - Bounded storage read and writes.
+		/// - Will be slightly heavier if the proposal is approved / disapproved after the vote.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(200_000)]
+		fn close(origin, proposal: T::Hash, #[compact] index: ProposalIndex) {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::is_member(&who), Error::<T, I>::NotMember);
+
+			let voting = Self::voting(&proposal).ok_or(Error::<T, I>::ProposalMissing)?;
+			ensure!(voting.index == index, Error::<T, I>::WrongIndex);
+
+			let mut no_votes = voting.nays.len() as MemberCount;
+			let mut yes_votes = voting.ayes.len() as MemberCount;
+			let seats = Self::members().len() as MemberCount;
+			let approved = yes_votes >= voting.threshold;
+			let disapproved = seats.saturating_sub(no_votes) < voting.threshold;
+			if approved || disapproved {
+				Self::finalize_proposal(approved, seats, voting, proposal);
+				return Ok(())
+			}
+
+			let now = system::Module::<T>::block_number();
+			ensure!(now >= voting.end, Error::<T, I>::TooEarly);
+
+			// default to true only if there's a prime and they voted in favour.
+			let default = Self::prime().map_or(false, |who| voting.ayes.iter().any(|a| a == &who));
+
+			let abstentions = seats.saturating_sub(yes_votes + no_votes);
+			if default {
+				yes_votes += abstentions;
+			} else {
+				no_votes += abstentions;
+			}
+
+			Self::deposit_event(RawEvent::Closed(proposal, yes_votes, no_votes));
+			Self::finalize_proposal(yes_votes >= voting.threshold, seats, voting, proposal);
+		}
 	}
 }
 
 impl<T: Trait<I>, I: Instance> Module<T, I> {
+	/// Give the motions proposed before they had an end the full motion duration from now.
+	///
+	/// Their votes are stored without the `end` field, so they don't decode as `Votes`.
+	fn migrate_voting() {
+		#[derive(Decode)]
+		struct OldVotes<AccountId> {
+			index: ProposalIndex,
+			threshold: MemberCount,
+			ayes: Vec<AccountId>,
+			nays: Vec<AccountId>,
+		}
+
+		let end = system::Module::<T>::block_number() + T::MotionDuration::get();
+		for proposal in Self::proposals() {
+			let key = <Voting<T, I>>::hashed_key_for(&proposal);
+			let raw = match unhashed::get_raw(&key) {
+				Some(raw) => raw,
+				None => continue,
+			};
+			if Votes::<T::AccountId, T::BlockNumber>::decode(&mut &raw[..]).is_ok() {
+				continue;
+			}
+			match OldVotes::<T::AccountId>::decode(&mut &raw[..]) {
+				Ok(OldVotes { index, threshold, ayes, nays }) => <Voting<T, I>>::insert(
+					proposal,
+					Votes { index, threshold, ayes, nays, end },
+				),
+				Err(_) => frame_support::print("Failed to decode the votes of a motion."),
+			}
+		}
+	}
+
 	pub fn is_member(who: &T::AccountId) -> bool {
 		Self::members().contains(who)
 	}
+
+	/// Approve or disapprove the motion `proposal`, executing it if approved, and remove it and
+	/// its votes from storage.
+	fn finalize_proposal(
+		approved: bool,
+		seats: MemberCount,
+		voting: Votes<T::AccountId, T::BlockNumber>,
+		proposal: T::Hash,
+	) {
+		if approved {
+			Self::deposit_event(RawEvent::Approved(proposal));
+
+			// execute motion, assuming it exists.
+			if let Some(p) = <ProposalOf<T, I>>::take(&proposal) {
+				let origin = RawOrigin::Members(voting.threshold, seats).into();
+				let ok = p.dispatch(origin).is_ok();
+				Self::deposit_event(RawEvent::Executed(proposal, ok));
+			}
+		} else {
+			// disapproved
+			<ProposalOf<T, I>>::remove(&proposal);
+			Self::deposit_event(RawEvent::Disapproved(proposal));
+		}
+
+		// remove vote
+		<Voting<T, I>>::remove(&proposal);
+		<Proposals<T, I>>::mutate(|proposals| proposals.retain(|h| h != &proposal));
+	}
 }
 
 impl<T: Trait<I>, I: Instance> ChangeMembers<T::AccountId> for Module<T, I> {
-	fn change_members_sorted(_incoming: &[T::AccountId], outgoing: &[T::AccountId], new: &[T::AccountId]) {
+	fn change_members_sorted(
+		_incoming: &[T::AccountId],
+		outgoing: &[T::AccountId],
+		new: &[T::AccountId],
+	) {
 		// remove accounts from all current voting in motions.
 		let mut outgoing = outgoing.to_vec();
 		outgoing.sort_unstable();
@@ -301,6 +433,14 @@ impl<T: Trait<I>, I: Instance> ChangeMembers<T::AccountId> for Module<T, I> {
 			);
 		}
 		<Members<T, I>>::put(new);
+		// the prime must remain a member.
+		if Self::prime().map_or(false, |prime| outgoing.binary_search(&prime).is_ok()) {
+			Prime::<T, I>::kill();
+		}
+	}
+
+	fn set_prime(prime: Option<T::AccountId>) {
+		Prime::<T, I>::mutate(|p| *p = prime);
 	}
 }
 
@@ -403,8 +543,8 @@ mod tests {
 	use hex_literal::hex;
 	use sp_core::H256;
 	use sp_runtime::{
-		Perbill, traits::{BlakeTwo256, IdentityLookup, Block as BlockT}, testing::Header,
-		BuildStorage,
+		Perbill, traits::{BlakeTwo256, IdentityLookup, Block as BlockT, OnRuntimeUpgrade},
+		testing::Header, BuildStorage,
 	};
 	use crate as collective;
 
@@ -413,6 +553,7 @@ mod tests {
 		pub const MaximumBlockWeight: Weight = 1024;
 		pub const MaximumBlockLength: u32 = 2 * 1024;
 		pub const AvailableBlockRatio: Perbill = Perbill::one();
+		pub const MotionDuration: u64 = 3;
 	}
 	impl frame_system::Trait for Test {
 		type Origin = Origin;
@@ -436,11 +577,13 @@ mod tests {
 		type Origin = Origin;
 		type Proposal = Call;
 		type Event = Event;
+		type MotionDuration = MotionDuration;
 	}
 	impl Trait for Test {
 		type Origin = Origin;
 		type Proposal = Call;
 		type Event = Event;
+		type MotionDuration = MotionDuration;
 	}
 
 	pub type Block = sp_runtime::generic::Block<Header, UncheckedExtrinsic>;
@@ -481,6 +624,138 @@ mod tests {
 		Call::System(frame_system::Call::remark(value.encode()))
 	}
 
+	#[test]
+	fn close_works() {
+		make_ext().execute_with(|| {
+			System::set_block_number(1);
+			let proposal = make_proposal(42);
+			let hash = BlakeTwo256::hash_of(&proposal);
+
+			assert_ok!(Collective::propose(Origin::signed(1), 3, Box::new(proposal.clone())));
+			assert_ok!(Collective::vote(Origin::signed(2), hash.clone(), 0, true));
+
+			System::set_block_number(3);
+			assert_noop!(
+				Collective::close(Origin::signed(4), hash.clone(), 0),
+				Error::<Test, Instance1>::NotMember,
+			);
+			assert_noop!(
+				Collective::close(Origin::signed(2), hash.clone(), 0),
+				Error::<Test, Instance1>::TooEarly,
+			);
+
+			System::set_block_number(4);
+			assert_ok!(Collective::close(Origin::signed(2), hash.clone(), 0));
+
+			let record = |event| EventRecord { phase: Phase::Finalization, event, topics: vec![] };
+			assert_eq!(System::events(), vec![
+				record(Event::collective_Instance1(RawEvent::Proposed(1, 0, hash.clone(), 3))),
+				record(Event::collective_Instance1(RawEvent::Voted(2, hash.clone(), true, 2, 0))),
+				record(Event::collective_Instance1(RawEvent::Closed(hash.clone(), 2, 1))),
+				record(Event::collective_Instance1(RawEvent::Disapproved(hash.clone())))
+			]);
+			assert_eq!(Collective::proposals(), vec![]);
+		});
+	}
+
+	#[test]
+	fn close_with_prime_works() {
+		make_ext().execute_with(|| {
+			System::set_block_number(1);
+			let proposal = make_proposal(42);
+			let hash = BlakeTwo256::hash_of(&proposal);
+			assert_ok!(Collective::set_members(Origin::ROOT, vec![1, 2, 3], Some(3)));
+
+			assert_ok!(Collective::propose(Origin::signed(1), 3, Box::new(proposal.clone())));
+			assert_ok!(Collective::vote(Origin::signed(2), hash.clone(), 0, true));
+
+			System::set_block_number(4);
+			assert_ok!(Collective::close(Origin::signed(2), hash.clone(), 0));
+
+			let record = |event| EventRecord { phase: Phase::Finalization, event, topics: vec![] };
+			assert_eq!(System::events(), vec![
+				record(Event::collective_Instance1(RawEvent::Proposed(1, 0, hash.clone(), 3))),
+				record(Event::collective_Instance1(RawEvent::Voted(2, hash.clone(), true, 2, 0))),
+				record(Event::collective_Instance1(RawEvent::Closed(hash.clone(), 2, 1))),
+				record(Event::collective_Instance1(RawEvent::Disapproved(hash.clone())))
+			]);
+		});
+	}
+
+	#[test]
+	fn close_with_voting_prime_works() {
+		make_ext().execute_with(|| {
+			System::set_block_number(1);
+			let proposal = make_proposal(42);
+			let hash = BlakeTwo256::hash_of(&proposal);
+			assert_ok!(Collective::set_members(Origin::ROOT, vec![1, 2, 3], Some(1)));
+
+			assert_ok!(Collective::propose(Origin::signed(1), 3, Box::new(proposal.clone())));
+			assert_ok!(Collective::vote(Origin::signed(2), hash.clone(), 0, true));
+
+			System::set_block_number(4);
+			assert_ok!(Collective::close(Origin::signed(2), hash.clone(), 0));
+
+			let record = |event| EventRecord { phase: Phase::Finalization, event, topics: vec![] };
+			assert_eq!(System::events(), vec![
+				record(Event::collective_Instance1(RawEvent::Proposed(1, 0, hash.clone(), 3))),
+				record(Event::collective_Instance1(RawEvent::Voted(2, hash.clone(), true, 2, 0))),
+				record(Event::collective_Instance1(RawEvent::Closed(hash.clone(), 3, 0))),
+				record(Event::collective_Instance1(RawEvent::Approved(hash.clone()))),
+				record(Event::collective_Instance1(RawEvent::Executed(hash.clone(), false)))
+			]);
+		});
+	}
+
+	#[test]
+	fn set_members_rejects_non_member_prime() {
+		make_ext().execute_with(|| {
+			assert_noop!(
+				Collective::set_members(Origin::ROOT, vec![1, 2, 3], Some(4)),
+				Error::<Test, Instance1>::PrimeNotMember,
+			);
+			assert_ok!(Collective::set_members(Origin::ROOT, vec![1, 2, 4], Some(4)));
+			assert_eq!(Collective::prime(), Some(4));
+		});
+	}
+
+	#[test]
+	fn votes_without_end_are_migrated() {
+		make_ext().execute_with(|| {
+			System::set_block_number(1);
+			let proposal = make_proposal(42);
+			let hash = BlakeTwo256::hash_of(&proposal);
+			assert_ok!(Collective::propose(Origin::signed(1), 3, Box::new(proposal.clone())));
+			let key = <Voting<Test, Instance1>>::hashed_key_for(&hash);
+			let old_votes = (0 as ProposalIndex, 3 as MemberCount, vec![1u64, 2], vec![0u64; 0]);
+			unhashed::put_raw(&key, &old_votes.encode());
+
+			System::set_block_number(5);
+			<Collective as OnRuntimeUpgrade>::on_runtime_upgrade();
+			let votes = Some(
+				Votes { index: 0, threshold: 3, ayes: vec![1, 2], nays: vec![], end: 8 }
+			);
+			assert_eq!(Collective::voting(&hash), votes);
+
+			// Running the upgrade hook again keeps the migrated votes.
+			System::set_block_number(6);
+			<Collective as OnRuntimeUpgrade>::on_runtime_upgrade();
+			assert_eq!(Collective::voting(&hash), votes);
+		});
+	}
+
+	#[test]
+	fn prime_is_removed_with_member() {
+		make_ext().execute_with(|| {
+			Collective::set_prime(Some(3));
+			assert_eq!(Collective::prime(), Some(3));
+			Collective::change_members_sorted(&[], &[2], &[1, 3]);
+			assert_eq!(Collective::prime(), Some(3));
+			Collective::change_members_sorted(&[4], &[3], &[1, 4]);
+			assert_eq!(Collective::prime(), None);
+		});
+	}
+
 	#[test]
 	fn removal_of_old_voters_votes_works() {
 		make_ext().execute_with(|| {
@@ -491,12 +766,12 @@ mod tests {
 			assert_ok!(Collective::vote(Origin::signed(2), hash.clone(), 0, true));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 3, ayes: vec![1, 2], nays: vec![] })
+				Some(Votes { index: 0, threshold: 3, ayes: vec![1, 2], nays: vec![], end: 4 })
 			);
 			Collective::change_members_sorted(&[4], &[1], &[2, 3, 4]);
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 3, ayes: vec![2], nays: vec![] })
+				Some(Votes { index: 0, threshold: 3, ayes: vec![2], nays: vec![], end: 4 })
 			);
 
 			let proposal = make_proposal(69);
@@ -505,12 +780,12 @@ mod tests {
 			assert_ok!(Collective::vote(Origin::signed(3), hash.clone(), 1, false));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![3] })
+				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![3], end: 4 })
 			);
 			Collective::change_members_sorted(&[], &[3], &[2, 4]);
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![] })
+				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![], end: 4 })
 			);
 		});
 	}
@@ -525,12 +800,12 @@ mod tests {
 			assert_ok!(Collective::vote(Origin::signed(2), hash.clone(), 0, true));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 3, ayes: vec![1, 2], nays: vec![] })
+				Some(Votes { index: 0, threshold: 3, ayes: vec![1, 2], nays: vec![], end: 4 })
 			);
-			assert_ok!(Collective::set_members(Origin::ROOT, vec![2, 3, 4]));
+			assert_ok!(Collective::set_members(Origin::ROOT, vec![2, 3, 4], None));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 3, ayes: vec![2], nays: vec![] })
+				Some(Votes { index: 0, threshold: 3, ayes: vec![2], nays: vec![], end: 4 })
 			);
 
 			let proposal = make_proposal(69);
@@ -539,12 +814,12 @@ mod tests {
 			assert_ok!(Collective::vote(Origin::signed(3), hash.clone(), 1, false));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![3] })
+				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![3], end: 4 })
 			);
-			assert_ok!(Collective::set_members(Origin::ROOT, vec![2, 4]));
+			assert_ok!(Collective::set_members(Origin::ROOT, vec![2, 4], None));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![] })
+				Some(Votes { index: 1, threshold: 2, ayes: vec![2], nays: vec![], end: 4 })
 			);
 		});
 	}
@@ -560,7 +835,7 @@ mod tests {
 			assert_eq!(Collective::proposal_of(&hash), Some(proposal));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 3, ayes: vec![1], nays: vec![] })
+				Some(Votes { index: 0, threshold: 3, ayes: vec![1], nays: vec![], end: 4 })
 			);
 
 			assert_eq!(System::events(), vec![
@@ -627,7 +902,7 @@ mod tests {
 			assert_ok!(Collective::propose(Origin::signed(1), 2, Box::new(proposal.clone())));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 2, ayes: vec![1], nays: vec![] })
+				Some(Votes { index: 0, threshold: 2, ayes: vec![1], nays: vec![], end: 4 })
 			);
 			assert_noop!(
 				Collective::vote(Origin::signed(1), hash.clone(), 0, true),
@@ -636,7 +911,7 @@ mod tests {
 			assert_ok!(Collective::vote(Origin::signed(1), hash.clone(), 0, false));
 			assert_eq!(
 				Collective::voting(&hash),
-				Some(Votes { index: 0, threshold: 2, ayes: vec![], nays: vec![1] })
+				Some(Votes { index: 0, threshold: 2, ayes: vec![], nays: vec![1], end: 4 })
 			);
 			assert_noop!(
 				Collective::vote(Origin::signed(1), hash.clone(), 0, false),
@@ -0,0 +1,62 @@
+(module
+	(import "env" "ext_scratch_size" (func $ext_scratch_size (result i32)))
+	(import "env" "ext_scratch_read" (func $ext_scratch_read (param i32 i32 i32)))
+	(import "env" "ext_return" (func $ext_return (param i32 i32)))
+	(import "env" "ext_hash_sha2_256" (func $ext_hash_sha2_256 (param i32 i32 i32)))
+	(import "env" "ext_hash_keccak_256" (func $ext_hash_keccak_256 (param i32 i32 i32)))
+	(import "env" "ext_hash_blake2_256" (func $ext_hash_blake2_256 (param i32 i32 i32)))
+	(import "env" "ext_hash_blake2_128" (func $ext_hash_blake2_128 (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func (export "deploy"))
+
+	;; Hashes the input data, except for its first byte, with the hash function selected by the
+	;; first byte and returns the resulting hash:
+	;;
+	;; | value | algorithm | bit width |
+	;; |-------|-----------|-----------|
+	;; |     0 |      SHA2 |       256 |
+	;; |     1 |    KECCAK |       256 |
+	;; |     2 |    BLAKE2 |       256 |
+	;; |     3 |    BLAKE2 |       128 |
+	;;
+	;; The hash is written over the start of the input, which exercises overlapping buffers.
+	(func (export "call")
+		(local $input_len i32)
+		(local $chosen_hash_fn i32)
+		(set_local $input_len (call $ext_scratch_size))
+		(call $ext_scratch_read
+			(i32.const 0)			;; Pointer in memory to the place where to copy.
+			(i32.const 0)			;; Offset from the start of the scratch buffer.
+			(get_local $input_len)	;; Count of bytes to copy.
+		)
+		(set_local $chosen_hash_fn (i32.load8_u (i32.const 0)))
+		(set_local $input_len (i32.sub (get_local $input_len) (i32.const 1)))
+		(if (i32.eq (get_local $chosen_hash_fn) (i32.const 0))
+			(then
+				(call $ext_hash_sha2_256 (i32.const 1) (get_local $input_len) (i32.const 0))
+				(call $ext_return (i32.const 0) (i32.const 32))
+			)
+		)
+		(if (i32.eq (get_local $chosen_hash_fn) (i32.const 1))
+			(then
+				(call $ext_hash_keccak_256 (i32.const 1) (get_local $input_len) (i32.const 0))
+				(call $ext_return (i32.const 0) (i32.const 32))
+			)
+		)
+		(if (i32.eq (get_local $chosen_hash_fn) (i32.const 2))
+			(then
+				(call $ext_hash_blake2_256 (i32.const 1) (get_local $input_len) (i32.const 0))
+				(call $ext_return (i32.const 0) (i32.const 32))
+			)
+		)
+		(if (i32.eq (get_local $chosen_hash_fn) (i32.const 3))
+			(then
+				(call $ext_hash_blake2_128 (i32.const 1) (get_local $input_len) (i32.const 0))
+				(call $ext_return (i32.const 0) (i32.const 16))
+			)
+		)
+		;; Unknown hash function.
+		(unreachable)
+	)
+)
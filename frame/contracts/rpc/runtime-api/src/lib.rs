@@ -44,25 +44,115 @@ pub enum ContractExecResult {
 	Error,
 }
 
+/// Whether a traced call is a call or an instantiation.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum CallKind {
+	/// A call to an account, optionally transferring some balance.
+	Call,
+	/// An instantiation of a new contract.
+	Instantiate,
+}
+
+/// A call or instantiation made while executing a contract.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct CallTrace<AccountId, Balance> {
+	/// Whether this is a call or an instantiation.
+	pub kind: CallKind,
+	/// The nesting level of the call. Calls made by the origin have a depth of 0.
+	pub depth: u32,
+	/// The account that made the call.
+	pub caller: AccountId,
+	/// The called account or the address of the instantiated contract.
+	pub callee: AccountId,
+	/// The value transferred along with the call or as endowment.
+	pub value: Balance,
+	/// The gas used by the call, including the gas used by the calls it made.
+	pub gas_used: u64,
+	/// The status code returned by the callee, or `None` if the call trapped.
+	pub status: Option<u8>,
+	/// The data returned by the callee.
+	pub output: Vec<u8>,
+	/// The reason why the call trapped, if it did.
+	pub trap_reason: Option<Vec<u8>>,
+}
+
+/// A result of execution of a contract together with debugging information.
+///
+/// See [`ContractsApi::call_with_debug`] for more info.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct ContractDebugExecResult<AccountId, Balance> {
+	/// The result of the execution.
+	pub result: ContractExecResult,
+	/// The lines printed by the executed contracts with `ext_println`.
+	pub debug_message: Vec<u8>,
+	/// The calls and instantiations in the order they were made. Each call is directly followed
+	/// by the calls it made in turn.
+	pub trace: Vec<CallTrace<AccountId, Balance>>,
+}
+
+/// The code a contract is instantiated from.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum Code<Hash> {
+	/// The Wasm code is stored before the contract is instantiated.
+	Upload(Vec<u8>),
+	/// The contract is instantiated from the code already stored under the given hash.
+	Existing(Hash),
+}
+
+/// A result of instantiating a contract.
+///
+/// See [`ContractsApi::instantiate`] for more info.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct ContractInstantiateResult<AccountId> {
+	/// The address of the instantiated contract, or `None` if the instantiation failed.
+	pub address: Option<AccountId>,
+	/// The result of executing the constructor of the contract.
+	pub result: ContractExecResult,
+	/// The gas consumed by storing the code, if any, and by the instantiation.
+	pub gas_consumed: u64,
+	/// The SCALE encoded runtime events deposited while storing the code and instantiating the
+	/// contract.
+	pub events: Vec<Vec<u8>>,
+}
+
 /// A result type of the get storage call.
 ///
 /// See [`ContractsApi::get_storage`] for more info.
-pub type GetStorageResult = Result<Option<Vec<u8>>, GetStorageError>;
+pub type GetStorageResult = Result<Option<Vec<u8>>, ContractAccessError>;
 
-/// The possible errors that can happen querying the storage of a contract.
+/// The possible errors that can happen accessing a contract, e.g. querying its storage.
 #[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
-pub enum GetStorageError {
+pub enum ContractAccessError {
 	/// The given address doesn't point on a contract.
 	ContractDoesntExist,
 	/// The specified contract is a tombstone and thus cannot have any storage.
 	IsTombstone,
 }
 
+/// A result type of the rent projection call.
+///
+/// See [`ContractsApi::rent_projection`] for more info.
+pub type RentProjectionResult<BlockNumber> = Result<RentProjection<BlockNumber>, ContractAccessError>;
+
+/// The projected time a contract will be able to sustain paying its rent.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum RentProjection<BlockNumber> {
+	/// Eviction is projected to happen at the specified block number.
+	EvictionAt(BlockNumber),
+	/// No eviction is scheduled.
+	///
+	/// E.g. because the contract accumulated enough funds to offset the rent storage costs.
+	NoEviction,
+}
+
 sp_api::decl_runtime_apis! {
 	/// The API to interact with contracts without using executive.
-	pub trait ContractsApi<AccountId, Balance> where
+	#[api_version(3)]
+	pub trait ContractsApi<AccountId, Balance, BlockNumber, Hash> where
 		AccountId: Codec,
 		Balance: Codec,
+		BlockNumber: Codec,
+		Hash: Codec,
 	{
 		/// Perform a call from a specified account to a given contract.
 		///
@@ -75,6 +165,31 @@ sp_api::decl_runtime_apis! {
 			input_data: Vec<u8>,
 		) -> ContractExecResult;
 
+		/// Perform a call from a specified account to a given contract while collecting the lines
+		/// printed with `ext_println` and a trace of all calls and instantiations.
+		///
+		/// Same as [`call`](ContractsApi::call) otherwise.
+		fn call_with_debug(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			input_data: Vec<u8>,
+		) -> ContractDebugExecResult<AccountId, Balance>;
+
+		/// Instantiate a new contract from the given code, storing the code first if it is
+		/// uploaded along with the call.
+		///
+		/// Returns the address of the new contract, the gas consumed and the events deposited.
+		/// See the contracts' `put_code` and `instantiate` dispatchable functions for more details.
+		fn instantiate(
+			origin: AccountId,
+			endowment: Balance,
+			gas_limit: u64,
+			code: Code<Hash>,
+			data: Vec<u8>,
+		) -> ContractInstantiateResult<AccountId>;
+
 		/// Query a given storage key in a given contract.
 		///
 		/// Returns `Ok(Some(Vec<u8>))` if the storage value exists under the given key in the
@@ -85,5 +200,12 @@ sp_api::decl_runtime_apis! {
 			address: AccountId,
 			key: [u8; 32],
 		) -> GetStorageResult;
+
+		/// Returns the projected time a given contract will be able to sustain paying its rent.
+		///
+		/// The returned projection is relevant for the current block, i.e. it is as if the
+		/// contract was accessed at the beginning of the current block. Returns `Err` if the
+		/// contract doesn't exist or is a tombstone, also after collecting the rent due.
+		fn rent_projection(address: AccountId) -> RentProjectionResult<BlockNumber>;
 	}
 }
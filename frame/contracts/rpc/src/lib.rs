@@ -25,12 +25,14 @@ use jsonrpc_derive::rpc;
 use sp_core::{H256, Bytes};
 use sp_rpc::number;
 use serde::{Deserialize, Serialize};
-use sp_runtime::{generic::BlockId, traits::Block as BlockT};
-use sp_api::ProvideRuntimeApi;
+use sp_runtime::{generic::BlockId, traits::{Block as BlockT, NumberFor}};
+use sp_api::{ApiExt, ProvideRuntimeApi};
 
 pub use self::gen_client::Client as ContractsClient;
 pub use pallet_contracts_rpc_runtime_api::{
-	self as runtime_api, ContractExecResult, ContractsApi as ContractsRuntimeApi, GetStorageResult,
+	self as runtime_api, CallKind, CallTrace, Code, ContractDebugExecResult, ContractExecResult,
+	ContractInstantiateResult, ContractsApi as ContractsRuntimeApi, GetStorageResult,
+	RentProjection, RentProjectionResult,
 };
 
 const RUNTIME_ERROR: i64 = 1;
@@ -46,11 +48,11 @@ const CONTRACT_IS_A_TOMBSTONE: i64 = 3;
 /// https://docs.google.com/spreadsheets/d/1h0RqncdqiWI4KgxO0z9JIpZEJESXjX_ZCK6LFX6veDo/view
 const GAS_PER_SECOND: u64 = 1_000_000_000;
 
-/// A private newtype for converting `GetStorageError` into an RPC error.
-struct GetStorageError(runtime_api::GetStorageError);
-impl From<GetStorageError> for Error {
-	fn from(e: GetStorageError) -> Error {
-		use runtime_api::GetStorageError::*;
+/// A private newtype for converting `ContractAccessError` into an RPC error.
+struct ContractAccessError(runtime_api::ContractAccessError);
+impl From<ContractAccessError> for Error {
+	fn from(e: ContractAccessError) -> Error {
+		use runtime_api::ContractAccessError::*;
 		match e.0 {
 			ContractDoesntExist => Error {
 				code: ErrorCode::ServerError(CONTRACT_DOESNT_EXIST),
@@ -78,6 +80,37 @@ pub struct CallRequest<AccountId, Balance> {
 	input_data: Bytes,
 }
 
+/// The code a contract is instantiated from, as passed over RPC.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcCode<Hash> {
+	/// Wasm code that is stored before the contract is instantiated
+	Upload(Bytes),
+	/// Hash of code that is already stored
+	Existing(Hash),
+}
+
+impl<Hash> From<RpcCode<Hash>> for Code<Hash> {
+	fn from(code: RpcCode<Hash>) -> Self {
+		match code {
+			RpcCode::Upload(code) => Code::Upload(code.to_vec()),
+			RpcCode::Existing(hash) => Code::Existing(hash),
+		}
+	}
+}
+
+/// A struct that encodes RPC parameters required to instantiate a new smart-contract.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct InstantiateRequest<AccountId, Balance, CodeHash> {
+	origin: AccountId,
+	endowment: Balance,
+	gas_limit: number::NumberOrHex<u64>,
+	code: RpcCode<CodeHash>,
+	data: Bytes,
+}
+
 /// An RPC serializable result of contract execution
 #[derive(Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -107,21 +140,174 @@ impl From<ContractExecResult> for RpcContractExecResult {
 	}
 }
 
+/// An RPC serializable kind of a traced call.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcCallKind {
+	/// A call to an account
+	Call,
+	/// An instantiation of a new contract
+	Instantiate,
+}
+
+/// An RPC serializable trace of a call or instantiation made during contract execution.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcCallTrace<AccountId, Balance> {
+	/// Whether this is a call or an instantiation
+	kind: RpcCallKind,
+	/// Nesting level of the call, calls made by the origin have a depth of 0
+	depth: u32,
+	/// Account that made the call
+	caller: AccountId,
+	/// Called account or address of the instantiated contract
+	callee: AccountId,
+	/// Value transferred along with the call or as endowment
+	value: Balance,
+	/// Gas used by the call, including the gas used by the calls it made
+	gas_used: u64,
+	/// Status code, or `None` if the call trapped
+	status: Option<u8>,
+	/// Output data
+	output: Bytes,
+	/// Reason why the call trapped, if it did
+	trap_reason: Option<String>,
+}
+
+impl<AccountId, Balance> From<CallTrace<AccountId, Balance>> for RpcCallTrace<AccountId, Balance> {
+	fn from(t: CallTrace<AccountId, Balance>) -> Self {
+		RpcCallTrace {
+			kind: match t.kind {
+				CallKind::Call => RpcCallKind::Call,
+				CallKind::Instantiate => RpcCallKind::Instantiate,
+			},
+			depth: t.depth,
+			caller: t.caller,
+			callee: t.callee,
+			value: t.value,
+			gas_used: t.gas_used,
+			status: t.status,
+			output: t.output.into(),
+			trap_reason: t.trap_reason.map(|reason| String::from_utf8_lossy(&reason).into_owned()),
+		}
+	}
+}
+
+/// An RPC serializable result of a contract call together with debugging information.
+///
+/// The result of the execution is flattened into this struct, so the `success` or `error` field
+/// is found next to the debugging information.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcContractCallResult<AccountId, Balance> {
+	/// Result of the execution
+	#[serde(flatten)]
+	result: RpcContractExecResult,
+	/// Lines printed by the executed contracts with `ext_println`
+	debug_message: String,
+	/// Calls and instantiations in the order they were made, each call is directly followed by
+	/// the calls it made in turn
+	trace: Vec<RpcCallTrace<AccountId, Balance>>,
+}
+
+impl<AccountId, Balance> From<ContractDebugExecResult<AccountId, Balance>>
+	for RpcContractCallResult<AccountId, Balance>
+{
+	fn from(r: ContractDebugExecResult<AccountId, Balance>) -> Self {
+		RpcContractCallResult {
+			result: r.result.into(),
+			debug_message: String::from_utf8_lossy(&r.debug_message).into_owned(),
+			trace: r.trace.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+/// An RPC serializable result of a contract instantiation.
+///
+/// The result of executing the constructor is flattened into this struct, so the `success` or
+/// `error` field is found next to the other fields.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcInstantiateResult<AccountId> {
+	/// Address of the instantiated contract, or `None` if the instantiation failed
+	address: Option<AccountId>,
+	/// Result of executing the constructor
+	#[serde(flatten)]
+	result: RpcContractExecResult,
+	/// Gas consumed by storing the code, if any, and by the instantiation
+	gas_consumed: u64,
+	/// SCALE encoded runtime events deposited during the instantiation
+	events: Vec<Bytes>,
+}
+
+impl<AccountId> From<ContractInstantiateResult<AccountId>> for RpcInstantiateResult<AccountId> {
+	fn from(r: ContractInstantiateResult<AccountId>) -> Self {
+		RpcInstantiateResult {
+			address: r.address,
+			result: r.result.into(),
+			gas_consumed: r.gas_consumed,
+			events: r.events.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+/// Check that the requested gas limit is valid and doesn't exceed the maximum allowed.
+fn check_gas_limit(gas_limit: number::NumberOrHex<u64>) -> Result<u64> {
+	let gas_limit = gas_limit.to_number().map_err(|e| Error {
+		code: ErrorCode::InvalidParams,
+		message: e,
+		data: None,
+	})?;
+
+	let max_gas_limit = 5 * GAS_PER_SECOND;
+	if gas_limit > max_gas_limit {
+		return Err(Error {
+			code: ErrorCode::InvalidParams,
+			message: format!(
+				"Requested gas limit is greater than maximum allowed: {} > {}",
+				gas_limit,
+				max_gas_limit
+			),
+			data: None,
+		});
+	}
+
+	Ok(gas_limit)
+}
+
 /// Contracts RPC methods.
 #[rpc]
-pub trait ContractsApi<BlockHash, AccountId, Balance> {
+pub trait ContractsApi<BlockHash, BlockNumber, AccountId, Balance, CodeHash> {
 	/// Executes a call to a contract.
 	///
 	/// This call is performed locally without submitting any transactions. Thus executing this
 	/// won't change any state. Nonetheless, the calling state-changing contracts is still possible.
 	///
 	/// This method is useful for calling getter-like methods on contracts.
+	///
+	/// Along with the result, the lines printed by the executed contracts with `ext_println` and
+	/// a trace of all calls and instantiations made during the execution are returned.
 	#[rpc(name = "contracts_call")]
 	fn call(
 		&self,
 		call_request: CallRequest<AccountId, Balance>,
 		at: Option<BlockHash>,
-	) -> Result<RpcContractExecResult>;
+	) -> Result<RpcContractCallResult<AccountId, Balance>>;
+
+	/// Instantiates a new contract, optionally uploading its code first.
+	///
+	/// This call is performed locally without submitting any transactions. Thus executing this
+	/// won't change any state.
+	///
+	/// This method is useful for finding out the address a contract would be instantiated at,
+	/// how much gas the instantiation consumes and which events it deposits.
+	#[rpc(name = "contracts_instantiate")]
+	fn instantiate(
+		&self,
+		instantiate_request: InstantiateRequest<AccountId, Balance, CodeHash>,
+		at: Option<BlockHash>,
+	) -> Result<RpcInstantiateResult<AccountId>>;
 
 	/// Returns the value under a specified storage `key` in a contract given by `address` param,
 	/// or `None` if it is not set.
@@ -132,6 +318,19 @@ pub trait ContractsApi<BlockHash, AccountId, Balance> {
 		key: H256,
 		at: Option<BlockHash>,
 	) -> Result<Option<Bytes>>;
+
+	/// Returns the projected time a given contract will be able to sustain paying its rent.
+	///
+	/// The returned projection is relevant for the given block, i.e. it is as if the contract was
+	/// accessed at the beginning of that block.
+	///
+	/// Returns `None` if the contract is exempted from rent.
+	#[rpc(name = "contracts_rentProjection")]
+	fn rent_projection(
+		&self,
+		address: AccountId,
+		at: Option<BlockHash>,
+	) -> Result<Option<BlockNumber>>;
 }
 
 /// An implementation of contract specific RPC methods.
@@ -150,20 +349,28 @@ impl<C, B> Contracts<C, B> {
 	}
 }
 
-impl<C, Block, AccountId, Balance> ContractsApi<<Block as BlockT>::Hash, AccountId, Balance>
+impl<C, Block, AccountId, Balance, CodeHash>
+	ContractsApi<<Block as BlockT>::Hash, NumberFor<Block>, AccountId, Balance, CodeHash>
 	for Contracts<C, Block>
 where
 	Block: BlockT,
 	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
-	C::Api: ContractsRuntimeApi<Block, AccountId, Balance>,
+	C::Api: ContractsRuntimeApi<
+		Block,
+		AccountId,
+		Balance,
+		NumberFor<Block>,
+		CodeHash,
+	>,
 	AccountId: Codec,
 	Balance: Codec,
+	CodeHash: Codec,
 {
 	fn call(
 		&self,
 		call_request: CallRequest<AccountId, Balance>,
 		at: Option<<Block as BlockT>::Hash>,
-	) -> Result<RpcContractExecResult> {
+	) -> Result<RpcContractCallResult<AccountId, Balance>> {
 		let api = self.client.runtime_api();
 		let at = BlockId::hash(at.unwrap_or_else(||
 			// If the block hash is not supplied assume the best block.
@@ -176,34 +383,70 @@ where
 			gas_limit,
 			input_data,
 		} = call_request;
-		let gas_limit = gas_limit.to_number().map_err(|e| Error {
-			code: ErrorCode::InvalidParams,
-			message: e,
-			data: None,
-		})?;
+		let gas_limit = check_gas_limit(gas_limit)?;
 
-		let max_gas_limit = 5 * GAS_PER_SECOND;
-		if gas_limit > max_gas_limit {
-			return Err(Error {
-				code: ErrorCode::InvalidParams,
-				message: format!(
-					"Requested gas limit is greater than maximum allowed: {} > {}",
-					gas_limit,
-					max_gas_limit
-				),
-				data: None,
-			});
+		let has_debug = api
+			.has_api_with::<dyn ContractsRuntimeApi<
+				Block,
+				AccountId,
+				Balance,
+				NumberFor<Block>,
+				CodeHash,
+				Error = (),
+			>, _>(&at, |version| version >= 2)
+			.map_err(|e| Error {
+				code: ErrorCode::ServerError(RUNTIME_ERROR),
+				message: "Unable to query the version of the contracts runtime API.".into(),
+				data: Some(format!("{:?}", e).into()),
+			})?;
+		let exec_result = if has_debug {
+			api.call_with_debug(&at, origin, dest, value, gas_limit, input_data.to_vec())
+		} else {
+			// Runtimes before version 2 of the API don't collect a debug message or a trace.
+			api.call(&at, origin, dest, value, gas_limit, input_data.to_vec())
+				.map(|result| ContractDebugExecResult {
+					result,
+					debug_message: Vec::new(),
+					trace: Vec::new(),
+				})
 		}
+		.map_err(|e| Error {
+			code: ErrorCode::ServerError(RUNTIME_ERROR),
+			message: "Runtime trapped while executing a contract.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})?;
+
+		Ok(exec_result.into())
+	}
 
-		let exec_result = api
-			.call(&at, origin, dest, value, gas_limit, input_data.to_vec())
+	fn instantiate(
+		&self,
+		instantiate_request: InstantiateRequest<AccountId, Balance, CodeHash>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<RpcInstantiateResult<AccountId>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+		let InstantiateRequest {
+			origin,
+			endowment,
+			gas_limit,
+			code,
+			data,
+		} = instantiate_request;
+		let gas_limit = check_gas_limit(gas_limit)?;
+
+		let instantiate_result = api
+			.instantiate(&at, origin, endowment, gas_limit, code.into(), data.to_vec())
 			.map_err(|e| Error {
 				code: ErrorCode::ServerError(RUNTIME_ERROR),
-				message: "Runtime trapped while executing a contract.".into(),
+				message: "Runtime trapped while instantiating a contract.".into(),
 				data: Some(format!("{:?}", e).into()),
 			})?;
 
-		Ok(exec_result.into())
+		Ok(instantiate_result.into())
 	}
 
 	fn get_storage(
@@ -226,11 +469,38 @@ where
 					message: "Runtime trapped while querying storage.".into(),
 					data: Some(format!("{:?}", e).into()),
 				})?
-			.map_err(GetStorageError)?
+			.map_err(ContractAccessError)?
 			.map(Bytes);
 
 		Ok(get_storage_result)
 	}
+
+	fn rent_projection(
+		&self,
+		address: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<NumberFor<Block>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+		let result = api
+			.rent_projection(&at, address)
+			.map_err(|e|
+				// Handle general API calling errors.
+				Error {
+					code: ErrorCode::ServerError(RUNTIME_ERROR),
+					message: "Runtime trapped while computing rent projection.".into(),
+					data: Some(format!("{:?}", e).into()),
+				})?
+			.map_err(ContractAccessError)?;
+
+		Ok(match result {
+			RentProjection::NoEviction => None,
+			RentProjection::EvictionAt(block_num) => Some(block_num),
+		})
+	}
 }
 
 #[cfg(test)]
@@ -248,4 +518,60 @@ mod tests {
 		test(r#"{"success":{"status":5,"data":"0x1234"}}"#);
 		test(r#"{"error":null}"#);
 	}
+
+	#[test]
+	fn should_serialize_deserialize_call_result_properly() {
+		fn test(expected: &str) {
+			let res: RpcContractCallResult<u64, u64> = serde_json::from_str(expected).unwrap();
+			let actual = serde_json::to_string(&res).unwrap();
+			assert_eq!(actual, expected);
+		}
+
+		test(concat!(
+			r#"{"success":{"status":0,"data":"0x"},"debugMessage":"hello\n","trace":["#,
+			r#"{"kind":"call","depth":0,"caller":1,"callee":2,"value":3,"gasUsed":4,"#,
+			r#""status":0,"output":"0x","trapReason":null},"#,
+			r#"{"kind":"instantiate","depth":1,"caller":2,"callee":5,"value":0,"gasUsed":1,"#,
+			r#""status":null,"output":"0x","trapReason":"contract trapped during execution"}]}"#,
+		));
+		test(r#"{"error":null,"debugMessage":"","trace":[]}"#);
+	}
+
+	#[test]
+	fn should_serialize_deserialize_instantiate_result_properly() {
+		fn test(expected: &str) {
+			let res: RpcInstantiateResult<u64> = serde_json::from_str(expected).unwrap();
+			let actual = serde_json::to_string(&res).unwrap();
+			assert_eq!(actual, expected);
+		}
+
+		test(concat!(
+			r#"{"address":2,"success":{"status":0,"data":"0x01020304"},"gasConsumed":5000,"#,
+			r#""events":["0x0102","0x"]}"#,
+		));
+		test(r#"{"address":null,"error":null,"gasConsumed":0,"events":[]}"#);
+	}
+
+	#[test]
+	fn should_deserialize_instantiate_request_properly() {
+		let req: InstantiateRequest<u64, u64, H256> = serde_json::from_str(concat!(
+			r#"{"origin":1,"endowment":100,"gasLimit":"0x3e8","code":{"existing":"#,
+			r#""0x0101010101010101010101010101010101010101010101010101010101010101"},"#,
+			r#""data":"0x"}"#,
+		)).unwrap();
+		assert_eq!(req.gas_limit.to_number(), Ok(1000));
+		match req.code {
+			RpcCode::Existing(hash) => assert_eq!(hash, H256::repeat_byte(1)),
+			RpcCode::Upload(_) => panic!("the code hash is deserialized as existing code"),
+		}
+
+		let req: InstantiateRequest<u64, u64, H256> = serde_json::from_str(concat!(
+			r#"{"origin":1,"endowment":100,"gasLimit":1000,"code":{"upload":"0x0061736d"},"#,
+			r#""data":"0x"}"#,
+		)).unwrap();
+		match req.code {
+			RpcCode::Upload(code) => assert_eq!(code.to_vec(), vec![0, 0x61, 0x73, 0x6d]),
+			RpcCode::Existing(_) => panic!("the code is deserialized as uploaded code"),
+		}
+	}
 }
@@ -17,7 +17,7 @@
 //! Auxiliaries to help with managing partial changes to accounts state.
 
 use super::{
-	AliveContractInfo, BalanceOf, CodeHash, ContractInfo, ContractInfoOf, Trait, TrieId,
+	AliveContractInfo, BalanceOf, CodeHash, ContractInfo, ContractInfoOf, Module, Trait, TrieId,
 	TrieIdGenerator,
 };
 use crate::exec::StorageKey;
@@ -175,6 +175,7 @@ impl<T: Trait> AccountDb<T> for DirectAccountDb {
 					(true, Some(info), None) => {
 						child::kill_storage(&info.trie_id, info.child_trie_unique_id());
 						<ContractInfoOf<T>>::remove(&address);
+						<Module<T>>::decrement_refcount(&info.code_hash);
 						continue;
 					}
 					// Existing contract is being replaced by a new one.
@@ -232,6 +233,14 @@ impl<T: Trait> AccountDb<T> for DirectAccountDb {
 					}
 				}
 
+				let old_code_hash = old_info.as_ref().map(|old_info| old_info.code_hash);
+				if old_code_hash != Some(new_info.code_hash) {
+					if let Some(old_code_hash) = old_code_hash {
+						<Module<T>>::decrement_refcount(&old_code_hash);
+					}
+					<Module<T>>::increment_refcount(&new_info.code_hash);
+				}
+
 				if old_info
 					.map(|old_info| old_info != new_info)
 					.unwrap_or(true)
@@ -312,6 +321,15 @@ impl<'a, T: Trait> OverlayAccountDb<'a, T> {
 		);
 	}
 
+	/// Assume contract exists
+	pub fn set_code_hash(&mut self, account: &T::AccountId, code_hash: CodeHash<T>) {
+		self.local
+			.borrow_mut()
+			.entry(account.clone())
+			.or_insert(Default::default())
+			.code_hash = Some(code_hash);
+	}
+
 	/// Assume contract exists
 	pub fn set_rent_allowance(&mut self, account: &T::AccountId, rent_allowance: BalanceOf<T>) {
 		self.local
@@ -0,0 +1,94 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! A mechanism for runtime authors to augment the functionality of contracts.
+//!
+//! The runtime can register a [`ChainExtension`] through the `ChainExtension` type of the
+//! module's `Trait`. Contracts call into it with `ext_call_chain_extension`, passing a function
+//! id that selects what the extension should do together with an arbitrary input buffer. Unlike
+//! `ext_dispatch_call` the extension is executed synchronously and its result is made available
+//! to the calling contract.
+//!
+//! # Security
+//!
+//! The extension is trusted code: it is responsible for charging an appropriate amount of gas
+//! for the work it performs and for validating all input supplied by the contract.
+//!
+//! Storage changes made by the extension are written directly to the runtime storage. They are
+//! reverted together with the other changes of the calling contract if it, or any of its
+//! callers, traps or returns a failure status.
+
+use crate::{Trait, Gas};
+use sp_std::prelude::*;
+use sp_runtime::DispatchError;
+
+/// A handler for `ext_call_chain_extension` calls made by contracts.
+pub trait ChainExtension<T: Trait> {
+	/// Execute the function identified by `func_id` on behalf of the calling contract.
+	///
+	/// The returned value is passed back to the contract as the return value of
+	/// `ext_call_chain_extension`. Returning an error makes the calling contract trap.
+	fn call(func_id: u32, env: &mut dyn Environment<T>) -> Result<u32, DispatchError>;
+
+	/// Whether contracts are allowed to call into the extension.
+	///
+	/// A contract that calls `ext_call_chain_extension` while this returns `false` traps.
+	fn enabled() -> bool {
+		true
+	}
+}
+
+/// The default chain extension, which is disabled.
+impl<T: Trait> ChainExtension<T> for () {
+	fn call(_func_id: u32, _env: &mut dyn Environment<T>) -> Result<u32, DispatchError> {
+		Err("chain extension is not enabled".into())
+	}
+
+	fn enabled() -> bool {
+		false
+	}
+}
+
+/// The interface exposed to a [`ChainExtension`] while it handles a call from a contract.
+///
+/// All memory accesses are charged to the gas meter of the calling contract the same way as the
+/// memory accesses of the other `ext_*` functions.
+pub trait Environment<T: Trait> {
+	/// The address of the contract that called into the extension.
+	fn address(&self) -> &T::AccountId;
+
+	/// The caller of the contract that called into the extension.
+	fn caller(&self) -> &T::AccountId;
+
+	/// Charge the given amount of gas from the calling contract.
+	///
+	/// Returns an error if the contract ran out of gas.
+	fn charge_gas(&mut self, amount: Gas) -> Result<(), DispatchError>;
+
+	/// Read the input buffer that the contract passed to `ext_call_chain_extension`.
+	fn input(&mut self) -> Result<Vec<u8>, DispatchError>;
+
+	/// Read `len` bytes from the sandbox memory of the contract, starting at `ptr`.
+	fn read(&mut self, ptr: u32, len: u32) -> Result<Vec<u8>, DispatchError>;
+
+	/// Write `buf` to the sandbox memory of the contract, starting at `ptr`.
+	fn write(&mut self, ptr: u32, buf: &[u8]) -> Result<(), DispatchError>;
+
+	/// Replace the contents of the scratch buffer of the contract with `output`.
+	///
+	/// The scratch buffer is cleared before the extension is called.
+	fn set_output(&mut self, output: Vec<u8>);
+}
@@ -23,7 +23,7 @@ use crate::rent;
 use sp_std::prelude::*;
 use sp_runtime::traits::{Bounded, CheckedAdd, CheckedSub, Zero};
 use frame_support::{
-	storage::unhashed, dispatch::DispatchError,
+	storage::{self, unhashed, TransactionOutcome}, dispatch::DispatchError,
 	traits::{WithdrawReason, Currency, Time, Randomness},
 };
 
@@ -141,6 +141,12 @@ pub trait Ext {
 		delta: Vec<StorageKey>,
 	);
 
+	/// Replace the code of the current contract with the code stored under `code_hash`.
+	///
+	/// The new code is used from the next call into the contract on. Returns an error if there is
+	/// no valid code stored under `code_hash`.
+	fn set_code_hash(&mut self, code_hash: CodeHash<Self::T>) -> Result<(), &'static str>;
+
 	/// Returns a reference to the account id of the caller.
 	fn caller(&self) -> &AccountIdOf<Self::T>;
 
@@ -185,6 +191,11 @@ pub trait Ext {
 	///
 	/// Returns `None` if the value doesn't exist.
 	fn get_runtime_storage(&self, key: &[u8]) -> Option<Vec<u8>>;
+
+	/// Appends a line to the debug message buffer.
+	///
+	/// The message is only recorded when debugging is enabled, i.e. in RPC dry-runs.
+	fn append_debug_message(&mut self, message: &str);
 }
 
 /// Loader is a companion of the `Vm` trait. It loads an appropriate abstract
@@ -270,6 +281,59 @@ pub enum DeferredAction<T: Trait> {
 	},
 }
 
+/// Whether a traced call is a call or an instantiation.
+#[derive(Clone, Copy, PartialEq, Eq, sp_runtime::RuntimeDebug)]
+pub enum CallKind {
+	/// A call to an account, optionally transferring some balance.
+	Call,
+	/// An instantiation of a new contract.
+	Instantiate,
+}
+
+pub type CallTrace<T> = RawCallTrace<AccountIdOf<T>, BalanceOf<T>>;
+
+/// A call or instantiation made while executing a contract with debugging enabled.
+#[derive(Clone, PartialEq, Eq, sp_runtime::RuntimeDebug)]
+pub struct RawCallTrace<AccountId, Balance> {
+	/// Whether this is a call or an instantiation.
+	pub kind: CallKind,
+	/// The nesting level of the call. Calls made by the origin have a depth of 0.
+	pub depth: u32,
+	/// The account that made the call.
+	pub caller: AccountId,
+	/// The called account or the address of the instantiated contract.
+	pub callee: AccountId,
+	/// The value transferred along with the call or as endowment.
+	pub value: Balance,
+	/// The gas used by the call, including the gas used by the calls it made.
+	pub gas_used: Gas,
+	/// The status code returned by the callee, or `None` if the call trapped.
+	pub status: Option<StatusCode>,
+	/// The data returned by the callee.
+	pub output: Vec<u8>,
+	/// The reason why the call trapped, if it did.
+	pub trap_reason: Option<&'static str>,
+}
+
+/// Debugging information collected while executing contracts with debugging enabled.
+pub struct DebugInfo<T: Trait> {
+	/// The lines printed by the executed contracts with `ext_println`.
+	pub message: Vec<u8>,
+	/// The calls and instantiations in the order they were made. Each call is directly followed
+	/// by the calls it made in turn.
+	pub calls: Vec<CallTrace<T>>,
+}
+
+// Cannot derive(Default) since it erroneously bounds T by Default.
+impl<T: Trait> Default for DebugInfo<T> {
+	fn default() -> Self {
+		DebugInfo {
+			message: Vec::new(),
+			calls: Vec::new(),
+		}
+	}
+}
+
 pub struct ExecutionContext<'a, T: Trait + 'a, V, L> {
 	pub parent: Option<&'a ExecutionContext<'a, T, V, L>>,
 	pub self_account: T::AccountId,
@@ -282,6 +346,8 @@ pub struct ExecutionContext<'a, T: Trait + 'a, V, L> {
 	pub loader: &'a L,
 	pub timestamp: MomentOf<T>,
 	pub block_number: T::BlockNumber,
+	/// The debugging information collected by this context, if debugging is enabled.
+	pub debug: Option<DebugInfo<T>>,
 }
 
 impl<'a, T, E, V, L> ExecutionContext<'a, T, V, L>
@@ -307,9 +373,18 @@ where
 			loader: &loader,
 			timestamp: T::Time::now(),
 			block_number: <frame_system::Module<T>>::block_number(),
+			debug: None,
 		}
 	}
 
+	/// Enable the collection of debugging information.
+	///
+	/// This must only be used for RPC dry-runs and never on-chain.
+	pub fn with_debugging(mut self) -> Self {
+		self.debug = Some(Default::default());
+		self
+	}
+
 	fn nested<'b, 'c: 'b>(&'c self, dest: T::AccountId, trie_id: Option<TrieId>)
 		-> ExecutionContext<'b, T, V, L>
 	{
@@ -325,6 +400,7 @@ where
 			loader: self.loader,
 			timestamp: self.timestamp.clone(),
 			block_number: self.block_number.clone(),
+			debug: self.debug.as_ref().map(|_| Default::default()),
 		}
 	}
 
@@ -335,6 +411,24 @@ where
 		value: BalanceOf<T>,
 		gas_meter: &mut GasMeter<T>,
 		input_data: Vec<u8>,
+	) -> ExecResult {
+		let gas_before = gas_meter.gas_left();
+		let mut nested_calls = Vec::new();
+
+		let result = self.do_call(dest.clone(), value, gas_meter, input_data, &mut nested_calls);
+
+		let gas_used = gas_before.saturating_sub(gas_meter.gas_left());
+		self.trace(CallKind::Call, dest, value, gas_used, result.as_ref(), nested_calls);
+		result
+	}
+
+	fn do_call(
+		&mut self,
+		dest: T::AccountId,
+		value: BalanceOf<T>,
+		gas_meter: &mut GasMeter<T>,
+		input_data: Vec<u8>,
+		nested_calls: &mut Vec<CallTrace<T>>,
 	) -> ExecResult {
 		if self.depth == self.config.max_depth as usize {
 			return Err(ExecError {
@@ -369,7 +463,7 @@ where
 		let caller = self.self_account.clone();
 		let dest_trie_id = contract_info.and_then(|i| i.as_alive().map(|i| i.trie_id.clone()));
 
-		self.with_nested_context(dest.clone(), dest_trie_id, |nested| {
+		self.with_nested_context(dest.clone(), dest_trie_id, nested_calls, |nested| {
 			if value > BalanceOf::<T>::zero() {
 				try_or_exec_error!(
 					transfer(
@@ -427,6 +521,44 @@ where
 		gas_meter: &mut GasMeter<T>,
 		code_hash: &CodeHash<T>,
 		input_data: Vec<u8>,
+	) -> Result<(T::AccountId, ExecReturnValue), ExecError> {
+		let dest = T::DetermineContractAddress::contract_address_for(
+			code_hash,
+			&input_data,
+			&self.self_account,
+		);
+		let gas_before = gas_meter.gas_left();
+		let mut nested_calls = Vec::new();
+
+		let result = self.do_instantiate(
+			dest.clone(),
+			endowment,
+			gas_meter,
+			code_hash,
+			input_data,
+			&mut nested_calls,
+		);
+
+		let gas_used = gas_before.saturating_sub(gas_meter.gas_left());
+		self.trace(
+			CallKind::Instantiate,
+			dest,
+			endowment,
+			gas_used,
+			result.as_ref().map(|(_address, output)| output),
+			nested_calls,
+		);
+		result
+	}
+
+	fn do_instantiate(
+		&mut self,
+		dest: T::AccountId,
+		endowment: BalanceOf<T>,
+		gas_meter: &mut GasMeter<T>,
+		code_hash: &CodeHash<T>,
+		input_data: Vec<u8>,
+		nested_calls: &mut Vec<CallTrace<T>>,
 	) -> Result<(T::AccountId, ExecReturnValue), ExecError> {
 		if self.depth == self.config.max_depth as usize {
 			return Err(ExecError {
@@ -446,16 +578,11 @@ where
 		}
 
 		let caller = self.self_account.clone();
-		let dest = T::DetermineContractAddress::contract_address_for(
-			code_hash,
-			&input_data,
-			&caller,
-		);
 
 		// TrieId has not been generated yet and storage is empty since contract is new.
 		let dest_trie_id = None;
 
-		let output = self.with_nested_context(dest.clone(), dest_trie_id, |nested| {
+		let output = self.with_nested_context(dest.clone(), dest_trie_id, nested_calls, |nested| {
 			try_or_exec_error!(
 				nested.overlay.instantiate_contract(&dest, code_hash.clone()),
 				input_data
@@ -521,16 +648,41 @@ where
 		}
 	}
 
-	fn with_nested_context<F>(&mut self, dest: T::AccountId, trie_id: Option<TrieId>, func: F)
-		-> ExecResult
+	/// Run `func` in a nested context.
+	///
+	/// `func` runs in a storage transaction, so that changes it writes directly to the storage,
+	/// e.g. through the chain extension, are reverted unless it succeeds.
+	///
+	/// If debugging is enabled, the debug messages of the nested context are appended to the ones
+	/// of this context, even if `func` fails, and the calls made in the nested context are put
+	/// into `nested_calls`.
+	fn with_nested_context<F>(
+		&mut self,
+		dest: T::AccountId,
+		trie_id: Option<TrieId>,
+		nested_calls: &mut Vec<CallTrace<T>>,
+		func: F,
+	) -> ExecResult
 		where F: FnOnce(&mut ExecutionContext<T, V, L>) -> ExecResult
 	{
-		let (output, change_set, deferred) = {
+		let (output, change_set, deferred, nested_debug) = storage::with_transaction(|| {
 			let mut nested = self.nested(dest, trie_id);
-			let output = func(&mut nested)?;
-			(output, nested.overlay.into_change_set(), nested.deferred)
-		};
+			let output = func(&mut nested);
+			let success = output.as_ref().map(|output| output.is_success()).unwrap_or(false);
+			let result = (output, nested.overlay.into_change_set(), nested.deferred, nested.debug);
+			if success {
+				TransactionOutcome::Commit(result)
+			} else {
+				TransactionOutcome::Rollback(result)
+			}
+		});
+
+		if let (Some(debug), Some(nested_debug)) = (self.debug.as_mut(), nested_debug) {
+			debug.message.extend(nested_debug.message);
+			*nested_calls = nested_debug.calls;
+		}
 
+		let output = output?;
 		if output.is_success() {
 			self.overlay.commit(change_set);
 			self.deferred.extend(deferred);
@@ -539,6 +691,38 @@ where
 		Ok(output)
 	}
 
+	/// Record a call or instantiation made from this context, followed by the calls it made in
+	/// turn, if debugging is enabled.
+	fn trace(
+		&mut self,
+		kind: CallKind,
+		callee: T::AccountId,
+		value: BalanceOf<T>,
+		gas_used: Gas,
+		result: Result<&ExecReturnValue, &ExecError>,
+		nested_calls: Vec<CallTrace<T>>,
+	) {
+		let depth = self.depth as u32;
+		if let Some(debug) = self.debug.as_mut() {
+			let (status, output, trap_reason) = match result {
+				Ok(output) => (Some(output.status), output.data.clone(), None),
+				Err(err) => (None, Vec::new(), Some(err.reason.into())),
+			};
+			debug.calls.push(RawCallTrace {
+				kind,
+				depth,
+				caller: self.self_account.clone(),
+				callee,
+				value,
+				gas_used,
+				status,
+				output,
+				trap_reason,
+			});
+			debug.calls.extend(nested_calls);
+		}
+	}
+
 	/// Returns whether a contract, identified by address, is currently live in the execution
 	/// stack, meaning it is in the middle of an execution.
 	fn is_live(&self, account: &T::AccountId) -> bool {
@@ -751,6 +935,12 @@ where
 		});
 	}
 
+	fn set_code_hash(&mut self, code_hash: CodeHash<T>) -> Result<(), &'static str> {
+		self.ctx.loader.load_main(&code_hash)?;
+		self.ctx.overlay.set_code_hash(&self.ctx.self_account, code_hash);
+		Ok(())
+	}
+
 	fn address(&self) -> &T::AccountId {
 		&self.ctx.self_account
 	}
@@ -804,6 +994,13 @@ where
 	fn get_runtime_storage(&self, key: &[u8]) -> Option<Vec<u8>> {
 		unhashed::get_raw(&key)
 	}
+
+	fn append_debug_message(&mut self, message: &str) {
+		if let Some(debug) = self.ctx.debug.as_mut() {
+			debug.message.extend_from_slice(message.as_bytes());
+			debug.message.push(b'\n');
+		}
+	}
 }
 
 /// These tests exercise the executive layer.
@@ -63,6 +63,8 @@
 //! This instantiates a new smart contract account and calls its contract deploy handler to
 //! initialize the contract.
 //! * `call` - Makes a call to an account, optionally transferring some balance.
+//! * `remove_code` - Removes code that is no longer used by any contract and refunds the deposit
+//! reserved by `put_code`.
 //!
 //! ### Signed Extensions
 //!
@@ -96,6 +98,8 @@ mod exec;
 mod wasm;
 mod rent;
 
+pub mod chain_extension;
+
 #[cfg(test)]
 mod tests;
 
@@ -104,7 +108,10 @@ use crate::account_db::{AccountDb, DirectAccountDb};
 use crate::wasm::{WasmLoader, WasmVm};
 
 pub use crate::gas::{Gas, GasMeter};
-pub use crate::exec::{ExecResult, ExecReturnValue, ExecError, StatusCode};
+pub use crate::exec::{
+	ExecResult, ExecReturnValue, ExecError, StatusCode, CallKind, CallTrace, RawCallTrace,
+	DebugInfo,
+};
 
 #[cfg(feature = "std")]
 use serde::{Serialize, Deserialize};
@@ -125,7 +132,9 @@ use frame_support::{
 	parameter_types, IsSubType,
 	weights::DispatchInfo,
 };
-use frame_support::traits::{OnFreeBalanceZero, OnUnbalanced, Currency, Get, Time, Randomness};
+use frame_support::traits::{
+	OnFreeBalanceZero, OnUnbalanced, Currency, ReservableCurrency, Get, Time, Randomness,
+};
 use frame_system::{self as system, ensure_signed, RawOrigin, ensure_root};
 use sp_core::storage::well_known_keys::CHILD_STORAGE_KEY_PREFIX;
 
@@ -230,6 +239,20 @@ impl<CodeHash, Balance, BlockNumber> RawAliveContractInfo<CodeHash, Balance, Blo
 	}
 }
 
+pub type CodeInfo<T> =
+	RawCodeInfo<<T as frame_system::Trait>::AccountId, BalanceOf<T>>;
+
+/// Information about a stored contract code.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct RawCodeInfo<AccountId, Balance> {
+	/// The account that stored the code.
+	pub owner: AccountId,
+	/// The deposit reserved from the owner for storing the code.
+	pub deposit: Balance,
+	/// The number of alive contracts that use the code.
+	pub refcount: u64,
+}
+
 /// Associated child trie unique id is built from the hash part of the trie id.
 pub(crate) fn trie_unique_id(trie_id: &[u8]) -> child::ChildInfo {
 	let start = CHILD_STORAGE_KEY_PREFIX.len() + b"default:".len();
@@ -343,7 +366,7 @@ parameter_types! {
 }
 
 pub trait Trait: frame_system::Trait {
-	type Currency: Currency<Self::AccountId>;
+	type Currency: ReservableCurrency<Self::AccountId>;
 	type Time: Time;
 	type Randomness: Randomness<Self::Hash>;
 
@@ -415,6 +438,11 @@ pub trait Trait: frame_system::Trait {
 	/// The fee required to instantiate a contract instance.
 	type ContractFee: Get<BalanceOf<Self>>;
 
+	/// The deposit reserved per byte of code when storing it with `put_code`.
+	///
+	/// The deposit is refunded when the code is removed with `remove_code`.
+	type CodeDepositPerByte: Get<BalanceOf<Self>>;
+
 	/// The base fee charged for calling into a contract.
 	type CallBaseFee: Get<Gas>;
 
@@ -429,6 +457,11 @@ pub trait Trait: frame_system::Trait {
 
 	/// The maximum amount of gas that could be expended per block.
 	type BlockGasLimit: Get<Gas>;
+
+	/// The chain extension that contracts can call into with `ext_call_chain_extension`.
+	///
+	/// Use `()` to disable chain extensions.
+	type ChainExtension: chain_extension::ChainExtension<Self>;
 }
 
 /// Simple contract address determiner.
@@ -481,7 +514,13 @@ decl_error! {
 		/// Tombstones don't match.
 		InvalidTombstone,
 		/// An origin TrieId written in the current block.
-		InvalidContractOrigin
+		InvalidContractOrigin,
+		/// No code is stored under the given code hash.
+		CodeNotFound,
+		/// Only the account that stored the code can remove it.
+		NotCodeOwner,
+		/// The code is still used by an alive contract.
+		CodeInUse,
 	}
 }
 
@@ -573,6 +612,9 @@ decl_module! {
 
 		/// Stores the given binary Wasm code into the chain's storage and returns its `codehash`.
 		/// You can instantiate contracts only with stored code.
+		///
+		/// A deposit of `CodeDepositPerByte` for each byte of the code is reserved from the sender
+		/// unless the code is already stored.
 		pub fn put_code(
 			origin,
 			#[compact] gas_limit: Gas,
@@ -583,7 +625,7 @@ decl_module! {
 			let (mut gas_meter, imbalance) = gas::buy_gas::<T>(&origin, gas_limit)?;
 
 			let schedule = <Module<T>>::current_schedule();
-			let result = wasm::save_code::<T>(code, &mut gas_meter, &schedule);
+			let result = wasm::save_code::<T>(code, &origin, &mut gas_meter, &schedule);
 			if let Ok(code_hash) = result {
 				Self::deposit_event(RawEvent::CodeStored(code_hash));
 			}
@@ -593,6 +635,31 @@ decl_module! {
 			result.map(|_| ()).map_err(Into::into)
 		}
 
+		/// Removes the code stored under `code_hash` and refunds the deposit reserved for it.
+		///
+		/// Only the account that stored the code can remove it, and only while no alive contract
+		/// uses it.
+		pub fn remove_code(origin, code_hash: CodeHash<T>) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+
+			let info = <CodeInfoOf<T>>::get(&code_hash).ok_or(Error::<T>::CodeNotFound)?;
+			if info.owner != origin {
+				Err(Error::<T>::NotCodeOwner)?
+			}
+			if info.refcount != 0 {
+				Err(Error::<T>::CodeInUse)?
+			}
+
+			<CodeInfoOf<T>>::remove(&code_hash);
+			<CodeStorage<T>>::remove(&code_hash);
+			<PristineCode<T>>::remove(&code_hash);
+			T::Currency::unreserve(&origin, info.deposit);
+
+			Self::deposit_event(RawEvent::CodeRemoved(code_hash));
+
+			Ok(())
+		}
+
 		/// Makes a call to an account, optionally transferring some balance.
 		///
 		/// * If the account is a smart-contract account, the associated code will be
@@ -634,7 +701,7 @@ decl_module! {
 		) -> DispatchResult {
 			let origin = ensure_signed(origin)?;
 
-			Self::execute_wasm(origin, gas_limit, |ctx, gas_meter| {
+			Self::execute_wasm(origin, gas_limit, None, |ctx, gas_meter| {
 				ctx.instantiate(endowment, gas_meter, &code_hash, data)
 					.map(|(_address, output)| output)
 			})
@@ -680,14 +747,48 @@ decl_module! {
 	}
 }
 
-/// The possible errors that can happen querying the storage of a contract.
-pub enum GetStorageError {
+/// The possible errors that can happen accessing a contract, e.g. querying its storage.
+#[derive(Eq, PartialEq, RuntimeDebug)]
+pub enum ContractAccessError {
 	/// The given address doesn't point on a contract.
 	ContractDoesntExist,
 	/// The specified contract is a tombstone and thus cannot have any storage.
 	IsTombstone,
 }
 
+/// The projected time a contract will be able to sustain paying its rent.
+#[derive(Eq, PartialEq, RuntimeDebug)]
+pub enum RentProjection<BlockNumber> {
+	/// Eviction is projected to happen at the specified block number.
+	EvictionAt(BlockNumber),
+	/// No eviction is scheduled.
+	///
+	/// E.g. because the contract accumulated enough funds to offset the rent storage costs.
+	NoEviction,
+}
+
+/// A result type of the rent projection of a contract.
+pub type RentProjectionResult<BlockNumber> =
+	sp_std::result::Result<RentProjection<BlockNumber>, ContractAccessError>;
+
+/// The code a contract is instantiated from.
+pub enum Code<Hash> {
+	/// The Wasm code is stored with `put_code` semantics before the contract is instantiated.
+	Upload(Vec<u8>),
+	/// The contract is instantiated from the code already stored under the given hash.
+	Existing(Hash),
+}
+
+/// The outcome of [`Module::bare_instantiate`].
+pub struct InstantiateOutcome<T: Trait> {
+	/// The address of the instantiated contract together with the output of its constructor.
+	pub result: sp_std::result::Result<(T::AccountId, ExecReturnValue), ExecError>,
+	/// The gas consumed by storing the code, if any, and by the instantiation.
+	pub gas_consumed: Gas,
+	/// The events deposited while storing the code and instantiating the contract.
+	pub events: Vec<<T as frame_system::Trait>::Event>,
+}
+
 /// Public APIs provided by the contracts module.
 impl<T: Trait> Module<T> {
 	/// Perform a call to a specified contract.
@@ -701,20 +802,95 @@ impl<T: Trait> Module<T> {
 		gas_limit: Gas,
 		input_data: Vec<u8>,
 	) -> ExecResult {
-		Self::execute_wasm(origin, gas_limit, |ctx, gas_meter| {
+		Self::execute_wasm(origin, gas_limit, None, |ctx, gas_meter| {
 			ctx.call(dest, value, gas_meter, input_data)
 		})
 	}
 
+	/// Perform a call to a specified contract while collecting debugging information.
+	///
+	/// This function is similar to `Self::bare_call`, but additionally returns the lines printed
+	/// by the executed contracts with `ext_println` and a trace of all calls and instantiations
+	/// made during the execution. It is meant for RPC dry-runs and must not be used on-chain.
+	pub fn bare_call_with_debug(
+		origin: T::AccountId,
+		dest: T::AccountId,
+		value: BalanceOf<T>,
+		gas_limit: Gas,
+		input_data: Vec<u8>,
+	) -> (ExecResult, DebugInfo<T>) {
+		let mut debug = DebugInfo::default();
+		let result = Self::execute_wasm(origin, gas_limit, Some(&mut debug), |ctx, gas_meter| {
+			ctx.call(dest, value, gas_meter, input_data)
+		});
+		(result, debug)
+	}
+
+	/// Instantiate a contract, optionally storing its code first.
+	///
+	/// This function is similar to `Self::instantiate`, but additionally returns the address of
+	/// the new contract, the gas consumed and the events deposited in the process. It is meant
+	/// for RPC dry-runs.
+	pub fn bare_instantiate(
+		origin: T::AccountId,
+		endowment: BalanceOf<T>,
+		gas_limit: Gas,
+		code: Code<CodeHash<T>>,
+		data: Vec<u8>,
+	) -> InstantiateOutcome<T> {
+		let events_before = <frame_system::Module<T>>::events().len();
+		let collect_events = || <frame_system::Module<T>>::events()
+			.into_iter()
+			.skip(events_before)
+			.map(|record| record.event)
+			.collect::<Vec<_>>();
+
+		// The code is stored with the gas bought for the whole instantiation.
+		let mut gas_left = gas_limit;
+		let mut address = None;
+		let transactor = origin.clone();
+		let result = Self::execute_wasm(origin, gas_limit, None, |ctx, gas_meter| {
+			let result = match code {
+				Code::Existing(code_hash) => Ok(code_hash),
+				Code::Upload(code) => {
+					let schedule = <Module<T>>::current_schedule();
+					wasm::save_code::<T>(code, &transactor, gas_meter, &schedule)
+						.map(|code_hash| {
+							Self::deposit_event(RawEvent::CodeStored(code_hash));
+							code_hash
+						})
+						.map_err(|reason| ExecError { reason: reason.into(), buffer: Vec::new() })
+				}
+			};
+			let result = result.and_then(|code_hash|
+				ctx.instantiate(endowment, gas_meter, &code_hash, data)
+			).map(|(new_address, output)| {
+				address = Some(new_address);
+				output
+			});
+			gas_left = gas_meter.gas_left();
+			result
+		});
+
+		InstantiateOutcome {
+			result: result.map(|output| (
+				address.expect("the address is set whenever the instantiation succeeds; qed"),
+				output,
+			)),
+			gas_consumed: gas_limit - gas_left,
+			events: collect_events(),
+		}
+	}
+
 	/// Query storage of a specified contract under a specified key.
 	pub fn get_storage(
 		address: T::AccountId,
 		key: [u8; 32],
-	) -> sp_std::result::Result<Option<Vec<u8>>, GetStorageError> {
+	) -> sp_std::result::Result<Option<Vec<u8>>, ContractAccessError> {
 		let contract_info = <ContractInfoOf<T>>::get(&address)
-			.ok_or(GetStorageError::ContractDoesntExist)?
+			.ok_or(ContractAccessError::ContractDoesntExist)?
 			.get_alive()
-			.ok_or(GetStorageError::IsTombstone)?;
+			.ok_or(ContractAccessError::IsTombstone)?;
 
 		let maybe_value = AccountDb::<T>::get_storage(
 			&DirectAccountDb,
@@ -724,12 +900,22 @@ impl<T: Trait> Module<T> {
 		);
 		Ok(maybe_value)
 	}
+
+	/// Query the block at which a specified contract is projected to be evicted for not paying
+	/// its rent.
+	///
+	/// The rent due up to the current block is collected first. The projection assumes that the
+	/// balance and the storage of the contract only change by paying the rent from then on.
+	pub fn rent_projection(address: T::AccountId) -> RentProjectionResult<T::BlockNumber> {
+		rent::compute_rent_projection::<T>(&address)
+	}
 }
 
 impl<T: Trait> Module<T> {
 	fn execute_wasm(
 		origin: T::AccountId,
 		gas_limit: Gas,
+		debug: Option<&mut DebugInfo<T>>,
 		func: impl FnOnce(&mut ExecutionContext<T, WasmVm, WasmLoader>, &mut GasMeter<T>) -> ExecResult
 	) -> ExecResult {
 		// Pay for the gas upfront.
@@ -747,9 +933,16 @@ impl<T: Trait> Module<T> {
 		let vm = WasmVm::new(&cfg.schedule);
 		let loader = WasmLoader::new(&cfg.schedule);
 		let mut ctx = ExecutionContext::top_level(origin.clone(), &cfg, &vm, &loader);
+		if debug.is_some() {
+			ctx = ctx.with_debugging();
+		}
 
 		let result = func(&mut ctx, &mut gas_meter);
 
+		if let (Some(debug), Some(collected)) = (debug, ctx.debug.take()) {
+			*debug = collected;
+		}
+
 		if result.as_ref().map(|output| output.is_success()).unwrap_or(false) {
 			// Commit all changes that made it thus far into the persistent storage.
 			DirectAccountDb.commit(ctx.overlay.into_change_set());
@@ -820,6 +1013,11 @@ impl<T: Trait> Module<T> {
 			.and_then(|c| c.get_tombstone())
 			.ok_or(Error::<T>::InvalidDestinationContract)?;
 
+		// The code may have been removed while no alive contract used it.
+		if !<CodeStorage<T>>::exists(&code_hash) || !<CodeInfoOf<T>>::exists(&code_hash) {
+			Err(Error::<T>::CodeNotFound)?
+		}
+
 		let last_write = if !delta.is_empty() {
 			Some(current_block)
 		} else {
@@ -870,6 +1068,9 @@ impl<T: Trait> Module<T> {
 			.map(|(_, value)| value.len() as u32)
 			.sum::<u32>();
 
+		Self::decrement_refcount(&origin_contract.code_hash);
+		Self::increment_refcount(&code_hash);
+
 		<ContractInfoOf<T>>::remove(&origin);
 		<ContractInfoOf<T>>::insert(&dest, ContractInfo::Alive(RawAliveContractInfo {
 			trie_id: origin_contract.trie_id,
@@ -886,6 +1087,24 @@ impl<T: Trait> Module<T> {
 
 		Ok(())
 	}
+
+	/// Increment the number of alive contracts that use the code stored under `code_hash`.
+	pub(crate) fn increment_refcount(code_hash: &CodeHash<T>) {
+		<CodeInfoOf<T>>::mutate(code_hash, |info| {
+			if let Some(info) = info {
+				info.refcount = info.refcount.saturating_add(1);
+			}
+		});
+	}
+
+	/// Decrement the number of alive contracts that use the code stored under `code_hash`.
+	pub(crate) fn decrement_refcount(code_hash: &CodeHash<T>) {
+		<CodeInfoOf<T>>::mutate(code_hash, |info| {
+			if let Some(info) = info {
+				info.refcount = info.refcount.saturating_sub(1);
+			}
+		});
+	}
 }
 
 decl_event! {
@@ -923,6 +1142,9 @@ decl_event! {
 		/// Code with the specified hash has been stored.
 		CodeStored(Hash),
 
+		/// Code with the specified hash has been removed.
+		CodeRemoved(Hash),
+
 		/// Triggered when the current schedule is updated.
 		ScheduleUpdated(u32),
 
@@ -945,6 +1167,10 @@ decl_storage! {
 		pub PristineCode: map CodeHash<T> => Option<Vec<u8>>;
 		/// A mapping between an original code hash and instrumented wasm code, ready for execution.
 		pub CodeStorage: map CodeHash<T> => Option<wasm::PrefabWasmModule>;
+		/// The owner, deposit and reference count of a stored code.
+		///
+		/// Code stored before reference counting was introduced has no entry and can't be removed.
+		pub CodeInfoOf: map CodeHash<T> => Option<CodeInfo<T>>;
 		/// The subtrie counter.
 		pub AccountCounter: u64 = 0;
 		/// The code associated with a given account.
@@ -958,6 +1184,7 @@ impl<T: Trait> OnFreeBalanceZero<T::AccountId> for Module<T> {
 	fn on_free_balance_zero(who: &T::AccountId) {
 		if let Some(ContractInfo::Alive(info)) = <ContractInfoOf<T>>::take(who) {
 			child::kill_storage(&info.trie_id, info.child_trie_unique_id());
+			<Module<T>>::decrement_refcount(&info.code_hash);
 		}
 	}
 }
@@ -1051,6 +1278,21 @@ pub struct Schedule {
 
 	/// The maximum length of a subject used for PRNG generation.
 	pub max_subject_len: u32,
+
+	/// Gas cost per one byte hashed by `ext_hash_sha2_256`.
+	pub hash_sha2_256_per_byte_cost: Gas,
+
+	/// Gas cost per one byte hashed by `ext_hash_keccak_256`.
+	pub hash_keccak_256_per_byte_cost: Gas,
+
+	/// Gas cost per one byte hashed by `ext_hash_blake2_256`.
+	pub hash_blake2_256_per_byte_cost: Gas,
+
+	/// Gas cost per one byte hashed by `ext_hash_blake2_128`.
+	pub hash_blake2_128_per_byte_cost: Gas,
+
+	/// Gas cost of recovering a public key with `ext_ecdsa_recover`.
+	pub ecdsa_recover_cost: Gas,
 }
 
 impl Default for Schedule {
@@ -1074,6 +1316,11 @@ impl Default for Schedule {
 			max_table_size: 16 * 1024,
 			enable_println: false,
 			max_subject_len: 32,
+			hash_sha2_256_per_byte_cost: 1,
+			hash_keccak_256_per_byte_cost: 1,
+			hash_blake2_256_per_byte_cost: 1,
+			hash_blake2_128_per_byte_cost: 1,
+			ecdsa_recover_cost: 100,
 		}
 	}
 }
@@ -15,8 +15,8 @@
 // along with Substrate. If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{Module, RawEvent, BalanceOf, ContractInfo, ContractInfoOf, TombstoneContractInfo,
-	Trait, AliveContractInfo};
-use sp_runtime::traits::{Bounded, CheckedDiv, CheckedMul, Saturating, Zero,
+	Trait, AliveContractInfo, ContractAccessError, RentProjection, RentProjectionResult};
+use sp_runtime::traits::{Bounded, CheckedDiv, CheckedMul, One, Saturating, Zero,
 	SaturatedConversion};
 use frame_support::traits::{Currency, ExistenceRequirement, Get, WithdrawReason, OnUnbalanced};
 use frame_support::StorageMap;
@@ -40,6 +40,29 @@ pub enum RentOutcome {
 	Ok,
 }
 
+/// Returns the amount of funds the contract has to pay per block for the storage it takes up,
+/// given the current free `balance` of the contract.
+fn compute_fee_per_block<T: Trait>(
+	balance: BalanceOf<T>,
+	contract: &AliveContractInfo<T>,
+) -> BalanceOf<T> {
+	let free_storage = balance
+		.checked_div(&T::RentDepositOffset::get())
+		.unwrap_or_else(Zero::zero);
+
+	let effective_storage_size =
+		<BalanceOf<T>>::from(contract.storage_size).saturating_sub(free_storage);
+
+	effective_storage_size
+		.checked_mul(&T::RentByteFee::get())
+		.unwrap_or(<BalanceOf<T>>::max_value())
+}
+
+/// Returns the minimal amount of funds required for a contract not to be evicted.
+fn subsistence_threshold<T: Trait>() -> BalanceOf<T> {
+	T::Currency::minimum_balance() + T::TombstoneDeposit::get()
+}
+
 /// Evict and optionally pay dues (or check account can pay them otherwise) at the current
 /// block number (modulo `handicap`, read on).
 ///
@@ -76,18 +99,7 @@ fn try_evict_or_and_pay_rent<T: Trait>(
 	let balance = T::Currency::free_balance(account);
 
 	// An amount of funds to charge per block for storage taken up by the contract.
-	let fee_per_block = {
-		let free_storage = balance
-			.checked_div(&T::RentDepositOffset::get())
-			.unwrap_or_else(Zero::zero);
-
-		let effective_storage_size =
-			<BalanceOf<T>>::from(contract.storage_size).saturating_sub(free_storage);
-
-		effective_storage_size
-			.checked_mul(&T::RentByteFee::get())
-			.unwrap_or(<BalanceOf<T>>::max_value())
-	};
+	let fee_per_block = compute_fee_per_block::<T>(balance, &contract);
 
 	if fee_per_block.is_zero() {
 		// The rent deposit offset reduced the fee to 0. This means that the contract
@@ -96,12 +108,13 @@ fn try_evict_or_and_pay_rent<T: Trait>(
 	}
 
 	// The minimal amount of funds required for a contract not to be evicted.
-	let subsistence_threshold = T::Currency::minimum_balance() + T::TombstoneDeposit::get();
+	let subsistence_threshold = subsistence_threshold::<T>();
 
 	if balance < subsistence_threshold {
 		// The contract cannot afford to leave a tombstone, so remove the contract info altogether.
 		<ContractInfoOf<T>>::remove(account);
 		child::kill_storage(&contract.trie_id, contract.child_trie_unique_id());
+		<Module<T>>::decrement_refcount(&contract.code_hash);
 		<Module<T>>::deposit_event(RawEvent::Evicted(account.clone(), false));
 		return (RentOutcome::Evicted, None);
 	}
@@ -161,6 +174,7 @@ fn try_evict_or_and_pay_rent<T: Trait>(
 		<ContractInfoOf<T>>::insert(account, &tombstone_info);
 
 		child::kill_storage(&contract.trie_id, contract.child_trie_unique_id());
+		<Module<T>>::decrement_refcount(&contract.code_hash);
 
 		<Module<T>>::deposit_event(RawEvent::Evicted(account.clone(), true));
 
@@ -199,3 +213,41 @@ pub fn pay_rent<T: Trait>(account: &T::AccountId) -> Option<ContractInfo<T>> {
 pub fn try_evict<T: Trait>(account: &T::AccountId, handicap: T::BlockNumber) -> RentOutcome {
 	try_evict_or_and_pay_rent::<T>(account, handicap, false).0
 }
+
+/// Returns the projected time a given contract will be able to sustain paying its rent.
+///
+/// The returned projection is relevant for the current block, i.e. it is as if the contract was
+/// accessed at the beginning of the current block. Returns an error if the contract doesn't
+/// exist or is (or becomes, as a result of the rent collection) a tombstone.
+///
+/// The projection assumes that the balance and the storage of the contract don't change
+/// apart from the rent payments, and that the fee per block stays as it is now.
+///
+/// NOTE: This function acts eagerly, it pays the rent up to the current block.
+pub fn compute_rent_projection<T: Trait>(
+	account: &T::AccountId,
+) -> RentProjectionResult<T::BlockNumber> {
+	let contract = match try_evict_or_and_pay_rent::<T>(account, Zero::zero(), true).1 {
+		None => return Err(ContractAccessError::ContractDoesntExist),
+		Some(ContractInfo::Tombstone(_)) => return Err(ContractAccessError::IsTombstone),
+		Some(ContractInfo::Alive(contract)) => contract,
+	};
+
+	let balance = T::Currency::free_balance(account);
+	let fee_per_block = compute_fee_per_block::<T>(balance, &contract);
+	if fee_per_block.is_zero() {
+		return Ok(RentProjection::NoEviction);
+	}
+
+	// The contract is evicted at the first block at which its dues exceed its rent budget.
+	let subsistence_threshold = subsistence_threshold::<T>();
+	let rent_budget = contract.rent_allowance.min(balance.saturating_sub(subsistence_threshold));
+	let blocks_left = (rent_budget / fee_per_block).saturated_into::<u32>();
+	let current_block_number = <frame_system::Module<T>>::block_number();
+
+	Ok(RentProjection::EvictionAt(
+		current_block_number
+			.saturating_add(blocks_left.into())
+			.saturating_add(One::one())
+	))
+}
@@ -23,6 +23,8 @@ use crate::{
 	BalanceOf, ComputeDispatchFee, ContractAddressFor, ContractInfo, ContractInfoOf, GenesisConfig,
 	Module, RawAliveContractInfo, RawEvent, Trait, TrieId, TrieIdFromParentCounter, Schedule,
 	TrieIdGenerator, CheckBlockGasLimit, account_db::{AccountDb, DirectAccountDb, OverlayAccountDb},
+	chain_extension::{ChainExtension, Environment}, Gas, CodeInfoOf, CodeStorage, PristineCode,
+	RawCodeInfo, Error, CallKind, Code, ContractAccessError, RentProjection,
 };
 use assert_matches::assert_matches;
 use hex_literal::*;
@@ -30,11 +32,11 @@ use codec::{Decode, Encode, KeyedVec};
 use sp_runtime::{
 	Perbill, BuildStorage, transaction_validity::{InvalidTransaction, ValidTransaction},
 	traits::{BlakeTwo256, Hash, IdentityLookup, SignedExtension},
-	testing::{Digest, DigestItem, Header, UintAuthorityId, H256},
+	testing::{Digest, DigestItem, Header, UintAuthorityId, H256}, DispatchError,
 };
 use frame_support::{
 	assert_ok, assert_err, impl_outer_dispatch, impl_outer_event, impl_outer_origin, parameter_types,
-	storage::child, StorageMap, StorageValue, traits::{Currency, Get},
+	storage::{child, unhashed}, StorageMap, StorageValue, traits::{Currency, Get},
 	weights::{DispatchInfo, DispatchClass, Weight},
 };
 use std::{cell::RefCell, sync::atomic::{AtomicUsize, Ordering}};
@@ -71,6 +73,7 @@ thread_local! {
 	static TRANSFER_FEE: RefCell<u64> = RefCell::new(0);
 	static INSTANTIATION_FEE: RefCell<u64> = RefCell::new(0);
 	static BLOCK_GAS_LIMIT: RefCell<u64> = RefCell::new(0);
+	static CODE_DEPOSIT_PER_BYTE: RefCell<u64> = RefCell::new(0);
 }
 
 pub struct ExistentialDeposit;
@@ -93,6 +96,11 @@ impl Get<u64> for BlockGasLimit {
 	fn get() -> u64 { BLOCK_GAS_LIMIT.with(|v| *v.borrow()) }
 }
 
+pub struct CodeDepositPerByte;
+impl Get<u64> for CodeDepositPerByte {
+	fn get() -> u64 { CODE_DEPOSIT_PER_BYTE.with(|v| *v.borrow()) }
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Test;
 parameter_types! {
@@ -176,11 +184,13 @@ impl Trait for Test {
 	type TransactionBaseFee = TransactionBaseFee;
 	type TransactionByteFee = TransactionByteFee;
 	type ContractFee = ContractFee;
+	type CodeDepositPerByte = CodeDepositPerByte;
 	type CallBaseFee = CallBaseFee;
 	type InstantiateBaseFee = InstantiateBaseFee;
 	type MaxDepth = MaxDepth;
 	type MaxValueSize = MaxValueSize;
 	type BlockGasLimit = BlockGasLimit;
+	type ChainExtension = TestExtension;
 }
 
 type Balances = pallet_balances::Module<Test>;
@@ -223,6 +233,44 @@ impl ComputeDispatchFee<Call, u64> for DummyComputeDispatchFee {
 	}
 }
 
+pub struct TestExtension;
+impl ChainExtension<Test> for TestExtension {
+	fn call(func_id: u32, env: &mut dyn Environment<Test>) -> Result<u32, DispatchError> {
+		match func_id {
+			// Echo the input in reverse order.
+			0 => {
+				let mut input = env.input()?;
+				input.reverse();
+				env.set_output(input);
+				Ok(0)
+			}
+			// Output the address of the calling contract and its caller.
+			1 => {
+				let output = (env.address(), env.caller()).encode();
+				env.set_output(output);
+				Ok(1)
+			}
+			// Charge the amount of gas given in the input.
+			2 => {
+				let amount = Gas::decode(&mut &env.input()?[..]).map_err(|_| "invalid input")?;
+				env.charge_gas(amount)?;
+				Ok(2)
+			}
+			// Write the input to the storage.
+			3 => {
+				unhashed::put_raw(b"chain_extension", &env.input()?);
+				Ok(3)
+			}
+			// Write the input to the storage and fail.
+			4 => {
+				unhashed::put_raw(b"chain_extension", &env.input()?);
+				Err("failed after writing".into())
+			}
+			_ => Err("unknown function id".into()),
+		}
+	}
+}
+
 const ALICE: u64 = 1;
 const BOB: u64 = 2;
 const CHARLIE: u64 = 3;
@@ -234,6 +282,7 @@ pub struct ExtBuilder {
 	block_gas_limit: u64,
 	transfer_fee: u64,
 	instantiation_fee: u64,
+	code_deposit_per_byte: u64,
 }
 impl Default for ExtBuilder {
 	fn default() -> Self {
@@ -243,6 +292,7 @@ impl Default for ExtBuilder {
 			block_gas_limit: 100_000_000,
 			transfer_fee: 0,
 			instantiation_fee: 0,
+			code_deposit_per_byte: 0,
 		}
 	}
 }
@@ -267,18 +317,22 @@ impl ExtBuilder {
 		self.instantiation_fee = instantiation_fee;
 		self
 	}
+	pub fn code_deposit_per_byte(mut self, code_deposit_per_byte: u64) -> Self {
+		self.code_deposit_per_byte = code_deposit_per_byte;
+		self
+	}
 	pub fn set_associated_consts(&self) {
 		EXISTENTIAL_DEPOSIT.with(|v| *v.borrow_mut() = self.existential_deposit);
 		TRANSFER_FEE.with(|v| *v.borrow_mut() = self.transfer_fee);
 		INSTANTIATION_FEE.with(|v| *v.borrow_mut() = self.instantiation_fee);
 		BLOCK_GAS_LIMIT.with(|v| *v.borrow_mut() = self.block_gas_limit);
+		CODE_DEPOSIT_PER_BYTE.with(|v| *v.borrow_mut() = self.code_deposit_per_byte);
 	}
 	pub fn build(self) -> sp_io::TestExternalities {
 		self.set_associated_consts();
 		let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
 		pallet_balances::GenesisConfig::<Test> {
 			balances: vec![],
-			vesting: vec![],
 		}.assimilate_storage(&mut t).unwrap();
 		GenesisConfig::<Test> {
 			current_schedule: Schedule {
@@ -1319,6 +1373,61 @@ fn restoration_success() {
 	restoration(false, false);
 }
 
+#[test]
+fn restoration_of_removed_code_fails() {
+	let (set_rent_wasm, set_rent_code_hash) = compile_module::<Test>(CODE_SET_RENT).unwrap();
+	let (restoration_wasm, restoration_code_hash) =
+		compile_module::<Test>(CODE_RESTORATION).unwrap();
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, restoration_wasm));
+		assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, set_rent_wasm));
+
+		// Create `BOB` with a rent allowance of 0 and let it be evicted.
+		assert_ok!(Contract::instantiate(
+			Origin::signed(ALICE),
+			30_000,
+			100_000,
+			set_rent_code_hash.into(),
+			<Test as pallet_balances::Trait>::Balance::from(0u32).encode()
+		));
+		initialize_block(5);
+		assert_err!(
+			Contract::call(Origin::signed(ALICE), BOB, 0, 100_000, call::null()),
+			"contract has been evicted"
+		);
+		assert!(ContractInfoOf::<Test>::get(BOB).unwrap().get_tombstone().is_some());
+
+		// No alive contract uses `BOB`'s code anymore, so it can be removed.
+		assert_ok!(Contract::remove_code(Origin::signed(ALICE), set_rent_code_hash.into()));
+
+		Balances::deposit_creating(&CHARLIE, 1_000_000);
+		assert_ok!(Contract::instantiate(
+			Origin::signed(CHARLIE),
+			30_000,
+			100_000,
+			restoration_code_hash.into(),
+			<Test as pallet_balances::Trait>::Balance::from(0u32).encode()
+		));
+		let django_trie_id = ContractInfoOf::<Test>::get(DJANGO).unwrap()
+			.get_alive().unwrap().trie_id;
+		initialize_block(6);
+
+		// The restoration fails and leaves both contracts as they were.
+		assert_ok!(Contract::call(Origin::signed(ALICE), DJANGO, 0, 100_000, vec![]));
+		assert!(ContractInfoOf::<Test>::get(BOB).unwrap().get_tombstone().is_some());
+		let django_contract = ContractInfoOf::<Test>::get(DJANGO).unwrap().get_alive().unwrap();
+		assert_eq!(django_contract.trie_id, django_trie_id);
+		assert_eq!(
+			System::events().pop().map(|record| record.event),
+			Some(MetaEvent::contract(
+				RawEvent::Restored(DJANGO, BOB, set_rent_code_hash.into(), 50, false)
+			)),
+		);
+	});
+}
+
 fn restoration(test_different_storage: bool, test_restore_to_with_dirty_storage: bool) {
 	let (set_rent_wasm, set_rent_code_hash) = compile_module::<Test>(CODE_SET_RENT).unwrap();
 	let (restoration_wasm, restoration_code_hash) =
@@ -2550,3 +2659,538 @@ fn get_runtime_storage() {
 		));
 	});
 }
+
+const CODE_CRYPTO_HASHES: &str = include_str!("../fixtures/crypto_hashes.wat");
+
+#[test]
+fn crypto_hashes() {
+	let (wasm, code_hash) = compile_module::<Test>(CODE_CRYPTO_HASHES).unwrap();
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, wasm));
+		assert_ok!(Contract::instantiate(
+			Origin::signed(ALICE),
+			100,
+			100_000,
+			code_hash.into(),
+			vec![],
+		));
+
+		use sp_io::hashing::{sha2_256, keccak_256, blake2_256, blake2_128};
+		let input = b"_DEAD_BEEF";
+		let expected: [(u8, Vec<u8>); 4] = [
+			(0, sha2_256(input).to_vec()),
+			(1, keccak_256(input).to_vec()),
+			(2, blake2_256(input).to_vec()),
+			(3, blake2_128(input).to_vec()),
+		];
+		for (selector, expected_hash) in expected.iter() {
+			let mut params = vec![*selector];
+			params.extend_from_slice(input);
+			let result = <Module<Test>>::bare_call(ALICE, BOB, 0, 100_000, params).unwrap();
+			assert_eq!(result.status, 0);
+			assert_eq!(&result.data[..], &expected_hash[..]);
+		}
+
+		// An unknown hash function traps.
+		assert!(<Module<Test>>::bare_call(ALICE, BOB, 0, 100_000, vec![4]).is_err());
+	})
+}
+
+const CODE_ECDSA_RECOVER: &str = r#"
+(module
+	(import "env" "ext_scratch_read" (func $ext_scratch_read (param i32 i32 i32)))
+	(import "env" "ext_return" (func $ext_return (param i32 i32)))
+	(import "env" "ext_ecdsa_recover" (func $ext_ecdsa_recover (param i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func (export "deploy"))
+
+	;; Expects the 65 bytes signature followed by the 32 bytes message hash as input. Returns the
+	;; result code of the recovery, followed by the 33 bytes of the recovered public key.
+	(func (export "call")
+		(call $ext_scratch_read
+			(i32.const 0)	;; Pointer in memory to the place where to copy.
+			(i32.const 0)	;; Offset from the start of the scratch buffer.
+			(i32.const 97)	;; Count of bytes to copy.
+		)
+		(i32.store8
+			(i32.const 100)
+			(call $ext_ecdsa_recover
+				(i32.const 0)	;; Pointer to the signature.
+				(i32.const 65)	;; Pointer to the message hash.
+				(i32.const 101)	;; Pointer to the output buffer.
+			)
+		)
+		(call $ext_return (i32.const 100) (i32.const 34))
+	)
+)
+"#;
+
+#[test]
+fn ecdsa_recover() {
+	use sp_core::{ecdsa, Pair};
+
+	let (wasm, code_hash) = compile_module::<Test>(CODE_ECDSA_RECOVER).unwrap();
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, wasm));
+		assert_ok!(Contract::instantiate(
+			Origin::signed(ALICE),
+			100,
+			100_000,
+			code_hash.into(),
+			vec![],
+		));
+
+		// `ecdsa::Pair` signs the BLAKE2 256 hash of the message.
+		let pair = ecdsa::Pair::from_seed(&[1; 32]);
+		let message = b"Hello contract!";
+		let signature = pair.sign(message);
+		let mut params = AsRef::<[u8]>::as_ref(&signature).to_vec();
+		params.extend_from_slice(&sp_io::hashing::blake2_256(message));
+
+		let result = <Module<Test>>::bare_call(ALICE, BOB, 0, 100_000, params.clone()).unwrap();
+		assert_eq!(result.data[0], 0);
+		assert_eq!(&result.data[1..], AsRef::<[u8]>::as_ref(&pair.public()));
+
+		// A signature with an invalid recovery id can't be recovered.
+		params[64] = 0xff;
+		let result = <Module<Test>>::bare_call(ALICE, BOB, 0, 100_000, params).unwrap();
+		assert_eq!(result.data[0], 1);
+		assert_eq!(&result.data[1..], &[0; 33][..]);
+	})
+}
+
+const CODE_CHAIN_EXTENSION: &str = r#"
+(module
+	(import "env" "ext_scratch_size" (func $ext_scratch_size (result i32)))
+	(import "env" "ext_scratch_read" (func $ext_scratch_read (param i32 i32 i32)))
+	(import "env" "ext_return" (func $ext_return (param i32 i32)))
+	(import "env" "ext_call_chain_extension"
+		(func $ext_call_chain_extension (param i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func (export "deploy"))
+
+	;; Expects the 4 bytes of the function id followed by the input of the chain extension as
+	;; input. Returns the 4 bytes of the result of the chain extension, followed by its output.
+	(func (export "call")
+		(local $input_len i32)
+		(local $output_len i32)
+		(set_local $input_len (call $ext_scratch_size))
+		(call $ext_scratch_read
+			(i32.const 0)	;; Pointer in memory to the place where to copy.
+			(i32.const 0)	;; Offset from the start of the scratch buffer.
+			(get_local $input_len)	;; Count of bytes to copy.
+		)
+		(i32.store
+			(i32.const 1024)
+			(call $ext_call_chain_extension
+				(i32.load (i32.const 0))	;; The function id.
+				(i32.const 4)	;; Pointer to the input of the chain extension.
+				(i32.sub (get_local $input_len) (i32.const 4))	;; Length of the input.
+			)
+		)
+		(set_local $output_len (call $ext_scratch_size))
+		(call $ext_scratch_read (i32.const 1028) (i32.const 0) (get_local $output_len))
+		(call $ext_return (i32.const 1024) (i32.add (get_local $output_len) (i32.const 4)))
+	)
+)
+"#;
+
+#[test]
+fn chain_extension_works() {
+	let (wasm, code_hash) = compile_module::<Test>(CODE_CHAIN_EXTENSION).unwrap();
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, wasm));
+		assert_ok!(Contract::instantiate(
+			Origin::signed(ALICE),
+			100,
+			100_000,
+			code_hash.into(),
+			vec![],
+		));
+
+		let call = |func_id: u32, input: &[u8]| {
+			let mut params = func_id.to_le_bytes().to_vec();
+			params.extend_from_slice(input);
+			<Module<Test>>::bare_call(ALICE, BOB, 0, 100_000, params)
+		};
+
+		// The extension receives the input and its output ends up in the scratch buffer.
+		let result = call(0, &[1, 2, 3]).unwrap();
+		assert_eq!(result.status, 0);
+		assert_eq!(result.data, vec![0, 0, 0, 0, 3, 2, 1]);
+
+		// The extension knows which contract called it and who called that contract.
+		let result = call(1, &[1, 2, 3]).unwrap();
+		let mut expected = 1u32.to_le_bytes().to_vec();
+		expected.extend((BOB, ALICE).encode());
+		assert_eq!(result.data, expected);
+
+		// The extension charges the gas meter of the contract.
+		let result = call(2, &100u64.encode()).unwrap();
+		assert_eq!(result.data, 2u32.to_le_bytes().to_vec());
+		assert!(call(2, &1_000_000u64.encode()).is_err());
+
+		// An error of the extension makes the contract trap.
+		assert!(call(5, &[]).is_err());
+
+		// Storage written by the extension is kept if the contract succeeds and reverted if it
+		// traps.
+		assert_eq!(call(3, &[1]).unwrap().data, 3u32.to_le_bytes().to_vec());
+		assert_eq!(unhashed::get_raw(b"chain_extension"), Some(vec![1]));
+		assert!(call(4, &[2]).is_err());
+		assert_eq!(unhashed::get_raw(b"chain_extension"), Some(vec![1]));
+	})
+}
+
+const CODE_SET_CODE_HASH: &str = r#"
+(module
+	(import "env" "ext_scratch_read" (func $ext_scratch_read (param i32 i32 i32)))
+	(import "env" "ext_return" (func $ext_return (param i32 i32)))
+	(import "env" "ext_set_code_hash" (func $ext_set_code_hash (param i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func (export "deploy"))
+
+	;; Expects the 32 bytes of the new code hash as input. Returns the result code of
+	;; `ext_set_code_hash` as a single byte.
+	(func (export "call")
+		(call $ext_scratch_read
+			(i32.const 0)	;; Pointer in memory to the place where to copy.
+			(i32.const 0)	;; Offset from the start of the scratch buffer.
+			(i32.const 32)	;; Count of bytes to copy.
+		)
+		(i32.store8
+			(i32.const 32)
+			(call $ext_set_code_hash
+				(i32.const 0)	;; Pointer to the code hash.
+				(i32.const 32)	;; Length of the code hash.
+			)
+		)
+		(call $ext_return (i32.const 32) (i32.const 1))
+	)
+)
+"#;
+
+const CODE_RETURN_CONSTANT: &str = r#"
+(module
+	(import "env" "ext_return" (func $ext_return (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $ext_return (i32.const 0) (i32.const 4))
+	)
+
+	(data (i32.const 0) "\02\02\02\02")
+)
+"#;
+
+#[test]
+fn set_code_hash_and_remove_code() {
+	let (set_code_wasm, set_code_hash) = compile_module::<Test>(CODE_SET_CODE_HASH).unwrap();
+	let (constant_wasm, constant_hash) = compile_module::<Test>(CODE_RETURN_CONSTANT).unwrap();
+	let set_code_deposit = 2 * set_code_wasm.len() as u64;
+	let constant_deposit = 2 * constant_wasm.len() as u64;
+
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.code_deposit_per_byte(2)
+		.build()
+		.execute_with(|| {
+			Balances::deposit_creating(&ALICE, 1_000_000);
+			Balances::deposit_creating(&CHARLIE, 1_000_000);
+
+			// Storing code reserves a deposit from the sender.
+			assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, set_code_wasm.clone()));
+			assert_ok!(Contract::put_code(Origin::signed(CHARLIE), 100_000, constant_wasm));
+			assert_eq!(Balances::reserved_balance(&ALICE), set_code_deposit);
+			assert_eq!(Balances::reserved_balance(&CHARLIE), constant_deposit);
+			assert_eq!(
+				CodeInfoOf::<Test>::get(set_code_hash),
+				Some(RawCodeInfo { owner: ALICE, deposit: set_code_deposit, refcount: 0 }),
+			);
+
+			// Storing the same code again doesn't reserve another deposit.
+			assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, set_code_wasm));
+			assert_eq!(Balances::reserved_balance(&ALICE), set_code_deposit);
+
+			assert_ok!(Contract::instantiate(
+				Origin::signed(ALICE),
+				100,
+				100_000,
+				set_code_hash.into(),
+				vec![],
+			));
+			assert_eq!(CodeInfoOf::<Test>::get(set_code_hash).unwrap().refcount, 1);
+
+			// Code can only be removed by its owner and while no contract uses it.
+			assert_err!(
+				Contract::remove_code(Origin::signed(ALICE), H256::repeat_byte(1)),
+				Error::<Test>::CodeNotFound,
+			);
+			assert_err!(
+				Contract::remove_code(Origin::signed(ALICE), constant_hash),
+				Error::<Test>::NotCodeOwner,
+			);
+			assert_err!(
+				Contract::remove_code(Origin::signed(ALICE), set_code_hash),
+				Error::<Test>::CodeInUse,
+			);
+
+			// Switching to code that doesn't exist fails and leaves the contract untouched.
+			let result = <Module<Test>>::bare_call(
+				ALICE,
+				BOB,
+				0,
+				100_000,
+				H256::repeat_byte(1).encode(),
+			).unwrap();
+			assert_eq!(result.data, vec![1]);
+			assert_eq!(
+				ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap().code_hash,
+				set_code_hash,
+			);
+
+			// Switching to stored code updates the contract and the reference counts.
+			let result = <Module<Test>>::bare_call(ALICE, BOB, 0, 100_000, constant_hash.encode())
+				.unwrap();
+			assert_eq!(result.data, vec![0]);
+			assert_eq!(
+				ContractInfoOf::<Test>::get(BOB).unwrap().get_alive().unwrap().code_hash,
+				constant_hash,
+			);
+			assert_eq!(CodeInfoOf::<Test>::get(set_code_hash).unwrap().refcount, 0);
+			assert_eq!(CodeInfoOf::<Test>::get(constant_hash).unwrap().refcount, 1);
+
+			// The next call executes the new code.
+			let result = <Module<Test>>::bare_call(ALICE, BOB, 0, 100_000, vec![]).unwrap();
+			assert_eq!(result.data, vec![2, 2, 2, 2]);
+
+			// The unused code can now be removed and its deposit is refunded.
+			assert_ok!(Contract::remove_code(Origin::signed(ALICE), set_code_hash));
+			assert_eq!(Balances::reserved_balance(&ALICE), 0);
+			assert!(!CodeInfoOf::<Test>::exists(set_code_hash));
+			assert!(!CodeStorage::<Test>::exists(set_code_hash));
+			assert!(!PristineCode::<Test>::exists(set_code_hash));
+			assert_eq!(
+				System::events().pop().map(|record| record.event),
+				Some(MetaEvent::contract(RawEvent::CodeRemoved(set_code_hash.into()))),
+			);
+		});
+}
+
+const CODE_DEBUG: &str = r#"
+(module
+	(import "env" "ext_scratch_size" (func $ext_scratch_size (result i32)))
+	(import "env" "ext_scratch_read" (func $ext_scratch_read (param i32 i32 i32)))
+	(import "env" "ext_address" (func $ext_address))
+	(import "env" "ext_call" (func $ext_call (param i32 i32 i64 i32 i32 i32 i32) (result i32)))
+	(import "env" "ext_println" (func $ext_println (param i32 i32)))
+	(import "env" "ext_return" (func $ext_return (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func (export "deploy"))
+
+	;; With non-empty input data, prints a line, calls DJANGO and then calls itself with empty
+	;; input data. With empty input data, prints a line and traps.
+	(func (export "call")
+		(if (i32.eqz (call $ext_scratch_size))
+			(then
+				(call $ext_println (i32.const 40) (i32.const 5))
+				(unreachable)
+			)
+		)
+		(call $ext_println (i32.const 32) (i32.const 5))
+
+		(drop
+			(call $ext_call
+				(i32.const 0)	;; Pointer to the address of DJANGO.
+				(i32.const 8)	;; Length of the address.
+				(i64.const 0)	;; How much gas to devote for the execution. 0 = all.
+				(i32.const 8)	;; Pointer to the buffer with value to transfer.
+				(i32.const 8)	;; Length of the buffer with value to transfer.
+				(i32.const 0)	;; Pointer to input data buffer address.
+				(i32.const 0)	;; Length of input data buffer.
+			)
+		)
+
+		;; Read own address into memory.
+		(call $ext_address)
+		(call $ext_scratch_read
+			(i32.const 16)	;; Pointer to write address to.
+			(i32.const 0)	;; Offset into scratch buffer.
+			(i32.const 8)	;; Length of encoded address.
+		)
+		(drop
+			(call $ext_call
+				(i32.const 16)	;; Pointer to own address.
+				(i32.const 8)	;; Length of own address.
+				(i64.const 0)	;; How much gas to devote for the execution. 0 = all.
+				(i32.const 8)	;; Pointer to the buffer with value to transfer.
+				(i32.const 8)	;; Length of the buffer with value to transfer.
+				(i32.const 0)	;; Pointer to input data buffer address.
+				(i32.const 0)	;; Length of input data buffer.
+			)
+		)
+
+		(call $ext_return (i32.const 48) (i32.const 2))
+	)
+
+	;; Address of DJANGO.
+	(data (i32.const 0) "\04\00\00\00\00\00\00\00")
+	(data (i32.const 32) "outer")
+	(data (i32.const 40) "inner")
+	(data (i32.const 48) "ok")
+)
+"#;
+
+#[test]
+fn debug_message_and_call_trace() {
+	let (wasm, code_hash) = compile_module::<Test>(CODE_DEBUG).unwrap();
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, wasm));
+		assert_ok!(Contract::instantiate(
+			Origin::signed(ALICE),
+			100,
+			100_000,
+			code_hash.into(),
+			vec![],
+		));
+
+		let (result, debug) = <Module<Test>>::bare_call_with_debug(ALICE, BOB, 0, 100_000, vec![1]);
+		assert_eq!(result.unwrap().data, b"ok".to_vec());
+
+		// Lines printed by a call that trapped are kept.
+		assert_eq!(debug.message, b"outer\ninner\n".to_vec());
+
+		let calls = debug.calls
+			.iter()
+			.map(|c| (c.kind, c.depth, c.caller, c.callee, c.status, c.output.clone(), c.trap_reason))
+			.collect::<Vec<_>>();
+		assert_eq!(calls, vec![
+			(CallKind::Call, 0, ALICE, BOB, Some(0), b"ok".to_vec(), None),
+			(CallKind::Call, 1, BOB, DJANGO, Some(0), vec![], None),
+			(CallKind::Call, 1, BOB, BOB, None, vec![], Some("contract trapped during execution")),
+		]);
+		assert!(debug.calls[0].gas_used > debug.calls[1].gas_used + debug.calls[2].gas_used);
+	});
+}
+
+#[test]
+fn bare_instantiate_returns_address_gas_and_events() {
+	let (wasm, code_hash) = compile_module::<Test>(CODE_RETURN_FROM_START_FN).unwrap();
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		Balances::deposit_creating(&ALICE, 1_000_000);
+		Balances::deposit_creating(&CHARLIE, 1_000_000);
+
+		// Upload the code and instantiate a contract from it.
+		let outcome = <Module<Test>>::bare_instantiate(
+			ALICE,
+			100,
+			100_000,
+			Code::Upload(wasm),
+			vec![],
+		);
+		let (address, output) = outcome.result.unwrap();
+		assert_eq!(address, BOB);
+		assert_eq!(output.data, vec![1, 2, 3, 4]);
+		assert!(ContractInfoOf::<Test>::exists(BOB));
+
+		// The consumed gas is what the origin paid for, given a gas price of 2.
+		assert!(outcome.gas_consumed > 0);
+		assert_eq!(Balances::free_balance(ALICE), 1_000_000 - 100 - 2 * outcome.gas_consumed);
+
+		// Only the events deposited by the instantiation are returned.
+		assert_eq!(outcome.events, vec![
+			MetaEvent::contract(RawEvent::CodeStored(code_hash.into())),
+			MetaEvent::balances(pallet_balances::RawEvent::NewAccount(BOB, 100)),
+			MetaEvent::contract(RawEvent::Transfer(ALICE, BOB, 100)),
+			MetaEvent::contract(RawEvent::ContractExecution(BOB, vec![1, 2, 3, 4])),
+			MetaEvent::contract(RawEvent::Instantiated(ALICE, BOB)),
+		]);
+
+		// Instantiate another contract from the stored code.
+		let outcome = <Module<Test>>::bare_instantiate(
+			CHARLIE,
+			100,
+			100_000,
+			Code::Existing(code_hash.into()),
+			vec![],
+		);
+		assert_eq!(outcome.result.unwrap().0, DJANGO);
+		assert_eq!(outcome.events, vec![
+			MetaEvent::balances(pallet_balances::RawEvent::NewAccount(DJANGO, 100)),
+			MetaEvent::contract(RawEvent::Transfer(CHARLIE, DJANGO, 100)),
+			MetaEvent::contract(RawEvent::ContractExecution(DJANGO, vec![1, 2, 3, 4])),
+			MetaEvent::contract(RawEvent::Instantiated(CHARLIE, DJANGO)),
+		]);
+
+		// Instantiating from code that isn't stored fails.
+		let outcome = <Module<Test>>::bare_instantiate(
+			CHARLIE,
+			100,
+			100_000,
+			Code::Existing(H256::repeat_byte(1)),
+			vec![],
+		);
+		assert!(outcome.result.is_err());
+		assert!(outcome.events.is_empty());
+	});
+}
+
+#[test]
+fn rent_projection() {
+	let (wasm, code_hash) = compile_module::<Test>(CODE_SET_RENT).unwrap();
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		Balances::deposit_creating(&ALICE, 1_000_000);
+		Balances::deposit_creating(&CHARLIE, 1_000_000);
+		assert_ok!(Contract::put_code(Origin::signed(ALICE), 100_000, wasm));
+		assert_ok!(Contract::instantiate(
+			Origin::signed(ALICE),
+			30_000,
+			100_000, code_hash.into(),
+			<Test as pallet_balances::Trait>::Balance::from(1_000u32).encode() // rent allowance
+		));
+
+		// The fee per block is (8 + 4 - 3) * 4 = 36, so the rent allowance of 1_000 lasts for
+		// 27 blocks.
+		let eviction_block = System::block_number() + 28;
+		assert_eq!(
+			<Module<Test>>::rent_projection(BOB),
+			Ok(RentProjection::EvictionAt(eviction_block)),
+		);
+
+		initialize_block(eviction_block - 1);
+		assert!(crate::rent::try_evict::<Test>(&BOB, 0) == crate::rent::RentOutcome::Ok);
+		initialize_block(eviction_block);
+		assert!(crate::rent::try_evict::<Test>(&BOB, 0) == crate::rent::RentOutcome::Evicted);
+
+		assert_eq!(<Module<Test>>::rent_projection(BOB), Err(ContractAccessError::IsTombstone));
+		assert_eq!(
+			<Module<Test>>::rent_projection(ALICE),
+			Err(ContractAccessError::ContractDoesntExist),
+		);
+
+		// A contract with a balance that offsets its storage doesn't pay any rent.
+		assert_ok!(Contract::instantiate(
+			Origin::signed(CHARLIE),
+			200_000,
+			100_000, code_hash.into(),
+			<Test as pallet_balances::Trait>::Balance::from(1_000u32).encode() // rent allowance
+		));
+		assert_eq!(<Module<Test>>::rent_projection(DJANGO), Ok(RentProjection::NoEviction));
+	});
+}
@@ -28,10 +28,10 @@
 
 use crate::gas::{Gas, GasMeter, Token};
 use crate::wasm::{prepare, runtime::Env, PrefabWasmModule};
-use crate::{CodeHash, CodeStorage, PristineCode, Schedule, Trait};
+use crate::{CodeHash, CodeInfo, CodeInfoOf, CodeStorage, PristineCode, Schedule, Trait};
 use sp_std::prelude::*;
-use sp_runtime::traits::{Hash, Bounded};
-use frame_support::StorageMap;
+use sp_runtime::traits::{Hash, Bounded, Saturating};
+use frame_support::{StorageMap, traits::{Get, ReservableCurrency}};
 
 /// Gas metering token that used for charging storing code into the code storage.
 ///
@@ -54,9 +54,11 @@ impl<T: Trait> Token<T> for PutCodeToken {
 /// Put code in the storage. The hash of code is used as a key and is returned
 /// as a result of this function.
 ///
-/// This function instruments the given code and caches it in the storage.
+/// This function instruments the given code and caches it in the storage. If the code isn't
+/// stored yet, a deposit proportional to its length is reserved from the `owner`.
 pub fn save<T: Trait>(
 	original_code: Vec<u8>,
+	owner: &T::AccountId,
 	gas_meter: &mut GasMeter<T>,
 	schedule: &Schedule,
 ) -> Result<CodeHash<T>, &'static str> {
@@ -72,6 +74,18 @@ pub fn save<T: Trait>(
 	let prefab_module = prepare::prepare_contract::<Env>(&original_code, schedule)?;
 	let code_hash = T::Hashing::hash(&original_code);
 
+	if !<PristineCode<T>>::exists(&code_hash) {
+		let deposit = T::CodeDepositPerByte::get()
+			.saturating_mul((original_code.len() as u32).into());
+		T::Currency::reserve(owner, deposit)
+			.map_err(|_| "not enough balance to reserve the code deposit")?;
+		<CodeInfoOf<T>>::insert(&code_hash, CodeInfo::<T> {
+			owner: owner.clone(),
+			deposit,
+			refcount: 0,
+		});
+	}
+
 	<CodeStorage<T>>::insert(code_hash, prefab_module);
 	<PristineCode<T>>::insert(code_hash, original_code);
 
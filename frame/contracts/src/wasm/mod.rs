@@ -198,6 +198,8 @@ mod tests {
 		transfers: Vec<TransferEntry>,
 		dispatches: Vec<DispatchEntry>,
 		restores: Vec<RestoreEntry>,
+		code_hashes: Vec<H256>,
+		debug_message: Vec<u8>,
 		// (topics, data)
 		events: Vec<(Vec<H256>, Vec<u8>)>,
 		next_account_id: u64,
@@ -278,6 +280,14 @@ mod tests {
 				delta,
 			});
 		}
+		fn set_code_hash(&mut self, code_hash: H256) -> Result<(), &'static str> {
+			self.code_hashes.push(code_hash);
+			Ok(())
+		}
+		fn append_debug_message(&mut self, message: &str) {
+			self.debug_message.extend_from_slice(message.as_bytes());
+			self.debug_message.push(b'\n');
+		}
 		fn caller(&self) -> &u64 {
 			&42
 		}
@@ -379,6 +389,12 @@ mod tests {
 				delta,
 			)
 		}
+		fn set_code_hash(&mut self, code_hash: H256) -> Result<(), &'static str> {
+			(**self).set_code_hash(code_hash)
+		}
+		fn append_debug_message(&mut self, message: &str) {
+			(**self).append_debug_message(message)
+		}
 		fn caller(&self) -> &u64 {
 			(**self).caller()
 		}
@@ -1764,4 +1780,42 @@ mod tests {
 			&mut gas_meter,
 		).unwrap();
 	}
+
+	/// A contract which hashes `len` bytes of its memory with SHA2 256.
+	fn code_hash_sha2_256(len: u32) -> String {
+		format!(r#"
+(module
+	(import "env" "ext_hash_sha2_256" (func $ext_hash_sha2_256 (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func (export "call")
+		(call $ext_hash_sha2_256
+			(i32.const 0)	;; Pointer to the input data.
+			(i32.const {})	;; Length of the input data.
+			(i32.const 0)	;; Pointer to the output buffer.
+		)
+	)
+	(func (export "deploy"))
+)
+"#, len)
+	}
+
+	#[test]
+	fn hashing_charges_gas_per_byte() {
+		let gas_left = |len: u32| {
+			let mut gas_meter = GasMeter::with_limit(50_000, 1);
+			let _ = execute(
+				&code_hash_sha2_256(len),
+				vec![],
+				MockExt::default(),
+				&mut gas_meter,
+			).unwrap();
+			gas_meter.gas_left()
+		};
+
+		// Each hashed byte is read from the sandbox memory and then hashed.
+		let schedule = crate::Schedule::default();
+		let per_byte = schedule.sandbox_data_read_cost + schedule.hash_sha2_256_per_byte_cost;
+		assert_eq!(gas_left(0) - gas_left(100), 100 * per_byte);
+	}
 }
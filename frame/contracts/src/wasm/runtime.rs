@@ -17,8 +17,9 @@
 //! Environment definition of the wasm smart-contract runtime.
 
 use crate::{Schedule, Trait, CodeHash, ComputeDispatchFee, BalanceOf};
+use crate::chain_extension::{ChainExtension, Environment};
 use crate::exec::{
-	Ext, ExecResult, ExecError, ExecReturnValue, StorageKey, TopicOf, STATUS_SUCCESS,
+	Ext, ExecResult, ExecError, ExecReturnValue, StorageKey, TopicOf, AccountIdOf, STATUS_SUCCESS,
 };
 use crate::gas::{Gas, GasMeter, Token, GasMeterResult, approx_gas_for_balance};
 use sp_sandbox;
@@ -27,7 +28,13 @@ use sp_std::prelude::*;
 use sp_std::convert::TryInto;
 use sp_std::mem;
 use codec::{Decode, Encode};
-use sp_runtime::traits::{Bounded, SaturatedConversion};
+use sp_runtime::{DispatchError, traits::{Bounded, SaturatedConversion}};
+use sp_io::hashing::{
+	keccak_256,
+	blake2_256,
+	blake2_128,
+	sha2_256,
+};
 
 /// The value returned from ext_call and ext_instantiate contract external functions if the call or
 /// instantiation traps. This value is chosen as if the execution does not trap, the return value
@@ -131,6 +138,18 @@ pub enum RuntimeToken {
 	/// (topic_count, data_bytes): A buffer of the given size is posted as an event indexed with the
 	/// given number of topics.
 	DepositEvent(u32, u32),
+	/// The given number of bytes is hashed with SHA2 256.
+	HashSha2256(u32),
+	/// The given number of bytes is hashed with Keccak 256.
+	HashKeccak256(u32),
+	/// The given number of bytes is hashed with BLAKE2 256.
+	HashBlake2256(u32),
+	/// The given number of bytes is hashed with BLAKE2 128.
+	HashBlake2128(u32),
+	/// A public key is recovered from an ECDSA signature.
+	EcdsaRecover,
+	/// Gas charged by the chain extension.
+	ChainExtension(Gas),
 }
 
 impl<T: Trait> Token<T> for RuntimeToken {
@@ -169,6 +188,20 @@ impl<T: Trait> Token<T> for RuntimeToken {
 					)
 			},
 			ComputedDispatchFee(gas) => Some(gas),
+			HashSha2256(byte_count) => metadata
+				.hash_sha2_256_per_byte_cost
+				.checked_mul(byte_count.into()),
+			HashKeccak256(byte_count) => metadata
+				.hash_keccak_256_per_byte_cost
+				.checked_mul(byte_count.into()),
+			HashBlake2256(byte_count) => metadata
+				.hash_blake2_256_per_byte_cost
+				.checked_mul(byte_count.into()),
+			HashBlake2128(byte_count) => metadata
+				.hash_blake2_128_per_byte_cost
+				.checked_mul(byte_count.into()),
+			EcdsaRecover => Some(metadata.ecdsa_recover_cost),
+			ChainExtension(gas) => Some(gas),
 		};
 
 		value.unwrap_or_else(|| Bounded::max_value())
@@ -712,6 +745,25 @@ define_env!(Env, <E: Ext>,
 		Ok(())
 	},
 
+	// Replace the code of the calling contract with the code stored under the given code hash.
+	//
+	// The new code is used from the next call into the contract on, the currently executing call
+	// keeps running the old code. The storage of the contract is left untouched.
+	//
+	// `code_hash_ptr`, `code_hash_len` - the pointer and the length of a buffer that encodes
+	// the code hash of the new code.
+	//
+	// Returns 0 on success and 1 if there is no valid code stored under the given code hash.
+	ext_set_code_hash(ctx, code_hash_ptr: u32, code_hash_len: u32) -> u32 => {
+		let code_hash: CodeHash<<E as Ext>::T> =
+			read_sandbox_memory_as(ctx, code_hash_ptr, code_hash_len)?;
+
+		match ctx.ext.set_code_hash(code_hash) {
+			Ok(()) => Ok(0),
+			Err(_) => Ok(1),
+		}
+	},
+
 	// Returns the size of the scratch buffer.
 	//
 	// For more details on the scratch buffer see `ext_scratch_read`.
@@ -820,11 +872,14 @@ define_env!(Env, <E: Ext>,
 
 	// Prints utf8 encoded string from the data buffer.
 	// Only available on `--dev` chains.
-	// This function may be removed at any time, superseded by a more general contract debugging feature.
+	//
+	// The string is printed to the node log and, when executed in an RPC dry-run, appended as a
+	// line to the debug message buffer returned to the caller of the RPC.
 	ext_println(ctx, str_ptr: u32, str_len: u32) => {
 		let data = read_sandbox_memory(ctx, str_ptr, str_len)?;
 		if let Ok(utf8) = core::str::from_utf8(&data) {
 			sp_runtime::print(utf8);
+			ctx.ext.append_debug_message(utf8);
 		}
 		Ok(())
 	},
@@ -861,8 +916,249 @@ define_env!(Env, <E: Ext>,
 			}
 		}
 	},
+
+	// Computes the SHA2 256-bit hash on the given input buffer.
+	//
+	// Returns the result directly into the given output buffer.
+	//
+	// # Note
+	//
+	// - The `input` and `output` buffer may overlap.
+	// - The output buffer is expected to hold at least 32 bytes (256 bits).
+	// - It is the callers responsibility to provide an output buffer that
+	//   is large enough to hold the expected amount of bytes returned by the
+	//   chosen hash function.
+	//
+	// # Parameters
+	//
+	// - `input_ptr`: the pointer into the linear memory where the input
+	//                data is placed.
+	// - `input_len`: the length of the input data in bytes.
+	// - `output_ptr`: the pointer into the linear memory where the output
+	//                 data is placed. The function will write the result
+	//                 directly into this buffer.
+	ext_hash_sha2_256(ctx, input_ptr: u32, input_len: u32, output_ptr: u32) => {
+		let token = RuntimeToken::HashSha2256(input_len);
+		compute_hash_on_intermediate_buffer(ctx, token, sha2_256, input_ptr, input_len, output_ptr)
+	},
+
+	// Computes the KECCAK 256-bit hash on the given input buffer.
+	//
+	// Returns the result directly into the given output buffer.
+	//
+	// # Note
+	//
+	// - The `input` and `output` buffer may overlap.
+	// - The output buffer is expected to hold at least 32 bytes (256 bits).
+	// - It is the callers responsibility to provide an output buffer that
+	//   is large enough to hold the expected amount of bytes returned by the
+	//   chosen hash function.
+	//
+	// # Parameters
+	//
+	// - `input_ptr`: the pointer into the linear memory where the input
+	//                data is placed.
+	// - `input_len`: the length of the input data in bytes.
+	// - `output_ptr`: the pointer into the linear memory where the output
+	//                 data is placed. The function will write the result
+	//                 directly into this buffer.
+	ext_hash_keccak_256(ctx, input_ptr: u32, input_len: u32, output_ptr: u32) => {
+		let token = RuntimeToken::HashKeccak256(input_len);
+		compute_hash_on_intermediate_buffer(ctx, token, keccak_256, input_ptr, input_len, output_ptr)
+	},
+
+	// Computes the BLAKE2 256-bit hash on the given input buffer.
+	//
+	// Returns the result directly into the given output buffer.
+	//
+	// # Note
+	//
+	// - The `input` and `output` buffer may overlap.
+	// - The output buffer is expected to hold at least 32 bytes (256 bits).
+	// - It is the callers responsibility to provide an output buffer that
+	//   is large enough to hold the expected amount of bytes returned by the
+	//   chosen hash function.
+	//
+	// # Parameters
+	//
+	// - `input_ptr`: the pointer into the linear memory where the input
+	//                data is placed.
+	// - `input_len`: the length of the input data in bytes.
+	// - `output_ptr`: the pointer into the linear memory where the output
+	//                 data is placed. The function will write the result
+	//                 directly into this buffer.
+	ext_hash_blake2_256(ctx, input_ptr: u32, input_len: u32, output_ptr: u32) => {
+		let token = RuntimeToken::HashBlake2256(input_len);
+		compute_hash_on_intermediate_buffer(ctx, token, blake2_256, input_ptr, input_len, output_ptr)
+	},
+
+	// Computes the BLAKE2 128-bit hash on the given input buffer.
+	//
+	// Returns the result directly into the given output buffer.
+	//
+	// # Note
+	//
+	// - The `input` and `output` buffer may overlap.
+	// - The output buffer is expected to hold at least 16 bytes (128 bits).
+	// - It is the callers responsibility to provide an output buffer that
+	//   is large enough to hold the expected amount of bytes returned by the
+	//   chosen hash function.
+	//
+	// # Parameters
+	//
+	// - `input_ptr`: the pointer into the linear memory where the input
+	//                data is placed.
+	// - `input_len`: the length of the input data in bytes.
+	// - `output_ptr`: the pointer into the linear memory where the output
+	//                 data is placed. The function will write the result
+	//                 directly into this buffer.
+	ext_hash_blake2_128(ctx, input_ptr: u32, input_len: u32, output_ptr: u32) => {
+		let token = RuntimeToken::HashBlake2128(input_len);
+		compute_hash_on_intermediate_buffer(ctx, token, blake2_128, input_ptr, input_len, output_ptr)
+	},
+
+	// Recovers the ECDSA public key from the given message hash and signature and return 0.
+	// If the signature could not be recovered then this function will return 1 and leave the
+	// output buffer untouched.
+	//
+	// The public key is written in its 33 bytes compressed form.
+	//
+	// - signature_ptr: the pointer into the linear memory where the 65 bytes of the signature
+	//   (`r ++ s ++ v`) are placed.
+	// - message_hash_ptr: the pointer into the linear memory where the 32 bytes of the hash of
+	//   the signed message are placed.
+	// - output_ptr: the pointer into the linear memory where the 33 bytes of the recovered public
+	//   key are written to.
+	ext_ecdsa_recover(ctx, signature_ptr: u32, message_hash_ptr: u32, output_ptr: u32) -> u32 => {
+		charge_gas(ctx.gas_meter, ctx.schedule, RuntimeToken::EcdsaRecover)?;
+
+		let mut signature: [u8; 65] = [0; 65];
+		read_sandbox_memory_into_buf(ctx, signature_ptr, &mut signature)?;
+		let mut message_hash: [u8; 32] = [0; 32];
+		read_sandbox_memory_into_buf(ctx, message_hash_ptr, &mut message_hash)?;
+
+		match sp_io::crypto::secp256k1_ecdsa_recover_compressed(&signature, &message_hash) {
+			Ok(public_key) => {
+				write_sandbox_memory(
+					ctx.schedule,
+					ctx.gas_meter,
+					&ctx.memory,
+					output_ptr,
+					&public_key,
+				)?;
+				Ok(0)
+			}
+			Err(_) => Ok(1),
+		}
+	},
+
+	// Call a function of the chain extension registered by the runtime and return its result.
+	//
+	// The scratch buffer is cleared before the call and holds the output of the chain extension
+	// afterwards.
+	//
+	// Traps if the runtime did not enable a chain extension or if the chain extension fails.
+	//
+	// - func_id: the id of the chain extension function to call.
+	// - input_ptr: the pointer into the linear memory where the input for the chain extension
+	//   is placed.
+	// - input_len: the length of the input in bytes.
+	ext_call_chain_extension(ctx, func_id: u32, input_ptr: u32, input_len: u32) -> u32 => {
+		if !<E::T as Trait>::ChainExtension::enabled() {
+			return Err(sp_sandbox::HostError);
+		}
+
+		ctx.scratch_buf.clear();
+		let mut env = ChainExtensionEnv { ctx: &mut *ctx, input_ptr, input_len };
+		<E::T as Trait>::ChainExtension::call(func_id, &mut env)
+			.map_err(|_| sp_sandbox::HostError)
+	},
 );
 
+/// The [`Environment`] handed to the chain extension while it handles `ext_call_chain_extension`.
+struct ChainExtensionEnv<'a, 'b, E: Ext + 'b> {
+	ctx: &'a mut Runtime<'b, E>,
+	input_ptr: u32,
+	input_len: u32,
+}
+
+impl<'a, 'b, E: Ext + 'b> Environment<E::T> for ChainExtensionEnv<'a, 'b, E> {
+	fn address(&self) -> &AccountIdOf<E::T> {
+		self.ctx.ext.address()
+	}
+
+	fn caller(&self) -> &AccountIdOf<E::T> {
+		self.ctx.ext.caller()
+	}
+
+	fn charge_gas(&mut self, amount: Gas) -> Result<(), DispatchError> {
+		charge_gas(self.ctx.gas_meter, self.ctx.schedule, RuntimeToken::ChainExtension(amount))
+			.map_err(|_| DispatchError::Other("out of gas"))
+	}
+
+	fn input(&mut self) -> Result<Vec<u8>, DispatchError> {
+		self.read(self.input_ptr, self.input_len)
+	}
+
+	fn read(&mut self, ptr: u32, len: u32) -> Result<Vec<u8>, DispatchError> {
+		read_sandbox_memory(self.ctx, ptr, len)
+			.map_err(|_| DispatchError::Other("failed to read from sandbox memory"))
+	}
+
+	fn write(&mut self, ptr: u32, buf: &[u8]) -> Result<(), DispatchError> {
+		write_sandbox_memory(self.ctx.schedule, self.ctx.gas_meter, &self.ctx.memory, ptr, buf)
+			.map_err(|_| DispatchError::Other("failed to write to sandbox memory"))
+	}
+
+	fn set_output(&mut self, output: Vec<u8>) {
+		self.ctx.scratch_buf = output;
+	}
+}
+
+/// Computes the given hash function on the supplied input.
+///
+/// Reads from the sandboxed input buffer into an intermediate buffer.
+/// Returns the result directly to the output buffer of the sandboxed memory.
+///
+/// The gas meter is charged with `token` for the hashing itself, on top of the costs of reading
+/// and writing the sandbox memory.
+///
+/// It is the callers responsibility to provide an output buffer that
+/// is large enough to hold the expected amount of bytes returned by the
+/// chosen hash function.
+///
+/// # Note
+///
+/// The `input` and `output` buffers may overlap.
+fn compute_hash_on_intermediate_buffer<E, F, R>(
+	ctx: &mut Runtime<E>,
+	token: RuntimeToken,
+	hash_fn: F,
+	input_ptr: u32,
+	input_len: u32,
+	output_ptr: u32,
+) -> Result<(), sp_sandbox::HostError>
+where
+	E: Ext,
+	F: FnOnce(&[u8]) -> R,
+	R: AsRef<[u8]>,
+{
+	charge_gas(ctx.gas_meter, ctx.schedule, token)?;
+	// Copy input into supervisor memory.
+	let input = read_sandbox_memory(ctx, input_ptr, input_len)?;
+	// Compute the hash on the input buffer using the given hash function.
+	let hash = hash_fn(&input);
+	// Write the resulting hash back into the sandboxed output buffer.
+	write_sandbox_memory(
+		ctx.schedule,
+		ctx.gas_meter,
+		&ctx.memory,
+		output_ptr,
+		hash.as_ref(),
+	)?;
+	Ok(())
+}
+
 /// Finds duplicates in a given vector.
 ///
 /// This function has complexity of O(n log n) and no additional memory is required, although
@@ -15,30 +15,79 @@
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Democratic system: Handles administration of general stakeholder voting.
+//!
+//! ## Voting
+//!
+//! Each account keeps a single [`Voting`](./enum.Voting.html) record, kept under `VotingOf`. An
+//! account is either voting directly, holding a vote for each referendum it takes part in, or it
+//! is delegating some of its balance with a conviction to another account.
+//!
+//! A direct vote is an [`AccountVote`](./enum.AccountVote.html): either a standard aye or nay vote
+//! backed by a balance and a conviction, or a split vote which places separate balances behind
+//! aye, nay and (optionally) abstain, all without conviction. Votes are tallied as they are cast,
+//! so every ongoing referendum carries a running tally.
+//!
+//! Votes may be removed with `remove_vote` at any time. Removing a vote from an ongoing referendum
+//! takes it out of the tally; removing a winning vote from a finished referendum keeps its balance
+//! locked until the conviction lock period it committed to has passed. Anyone may remove the vote
+//! of another account with `remove_other_vote` once doing so is no longer subject to any lock.
+//!
+//! The balance locked for voting is the largest of the balances behind the account's current
+//! votes, its delegation and any prior lock that has not yet expired. `unlock` recomputes this and
+//! releases whatever is no longer needed.
+//!
+//! ### Migration
+//!
+//! Chains upgrading from the previous layout (`VotersFor`, `VoteOf` and `Delegations`) are
+//! migrated over the blocks following the upgrade, a bounded number of votes and delegations at a
+//! time, and in full before any referendum matures: ongoing referenda are rewritten with a tally,
+//! their votes become standard votes backed by the voter's free balance and delegations carry
+//! the delegator's free balance. Delegators who also voted directly in an ongoing referendum keep
+//! their direct votes and drop their delegation, and votes or delegations made since the upgrade
+//! take precedence over legacy ones. Conviction locks placed by the previous layout are kept under
+//! their own identifier and expire when they were set to.
+//!
+//! The voting proxies of the previous layout are removed on the runtime upgrade; accounts that
+//! want another account to vote for them register it with the proxy module instead.
 #![recursion_limit="128"]
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use sp_std::prelude::*;
-use sp_std::{result, convert::TryFrom};
 use sp_runtime::{
-	RuntimeDebug, DispatchResult,
-	traits::{Zero, Bounded, CheckedMul, CheckedDiv, EnsureOrigin, Hash, Dispatchable, Saturating},
+	DispatchResult, DispatchError,
+	traits::{Zero, Bounded, EnsureOrigin, Hash, Dispatchable, Saturating},
 };
-use codec::{Ref, Encode, Decode, Input, Output};
+use codec::{Ref, Decode};
 use frame_support::{
 	decl_module, decl_storage, decl_event, decl_error, ensure, Parameter,
 	weights::SimpleDispatchInfo,
+	storage,
 	traits::{
 		Currency, ReservableCurrency, LockableCurrency, WithdrawReason, LockIdentifier, Get,
-		OnFreeBalanceZero, OnUnbalanced
+		OnUnbalanced
 	}
 };
 use frame_system::{self as system, ensure_signed, ensure_root};
 
 mod vote_threshold;
+mod vote;
+mod conviction;
+mod types;
 pub use vote_threshold::{Approved, VoteThreshold};
+pub use vote::{Vote, AccountVote, Voting, PriorLock};
+pub use conviction::Conviction;
+pub use types::{ReferendumInfo, ReferendumStatus, Tally, UnvoteScope, Delegated};
 
-const DEMOCRACY_ID: LockIdentifier = *b"democrac";
+/// The lock placed on the voting balance of an account.
+const DEMOCRACY_ID: LockIdentifier = *b"democvot";
+
+/// The lock of the previous layout, placed on delegated balances until they are undelegated and
+/// on winning votes for their conviction's lock period. It is removed from every account whose
+/// votes or delegation are migrated, which are locked under `DEMOCRACY_ID` instead.
+const LEGACY_DEMOCRACY_ID: LockIdentifier = *b"democrac";
+
+/// The number of legacy votes and delegations migrated per block.
+const MIGRATION_BATCH: u32 = 1_000;
 
 /// A proposal index.
 pub type PropIndex = u32;
@@ -46,131 +95,6 @@ pub type PropIndex = u32;
 /// A referendum index.
 pub type ReferendumIndex = u32;
 
-/// A value denoting the strength of conviction of a vote.
-#[derive(Encode, Decode, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, RuntimeDebug)]
-pub enum Conviction {
-	/// 0.1x votes, unlocked.
-	None,
-	/// 1x votes, locked for an enactment period following a successful vote.
-	Locked1x,
-	/// 2x votes, locked for 2x enactment periods following a successful vote.
-	Locked2x,
-	/// 3x votes, locked for 4x...
-	Locked3x,
-	/// 4x votes, locked for 8x...
-	Locked4x,
-	/// 5x votes, locked for 16x...
-	Locked5x,
-	/// 6x votes, locked for 32x...
-	Locked6x,
-}
-
-impl Default for Conviction {
-	fn default() -> Self {
-		Conviction::None
-	}
-}
-
-impl From<Conviction> for u8 {
-	fn from(c: Conviction) -> u8 {
-		match c {
-			Conviction::None => 0,
-			Conviction::Locked1x => 1,
-			Conviction::Locked2x => 2,
-			Conviction::Locked3x => 3,
-			Conviction::Locked4x => 4,
-			Conviction::Locked5x => 5,
-			Conviction::Locked6x => 6,
-		}
-	}
-}
-
-impl TryFrom<u8> for Conviction {
-	type Error = ();
-	fn try_from(i: u8) -> result::Result<Conviction, ()> {
-		Ok(match i {
-			0 => Conviction::None,
-			1 => Conviction::Locked1x,
-			2 => Conviction::Locked2x,
-			3 => Conviction::Locked3x,
-			4 => Conviction::Locked4x,
-			5 => Conviction::Locked5x,
-			6 => Conviction::Locked6x,
-			_ => return Err(()),
-		})
-	}
-}
-
-impl Conviction {
-	/// The amount of time (in number of periods) that our conviction implies a successful voter's
-	/// balance should be locked for.
-	fn lock_periods(self) -> u32 {
-		match self {
-			Conviction::None => 0,
-			Conviction::Locked1x => 1,
-			Conviction::Locked2x => 2,
-			Conviction::Locked3x => 4,
-			Conviction::Locked4x => 8,
-			Conviction::Locked5x => 16,
-			Conviction::Locked6x => 32,
-		}
-	}
-
-	/// The votes of a voter of the given `balance` with our conviction.
-	fn votes<
-		B: From<u8> + Zero + Copy + CheckedMul + CheckedDiv + Bounded
-	>(self, balance: B) -> (B, B) {
-		match self {
-			Conviction::None => {
-				let r = balance.checked_div(&10u8.into()).unwrap_or_else(Zero::zero);
-				(r, r)
-			}
-			x => (
-				balance.checked_mul(&u8::from(x).into()).unwrap_or_else(B::max_value),
-				balance,
-			)
-		}
-	}
-}
-
-impl Bounded for Conviction {
-	fn min_value() -> Self {
-		Conviction::None
-	}
-
-	fn max_value() -> Self {
-		Conviction::Locked6x
-	}
-}
-
-const MAX_RECURSION_LIMIT: u32 = 16;
-
-/// A number of lock periods, plus a vote, one way or the other.
-#[derive(Copy, Clone, Eq, PartialEq, Default, RuntimeDebug)]
-pub struct Vote {
-	pub aye: bool,
-	pub conviction: Conviction,
-}
-
-impl Encode for Vote {
-	fn encode_to<T: Output>(&self, output: &mut T) {
-		output.push_byte(u8::from(self.conviction) | if self.aye { 0b1000_0000 } else { 0 });
-	}
-}
-
-impl codec::EncodeLike for Vote {}
-
-impl Decode for Vote {
-	fn decode<I: Input>(input: &mut I) -> core::result::Result<Self, codec::Error> {
-		let b = input.read_byte()?;
-		Ok(Vote {
-			aye: (b & 0b1000_0000) == 0b1000_0000,
-			conviction: Conviction::try_from(b & 0b0111_1111)
-				.map_err(|_| codec::Error::from("Invalid conviction"))?,
-		})
-	}
-}
-
 type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
 type NegativeImbalanceOf<T> =
 <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
@@ -235,31 +159,6 @@ pub trait Trait: frame_system::Trait + Sized {
 	type Slash: OnUnbalanced<NegativeImbalanceOf<Self>>;
 }
 
-/// Info regarding an ongoing referendum.
-#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
-pub struct ReferendumInfo<BlockNumber: Parameter, Hash: Parameter> {
-	/// When voting on this referendum will end.
-	end: BlockNumber,
-	/// The hash of the proposal being voted on.
-	proposal_hash: Hash,
-	/// The thresholding mechanism to determine whether it passed.
-	threshold: VoteThreshold,
-	/// The delay (in blocks) to wait after a successful referendum before deploying.
-	delay: BlockNumber,
-}
-
-impl<BlockNumber: Parameter, Hash: Parameter> ReferendumInfo<BlockNumber, Hash> {
-	/// Create a new instance.
-	pub fn new(
-		end: BlockNumber,
-		proposal_hash: Hash,
-		threshold: VoteThreshold,
-		delay: BlockNumber
-	) -> Self {
-		ReferendumInfo { end, proposal_hash, threshold, delay }
-	}
-}
-
 decl_storage! {
 	trait Store for Module<T: Trait> as Democracy {
 		/// The number of (public) proposals that have been made so far.
@@ -279,25 +178,31 @@ decl_storage! {
 		pub LowestUnbaked get(fn lowest_unbaked) build(|_| 0 as ReferendumIndex): ReferendumIndex;
 		/// Information concerning any given referendum.
 		pub ReferendumInfoOf get(fn referendum_info):
-			map ReferendumIndex => Option<ReferendumInfo<T::BlockNumber, T::Hash>>;
+			map ReferendumIndex => Option<ReferendumInfo<T::BlockNumber, T::Hash, BalanceOf<T>>>;
 		/// Queue of successful referenda to be dispatched. Stored ordered by block number.
 		pub DispatchQueue get(fn dispatch_queue): Vec<(T::BlockNumber, T::Hash, ReferendumIndex)>;
 
-		/// Get the voters for the current proposal.
-		pub VotersFor get(fn voters_for): map ReferendumIndex => Vec<T::AccountId>;
+		/// The voting record of each account: either its direct votes or its delegation, together
+		/// with the delegations it has received and any prior lock still in force.
+		pub VotingOf get(fn voting_of):
+			map T::AccountId => Voting<BalanceOf<T>, T::AccountId, T::BlockNumber>;
+
+		/// Whether the votes and delegations of the previous layout have been moved into
+		/// `VotingOf`. Always true for chains started with this layout.
+		pub VotingMigrated build(|_| true): bool;
 
-		/// Get the vote in a given referendum of a particular voter. The result is meaningful only
-		/// if `voters_for` includes the voter when called with the referendum (you'll get the
-		/// default `Vote` value otherwise). If you don't want to check `voters_for`, then you can
-		/// also check for simple existence with `VoteOf::exists` first.
-		pub VoteOf get(fn vote_of): map (ReferendumIndex, T::AccountId) => Vote;
+		/// Whether the voting proxies of the previous layout have been removed. Always true for
+		/// chains started with this layout.
+		pub ProxiesMigrated build(|_| true): bool;
 
-		/// Who is able to vote for whom. Value is the fund-holding account, key is the
-		/// vote-transaction-sending account.
-		pub Proxy get(fn proxy): map T::AccountId => Option<T::AccountId>;
+		/// Deprecated: voters of a referendum in the previous layout. Only read by the migration.
+		VotersFor: map ReferendumIndex => Vec<T::AccountId>;
 
-		/// Get the account (and lock periods) to which another account is delegating vote.
-		pub Delegations get(fn delegations): linked_map T::AccountId => (T::AccountId, Conviction);
+		/// Deprecated: votes of the previous layout. Only read by the migration.
+		VoteOf: map (ReferendumIndex, T::AccountId) => Vote;
+
+		/// Deprecated: delegations of the previous layout. Only read by the migration.
+		Delegations: linked_map T::AccountId => (T::AccountId, Conviction);
 
 		/// True if the last referendum tabled was submitted externally. False if it was a public
 		/// proposal.
@@ -366,8 +271,6 @@ decl_error! {
 		ValueLow,
 		/// Proposal does not exist
 		ProposalMissing,
-		/// Not a proxy
-		NotProxy,
 		/// Unknown index
 		BadIndex,
 		/// Cannot cancel the same proposal twice
@@ -384,10 +287,6 @@ decl_error! {
 		NoProposal,
 		/// Identity may not veto a proposal twice
 		AlreadyVetoed,
-		/// Already a proxy
-		AlreadyProxy,
-		/// Wrong proxy
-		WrongProxy,
 		/// Not delegated
 		NotDelegated,
 		/// Preimage already noted
@@ -406,6 +305,23 @@ decl_error! {
 		PreimageInvalid,
 		/// No proposals waiting
 		NoneWaiting,
+		/// The given account did not vote on the referendum.
+		NotVoter,
+		/// The actor has no permission to conduct the action.
+		NoPermission,
+		/// The account is already delegating.
+		AlreadyDelegating,
+		/// An unexpected integer overflow occurred.
+		Overflow,
+		/// An unexpected integer underflow occurred.
+		Underflow,
+		/// Too high a balance was provided that the account cannot afford.
+		InsufficientFunds,
+		/// The account currently has votes attached to it and the operation cannot succeed until
+		/// these are removed, either through `remove_vote` or `remove_other_vote`.
+		VotesExist,
+		/// Delegation to oneself makes no sense.
+		Nonsense,
 	}
 }
 
@@ -483,33 +399,23 @@ decl_module! {
 		/// Vote in a referendum. If `vote.is_aye()`, the vote is to enact the proposal;
 		/// otherwise it is a vote to keep the status quo.
 		///
+		/// The dispatch origin of this call must be _Signed_.
+		///
+		/// - `ref_index`: The index of the referendum to vote for.
+		/// - `vote`: The vote configuration. Its balance must not exceed the free balance of the
+		///   voter and is locked until the vote is removed and any conviction period has passed.
+		///
 		/// # <weight>
-		/// - O(1).
+		/// - O(R) where R is the number of referendums the voter has voted on.
 		/// - One DB change, one DB entry.
 		/// # </weight>
 		#[weight = SimpleDispatchInfo::FixedNormal(200_000)]
 		fn vote(origin,
 			#[compact] ref_index: ReferendumIndex,
-			vote: Vote
+			vote: AccountVote<BalanceOf<T>>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			Self::do_vote(who, ref_index, vote)
-		}
-
-		/// Vote in a referendum on behalf of a stash. If `vote.is_aye()`, the vote is to enact
-		/// the proposal; otherwise it is a vote to keep the status quo.
-		///
-		/// # <weight>
-		/// - O(1).
-		/// - One DB change, one DB entry.
-		/// # </weight>
-		#[weight = SimpleDispatchInfo::FixedNormal(200_000)]
-		fn proxy_vote(origin,
-			#[compact] ref_index: ReferendumIndex,
-			vote: Vote
-		) -> DispatchResult {
-			let who = Self::proxy(ensure_signed(origin)?).ok_or(Error::<T>::NotProxy)?;
-			Self::do_vote(who, ref_index, vote)
+			Self::try_vote(&who, ref_index, vote)
 		}
 
 		/// Schedule an emergency cancellation of a referendum. Cannot happen twice to the same
@@ -518,8 +424,8 @@ decl_module! {
 		fn emergency_cancel(origin, ref_index: ReferendumIndex) {
 			T::CancellationOrigin::ensure_origin(origin)?;
 
-			let info = Self::referendum_info(ref_index).ok_or(Error::<T>::BadIndex)?;
-			let h = info.proposal_hash;
+			let status = Self::referendum_status(ref_index).map_err(|_| Error::<T>::BadIndex)?;
+			let h = status.proposal_hash;
 			ensure!(!<Cancellations<T>>::exists(h), Error::<T>::AlreadyCanceled);
 
 			<Cancellations<T>>::insert(h, true);
@@ -636,91 +542,62 @@ decl_module! {
 			<DispatchQueue<T>>::put(items);
 		}
 
+		fn on_runtime_upgrade() {
+			Self::migrate_proxies();
+		}
+
 		fn on_initialize(n: T::BlockNumber) {
+			Self::migrate_voting(n, MIGRATION_BATCH);
+
 			if let Err(e) = Self::begin_block(n) {
 				sp_runtime::print(e);
 			}
 		}
 
-		/// Specify a proxy. Called by the stash.
+		/// Delegate the voting power (with some given conviction) of the sending account.
 		///
-		/// # <weight>
-		/// - One extra DB entry.
-		/// # </weight>
-		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
-		fn set_proxy(origin, proxy: T::AccountId) {
-			let who = ensure_signed(origin)?;
-			ensure!(!<Proxy<T>>::exists(&proxy), Error::<T>::AlreadyProxy);
-			<Proxy<T>>::insert(proxy, who)
-		}
-
-		/// Clear the proxy. Called by the proxy.
+		/// The balance delegated is locked for as long as it's delegated, and thereafter for the
+		/// time appropriate for the conviction's lock period.
 		///
-		/// # <weight>
-		/// - One DB clear.
-		/// # </weight>
-		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
-		fn resign_proxy(origin) {
-			let who = ensure_signed(origin)?;
-			<Proxy<T>>::remove(who);
-		}
-
-		/// Clear the proxy. Called by the stash.
+		/// The dispatch origin of this call must be _Signed_, and the signing account must either:
+		///   - be delegating already; or
+		///   - have no voting activity (if there is, then it will need to be removed/consolidated
+		///     through `remove_vote`).
 		///
-		/// # <weight>
-		/// - One DB clear.
-		/// # </weight>
-		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
-		fn remove_proxy(origin, proxy: T::AccountId) {
-			let who = ensure_signed(origin)?;
-			ensure!(
-				&Self::proxy(&proxy).ok_or(Error::<T>::NotProxy)? == &who,
-				Error::<T>::WrongProxy,
-			);
-			<Proxy<T>>::remove(proxy);
-		}
-
-		/// Delegate vote.
+		/// - `to`: The account whose voting the sender's voting power will follow.
+		/// - `conviction`: The conviction that will be attached to the delegated votes. When the
+		///   account is undelegated, the funds will be locked for the corresponding period.
+		/// - `balance`: The amount of the account's balance to be used in delegating. This must
+		///   not be more than the account's current balance.
+		///
+		/// Emits `Delegated`.
 		///
 		/// # <weight>
-		/// - One extra DB entry.
+		/// - O(R) where R is the number of referendums the target has voted on.
 		/// # </weight>
 		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
-		pub fn delegate(origin, to: T::AccountId, conviction: Conviction) {
+		pub fn delegate(origin, to: T::AccountId, conviction: Conviction, balance: BalanceOf<T>) {
 			let who = ensure_signed(origin)?;
-			<Delegations<T>>::insert(&who, (&to, conviction));
-			// Currency is locked indefinitely as long as it's delegated.
-			T::Currency::extend_lock(
-				DEMOCRACY_ID,
-				&who,
-				Bounded::max_value(),
-				T::BlockNumber::max_value(),
-				WithdrawReason::Transfer.into()
-			);
-			Self::deposit_event(RawEvent::Delegated(who, to));
+			Self::try_delegate(who, to, conviction, balance)?;
 		}
 
-		/// Undelegate vote.
+		/// Undelegate the voting power of the sending account.
+		///
+		/// Tokens may be unlocked with `unlock` once an amount of time consistent with the lock
+		/// period of the conviction with which the delegation was issued has passed.
+		///
+		/// The dispatch origin of this call must be _Signed_ and the signing account must be
+		/// currently delegating.
+		///
+		/// Emits `Undelegated`.
 		///
 		/// # <weight>
-		/// - O(1).
+		/// - O(R) where R is the number of referendums the target has voted on.
 		/// # </weight>
 		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
 		fn undelegate(origin) {
 			let who = ensure_signed(origin)?;
-			ensure!(<Delegations<T>>::exists(&who), Error::<T>::NotDelegated);
-			let (_, conviction) = <Delegations<T>>::take(&who);
-			// Indefinite lock is reduced to the maximum voting lock that could be possible.
-			let now = <frame_system::Module<T>>::block_number();
-			let locked_until = now + T::EnactmentPeriod::get() * conviction.lock_periods().into();
-			T::Currency::set_lock(
-				DEMOCRACY_ID,
-				&who,
-				Bounded::max_value(),
-				locked_until,
-				WithdrawReason::Transfer.into()
-			);
-			Self::deposit_event(RawEvent::Undelegated(who));
+			Self::try_undelegate(who)?;
 		}
 
 		/// Veto and blacklist the proposal hash. Must be from Root origin.
@@ -789,6 +666,78 @@ decl_module! {
 			<Preimages<T>>::remove(&proposal_hash);
 			Self::deposit_event(RawEvent::PreimageReaped(proposal_hash, old, deposit, who));
 		}
+
+		/// Unlock tokens that have an expired lock.
+		///
+		/// The dispatch origin of this call must be _Signed_.
+		///
+		/// - `target`: The account to remove the lock on.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		fn unlock(origin, target: T::AccountId) {
+			ensure_signed(origin)?;
+			Self::update_lock(&target);
+		}
+
+		/// Remove a vote for a referendum.
+		///
+		/// If:
+		/// - the referendum was cancelled, or
+		/// - the referendum is ongoing, or
+		/// - the referendum has ended such that
+		///   - the vote of the account was in opposition to the result; or
+		///   - there was no conviction to the account's vote; or
+		///   - the account made a split vote
+		/// ...then the vote is removed cleanly and a following call to `unlock` may result in more
+		/// funds being available.
+		///
+		/// If, however, the referendum has ended and:
+		/// - it finished corresponding to the vote of the account, and
+		/// - the account made a standard vote with conviction, and
+		/// - the lock period of the conviction is not over
+		/// ...then the lock will be aggregated into the overall account's lock, which may involve
+		/// *overlocking* (where the two locks are combined into a single lock that is the maximum
+		/// of both the amount locked and the time it is locked for).
+		///
+		/// The dispatch origin of this call must be _Signed_, and the signer must have a vote
+		/// registered for referendum `index`.
+		///
+		/// - `index`: The index of referendum of the vote to be removed.
+		///
+		/// # <weight>
+		/// - `O(R + log R)` where R is the number of referenda that `target` has voted on.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		fn remove_vote(origin, index: ReferendumIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::try_remove_vote(&who, index, UnvoteScope::Any)
+		}
+
+		/// Remove a vote for a referendum.
+		///
+		/// If the `target` is equal to the signer, then this function is exactly equivalent to
+		/// `remove_vote`. If not equal to the signer, then the vote must have expired,
+		/// either because the referendum was cancelled, because the voter lost the referendum or
+		/// because the conviction period is over.
+		///
+		/// The dispatch origin of this call must be _Signed_.
+		///
+		/// - `target`: The account of the vote to be removed; this account must have voted for
+		///   referendum `index`.
+		/// - `index`: The index of referendum of the vote to be removed.
+		///
+		/// # <weight>
+		/// - `O(R + log R)` where R is the number of referenda that `target` has voted on.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		fn remove_other_vote(origin, target: T::AccountId, index: ReferendumIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let scope = if target == who { UnvoteScope::Any } else { UnvoteScope::OnlyExpired };
+			Self::try_remove_vote(&target, index, scope)
+		}
 	}
 }
 
@@ -803,112 +752,44 @@ impl<T: Trait> Module<T> {
 
 	/// Return true if `ref_index` is an on-going referendum.
 	pub fn is_active_referendum(ref_index: ReferendumIndex) -> bool {
-		<ReferendumInfoOf<T>>::exists(ref_index)
+		match <ReferendumInfoOf<T>>::get(ref_index) {
+			Some(ReferendumInfo::Ongoing(_)) => true,
+			_ => false,
+		}
 	}
 
 	/// Get all referenda currently active.
 	pub fn active_referenda()
-		-> Vec<(ReferendumIndex, ReferendumInfo<T::BlockNumber, T::Hash>)>
+		-> Vec<(ReferendumIndex, ReferendumStatus<T::BlockNumber, T::Hash, BalanceOf<T>>)>
 	{
 		let next = Self::lowest_unbaked();
 		let last = Self::referendum_count();
 		(next..last).into_iter()
-			.filter_map(|i| Self::referendum_info(i).map(|info| (i, info)))
+			.filter_map(|i| Self::referendum_status(i).ok().map(|status| (i, status)))
 			.collect()
 	}
 
 	/// Get all referenda ready for tally at block `n`.
 	pub fn maturing_referenda_at(
 		n: T::BlockNumber
-	) -> Vec<(ReferendumIndex, ReferendumInfo<T::BlockNumber, T::Hash>)> {
-		let next = Self::lowest_unbaked();
-		let last = Self::referendum_count();
-		(next..last).into_iter()
-			.filter_map(|i| Self::referendum_info(i).map(|info| (i, info)))
-			.filter(|&(_, ref info)| info.end == n)
+	) -> Vec<(ReferendumIndex, ReferendumStatus<T::BlockNumber, T::Hash, BalanceOf<T>>)> {
+		Self::active_referenda().into_iter()
+			.filter(|&(_, ref status)| status.end == n)
 			.collect()
 	}
 
-	/// Get the voters for the current proposal.
-	pub fn tally(ref_index: ReferendumIndex) -> (BalanceOf<T>, BalanceOf<T>, BalanceOf<T>) {
-		let (approve, against, capital):
-			(BalanceOf<T>, BalanceOf<T>, BalanceOf<T>) = Self::voters_for(ref_index)
-				.iter()
-				.map(|voter| (
-					T::Currency::total_balance(voter), Self::vote_of((ref_index, voter.clone()))
-				))
-				.map(|(balance, Vote { aye, conviction })| {
-					let (votes, turnout) = conviction.votes(balance);
-					if aye {
-						(votes, Zero::zero(), turnout)
-					} else {
-						(Zero::zero(), votes, turnout)
-					}
-				}).fold(
-					(Zero::zero(), Zero::zero(), Zero::zero()),
-					|(a, b, c), (d, e, f)| (a + d, b + e, c + f)
-				);
-		let (del_approve, del_against, del_capital) = Self::tally_delegation(ref_index);
-		(approve + del_approve, against + del_against, capital + del_capital)
-	}
-
-	/// Get the delegated voters for the current proposal.
-	/// I think this goes into a worker once https://github.com/paritytech/substrate/issues/1458 is
-	/// done.
-	fn tally_delegation(ref_index: ReferendumIndex) -> (BalanceOf<T>, BalanceOf<T>, BalanceOf<T>) {
-		Self::voters_for(ref_index).iter().fold(
-			(Zero::zero(), Zero::zero(), Zero::zero()),
-			|(approve_acc, against_acc, turnout_acc), voter| {
-				let Vote { aye, conviction } = Self::vote_of((ref_index, voter.clone()));
-				let (votes, turnout) = Self::delegated_votes(
-					ref_index,
-					voter.clone(),
-					conviction,
-					MAX_RECURSION_LIMIT
-				);
-				if aye {
-					(approve_acc + votes, against_acc, turnout_acc + turnout)
-				} else {
-					(approve_acc, against_acc + votes, turnout_acc + turnout)
-				}
-			}
-		)
-	}
-
-	fn delegated_votes(
-		ref_index: ReferendumIndex,
-		to: T::AccountId,
-		parent_conviction: Conviction,
-		recursion_limit: u32,
-	) -> (BalanceOf<T>, BalanceOf<T>) {
-		if recursion_limit == 0 { return (Zero::zero(), Zero::zero()); }
-		<Delegations<T>>::enumerate()
-			.filter(|(delegator, (delegate, _))|
-				*delegate == to && !<VoteOf<T>>::exists(&(ref_index, delegator.clone()))
-			).fold(
-				(Zero::zero(), Zero::zero()),
-				|(votes_acc, turnout_acc), (delegator, (_delegate, max_conviction))| {
-					let conviction = Conviction::min(parent_conviction, max_conviction);
-					let balance = T::Currency::total_balance(&delegator);
-					let (votes, turnout) = conviction.votes(balance);
-					let (del_votes, del_turnout) = Self::delegated_votes(
-						ref_index,
-						delegator,
-						conviction,
-						recursion_limit - 1
-					);
-					(votes_acc + votes + del_votes, turnout_acc + turnout + del_turnout)
-				}
-			)
+	/// Get the status of an ongoing referendum, or `ReferendumInvalid` if it isn't ongoing.
+	pub fn referendum_status(ref_index: ReferendumIndex)
+		-> Result<ReferendumStatus<T::BlockNumber, T::Hash, BalanceOf<T>>, DispatchError>
+	{
+		match <ReferendumInfoOf<T>>::get(ref_index) {
+			Some(ReferendumInfo::Ongoing(status)) => Ok(status),
+			_ => Err(Error::<T>::ReferendumInvalid.into()),
+		}
 	}
 
 	// Exposed mutables.
 
-	#[cfg(feature = "std")]
-	pub fn force_proxy(stash: T::AccountId, proxy: T::AccountId) {
-		<Proxy<T>>::insert(proxy, stash)
-	}
-
 	/// Start a referendum.
 	pub fn internal_start_referendum(
 		proposal_hash: T::Hash,
@@ -932,15 +813,217 @@ impl<T: Trait> Module<T> {
 	// private.
 
 	/// Actually enact a vote, if legit.
-	fn do_vote(who: T::AccountId, ref_index: ReferendumIndex, vote: Vote) -> DispatchResult {
-		ensure!(Self::is_active_referendum(ref_index), Error::<T>::ReferendumInvalid);
-		if !<VoteOf<T>>::exists((ref_index, &who)) {
-			<VotersFor<T>>::append_or_insert(ref_index, &[&who][..]);
+	fn try_vote(
+		who: &T::AccountId,
+		ref_index: ReferendumIndex,
+		vote: AccountVote<BalanceOf<T>>,
+	) -> DispatchResult {
+		let mut status = Self::referendum_status(ref_index)?;
+		ensure!(vote.balance() <= T::Currency::free_balance(who), Error::<T>::InsufficientFunds);
+
+		let mut voting = <VotingOf<T>>::get(who);
+		match &mut voting {
+			Voting::Direct { votes, delegations, .. } => {
+				match votes.binary_search_by_key(&ref_index, |i| i.0) {
+					Ok(i) => {
+						// Shouldn't be possible to fail, but we handle it gracefully.
+						status.tally.remove(votes[i].1).ok_or(Error::<T>::Underflow)?;
+						if let Some(approve) = votes[i].1.as_standard() {
+							status.tally.reduce(approve, *delegations);
+						}
+						votes[i].1 = vote;
+					}
+					Err(i) => votes.insert(i, (ref_index, vote)),
+				}
+				// Shouldn't be possible to fail, but we handle it gracefully.
+				status.tally.add(vote).ok_or(Error::<T>::Overflow)?;
+				if let Some(approve) = vote.as_standard() {
+					status.tally.increase(approve, *delegations);
+				}
+			}
+			Voting::Delegating { .. } => Err(Error::<T>::AlreadyDelegating)?,
+		}
+
+		<VotingOf<T>>::insert(who, voting);
+		<ReferendumInfoOf<T>>::insert(ref_index, ReferendumInfo::Ongoing(status));
+		Self::update_lock(who);
+		Ok(())
+	}
+
+	/// Remove the account's vote for the given referendum if possible. This is possible when:
+	/// - The referendum has not finished.
+	/// - The referendum has finished and the voter lost their direction.
+	/// - The referendum has finished and the voter's lock period is up.
+	///
+	/// This will generally be combined with a call to `unlock`.
+	fn try_remove_vote(
+		who: &T::AccountId,
+		ref_index: ReferendumIndex,
+		scope: UnvoteScope,
+	) -> DispatchResult {
+		let mut voting = <VotingOf<T>>::get(who);
+		let (votes, delegations, prior) = match &mut voting {
+			Voting::Direct { votes, delegations, prior } => (votes, *delegations, prior),
+			Voting::Delegating { .. } => Err(Error::<T>::NotVoter)?,
+		};
+		let i = votes.binary_search_by_key(&ref_index, |i| i.0).map_err(|_| Error::<T>::NotVoter)?;
+		match <ReferendumInfoOf<T>>::get(ref_index) {
+			Some(ReferendumInfo::Ongoing(mut status)) => {
+				if let UnvoteScope::OnlyExpired = scope {
+					Err(Error::<T>::NoPermission)?
+				}
+				// Shouldn't be possible to fail, but we handle it gracefully.
+				status.tally.remove(votes[i].1).ok_or(Error::<T>::Underflow)?;
+				if let Some(approve) = votes[i].1.as_standard() {
+					status.tally.reduce(approve, delegations);
+				}
+				<ReferendumInfoOf<T>>::insert(ref_index, ReferendumInfo::Ongoing(status));
+			}
+			Some(ReferendumInfo::Finished { end, approved }) => {
+				if let Some((lock_periods, balance)) = votes[i].1.locked_if(approved) {
+					let unlock_at = end + T::EnactmentPeriod::get() * lock_periods.into();
+					let now = <frame_system::Module<T>>::block_number();
+					if now < unlock_at {
+						if let UnvoteScope::OnlyExpired = scope {
+							Err(Error::<T>::NoPermission)?
+						}
+						prior.accumulate(unlock_at, balance);
+					}
+				}
+			}
+			// Referendum was cancelled.
+			None => {}
 		}
-		<VoteOf<T>>::insert((ref_index, &who), vote);
+		votes.remove(i);
+		<VotingOf<T>>::insert(who, voting);
 		Ok(())
 	}
 
+	/// Add `amount` of delegated votes to `who`, and to the tally of every ongoing referendum in
+	/// which `who` holds a standard vote.
+	fn increase_upstream_delegation(who: &T::AccountId, amount: Delegated<BalanceOf<T>>) {
+		<VotingOf<T>>::mutate(who, |voting| match voting {
+			// We don't support second level delegating, so we don't need to do anything more.
+			Voting::Delegating { delegations, .. } =>
+				*delegations = delegations.saturating_add(amount),
+			Voting::Direct { votes, delegations, .. } => {
+				*delegations = delegations.saturating_add(amount);
+				for &(ref_index, account_vote) in votes.iter() {
+					if let AccountVote::Standard { vote, .. } = account_vote {
+						<ReferendumInfoOf<T>>::mutate(ref_index, |maybe_info|
+							if let Some(ReferendumInfo::Ongoing(ref mut status)) = maybe_info {
+								status.tally.increase(vote.aye, amount);
+							}
+						);
+					}
+				}
+			}
+		})
+	}
+
+	/// Remove `amount` of delegated votes from `who`, and from the tally of every ongoing
+	/// referendum in which `who` holds a standard vote.
+	fn reduce_upstream_delegation(who: &T::AccountId, amount: Delegated<BalanceOf<T>>) {
+		<VotingOf<T>>::mutate(who, |voting| match voting {
+			// We don't support second level delegating, so we don't need to do anything more.
+			Voting::Delegating { delegations, .. } =>
+				*delegations = delegations.saturating_sub(amount),
+			Voting::Direct { votes, delegations, .. } => {
+				*delegations = delegations.saturating_sub(amount);
+				for &(ref_index, account_vote) in votes.iter() {
+					if let AccountVote::Standard { vote, .. } = account_vote {
+						<ReferendumInfoOf<T>>::mutate(ref_index, |maybe_info|
+							if let Some(ReferendumInfo::Ongoing(ref mut status)) = maybe_info {
+								status.tally.reduce(vote.aye, amount);
+							}
+						);
+					}
+				}
+			}
+		})
+	}
+
+	/// Attempt to delegate `balance` times `conviction` of voting power from `who` to `target`.
+	fn try_delegate(
+		who: T::AccountId,
+		target: T::AccountId,
+		conviction: Conviction,
+		balance: BalanceOf<T>,
+	) -> DispatchResult {
+		ensure!(who != target, Error::<T>::Nonsense);
+		ensure!(balance <= T::Currency::free_balance(&who), Error::<T>::InsufficientFunds);
+		let (delegations, prior) = match <VotingOf<T>>::get(&who) {
+			Voting::Delegating {
+				balance: old_balance,
+				target: old_target,
+				conviction: old_conviction,
+				delegations,
+				mut prior,
+			} => {
+				// remove any delegation votes to our current target, keeping its lock around.
+				Self::reduce_upstream_delegation(&old_target, old_conviction.votes(old_balance));
+				let now = <frame_system::Module<T>>::block_number();
+				let lock_periods = old_conviction.lock_periods().into();
+				prior.accumulate(now + T::EnactmentPeriod::get() * lock_periods, old_balance);
+				(delegations, prior)
+			}
+			Voting::Direct { votes, delegations, prior } => {
+				// here we just ensure that we're currently idling with no votes recorded.
+				ensure!(votes.is_empty(), Error::<T>::VotesExist);
+				(delegations, prior)
+			}
+		};
+		Self::increase_upstream_delegation(&target, conviction.votes(balance));
+		<VotingOf<T>>::insert(&who, Voting::Delegating {
+			balance,
+			target: target.clone(),
+			conviction,
+			delegations,
+			prior,
+		});
+		Self::update_lock(&who);
+		Self::deposit_event(RawEvent::Delegated(who, target));
+		Ok(())
+	}
+
+	/// Attempt to end the current delegation.
+	fn try_undelegate(who: T::AccountId) -> DispatchResult {
+		match <VotingOf<T>>::get(&who) {
+			Voting::Delegating { balance, target, conviction, delegations, mut prior } => {
+				// remove any delegation votes to our current target.
+				Self::reduce_upstream_delegation(&target, conviction.votes(balance));
+				let now = <frame_system::Module<T>>::block_number();
+				let lock_periods = conviction.lock_periods().into();
+				prior.accumulate(now + T::EnactmentPeriod::get() * lock_periods, balance);
+				let voting = Voting::Direct { votes: Vec::new(), delegations, prior };
+				<VotingOf<T>>::insert(&who, voting);
+			}
+			Voting::Direct { .. } => Err(Error::<T>::NotDelegated)?,
+		}
+		Self::deposit_event(RawEvent::Undelegated(who));
+		Ok(())
+	}
+
+	/// Set the lock on an account to exactly what its voting record currently needs, forgetting
+	/// any prior lock that has expired.
+	fn update_lock(who: &T::AccountId) {
+		let lock_needed = <VotingOf<T>>::mutate(who, |voting| {
+			voting.rejig(<frame_system::Module<T>>::block_number());
+			voting.locked_balance()
+		});
+		if lock_needed.is_zero() {
+			T::Currency::remove_lock(DEMOCRACY_ID, who);
+		} else {
+			T::Currency::set_lock(
+				DEMOCRACY_ID,
+				who,
+				lock_needed,
+				T::BlockNumber::max_value(),
+				WithdrawReason::Transfer.into(),
+			);
+		}
+	}
+
 	/// Start a referendum
 	fn inject_referendum(
 		end: T::BlockNumber,
@@ -950,7 +1033,7 @@ impl<T: Trait> Module<T> {
 	) -> ReferendumIndex {
 		let ref_index = Self::referendum_count();
 		ReferendumCount::put(ref_index + 1);
-		let item = ReferendumInfo { end, proposal_hash, threshold, delay };
+		let item = ReferendumInfo::new(end, proposal_hash, threshold, delay);
 		<ReferendumInfoOf<T>>::insert(ref_index, item);
 		Self::deposit_event(RawEvent::Started(ref_index, threshold));
 		ref_index
@@ -959,8 +1042,12 @@ impl<T: Trait> Module<T> {
 	/// Remove all info on a referendum.
 	fn clear_referendum(ref_index: ReferendumIndex) {
 		<ReferendumInfoOf<T>>::remove(ref_index);
+		Self::bump_lowest_unbaked(ref_index);
+	}
 
-
+	/// Move `LowestUnbaked` past `ref_index` and any following referenda that are no longer
+	/// ongoing, if `ref_index` was the lowest unbaked one.
+	fn bump_lowest_unbaked(ref_index: ReferendumIndex) {
 		LowestUnbaked::mutate(|i| if *i == ref_index {
 			*i += 1;
 			let end = ReferendumCount::get();
@@ -968,10 +1055,6 @@ impl<T: Trait> Module<T> {
 				*i += 1;
 			}
 		});
-		<VotersFor<T>>::remove(ref_index);
-		for v in Self::voters_for(ref_index) {
-			<VoteOf<T>>::remove((ref_index, v));
-		}
 	}
 
 	/// Enact a proposal from a referendum.
@@ -1056,42 +1139,18 @@ impl<T: Trait> Module<T> {
 	fn bake_referendum(
 		now: T::BlockNumber,
 		index: ReferendumIndex,
-		info: ReferendumInfo<T::BlockNumber, T::Hash>
-	) -> DispatchResult {
-		let (approve, against, capital) = Self::tally(index);
+		status: ReferendumStatus<T::BlockNumber, T::Hash, BalanceOf<T>>,
+	) -> Result<bool, DispatchError> {
+		let Tally { ayes, nays, turnout } = status.tally;
 		let total_issuance = T::Currency::total_issuance();
-		let approved = info.threshold.approved(approve, against, capital, total_issuance);
-
-		// Logic defined in https://www.slideshare.net/gavofyork/governance-in-polkadot-poc3
-		// Essentially, we extend the lock-period of the coins behind the winning votes to be the
-		// vote strength times the public delay period from now.
-		for (a, Vote { conviction, .. }) in Self::voters_for(index).into_iter()
-			.map(|a| (a.clone(), Self::vote_of((index, a))))
-			// ^^^ defensive only: all items come from `voters`; for an item to be in `voters`
-			// there must be a vote registered; qed
-			.filter(|&(_, vote)| vote.aye == approved)  // Just the winning coins
-		{
-			// now plus: the base lock period multiplied by the number of periods this voter
-			// offered to lock should they win...
-			let locked_until = now + T::EnactmentPeriod::get() * conviction.lock_periods().into();
-			// ...extend their bondage until at least then.
-			T::Currency::extend_lock(
-				DEMOCRACY_ID,
-				&a,
-				Bounded::max_value(),
-				locked_until,
-				WithdrawReason::Transfer.into()
-			);
-		}
-
-		Self::clear_referendum(index);
+		let approved = status.threshold.approved(ayes, nays, turnout, total_issuance);
 
 		if approved {
 			Self::deposit_event(RawEvent::Passed(index));
-			if info.delay.is_zero() {
-				let _ = Self::enact_proposal(info.proposal_hash, index);
+			if status.delay.is_zero() {
+				let _ = Self::enact_proposal(status.proposal_hash, index);
 			} else {
-				let item = (now + info.delay,info.proposal_hash, index);
+				let item = (now + status.delay, status.proposal_hash, index);
 				<DispatchQueue<T>>::mutate(|queue| {
 					let pos = queue.binary_search_by_key(&item.0, |x| x.0).unwrap_or_else(|e| e);
 					queue.insert(pos, item);
@@ -1101,7 +1160,7 @@ impl<T: Trait> Module<T> {
 			Self::deposit_event(RawEvent::NotPassed(index));
 		}
 
-		Ok(())
+		Ok(approved)
 	}
 
 	/// Current era is ending; we should finish up any proposals.
@@ -1114,8 +1173,12 @@ impl<T: Trait> Module<T> {
 		}
 
 		// tally up votes for any expiring referenda.
-		for (index, info) in Self::maturing_referenda_at(now).into_iter() {
-			Self::bake_referendum(now, index, info)?;
+		for (index, status) in Self::maturing_referenda_at(now).into_iter() {
+			let approved = Self::bake_referendum(now, index, status)?;
+			// Voters on the winning side stay locked according to their conviction; the record is
+			// kept so that `remove_vote` can work out for how long.
+			<ReferendumInfoOf<T>>::insert(index, ReferendumInfo::Finished { end: now, approved });
+			Self::bump_lowest_unbaked(index);
 		}
 
 		let queue = <DispatchQueue<T>>::get();
@@ -1130,11 +1193,119 @@ impl<T: Trait> Module<T> {
 		}
 		Ok(())
 	}
-}
 
-impl<T: Trait> OnFreeBalanceZero<T::AccountId> for Module<T> {
-	fn on_free_balance_zero(who: &T::AccountId) {
-		<Proxy<T>>::remove(who)
+	/// Remove the voting proxies of the previous layout. Accounts vote on behalf of others through
+	/// the proxy module instead. No deposit was held for them.
+	fn migrate_proxies() {
+		if ProxiesMigrated::get() {
+			return
+		}
+
+		let mut prefix = sp_io::hashing::twox_128(b"Democracy").to_vec();
+		prefix.extend_from_slice(&sp_io::hashing::twox_128(b"Proxy"));
+		storage::unhashed::kill_prefix(&prefix);
+		ProxiesMigrated::put(true);
+	}
+
+	/// Move the votes and delegations of the previous layout into `VotingOf`, rewriting ongoing
+	/// referenda with a running tally. The votes of referenda maturing at `now` are all moved, so
+	/// that they are tallied in full. Of the others, at most `budget` votes and delegations are
+	/// moved; the rest are left for the next call. No-op once done.
+	fn migrate_voting(now: T::BlockNumber, budget: u32) {
+		if VotingMigrated::get() {
+			return
+		}
+
+		// Ongoing referenda were stored as `(end, proposal_hash, threshold, delay)`.
+		for index in Self::lowest_unbaked()..Self::referendum_count() {
+			let key = <ReferendumInfoOf<T>>::hashed_key_for(index);
+			let legacy = storage::unhashed::get_raw(&key).and_then(|raw| {
+				let mut input = &raw[..];
+				<(T::BlockNumber, T::Hash, VoteThreshold, T::BlockNumber)>::decode(&mut input)
+					.ok()
+					.filter(|_| input.is_empty())
+			});
+			if let Some((end, proposal_hash, threshold, delay)) = legacy {
+				let status = ReferendumStatus {
+					end,
+					proposal_hash,
+					threshold,
+					delay,
+					tally: Default::default(),
+				};
+				<ReferendumInfoOf<T>>::insert(index, ReferendumInfo::Ongoing(status));
+			}
+		}
+
+		// Referenda maturing now must be tallied with all of their votes.
+		for (index, _) in Self::maturing_referenda_at(now) {
+			Self::migrate_votes(index, <VotersFor<T>>::take(index));
+		}
+
+		let mut budget = budget;
+		for index in Self::lowest_unbaked()..Self::referendum_count() {
+			if budget == 0 {
+				return
+			}
+			let mut voters = <VotersFor<T>>::take(index);
+			let rest = voters.split_off(voters.len().min(budget as usize));
+			budget -= voters.len() as u32;
+			if !rest.is_empty() {
+				<VotersFor<T>>::insert(index, rest);
+			}
+			Self::migrate_votes(index, voters);
+		}
+
+		let delegations = <Delegations<T>>::enumerate()
+			.take(budget as usize)
+			.collect::<Vec<_>>();
+		for (who, (target, conviction)) in delegations {
+			<Delegations<T>>::remove(&who);
+			T::Currency::remove_lock(LEGACY_DEMOCRACY_ID, &who);
+			let (delegations, prior) = match <VotingOf<T>>::get(&who) {
+				Voting::Direct { ref votes, delegations, prior }
+					if votes.is_empty() && who != target => (delegations, prior),
+				// A direct vote used to take precedence over the delegation; keep the vote.
+				_ => {
+					Self::update_lock(&who);
+					continue
+				}
+			};
+			let balance = T::Currency::free_balance(&who);
+			Self::increase_upstream_delegation(&target, conviction.votes(balance));
+			<VotingOf<T>>::insert(&who, Voting::Delegating {
+				balance,
+				target,
+				conviction,
+				delegations,
+				prior,
+			});
+			Self::update_lock(&who);
+		}
+
+		if <Delegations<T>>::enumerate().next().is_none() {
+			VotingMigrated::put(true);
+		}
+	}
+
+	/// Move the legacy votes of `voters` on referendum `index` into `VotingOf`.
+	fn migrate_votes(index: ReferendumIndex, voters: Vec<T::AccountId>) {
+		for voter in voters {
+			T::Currency::remove_lock(LEGACY_DEMOCRACY_ID, &voter);
+			let vote = AccountVote::Standard {
+				vote: <VoteOf<T>>::take((index, &voter)),
+				balance: T::Currency::free_balance(&voter),
+			};
+			// A vote or delegation made since the upgrade supersedes the legacy vote.
+			let superseded = match <VotingOf<T>>::get(&voter) {
+				Voting::Direct { votes, .. } =>
+					votes.binary_search_by_key(&index, |i| i.0).is_ok(),
+				Voting::Delegating { .. } => true,
+			};
+			if !superseded {
+				let _ = Self::try_vote(&voter, index, vote);
+			}
+		}
 	}
 }
 
@@ -1142,13 +1313,14 @@ impl<T: Trait> OnFreeBalanceZero<T::AccountId> for Module<T> {
 mod tests {
 	use super::*;
 	use std::cell::RefCell;
+	use codec::Encode;
 	use frame_support::{
 		impl_outer_origin, impl_outer_dispatch, assert_noop, assert_ok, parameter_types,
 		ord_parameter_types, traits::Contains, weights::Weight,
 	};
 	use sp_core::H256;
 	use sp_runtime::{
-		traits::{BlakeTwo256, IdentityLookup, Bounded, BadOrigin},
+		traits::{BlakeTwo256, IdentityLookup, BadOrigin, OnRuntimeUpgrade},
 		testing::Header, Perbill,
 	};
 	use pallet_balances::{BalanceLock, Error as BalancesError};
@@ -1159,6 +1331,26 @@ mod tests {
 	const BIG_AYE: Vote = Vote{ aye: true, conviction: Conviction::Locked1x };
 	const BIG_NAY: Vote = Vote{ aye: false, conviction: Conviction::Locked1x };
 
+	fn aye(who: u64) -> AccountVote<u64> {
+		AccountVote::Standard { vote: AYE, balance: Balances::free_balance(&who) }
+	}
+
+	fn nay(who: u64) -> AccountVote<u64> {
+		AccountVote::Standard { vote: NAY, balance: Balances::free_balance(&who) }
+	}
+
+	fn big_aye(who: u64) -> AccountVote<u64> {
+		AccountVote::Standard { vote: BIG_AYE, balance: Balances::free_balance(&who) }
+	}
+
+	fn big_nay(who: u64) -> AccountVote<u64> {
+		AccountVote::Standard { vote: BIG_NAY, balance: Balances::free_balance(&who) }
+	}
+
+	fn tally(r: ReferendumIndex) -> Tally<u64> {
+		Democracy::referendum_status(r).unwrap().tally
+	}
+
 	impl_outer_origin! {
 		pub enum Origin for Test  where system = frame_system {}
 	}
@@ -1266,7 +1458,6 @@ mod tests {
 		let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
 		pallet_balances::GenesisConfig::<Test>{
 			balances: vec![(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60)],
-			vesting: vec![],
 		}.assimilate_storage(&mut t).unwrap();
 		GenesisConfig::default().assimilate_storage(&mut t).unwrap();
 		sp_io::TestExternalities::new(t)
@@ -1341,7 +1532,7 @@ mod tests {
 				VoteThreshold::SuperMajorityApprove,
 				0
 			);
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 
 			next_block();
 			next_block();
@@ -1368,7 +1559,7 @@ mod tests {
 				VoteThreshold::SuperMajorityApprove,
 				0
 			);
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 
 			assert_eq!(Balances::reserved_balance(6), 12);
 
@@ -1444,7 +1635,7 @@ mod tests {
 				VoteThreshold::SuperMajorityApprove,
 				1
 			);
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 
 			assert_noop!(
 				Democracy::note_imminent_preimage(Origin::signed(7), set_balance_proposal(2)),
@@ -1468,7 +1659,7 @@ mod tests {
 			System::set_block_number(1);
 			let h = set_balance_proposal_hash_and_note(2);
 			let r = Democracy::inject_referendum(3, h, VoteThreshold::SuperMajorityApprove, 1);
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 			next_block();
 			next_block();
 			// now imminent.
@@ -1491,12 +1682,13 @@ mod tests {
 			// both waiting: external goes first.
 			assert_eq!(
 				Democracy::referendum_info(0),
-				Some(ReferendumInfo {
+				Some(ReferendumInfo::Ongoing(ReferendumStatus {
 					end: 4,
 					proposal_hash: set_balance_proposal_hash_and_note(1),
 					threshold: VoteThreshold::SuperMajorityApprove,
-					delay: 2
-				})
+					delay: 2,
+					tally: Tally { ayes: 0, nays: 0, turnout: 0 },
+				}))
 			);
 			// replenish external
 			assert_ok!(Democracy::external_propose(
@@ -1509,12 +1701,13 @@ mod tests {
 			// both waiting: public goes next.
 			assert_eq!(
 				Democracy::referendum_info(1),
-				Some(ReferendumInfo {
+				Some(ReferendumInfo::Ongoing(ReferendumStatus {
 					end: 6,
 					proposal_hash: set_balance_proposal_hash_and_note(2),
 					threshold: VoteThreshold::SuperMajorityApprove,
-					delay: 2
-				})
+					delay: 2,
+					tally: Tally { ayes: 0, nays: 0, turnout: 0 },
+				}))
 			);
 			// don't replenish public
 
@@ -1523,12 +1716,13 @@ mod tests {
 			// it's external "turn" again, though since public is empty that doesn't really matter
 			assert_eq!(
 				Democracy::referendum_info(2),
-				Some(ReferendumInfo {
+				Some(ReferendumInfo::Ongoing(ReferendumStatus {
 					end: 8,
 					proposal_hash: set_balance_proposal_hash_and_note(3),
 					threshold: VoteThreshold::SuperMajorityApprove,
-					delay: 2
-				})
+					delay: 2,
+					tally: Tally { ayes: 0, nays: 0, turnout: 0 },
+				}))
 			);
 			// replenish external
 			assert_ok!(Democracy::external_propose(
@@ -1541,12 +1735,13 @@ mod tests {
 			// external goes again because there's no public waiting.
 			assert_eq!(
 				Democracy::referendum_info(3),
-				Some(ReferendumInfo {
+				Some(ReferendumInfo::Ongoing(ReferendumStatus {
 					end: 10,
 					proposal_hash: set_balance_proposal_hash_and_note(5),
 					threshold: VoteThreshold::SuperMajorityApprove,
-					delay: 2
-				})
+					delay: 2,
+					tally: Tally { ayes: 0, nays: 0, turnout: 0 },
+				}))
 			);
 			// replenish both
 			assert_ok!(Democracy::external_propose(
@@ -1560,12 +1755,13 @@ mod tests {
 			// public goes now since external went last time.
 			assert_eq!(
 				Democracy::referendum_info(4),
-				Some(ReferendumInfo {
+				Some(ReferendumInfo::Ongoing(ReferendumStatus {
 					end: 12,
 					proposal_hash: set_balance_proposal_hash_and_note(4),
 					threshold: VoteThreshold::SuperMajorityApprove,
-					delay: 2
-				})
+					delay: 2,
+					tally: Tally { ayes: 0, nays: 0, turnout: 0 },
+				}))
 			);
 			// replenish public again
 			assert_ok!(propose_set_balance_and_note(6, 6, 2));
@@ -1578,12 +1774,13 @@ mod tests {
 			// public goes again now since there's no external waiting.
 			assert_eq!(
 				Democracy::referendum_info(5),
-				Some(ReferendumInfo {
+				Some(ReferendumInfo::Ongoing(ReferendumStatus {
 					end: 14,
 					proposal_hash: set_balance_proposal_hash_and_note(6),
 					threshold: VoteThreshold::SuperMajorityApprove,
-					delay: 2
-				})
+					delay: 2,
+					tally: Tally { ayes: 0, nays: 0, turnout: 0 },
+				}))
 			);
 		});
 	}
@@ -1700,12 +1897,13 @@ mod tests {
 			fast_forward_to(2);
 			assert_eq!(
 				Democracy::referendum_info(0),
-				Some(ReferendumInfo {
+				Some(ReferendumInfo::Ongoing(ReferendumStatus {
 					end: 4,
 					proposal_hash: set_balance_proposal_hash(2),
 					threshold: VoteThreshold::SuperMajorityApprove,
-					delay: 2
-				})
+					delay: 2,
+					tally: Tally { ayes: 0, nays: 0, turnout: 0 },
+				}))
 			);
 		});
 	}
@@ -1728,12 +1926,13 @@ mod tests {
 			fast_forward_to(2);
 			assert_eq!(
 				Democracy::referendum_info(0),
-				Some(ReferendumInfo {
+				Some(ReferendumInfo::Ongoing(ReferendumStatus {
 					end: 4,
 					proposal_hash: set_balance_proposal_hash(2),
 					threshold: VoteThreshold::SimpleMajority,
 					delay: 2,
-				})
+					tally: Tally { ayes: 0, nays: 0, turnout: 0 },
+				}))
 			);
 		});
 	}
@@ -1756,12 +1955,13 @@ mod tests {
 			fast_forward_to(2);
 			assert_eq!(
 				Democracy::referendum_info(0),
-				Some(ReferendumInfo {
+				Some(ReferendumInfo::Ongoing(ReferendumStatus {
 					end: 4,
 					proposal_hash: set_balance_proposal_hash(2),
 					threshold: VoteThreshold::SuperMajorityAgainst,
 					delay: 2,
-				})
+					tally: Tally { ayes: 0, nays: 0, turnout: 0 },
+				}))
 			);
 		});
 	}
@@ -1780,12 +1980,13 @@ mod tests {
 			assert_ok!(Democracy::fast_track(Origin::signed(5), h, 0, 0));
 			assert_eq!(
 				Democracy::referendum_info(0),
-				Some(ReferendumInfo {
+				Some(ReferendumInfo::Ongoing(ReferendumStatus {
 					end: 1,
 					proposal_hash: set_balance_proposal_hash_and_note(2),
 					threshold: VoteThreshold::SimpleMajority,
 					delay: 0,
-				})
+					tally: Tally { ayes: 0, nays: 0, turnout: 0 },
+				}))
 			);
 		});
 	}
@@ -1830,21 +2031,19 @@ mod tests {
 			fast_forward_to(2);
 
 			let r = 0;
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 
 			assert_eq!(Democracy::referendum_count(), 1);
 			assert_eq!(
 				Democracy::referendum_info(0),
-				Some(ReferendumInfo {
+				Some(ReferendumInfo::Ongoing(ReferendumStatus {
 					end: 4,
 					proposal_hash: set_balance_proposal_hash_and_note(2),
 					threshold: VoteThreshold::SuperMajorityApprove,
-					delay: 2
-				})
+					delay: 2,
+					tally: Tally { ayes: 1, nays: 0, turnout: 10 },
+				}))
 			);
-			assert_eq!(Democracy::voters_for(r), vec![1]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
-			assert_eq!(Democracy::tally(r), (1, 0, 1));
 
 			fast_forward_to(3);
 
@@ -1854,7 +2053,10 @@ mod tests {
 			// referendum runs during 2 and 3, ends @ start of 4.
 			fast_forward_to(4);
 
-			assert!(Democracy::referendum_info(0).is_none());
+			assert_eq!(
+				Democracy::referendum_info(0),
+				Some(ReferendumInfo::Finished { end: 4, approved: true })
+			);
 			assert_eq!(Democracy::dispatch_queue(), vec![
 				(6, set_balance_proposal_hash_and_note(2), 0)
 			]);
@@ -1875,7 +2077,7 @@ mod tests {
 			// start of 2 => next referendum scheduled.
 			fast_forward_to(2);
 
-			assert_ok!(Democracy::vote(Origin::signed(1), 0, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), 0, aye(1)));
 
 			fast_forward_to(4);
 
@@ -1890,82 +2092,56 @@ mod tests {
 	}
 
 	#[test]
-	fn proxy_should_work() {
-		new_test_ext().execute_with(|| {
-			assert_eq!(Democracy::proxy(10), None);
-			assert_ok!(Democracy::set_proxy(Origin::signed(1), 10));
-			assert_eq!(Democracy::proxy(10), Some(1));
-
-			// Can't set when already set.
-			assert_noop!(Democracy::set_proxy(Origin::signed(2), 10), Error::<Test>::AlreadyProxy);
-
-			// But this works because 11 isn't proxying.
-			assert_ok!(Democracy::set_proxy(Origin::signed(2), 11));
-			assert_eq!(Democracy::proxy(10), Some(1));
-			assert_eq!(Democracy::proxy(11), Some(2));
-
-			// 2 cannot fire 1's proxy:
-			assert_noop!(Democracy::remove_proxy(Origin::signed(2), 10), Error::<Test>::WrongProxy);
-
-			// 1 fires his proxy:
-			assert_ok!(Democracy::remove_proxy(Origin::signed(1), 10));
-			assert_eq!(Democracy::proxy(10), None);
-			assert_eq!(Democracy::proxy(11), Some(2));
-
-			// 11 resigns:
-			assert_ok!(Democracy::resign_proxy(Origin::signed(11)));
-			assert_eq!(Democracy::proxy(10), None);
-			assert_eq!(Democracy::proxy(11), None);
-		});
-	}
-
-	#[test]
-	fn single_proposal_should_work_with_proxy() {
+	fn single_proposal_should_work_with_delegation() {
 		new_test_ext().execute_with(|| {
 			System::set_block_number(0);
+
 			assert_ok!(propose_set_balance_and_note(1, 2, 1));
 
 			fast_forward_to(2);
-			let r = 0;
-			assert_ok!(Democracy::set_proxy(Origin::signed(1), 10));
-			assert_ok!(Democracy::proxy_vote(Origin::signed(10), r, AYE));
 
-			assert_eq!(Democracy::voters_for(r), vec![1]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
-			assert_eq!(Democracy::tally(r), (1, 0, 1));
+			// Delegate first vote.
+			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::None, 20));
+			let r = 0;
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
+			assert_eq!(tally(r), Tally { ayes: 3, nays: 0, turnout: 30 });
 
-			fast_forward_to(6);
-			assert_eq!(Balances::free_balance(&42), 2);
-		});
-	}
+			// Delegate a second vote.
+			assert_ok!(Democracy::delegate(Origin::signed(3), 1, Conviction::None, 30));
+			assert_eq!(tally(r), Tally { ayes: 6, nays: 0, turnout: 60 });
 
-	#[test]
-	fn single_proposal_should_work_with_delegation() {
-		new_test_ext().execute_with(|| {
-			System::set_block_number(0);
+			// Reduce first vote.
+			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::None, 10));
+			assert_eq!(tally(r), Tally { ayes: 5, nays: 0, turnout: 50 });
 
-			assert_ok!(propose_set_balance_and_note(1, 2, 1));
+			// Second vote delegates to voter who is not voting.
+			assert_ok!(Democracy::delegate(Origin::signed(3), 2, Conviction::None, 30));
+			assert_eq!(tally(r), Tally { ayes: 2, nays: 0, turnout: 20 });
 
-			fast_forward_to(2);
+			// Second vote delegates back to voter.
+			assert_ok!(Democracy::delegate(Origin::signed(3), 1, Conviction::None, 30));
+			assert_eq!(tally(r), Tally { ayes: 5, nays: 0, turnout: 50 });
 
-			// Delegate vote.
-			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::max_value()));
+			// Second vote cancels delegation.
+			assert_ok!(Democracy::undelegate(Origin::signed(3)));
+			assert_eq!(tally(r), Tally { ayes: 2, nays: 0, turnout: 20 });
 
-			let r = 0;
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
-			assert_eq!(Democracy::voters_for(r), vec![1]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
-			// Delegated vote is counted.
-			assert_eq!(Democracy::tally(r), (3, 0, 3));
+			// Main voter cancels their vote.
+			assert_ok!(Democracy::remove_vote(Origin::signed(1), r));
+			assert_eq!(tally(r), Tally { ayes: 0, nays: 0, turnout: 0 });
 
-			fast_forward_to(6);
+			// First delegator delegates half funds with conviction; nothing changes yet.
+			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::Locked1x, 10));
+			assert_eq!(tally(r), Tally { ayes: 0, nays: 0, turnout: 0 });
 
-			assert_eq!(Balances::free_balance(&42), 2);
+			// Main voter reinstates their vote.
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
+			assert_eq!(tally(r), Tally { ayes: 11, nays: 0, turnout: 20 });
 		});
 	}
 
 	#[test]
-	fn single_proposal_should_work_with_cyclic_delegation() {
+	fn cyclic_delegation_should_unwind() {
 		new_test_ext().execute_with(|| {
 			System::set_block_number(0);
 
@@ -1974,15 +2150,23 @@ mod tests {
 			fast_forward_to(2);
 
 			// Check behavior with cycle.
-			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::max_value()));
-			assert_ok!(Democracy::delegate(Origin::signed(3), 2, Conviction::max_value()));
-			assert_ok!(Democracy::delegate(Origin::signed(1), 3, Conviction::max_value()));
+			assert_ok!(Democracy::delegate(Origin::signed(1), 2, Conviction::Locked1x, 10));
+			assert_ok!(Democracy::delegate(Origin::signed(2), 3, Conviction::Locked1x, 20));
+			assert_ok!(Democracy::delegate(Origin::signed(3), 1, Conviction::Locked1x, 30));
 			let r = 0;
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
-			assert_eq!(Democracy::voters_for(r), vec![1]);
+			// Nobody in the cycle can vote while delegating.
+			assert_noop!(
+				Democracy::vote(Origin::signed(1), r, aye(1)),
+				Error::<Test>::AlreadyDelegating
+			);
 
-			// Delegated vote is counted.
-			assert_eq!(Democracy::tally(r), (6, 0, 6));
+			assert_ok!(Democracy::undelegate(Origin::signed(3)));
+			assert_ok!(Democracy::vote(Origin::signed(3), r, aye(3)));
+			assert_ok!(Democracy::undelegate(Origin::signed(1)));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, nay(1)));
+
+			// Only the delegation to a direct voter is counted; delegations are not passed on.
+			assert_eq!(tally(r), Tally { ayes: 23, nays: 1, turnout: 60 });
 
 			fast_forward_to(6);
 
@@ -1991,8 +2175,7 @@ mod tests {
 	}
 
 	#[test]
-	/// If transactor already voted, delegated vote is overwriten.
-	fn single_proposal_should_work_with_vote_and_delegation() {
+	fn delegation_requires_votes_to_be_removed() {
 		new_test_ext().execute_with(|| {
 			System::set_block_number(0);
 
@@ -2001,15 +2184,24 @@ mod tests {
 			fast_forward_to(2);
 
 			let r = 0;
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
-			// Vote.
-			assert_ok!(Democracy::vote(Origin::signed(2), r, AYE));
-			// Delegate vote.
-			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::max_value()));
-			assert_eq!(Democracy::voters_for(r), vec![1, 2]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
-			// Delegated vote is not counted.
-			assert_eq!(Democracy::tally(r), (3, 0, 3));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, aye(2)));
+			assert_noop!(
+				Democracy::delegate(Origin::signed(2), 1, Conviction::None, 20),
+				Error::<Test>::VotesExist
+			);
+			assert_noop!(
+				Democracy::delegate(Origin::signed(3), 3, Conviction::None, 30),
+				Error::<Test>::Nonsense
+			);
+			assert_noop!(
+				Democracy::delegate(Origin::signed(3), 1, Conviction::None, 31),
+				Error::<Test>::InsufficientFunds
+			);
+
+			assert_ok!(Democracy::remove_vote(Origin::signed(2), r));
+			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::None, 20));
+			assert_eq!(tally(r), Tally { ayes: 3, nays: 0, turnout: 30 });
 
 			fast_forward_to(6);
 
@@ -2025,19 +2217,18 @@ mod tests {
 			assert_ok!(propose_set_balance_and_note(1, 2, 1));
 
 			// Delegate and undelegate vote.
-			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::max_value()));
+			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::None, 20));
 			assert_ok!(Democracy::undelegate(Origin::signed(2)));
+			assert_noop!(Democracy::undelegate(Origin::signed(2)), Error::<Test>::NotDelegated);
 
 			fast_forward_to(2);
 			let r = 0;
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 
 			assert_eq!(Democracy::referendum_count(), 1);
-			assert_eq!(Democracy::voters_for(r), vec![1]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
 
 			// Delegated vote is not counted.
-			assert_eq!(Democracy::tally(r), (1, 0, 1));
+			assert_eq!(tally(r), Tally { ayes: 1, nays: 0, turnout: 10 });
 
 			fast_forward_to(6);
 
@@ -2046,7 +2237,7 @@ mod tests {
 	}
 
 	#[test]
-	/// If transactor voted, delegated vote is overwriten.
+	/// A delegating account must undelegate before voting directly.
 	fn single_proposal_should_work_with_delegation_and_vote() {
 		new_test_ext().execute_with(|| {
 			System::set_block_number(0);
@@ -2056,20 +2247,24 @@ mod tests {
 			fast_forward_to(2);
 			let r = 0;
 
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 
 			// Delegate vote.
-			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::max_value()));
+			assert_ok!(Democracy::delegate(Origin::signed(2), 1, Conviction::Locked1x, 20));
+			assert_eq!(tally(r), Tally { ayes: 21, nays: 0, turnout: 30 });
 
 			// Vote.
-			assert_ok!(Democracy::vote(Origin::signed(2), r, AYE));
+			assert_noop!(
+				Democracy::vote(Origin::signed(2), r, aye(2)),
+				Error::<Test>::AlreadyDelegating
+			);
+			assert_ok!(Democracy::undelegate(Origin::signed(2)));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, aye(2)));
 
 			assert_eq!(Democracy::referendum_count(), 1);
-			assert_eq!(Democracy::voters_for(r), vec![1, 2]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
 
-			// Delegated vote is not counted.
-			assert_eq!(Democracy::tally(r), (3, 0, 3));
+			// Delegated vote is replaced by the direct one.
+			assert_eq!(tally(r), Tally { ayes: 3, nays: 0, turnout: 30 });
 
 			fast_forward_to(6);
 
@@ -2141,11 +2336,11 @@ mod tests {
 			assert_ok!(propose_set_balance_and_note(1, 4, 4));
 			assert_ok!(propose_set_balance_and_note(1, 3, 3));
 			fast_forward_to(2);
-			assert_ok!(Democracy::vote(Origin::signed(1), 0, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), 0, aye(1)));
 			fast_forward_to(4);
-			assert_ok!(Democracy::vote(Origin::signed(1), 1, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), 1, aye(1)));
 			fast_forward_to(6);
-			assert_ok!(Democracy::vote(Origin::signed(1), 2, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), 2, aye(1)));
 		});
 	}
 
@@ -2166,18 +2361,14 @@ mod tests {
 				0
 			);
 
-			assert_ok!(Democracy::vote(Origin::signed(1), r2, AYE));
-			assert_eq!(Democracy::voters_for(r2), vec![1]);
-			assert_eq!(Democracy::vote_of((r2, 1)), AYE);
-			assert_eq!(Democracy::tally(r2), (1, 0, 1));
+			assert_ok!(Democracy::vote(Origin::signed(1), r2, aye(1)));
+			assert_eq!(tally(r2), Tally { ayes: 1, nays: 0, turnout: 10 });
 
 			next_block();
 			assert_eq!(Balances::free_balance(&42), 2);
 
-			assert_ok!(Democracy::vote(Origin::signed(1), r1, AYE));
-			assert_eq!(Democracy::voters_for(r1), vec![1]);
-			assert_eq!(Democracy::vote_of((r1, 1)), AYE);
-			assert_eq!(Democracy::tally(r1), (1, 0, 1));
+			assert_ok!(Democracy::vote(Origin::signed(1), r1, aye(1)));
+			assert_eq!(tally(r1), Tally { ayes: 1, nays: 0, turnout: 10 });
 
 			next_block();
 			assert_eq!(Balances::free_balance(&42), 3);
@@ -2194,11 +2385,9 @@ mod tests {
 				VoteThreshold::SuperMajorityApprove,
 				0
 			);
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 
-			assert_eq!(Democracy::voters_for(r), vec![1]);
-			assert_eq!(Democracy::vote_of((r, 1)), AYE);
-			assert_eq!(Democracy::tally(r), (1, 0, 1));
+			assert_eq!(tally(r), Tally { ayes: 1, nays: 0, turnout: 10 });
 
 			next_block();
 			next_block();
@@ -2217,7 +2406,7 @@ mod tests {
 				VoteThreshold::SuperMajorityApprove,
 				0
 			);
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
 			assert_ok!(Democracy::cancel_referendum(Origin::ROOT, r.into()));
 
 			next_block();
@@ -2237,11 +2426,9 @@ mod tests {
 				VoteThreshold::SuperMajorityApprove,
 				0
 			);
-			assert_ok!(Democracy::vote(Origin::signed(1), r, NAY));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, nay(1)));
 
-			assert_eq!(Democracy::voters_for(r), vec![1]);
-			assert_eq!(Democracy::vote_of((r, 1)), NAY);
-			assert_eq!(Democracy::tally(r), (0, 1, 1));
+			assert_eq!(tally(r), Tally { ayes: 0, nays: 1, turnout: 10 });
 
 			next_block();
 			next_block();
@@ -2261,14 +2448,14 @@ mod tests {
 				0
 			);
 
-			assert_ok!(Democracy::vote(Origin::signed(1), r, BIG_AYE));
-			assert_ok!(Democracy::vote(Origin::signed(2), r, BIG_NAY));
-			assert_ok!(Democracy::vote(Origin::signed(3), r, BIG_NAY));
-			assert_ok!(Democracy::vote(Origin::signed(4), r, BIG_AYE));
-			assert_ok!(Democracy::vote(Origin::signed(5), r, BIG_NAY));
-			assert_ok!(Democracy::vote(Origin::signed(6), r, BIG_AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, big_aye(1)));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, big_nay(2)));
+			assert_ok!(Democracy::vote(Origin::signed(3), r, big_nay(3)));
+			assert_ok!(Democracy::vote(Origin::signed(4), r, big_aye(4)));
+			assert_ok!(Democracy::vote(Origin::signed(5), r, big_nay(5)));
+			assert_ok!(Democracy::vote(Origin::signed(6), r, big_aye(6)));
 
-			assert_eq!(Democracy::tally(r), (110, 100, 210));
+			assert_eq!(tally(r), Tally { ayes: 110, nays: 100, turnout: 210 });
 
 			next_block();
 			next_block();
@@ -2287,14 +2474,14 @@ mod tests {
 				VoteThreshold::SuperMajorityApprove,
 				1
 			);
-			assert_ok!(Democracy::vote(Origin::signed(1), r, AYE));
-			assert_ok!(Democracy::vote(Origin::signed(2), r, AYE));
-			assert_ok!(Democracy::vote(Origin::signed(3), r, AYE));
-			assert_ok!(Democracy::vote(Origin::signed(4), r, AYE));
-			assert_ok!(Democracy::vote(Origin::signed(5), r, AYE));
-			assert_ok!(Democracy::vote(Origin::signed(6), r, AYE));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, aye(2)));
+			assert_ok!(Democracy::vote(Origin::signed(3), r, aye(3)));
+			assert_ok!(Democracy::vote(Origin::signed(4), r, aye(4)));
+			assert_ok!(Democracy::vote(Origin::signed(5), r, aye(5)));
+			assert_ok!(Democracy::vote(Origin::signed(6), r, aye(6)));
 
-			assert_eq!(Democracy::tally(r), (21, 0, 21));
+			assert_eq!(tally(r), Tally { ayes: 21, nays: 0, turnout: 210 });
 
 			next_block();
 			assert_eq!(Balances::free_balance(&42), 0);
@@ -2315,10 +2502,10 @@ mod tests {
 				VoteThreshold::SuperMajorityApprove,
 				0
 			);
-			assert_ok!(Democracy::vote(Origin::signed(5), r, BIG_NAY));
-			assert_ok!(Democracy::vote(Origin::signed(6), r, BIG_AYE));
+			assert_ok!(Democracy::vote(Origin::signed(5), r, big_nay(5)));
+			assert_ok!(Democracy::vote(Origin::signed(6), r, big_aye(6)));
 
-			assert_eq!(Democracy::tally(r), (60, 50, 110));
+			assert_eq!(tally(r), Tally { ayes: 60, nays: 50, turnout: 110 });
 
 			next_block();
 			next_block();
@@ -2340,11 +2527,11 @@ mod tests {
 				VoteThreshold::SuperMajorityApprove,
 				0
 			);
-			assert_ok!(Democracy::vote(Origin::signed(4), r, BIG_AYE));
-			assert_ok!(Democracy::vote(Origin::signed(5), r, BIG_NAY));
-			assert_ok!(Democracy::vote(Origin::signed(6), r, BIG_AYE));
+			assert_ok!(Democracy::vote(Origin::signed(4), r, big_aye(4)));
+			assert_ok!(Democracy::vote(Origin::signed(5), r, big_nay(5)));
+			assert_ok!(Democracy::vote(Origin::signed(6), r, big_aye(6)));
 
-			assert_eq!(Democracy::tally(r), (100, 50, 150));
+			assert_eq!(tally(r), Tally { ayes: 100, nays: 50, turnout: 150 });
 
 			next_block();
 			next_block();
@@ -2353,6 +2540,19 @@ mod tests {
 		});
 	}
 
+	fn the_lock(amount: u64) -> BalanceLock<u64, u64> {
+		BalanceLock {
+			id: DEMOCRACY_ID,
+			amount,
+			until: u64::max_value(),
+			reasons: WithdrawReason::Transfer.into(),
+		}
+	}
+
+	fn vote_with(aye: bool, conviction: Conviction, balance: u64) -> AccountVote<u64> {
+		AccountVote::Standard { vote: Vote { aye, conviction }, balance }
+	}
+
 	#[test]
 	fn lock_voting_should_work() {
 		new_test_ext().execute_with(|| {
@@ -2363,53 +2563,68 @@ mod tests {
 				VoteThreshold::SuperMajorityApprove,
 				0
 			);
-			assert_ok!(Democracy::vote(Origin::signed(1), r, Vote {
-				aye: false,
-				conviction: Conviction::Locked5x
-			}));
-			assert_ok!(Democracy::vote(Origin::signed(2), r, Vote {
-				aye: true,
-				conviction: Conviction::Locked4x
-			}));
-			assert_ok!(Democracy::vote(Origin::signed(3), r, Vote {
-				aye: true,
-				conviction: Conviction::Locked3x
-			}));
-			assert_ok!(Democracy::vote(Origin::signed(4), r, Vote {
-				aye: true,
-				conviction: Conviction::Locked2x
-			}));
-			assert_ok!(Democracy::vote(Origin::signed(5), r, Vote {
-				aye: false,
-				conviction: Conviction::Locked1x
-			}));
-
-			assert_eq!(Democracy::tally(r), (250, 100, 150));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, vote_with(false, Conviction::Locked5x, 10)));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, vote_with(true, Conviction::Locked4x, 20)));
+			assert_ok!(Democracy::vote(Origin::signed(3), r, vote_with(true, Conviction::Locked3x, 30)));
+			assert_ok!(Democracy::vote(Origin::signed(4), r, vote_with(true, Conviction::Locked2x, 40)));
+			assert_ok!(Democracy::vote(Origin::signed(5), r, vote_with(false, Conviction::Locked1x, 50)));
+
+			assert_eq!(tally(r), Tally { ayes: 250, nays: 100, turnout: 150 });
+
+			// All balances are currently locked.
+			for i in 1..=5 {
+				assert_eq!(Balances::locks(i), vec![the_lock(i * 10)]);
+			}
 
 			fast_forward_to(2);
 
+			// Referendum passed; 1 and 5 didn't get their way and can now reap and unlock.
+			assert_ok!(Democracy::remove_vote(Origin::signed(1), r));
+			assert_ok!(Democracy::unlock(Origin::signed(1), 1));
+			// Anyone can reap and unlock anyone else's in this context.
+			assert_ok!(Democracy::remove_other_vote(Origin::signed(2), 5, r));
+			assert_ok!(Democracy::unlock(Origin::signed(2), 5));
+
+			// 2, 3, 4 got their way with the vote, so they cannot be reaped by others.
+			assert_noop!(Democracy::remove_other_vote(Origin::signed(1), 2, r), Error::<Test>::NoPermission);
+			// However, they can be unvoted by the owner, though it will make no difference to the lock.
+			assert_ok!(Democracy::remove_vote(Origin::signed(2), r));
+			assert_ok!(Democracy::unlock(Origin::signed(2), 2));
+
 			assert_eq!(Balances::locks(1), vec![]);
-			assert_eq!(Balances::locks(2), vec![BalanceLock {
-				id: DEMOCRACY_ID,
-				amount: u64::max_value(),
-				until: 18,
-				reasons: WithdrawReason::Transfer.into()
-			}]);
-			assert_eq!(Balances::locks(3), vec![BalanceLock {
-				id: DEMOCRACY_ID,
-				amount: u64::max_value(),
-				until: 10,
-				reasons: WithdrawReason::Transfer.into()
-			}]);
-			assert_eq!(Balances::locks(4), vec![BalanceLock {
-				id: DEMOCRACY_ID,
-				amount: u64::max_value(),
-				until: 6,
-				reasons: WithdrawReason::Transfer.into()
-			}]);
+			assert_eq!(Balances::locks(2), vec![the_lock(20)]);
+			assert_eq!(Balances::locks(3), vec![the_lock(30)]);
+			assert_eq!(Balances::locks(4), vec![the_lock(40)]);
 			assert_eq!(Balances::locks(5), vec![]);
-
 			assert_eq!(Balances::free_balance(&42), 2);
+
+			fast_forward_to(5);
+			// No change yet...
+			assert_noop!(Democracy::remove_other_vote(Origin::signed(1), 4, r), Error::<Test>::NoPermission);
+			assert_ok!(Democracy::unlock(Origin::signed(1), 4));
+			assert_eq!(Balances::locks(4), vec![the_lock(40)]);
+			fast_forward_to(6);
+			// 4 should now be able to reap and unlock
+			assert_ok!(Democracy::remove_other_vote(Origin::signed(1), 4, r));
+			assert_ok!(Democracy::unlock(Origin::signed(1), 4));
+			assert_eq!(Balances::locks(4), vec![]);
+
+			fast_forward_to(9);
+			assert_noop!(Democracy::remove_other_vote(Origin::signed(1), 3, r), Error::<Test>::NoPermission);
+			assert_ok!(Democracy::unlock(Origin::signed(1), 3));
+			assert_eq!(Balances::locks(3), vec![the_lock(30)]);
+			fast_forward_to(10);
+			assert_ok!(Democracy::remove_other_vote(Origin::signed(1), 3, r));
+			assert_ok!(Democracy::unlock(Origin::signed(1), 3));
+			assert_eq!(Balances::locks(3), vec![]);
+
+			// 2 doesn't need to reap_vote here because it was already done before.
+			fast_forward_to(17);
+			assert_ok!(Democracy::unlock(Origin::signed(1), 2));
+			assert_eq!(Balances::locks(2), vec![the_lock(20)]);
+			fast_forward_to(18);
+			assert_ok!(Democracy::unlock(Origin::signed(1), 2));
+			assert_eq!(Balances::locks(2), vec![]);
 		});
 	}
 
@@ -2423,25 +2638,143 @@ mod tests {
 				VoteThreshold::SuperMajorityApprove,
 				0
 			);
-			assert_ok!(Democracy::vote(Origin::signed(1), r, Vote {
-				aye: false,
-				conviction: Conviction::Locked5x
-			}));
-			assert_ok!(Democracy::vote(Origin::signed(2), r, Vote {
-				aye: true,
-				conviction: Conviction::Locked4x
-			}));
-			assert_ok!(Democracy::vote(Origin::signed(3), r, Vote {
-				aye: true,
-				conviction: Conviction::Locked3x
-			}));
-			assert_ok!(Democracy::delegate(Origin::signed(4), 2, Conviction::Locked2x));
-			assert_ok!(Democracy::vote(Origin::signed(5), r, Vote {
-				aye: false,
-				conviction: Conviction::Locked1x
-			}));
-
-			assert_eq!(Democracy::tally(r), (250, 100, 150));
+			assert_ok!(Democracy::vote(Origin::signed(1), r, vote_with(false, Conviction::Locked5x, 10)));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, vote_with(true, Conviction::Locked4x, 20)));
+			assert_ok!(Democracy::vote(Origin::signed(3), r, vote_with(true, Conviction::Locked3x, 30)));
+			assert_ok!(Democracy::delegate(Origin::signed(4), 2, Conviction::Locked2x, 40));
+			assert_ok!(Democracy::vote(Origin::signed(5), r, vote_with(false, Conviction::Locked1x, 50)));
+
+			assert_eq!(tally(r), Tally { ayes: 250, nays: 100, turnout: 150 });
+
+			next_block();
+			next_block();
+
+			assert_eq!(Balances::free_balance(&42), 2);
+		});
+	}
+
+	#[test]
+	fn undelegation_keeps_lock_for_conviction_period() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(Democracy::delegate(Origin::signed(4), 2, Conviction::Locked2x, 40));
+			// Delegated balance stays locked for as long as it is delegated.
+			assert_eq!(Balances::locks(4), vec![the_lock(40)]);
+
+			fast_forward_to(3);
+			// Lowering the delegated balance keeps the old lock for its conviction period.
+			assert_ok!(Democracy::delegate(Origin::signed(4), 2, Conviction::None, 10));
+			assert_eq!(Balances::locks(4), vec![the_lock(40)]);
+
+			// Undelegating at 5 keeps the remaining 10 locked until 5; the 40 until 3 + 4.
+			fast_forward_to(5);
+			assert_ok!(Democracy::undelegate(Origin::signed(4)));
+			assert_ok!(Democracy::unlock(Origin::signed(4), 4));
+			assert_eq!(Balances::locks(4), vec![the_lock(40)]);
+
+			fast_forward_to(6);
+			assert_ok!(Democracy::unlock(Origin::signed(4), 4));
+			assert_eq!(Balances::locks(4), vec![the_lock(40)]);
+			fast_forward_to(7);
+			assert_ok!(Democracy::unlock(Origin::signed(4), 4));
+			assert_eq!(Balances::locks(4), vec![]);
+		});
+	}
+
+	#[test]
+	fn split_vote_should_work() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(0);
+			let r = Democracy::inject_referendum(
+				2,
+				set_balance_proposal_hash_and_note(2),
+				VoteThreshold::SuperMajorityApprove,
+				0
+			);
+			assert_noop!(
+				Democracy::vote(Origin::signed(5), r, AccountVote::Split { aye: 40, nay: 20 }),
+				Error::<Test>::InsufficientFunds
+			);
+			assert_ok!(Democracy::vote(Origin::signed(5), r, AccountVote::Split { aye: 30, nay: 20 }));
+			assert_eq!(tally(r), Tally { ayes: 3, nays: 2, turnout: 50 });
+			assert_eq!(Balances::locks(5), vec![the_lock(50)]);
+
+			// Delegations only follow standard votes.
+			assert_ok!(Democracy::delegate(Origin::signed(2), 5, Conviction::None, 20));
+			assert_eq!(tally(r), Tally { ayes: 3, nays: 2, turnout: 50 });
+
+			// Changing the vote replaces it in the tally.
+			assert_ok!(Democracy::vote(Origin::signed(5), r, aye(5)));
+			assert_eq!(tally(r), Tally { ayes: 7, nays: 0, turnout: 70 });
+			assert_ok!(Democracy::vote(Origin::signed(5), r, AccountVote::Split { aye: 10, nay: 10 }));
+			assert_eq!(tally(r), Tally { ayes: 1, nays: 1, turnout: 20 });
+			assert_eq!(Balances::locks(5), vec![the_lock(20)]);
+
+			fast_forward_to(2);
+
+			// Split votes carry no conviction, so they can be removed by anyone.
+			assert_ok!(Democracy::remove_other_vote(Origin::signed(1), 5, r));
+			assert_ok!(Democracy::unlock(Origin::signed(1), 5));
+			assert_eq!(Balances::locks(5), vec![]);
+		});
+	}
+
+	#[test]
+	fn abstain_vote_should_only_count_for_turnout() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(0);
+			let r = Democracy::inject_referendum(
+				2,
+				set_balance_proposal_hash_and_note(2),
+				VoteThreshold::SuperMajorityApprove,
+				0
+			);
+			assert_ok!(Democracy::vote(
+				Origin::signed(5),
+				r,
+				AccountVote::SplitAbstain { aye: 0, nay: 0, abstain: 50 },
+			));
+			assert_ok!(Democracy::vote(
+				Origin::signed(6),
+				r,
+				AccountVote::SplitAbstain { aye: 10, nay: 0, abstain: 50 },
+			));
+			assert_eq!(tally(r), Tally { ayes: 1, nays: 0, turnout: 110 });
+			assert_eq!(Balances::locks(6), vec![the_lock(60)]);
+
+			assert_ok!(Democracy::remove_vote(Origin::signed(5), r));
+			assert_eq!(tally(r), Tally { ayes: 1, nays: 0, turnout: 60 });
+
+			next_block();
+			next_block();
+
+			assert_eq!(Balances::free_balance(&42), 2);
+		});
+	}
+
+	#[test]
+	fn removed_vote_should_leave_the_tally() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(0);
+			let r = Democracy::inject_referendum(
+				2,
+				set_balance_proposal_hash_and_note(2),
+				VoteThreshold::SuperMajorityApprove,
+				0
+			);
+			assert_ok!(Democracy::vote(Origin::signed(1), r, aye(1)));
+			assert_ok!(Democracy::vote(Origin::signed(2), r, big_nay(2)));
+			assert_eq!(tally(r), Tally { ayes: 1, nays: 20, turnout: 30 });
+
+			// Nobody else may remove a vote from an ongoing referendum.
+			assert_noop!(Democracy::remove_other_vote(Origin::signed(1), 2, r), Error::<Test>::NoPermission);
+			assert_noop!(Democracy::remove_vote(Origin::signed(3), r), Error::<Test>::NotVoter);
+
+			assert_ok!(Democracy::remove_vote(Origin::signed(2), r));
+			assert_eq!(tally(r), Tally { ayes: 1, nays: 0, turnout: 10 });
+			assert_ok!(Democracy::unlock(Origin::signed(2), 2));
+			assert_eq!(Balances::locks(2), vec![]);
+			assert_noop!(Democracy::remove_vote(Origin::signed(2), r), Error::<Test>::NotVoter);
 
 			next_block();
 			next_block();
@@ -2449,4 +2782,147 @@ mod tests {
 			assert_eq!(Balances::free_balance(&42), 2);
 		});
 	}
+
+	#[test]
+	fn votes_on_cancelled_referenda_can_be_removed() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(0);
+			let r = Democracy::inject_referendum(
+				2,
+				set_balance_proposal_hash_and_note(2),
+				VoteThreshold::SuperMajorityApprove,
+				0
+			);
+			assert_ok!(Democracy::vote(Origin::signed(1), r, big_aye(1)));
+			assert_ok!(Democracy::cancel_referendum(Origin::ROOT, r));
+			assert_noop!(Democracy::vote(Origin::signed(2), r, aye(2)), Error::<Test>::ReferendumInvalid);
+
+			assert_ok!(Democracy::remove_other_vote(Origin::signed(2), 1, r));
+			assert_ok!(Democracy::unlock(Origin::signed(2), 1));
+			assert_eq!(Balances::locks(1), vec![]);
+			assert_eq!(Democracy::voting_of(1), Voting::default());
+		});
+	}
+
+	#[test]
+	fn legacy_votes_and_delegations_should_migrate() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			// A referendum in the previous layout: (end, proposal_hash, threshold, delay).
+			let r = Democracy::referendum_count();
+			ReferendumCount::put(r + 1);
+			let h = set_balance_proposal_hash_and_note(2);
+			storage::unhashed::put(
+				&<ReferendumInfoOf<Test>>::hashed_key_for(r),
+				&(3u64, h, VoteThreshold::SuperMajorityApprove, 0u64),
+			);
+			<VotersFor<Test>>::insert(r, vec![1, 2]);
+			<VoteOf<Test>>::insert((r, 1), AYE);
+			<VoteOf<Test>>::insert((r, 2), BIG_NAY);
+			// 3 delegates to a voter, 2 voted while delegating and 4 delegates to a delegator.
+			<Delegations<Test>>::insert(3, (1, Conviction::Locked2x));
+			<Delegations<Test>>::insert(2, (1, Conviction::Locked1x));
+			<Delegations<Test>>::insert(4, (3, Conviction::None));
+			for who in 1..=4 {
+				let reasons = WithdrawReason::Transfer.into();
+				Balances::set_lock(LEGACY_DEMOCRACY_ID, &who, 5, 10, reasons);
+			}
+			VotingMigrated::put(false);
+
+			// Only one vote is moved per call while no referendum matures.
+			Democracy::migrate_voting(1, 1);
+			assert!(!VotingMigrated::get());
+			assert_eq!(<VotersFor<Test>>::get(r), vec![2]);
+			assert_eq!(tally(r), Tally { ayes: 1, nays: 0, turnout: 10 });
+
+			// All votes of a referendum are moved in the block in which it matures, leaving the
+			// budget for the delegations.
+			Democracy::migrate_voting(3, 0);
+			assert!(!VotingMigrated::get());
+			assert_eq!(<VotersFor<Test>>::get(r), Vec::<u64>::new());
+			assert_eq!(<Delegations<Test>>::enumerate().count(), 3);
+			assert_eq!(tally(r), Tally { ayes: 1, nays: 20, turnout: 30 });
+
+			Democracy::migrate_voting(3, 3);
+			assert!(VotingMigrated::get());
+			assert_eq!(<VotersFor<Test>>::get(r), Vec::<u64>::new());
+			assert!(!<VoteOf<Test>>::exists((r, 1)));
+			assert_eq!(<Delegations<Test>>::enumerate().count(), 0);
+			// 3's delegation counts towards 1's vote; 4's delegation to 3 is not passed on.
+			assert_eq!(tally(r), Tally { ayes: 61, nays: 20, turnout: 60 });
+			assert_eq!(Democracy::voting_of(2), Voting::Direct {
+				votes: vec![(r, big_nay(2))],
+				delegations: Default::default(),
+				prior: Default::default(),
+			});
+			match Democracy::voting_of(3) {
+				Voting::Delegating { balance, target, conviction, delegations, .. } => {
+					assert_eq!((balance, target, conviction), (30, 1, Conviction::Locked2x));
+					assert_eq!(delegations, Delegated { votes: 4, capital: 40 });
+				}
+				_ => panic!("3 should be delegating"),
+			}
+			// Legacy locks are replaced by the new lock.
+			assert_eq!(Balances::locks(1), vec![the_lock(10)]);
+			assert_eq!(Balances::locks(2), vec![the_lock(20)]);
+			assert_eq!(Balances::locks(3), vec![the_lock(30)]);
+			assert_eq!(Balances::locks(4), vec![the_lock(40)]);
+
+			// Running it again does nothing.
+			Democracy::migrate_voting(3, MIGRATION_BATCH);
+			assert_eq!(tally(r), Tally { ayes: 61, nays: 20, turnout: 60 });
+
+			fast_forward_to(3);
+			assert_eq!(
+				Democracy::referendum_info(r),
+				Some(ReferendumInfo::Finished { end: 3, approved: true })
+			);
+		});
+	}
+
+	#[test]
+	fn legacy_delegation_lock_should_be_removed() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			// Delegations of the previous layout locked everything until undelegated.
+			<Delegations<Test>>::insert(2, (1, Conviction::None));
+			Balances::set_lock(
+				LEGACY_DEMOCRACY_ID,
+				&2,
+				u64::max_value(),
+				u64::max_value(),
+				WithdrawReason::Transfer.into(),
+			);
+			VotingMigrated::put(false);
+
+			Democracy::migrate_voting(1, MIGRATION_BATCH);
+			assert!(VotingMigrated::get());
+			assert_eq!(Balances::locks(2), vec![the_lock(20)]);
+
+			assert_ok!(Democracy::undelegate(Origin::signed(2)));
+			assert_ok!(Democracy::unlock(Origin::signed(2), 2));
+			assert_eq!(Balances::locks(2), vec![]);
+			assert_ok!(Balances::transfer(Origin::signed(2), 3, 20));
+		});
+	}
+
+	#[test]
+	fn legacy_proxies_should_be_removed() {
+		new_test_ext().execute_with(|| {
+			let mut key = sp_io::hashing::twox_128(b"Democracy").to_vec();
+			key.extend_from_slice(&sp_io::hashing::twox_128(b"Proxy"));
+			key.extend_from_slice(&10u64.using_encoded(sp_io::hashing::blake2_256));
+			storage::unhashed::put(&key, &1u64);
+			ProxiesMigrated::put(false);
+
+			<Democracy as OnRuntimeUpgrade>::on_runtime_upgrade();
+			assert!(!storage::unhashed::exists(&key));
+			assert!(ProxiesMigrated::get());
+
+			// Later upgrades leave the prefix alone.
+			storage::unhashed::put(&key, &1u64);
+			<Democracy as OnRuntimeUpgrade>::on_runtime_upgrade();
+			assert!(storage::unhashed::exists(&key));
+		});
+	}
 }
@@ -34,19 +34,21 @@
 //!
 //! Voters can vote for any set of the candidates by providing a list of account ids. Invalid votes
 //! (voting for non-candidates) are ignored during election. Yet, a voter _might_ vote for a future
-//! candidate. Voters reserve a bond as they vote. Each vote defines a `value`. This amount is
+//! candidate. Voters reserve a bond as they vote, made of a fixed `VotingBond` plus
+//! `VotingBondFactor` for each account they vote for. Each vote defines a `value`. This amount is
 //! locked from the account of the voter and indicates the weight of the vote. Voters can update
-//! their votes at any time by calling `vote()` again. This keeps the bond untouched but can
-//! optionally change the locked `value`. After a round, votes are kept and might still be valid for
-//! further rounds. A voter is responsible for calling `remove_voter` once they are done to have
-//! their bond back and remove the lock.
+//! their votes at any time by calling `vote()` again. This adjusts the bond to the new number of
+//! votes and can optionally change the locked `value`. After a round, votes are kept and might
+//! still be valid for further rounds. A voter is responsible for calling `remove_voter` once they
+//! are done to have their bond back and remove the lock.
 //!
 //! Voters also report other voters as being defunct to earn their bond. A voter is defunct once all
 //! of the candidates that they have voted for are neither a valid candidate anymore nor a member.
 //! Upon reporting, if the target voter is actually defunct, the reporter will be rewarded by the
 //! voting bond of the target. The target will lose their bond and get removed. If the target is not
 //! defunct, the reporter is slashed and removed. To prevent being reported, voters should manually
-//! submit a `remove_voter()` as soon as they are in the defunct state.
+//! submit a `remove_voter()` as soon as they are in the defunct state. Root can also remove a list
+//! of defunct voters at once with [`clean_defunct_voters`], in which case their bonds are returned.
 //!
 //! ### Candidacy and Members
 //!
@@ -61,12 +63,17 @@
 //!     election.
 //!   - **Loser**: Any of the candidate who are not a winner are left as losers. A loser might be an
 //!     _outgoing member or runner_, meaning that they are an active member who failed to keep their
-//!     spot. An outgoing will always lose their bond.
+//!     spot. An outgoing will always lose their bond. A member who is demoted to a runner-up, or a
+//!     runner-up who is promoted to a member, keeps their bond.
+//!
+//! After each round, the member with the highest backing is reported to `ChangeMembers` as the
+//! prime member.
 //!
 //! ##### Renouncing candidacy.
 //!
 //! All candidates, elected or not, can renounce their candidacy. A call to [`renounce_candidacy`]
-//! will always cause the candidacy bond to be refunded.
+//! must state, through [`Renouncing`], whether the origin is a member, a runner-up or a plain
+//! candidate, and will always cause the candidacy bond to be refunded.
 //!
 //! Note that with the members being the default candidates for the next round and votes persisting
 //! in storage, the election system is entirely stable given no further input. This means that if
@@ -83,9 +90,14 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use sp_std::prelude::*;
-use sp_runtime::{print, DispatchResult, DispatchError, traits::{Zero, StaticLookup, Bounded, Convert}};
+use codec::{Encode, Decode};
+use sp_runtime::{
+	print, DispatchResult, DispatchError, RuntimeDebug,
+	traits::{Zero, StaticLookup, Bounded, Convert, Saturating},
+};
 use frame_support::{
-	decl_storage, decl_event, ensure, decl_module, decl_error, weights::SimpleDispatchInfo,
+	decl_storage, decl_event, ensure, decl_module, decl_error,
+	weights::{SimpleDispatchInfo, FunctionOf, DispatchClass, Weight},
 	traits::{
 		Currency, Get, LockableCurrency, LockIdentifier, ReservableCurrency, WithdrawReasons,
 		ChangeMembers, OnUnbalanced, WithdrawReason, Contains
@@ -103,6 +115,17 @@ type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trai
 type NegativeImbalanceOf<T> =
 	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
 
+/// The seat that an account renounces through `renounce_candidacy`.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Renouncing {
+	/// A current member.
+	Member,
+	/// A current runner-up.
+	RunnerUp,
+	/// A candidate for the upcoming round, who is neither a member nor a runner-up.
+	Candidate,
+}
+
 pub trait Trait: frame_system::Trait {
 	/// The overarching event type.c
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
@@ -122,9 +145,13 @@ pub trait Trait: frame_system::Trait {
 	/// How much should be locked up in order to submit one's candidacy.
 	type CandidacyBond: Get<BalanceOf<Self>>;
 
-	/// How much should be locked up in order to be able to submit votes.
+	/// How much should be locked up in order to be able to submit votes, regardless of their count.
 	type VotingBond: Get<BalanceOf<Self>>;
 
+	/// How much should be locked up for each account that a voter votes for, on top of
+	/// `VotingBond`.
+	type VotingBondFactor: Get<BalanceOf<Self>>;
+
 	/// Handler for the unbalanced reduction when a candidate has lost (and is not a runner-up)
 	type LoserCandidate: OnUnbalanced<NegativeImbalanceOf<Self>>;
 
@@ -160,6 +187,9 @@ decl_storage! {
 		pub VotesOf get(fn votes_of): linked_map T::AccountId => Vec<T::AccountId>;
 		/// Locked stake of a voter.
 		pub StakeOf get(fn stake_of): map T::AccountId => BalanceOf<T>;
+		/// The bond reserved by a voter. Voters who registered before bonds depended on the number
+		/// of votes have no entry here and have reserved exactly `VotingBond`.
+		pub DepositOf: map T::AccountId => Option<BalanceOf<T>>;
 
 		/// The present candidate list. Sorted based on account-id. A current member or a runner can
 		/// never enter this vector and is always implicitly assumed to be a candidate.
@@ -194,8 +224,8 @@ decl_error! {
 		RunnerSubmit,
 		/// Candidate does not have enough funds.
 		InsufficientCandidateFunds,
-		/// Origin is not a candidate, member or a runner up.
-		InvalidOrigin,
+		/// Origin is not a member, runner-up or candidate, as claimed by the renouncing.
+		InvalidRenouncing,
 		/// Not a member.
 		NotMember,
 	}
@@ -209,6 +239,7 @@ decl_module! {
 
 		const CandidacyBond: BalanceOf<T> = T::CandidacyBond::get();
 		const VotingBond: BalanceOf<T> = T::VotingBond::get();
+		const VotingBondFactor: BalanceOf<T> = T::VotingBondFactor::get();
 		const DesiredMembers: u32 = T::DesiredMembers::get();
 		const DesiredRunnersUp: u32 = T::DesiredRunnersUp::get();
 		const TermDuration: T::BlockNumber = T::TermDuration::get();
@@ -219,9 +250,10 @@ decl_module! {
 		///   - not be empty.
 		///   - be less than the number of candidates.
 		///
-		/// Upon voting, `value` units of `who`'s balance is locked and a bond amount is reserved.
-		/// It is the responsibility of the caller to not place all of their balance into the lock
-		/// and keep some for further transactions.
+		/// Upon voting, `value` units of `who`'s balance is locked and a bond amount, proportional
+		/// to the number of `votes`, is reserved. Voting again tops up or partially returns the bond
+		/// to match the new number of votes. It is the responsibility of the caller to not place all
+		/// of their balance into the lock and keep some for further transactions.
 		///
 		/// # <weight>
 		/// #### State
@@ -247,10 +279,19 @@ decl_module! {
 				Error::<T>::LowBalance,
 			);
 
-			if !Self::is_voter(&who) {
-				// first time voter. Reserve bond.
-				T::Currency::reserve(&who, T::VotingBond::get())
+			// adjust the bond to the new number of votes. Nothing is reserved yet for a first time
+			// voter.
+			let old_deposit = if Self::is_voter(&who) {
+				Self::voter_deposit(&who)
+			} else {
+				Zero::zero()
+			};
+			let new_deposit = Self::voting_bond(votes.len());
+			if new_deposit > old_deposit {
+				T::Currency::reserve(&who, new_deposit - old_deposit)
 					.map_err(|_| Error::<T>::UnableToPayBond)?;
+			} else if new_deposit < old_deposit {
+				T::Currency::unreserve(&who, old_deposit - new_deposit);
 			}
 			// Amount to be locked up.
 			let locked_balance = value.min(T::Currency::total_balance(&who));
@@ -264,6 +305,7 @@ decl_module! {
 				WithdrawReasons::except(WithdrawReason::TransactionPayment),
 			);
 			<StakeOf<T>>::insert(&who, locked_balance);
+			<DepositOf<T>>::insert(&who, new_deposit);
 			<VotesOf<T>>::insert(&who, votes);
 		}
 
@@ -312,12 +354,13 @@ decl_module! {
 			let valid = Self::is_defunct_voter(&target);
 			if valid {
 				// reporter will get the voting bond of the target
-				T::Currency::repatriate_reserved(&target, &reporter, T::VotingBond::get())?;
+				T::Currency::repatriate_reserved(&target, &reporter, Self::voter_deposit(&target))?;
 				// remove the target. They are defunct.
 				Self::do_remove_voter(&target, false);
 			} else {
 				// slash the bond of the reporter.
-				let imbalance = T::Currency::slash_reserved(&reporter, T::VotingBond::get()).0;
+				let deposit = Self::voter_deposit(&reporter);
+				let imbalance = T::Currency::slash_reserved(&reporter, deposit).0;
 				T::BadReport::on_unbalanced(imbalance);
 				// remove the reporter.
 				Self::do_remove_voter(&reporter, false);
@@ -357,59 +400,51 @@ decl_module! {
 			<Candidates<T>>::mutate(|c| c.insert(index, who));
 		}
 
-		/// Renounce one's intention to be a candidate for the next election round. 3 potential
-		/// outcomes exist:
-		/// - `origin` is a candidate and not elected in any set. In this case, the bond is
-		///   unreserved, returned and origin is removed as a candidate.
-		/// - `origin` is a current runner up. In this case, the bond is unreserved, returned and
-		///   origin is removed as a runner.
-		/// - `origin` is a current member. In this case, the bond is unreserved and origin is
-		///   removed as a member, consequently not being a candidate for the next round anymore.
-		///   Similar to [`remove_voter`], if replacement runners exists, they are immediately used.
+		/// Renounce one's intention to be a candidate for the next election round. `renouncing`
+		/// states which seat `origin` currently holds, with 3 potential outcomes:
+		/// - [`Renouncing::Member`]: the bond is unreserved and origin is removed as a member,
+		///   consequently not being a candidate for the next round anymore. Similar to
+		///   [`remove_member`], if replacement runners exists, they are immediately used.
+		/// - [`Renouncing::RunnerUp`]: the bond is unreserved, returned and origin is removed as a
+		///   runner-up.
+		/// - [`Renouncing::Candidate`]: the bond is unreserved, returned and origin is removed as a
+		///   candidate.
+		///
+		/// Fails with `InvalidRenouncing` if `origin` does not hold the stated seat.
+		///
+		/// # <weight>
+		/// #### State
+		/// Reads: O(LogN) Given N candidates, members or runners-up.
+		/// Writes: O(1)
+		/// # </weight>
 		#[weight = SimpleDispatchInfo::FixedOperational(2_000_000)]
-		fn renounce_candidacy(origin) {
+		fn renounce_candidacy(origin, renouncing: Renouncing) {
 			let who = ensure_signed(origin)?;
 
-			// NOTE: this function attempts the 3 conditions (being a candidate, member, runner) and
-			// fails if none are matched. Unlike other Palette functions and modules where checks
-			// happen first and then execution happens, this function is written the other way
-			// around. The main intention is that reading all of the candidates, members and runners
-			// from storage is expensive. Furthermore, we know (soft proof) that they are always
-			// mutually exclusive. Hence, we try one, and only then decode more storage.
-
-			if let Ok(_replacement) = Self::remove_and_replace_member(&who) {
-				T::Currency::unreserve(&who, T::CandidacyBond::get());
-				Self::deposit_event(RawEvent::MemberRenounced(who.clone()));
-
-				// safety guard to make sure we do only one arm. Better to read runners later.
-				return Ok(());
-			}
-
-			let mut runners_up_with_stake = Self::runners_up();
-			if let Some(index) = runners_up_with_stake.iter()
-				.position(|(ref r, ref _s)| r == &who)
-			{
-				runners_up_with_stake.remove(index);
-				// unreserve the bond
-				T::Currency::unreserve(&who, T::CandidacyBond::get());
-				// update storage.
-				<RunnersUp<T>>::put(runners_up_with_stake);
-				// safety guard to make sure we do only one arm. Better to read runners later.
-				return Ok(());
-			}
-
-			let mut candidates = Self::candidates();
-			if let Ok(index) = candidates.binary_search(&who) {
-				candidates.remove(index);
-				// unreserve the bond
-				T::Currency::unreserve(&who, T::CandidacyBond::get());
-				// update storage.
-				<Candidates<T>>::put(candidates);
-				// safety guard to make sure we do only one arm. Better to read runners later.
-				return Ok(());
+			match renouncing {
+				Renouncing::Member => {
+					Self::remove_and_replace_member(&who)
+						.map_err(|_| Error::<T>::InvalidRenouncing)?;
+				}
+				Renouncing::RunnerUp => {
+					let mut runners_up_with_stake = Self::runners_up();
+					let index = runners_up_with_stake.iter()
+						.position(|(ref r, ref _s)| r == &who)
+						.ok_or(Error::<T>::InvalidRenouncing)?;
+					runners_up_with_stake.remove(index);
+					<RunnersUp<T>>::put(runners_up_with_stake);
+				}
+				Renouncing::Candidate => {
+					let mut candidates = Self::candidates();
+					let index = candidates.binary_search(&who)
+						.map_err(|_| Error::<T>::InvalidRenouncing)?;
+					candidates.remove(index);
+					<Candidates<T>>::put(candidates);
+				}
 			}
 
-			Err(Error::<T>::InvalidOrigin)?
+			T::Currency::unreserve(&who, T::CandidacyBond::get());
+			Self::deposit_event(RawEvent::Renounced(who));
 		}
 
 		/// Remove a particular member from the set. This is effective immediately and the bond of
@@ -441,6 +476,30 @@ decl_module! {
 			})
 		}
 
+		/// Remove the given voters if they are defunct, returning their bond and removing their
+		/// lock. Accounts that are not defunct voters are skipped.
+		///
+		/// Unlike [`report_defunct_voter`], nobody is rewarded, hence the bond goes back to the
+		/// voter. The origin must be root.
+		///
+		/// # <weight>
+		/// #### State
+		/// Reads: O(D*E) given D accounts to check and E votes per voter.
+		/// Writes: O(D).
+		/// # </weight>
+		#[weight = FunctionOf(
+			|args: (&Vec<T::AccountId>,)| (args.0.len() as Weight).saturating_mul(1_000_000),
+			DispatchClass::Operational,
+			true,
+		)]
+		fn clean_defunct_voters(origin, defunct: Vec<T::AccountId>) {
+			ensure_root(origin)?;
+
+			defunct.iter()
+				.filter(|v| Self::is_defunct_voter(v))
+				.for_each(|v| Self::do_remove_voter(v, true));
+		}
+
 		/// What to do at the end of each block. Checks if an election needs to happen or not.
 		fn on_initialize(n: T::BlockNumber) {
 			if let Err(e) = Self::end_block(n) {
@@ -464,8 +523,8 @@ decl_event!(
 		/// A member has been removed. This should always be followed by either `NewTerm` ot
 		/// `EmptyTerm`.
 		MemberKicked(AccountId),
-		/// A member has renounced their candidacy.
-		MemberRenounced(AccountId),
+		/// A member, runner-up or candidate has renounced their candidacy.
+		Renounced(AccountId),
 		/// A voter (first element) was reported (byt the second element) with the the report being
 		/// successful or not (third element).
 		VoterReported(AccountId, AccountId, bool),
@@ -496,6 +555,7 @@ impl<T: Trait> Module<T> {
 			);
 
 			<Members<T>>::put(&members_with_stake);
+			let prime = Self::highest_backed(&members_with_stake);
 			let members = members_with_stake.into_iter().map(|m| m.0).collect::<Vec<_>>();
 			let result = Ok(maybe_replacement.is_some());
 			let old = [who.clone()];
@@ -503,6 +563,7 @@ impl<T: Trait> Module<T> {
 				Some(new) => T::ChangeMembers::change_members_sorted(&[new], &old, &members),
 				None => T::ChangeMembers::change_members_sorted(&[], &old, &members),
 			}
+			T::ChangeMembers::set_prime(prime);
 			result
 		} else {
 			Err(Error::<T>::NotMember)?
@@ -583,16 +644,38 @@ impl<T: Trait> Module<T> {
 	/// This will clean always clean the storage associated with the voter, and remove the balance
 	/// lock. Optionally, it would also return the reserved voting bond if indicated by `unreserve`.
 	fn do_remove_voter(who: &T::AccountId, unreserve: bool) {
+		let deposit = Self::voter_deposit(who);
+
 		// remove storage and lock.
 		<VotesOf<T>>::remove(who);
 		<StakeOf<T>>::remove(who);
+		<DepositOf<T>>::remove(who);
 		T::Currency::remove_lock(MODULE_ID, who);
 
 		if unreserve {
-			T::Currency::unreserve(who, T::VotingBond::get());
+			T::Currency::unreserve(who, deposit);
 		}
 	}
 
+	/// The voting bond required for casting `count` votes.
+	fn voting_bond(count: usize) -> BalanceOf<T> {
+		let factor = T::VotingBondFactor::get().saturating_mul((count as u32).into());
+		T::VotingBond::get().saturating_add(factor)
+	}
+
+	/// The voting bond currently reserved by `who`. Only meaningful if `who` is a voter.
+	fn voter_deposit(who: &T::AccountId) -> BalanceOf<T> {
+		<DepositOf<T>>::get(who).unwrap_or_else(T::VotingBond::get)
+	}
+
+	/// The member with the highest backing, if any. Ties are resolved in favour of the greater
+	/// account id.
+	fn highest_backed(members_with_stake: &[(T::AccountId, BalanceOf<T>)]) -> Option<T::AccountId> {
+		members_with_stake.iter()
+			.max_by_key(|(_, s)| *s)
+			.map(|(m, _)| m.clone())
+	}
+
 	/// The locked stake of a voter.
 	fn locked_stake_of(who: &T::AccountId) -> BalanceOf<T> {
 		Self::stake_of(who)
@@ -721,17 +804,19 @@ impl<T: Trait> Module<T> {
 				&new_members_ids,
 			);
 
-			// outgoing candidates lose their bond.
-			let mut to_burn_bond = outgoing.to_vec();
+			T::ChangeMembers::set_prime(Self::highest_backed(&new_members));
 
-			// compute the outgoing of runners up as well and append them to the `to_burn_bond`
-			{
-				let (_, outgoing) = T::ChangeMembers::compute_members_diff(
-					&new_runners_up_ids,
-					&old_runners_up_ids,
-				);
-				to_burn_bond.extend(outgoing);
-			}
+			// outgoing candidates lose their bond, unless they merely moved between the members and
+			// the runners-up.
+			let mut to_burn_bond = outgoing.into_iter()
+				.filter(|o| !new_runners_up_ids.contains(o))
+				.collect::<Vec<T::AccountId>>();
+
+			// runners up who hold no seat anymore lose their bond as well. The old runners up are
+			// sorted by merit, not by id, hence the linear scan.
+			to_burn_bond.extend(old_runners_up_ids.into_iter().filter(|r|
+				!new_runners_up_ids.contains(r) && new_members_ids.binary_search(r).is_err()
+			));
 
 			// Burn loser bond. members list is sorted. O(NLogM) (N candidates, M members)
 			// runner up list is not sorted. O(K*N) given K runner ups. Overall: O(NLogM + N*K)
@@ -778,12 +863,14 @@ impl<T: Trait> Contains<T::AccountId> for Module<T> {
 mod tests {
 	use super::*;
 	use std::cell::RefCell;
-	use frame_support::{assert_ok, assert_noop, parameter_types, weights::Weight};
+	use frame_support::{
+		assert_ok, assert_noop, parameter_types, weights::{Weight, GetDispatchInfo},
+	};
 	use substrate_test_utils::assert_eq_uvec;
 	use sp_core::H256;
 	use sp_runtime::{
 		Perbill, testing::Header, BuildStorage,
-		traits::{BlakeTwo256, IdentityLookup, Block as BlockT},
+		traits::{BlakeTwo256, IdentityLookup, Block as BlockT, BadOrigin},
 	};
 	use crate as elections;
 	use frame_system as system;
@@ -839,6 +926,7 @@ mod tests {
 
 	thread_local! {
 		static VOTING_BOND: RefCell<u64> = RefCell::new(2);
+		static VOTING_BOND_FACTOR: RefCell<u64> = RefCell::new(0);
 		static DESIRED_MEMBERS: RefCell<u32> = RefCell::new(2);
 		static DESIRED_RUNNERS_UP: RefCell<u32> = RefCell::new(2);
 		static TERM_DURATION: RefCell<u64> = RefCell::new(5);
@@ -849,6 +937,11 @@ mod tests {
 		fn get() -> u64 { VOTING_BOND.with(|v| *v.borrow()) }
 	}
 
+	pub struct VotingBondFactor;
+	impl Get<u64> for VotingBondFactor {
+		fn get() -> u64 { VOTING_BOND_FACTOR.with(|v| *v.borrow()) }
+	}
+
 	pub struct DesiredMembers;
 	impl Get<u32> for DesiredMembers {
 		fn get() -> u32 { DESIRED_MEMBERS.with(|v| *v.borrow()) }
@@ -866,6 +959,7 @@ mod tests {
 
 	thread_local! {
 		pub static MEMBERS: RefCell<Vec<u64>> = RefCell::new(vec![]);
+		pub static PRIME: RefCell<Option<u64>> = RefCell::new(None);
 	}
 
 	pub struct TestChangeMembers;
@@ -900,6 +994,11 @@ mod tests {
 			assert_eq!(old_plus_incoming, new_plus_outgoing);
 
 			MEMBERS.with(|m| *m.borrow_mut() = new.to_vec());
+			PRIME.with(|p| *p.borrow_mut() = None);
+		}
+
+		fn set_prime(who: Option<u64>) {
+			PRIME.with(|p| *p.borrow_mut() = who);
 		}
 	}
 
@@ -921,6 +1020,7 @@ mod tests {
 		type ChangeMembers = TestChangeMembers;
 		type CandidacyBond = CandidacyBond;
 		type VotingBond = VotingBond;
+		type VotingBondFactor = VotingBondFactor;
 		type TermDuration = TermDuration;
 		type DesiredMembers = DesiredMembers;
 		type DesiredRunnersUp = DesiredRunnersUp;
@@ -947,6 +1047,7 @@ mod tests {
 	pub struct ExtBuilder {
 		balance_factor: u64,
 		voter_bond: u64,
+		voter_bond_factor: u64,
 		term_duration: u64,
 		desired_runners_up: u32,
 	}
@@ -956,6 +1057,7 @@ mod tests {
 			Self {
 				balance_factor: 1,
 				voter_bond: 2,
+				voter_bond_factor: 0,
 				desired_runners_up: 0,
 				term_duration: 5,
 			}
@@ -967,6 +1069,10 @@ mod tests {
 			self.voter_bond = fee;
 			self
 		}
+		pub fn voter_bond_factor(mut self, factor: u64) -> Self {
+			self.voter_bond_factor = factor;
+			self
+		}
 		pub fn desired_runners_up(mut self, count: u32) -> Self {
 			self.desired_runners_up = count;
 			self
@@ -977,6 +1083,7 @@ mod tests {
 		}
 		pub fn build(self) -> sp_io::TestExternalities {
 			VOTING_BOND.with(|v| *v.borrow_mut() = self.voter_bond);
+			VOTING_BOND_FACTOR.with(|v| *v.borrow_mut() = self.voter_bond_factor);
 			TERM_DURATION.with(|v| *v.borrow_mut() = self.term_duration);
 			DESIRED_RUNNERS_UP.with(|v| *v.borrow_mut() = self.desired_runners_up);
 			GenesisConfig {
@@ -989,7 +1096,6 @@ mod tests {
 						(5, 50 * self.balance_factor),
 						(6, 60 * self.balance_factor)
 					],
-					vesting: vec![],
 				}),
 			}.build_storage().unwrap().into()
 		}
@@ -1925,7 +2031,7 @@ mod tests {
 
 			assert_ok!(Elections::submit_candidacy(Origin::signed(2)));
 			assert_ok!(Elections::submit_candidacy(Origin::signed(4)));
-			assert_ok!(Elections::renounce_candidacy(Origin::signed(3)));
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(3), Renouncing::Candidate));
 
 			assert_eq!(Elections::candidates(), vec![2, 4, 5]);
 		})
@@ -1968,7 +2074,7 @@ mod tests {
 			assert_ok!(Elections::end_block(System::block_number()));
 
 			assert_eq!(Elections::members_ids(), vec![2, 4]);
-			assert_ok!(Elections::renounce_candidacy(Origin::signed(3)));
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(3), Renouncing::RunnerUp));
 		});
 	}
 
@@ -1991,7 +2097,7 @@ mod tests {
 			assert_eq!(Elections::members_ids(), vec![4, 5]);
 			assert_eq!(Elections::runners_up_ids(), vec![2, 3]);
 
-			assert_ok!(Elections::renounce_candidacy(Origin::signed(4)));
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(4), Renouncing::Member));
 			assert_eq!(balances(&4), (38, 2)); // 2 is voting bond.
 
 			assert_eq!(Elections::members_ids(), vec![3, 5]);
@@ -2020,7 +2126,7 @@ mod tests {
 			assert_eq!(Elections::runners_up_ids(), vec![]);
 			assert_eq!(Elections::candidates(), vec![2, 3]);
 
-			assert_ok!(Elections::renounce_candidacy(Origin::signed(4)));
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(4), Renouncing::Member));
 			assert_eq!(balances(&4), (38, 2)); // 2 is voting bond.
 
 			// no replacement
@@ -2050,7 +2156,7 @@ mod tests {
 			assert_eq!(Elections::members_ids(), vec![4, 5]);
 			assert_eq!(Elections::runners_up_ids(), vec![2, 3]);
 
-			assert_ok!(Elections::renounce_candidacy(Origin::signed(3)));
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(3), Renouncing::RunnerUp));
 			assert_eq!(balances(&3), (28, 2)); // 2 is voting bond.
 
 			assert_eq!(Elections::members_ids(), vec![4, 5]);
@@ -2065,7 +2171,7 @@ mod tests {
 			assert_eq!(balances(&5), (47, 3));
 			assert_eq!(Elections::candidates(), vec![5]);
 
-			assert_ok!(Elections::renounce_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(5), Renouncing::Candidate));
 			assert_eq!(balances(&5), (50, 0));
 			assert_eq!(Elections::candidates(), vec![]);
 		})
@@ -2075,12 +2181,227 @@ mod tests {
 	fn wrong_renounce_candidacy_should_fail() {
 		ExtBuilder::default().build().execute_with(|| {
 			assert_noop!(
-				Elections::renounce_candidacy(Origin::signed(5)),
-				Error::<Test>::InvalidOrigin,
+				Elections::renounce_candidacy(Origin::signed(5), Renouncing::Candidate),
+				Error::<Test>::InvalidRenouncing,
+			);
+			assert_noop!(
+				Elections::renounce_candidacy(Origin::signed(5), Renouncing::Member),
+				Error::<Test>::InvalidRenouncing,
+			);
+			assert_noop!(
+				Elections::renounce_candidacy(Origin::signed(5), Renouncing::RunnerUp),
+				Error::<Test>::InvalidRenouncing,
+			);
+		})
+	}
+
+	#[test]
+	fn renounce_candidacy_must_match_the_seat() {
+		ExtBuilder::default().desired_runners_up(1).build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(4)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(3)));
+
+			assert_ok!(Elections::vote(Origin::signed(5), vec![5], 50));
+			assert_ok!(Elections::vote(Origin::signed(4), vec![4], 40));
+			assert_ok!(Elections::vote(Origin::signed(3), vec![3], 30));
+
+			System::set_block_number(5);
+			assert_ok!(Elections::end_block(System::block_number()));
+
+			assert_eq!(Elections::members_ids(), vec![4, 5]);
+			assert_eq!(Elections::runners_up_ids(), vec![3]);
+
+			assert_ok!(Elections::submit_candidacy(Origin::signed(2)));
+
+			assert_noop!(
+				Elections::renounce_candidacy(Origin::signed(5), Renouncing::RunnerUp),
+				Error::<Test>::InvalidRenouncing,
+			);
+			assert_noop!(
+				Elections::renounce_candidacy(Origin::signed(3), Renouncing::Member),
+				Error::<Test>::InvalidRenouncing,
+			);
+			assert_noop!(
+				Elections::renounce_candidacy(Origin::signed(2), Renouncing::RunnerUp),
+				Error::<Test>::InvalidRenouncing,
+			);
+			assert_noop!(
+				Elections::renounce_candidacy(Origin::signed(3), Renouncing::Candidate),
+				Error::<Test>::InvalidRenouncing,
+			);
+
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(3), Renouncing::RunnerUp));
+			assert_eq!(
+				System::events().last().unwrap().event,
+				Event::elections(RawEvent::Renounced(3)),
 			);
 		})
 	}
 
+	#[test]
+	fn voting_bond_is_proportional_to_votes() {
+		ExtBuilder::default().voter_bond_factor(1).build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(4)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(3)));
+
+			assert_ok!(Elections::vote(Origin::signed(2), vec![5], 10));
+			assert_eq!(balances(&2), (17, 3));
+
+			// more votes reserve more.
+			assert_ok!(Elections::vote(Origin::signed(2), vec![5, 4, 3], 10));
+			assert_eq!(balances(&2), (15, 5));
+
+			// fewer votes return the difference.
+			assert_ok!(Elections::vote(Origin::signed(2), vec![4, 3], 10));
+			assert_eq!(balances(&2), (16, 4));
+
+			assert_ok!(Elections::remove_voter(Origin::signed(2)));
+			assert_eq!(balances(&2), (20, 0));
+		});
+	}
+
+	#[test]
+	fn proportional_voting_bond_is_paid_to_reporter() {
+		ExtBuilder::default().voter_bond_factor(1).build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(4)));
+
+			assert_ok!(Elections::vote(Origin::signed(5), vec![5], 50));
+			assert_ok!(Elections::vote(Origin::signed(2), vec![4, 5], 5));
+			assert_eq!(balances(&2), (16, 4));
+
+			System::set_block_number(5);
+			assert_ok!(Elections::end_block(System::block_number()));
+			assert_eq!(Elections::members_ids(), vec![4, 5]);
+
+			assert_ok!(Elections::remove_member(Origin::ROOT, 4));
+			assert_ok!(Elections::remove_member(Origin::ROOT, 5));
+			assert!(Elections::is_defunct_voter(&2));
+
+			assert_ok!(Elections::report_defunct_voter(Origin::signed(5), 2));
+			// 5 gets the whole bond of 2, proportional to its two votes.
+			assert_eq!(balances(&5), (48, 3));
+			assert_eq!(balances(&2), (16, 0));
+		});
+	}
+
+	#[test]
+	fn legacy_voter_deposit_is_voting_bond() {
+		ExtBuilder::default().voter_bond_factor(1).build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+
+			assert_ok!(Elections::vote(Origin::signed(2), vec![5], 10));
+			assert_eq!(balances(&2), (17, 3));
+
+			// as if this voter registered before the deposit was recorded.
+			<DepositOf<Test>>::remove(&2);
+			Balances::unreserve(&2, 1);
+			assert_eq!(balances(&2), (18, 2));
+
+			assert_ok!(Elections::remove_voter(Origin::signed(2)));
+			assert_eq!(balances(&2), (20, 0));
+		});
+	}
+
+	#[test]
+	fn clean_defunct_voters_should_work() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(4)));
+
+			assert_ok!(Elections::vote(Origin::signed(5), vec![5], 50));
+			assert_ok!(Elections::vote(Origin::signed(4), vec![4], 40));
+			assert_ok!(Elections::vote(Origin::signed(3), vec![4], 30));
+			assert_ok!(Elections::vote(Origin::signed(2), vec![3], 20));
+			assert_ok!(Elections::vote(Origin::signed(1), vec![3], 5));
+
+			assert_noop!(Elections::clean_defunct_voters(Origin::signed(1), vec![1]), BadOrigin);
+
+			// Only the given accounts are checked; the weight grows with their number.
+			let call = elections::Call::<Test>::clean_defunct_voters(vec![1, 2, 3, 6]);
+			assert_eq!(call.get_dispatch_info().weight, 4_000_000);
+			assert_eq!(call.get_dispatch_info().class, DispatchClass::Operational);
+			assert_ok!(Elections::clean_defunct_voters(Origin::ROOT, vec![2, 3, 6]));
+
+			// 2 voted for a non-candidate and loses its vote, but keeps its bond. 3 is not defunct
+			// and 1 was not given, so both keep voting.
+			assert_eq_uvec!(all_voters(), vec![1, 3, 4, 5]);
+			assert_eq!(balances(&2), (20, 0));
+			assert!(Balances::locks(&2).is_empty());
+			assert_eq!(balances(&3), (28, 2));
+
+			assert_ok!(Elections::clean_defunct_voters(Origin::ROOT, vec![1]));
+			assert_eq_uvec!(all_voters(), vec![3, 4, 5]);
+			assert_eq!(balances(&1), (10, 0));
+		});
+	}
+
+	#[test]
+	fn highest_backed_member_becomes_prime() {
+		ExtBuilder::default().desired_runners_up(1).build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(4)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(3)));
+
+			assert_ok!(Elections::vote(Origin::signed(1), vec![4], 10));
+			assert_ok!(Elections::vote(Origin::signed(5), vec![5], 50));
+			assert_ok!(Elections::vote(Origin::signed(4), vec![4], 40));
+			assert_ok!(Elections::vote(Origin::signed(3), vec![3], 30));
+
+			System::set_block_number(5);
+			assert_ok!(Elections::end_block(System::block_number()));
+
+			assert_eq!(Elections::members(), vec![(4, 50), (5, 50)]);
+			// ties go to the greater account id.
+			assert_eq!(PRIME.with(|p| *p.borrow()), Some(5));
+
+			assert_ok!(Elections::vote(Origin::signed(5), vec![5], 45));
+
+			System::set_block_number(10);
+			assert_ok!(Elections::end_block(System::block_number()));
+			assert_eq!(Elections::members(), vec![(4, 50), (5, 45)]);
+			assert_eq!(PRIME.with(|p| *p.borrow()), Some(4));
+
+			// the prime is re-computed when the membership changes in between rounds.
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(4), Renouncing::Member));
+			assert_eq!(Elections::members(), vec![(3, 30), (5, 45)]);
+			assert_eq!(PRIME.with(|p| *p.borrow()), Some(5));
+		});
+	}
+
+	#[test]
+	fn seat_holders_keep_bond_when_moving_between_members_and_runners_up() {
+		ExtBuilder::default().desired_runners_up(1).build().execute_with(|| {
+			assert_ok!(Elections::submit_candidacy(Origin::signed(5)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(4)));
+			assert_ok!(Elections::submit_candidacy(Origin::signed(3)));
+
+			assert_ok!(Elections::vote(Origin::signed(5), vec![5], 50));
+			assert_ok!(Elections::vote(Origin::signed(4), vec![4], 40));
+			assert_ok!(Elections::vote(Origin::signed(3), vec![3], 30));
+
+			System::set_block_number(5);
+			assert_ok!(Elections::end_block(System::block_number()));
+			assert_eq!(Elections::members_ids(), vec![4, 5]);
+			assert_eq!(Elections::runners_up_ids(), vec![3]);
+
+			// 4 and 3 swap places.
+			assert_ok!(Elections::vote(Origin::signed(4), vec![4], 20));
+
+			System::set_block_number(10);
+			assert_ok!(Elections::end_block(System::block_number()));
+			assert_eq!(Elections::members_ids(), vec![3, 5]);
+			assert_eq!(Elections::runners_up_ids(), vec![4]);
+
+			// nobody lost their candidacy bond.
+			assert_eq!(balances(&3), (25, 5));
+			assert_eq!(balances(&4), (35, 5));
+			assert_eq!(balances(&5), (45, 5));
+		});
+	}
+
 	#[test]
 	fn behavior_with_dupe_candidate() {
 		ExtBuilder::default().desired_runners_up(2).build().execute_with(|| {
@@ -0,0 +1,115 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API definition required by the Ethereum-compatible RPC extensions of the EVM module.
+//!
+//! This API should be imported and implemented by the runtime of a node that wants to expose
+//! its EVM module through the `eth_*` RPC methods. The dry-runs can be implemented on top of the
+//! module's `dry_run_call` and `dry_run_create` functions.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_std::vec::Vec;
+use codec::{Encode, Decode};
+use sp_core::{H160, H256, U256};
+use sp_runtime::{DispatchError, RuntimeDebug};
+
+/// The nonce and balance of an EVM account.
+#[derive(Eq, PartialEq, Encode, Decode, Default, RuntimeDebug)]
+pub struct Account {
+	/// Account nonce.
+	pub nonce: U256,
+	/// Account balance.
+	pub balance: U256,
+}
+
+/// Information about a block that is not part of its header.
+#[derive(Eq, PartialEq, Encode, Decode, Default, RuntimeDebug)]
+pub struct BlockInfo {
+	/// The Ethereum address of the block author.
+	pub author: H160,
+	/// The timestamp of the block, as exposed to contracts by the `TIMESTAMP` opcode.
+	pub timestamp: u64,
+}
+
+/// The way an EVM execution ended.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum ExitStatus {
+	/// The execution succeeded.
+	Succeed,
+	/// The execution was reverted by the `REVERT` opcode.
+	Revert,
+	/// The execution failed, e.g. because it ran out of gas.
+	Error,
+	/// The execution hit a fatal error of the EVM.
+	Fatal,
+}
+
+/// The outcome of an EVM call or create operation.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct ExecutionInfo<Value> {
+	/// The way the execution ended.
+	pub exit_status: ExitStatus,
+	/// The output of a call, or the address of the created contract.
+	pub value: Value,
+	/// The gas used by the execution.
+	pub used_gas: U256,
+}
+
+sp_api::decl_runtime_apis! {
+	/// The API to query and dry-run the EVM module.
+	pub trait EvmApi {
+		/// Returns the chain id used for replay protection of transactions (EIP-155).
+		fn chain_id() -> u64;
+
+		/// Returns the nonce and balance of the given account.
+		fn account_basic(address: H160) -> Account;
+
+		/// Returns the code stored at the given account, which is empty for non-contracts.
+		fn account_code_at(address: H160) -> Vec<u8>;
+
+		/// Returns the value in the storage of the given account at the given index.
+		fn storage_at(address: H160, index: U256) -> H256;
+
+		/// Returns the information about the block the API is called at.
+		fn block_info() -> BlockInfo;
+
+		/// Perform a call from the given account. The changes are not persisted.
+		///
+		/// No fee is charged for the gas, so `from` only needs to hold `value`. If `gas_price` is
+		/// `None`, the minimal gas price accepted by the runtime is used. An error is returned if
+		/// the call cannot be executed at all, e.g. because the gas limit is too high.
+		fn call(
+			from: H160,
+			to: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			gas_price: Option<U256>,
+		) -> Result<ExecutionInfo<Vec<u8>>, DispatchError>;
+
+		/// Create a contract from the given account. The changes are not persisted.
+		///
+		/// Same as [`call`](EvmApi::call) otherwise.
+		fn create(
+			from: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			gas_price: Option<U256>,
+		) -> Result<ExecutionInfo<H160>, DispatchError>;
+	}
+}
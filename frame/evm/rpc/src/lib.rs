@@ -0,0 +1,372 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ethereum-compatible RPC methods for interaction with the EVM module.
+//!
+//! The methods follow the Ethereum JSON-RPC specification, so that tooling built for Ethereum
+//! can talk to a node running the EVM module. Substrate blocks are presented as Ethereum
+//! blocks: their author is mapped to an Ethereum address by the `ConvertAccountId` of the
+//! module and extrinsics are not listed as transactions.
+
+use std::sync::Arc;
+
+use hex_literal::hex;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{H160, H256, U256, Bytes, hexdisplay::HexDisplay};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, UniqueSaturatedInto, Zero},
+};
+
+mod types;
+
+pub use self::gen_client::Client as EthClient;
+pub use self::types::{Block, BlockNumber, CallRequest};
+pub use pallet_evm_rpc_runtime_api::{
+	self as runtime_api, EvmApi as EvmRuntimeApi, ExitStatus,
+};
+
+const RUNTIME_ERROR: i64 = 1;
+const EXECUTION_REVERTED: i64 = 3;
+const EXECUTION_FAILED: i64 = 4;
+
+/// The gas limit of dry-runs whose request doesn't give one.
+const DEFAULT_GAS_LIMIT: u64 = 10_000_000;
+
+/// The maximum gas limit of dry-runs. This bounds how long a single request may block the RPC.
+const MAX_GAS_LIMIT: u64 = 50_000_000;
+
+/// The hash of an RLP-encoded empty list, which is the uncles hash of every block.
+const EMPTY_UNCLES_HASH: H256 = H256(
+	hex!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347")
+);
+
+/// Ethereum RPC methods.
+#[rpc]
+pub trait EthApi {
+	/// Returns the chain id used for replay protection of transactions (EIP-155).
+	#[rpc(name = "eth_chainId")]
+	fn chain_id(&self) -> Result<U256>;
+
+	/// Returns the number of the best block.
+	#[rpc(name = "eth_blockNumber")]
+	fn block_number(&self) -> Result<U256>;
+
+	/// Returns the balance of the given account.
+	#[rpc(name = "eth_getBalance")]
+	fn balance(&self, address: H160, number: Option<BlockNumber>) -> Result<U256>;
+
+	/// Returns the code stored at the given account.
+	#[rpc(name = "eth_getCode")]
+	fn code_at(&self, address: H160, number: Option<BlockNumber>) -> Result<Bytes>;
+
+	/// Returns the value in the storage of the given account at the given index.
+	#[rpc(name = "eth_getStorageAt")]
+	fn storage_at(
+		&self,
+		address: H160,
+		index: U256,
+		number: Option<BlockNumber>,
+	) -> Result<H256>;
+
+	/// Returns the nonce of the given account.
+	#[rpc(name = "eth_getTransactionCount")]
+	fn transaction_count(&self, address: H160, number: Option<BlockNumber>) -> Result<U256>;
+
+	/// Executes a call without creating a transaction and returns its output.
+	///
+	/// This call is performed locally, so the state is not changed.
+	#[rpc(name = "eth_call")]
+	fn call(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<Bytes>;
+
+	/// Executes a call or contract creation without creating a transaction and returns the
+	/// gas it used.
+	#[rpc(name = "eth_estimateGas")]
+	fn estimate_gas(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<U256>;
+
+	/// Returns the block with the given number.
+	///
+	/// Extrinsics are not Ethereum transactions, so the list of transactions is always empty
+	/// regardless of `full`.
+	#[rpc(name = "eth_getBlockByNumber")]
+	fn block_by_number(&self, number: BlockNumber, full: bool) -> Result<Option<Block>>;
+
+	/// Returns the block with the given hash.
+	///
+	/// See [`block_by_number`](EthApi::block_by_number) for more details.
+	#[rpc(name = "eth_getBlockByHash")]
+	fn block_by_hash(&self, hash: H256, full: bool) -> Result<Option<Block>>;
+}
+
+/// An implementation of the Ethereum RPC methods.
+pub struct Eth<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Eth<C, B> {
+	/// Create new `Eth` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Eth {
+			client,
+			_marker: Default::default(),
+		}
+	}
+}
+
+/// Convert an error of the runtime API into an RPC error.
+fn runtime_error(message: &str, e: impl std::fmt::Debug) -> Error {
+	Error {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: message.into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+/// Check that the requested gas limit doesn't exceed the maximum allowed. No gas limit defaults to
+/// `DEFAULT_GAS_LIMIT`.
+fn check_gas_limit(gas: Option<U256>) -> Result<U256> {
+	let gas_limit = gas.unwrap_or_else(|| U256::from(DEFAULT_GAS_LIMIT));
+	if gas_limit > U256::from(MAX_GAS_LIMIT) {
+		return Err(Error {
+			code: ErrorCode::InvalidParams,
+			message: format!(
+				"Requested gas limit is greater than maximum allowed: {} > {}",
+				gas_limit,
+				MAX_GAS_LIMIT
+			),
+			data: None,
+		});
+	}
+
+	Ok(gas_limit)
+}
+
+/// Convert the exit status of an execution that didn't succeed into an RPC error.
+///
+/// The `output` of a reverted execution is passed along with the error.
+fn check_exit_status(exit_status: &ExitStatus, output: &[u8]) -> Result<()> {
+	match exit_status {
+		ExitStatus::Succeed => Ok(()),
+		ExitStatus::Revert => Err(Error {
+			code: ErrorCode::ServerError(EXECUTION_REVERTED),
+			message: "Execution reverted.".into(),
+			data: Some(format!("0x{}", HexDisplay::from(&output)).into()),
+		}),
+		ExitStatus::Error | ExitStatus::Fatal => Err(Error {
+			code: ErrorCode::ServerError(EXECUTION_FAILED),
+			message: "Execution failed.".into(),
+			data: Some(format!("{:?}", exit_status).into()),
+		}),
+	}
+}
+
+impl<C, B> Eth<C, B> where
+	B: BlockT<Hash = H256>,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+{
+	/// Resolve the block a request refers to. No block defaults to the best block.
+	fn block_id(&self, number: Option<BlockNumber>) -> BlockId<B> {
+		match number.unwrap_or_default() {
+			BlockNumber::Num(n) => BlockId::Number(n.unique_saturated_into()),
+			BlockNumber::Earliest => BlockId::Number(Zero::zero()),
+			BlockNumber::Latest | BlockNumber::Pending =>
+				BlockId::Hash(self.client.info().best_hash),
+		}
+	}
+}
+
+impl<C, B> Eth<C, B> where
+	B: BlockT<Hash = H256>,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: EvmRuntimeApi<B>,
+{
+	/// Present the Substrate block with the given id as an Ethereum block.
+	fn block(&self, id: BlockId<B>) -> Result<Option<Block>> {
+		let header = match self.client.header(id).map_err(|e| Error {
+			code: ErrorCode::InternalError,
+			message: format!("{:?}", e),
+			data: None,
+		})? {
+			Some(header) => header,
+			None => return Ok(None),
+		};
+
+		let hash = header.hash();
+		let info = self.client.runtime_api()
+			.block_info(&BlockId::Hash(hash))
+			.map_err(|e| runtime_error("Runtime trapped while querying the block.", e))?;
+		let number: u128 = (*header.number()).unique_saturated_into();
+
+		Ok(Some(Block {
+			hash: Some(hash),
+			parent_hash: *header.parent_hash(),
+			uncles_hash: EMPTY_UNCLES_HASH,
+			author: info.author,
+			state_root: *header.state_root(),
+			transactions_root: *header.extrinsics_root(),
+			receipts_root: H256::zero(),
+			number: Some(U256::from(number)),
+			gas_used: U256::zero(),
+			gas_limit: U256::zero(),
+			extra_data: Bytes(Vec::new()),
+			timestamp: U256::from(info.timestamp),
+			difficulty: U256::zero(),
+			total_difficulty: Some(U256::zero()),
+			uncles: Vec::new(),
+			transactions: Vec::new(),
+			size: None,
+		}))
+	}
+
+	/// Dry-run the given request and return the gas it used and, for calls, its output.
+	fn execute(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+	) -> Result<(Option<Vec<u8>>, U256)> {
+		let api = self.client.runtime_api();
+		let at = self.block_id(number);
+
+		let CallRequest { from, to, gas_price, gas, value, data } = request;
+		let from = from.unwrap_or_default();
+		let gas_limit = check_gas_limit(gas)?;
+		let value = value.unwrap_or_default();
+		let data = data.map(|data| data.0).unwrap_or_default();
+
+		match to {
+			Some(to) => {
+				let info = api
+					.call(&at, from, to, data, value, gas_limit, gas_price)
+					.map_err(|e| runtime_error("Runtime trapped while executing a call.", e))?
+					.map_err(|e| runtime_error("The call could not be executed.", e))?;
+				check_exit_status(&info.exit_status, &info.value)?;
+				Ok((Some(info.value), info.used_gas))
+			},
+			None => {
+				let info = api
+					.create(&at, from, data, value, gas_limit, gas_price)
+					.map_err(|e| runtime_error("Runtime trapped while creating a contract.", e))?
+					.map_err(|e| runtime_error("The contract could not be created.", e))?;
+				check_exit_status(&info.exit_status, &[])?;
+				Ok((None, info.used_gas))
+			},
+		}
+	}
+}
+
+impl<C, B> EthApi for Eth<C, B> where
+	B: BlockT<Hash = H256>,
+	C: Send + Sync + 'static + ProvideRuntimeApi<B> + HeaderBackend<B>,
+	C::Api: EvmRuntimeApi<B>,
+{
+	fn chain_id(&self) -> Result<U256> {
+		let at = self.block_id(None);
+		self.client.runtime_api()
+			.chain_id(&at)
+			.map(U256::from)
+			.map_err(|e| runtime_error("Runtime trapped while querying the chain id.", e))
+	}
+
+	fn block_number(&self) -> Result<U256> {
+		let number: u128 = self.client.info().best_number.unique_saturated_into();
+		Ok(U256::from(number))
+	}
+
+	fn balance(&self, address: H160, number: Option<BlockNumber>) -> Result<U256> {
+		let at = self.block_id(number);
+		self.client.runtime_api()
+			.account_basic(&at, address)
+			.map(|account| account.balance)
+			.map_err(|e| runtime_error("Runtime trapped while querying the balance.", e))
+	}
+
+	fn code_at(&self, address: H160, number: Option<BlockNumber>) -> Result<Bytes> {
+		let at = self.block_id(number);
+		self.client.runtime_api()
+			.account_code_at(&at, address)
+			.map(Bytes)
+			.map_err(|e| runtime_error("Runtime trapped while querying the code.", e))
+	}
+
+	fn storage_at(
+		&self,
+		address: H160,
+		index: U256,
+		number: Option<BlockNumber>,
+	) -> Result<H256> {
+		let at = self.block_id(number);
+		self.client.runtime_api()
+			.storage_at(&at, address, index)
+			.map_err(|e| runtime_error("Runtime trapped while querying storage.", e))
+	}
+
+	fn transaction_count(&self, address: H160, number: Option<BlockNumber>) -> Result<U256> {
+		let at = self.block_id(number);
+		self.client.runtime_api()
+			.account_basic(&at, address)
+			.map(|account| account.nonce)
+			.map_err(|e| runtime_error("Runtime trapped while querying the nonce.", e))
+	}
+
+	fn call(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<Bytes> {
+		if request.to.is_none() {
+			return Err(Error {
+				code: ErrorCode::InvalidParams,
+				message: "The recipient of a call must be given.".into(),
+				data: None,
+			});
+		}
+
+		let (output, _) = self.execute(request, number)?;
+		Ok(Bytes(output.unwrap_or_default()))
+	}
+
+	fn estimate_gas(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<U256> {
+		let (_, used_gas) = self.execute(request, number)?;
+		Ok(used_gas)
+	}
+
+	fn block_by_number(&self, number: BlockNumber, _full: bool) -> Result<Option<Block>> {
+		self.block(self.block_id(Some(number)))
+	}
+
+	fn block_by_hash(&self, hash: H256, _full: bool) -> Result<Option<Block>> {
+		self.block(BlockId::Hash(hash))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn gas_limit_is_bounded() {
+		assert_eq!(check_gas_limit(None).unwrap(), U256::from(DEFAULT_GAS_LIMIT));
+		assert_eq!(check_gas_limit(Some(U256::from(21_000))).unwrap(), U256::from(21_000));
+		assert_eq!(
+			check_gas_limit(Some(U256::from(MAX_GAS_LIMIT))).unwrap(),
+			U256::from(MAX_GAS_LIMIT),
+		);
+		assert_eq!(
+			check_gas_limit(Some(U256::from(MAX_GAS_LIMIT) + 1)).unwrap_err().code,
+			ErrorCode::InvalidParams,
+		);
+	}
+}
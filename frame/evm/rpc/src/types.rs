@@ -0,0 +1,230 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ethereum-shaped types used by the RPC methods.
+
+use std::fmt;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer, de::{self, Visitor}};
+use sp_core::{H160, H256, U256, Bytes};
+
+/// The block a request refers to: either a block number or one of the tags `latest`, `earliest`
+/// and `pending`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BlockNumber {
+	/// The block with the given number.
+	Num(u64),
+	/// The best block.
+	Latest,
+	/// The genesis block.
+	Earliest,
+	/// The block that is currently being built. Treated the same as `Latest`.
+	Pending,
+}
+
+impl Default for BlockNumber {
+	fn default() -> Self {
+		BlockNumber::Latest
+	}
+}
+
+impl Serialize for BlockNumber {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match *self {
+			BlockNumber::Num(n) => serializer.serialize_str(&format!("0x{:x}", n)),
+			BlockNumber::Latest => serializer.serialize_str("latest"),
+			BlockNumber::Earliest => serializer.serialize_str("earliest"),
+			BlockNumber::Pending => serializer.serialize_str("pending"),
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for BlockNumber {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<BlockNumber, D::Error> {
+		deserializer.deserialize_any(BlockNumberVisitor)
+	}
+}
+
+struct BlockNumberVisitor;
+
+impl<'de> Visitor<'de> for BlockNumberVisitor {
+	type Value = BlockNumber;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		write!(formatter, "a block number or 'latest', 'earliest' or 'pending'")
+	}
+
+	fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+		match value {
+			"latest" => Ok(BlockNumber::Latest),
+			"earliest" => Ok(BlockNumber::Earliest),
+			"pending" => Ok(BlockNumber::Pending),
+			_ if value.starts_with("0x") => u64::from_str_radix(&value[2..], 16)
+				.map(BlockNumber::Num)
+				.map_err(|e| E::custom(format!("Invalid block number: {}", e))),
+			_ => Err(E::custom("Invalid block number: missing 0x prefix".to_string())),
+		}
+	}
+
+	fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+		Ok(BlockNumber::Num(value))
+	}
+}
+
+/// The parameters of a call or create operation that is executed without creating a
+/// transaction.
+#[derive(Debug, Default, PartialEq, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallRequest {
+	/// Sender
+	pub from: Option<H160>,
+	/// Recipient, or `None` to create a contract
+	pub to: Option<H160>,
+	/// Gas price
+	pub gas_price: Option<U256>,
+	/// Gas limit
+	pub gas: Option<U256>,
+	/// Value to transfer
+	pub value: Option<U256>,
+	/// Call data, or init code when creating a contract
+	pub data: Option<Bytes>,
+}
+
+/// A Substrate block presented in the shape of an Ethereum block.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Block {
+	/// Hash of the block
+	pub hash: Option<H256>,
+	/// Hash of the parent block
+	pub parent_hash: H256,
+	/// Hash of the uncles, which is always the hash of an empty list
+	#[serde(rename = "sha3Uncles")]
+	pub uncles_hash: H256,
+	/// Ethereum address of the block author
+	#[serde(rename = "miner")]
+	pub author: H160,
+	/// State root
+	pub state_root: H256,
+	/// Transactions root, which is the extrinsics root of the Substrate block
+	pub transactions_root: H256,
+	/// Receipts root
+	pub receipts_root: H256,
+	/// Block number
+	pub number: Option<U256>,
+	/// Gas used
+	pub gas_used: U256,
+	/// Gas limit
+	pub gas_limit: U256,
+	/// Extra data
+	pub extra_data: Bytes,
+	/// Timestamp
+	pub timestamp: U256,
+	/// Difficulty, which is always zero
+	pub difficulty: U256,
+	/// Total difficulty, which is always zero
+	pub total_difficulty: Option<U256>,
+	/// Hashes of the uncles, which are always empty
+	pub uncles: Vec<H256>,
+	/// Hashes of the transactions
+	pub transactions: Vec<H256>,
+	/// Size of the block in bytes
+	pub size: Option<U256>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn block_number_serialize_deserialize_properly() {
+		fn test(expected: &str, number: BlockNumber) {
+			let deserialized: BlockNumber = serde_json::from_str(expected).unwrap();
+			assert_eq!(deserialized, number);
+			assert_eq!(serde_json::to_string(&number).unwrap(), expected);
+		}
+
+		test(r#""0x0""#, BlockNumber::Num(0));
+		test(r#""0x1a""#, BlockNumber::Num(26));
+		test(r#""latest""#, BlockNumber::Latest);
+		test(r#""earliest""#, BlockNumber::Earliest);
+		test(r#""pending""#, BlockNumber::Pending);
+
+		assert_eq!(serde_json::from_str::<BlockNumber>("26").unwrap(), BlockNumber::Num(26));
+		assert!(serde_json::from_str::<BlockNumber>(r#""26""#).is_err());
+		assert!(serde_json::from_str::<BlockNumber>(r#""0xzz""#).is_err());
+	}
+
+	#[test]
+	fn call_request_deserialize_properly() {
+		let request: CallRequest = serde_json::from_str(r#"{
+			"from": "0x0000000000000000000000000000000000000001",
+			"to": "0x0000000000000000000000000000000000000002",
+			"gas": "0x5208",
+			"data": "0x0102"
+		}"#).unwrap();
+
+		assert_eq!(request, CallRequest {
+			from: Some(H160::from_low_u64_be(1)),
+			to: Some(H160::from_low_u64_be(2)),
+			gas_price: None,
+			gas: Some(U256::from(21_000)),
+			value: None,
+			data: Some(Bytes(vec![1, 2])),
+		});
+
+		let request: CallRequest = serde_json::from_str("{}").unwrap();
+		assert_eq!(request, CallRequest::default());
+	}
+
+	#[test]
+	fn block_serialize_properly() {
+		let block = Block {
+			hash: Some(H256::repeat_byte(1)),
+			parent_hash: H256::zero(),
+			uncles_hash: H256::zero(),
+			author: H160::repeat_byte(2),
+			state_root: H256::zero(),
+			transactions_root: H256::zero(),
+			receipts_root: H256::zero(),
+			number: Some(U256::from(5)),
+			gas_used: U256::zero(),
+			gas_limit: U256::zero(),
+			extra_data: Bytes(vec![]),
+			timestamp: U256::from(1_000),
+			difficulty: U256::zero(),
+			total_difficulty: Some(U256::zero()),
+			uncles: vec![],
+			transactions: vec![],
+			size: None,
+		};
+
+		let zero = "0x0000000000000000000000000000000000000000000000000000000000000000";
+		let expected = format!(
+			concat!(
+				r#"{{"hash":"0x{}","parentHash":"{zero}","sha3Uncles":"{zero}","#,
+				r#""miner":"0x{}","stateRoot":"{zero}","transactionsRoot":"{zero}","#,
+				r#""receiptsRoot":"{zero}","number":"0x5","gasUsed":"0x0","gasLimit":"0x0","#,
+				r#""extraData":"0x","timestamp":"0x3e8","difficulty":"0x0","#,
+				r#""totalDifficulty":"0x0","uncles":[],"transactions":[],"size":null}}"#,
+			),
+			"01".repeat(32),
+			"02".repeat(20),
+			zero = zero,
+		);
+		assert_eq!(serde_json::to_string(&block).unwrap(), expected);
+	}
+}
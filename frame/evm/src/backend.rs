@@ -5,11 +5,12 @@ use serde::{Serialize, Deserialize};
 use codec::{Encode, Decode};
 use sp_core::{U256, H256, H160};
 use sp_runtime::traits::UniqueSaturatedInto;
-use frame_support::storage::{StorageMap, StorageDoubleMap};
+use frame_support::storage::{StorageMap, StorageDoubleMap, StorageValue};
+use frame_support::traits::Get;
 use sha3::{Keccak256, Digest};
 use evm::Config;
 use evm::backend::{Backend as BackendT, ApplyBackend, Apply};
-use crate::{Trait, Accounts, AccountStorages, AccountCodes, Module, Event};
+use crate::{Trait, Accounts, AccountStorages, AccountCodes, Coinbase, Module, Event};
 
 #[derive(Clone, Eq, PartialEq, Encode, Decode, Default)]
 #[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
@@ -79,7 +80,7 @@ impl<'vicinity, T: Trait> BackendT for Backend<'vicinity, T> {
 	}
 
 	fn block_coinbase(&self) -> H160 {
-		H160::default()
+		Coinbase::get()
 	}
 
 	fn block_timestamp(&self) -> U256 {
@@ -96,7 +97,7 @@ impl<'vicinity, T: Trait> BackendT for Backend<'vicinity, T> {
 	}
 
 	fn chain_id(&self) -> U256 {
-		U256::from(sp_io::misc::chain_id())
+		U256::from(T::ChainId::get())
 	}
 
 	fn exists(&self, _address: H160) -> bool {
@@ -20,29 +20,66 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 mod backend;
+mod precompiles;
+mod transaction;
 
 pub use crate::backend::{Account, Log, Vicinity, Backend};
+pub use crate::precompiles::StandardPrecompiles;
+pub use crate::transaction::{
+	Transaction, TransactionAction, TransactionSignature, Receipt, Bloom,
+};
+pub use evm::ExitReason;
 
-use sp_std::{vec::Vec, marker::PhantomData};
+use sp_std::{prelude::*, marker::PhantomData};
+use codec::Encode;
 use frame_support::{ensure, decl_module, decl_storage, decl_event, decl_error};
 use frame_support::weights::{Weight, WeighData, ClassifyDispatch, DispatchClass, PaysFee};
-use frame_support::traits::{Currency, WithdrawReason, ExistenceRequirement};
-use frame_system::{self as system, ensure_signed};
+use frame_support::traits::{
+	Currency, WithdrawReason, ExistenceRequirement, FindAuthor, OnUnbalanced, Get,
+};
+use frame_system::{self as system, ensure_signed, ensure_none};
 use sp_runtime::ModuleId;
 use frame_support::weights::SimpleDispatchInfo;
 use sp_core::{U256, H256, H160, Hasher};
 use sp_runtime::{
 	DispatchResult, traits::{UniqueSaturatedInto, AccountIdConversion, SaturatedConversion},
+	transaction_validity::{
+		TransactionLongevity, TransactionValidity, ValidTransaction, InvalidTransaction,
+	},
 };
-use evm::{ExitReason, ExitSucceed, ExitError};
+use evm::{ExitSucceed, ExitError};
 use evm::executor::StackExecutor;
 use evm::backend::ApplyBackend;
 
 const MODULE_ID: ModuleId = ModuleId(*b"py/ethvm");
 
+/// How far the nonce of a pooled transaction may be ahead of the nonce of its sender.
+const MAX_NONCE_GAP: u64 = 64;
+
 /// Type alias for currency balance.
 pub type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
 
+/// Type alias for negative imbalances of the currency.
+pub type NegativeImbalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
+
+/// Custom validity errors used while validating unsigned `transact` transactions.
+#[repr(u8)]
+pub enum ValidityError {
+	/// The signature is invalid or not replay protected for this chain.
+	InvalidSignature = 0,
+	/// The gas limit is higher than the maximal gas limit of a transaction.
+	GasLimitTooHigh = 1,
+	/// The gas price is lower than the minimal gas price.
+	GasPriceTooLow = 2,
+}
+
+impl From<ValidityError> for u8 {
+	fn from(err: ValidityError) -> Self {
+		err as u8
+	}
+}
+
 /// Trait that outputs the current transaction gas price.
 pub trait FeeCalculator {
 	/// Return the minimal required gas price.
@@ -92,6 +129,8 @@ impl<H: Hasher, A: AsRef<[u8]>> ConvertAccountId<A> for HashTruncateConvertAccou
 }
 
 /// Custom precompiles to be used by EVM engine.
+///
+/// [`StandardPrecompiles`] implements the standard precompiles of Ethereum.
 pub trait Precompiles {
 	/// Try to execute the code address as precompile. If the code address is not
 	/// a precompile or the precompile is not yet available, return `None`.
@@ -135,6 +174,13 @@ impl WeighData<(&Vec<u8>, &U256, &u32, &U256)> for WeightForCallCreate {
 	}
 }
 
+impl WeighData<(&Transaction,)> for WeightForCallCreate {
+	fn weigh_data(&self, (transaction,): (&Transaction,)) -> Weight {
+		transaction.gas_price.saturated_into::<Weight>()
+			.saturating_mul(transaction.gas_limit.saturated_into::<Weight>())
+	}
+}
+
 impl<T> ClassifyDispatch<T> for WeightForCallCreate {
 	fn classify_dispatch(&self, _: T) -> DispatchClass {
 		DispatchClass::Normal
@@ -159,13 +205,26 @@ pub trait Trait: frame_system::Trait + pallet_timestamp::Trait {
 	type Event: From<Event> + Into<<Self as frame_system::Trait>::Event>;
 	/// Precompiles associated with this EVM engine.
 	type Precompiles: Precompiles;
+	/// Find the author of the current block, which is used as the block coinbase.
+	type FindAuthor: FindAuthor<Self::AccountId>;
+	/// Handler for the fees paid for the gas used by Ethereum transactions.
+	type GasPayment: OnUnbalanced<NegativeImbalanceOf<Self>>;
+	/// The chain id that Ethereum transactions must be replay protected for (EIP-155).
+	type ChainId: Get<u64>;
 }
 
 decl_storage! {
 	trait Store for Module<T: Trait> as Example {
 		Accounts get(fn accounts) config(): map H160 => Account;
-		AccountCodes: map H160 => Vec<u8>;
-		AccountStorages: double_map H160, H256 => H256;
+		AccountCodes get(fn account_codes): map H160 => Vec<u8>;
+		AccountStorages get(fn account_storages): double_map H160, H256 => H256;
+		/// The Ethereum address of the author of the current block.
+		Coinbase get(fn coinbase): H160;
+		/// The receipts of the Ethereum transactions executed in the current block, by the index
+		/// of the transaction within the block.
+		Receipts get(fn receipt): map hasher(twox_64_concat) u32 => Option<Receipt>;
+		/// The number of Ethereum transactions executed in the current block.
+		TransactionCount get(fn transaction_count): u32;
 	}
 }
 
@@ -195,6 +254,12 @@ decl_error! {
 		ExitReasonRevert,
 		/// Call returned VM fatal error
 		ExitReasonFatal,
+		/// The signature of the transaction is invalid or not replay protected for this chain.
+		InvalidSignature,
+		/// The nonce of the transaction doesn't match the nonce of the sender.
+		InvalidNonce,
+		/// The gas limit of the transaction is too high.
+		GasLimitTooHigh,
 	}
 }
 
@@ -258,52 +323,18 @@ decl_module! {
 			gas_price: U256,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
-			ensure!(gas_price >= T::FeeCalculator::min_gas_price(), Error::<T>::GasPriceTooLow);
 			let source = T::ConvertAccountId::convert_account_id(&sender);
 
-			let vicinity = Vicinity {
-				gas_price,
-				origin: source,
-			};
-
-			let mut backend = Backend::<T>::new(&vicinity);
-			let mut executor = StackExecutor::new_with_precompile(
-				&backend,
-				gas_limit as usize,
-				&backend::GASOMETER_CONFIG,
-				T::Precompiles::execute,
-			);
-
-			let total_fee = gas_price.checked_mul(U256::from(gas_limit))
-				.ok_or(Error::<T>::FeeOverflow)?;
-			if Accounts::get(&source).balance <
-				value.checked_add(total_fee).ok_or(Error::<T>::PaymentOverflow)?
-			{
-				Err(Error::<T>::BalanceLow)?
-			}
-			executor.withdraw(source, total_fee).map_err(|_| Error::<T>::WithdrawFailed)?;
-
-			let reason = executor.transact_call(
+			let (reason, _, _, _) = Self::execute_call(
 				source,
 				target,
-				value,
 				input,
-				gas_limit as usize,
-			);
-
-			let ret = match reason {
-				ExitReason::Succeed(_) => Ok(()),
-				ExitReason::Error(_) => Err(Error::<T>::ExitReasonFailed),
-				ExitReason::Revert(_) => Err(Error::<T>::ExitReasonRevert),
-				ExitReason::Fatal(_) => Err(Error::<T>::ExitReasonFatal),
-			};
-			let actual_fee = executor.fee(gas_price);
-			executor.deposit(source, total_fee.saturating_sub(actual_fee));
-
-			let (values, logs) = executor.deconstruct();
-			backend.apply(values, logs, true);
+				value,
+				gas_limit,
+				gas_price,
+			)?;
 
-			ret.map_err(Into::into)
+			Self::exit_reason_to_result(reason)
 		}
 
 		/// Issue an EVM create operation. This is similar to a contract creation transaction in
@@ -317,52 +348,105 @@ decl_module! {
 			gas_price: U256,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
-			ensure!(gas_price >= T::FeeCalculator::min_gas_price(), Error::<T>::GasPriceTooLow);
-
 			let source = T::ConvertAccountId::convert_account_id(&sender);
 
-			let vicinity = Vicinity {
+			let (reason, _, _, _) = Self::execute_create(
+				source,
+				init,
+				value,
+				gas_limit,
 				gas_price,
-				origin: source,
-			};
-
-			let mut backend = Backend::<T>::new(&vicinity);
-			let mut executor = StackExecutor::new_with_precompile(
-				&backend,
-				gas_limit as usize,
-				&backend::GASOMETER_CONFIG,
-				T::Precompiles::execute,
-			);
+			)?;
 
-			let total_fee = gas_price.checked_mul(U256::from(gas_limit))
-				.ok_or(Error::<T>::FeeOverflow)?;
-			if Accounts::get(&source).balance <
-				value.checked_add(total_fee).ok_or(Error::<T>::PaymentOverflow)?
-			{
-				Err(Error::<T>::BalanceLow)?
-			}
-			executor.withdraw(source, total_fee).map_err(|_| Error::<T>::WithdrawFailed)?;
+			Self::exit_reason_to_result(reason)
+		}
 
-			let reason = executor.transact_create(
-				source,
-				value,
-				init,
-				gas_limit as usize,
+		/// Execute a signed Ethereum transaction.
+		///
+		/// The transaction is submitted unsigned and authorized by its own signature, which must
+		/// be replay protected for the chain id of this chain as defined by EIP-155. The fee for
+		/// the used gas is paid in `Currency` and a receipt is recorded even if the execution
+		/// failed.
+		#[weight = WeightForCallCreate]
+		fn transact(origin, transaction: Transaction) {
+			ensure_none(origin)?;
+
+			let source = Self::transaction_sender(&transaction)
+				.ok_or(Error::<T>::InvalidSignature)?;
+			ensure!(transaction.nonce == Accounts::get(&source).nonce, Error::<T>::InvalidNonce);
+			ensure!(
+				transaction.gas_limit <= U256::from(u32::max_value()),
+				Error::<T>::GasLimitTooHigh
 			);
+			let gas_limit = transaction.gas_limit.low_u32();
+
+			let (reason, contract_address, used_gas, logs) = match transaction.action {
+				TransactionAction::Call(target) => {
+					let (reason, _, used_gas, logs) = Self::execute_call(
+						source,
+						target,
+						transaction.input.clone(),
+						transaction.value,
+						gas_limit,
+						transaction.gas_price,
+					)?;
+					(reason, None, used_gas, logs)
+				},
+				TransactionAction::Create => {
+					let (reason, address, used_gas, logs) = Self::execute_create(
+						source,
+						transaction.input.clone(),
+						transaction.value,
+						gas_limit,
+						transaction.gas_price,
+					)?;
+					(reason, Some(address), used_gas, logs)
+				},
+			};
 
-			let ret = match reason {
-				ExitReason::Succeed(_) => Ok(()),
-				ExitReason::Error(_) => Err(Error::<T>::ExitReasonFailed),
-				ExitReason::Revert(_) => Err(Error::<T>::ExitReasonRevert),
-				ExitReason::Fatal(_) => Err(Error::<T>::ExitReasonFatal),
+			let fee = used_gas.saturating_mul(transaction.gas_price);
+			let fee = if fee > U256::from(u128::max_value()) {
+				u128::max_value()
+			} else {
+				fee.low_u128()
 			};
-			let actual_fee = executor.fee(gas_price);
-			executor.deposit(source, total_fee.saturating_sub(actual_fee));
+			let (imbalance, _) = T::Currency::slash(
+				&Self::account_id(),
+				fee.unique_saturated_into(),
+			);
+			T::GasPayment::on_unbalanced(imbalance);
+
+			let status = Self::exit_reason_to_result(reason).is_ok();
+			let index = TransactionCount::get();
+			let cumulative_gas_used = index.checked_sub(1)
+				.and_then(|previous| Receipts::get(previous))
+				.map(|receipt| receipt.cumulative_gas_used)
+				.unwrap_or_default()
+				.saturating_add(used_gas);
+			Receipts::insert(index, Receipt {
+				transaction_hash: transaction.hash(),
+				from: source,
+				contract_address: if status { contract_address } else { None },
+				status,
+				used_gas,
+				cumulative_gas_used,
+				logs_bloom: Bloom::from_logs(&logs),
+				logs,
+			});
+			TransactionCount::put(index + 1);
+		}
 
-			let (values, logs) = executor.deconstruct();
-			backend.apply(values, logs, true);
+		fn on_initialize() {
+			for index in 0..TransactionCount::take() {
+				Receipts::remove(index);
+			}
 
-			ret.map_err(Into::into)
+			let digest = <frame_system::Module<T>>::digest();
+			let pre_runtime_digests = digest.logs.iter().filter_map(|d| d.as_pre_runtime());
+			let coinbase = T::FindAuthor::find_author(pre_runtime_digests)
+				.map(|author| T::ConvertAccountId::convert_account_id(&author))
+				.unwrap_or_default();
+			Coinbase::put(coinbase);
 		}
 	}
 }
@@ -399,4 +483,468 @@ impl<T: Trait> Module<T> {
 		AccountCodes::remove(address);
 		AccountStorages::remove_prefix(address);
 	}
+
+	/// Recover the sender of a transaction that is replay protected for this chain.
+	pub fn transaction_sender(transaction: &Transaction) -> Option<H160> {
+		if transaction.chain_id() != Some(T::ChainId::get()) {
+			return None;
+		}
+		transaction.recover_sender()
+	}
+
+	/// Execute an EVM call operation on behalf of `source` and apply the resulting state changes.
+	///
+	/// The fee for `gas_limit` is withdrawn from `source` upfront and the unused part of it is
+	/// refunded afterwards. Returns the exit reason, the output of the call, the used gas and the
+	/// logs emitted by the call.
+	pub fn execute_call(
+		source: H160,
+		target: H160,
+		input: Vec<u8>,
+		value: U256,
+		gas_limit: u32,
+		gas_price: U256,
+	) -> Result<(ExitReason, Vec<u8>, U256, Vec<Log>), Error<T>> {
+		Self::execute_evm(source, value, gas_limit, gas_price, true, |executor| {
+			executor.transact_call(source, target, value, input, gas_limit as usize)
+		})
+	}
+
+	/// Execute an EVM create operation on behalf of `source` and apply the resulting state
+	/// changes.
+	///
+	/// Fees are handled the same way as in [`Module::execute_call`]. Returns the exit reason, the
+	/// address of the created contract, the used gas and the logs emitted by the creation.
+	pub fn execute_create(
+		source: H160,
+		init: Vec<u8>,
+		value: U256,
+		gas_limit: u32,
+		gas_price: U256,
+	) -> Result<(ExitReason, H160, U256, Vec<Log>), Error<T>> {
+		Self::execute_evm(source, value, gas_limit, gas_price, true, |executor| {
+			let address = executor.create_address(evm::CreateScheme::Legacy { caller: source });
+			let reason = executor.transact_create(source, value, init, gas_limit as usize);
+			(reason, address)
+		})
+	}
+
+	/// Dry-run an EVM call operation on behalf of `source` without applying its state changes.
+	///
+	/// No fee is charged, so `source` doesn't need to hold any balance besides `value`. If
+	/// `gas_price` is `None`, the minimal gas price is used. Returns the exit reason, the output
+	/// of the call and the used gas.
+	pub fn dry_run_call(
+		source: H160,
+		target: H160,
+		input: Vec<u8>,
+		value: U256,
+		gas_limit: U256,
+		gas_price: Option<U256>,
+	) -> Result<(ExitReason, Vec<u8>, U256), Error<T>> {
+		ensure!(gas_limit <= U256::from(u32::max_value()), Error::<T>::GasLimitTooHigh);
+		let gas_limit = gas_limit.low_u32();
+		let gas_price = gas_price.unwrap_or_else(T::FeeCalculator::min_gas_price);
+		Self::execute_evm(source, value, gas_limit, gas_price, false, |executor| {
+			executor.transact_call(source, target, value, input, gas_limit as usize)
+		}).map(|(reason, output, used_gas, _)| (reason, output, used_gas))
+	}
+
+	/// Dry-run an EVM create operation on behalf of `source` without applying its state changes.
+	///
+	/// Same as [`Module::dry_run_call`] otherwise, except that the address of the created
+	/// contract is returned instead of an output.
+	pub fn dry_run_create(
+		source: H160,
+		init: Vec<u8>,
+		value: U256,
+		gas_limit: U256,
+		gas_price: Option<U256>,
+	) -> Result<(ExitReason, H160, U256), Error<T>> {
+		ensure!(gas_limit <= U256::from(u32::max_value()), Error::<T>::GasLimitTooHigh);
+		let gas_limit = gas_limit.low_u32();
+		let gas_price = gas_price.unwrap_or_else(T::FeeCalculator::min_gas_price);
+		Self::execute_evm(source, value, gas_limit, gas_price, false, |executor| {
+			let address = executor.create_address(evm::CreateScheme::Legacy { caller: source });
+			let reason = executor.transact_create(source, value, init, gas_limit as usize);
+			(reason, address)
+		}).map(|(reason, address, used_gas, _)| (reason, address, used_gas))
+	}
+
+	/// Run `f` in a new executor. If `apply` is set, the fees are charged to `source` and the
+	/// state changes are applied; otherwise the execution is a dry-run that leaves no trace.
+	fn execute_evm<F, R>(
+		source: H160,
+		value: U256,
+		gas_limit: u32,
+		gas_price: U256,
+		apply: bool,
+		f: F,
+	) -> Result<(ExitReason, R, U256, Vec<Log>), Error<T>> where
+		F: FnOnce(&mut StackExecutor<Backend<T>>) -> (ExitReason, R),
+	{
+		ensure!(gas_price >= T::FeeCalculator::min_gas_price(), Error::<T>::GasPriceTooLow);
+
+		let vicinity = Vicinity {
+			gas_price,
+			origin: source,
+		};
+
+		let mut backend = Backend::<T>::new(&vicinity);
+		let mut executor = StackExecutor::new_with_precompile(
+			&backend,
+			gas_limit as usize,
+			&backend::GASOMETER_CONFIG,
+			T::Precompiles::execute,
+		);
+
+		let total_fee = if apply {
+			let total_fee = gas_price.checked_mul(U256::from(gas_limit))
+				.ok_or(Error::<T>::FeeOverflow)?;
+			if Accounts::get(&source).balance <
+				value.checked_add(total_fee).ok_or(Error::<T>::PaymentOverflow)?
+			{
+				Err(Error::<T>::BalanceLow)?
+			}
+			executor.withdraw(source, total_fee).map_err(|_| Error::<T>::WithdrawFailed)?;
+			total_fee
+		} else {
+			U256::zero()
+		};
+
+		let (reason, retv) = f(&mut executor);
+
+		let used_gas = U256::from(executor.used_gas());
+		if apply {
+			let actual_fee = executor.fee(gas_price);
+			executor.deposit(source, total_fee.saturating_sub(actual_fee));
+		}
+
+		let (values, logs) = executor.deconstruct();
+		let logs = logs.into_iter().collect::<Vec<_>>();
+		let emitted = logs.iter()
+			.map(|log| Log {
+				address: log.address,
+				topics: log.topics.clone(),
+				data: log.data.clone(),
+			})
+			.collect();
+		if apply {
+			backend.apply(values, logs, true);
+		}
+
+		Ok((reason, retv, used_gas, emitted))
+	}
+
+	/// Map the exit reason of an execution to the result of the dispatchable that triggered it.
+	fn exit_reason_to_result(reason: ExitReason) -> DispatchResult {
+		match reason {
+			ExitReason::Succeed(_) => Ok(()),
+			ExitReason::Error(_) => Err(Error::<T>::ExitReasonFailed.into()),
+			ExitReason::Revert(_) => Err(Error::<T>::ExitReasonRevert.into()),
+			ExitReason::Fatal(_) => Err(Error::<T>::ExitReasonFatal.into()),
+		}
+	}
+}
+
+impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
+	type Call = Call<T>;
+
+	fn validate_unsigned(call: &Self::Call) -> TransactionValidity {
+		if let Call::transact(transaction) = call {
+			let sender = match Self::transaction_sender(transaction) {
+				Some(sender) => sender,
+				None => return InvalidTransaction::Custom(
+					ValidityError::InvalidSignature.into()
+				).into(),
+			};
+
+			if transaction.gas_limit > U256::from(u32::max_value()) {
+				return InvalidTransaction::Custom(ValidityError::GasLimitTooHigh.into()).into();
+			}
+			if transaction.gas_price < T::FeeCalculator::min_gas_price() {
+				return InvalidTransaction::Custom(ValidityError::GasPriceTooLow.into()).into();
+			}
+
+			let account = Accounts::get(&sender);
+			if transaction.nonce < account.nonce {
+				return InvalidTransaction::Stale.into();
+			}
+			if transaction.nonce > account.nonce.saturating_add(U256::from(MAX_NONCE_GAP)) {
+				return InvalidTransaction::Future.into();
+			}
+
+			let fee = transaction.gas_price.saturating_mul(transaction.gas_limit);
+			if account.balance < transaction.value.saturating_add(fee) {
+				return InvalidTransaction::Payment.into();
+			}
+
+			// Transactions with a future nonce wait for the transaction with the previous one.
+			let requires = if transaction.nonce > account.nonce {
+				vec![(sender, transaction.nonce - 1).encode()]
+			} else {
+				vec![]
+			};
+
+			Ok(ValidTransaction {
+				priority: transaction.gas_price.saturated_into(),
+				requires,
+				provides: vec![(sender, transaction.nonce).encode()],
+				longevity: TransactionLongevity::max_value(),
+				propagate: true,
+			})
+		} else {
+			InvalidTransaction::Call.into()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use frame_support::{
+		assert_ok, assert_noop, impl_outer_origin, parameter_types, weights::Weight,
+	};
+	use sp_runtime::{
+		Perbill, testing::Header, traits::{BlakeTwo256, IdentityLookup, OnInitialize},
+	};
+	use sp_io::hashing::keccak_256;
+
+	impl_outer_origin! {
+		pub enum Origin for Test where system = frame_system {}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+	impl frame_system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Call = ();
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+		type ModuleToIndex = ();
+	}
+	parameter_types! {
+		pub const ChainId: u64 = CHAIN_ID;
+	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 1;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+	}
+	impl pallet_balances::Trait for Test {
+		type Balance = u64;
+		type OnFreeBalanceZero = ();
+		type OnReapAccount = System;
+		type OnNewAccount = ();
+		type Event = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+	}
+	parameter_types! {
+		pub const MinimumPeriod: u64 = 1;
+	}
+	impl pallet_timestamp::Trait for Test {
+		type Moment = u64;
+		type OnTimestampSet = ();
+		type MinimumPeriod = MinimumPeriod;
+	}
+	pub struct LowU64ConvertAccountId;
+	impl ConvertAccountId<u64> for LowU64ConvertAccountId {
+		fn convert_account_id(account_id: &u64) -> H160 {
+			H160::from_low_u64_be(*account_id)
+		}
+	}
+	impl Trait for Test {
+		type FeeCalculator = ();
+		type ConvertAccountId = LowU64ConvertAccountId;
+		type Currency = Balances;
+		type Event = ();
+		type Precompiles = ();
+		type FindAuthor = ();
+		type GasPayment = ();
+		type ChainId = ChainId;
+	}
+	type System = frame_system::Module<Test>;
+	type Balances = pallet_balances::Module<Test>;
+	type Evm = Module<Test>;
+
+	/// The chain id of the test runtime.
+	const CHAIN_ID: u64 = 42;
+
+	fn alice() -> secp256k1::SecretKey {
+		secp256k1::SecretKey::parse(&keccak_256(b"Alice")).unwrap()
+	}
+
+	fn address(secret: &secp256k1::SecretKey) -> H160 {
+		let public = secp256k1::PublicKey::from_secret_key(secret);
+		H160::from_slice(&keccak_256(&public.serialize()[1..65])[12..])
+	}
+
+	/// A transfer of `value` to `0x3535…35`, signed by `secret` for the test chain.
+	fn transfer(secret: &secp256k1::SecretKey, nonce: u64, value: u64) -> Transaction {
+		let mut transaction = Transaction {
+			nonce: U256::from(nonce),
+			gas_price: U256::from(1),
+			gas_limit: U256::from(30_000),
+			action: TransactionAction::Call(H160::repeat_byte(0x35)),
+			value: U256::from(value),
+			input: Vec::new(),
+			signature: TransactionSignature {
+				v: CHAIN_ID * 2 + 35,
+				r: H256::zero(),
+				s: H256::zero(),
+			},
+		};
+		let message = secp256k1::Message::parse(transaction.message_hash().as_fixed_bytes());
+		let (signature, recovery_id) = secp256k1::sign(&message, secret);
+		let signature = signature.serialize();
+		transaction.signature = TransactionSignature {
+			v: CHAIN_ID * 2 + 35 + recovery_id.serialize() as u64,
+			r: H256::from_slice(&signature[0..32]),
+			s: H256::from_slice(&signature[32..64]),
+		};
+		transaction
+	}
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		let mut ext = sp_io::TestExternalities::new(t);
+		ext.execute_with(|| {
+			System::set_block_number(1);
+			Balances::make_free_balance_be(&Evm::account_id(), 1_000_000);
+			Accounts::insert(address(&alice()), Account {
+				nonce: U256::zero(),
+				balance: U256::from(1_000_000),
+			});
+		});
+		ext
+	}
+
+	#[test]
+	fn transact_should_work() {
+		new_test_ext().execute_with(|| {
+			let sender = address(&alice());
+			let transaction = transfer(&alice(), 0, 100);
+			assert_eq!(Evm::transaction_sender(&transaction), Some(sender));
+
+			assert_ok!(Evm::transact(Origin::NONE, transaction.clone()));
+
+			// The nonce is bumped and the used gas is paid for at the gas price.
+			assert_eq!(Evm::accounts(sender), Account {
+				nonce: U256::from(1),
+				balance: U256::from(1_000_000 - 100 - 21_000),
+			});
+			assert_eq!(Evm::accounts(H160::repeat_byte(0x35)).balance, U256::from(100));
+			// The fee is slashed from the module account.
+			assert_eq!(Balances::free_balance(&Evm::account_id()), 1_000_000 - 21_000);
+
+			assert_eq!(Evm::transaction_count(), 1);
+			assert_eq!(Evm::receipt(0), Some(Receipt {
+				transaction_hash: transaction.hash(),
+				from: sender,
+				contract_address: None,
+				status: true,
+				used_gas: U256::from(21_000),
+				cumulative_gas_used: U256::from(21_000),
+				logs_bloom: Bloom::default(),
+				logs: Vec::new(),
+			}));
+		});
+	}
+
+	#[test]
+	fn receipts_accumulate_gas_and_are_cleared_on_the_next_block() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Evm::transact(Origin::NONE, transfer(&alice(), 0, 100)));
+			assert_ok!(Evm::transact(Origin::NONE, transfer(&alice(), 1, 100)));
+
+			assert_eq!(Evm::transaction_count(), 2);
+			let receipt = Evm::receipt(1).unwrap();
+			assert_eq!(receipt.used_gas, U256::from(21_000));
+			assert_eq!(receipt.cumulative_gas_used, U256::from(42_000));
+
+			<Evm as OnInitialize<u64>>::on_initialize(2);
+			assert_eq!(Evm::transaction_count(), 0);
+			assert_eq!(Evm::receipt(0), None);
+			assert_eq!(Evm::receipt(1), None);
+		});
+	}
+
+	#[test]
+	fn transact_checks_nonce_and_signature() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				Evm::transact(Origin::NONE, transfer(&alice(), 1, 100)),
+				Error::<Test>::InvalidNonce,
+			);
+
+			let mut transaction = transfer(&alice(), 0, 100);
+			transaction.signature.v = 37;
+			assert_noop!(
+				Evm::transact(Origin::NONE, transaction),
+				Error::<Test>::InvalidSignature,
+			);
+		});
+	}
+
+	#[test]
+	fn validate_unsigned_bounds_the_nonce() {
+		use frame_support::unsigned::ValidateUnsigned;
+
+		new_test_ext().execute_with(|| {
+			let validity = |nonce| {
+				Evm::validate_unsigned(&Call::transact(transfer(&alice(), nonce, 1)))
+			};
+
+			assert_eq!(validity(0).unwrap().requires, Vec::<Vec<u8>>::new());
+			assert_eq!(
+				validity(MAX_NONCE_GAP).unwrap().requires,
+				vec![(address(&alice()), U256::from(MAX_NONCE_GAP - 1)).encode()],
+			);
+			assert_eq!(validity(MAX_NONCE_GAP + 1), InvalidTransaction::Future.into());
+
+			Accounts::mutate(address(&alice()), |account| account.nonce = U256::from(2));
+			assert_eq!(validity(1), InvalidTransaction::Stale.into());
+			assert!(validity(MAX_NONCE_GAP + 2).is_ok());
+		});
+	}
+
+	#[test]
+	fn transactions_must_be_signed_for_the_chain_id() {
+		use frame_support::unsigned::ValidateUnsigned;
+
+		new_test_ext().execute_with(|| {
+			let mut transaction = transfer(&alice(), 0, 1);
+			assert_eq!(Evm::transaction_sender(&transaction), Some(address(&alice())));
+
+			// Re-sign the transaction for another chain.
+			transaction.signature.v += 2;
+			assert_eq!(Evm::transaction_sender(&transaction), None);
+			assert_eq!(
+				Evm::validate_unsigned(&Call::transact(transaction)),
+				InvalidTransaction::Custom(ValidityError::InvalidSignature.into()).into(),
+			);
+		});
+	}
 }
@@ -0,0 +1,633 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The standard precompiles of Ethereum.
+//!
+//! The precompiles at the addresses `0x01` to `0x05` are available with the gas costs of the
+//! Byzantium hard fork.
+
+use sp_std::{cmp::min, prelude::*};
+use sp_core::{H160, U256};
+use sp_io::{hashing::{keccak_256, sha2_256}, crypto::secp256k1_ecdsa_recover};
+use evm::{ExitSucceed, ExitError};
+use ripemd160::Digest;
+use crate::Precompiles;
+
+/// The maximal length of the base, the exponent and the modulus of `modexp` without a gas limit.
+const MAX_UNMETERED_MODEXP_LEN: usize = 1024;
+
+/// The result of the execution of a precompile.
+type PrecompileResult = core::result::Result<(ExitSucceed, Vec<u8>, usize), ExitError>;
+
+/// The standard precompiles of Ethereum:
+///
+/// - `0x01`: `ecrecover`, the address of the signer of a message hash.
+/// - `0x02`: `sha256`, the SHA2-256 hash of the input.
+/// - `0x03`: `ripemd160`, the RIPEMD-160 hash of the input.
+/// - `0x04`: `identity`, which returns the input.
+/// - `0x05`: `modexp`, the modular exponentiation of big integers as defined by EIP-198.
+pub struct StandardPrecompiles;
+
+impl Precompiles for StandardPrecompiles {
+	fn execute(
+		address: H160,
+		input: &[u8],
+		target_gas: Option<usize>
+	) -> Option<PrecompileResult> {
+		let address = address.as_bytes();
+		if address[..19] != [0u8; 19] {
+			return None;
+		}
+
+		match address[19] {
+			1 => Some(ecrecover(input, target_gas)),
+			2 => Some(sha256(input, target_gas)),
+			3 => Some(ripemd160(input, target_gas)),
+			4 => Some(identity(input, target_gas)),
+			5 => Some(modexp(input, target_gas)),
+			_ => None,
+		}
+	}
+}
+
+/// Fail with `OutOfGas` if `cost` exceeds `target_gas`.
+fn ensure_gas(cost: usize, target_gas: Option<usize>) -> Result<(), ExitError> {
+	match target_gas {
+		Some(target_gas) if cost > target_gas => Err(ExitError::OutOfGas),
+		_ => Ok(()),
+	}
+}
+
+/// The cost of a precompile which charges `base` plus `word` for every 32-byte word of input.
+fn linear_cost(input: &[u8], base: usize, word: usize) -> usize {
+	let words = input.len() / 32 + if input.len() % 32 == 0 { 0 } else { 1 };
+	base.saturating_add(word.saturating_mul(words))
+}
+
+/// Read `len` bytes of the input at `offset`, padded with zeros beyond the end of the input.
+fn read_input(input: &[u8], offset: usize, len: usize) -> Vec<u8> {
+	let mut output = vec![0u8; len];
+	if offset < input.len() {
+		let end = min(input.len(), offset.saturating_add(len));
+		output[..end - offset].copy_from_slice(&input[offset..end]);
+	}
+	output
+}
+
+/// Recover the address of the signer from a message hash and a signature.
+///
+/// The input consists of the hash, `v`, `r` and `s`, each 32 bytes. The address is returned
+/// left-padded to 32 bytes, or nothing is returned if the signature is invalid.
+fn ecrecover(input: &[u8], target_gas: Option<usize>) -> PrecompileResult {
+	let cost = 3000;
+	ensure_gas(cost, target_gas)?;
+
+	let input = read_input(input, 0, 128);
+	let mut message = [0u8; 32];
+	message.copy_from_slice(&input[0..32]);
+	let v = &input[32..64];
+	if v[..31] != [0u8; 31] || (v[31] != 27 && v[31] != 28) {
+		return Ok((ExitSucceed::Returned, Vec::new(), cost));
+	}
+
+	let mut signature = [0u8; 65];
+	signature[0..64].copy_from_slice(&input[64..128]);
+	signature[64] = v[31] - 27;
+
+	let output = match secp256k1_ecdsa_recover(&signature, &message) {
+		Ok(public) => {
+			let mut output = vec![0u8; 32];
+			output[12..].copy_from_slice(&keccak_256(&public)[12..]);
+			output
+		},
+		Err(_) => Vec::new(),
+	};
+	Ok((ExitSucceed::Returned, output, cost))
+}
+
+/// The SHA2-256 hash of the input.
+fn sha256(input: &[u8], target_gas: Option<usize>) -> PrecompileResult {
+	let cost = linear_cost(input, 60, 12);
+	ensure_gas(cost, target_gas)?;
+
+	Ok((ExitSucceed::Returned, sha2_256(input).to_vec(), cost))
+}
+
+/// The RIPEMD-160 hash of the input, left-padded to 32 bytes.
+fn ripemd160(input: &[u8], target_gas: Option<usize>) -> PrecompileResult {
+	let cost = linear_cost(input, 600, 120);
+	ensure_gas(cost, target_gas)?;
+
+	let mut hasher = ripemd160::Ripemd160::new();
+	hasher.input(input);
+	let mut output = vec![0u8; 32];
+	output[12..].copy_from_slice(&hasher.result());
+	Ok((ExitSucceed::Returned, output, cost))
+}
+
+/// The input itself.
+fn identity(input: &[u8], target_gas: Option<usize>) -> PrecompileResult {
+	let cost = linear_cost(input, 15, 3);
+	ensure_gas(cost, target_gas)?;
+
+	Ok((ExitSucceed::Returned, input.to_vec(), cost))
+}
+
+/// The modular exponentiation `base ^ exponent % modulus` as defined by EIP-198.
+///
+/// The input consists of the lengths of the base, the exponent and the modulus, each 32 bytes,
+/// followed by the big-endian base, exponent and modulus. An input that is shorter than the
+/// lengths require is padded with zeros. The result is returned with the length of the modulus.
+///
+/// Without a gas limit, lengths above `MAX_UNMETERED_MODEXP_LEN` are rejected.
+fn modexp(input: &[u8], target_gas: Option<usize>) -> PrecompileResult {
+	let base_len = U256::from_big_endian(&read_input(input, 0, 32));
+	let exponent_len = U256::from_big_endian(&read_input(input, 32, 32));
+	let modulus_len = U256::from_big_endian(&read_input(input, 64, 32));
+
+	// The first 32 bytes of the exponent determine the cost together with the lengths.
+	let exponent_start = U256::from(96).saturating_add(base_len);
+	let exponent_head = if exponent_start >= U256::from(input.len()) {
+		U256::zero()
+	} else {
+		let head_len = min(exponent_len, U256::from(32)).as_usize();
+		U256::from_big_endian(&read_input(input, exponent_start.as_usize(), head_len))
+	};
+
+	let cost = modexp_cost(base_len, exponent_len, modulus_len, exponent_head);
+	if cost > U256::from(usize::max_value()) {
+		return Err(ExitError::OutOfGas);
+	}
+	let cost = cost.as_usize();
+	ensure_gas(cost, target_gas)?;
+
+	if modulus_len.is_zero() {
+		return Ok((ExitSucceed::Returned, Vec::new(), cost));
+	}
+
+	// Without a gas limit the cost doesn't bound the lengths, so they are capped before they are
+	// allocated.
+	let max_len = match target_gas {
+		Some(_) => U256::from(u32::max_value()),
+		None => U256::from(MAX_UNMETERED_MODEXP_LEN),
+	};
+	if base_len > max_len || exponent_len > max_len || modulus_len > max_len {
+		return Err(ExitError::InvalidRange);
+	}
+	let (base_len, exponent_len, modulus_len) =
+		(base_len.as_usize(), exponent_len.as_usize(), modulus_len.as_usize());
+	let exponent_start = 96usize.saturating_add(base_len);
+	let modulus_start = exponent_start.saturating_add(exponent_len);
+	let base = read_input(input, 96, base_len);
+	let exponent = read_input(input, exponent_start, exponent_len);
+	let modulus = read_input(input, modulus_start, modulus_len);
+
+	Ok((ExitSucceed::Returned, modpow(&base, &exponent, &modulus, modulus_len), cost))
+}
+
+/// The cost of `modexp` as defined by EIP-198.
+fn modexp_cost(
+	base_len: U256,
+	exponent_len: U256,
+	modulus_len: U256,
+	exponent_head: U256,
+) -> U256 {
+	let x = base_len.max(modulus_len);
+	let complexity = if x <= U256::from(64) {
+		x * x
+	} else if x <= U256::from(1024) {
+		x * x / 4 + x * 96 - 3072
+	} else {
+		(x.saturating_mul(x) / 16).saturating_add(x.saturating_mul(U256::from(480))) - 199680
+	};
+
+	let head_bits = if exponent_head.is_zero() { 0 } else { exponent_head.bits() - 1 };
+	let adjusted_exponent_len = if exponent_len <= U256::from(32) {
+		U256::from(head_bits)
+	} else {
+		(exponent_len - 32).saturating_mul(U256::from(8)).saturating_add(U256::from(head_bits))
+	};
+
+	complexity.saturating_mul(adjusted_exponent_len.max(U256::one())) / 20
+}
+
+/// Convert big-endian bytes into little-endian 32-bit limbs without leading zero limbs.
+fn from_be_bytes(bytes: &[u8]) -> Vec<u32> {
+	let mut limbs = bytes.rchunks(4)
+		.map(|chunk| chunk.iter().fold(0u32, |limb, byte| limb << 8 | *byte as u32))
+		.collect::<Vec<_>>();
+	normalize(&mut limbs);
+	limbs
+}
+
+/// Convert little-endian 32-bit limbs into `len` big-endian bytes.
+///
+/// The value must fit into `len` bytes.
+fn to_be_bytes(limbs: &[u32], len: usize) -> Vec<u8> {
+	let mut bytes = vec![0u8; len];
+	for (i, limb) in limbs.iter().enumerate() {
+		for j in 0..4 {
+			let byte = (limb >> (8 * j)) as u8;
+			if byte != 0 {
+				bytes[len - 1 - 4 * i - j] = byte;
+			}
+		}
+	}
+	bytes
+}
+
+/// Remove the leading zero limbs.
+fn normalize(limbs: &mut Vec<u32>) {
+	while limbs.last() == Some(&0) {
+		limbs.pop();
+	}
+}
+
+/// Multiply two numbers.
+fn mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+	let mut product = vec![0u32; a.len() + b.len()];
+	for (i, x) in a.iter().enumerate() {
+		let mut carry = 0u64;
+		for (j, y) in b.iter().enumerate() {
+			let t = *x as u64 * *y as u64 + product[i + j] as u64 + carry;
+			product[i + j] = t as u32;
+			carry = t >> 32;
+		}
+		product[i + b.len()] = carry as u32;
+	}
+	normalize(&mut product);
+	product
+}
+
+/// Compute the remainder of the division of `a` by the non-zero, normalized `m`.
+///
+/// This is algorithm D of Knuth, The Art of Computer Programming, Vol. 2, 4.3.1.
+fn rem(a: &[u32], m: &[u32]) -> Vec<u32> {
+	let n = m.len();
+	if a.len() < n || (a.len() == n && a.iter().rev().lt(m.iter().rev())) {
+		return a.to_vec();
+	}
+	if n == 1 {
+		let divisor = m[0] as u64;
+		let remainder = a.iter().rev()
+			.fold(0u64, |remainder, limb| (remainder << 32 | *limb as u64) % divisor);
+		let mut remainder = vec![remainder as u32];
+		normalize(&mut remainder);
+		return remainder;
+	}
+
+	// Normalize so that the highest bit of the divisor is set.
+	let shift = m[n - 1].leading_zeros();
+	let v = shl(m, shift, n);
+	let mut u = shl(a, shift, a.len() + 1);
+
+	for j in (0..u.len() - n).rev() {
+		let numerator = (u[j + n] as u64) << 32 | u[j + n - 1] as u64;
+		let mut qhat = numerator / v[n - 1] as u64;
+		let mut rhat = numerator % v[n - 1] as u64;
+		while qhat >> 32 != 0 || qhat * v[n - 2] as u64 > (rhat << 32 | u[j + n - 2] as u64) {
+			qhat -= 1;
+			rhat += v[n - 1] as u64;
+			if rhat >> 32 != 0 {
+				break;
+			}
+		}
+
+		// Subtract `qhat * v` from the current part of `u`.
+		let mut borrow = 0i64;
+		let mut carry = 0u64;
+		for i in 0..n {
+			let p = qhat * v[i] as u64 + carry;
+			carry = p >> 32;
+			let t = u[i + j] as i64 - borrow - (p & 0xffff_ffff) as i64;
+			u[i + j] = t as u32;
+			borrow = if t < 0 { 1 } else { 0 };
+		}
+		let t = u[j + n] as i64 - borrow - carry as i64;
+		u[j + n] = t as u32;
+
+		// `qhat` was one too large, so add `v` back.
+		if t < 0 {
+			let mut carry = 0u64;
+			for i in 0..n {
+				let t = u[i + j] as u64 + v[i] as u64 + carry;
+				u[i + j] = t as u32;
+				carry = t >> 32;
+			}
+			u[j + n] = u[j + n].wrapping_add(carry as u32);
+		}
+	}
+
+	let mut remainder = (0..n)
+		.map(|i| if shift == 0 {
+			u[i]
+		} else {
+			u[i] >> shift | u[i + 1] << (32 - shift)
+		})
+		.collect::<Vec<_>>();
+	normalize(&mut remainder);
+	remainder
+}
+
+/// Shift `a` left by `shift` bits, which must be less than 32, into `len` limbs.
+fn shl(a: &[u32], shift: u32, len: usize) -> Vec<u32> {
+	let mut result = vec![0u32; len];
+	for (i, limb) in a.iter().enumerate() {
+		result[i] |= limb << shift;
+		if shift != 0 && i + 1 < len {
+			result[i + 1] = limb >> (32 - shift);
+		}
+	}
+	result
+}
+
+/// Compute `base ^ exponent % modulus` of big-endian numbers, returned as `len` big-endian bytes.
+fn modpow(base: &[u8], exponent: &[u8], modulus: &[u8], len: usize) -> Vec<u8> {
+	let modulus = from_be_bytes(modulus);
+	if modulus.is_empty() {
+		return vec![0u8; len];
+	}
+
+	let base = rem(&from_be_bytes(base), &modulus);
+	let mut result = rem(&[1], &modulus);
+	for byte in exponent {
+		for bit in (0..8).rev() {
+			result = rem(&mul(&result, &result), &modulus);
+			if byte >> bit & 1 == 1 {
+				result = rem(&mul(&result, &base), &modulus);
+			}
+		}
+	}
+	to_be_bytes(&result, len)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hex_literal::hex;
+
+	fn execute(address: u8, input: &[u8], target_gas: Option<usize>) -> PrecompileResult {
+		let mut bytes = [0u8; 20];
+		bytes[19] = address;
+		StandardPrecompiles::execute(H160::from(bytes), input, target_gas)
+			.expect("address is a precompile")
+	}
+
+	#[test]
+	fn only_standard_addresses_are_precompiles() {
+		assert!(StandardPrecompiles::execute(H160::zero(), &[], None).is_none());
+		assert!(StandardPrecompiles::execute(H160::repeat_byte(1), &[], None).is_none());
+		let mut bytes = [0u8; 20];
+		bytes[19] = 6;
+		assert!(StandardPrecompiles::execute(H160::from(bytes), &[], None).is_none());
+	}
+
+	#[test]
+	fn ecrecover_works() {
+		let input = hex!("
+			18c547e4f7b0f325ad1e56f57e26c745b09a3e503d86e00e5255ff7f715d3d1c
+			000000000000000000000000000000000000000000000000000000000000001c
+			73b1693892219d736caba55bdb67216e485557ea6b6af75f37096c9aa6a5a75f
+			eeb940b1d03b21e36b0e47e79769f095fe2ab855bd91e3a38756b7d75a9c4549
+		");
+		assert_eq!(
+			execute(1, &input, None),
+			Ok((
+				ExitSucceed::Returned,
+				hex!("000000000000000000000000a94f5374fce5edbc8e2a8697c15331677e6ebf0b").to_vec(),
+				3000,
+			)),
+		);
+
+		// An invalid `v` returns nothing, but still costs gas.
+		let mut invalid = input;
+		invalid[63] = 29;
+		assert_eq!(execute(1, &invalid, None), Ok((ExitSucceed::Returned, Vec::new(), 3000)));
+
+		assert_eq!(execute(1, &input, Some(2999)), Err(ExitError::OutOfGas));
+	}
+
+	#[test]
+	fn sha256_works() {
+		assert_eq!(
+			execute(2, &[], None),
+			Ok((
+				ExitSucceed::Returned,
+				hex!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855").to_vec(),
+				60,
+			)),
+		);
+		assert_eq!(
+			execute(2, b"abc", None),
+			Ok((
+				ExitSucceed::Returned,
+				hex!("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad").to_vec(),
+				72,
+			)),
+		);
+	}
+
+	#[test]
+	fn ripemd160_works() {
+		assert_eq!(
+			execute(3, &[], None),
+			Ok((
+				ExitSucceed::Returned,
+				hex!("0000000000000000000000009c1185a5c5e9fc54612808977ee8f548b2258d31").to_vec(),
+				600,
+			)),
+		);
+		assert_eq!(
+			execute(3, b"abc", None),
+			Ok((
+				ExitSucceed::Returned,
+				hex!("0000000000000000000000008eb208f7e05d987a9b044a8e98c6b087f15a0bfc").to_vec(),
+				720,
+			)),
+		);
+	}
+
+	#[test]
+	fn identity_works() {
+		let input = [7u8; 33];
+		assert_eq!(execute(4, &input, None), Ok((ExitSucceed::Returned, input.to_vec(), 21)));
+		assert_eq!(execute(4, &input, Some(20)), Err(ExitError::OutOfGas));
+	}
+
+	#[test]
+	fn modexp_works() {
+		// The examples of EIP-198.
+		let fermat = hex!("
+			0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000020
+			0000000000000000000000000000000000000000000000000000000000000020
+			03
+			fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e
+			fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f
+		");
+		assert_eq!(
+			execute(5, &fermat, None),
+			Ok((
+				ExitSucceed::Returned,
+				hex!("0000000000000000000000000000000000000000000000000000000000000001").to_vec(),
+				13056,
+			)),
+		);
+
+		let zero_base = hex!("
+			0000000000000000000000000000000000000000000000000000000000000000
+			0000000000000000000000000000000000000000000000000000000000000020
+			0000000000000000000000000000000000000000000000000000000000000020
+			fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e
+			fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f
+		");
+		assert_eq!(
+			execute(5, &zero_base, None),
+			Ok((ExitSucceed::Returned, vec![0u8; 32], 13056)),
+		);
+
+		// Huge lengths run out of gas instead of being read.
+		let huge_lengths = hex!("
+			0000000000000000000000000000000000000000000000000000000000000000
+			ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff
+			ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff
+		");
+		assert_eq!(execute(5, &huge_lengths, Some(10_000_000)), Err(ExitError::OutOfGas));
+
+		// Without a gas limit, lengths are capped before anything is allocated.
+		let unmetered_modulus = hex!("
+			0000000000000000000000000000000000000000000000000000000000000000
+			0000000000000000000000000000000000000000000000000000000000000000
+			0000000000000000000000000000000000000000000000000000000000000401
+		");
+		assert_eq!(execute(5, &unmetered_modulus, None), Err(ExitError::InvalidRange));
+		assert_eq!(
+			execute(5, &unmetered_modulus, Some(100_000)),
+			Ok((ExitSucceed::Returned, vec![0u8; 1025], 17899)),
+		);
+
+		// An input that ends before the modulus is padded with zeros.
+		let padded_modulus = hex!("
+			0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000002
+			03
+			02
+			01
+		");
+		assert_eq!(
+			execute(5, &padded_modulus, None),
+			Ok((ExitSucceed::Returned, vec![0, 9], 0)),
+		);
+		let padded_fermat = &fermat[..96 + 1 + 32];
+		assert_eq!(
+			execute(5, padded_fermat, None),
+			Ok((ExitSucceed::Returned, vec![0u8; 32], 13056)),
+		);
+
+		// A zero modulus returns zero with the length of the modulus.
+		let zero_modulus = hex!("
+			0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000002
+			05
+			03
+			0000
+		");
+		assert_eq!(execute(5, &zero_modulus, None), Ok((ExitSucceed::Returned, vec![0, 0], 0)));
+	}
+
+	#[test]
+	fn modexp_matches_consensus_tests() {
+		// The `nagydani-1` vectors of the Ethereum consensus tests.
+		let input = |exponent: &[u8]| {
+			let mut input = vec![0u8; 96];
+			input[31] = 64;
+			input[63] = exponent.len() as u8;
+			input[95] = 64;
+			input.extend_from_slice(&hex!("
+				e09ad9675465c53a109fac66a445c91b292d2bb2c5268addb30cd82f80fcb003
+				3ff97c80a5fc6f39193ae969c6ede6710a6b7ac27078a06d90ef1c72e5c85fb5
+			"));
+			input.extend_from_slice(exponent);
+			input.extend_from_slice(&hex!("
+				fc9e1f6beb81516545975218075ec2af118cd8798df6e08a147c60fd6095ac2b
+				b02c2908cf4dd7c81f11c289e4bce98f3553768f392a80ce22bf5c4f4a248c6b
+			"));
+			input
+		};
+
+		assert_eq!(
+			execute(5, &input(&[0x02]), None),
+			Ok((
+				ExitSucceed::Returned,
+				hex!("
+					60008f1614cc01dcfb6bfb09c625cf90b47d4468db81b5f8b7a39d42f332eab9
+					b2da8f2d95311648a8f243f4bb13cfb3d8f7f2a3c014122ebb3ed41b02783adc
+				").to_vec(),
+				204,
+			)),
+		);
+		assert_eq!(
+			execute(5, &input(&[0x03]), None),
+			Ok((
+				ExitSucceed::Returned,
+				hex!("
+					4834a46ba565db27903b1c720c9d593e84e4cbd6ad2e64b31885d944f68cd801
+					f92225a8961c952ddf2797fa4701b330c85c4b363798100b921a1a22a46a7fec
+				").to_vec(),
+				204,
+			)),
+		);
+		assert_eq!(
+			execute(5, &input(&[0x01, 0x00, 0x01]), None),
+			Ok((
+				ExitSucceed::Returned,
+				hex!("
+					c36d804180c35d4426b57b50c5bfcca5c01856d104564cd513b461d3c8b84091
+					28a5573e416d0ebe38f5f736766d9dc27143e4da981dfa4d67f7dc474cbee6d2
+				").to_vec(),
+				3276,
+			)),
+		);
+		assert_eq!(execute(5, &input(&[0x01, 0x00, 0x01]), Some(3275)), Err(ExitError::OutOfGas));
+	}
+
+	#[test]
+	fn modpow_matches_small_numbers() {
+		for base in 0..20u64 {
+			for exponent in 0..10u32 {
+				for modulus in 1..40u64 {
+					let expected = (0..exponent).fold(1 % modulus, |acc, _| acc * base % modulus);
+					let result = modpow(
+						&base.to_be_bytes(),
+						&exponent.to_be_bytes(),
+						&modulus.to_be_bytes(),
+						8,
+					);
+					assert_eq!(result, expected.to_be_bytes().to_vec());
+				}
+			}
+		}
+
+		// 2 ^ 200 % (2 ^ 127 - 1) = 2 ^ (200 - 127) = 2 ^ 73, which exercises multi-limb division.
+		let mut modulus = vec![0xffu8; 16];
+		modulus[0] = 0x7f;
+		let mut expected = vec![0u8; 16];
+		expected[16 - 10] = 0x02;
+		assert_eq!(modpow(&[2], &[200], &modulus, 16), expected);
+	}
+}
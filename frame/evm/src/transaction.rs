@@ -0,0 +1,344 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Signed Ethereum transactions and their receipts.
+
+use sp_std::{fmt, vec::Vec};
+use codec::{Encode, Decode};
+use sp_core::{U256, H256, H160};
+use sp_io::{hashing::keccak_256, crypto::secp256k1_ecdsa_recover};
+use sp_runtime::RuntimeDebug;
+use rlp::{Rlp, RlpStream, DecoderError};
+use hex_literal::hex;
+use crate::backend::Log;
+
+/// Half of the order of the secp256k1 curve. Any signature has a second valid form whose `s`
+/// value is larger than this, which is rejected as defined by EIP-2.
+const SECP256K1N_HALF: H256 = H256(
+	hex!("7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0")
+);
+
+/// The action of an Ethereum transaction.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum TransactionAction {
+	/// Call the given address.
+	Call(H160),
+	/// Create a new contract.
+	Create,
+}
+
+/// The signature of an Ethereum transaction.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct TransactionSignature {
+	/// The recovery id, which includes the chain id for EIP-155 transactions.
+	pub v: u64,
+	/// The `r` value of the signature.
+	pub r: H256,
+	/// The `s` value of the signature.
+	pub s: H256,
+}
+
+/// A signed Ethereum transaction.
+///
+/// The transaction is SCALE encoded when submitted as an extrinsic and RLP encoded, as in
+/// Ethereum, when signed and hashed.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Transaction {
+	/// The nonce of the sender.
+	pub nonce: U256,
+	/// The price paid for each unit of gas.
+	pub gas_price: U256,
+	/// The maximal amount of gas the transaction may use.
+	pub gas_limit: U256,
+	/// Whether the transaction is a call or creates a contract.
+	pub action: TransactionAction,
+	/// The value transferred to the callee or to the created contract.
+	pub value: U256,
+	/// The call data or the init code of the created contract.
+	pub input: Vec<u8>,
+	/// The signature of the sender.
+	pub signature: TransactionSignature,
+}
+
+impl Transaction {
+	/// The hash of the transaction, which is the Keccak-256 hash of its RLP encoding.
+	pub fn hash(&self) -> H256 {
+		H256::from(keccak_256(&rlp::encode(self)))
+	}
+
+	/// The chain id the transaction is signed for, or `None` if it is not replay protected as
+	/// defined by EIP-155.
+	pub fn chain_id(&self) -> Option<u64> {
+		if self.signature.v >= 35 {
+			Some((self.signature.v - 35) / 2)
+		} else {
+			None
+		}
+	}
+
+	/// The hash that is signed by the sender.
+	pub fn message_hash(&self) -> H256 {
+		let chain_id = self.chain_id();
+		let mut stream = RlpStream::new_list(if chain_id.is_some() { 9 } else { 6 });
+		self.rlp_append_unsigned(&mut stream);
+		if let Some(chain_id) = chain_id {
+			stream.append(&chain_id);
+			stream.append(&0u8);
+			stream.append(&0u8);
+		}
+		H256::from(keccak_256(&stream.out()))
+	}
+
+	/// Recover the address of the sender from the signature.
+	///
+	/// Returns `None` if the signature is invalid or its `s` value is in the upper half of the
+	/// curve order.
+	pub fn recover_sender(&self) -> Option<H160> {
+		if self.signature.s > SECP256K1N_HALF {
+			return None;
+		}
+		let recovery_id = match self.chain_id() {
+			Some(chain_id) => self.signature.v - chain_id * 2 - 35,
+			None => self.signature.v.checked_sub(27)?,
+		};
+		if recovery_id > 1 {
+			return None;
+		}
+
+		let mut signature = [0u8; 65];
+		signature[0..32].copy_from_slice(self.signature.r.as_bytes());
+		signature[32..64].copy_from_slice(self.signature.s.as_bytes());
+		signature[64] = recovery_id as u8;
+
+		let public = secp256k1_ecdsa_recover(&signature, self.message_hash().as_fixed_bytes())
+			.ok()?;
+		Some(H160::from_slice(&keccak_256(&public)[12..]))
+	}
+
+	/// Append the fields of the transaction except for the signature.
+	fn rlp_append_unsigned(&self, stream: &mut RlpStream) {
+		stream.append(&self.nonce);
+		stream.append(&self.gas_price);
+		stream.append(&self.gas_limit);
+		match self.action {
+			TransactionAction::Call(ref address) => stream.append(address),
+			TransactionAction::Create => stream.append_empty_data(),
+		};
+		stream.append(&self.value);
+		stream.append(&self.input);
+	}
+}
+
+impl rlp::Encodable for Transaction {
+	fn rlp_append(&self, stream: &mut RlpStream) {
+		stream.begin_list(9);
+		self.rlp_append_unsigned(stream);
+		stream.append(&self.signature.v);
+		stream.append(&U256::from_big_endian(self.signature.r.as_bytes()));
+		stream.append(&U256::from_big_endian(self.signature.s.as_bytes()));
+	}
+}
+
+impl rlp::Decodable for Transaction {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 9 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+
+		let action = {
+			let to = rlp.at(3)?;
+			if to.is_empty() {
+				TransactionAction::Create
+			} else {
+				TransactionAction::Call(to.as_val()?)
+			}
+		};
+		let to_h256 = |value: U256| {
+			let mut bytes = [0u8; 32];
+			value.to_big_endian(&mut bytes);
+			H256::from(bytes)
+		};
+
+		Ok(Transaction {
+			nonce: rlp.val_at(0)?,
+			gas_price: rlp.val_at(1)?,
+			gas_limit: rlp.val_at(2)?,
+			action,
+			value: rlp.val_at(4)?,
+			input: rlp.val_at(5)?,
+			signature: TransactionSignature {
+				v: rlp.val_at(6)?,
+				r: to_h256(rlp.val_at(7)?),
+				s: to_h256(rlp.val_at(8)?),
+			},
+		})
+	}
+}
+
+/// A 2048-bit Bloom filter over the addresses and topics of logs, as used in Ethereum receipts.
+#[derive(Clone, Encode, Decode)]
+pub struct Bloom(pub [u8; 256]);
+
+impl Bloom {
+	/// Create a Bloom filter that contains the addresses and topics of the given logs.
+	pub fn from_logs(logs: &[Log]) -> Self {
+		let mut bloom = Bloom::default();
+		for log in logs {
+			bloom.accrue(log.address.as_bytes());
+			for topic in &log.topics {
+				bloom.accrue(topic.as_bytes());
+			}
+		}
+		bloom
+	}
+
+	/// Add the given input to the filter.
+	///
+	/// Three bits of the filter are set, each selected by a pair of bytes of the Keccak-256 hash
+	/// of the input.
+	pub fn accrue(&mut self, input: &[u8]) {
+		let hash = keccak_256(input);
+		for i in 0..3 {
+			let bit = ((hash[2 * i] as usize) << 8 | hash[2 * i + 1] as usize) & 2047;
+			self.0[255 - bit / 8] |= 1 << (bit % 8);
+		}
+	}
+
+	/// Whether the filter possibly contains the given input.
+	pub fn contains_input(&self, input: &[u8]) -> bool {
+		let mut bloom = Bloom::default();
+		bloom.accrue(input);
+		self.0.iter().zip(bloom.0.iter()).all(|(a, b)| a & b == *b)
+	}
+}
+
+impl Default for Bloom {
+	fn default() -> Self {
+		Bloom([0u8; 256])
+	}
+}
+
+impl PartialEq for Bloom {
+	fn eq(&self, other: &Self) -> bool {
+		self.0[..] == other.0[..]
+	}
+}
+
+impl Eq for Bloom {}
+
+impl fmt::Debug for Bloom {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Bloom(0x")?;
+		for byte in self.0.iter() {
+			write!(f, "{:02x}", byte)?;
+		}
+		write!(f, ")")
+	}
+}
+
+/// The receipt of an Ethereum transaction executed by the module.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Receipt {
+	/// The hash of the transaction.
+	pub transaction_hash: H256,
+	/// The sender of the transaction.
+	pub from: H160,
+	/// The address of the created contract, if the transaction created one.
+	pub contract_address: Option<H160>,
+	/// Whether the execution of the transaction succeeded.
+	pub status: bool,
+	/// The gas used by the transaction.
+	pub used_gas: U256,
+	/// The gas used by this and all previous transactions of the block.
+	pub cumulative_gas_used: U256,
+	/// The Bloom filter of the logs of the transaction.
+	pub logs_bloom: Bloom,
+	/// The logs emitted by the transaction, which are empty if the execution failed.
+	pub logs: Vec<Log>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// The example transaction of EIP-155, signed for chain id 1.
+	const EIP155_TRANSACTION: [u8; 110] = hex!("
+		f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000
+		8025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f
+		761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83
+	");
+
+	#[test]
+	fn decodes_and_encodes_eip155_transaction() {
+		let transaction: Transaction = rlp::decode(&EIP155_TRANSACTION[..]).unwrap();
+
+		assert_eq!(transaction.nonce, U256::from(9));
+		assert_eq!(transaction.gas_price, U256::from(20_000_000_000u64));
+		assert_eq!(transaction.gas_limit, U256::from(21_000));
+		assert_eq!(transaction.action, TransactionAction::Call(H160::repeat_byte(0x35)));
+		assert_eq!(transaction.value, U256::from(1_000_000_000_000_000_000u64));
+		assert!(transaction.input.is_empty());
+		assert_eq!(transaction.chain_id(), Some(1));
+		assert_eq!(rlp::encode(&transaction), EIP155_TRANSACTION.to_vec());
+	}
+
+	const EIP155_MESSAGE_HASH: [u8; 32] =
+		hex!("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53");
+	const EIP155_SENDER: [u8; 20] = hex!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+
+	#[test]
+	fn recovers_sender_of_eip155_transaction() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			let mut transaction: Transaction = rlp::decode(&EIP155_TRANSACTION[..]).unwrap();
+
+			assert_eq!(transaction.message_hash(), H256::from(EIP155_MESSAGE_HASH));
+			assert_eq!(transaction.recover_sender(), Some(H160::from(EIP155_SENDER)));
+
+			// Changing the transaction changes the recovered sender.
+			transaction.nonce = U256::from(10);
+			assert_ne!(transaction.recover_sender(), Some(H160::from(EIP155_SENDER)));
+		});
+	}
+
+	#[test]
+	fn rejects_signature_with_high_s() {
+		sp_io::TestExternalities::default().execute_with(|| {
+			let mut transaction: Transaction = rlp::decode(&EIP155_TRANSACTION[..]).unwrap();
+
+			// The same signature with `s` replaced by `n - s` and the other recovery id.
+			transaction.signature.v = 38;
+			transaction.signature.s = H256(
+				hex!("98341627668089e51348fccfb4c7ff31c55912f2d2e47ef09652acf665fad3be")
+			);
+			assert_eq!(transaction.recover_sender(), None);
+		});
+	}
+
+	#[test]
+	fn bloom_contains_log_address_and_topics() {
+		let log = Log {
+			address: H160::repeat_byte(1),
+			topics: vec![H256::repeat_byte(2)],
+			data: vec![3],
+		};
+		let bloom = Bloom::from_logs(&[log]);
+
+		assert!(bloom.contains_input(H160::repeat_byte(1).as_bytes()));
+		assert!(bloom.contains_input(H256::repeat_byte(2).as_bytes()));
+		assert!(bloom.0.iter().map(|byte| byte.count_ones()).sum::<u32>() <= 6);
+		assert_eq!(Bloom::from_logs(&[]), Bloom::default());
+	}
+}
@@ -28,6 +28,7 @@
 //! The Executive module provides functions to:
 //!
 //! - Check transaction validity.
+//! - Run the runtime upgrade hooks of the modules after a runtime upgrade.
 //! - Initialize a block.
 //! - Apply extrinsics.
 //! - Execute a block.
@@ -77,12 +78,12 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use sp_std::{prelude::*, marker::PhantomData};
-use frame_support::weights::{GetDispatchInfo, WeighBlock, DispatchInfo};
+use frame_support::{storage::StorageValue, weights::{GetDispatchInfo, WeighBlock, DispatchInfo}};
 use sp_runtime::{
 	generic::Digest, ApplyExtrinsicResult,
 	traits::{
 		self, Header, Zero, One, Checkable, Applyable, CheckEqual, OnFinalize, OnInitialize,
-		NumberFor, Block as BlockT, OffchainWorker, Dispatchable, Saturating,
+		OnRuntimeUpgrade, NumberFor, Block as BlockT, OffchainWorker, Dispatchable, Saturating,
 	},
 	transaction_validity::TransactionValidity,
 };
@@ -112,6 +113,7 @@ impl<
 	Context: Default,
 	UnsignedValidator,
 	AllModules:
+		OnRuntimeUpgrade +
 		OnInitialize<System::BlockNumber> +
 		OnFinalize<System::BlockNumber> +
 		OffchainWorker<System::BlockNumber> +
@@ -138,6 +140,7 @@ impl<
 	Context: Default,
 	UnsignedValidator,
 	AllModules:
+		OnRuntimeUpgrade +
 		OnInitialize<System::BlockNumber> +
 		OnFinalize<System::BlockNumber> +
 		OffchainWorker<System::BlockNumber> +
@@ -186,6 +189,9 @@ where
 			digest,
 			frame_system::InitKind::Full,
 		);
+		if Self::runtime_upgraded() {
+			<AllModules as OnRuntimeUpgrade>::on_runtime_upgrade();
+		}
 		<AllModules as OnInitialize<System::BlockNumber>>::on_initialize(*block_number);
 		<frame_system::Module<System>>::register_extra_weight_unchecked(
 			<AllModules as WeighBlock<System::BlockNumber>>::on_initialize(*block_number)
@@ -195,6 +201,19 @@ where
 		);
 	}
 
+	/// Returns whether the runtime executing this block has a different `spec_version` than the
+	/// one that last ran the runtime upgrade hooks, recording the current one if so.
+	///
+	/// A chain without any recorded version is upgrading to a runtime that introduces the hooks.
+	fn runtime_upgraded() -> bool {
+		let current = <System::Version as frame_support::traits::Get<_>>::get().spec_version;
+		if frame_system::LastRuntimeUpgrade::get() == Some(current) {
+			return false;
+		}
+		frame_system::LastRuntimeUpgrade::put(current);
+		true
+	}
+
 	fn initial_checks(block: &Block) {
 		let header = block.header();
 
@@ -401,10 +420,17 @@ mod tests {
 				fn on_finalize() {
 					println!("on_finalize(?)");
 				}
+
+				fn on_runtime_upgrade() {
+					frame_support::storage::unhashed::put(super::RUNTIME_UPGRADED_KEY, &true);
+				}
 			}
 		}
 	}
 
+	/// Set by the `on_runtime_upgrade` hook of the custom module.
+	const RUNTIME_UPGRADED_KEY: &[u8] = b":test:runtime_upgraded";
+
 	type System = frame_system::Module<Runtime>;
 	type Balances = pallet_balances::Module<Runtime>;
 	type Custom = custom::Module<Runtime>;
@@ -529,7 +555,6 @@ mod tests {
 		let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
 		pallet_balances::GenesisConfig::<Runtime> {
 			balances: vec![(1, 211)],
-			vesting: vec![],
 		}.assimilate_storage(&mut t).unwrap();
 		let xt = sp_runtime::testing::TestXt(sign_extra(1, 0, 0), Call::Balances(BalancesCall::transfer(2, 69)));
 		let weight = xt.get_dispatch_info().weight as u64;
@@ -553,7 +578,6 @@ mod tests {
 		let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
 		pallet_balances::GenesisConfig::<Runtime> {
 			balances: vec![(1, 111 * balance_factor)],
-			vesting: vec![],
 		}.assimilate_storage(&mut t).unwrap();
 		t.into()
 	}
@@ -565,7 +589,9 @@ mod tests {
 				header: Header {
 					parent_hash: [69u8; 32].into(),
 					number: 1,
-					state_root: hex!("c6b01b27df520ba23adb96e7fc032acb7c586ba1b477c6282de43184111f2091").into(),
+					// Includes `LastRuntimeUpgrade`, which the first block records before running
+					// the runtime upgrade hooks.
+					state_root: hex!("1d17238449de42e6793fedb3b7851c2181c394a00ac076b648f2743013e01d7b").into(),
 					extrinsics_root: hex!("03170a2e7597b7b7e3d84c05391d139a62b157e78786d8c082f29dcf4c111314").into(),
 					digest: Digest { logs: vec![], },
 				},
@@ -574,6 +600,35 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn runtime_upgrade_hooks_run_once_per_spec_version() {
+		let upgraded = || frame_support::storage::unhashed::take::<bool>(RUNTIME_UPGRADED_KEY);
+		let initialize = |number| Executive::initialize_block(&Header::new(
+			number,
+			H256::default(),
+			H256::default(),
+			[69u8; 32].into(),
+			Digest::default(),
+		));
+
+		new_test_ext(1).execute_with(|| {
+			// No version recorded yet.
+			initialize(1);
+			assert_eq!(upgraded(), Some(true));
+			assert_eq!(System::last_runtime_upgrade(), Some(0));
+
+			// Same runtime.
+			initialize(2);
+			assert_eq!(upgraded(), None);
+
+			// The previous runtime had another `spec_version`.
+			frame_system::LastRuntimeUpgrade::put(1);
+			initialize(3);
+			assert_eq!(upgraded(), Some(true));
+			assert_eq!(System::last_runtime_upgrade(), Some(0));
+		});
+	}
+
 	#[test]
 	#[should_panic]
 	fn block_import_of_bad_state_root_fails() {
@@ -15,45 +15,34 @@
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
 //! An index is a short form of an address. This module handles allocation
-//! of indices for a newly created accounts.
+//! of indices for accounts.
+//!
+//! An account `claim`s a free index by placing a deposit on hold, which is returned when the
+//! index is `free`d again. The owner of an index can `transfer` it to another account, along
+//! with the responsibility for its deposit, and the root origin can `force_transfer` any index.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use sp_std::{prelude::*, marker::PhantomData, convert::TryInto};
-use codec::{Encode, Codec};
-use frame_support::{Parameter, decl_module, decl_event, decl_storage};
-use sp_runtime::traits::{One, SimpleArithmetic, StaticLookup, Member, LookupError};
-use frame_system::{IsDeadAccount, OnNewAccount};
-
+use sp_std::prelude::*;
+use codec::Codec;
+use sp_runtime::traits::{
+	StaticLookup, Member, LookupError, Zero, One, SimpleArithmetic, Saturating,
+};
+use frame_support::{Parameter, decl_module, decl_error, decl_event, decl_storage, ensure};
+use frame_support::weights::SimpleDispatchInfo;
+use frame_support::traits::{Currency, ReservableCurrency, Get};
+use frame_system::{ensure_signed, ensure_root};
 use self::address::Address as RawAddress;
 
 mod mock;
-
 pub mod address;
 mod tests;
 
-/// Number of account IDs stored per enum set.
+/// Number of account IDs stored per enum set of the deprecated storage layout.
 const ENUM_SET_SIZE: u32 = 64;
 
 pub type Address<T> = RawAddress<<T as frame_system::Trait>::AccountId, <T as Trait>::AccountIndex>;
-
-/// Turn an Id into an Index, or None for the purpose of getting
-/// a hint at a possibly desired index.
-pub trait ResolveHint<AccountId, AccountIndex> {
-	/// Turn an Id into an Index, or None for the purpose of getting
-	/// a hint at a possibly desired index.
-	fn resolve_hint(who: &AccountId) -> Option<AccountIndex>;
-}
-
-/// Simple encode-based resolve hint implementation.
-pub struct SimpleResolveHint<AccountId, AccountIndex>(PhantomData<(AccountId, AccountIndex)>);
-impl<AccountId: Encode, AccountIndex: From<u32>>
-	ResolveHint<AccountId, AccountIndex> for SimpleResolveHint<AccountId, AccountIndex>
-{
-	fn resolve_hint(who: &AccountId) -> Option<AccountIndex> {
-		Some(AccountIndex::from(who.using_encoded(|e| e[0] as u32 + e[1] as u32 * 256)))
-	}
-}
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
 
 /// The module's config trait.
 pub trait Trait: frame_system::Trait {
@@ -61,19 +50,39 @@ pub trait Trait: frame_system::Trait {
 	/// can hold.
 	type AccountIndex: Parameter + Member + Codec + Default + SimpleArithmetic + Copy;
 
-	/// Whether an account is dead or not.
-	type IsDeadAccount: IsDeadAccount<Self::AccountId>;
+	/// The currency trait.
+	type Currency: ReservableCurrency<Self::AccountId>;
 
-	/// How to turn an id into an index.
-	type ResolveHint: ResolveHint<Self::AccountId, Self::AccountIndex>;
+	/// The deposit needed for reserving an index.
+	type Deposit: Get<BalanceOf<Self>>;
 
 	/// The overarching event type.
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 }
 
-decl_module! {
-	pub struct Module<T: Trait> for enum Call where origin: T::Origin, system = frame_system {
-		fn deposit_event() = default;
+decl_storage! {
+	trait Store for Module<T: Trait> as Indices {
+		/// The lookup from index to account, along with the deposit held for it.
+		pub Accounts get(fn accounts) build(|config: &GenesisConfig<T>|
+			config.indices.iter()
+				.cloned()
+				.map(|(a, b)| (a, (b, Zero::zero())))
+				.collect::<Vec<_>>()
+		): map hasher(blake2_256) T::AccountIndex => Option<(T::AccountId, BalanceOf<T>)>;
+
+		/// DEPRECATED
+		///
+		/// The next free enumeration set. Only kept to migrate the old storage layout to
+		/// `Accounts`.
+		NextEnumSet: Option<T::AccountIndex>;
+
+		/// DEPRECATED
+		///
+		/// The enumeration sets. Only kept to migrate the old storage layout to `Accounts`.
+		EnumSet: map T::AccountIndex => Vec<T::AccountId>;
+	}
+	add_extra_genesis {
+		config(indices): Vec<(T::AccountIndex, T::AccountId)>;
 	}
 }
 
@@ -82,36 +91,158 @@ decl_event!(
 		<T as frame_system::Trait>::AccountId,
 		<T as Trait>::AccountIndex
 	{
-		/// A new account index was assigned.
-		///
-		/// This event is not triggered when an existing index is reassigned
-		/// to another `AccountId`.
-		NewAccountIndex(AccountId, AccountIndex),
+		/// A account index was assigned.
+		IndexAssigned(AccountId, AccountIndex),
+		/// A account index has been freed up (unassigned).
+		IndexFreed(AccountIndex),
 	}
 );
 
-decl_storage! {
-	trait Store for Module<T: Trait> as Indices {
-		/// The next free enumeration set.
-		pub NextEnumSet get(fn next_enum_set) build(|config: &GenesisConfig<T>| {
-			(config.ids.len() as u32 / ENUM_SET_SIZE).into()
-		}): T::AccountIndex;
-
-		/// The enumeration sets.
-		pub EnumSet get(fn enum_set) build(|config: &GenesisConfig<T>| {
-			(0..((config.ids.len() as u32) + ENUM_SET_SIZE - 1) / ENUM_SET_SIZE)
-				.map(|i| (
-					i.into(),
-					config.ids[
-						(i * ENUM_SET_SIZE) as usize..
-						config.ids.len().min(((i + 1) * ENUM_SET_SIZE) as usize)
-					].to_owned(),
-				))
-				.collect::<Vec<_>>()
-		}): map T::AccountIndex => Vec<T::AccountId>;
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// The index is already in use.
+		InUse,
+		/// The index is not assigned.
+		NotAssigned,
+		/// The index is assigned to another account.
+		NotOwner,
+		/// The source and destination accounts of a transfer are the same.
+		NotTransfer,
 	}
-	add_extra_genesis {
-		config(ids): Vec<T::AccountId>;
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin, system = frame_system {
+		type Error = Error<T>;
+
+		/// The deposit needed for reserving an index.
+		const Deposit: BalanceOf<T> = T::Deposit::get();
+
+		fn deposit_event() = default;
+
+		fn on_runtime_upgrade() {
+			Self::migrate_enum_set();
+		}
+
+		/// Assign an previously unassigned index.
+		///
+		/// Payment: `Deposit` is reserved from the sender account.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `index`: the index to be claimed. This must not be in use.
+		///
+		/// Emits `IndexAssigned` if successful.
+		///
+		/// # <weight>
+		/// - `O(1)`.
+		/// - One storage mutation (codec `O(1)`).
+		/// - One reserve operation.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(1_000_000)]
+		fn claim(origin, index: T::AccountIndex) {
+			let who = ensure_signed(origin)?;
+
+			ensure!(!Accounts::<T>::exists(index), Error::<T>::InUse);
+			let deposit = T::Deposit::get();
+			T::Currency::reserve(&who, deposit)?;
+			Accounts::<T>::insert(index, (who.clone(), deposit));
+
+			Self::deposit_event(RawEvent::IndexAssigned(who, index));
+		}
+
+		/// Assign an index already owned by the sender to another account. The balance reservation
+		/// is effectively transferred to the new account.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `new`: the new owner of the index. This must not be the sender.
+		/// - `index`: the index to be re-assigned. This must be owned by the sender.
+		///
+		/// Emits `IndexAssigned` if successful.
+		///
+		/// # <weight>
+		/// - `O(1)`.
+		/// - One storage mutation (codec `O(1)`).
+		/// - One transfer operation.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(1_000_000)]
+		fn transfer(origin, new: T::AccountId, index: T::AccountIndex) {
+			let who = ensure_signed(origin)?;
+			ensure!(who != new, Error::<T>::NotTransfer);
+
+			let (account, amount) = Accounts::<T>::get(index).ok_or(Error::<T>::NotAssigned)?;
+			ensure!(account == who, Error::<T>::NotOwner);
+			let lost = T::Currency::repatriate_reserved(&who, &new, amount)?;
+			let moved = amount.saturating_sub(lost);
+			// the deposit only stays bound to the index if it can be reserved again.
+			let deposit = match T::Currency::reserve(&new, moved) {
+				Ok(()) => moved,
+				Err(_) => Zero::zero(),
+			};
+			Accounts::<T>::insert(index, (new.clone(), deposit));
+
+			Self::deposit_event(RawEvent::IndexAssigned(new, index));
+		}
+
+		/// Free up an index owned by the sender.
+		///
+		/// Payment: Any previous deposit placed for the index is unreserved in the sender account.
+		///
+		/// The dispatch origin for this call must be _Signed_ and the sender must own the index.
+		///
+		/// - `index`: the index to be freed. This must be owned by the sender.
+		///
+		/// Emits `IndexFreed` if successful.
+		///
+		/// # <weight>
+		/// - `O(1)`.
+		/// - One storage mutation (codec `O(1)`).
+		/// - One reserve operation.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(1_000_000)]
+		fn free(origin, index: T::AccountIndex) {
+			let who = ensure_signed(origin)?;
+
+			let (account, amount) = Accounts::<T>::get(index).ok_or(Error::<T>::NotAssigned)?;
+			ensure!(account == who, Error::<T>::NotOwner);
+			let _ = T::Currency::unreserve(&who, amount);
+			Accounts::<T>::remove(index);
+
+			Self::deposit_event(RawEvent::IndexFreed(index));
+		}
+
+		/// Force an index to an account. This doesn't require a deposit. If the index is already
+		/// held, then any deposit is reimbursed to its current owner.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		///
+		/// - `index`: the index to be (re-)assigned.
+		/// - `new`: the new owner of the index. Any deposit is reimbursed even if it is the current
+		///   owner.
+		///
+		/// Emits `IndexAssigned` if successful.
+		///
+		/// # <weight>
+		/// - `O(1)`.
+		/// - One storage mutation (codec `O(1)`).
+		/// - Up to one reserve operation.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(1_000_000)]
+		fn force_transfer(origin, new: T::AccountId, index: T::AccountIndex) {
+			ensure_root(origin)?;
+
+			if let Some((account, amount)) = Accounts::<T>::get(index) {
+				let _ = T::Currency::unreserve(&account, amount);
+			}
+			Accounts::<T>::insert(index, (new.clone(), BalanceOf::<T>::zero()));
+
+			Self::deposit_event(RawEvent::IndexAssigned(new, index));
+		}
 	}
 }
 
@@ -120,22 +251,7 @@ impl<T: Trait> Module<T> {
 
 	/// Lookup an T::AccountIndex to get an Id, if there's one there.
 	pub fn lookup_index(index: T::AccountIndex) -> Option<T::AccountId> {
-		let enum_set_size = Self::enum_set_size();
-		let set = Self::enum_set(index / enum_set_size);
-		let i: usize = (index % enum_set_size).try_into().ok()?;
-		set.get(i).cloned()
-	}
-
-	/// `true` if the account `index` is ready for reclaim.
-	pub fn can_reclaim(try_index: T::AccountIndex) -> bool {
-		let enum_set_size = Self::enum_set_size();
-		let try_set = Self::enum_set(try_index / enum_set_size);
-		let maybe_usize: Result<usize, _> = (try_index % enum_set_size).try_into();
-		if let Ok(i) = maybe_usize {
-			i < try_set.len() && T::IsDeadAccount::is_dead_account(&try_set[i])
-		} else {
-			false
-		}
+		Accounts::<T>::get(index).map(|x| x.0)
 	}
 
 	/// Lookup an address to get an Id, if there's one there.
@@ -148,76 +264,28 @@ impl<T: Trait> Module<T> {
 		}
 	}
 
-	// PUBLIC MUTABLES (DANGEROUS)
-
-	fn enum_set_size() -> T::AccountIndex {
-		ENUM_SET_SIZE.into()
-	}
-}
-
-impl<T: Trait> OnNewAccount<T::AccountId> for Module<T> {
-	// Implementation of the config type managing the creation of new accounts.
-	// See Balances module for a concrete example.
-	//
-	// # <weight>
-	// - Independent of the arguments.
-	// - Given the correct value of `Self::next_enum_set`, it always has a limited
-	//   number of reads and writes and no complex computation.
-	//
-	// As for storage, calling this function with _non-dead-indices_ will linearly grow the length of
-	// of `Self::enum_set`. Appropriate economic incentives should exist to make callers of this
-	// function provide a `who` argument that reclaims a dead account.
-	//
-	// At the time of this writing, only the Balances module calls this function upon creation
-	// of new accounts.
-	// # </weight>
-	fn on_new_account(who: &T::AccountId) {
-		let enum_set_size = Self::enum_set_size();
-		let next_set_index = Self::next_enum_set();
-
-		if let Some(try_index) = T::ResolveHint::resolve_hint(who) {
-			// then check to see if this account id identifies a dead account index.
-			let set_index = try_index / enum_set_size;
-			let mut try_set = Self::enum_set(set_index);
-			if let Ok(item_index) = (try_index % enum_set_size).try_into() {
-				if item_index < try_set.len() {
-					if T::IsDeadAccount::is_dead_account(&try_set[item_index]) {
-						// yup - this index refers to a dead account. can be reused.
-						try_set[item_index] = who.clone();
-						<EnumSet<T>>::insert(set_index, try_set);
-
-						return
-					}
-				}
-			}
+	/// Move the indices of the deprecated enum set layout over to `Accounts`.
+	///
+	/// Indices of accounts that no longer hold any funds are dropped. The remaining ones are kept
+	/// by their accounts without a deposit. No-op once the old layout is gone.
+	fn migrate_enum_set() {
+		let had_next_set = NextEnumSet::<T>::take().is_some();
+		if !had_next_set && !EnumSet::<T>::exists(T::AccountIndex::zero()) {
+			return
 		}
 
-		// insert normally as a back up
-		let mut set_index = next_set_index;
-		// defensive only: this loop should never iterate since we keep NextEnumSet up to date
-		// later.
-		let mut set = loop {
-			let set = Self::enum_set(set_index);
-			if set.len() < ENUM_SET_SIZE as usize {
-				break set;
+		let set_size: T::AccountIndex = ENUM_SET_SIZE.into();
+		let mut set_index: T::AccountIndex = Zero::zero();
+		while EnumSet::<T>::exists(set_index) {
+			let accounts = EnumSet::<T>::take(set_index);
+			for (item_index, target) in accounts.into_iter().enumerate() {
+				if !T::Currency::total_balance(&target).is_zero() {
+					let index = set_index * set_size + T::AccountIndex::from(item_index as u32);
+					Accounts::<T>::insert(index, (target, BalanceOf::<T>::zero()));
+				}
 			}
 			set_index += One::one();
-		};
-
-		let index = set_index * enum_set_size + T::AccountIndex::from(set.len() as u32);
-
-		// update set.
-		set.push(who.clone());
-
-		// keep NextEnumSet up to date
-		if set.len() == ENUM_SET_SIZE as usize {
-			<NextEnumSet<T>>::put(set_index + One::one());
 		}
-
-		// write set.
-		<EnumSet<T>>::insert(set_index, set);
-
-		Self::deposit_event(RawEvent::NewAccountIndex(who.clone(), index));
 	}
 }
 
@@ -18,48 +18,16 @@
 
 #![cfg(test)]
 
-use std::{cell::RefCell, collections::HashSet};
 use sp_runtime::testing::Header;
 use sp_runtime::Perbill;
 use sp_core::H256;
 use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
-use crate::{GenesisConfig, Module, Trait, IsDeadAccount, OnNewAccount, ResolveHint};
+use crate::{GenesisConfig, Module, Trait};
 
 impl_outer_origin!{
 	pub enum Origin for Runtime where system = frame_system {}
 }
 
-thread_local! {
-	static ALIVE: RefCell<HashSet<u64>> = Default::default();
-}
-
-pub fn make_account(who: u64) {
-	ALIVE.with(|a| a.borrow_mut().insert(who));
-	Indices::on_new_account(&who);
-}
-
-pub fn kill_account(who: u64) {
-	ALIVE.with(|a| a.borrow_mut().remove(&who));
-}
-
-pub struct TestIsDeadAccount {}
-impl IsDeadAccount<u64> for TestIsDeadAccount {
-	fn is_dead_account(who: &u64) -> bool {
-		!ALIVE.with(|a| a.borrow_mut().contains(who))
-	}
-}
-
-pub struct TestResolveHint;
-impl ResolveHint<u64, u64> for TestResolveHint {
-	fn resolve_hint(who: &u64) -> Option<u64> {
-		if *who < 256 {
-			None
-		} else {
-			Some(*who - 256)
-		}
-	}
-}
-
 // Workaround for https://github.com/rust-lang/rust/issues/26925 . Remove when sorted.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Runtime;
@@ -89,27 +57,47 @@ impl frame_system::Trait for Runtime {
 	type ModuleToIndex = ();
 }
 
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+	pub const TransferFee: u64 = 0;
+	pub const CreationFee: u64 = 0;
+}
+
+impl pallet_balances::Trait for Runtime {
+	type Balance = u64;
+	type OnFreeBalanceZero = ();
+	type OnReapAccount = System;
+	type OnNewAccount = ();
+	type Event = ();
+	type DustRemoval = ();
+	type TransferPayment = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type TransferFee = TransferFee;
+	type CreationFee = CreationFee;
+}
+
+parameter_types! {
+	pub const Deposit: u64 = 1;
+}
+
 impl Trait for Runtime {
 	type AccountIndex = u64;
-	type IsDeadAccount = TestIsDeadAccount;
-	type ResolveHint = TestResolveHint;
+	type Currency = Balances;
+	type Deposit = Deposit;
 	type Event = ();
 }
 
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	{
-		ALIVE.with(|a| {
-			let mut h = a.borrow_mut();
-			h.clear();
-			for i in 1..5 { h.insert(i); }
-		});
-	}
-
 	let mut t = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	pallet_balances::GenesisConfig::<Runtime>{
+		balances: vec![(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60)],
+	}.assimilate_storage(&mut t).unwrap();
 	GenesisConfig::<Runtime> {
-		ids: vec![1, 2, 3, 4]
+		indices: vec![(0, 1), (1, 2), (2, 3), (3, 4)],
 	}.assimilate_storage(&mut t).unwrap();
 	t.into()
 }
 
+pub type System = frame_system::Module<Runtime>;
+pub type Balances = pallet_balances::Module<Runtime>;
 pub type Indices = Module<Runtime>;
@@ -19,49 +19,110 @@
 #![cfg(test)]
 
 use super::*;
-use crate::mock::{Indices, new_test_ext, make_account, kill_account, TestIsDeadAccount};
+use super::mock::*;
+use frame_support::{assert_ok, assert_noop, StorageValue, StorageMap};
+use sp_runtime::traits::{BadOrigin, OnRuntimeUpgrade};
 
 #[test]
-fn indexing_lookup_should_work() {
+fn genesis_indices_should_work() {
 	new_test_ext().execute_with(|| {
 		assert_eq!(Indices::lookup_index(0), Some(1));
-		assert_eq!(Indices::lookup_index(1), Some(2));
-		assert_eq!(Indices::lookup_index(2), Some(3));
 		assert_eq!(Indices::lookup_index(3), Some(4));
 		assert_eq!(Indices::lookup_index(4), None);
+		// genesis indices are held without a deposit.
+		assert_eq!(Balances::reserved_balance(&1), 0);
 	});
 }
 
 #[test]
-fn default_indexing_on_new_accounts_should_work() {
+fn claiming_should_work() {
 	new_test_ext().execute_with(|| {
-		assert_eq!(Indices::lookup_index(4), None);
-		make_account(5);
+		assert_noop!(
+			Indices::claim(Some(0).into(), 4),
+			pallet_balances::Error::<Runtime, _>::InsufficientBalance
+		);
+		assert_ok!(Indices::claim(Some(5).into(), 4));
+		assert_noop!(Indices::claim(Some(6).into(), 4), Error::<Runtime>::InUse);
+		assert_eq!(Balances::reserved_balance(&5), 1);
 		assert_eq!(Indices::lookup_index(4), Some(5));
 	});
 }
 
 #[test]
-fn reclaim_indexing_on_new_accounts_should_work() {
+fn freeing_should_work() {
 	new_test_ext().execute_with(|| {
-		assert_eq!(Indices::lookup_index(1), Some(2));
+		assert_ok!(Indices::claim(Some(5).into(), 4));
+		assert_ok!(Indices::claim(Some(6).into(), 5));
+		assert_noop!(Indices::free(Some(6).into(), 4), Error::<Runtime>::NotOwner);
+		assert_noop!(Indices::free(Some(5).into(), 6), Error::<Runtime>::NotAssigned);
+		assert_ok!(Indices::free(Some(5).into(), 4));
+		assert_eq!(Balances::reserved_balance(&5), 0);
 		assert_eq!(Indices::lookup_index(4), None);
+		assert_ok!(Indices::claim(Some(6).into(), 4));
+		assert_eq!(Indices::lookup_index(4), Some(6));
+	});
+}
 
-		kill_account(2);					// index 1 no longer locked to id 2
+#[test]
+fn indexing_lookup_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Indices::claim(Some(5).into(), 4));
+		assert_eq!(Indices::lookup_address(address::Address::Index(4)), Some(5));
+		assert_eq!(Indices::lookup_address(address::Address::Id(6)), Some(6));
+		assert_eq!(Indices::lookup_address(address::Address::Index(5)), None);
+		assert_eq!(<Indices as StaticLookup>::lookup(address::Address::Index(4)).ok(), Some(5));
+	});
+}
 
-		make_account(1 + 256);				// id 257 takes index 1.
-		assert_eq!(Indices::lookup_index(1), Some(257));
+#[test]
+fn transferring_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Indices::claim(Some(5).into(), 4));
+		assert_noop!(Indices::transfer(Some(5).into(), 5, 4), Error::<Runtime>::NotTransfer);
+		assert_noop!(Indices::transfer(Some(6).into(), 5, 4), Error::<Runtime>::NotOwner);
+		assert_noop!(Indices::transfer(Some(5).into(), 6, 5), Error::<Runtime>::NotAssigned);
+		assert_ok!(Indices::transfer(Some(5).into(), 6, 4));
+		assert_eq!(Balances::reserved_balance(&5), 0);
+		assert_eq!(Balances::reserved_balance(&6), 1);
+		assert_eq!(Indices::lookup_index(4), Some(6));
+		assert_ok!(Indices::free(Some(6).into(), 4));
+		assert_eq!(Balances::reserved_balance(&6), 0);
 	});
 }
 
 #[test]
-fn alive_account_should_prevent_reclaim() {
+fn force_transfer_should_work() {
 	new_test_ext().execute_with(|| {
-		assert!(!TestIsDeadAccount::is_dead_account(&2));
-		assert_eq!(Indices::lookup_index(1), Some(2));
-		assert_eq!(Indices::lookup_index(4), None);
+		assert_ok!(Indices::claim(Some(5).into(), 4));
+		assert_noop!(Indices::force_transfer(Some(5).into(), 6, 4), BadOrigin);
+		assert_ok!(Indices::force_transfer(frame_system::RawOrigin::Root.into(), 6, 4));
+		assert_eq!(Balances::reserved_balance(&5), 0);
+		assert_eq!(Balances::reserved_balance(&6), 0);
+		assert_eq!(Indices::lookup_index(4), Some(6));
+		// the forced index is held without a deposit.
+		assert_ok!(Indices::free(Some(6).into(), 4));
+		assert_eq!(Balances::free_balance(&6), 60);
+	});
+}
 
-		make_account(1 + 256);				// id 257 takes index 1.
-		assert_eq!(Indices::lookup_index(4), Some(257));
+#[test]
+fn enum_set_should_be_migrated() {
+	new_test_ext().execute_with(|| {
+		NextEnumSet::<Runtime>::put(1);
+		EnumSet::<Runtime>::insert(0, (1..=64).collect::<Vec<u64>>());
+		EnumSet::<Runtime>::insert(1, vec![65, 2]);
+
+		<Indices as OnRuntimeUpgrade>::on_runtime_upgrade();
+
+		assert!(!NextEnumSet::<Runtime>::exists());
+		assert!(!EnumSet::<Runtime>::exists(0));
+		assert!(!EnumSet::<Runtime>::exists(1));
+		// only accounts with funds keep their index, without a deposit.
+		assert_eq!(Indices::lookup_index(0), Some(1));
+		assert_eq!(Indices::lookup_index(5), Some(6));
+		assert_eq!(Indices::lookup_index(6), None);
+		assert_eq!(Indices::lookup_index(64), None);
+		assert_eq!(Indices::lookup_index(65), Some(2));
+		assert_eq!(Balances::reserved_balance(&2), 0);
 	});
 }
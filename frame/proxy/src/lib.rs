@@ -0,0 +1,451 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Proxy Module
+//! A module allowing accounts to give permission to other accounts to dispatch types of calls from
+//! their signed origin.
+//!
+//! - [`proxy::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! An account may register any number (up to `MaxProxies`) of delegates, each together with a
+//! proxy type. The proxy type is an instance filter over the runtime's `Call`s: it determines
+//! which calls the delegate may dispatch on behalf of the account. A deposit is held for every
+//! registered proxy.
+//!
+//! An account may also spawn "anonymous" proxy accounts: fresh accounts with no private key,
+//! derived deterministically from the spawner, the extrinsic that spawned them and an index, whose
+//! only proxy is the spawner. This allows e.g. a stash to be controlled entirely through a proxy.
+//! The spawner pays the deposit for the anonymous account and gets it back once the account's
+//! proxies are removed.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! * `proxy` - Dispatch a call on behalf of an account for which the sender is a proxy.
+//! * `add_proxy` - Register a delegate of the sender with a proxy type.
+//! * `remove_proxy` - Unregister a delegate of the sender.
+//! * `remove_proxies` - Unregister all delegates of the sender.
+//! * `anonymous` - Spawn an anonymous account whose proxy is the sender.
+//! * `kill_anonymous` - Remove an anonymous account, called by the anonymous account itself.
+//!
+//! [`Call`]: ./enum.Call.html
+//! [`Trait`]: ./trait.Trait.html
+
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_std::prelude::*;
+use codec::{Encode, Decode};
+use sp_io::hashing::blake2_256;
+use sp_runtime::{DispatchResult, traits::{Dispatchable, Zero, Member}};
+use frame_support::{decl_module, decl_event, decl_error, decl_storage, Parameter, ensure, IsSubType};
+use frame_support::{traits::{Get, ReservableCurrency, Currency, InstanceFilter}, weights::{
+	GetDispatchInfo, ClassifyDispatch, WeighData, Weight, DispatchClass, PaysFee, SimpleDispatchInfo,
+}};
+use frame_system::{self as system, ensure_signed};
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// Configuration trait.
+pub trait Trait: frame_system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The overarching call type.
+	type Call: Parameter + Dispatchable<Origin=Self::Origin> + GetDispatchInfo
+		+ IsSubType<Module<Self>, Self>;
+
+	/// The currency mechanism.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// A kind of proxy; specified with the proxy and used as an instance filter determining
+	/// whether a given call may be proxied under this type.
+	type ProxyType: Parameter + Member + Ord + PartialOrd + InstanceFilter<<Self as Trait>::Call>
+		+ Default;
+
+	/// The base amount of currency needed to reserve for creating a proxy.
+	///
+	/// This is held for an additional storage item whose value size is
+	/// `sizeof(Balance)` bytes and whose key size is `sizeof(AccountId)` bytes.
+	type ProxyDepositBase: Get<BalanceOf<Self>>;
+
+	/// The amount of currency needed per proxy added.
+	///
+	/// This is held for adding 32 bytes plus an instance of `ProxyType` more into a pre-existing
+	/// storage value.
+	type ProxyDepositFactor: Get<BalanceOf<Self>>;
+
+	/// The maximum amount of proxies allowed for a single account.
+	type MaxProxies: Get<u16>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Proxy {
+		/// The set of account proxies. Maps the account which has delegated to the accounts
+		/// which are being delegated to, together with the amount held on deposit.
+		pub Proxies get(fn proxies): map hasher(twox_64_concat) T::AccountId
+			=> (Vec<(T::AccountId, T::ProxyType)>, BalanceOf<T>);
+		/// The spawner of each anonymous account, together with the amount it holds on deposit
+		/// for it.
+		pub AnonymousDeposits get(fn anonymous_deposit): map hasher(twox_64_concat) T::AccountId
+			=> Option<(T::AccountId, BalanceOf<T>)>;
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// There are too many proxies registered.
+		TooMany,
+		/// Proxy registration not found.
+		NotFound,
+		/// Sender is not a proxy of the account to be proxied.
+		NotProxy,
+		/// A call which is not allowed by the proxy type was attempted.
+		Unproxyable,
+		/// Account is already a proxy.
+		Duplicate,
+		/// Call may not be made by proxy because it may escalate its privileges.
+		NoPermission,
+	}
+}
+
+decl_event! {
+	/// Events type.
+	pub enum Event<T> where
+		AccountId = <T as frame_system::Trait>::AccountId,
+		ProxyType = <T as Trait>::ProxyType
+	{
+		/// A proxy was executed correctly, with the given result.
+		ProxyExecuted(DispatchResult),
+		/// Anonymous account (first parameter) has been created by new proxy (second) with given
+		/// disambiguation index and proxy type.
+		AnonymousCreated(AccountId, AccountId, ProxyType, u16),
+	}
+}
+
+/// Weight pass-through of the proxied call.
+struct ProxyPassthrough<AccountId, ProxyType, Call>(
+	sp_std::marker::PhantomData<(AccountId, ProxyType, Call)>
+);
+
+impl<AccountId, ProxyType, Call> ProxyPassthrough<AccountId, ProxyType, Call> {
+	fn new() -> Self { Self(Default::default()) }
+}
+impl<AccountId, ProxyType, Call: GetDispatchInfo> WeighData<(&AccountId, &Option<ProxyType>, &Box<Call>)>
+	for ProxyPassthrough<AccountId, ProxyType, Call>
+{
+	fn weigh_data(&self, (_, _, call): (&AccountId, &Option<ProxyType>, &Box<Call>)) -> Weight {
+		call.get_dispatch_info().weight + 10_000
+	}
+}
+impl<AccountId, ProxyType, Call: GetDispatchInfo> ClassifyDispatch<(&AccountId, &Option<ProxyType>, &Box<Call>)>
+	for ProxyPassthrough<AccountId, ProxyType, Call>
+{
+	fn classify_dispatch(&self, (_, _, call): (&AccountId, &Option<ProxyType>, &Box<Call>)) -> DispatchClass {
+		call.get_dispatch_info().class
+	}
+}
+impl<AccountId, ProxyType, Call: GetDispatchInfo> PaysFee<(&AccountId, &Option<ProxyType>, &Box<Call>)>
+	for ProxyPassthrough<AccountId, ProxyType, Call>
+{
+	fn pays_fee(&self, (_, _, call): (&AccountId, &Option<ProxyType>, &Box<Call>)) -> bool {
+		call.get_dispatch_info().pays_fee
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		/// Deposit one of this module's events by using the default implementation.
+		fn deposit_event() = default;
+
+		/// The base amount of currency needed to reserve for creating a proxy.
+		const ProxyDepositBase: BalanceOf<T> = T::ProxyDepositBase::get();
+
+		/// The amount of currency needed per proxy added.
+		const ProxyDepositFactor: BalanceOf<T> = T::ProxyDepositFactor::get();
+
+		/// The maximum amount of proxies allowed for a single account.
+		const MaxProxies: u16 = T::MaxProxies::get();
+
+		/// Dispatch the given `call` from an account that the sender is authorised for through
+		/// `add_proxy`.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// Parameters:
+		/// - `real`: The account that the proxy will make a call on behalf of.
+		/// - `force_proxy_type`: Specify the exact proxy type to be used and checked for this call.
+		/// - `call`: The call to be made by the `real` account.
+		///
+		/// # <weight>
+		/// - `O(P)` where `P` is the number of proxies of `real`.
+		/// - One storage read.
+		/// - One event.
+		/// - The weight of the `call`.
+		/// # </weight>
+		#[weight = <ProxyPassthrough<T::AccountId, T::ProxyType, <T as Trait>::Call>>::new()]
+		fn proxy(origin,
+			real: T::AccountId,
+			force_proxy_type: Option<T::ProxyType>,
+			call: Box<<T as Trait>::Call>,
+		) {
+			let who = ensure_signed(origin)?;
+			let (_, proxy_type) = Proxies::<T>::get(&real).0.into_iter()
+				.find(|x| &x.0 == &who && force_proxy_type.as_ref().map_or(true, |y| &x.1 == y))
+				.ok_or(Error::<T>::NotProxy)?;
+			ensure!(proxy_type.filter(&call), Error::<T>::Unproxyable);
+
+			// A proxy may not manage proxies of a type that is not a subset of its own, otherwise
+			// it could escalate its own privileges.
+			match call.is_sub_type() {
+				Some(Call::add_proxy(_, ref pt))
+				| Some(Call::remove_proxy(_, ref pt))
+				| Some(Call::anonymous(ref pt, _))
+					if !proxy_type.is_superset(pt) => Err(Error::<T>::NoPermission)?,
+				_ => (),
+			}
+
+			let e = call.dispatch(frame_system::RawOrigin::Signed(real).into());
+			Self::deposit_event(RawEvent::ProxyExecuted(e));
+		}
+
+		/// Register a proxy account for the sender that is able to make calls on its behalf.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// Payment: `ProxyDepositFactor` is reserved for the proxy, as well as
+		/// `ProxyDepositBase` if this is the first proxy of the sender. Anonymous accounts only
+		/// pay for the proxies beyond the first one, whose deposit is held by their spawner.
+		///
+		/// Parameters:
+		/// - `proxy`: The account that the `caller` would like to make a proxy.
+		/// - `proxy_type`: The permissions allowed for this proxy account.
+		///
+		/// # <weight>
+		/// - `O(P)` where `P` is the number of proxies of the sender.
+		/// - One storage read and write.
+		/// - One balance reserve operation.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
+		fn add_proxy(origin, proxy: T::AccountId, proxy_type: T::ProxyType) {
+			let who = ensure_signed(origin)?;
+			let (mut proxies, old_deposit) = Proxies::<T>::get(&who);
+			ensure!(proxies.len() < T::MaxProxies::get() as usize, Error::<T>::TooMany);
+			let typed_proxy = (proxy, proxy_type);
+			let i = proxies.binary_search(&typed_proxy).err().ok_or(Error::<T>::Duplicate)?;
+			proxies.insert(i, typed_proxy);
+			let new_deposit = Self::deposit(&who, proxies.len());
+			if new_deposit > old_deposit {
+				T::Currency::reserve(&who, new_deposit - old_deposit)?;
+			} else if new_deposit < old_deposit {
+				let _ = T::Currency::unreserve(&who, old_deposit - new_deposit);
+			}
+			Proxies::<T>::insert(who, (proxies, new_deposit));
+		}
+
+		/// Unregister a proxy account for the sender.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// Parameters:
+		/// - `proxy`: The account that the `caller` would like to remove as a proxy.
+		/// - `proxy_type`: The permissions currently enabled for the removed proxy account.
+		///
+		/// # <weight>
+		/// - `O(P)` where `P` is the number of proxies of the sender.
+		/// - One storage read and write.
+		/// - One balance unreserve operation.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
+		fn remove_proxy(origin, proxy: T::AccountId, proxy_type: T::ProxyType) {
+			let who = ensure_signed(origin)?;
+			let (mut proxies, old_deposit) = Proxies::<T>::get(&who);
+			let typed_proxy = (proxy, proxy_type);
+			let i = proxies.binary_search(&typed_proxy).ok().ok_or(Error::<T>::NotFound)?;
+			proxies.remove(i);
+			let new_deposit = Self::deposit(&who, proxies.len());
+			if new_deposit > old_deposit {
+				T::Currency::reserve(&who, new_deposit - old_deposit)?;
+			} else if new_deposit < old_deposit {
+				let _ = T::Currency::unreserve(&who, old_deposit - new_deposit);
+			}
+			if proxies.is_empty() {
+				Proxies::<T>::remove(&who);
+				Self::release_anonymous_deposit(&who);
+			} else {
+				Proxies::<T>::insert(&who, (proxies, new_deposit));
+			}
+		}
+
+		/// Unregister all proxy accounts for the sender.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// WARNING: This may be called on accounts created by `anonymous`, however if done, then
+		/// any funds held in the account will be inaccessible, and the deposit is returned to its
+		/// spawner. **All access to this account will be lost.**
+		///
+		/// # <weight>
+		/// - `O(P)` where `P` is the number of proxies of the sender.
+		/// - One storage read and removal.
+		/// - One balance unreserve operation.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
+		fn remove_proxies(origin) {
+			let who = ensure_signed(origin)?;
+			let (_, old_deposit) = Proxies::<T>::take(&who);
+			let _ = T::Currency::unreserve(&who, old_deposit);
+			Self::release_anonymous_deposit(&who);
+		}
+
+		/// Spawn a fresh new account that is guaranteed to be otherwise inaccessible, and
+		/// initialize it with a proxy of `proxy_type` for `origin` sender.
+		///
+		/// Requires a `Signed` origin.
+		///
+		/// - `proxy_type`: The type of the proxy that the sender will be registered as over the
+		/// new account. This will almost always be the most permissive `ProxyType` possible to
+		/// allow for maximum flexibility.
+		/// - `index`: A disambiguation index, in case this is called multiple times in the same
+		/// transaction (e.g. with `utility::batch`). Unless you're using `batch` you probably just
+		/// want to use `0`.
+		///
+		/// Fails with `Duplicate` if this has already been called in this transaction, from the
+		/// same sender, with the same parameters.
+		///
+		/// Fails if there are insufficient funds to pay for deposit. The deposit is reserved from
+		/// the sender, which gets it back once the proxies of the new account are removed.
+		///
+		/// # <weight>
+		/// - `O(1)`.
+		/// - One storage read and write.
+		/// - One balance reserve operation.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
+		fn anonymous(origin, proxy_type: T::ProxyType, index: u16) {
+			let who = ensure_signed(origin)?;
+
+			let anonymous = Self::anonymous_account(&who, &proxy_type, index, None);
+			ensure!(!Proxies::<T>::exists(&anonymous), Error::<T>::Duplicate);
+			let deposit = T::ProxyDepositBase::get() + T::ProxyDepositFactor::get();
+			T::Currency::reserve(&who, deposit)?;
+			let proxies = vec![(who.clone(), proxy_type.clone())];
+			Proxies::<T>::insert(&anonymous, (proxies, BalanceOf::<T>::zero()));
+			AnonymousDeposits::<T>::insert(&anonymous, (who.clone(), deposit));
+			Self::deposit_event(RawEvent::AnonymousCreated(anonymous, who, proxy_type, index));
+		}
+
+		/// Removes a previously spawned anonymous proxy.
+		///
+		/// WARNING: **All access to this account will be lost.** Any funds held in it will be
+		/// inaccessible.
+		///
+		/// Requires a `Signed` origin, and the sender account must have been created by a call to
+		/// `anonymous` with corresponding parameters.
+		///
+		/// - `spawner`: The account that originally called `anonymous` to create this account.
+		/// - `proxy_type`: The proxy type originally passed to `anonymous`.
+		/// - `index`: The disambiguation index originally passed to `anonymous`. Probably `0`.
+		/// - `height`: The height of the chain when the call to `anonymous` was processed.
+		/// - `ext_index`: The extrinsic index in which the call to `anonymous` was processed.
+		///
+		/// Fails with `NoPermission` in case the caller is not a previously created anonymous
+		/// account whose `anonymous` call has corresponding parameters.
+		///
+		/// # <weight>
+		/// - `O(1)`.
+		/// - Two storage reads and removals.
+		/// - Two balance unreserve operations.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
+		fn kill_anonymous(origin,
+			spawner: T::AccountId,
+			proxy_type: T::ProxyType,
+			index: u16,
+			#[compact] height: T::BlockNumber,
+			#[compact] ext_index: u32,
+		) {
+			let who = ensure_signed(origin)?;
+
+			let when = (height, ext_index);
+			let proxy = Self::anonymous_account(&spawner, &proxy_type, index, Some(when));
+			ensure!(proxy == who, Error::<T>::NoPermission);
+
+			let (_, deposit) = Proxies::<T>::take(&who);
+			let _ = T::Currency::unreserve(&who, deposit);
+			Self::release_anonymous_deposit(&who);
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Calculate the address of an anonymous account.
+	///
+	/// - `who`: The spawner account.
+	/// - `proxy_type`: The type of the proxy that the sender will be registered as over the
+	/// new account. This will almost always be the most permissive `ProxyType` possible to
+	/// allow for maximum flexibility.
+	/// - `index`: A disambiguation index, in case this is called multiple times in the same
+	/// transaction (e.g. with `utility::batch`). Unless you're using `batch` you probably just
+	/// want to use `0`.
+	/// - `maybe_when`: The block height and extrinsic index of when the anonymous account was
+	/// created. None to use current block height and extrinsic index.
+	pub fn anonymous_account(
+		who: &T::AccountId,
+		proxy_type: &T::ProxyType,
+		index: u16,
+		maybe_when: Option<(T::BlockNumber, u32)>,
+	) -> T::AccountId {
+		let (height, ext_index) = maybe_when.unwrap_or_else(|| (
+			system::Module::<T>::block_number(),
+			system::Module::<T>::extrinsic_index().unwrap_or_default()
+		));
+		let entropy = (b"modlpy/proxy____", who, height, ext_index, proxy_type, index)
+			.using_encoded(blake2_256);
+		T::AccountId::decode(&mut &entropy[..]).unwrap_or_default()
+	}
+
+	/// The deposit held by `who` for `count` proxies.
+	///
+	/// The deposit of an anonymous account only covers the proxies beyond the first one, the
+	/// spawner holds `ProxyDepositBase` and `ProxyDepositFactor` for it in `AnonymousDeposits`.
+	fn deposit(who: &T::AccountId, count: usize) -> BalanceOf<T> {
+		if count == 0 {
+			Zero::zero()
+		} else if AnonymousDeposits::<T>::exists(who) {
+			T::ProxyDepositFactor::get() * (count as u32 - 1).into()
+		} else {
+			T::ProxyDepositBase::get() + T::ProxyDepositFactor::get() * (count as u32).into()
+		}
+	}
+
+	/// Return the deposit held by the spawner of `who`, if `who` is an anonymous account.
+	fn release_anonymous_deposit(who: &T::AccountId) {
+		if let Some((spawner, deposit)) = AnonymousDeposits::<T>::take(who) {
+			let _ = T::Currency::unreserve(&spawner, deposit);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests;
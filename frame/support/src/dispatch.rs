@@ -202,6 +202,12 @@ impl<T> Parameter for T where T: Codec + EncodeLike + Clone + Eq + fmt::Debug {}
 /// * `offchain_worker`: Executes at the beginning of a block and produces extrinsics for a future block
 /// upon completion. Using this function will implement the
 /// [`OffchainWorker`](../sp_runtime/traits/trait.OffchainWorker.html) trait.
+///
+/// The following reserved function takes no input:
+///
+/// * `on_runtime_upgrade`: Executes at the beginning of the first block after a runtime upgrade,
+/// before `on_initialize`. Using this function will implement the
+/// [`OnRuntimeUpgrade`](../sp_runtime/traits/trait.OnRuntimeUpgrade.html) trait.
 #[macro_export]
 macro_rules! decl_module {
 	// Entry point #1.
@@ -228,6 +234,7 @@ macro_rules! decl_module {
 			{}
 			{}
 			{}
+			{}
 			[]
 			$($t)*
 		);
@@ -260,6 +267,7 @@ macro_rules! decl_module {
 			{}
 			{}
 			{}
+			{}
 			[]
 			$($t)*
 		);
@@ -274,6 +282,7 @@ macro_rules! decl_module {
 		{}
 		{ $( $on_initialize:tt )* }
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $( $error_type:tt )* }
@@ -290,6 +299,7 @@ macro_rules! decl_module {
 			{ $vis fn deposit_event() = default; }
 			{ $( $on_initialize )* }
 			{ $( $on_finalize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
 			{ $( $error_type )* }
@@ -305,6 +315,7 @@ macro_rules! decl_module {
 		{}
 		{ $( $on_initialize:tt )* }
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $( $error_type:tt )* }
@@ -326,6 +337,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
 		{}
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $( $error_type:tt )* }
@@ -345,6 +357,7 @@ macro_rules! decl_module {
 				#[weight = $crate::dispatch::SimpleDispatchInfo::zero()]
 				fn on_finalize( $( $param_name : $param ),* ) { $( $impl )* }
 			}
+			{ $( $on_runtime_upgrade )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
 			{ $( $error_type )* }
@@ -361,6 +374,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
 		{}
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $( $error_type:tt )* }
@@ -381,6 +395,7 @@ macro_rules! decl_module {
 				#[weight = $weight]
 				fn on_finalize( $( $param_name : $param ),* ) { $( $impl )* }
 			}
+			{ $( $on_runtime_upgrade )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
 			{ $( $error_type )* }
@@ -399,6 +414,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{}
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $( $error_type:tt )* }
@@ -418,6 +434,7 @@ macro_rules! decl_module {
 				fn on_initialize( $( $param_name : $param ),* ) { $( $impl )* }
 			}
 			{ $( $on_finalize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
 			{ $( $error_type )* }
@@ -436,6 +453,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{}
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $( $error_type:tt )* }
@@ -456,6 +474,43 @@ macro_rules! decl_module {
 				fn on_initialize( $( $param_name : $param ),* ) { $( $impl )* }
 			}
 			{ $( $on_finalize )* }
+			{ $( $on_runtime_upgrade )* }
+			{ $( $offchain )* }
+			{ $( $constants )* }
+			{ $( $error_type )* }
+			[ $( $dispatchables )* ]
+			$($rest)*
+		);
+	};
+	// Add on_runtime_upgrade.
+	(@normalize
+		$(#[$attr:meta])*
+		pub struct $mod_type:ident<
+			$trait_instance:ident: $trait_name:ident$(<I>, I: $instantiable:path $(= $module_default_instance:path)?)?
+		>
+		for enum $call_type:ident where origin: $origin_type:ty, system = $system:ident
+		{ $( $other_where_bounds:tt )* }
+		{ $( $deposit_event:tt )* }
+		{ $( $on_initialize:tt )* }
+		{ $( $on_finalize:tt )* }
+		{}
+		{ $( $offchain:tt )* }
+		{ $( $constants:tt )* }
+		{ $( $error_type:tt )* }
+		[ $( $dispatchables:tt )* ]
+		$(#[doc = $doc_attr:tt])*
+		fn on_runtime_upgrade() { $( $impl:tt )* }
+		$($rest:tt)*
+	) => {
+		$crate::decl_module!(@normalize
+			$(#[$attr])*
+			pub struct $mod_type<$trait_instance: $trait_name$(<I>, I: $instantiable $(= $module_default_instance)?)?>
+			for enum $call_type where origin: $origin_type, system = $system
+			{ $( $other_where_bounds )* }
+			{ $( $deposit_event )* }
+			{ $( $on_initialize )* }
+			{ $( $on_finalize )* }
+			{ fn on_runtime_upgrade() { $( $impl )* } }
 			{ $( $offchain )* }
 			{ $( $constants )* }
 			{ $( $error_type )* }
@@ -474,6 +529,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ }
 		{ $( $constants:tt )* }
 		{ $( $error_type:tt )* }
@@ -492,6 +548,7 @@ macro_rules! decl_module {
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
 			{ $( $on_finalize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ fn offchain_worker( $( $param_name : $param ),* ) { $( $impl )* } }
 			{ $( $constants )* }
 			{ $( $error_type )* }
@@ -512,6 +569,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $( $error_type:tt )* }
@@ -531,6 +589,7 @@ macro_rules! decl_module {
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
 			{ $( $on_finalize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $offchain )* }
 			{
 				$( $constants )*
@@ -555,6 +614,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ }
@@ -573,6 +633,7 @@ macro_rules! decl_module {
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
 			{ $( $on_finalize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
 			{ $error_type }
@@ -592,6 +653,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ }
@@ -608,6 +670,7 @@ macro_rules! decl_module {
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
 			{ $( $on_finalize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
 			{ &'static str }
@@ -628,6 +691,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $error_type:ty }
@@ -649,6 +713,7 @@ macro_rules! decl_module {
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
 			{ $( $on_finalize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
 			{ $error_type }
@@ -676,6 +741,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $( $error_type:tt )* }
@@ -696,6 +762,7 @@ macro_rules! decl_module {
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
 			{ $( $on_finalize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
 			{ $( $error_type )* }
@@ -717,6 +784,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $( $error_type:tt )* }
@@ -742,6 +810,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $( $error_type:tt )* }
@@ -767,6 +836,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $( $error_type:tt )* }
@@ -793,6 +863,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $( $error_type:tt )* }
@@ -808,6 +879,7 @@ macro_rules! decl_module {
 			{ $( $deposit_event )* }
 			{ $( $on_initialize )* }
 			{ $( $on_finalize )* }
+			{ $( $on_runtime_upgrade )* }
 			{ $( $offchain )* }
 			{ $( $constants )* }
 			{ $( $error_type )* }
@@ -986,6 +1058,37 @@ macro_rules! decl_module {
 		}
 	};
 
+	(@impl_on_runtime_upgrade
+		$module:ident<$trait_instance:ident: $trait_name:ident$(<I>, $instance:ident: $instantiable:path)?>;
+		{ $( $other_where_bounds:tt )* }
+		fn on_runtime_upgrade() { $( $impl:tt )* }
+	) => {
+		impl<$trait_instance: $trait_name$(<I>, $instance: $instantiable)?>
+			$crate::sp_runtime::traits::OnRuntimeUpgrade
+			for $module<$trait_instance$(, $instance)?> where $( $other_where_bounds )*
+		{
+			fn on_runtime_upgrade() {
+				use $crate::sp_std::if_std;
+				if_std! {
+					use $crate::tracing;
+					let span = tracing::span!(tracing::Level::DEBUG, "on_runtime_upgrade");
+					let _enter = span.enter();
+				}
+				{ $( $impl )* }
+			}
+		}
+	};
+
+	(@impl_on_runtime_upgrade
+		$module:ident<$trait_instance:ident: $trait_name:ident$(<I>, $instance:ident: $instantiable:path)?>;
+		{ $( $other_where_bounds:tt )* }
+	) => {
+		impl<$trait_instance: $trait_name$(<I>, $instance: $instantiable)?>
+			$crate::sp_runtime::traits::OnRuntimeUpgrade
+			for $module<$trait_instance$(, $instance)?> where $( $other_where_bounds )*
+		{}
+	};
+
 	(@impl_offchain
 		$module:ident<$trait_instance:ident: $trait_name:ident$(<I>, $instance:ident: $instantiable:path)?>;
 		{ $( $other_where_bounds:tt )* }
@@ -1208,6 +1311,7 @@ macro_rules! decl_module {
 		{ $( $deposit_event:tt )* }
 		{ $( $on_initialize:tt )* }
 		{ $( $on_finalize:tt )* }
+		{ $( $on_runtime_upgrade:tt )* }
 		{ $( $offchain:tt )* }
 		{ $( $constants:tt )* }
 		{ $error_type:ty }
@@ -1245,6 +1349,13 @@ macro_rules! decl_module {
 			@fin $( $on_finalize )*
 		}
 
+		$crate::decl_module! {
+			@impl_on_runtime_upgrade
+			$mod_type<$trait_instance: $trait_name $(<I>, $instance: $instantiable)?>;
+			{ $( $other_where_bounds )* }
+			$( $on_runtime_upgrade )*
+		}
+
 		$crate::decl_module! {
 			@impl_offchain
 			$mod_type<$trait_instance: $trait_name $(<I>, $instance: $instantiable)?>;
@@ -1836,6 +1947,9 @@ macro_rules! __check_reserved_fn_name {
 	(offchain_worker $( $rest:ident )*) => {
 		$crate::__check_reserved_fn_name!(@compile_error offchain_worker);
 	};
+	(on_runtime_upgrade $( $rest:ident )*) => {
+		$crate::__check_reserved_fn_name!(@compile_error on_runtime_upgrade);
+	};
 	($t:ident $( $rest:ident )*) => {
 		$crate::__check_reserved_fn_name!($( $rest )*);
 	};
@@ -1869,7 +1983,7 @@ macro_rules! __check_reserved_fn_name {
 #[allow(dead_code)]
 mod tests {
 	use super::*;
-	use crate::sp_runtime::traits::{OnInitialize, OnFinalize};
+	use crate::sp_runtime::traits::{OnInitialize, OnFinalize, OnRuntimeUpgrade};
 	use crate::weights::{DispatchInfo, DispatchClass};
 
 	pub trait Trait: system::Trait + Sized where Self::AccountId: From<u32> {
@@ -1913,6 +2027,7 @@ mod tests {
 			fn on_initialize(n: T::BlockNumber,) { if n.into() == 42 { panic!("on_initialize") } }
 			#[weight = BLockWeight]
 			fn on_finalize(n: T::BlockNumber) { if n.into() == 42 { panic!("on_finalize") } }
+			fn on_runtime_upgrade() { panic!("on_runtime_upgrade") }
 			fn offchain_worker() {}
 
 			#[weight = SimpleDispatchInfo::FixedOperational(5)]
@@ -2054,6 +2169,12 @@ mod tests {
 		<Module<TraitImpl> as OnFinalize<u32>>::on_finalize(42);
 	}
 
+	#[test]
+	#[should_panic(expected = "on_runtime_upgrade")]
+	fn on_runtime_upgrade_should_work() {
+		<Module<TraitImpl> as OnRuntimeUpgrade>::on_runtime_upgrade();
+	}
+
 	#[test]
 	fn weight_should_attach_to_call_enum() {
 		// operational.
@@ -0,0 +1,116 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Helpers for migrating the maps of other or previous storage layouts, whose keys can't be
+//! recovered from their hashes.
+//!
+//! Items are addressed by the name of their module, the name of the map and the hash of their key,
+//! i.e. the storage key without the `twox_128(module) ++ twox_128(item)` prefix.
+
+use sp_std::prelude::*;
+use codec::{Encode, Decode};
+use crate::{storage::unhashed, hash::{Twox128, StorageHasher}};
+
+/// Iterator over the items of the map `item` of `module`, yielding the hash of the key and the
+/// value of every item. Items whose value can't be decoded are skipped.
+pub struct StorageIterator<T> {
+	prefix: [u8; 32],
+	previous_key: Vec<u8>,
+	drain: bool,
+	_phantom: sp_std::marker::PhantomData<T>,
+}
+
+impl<T> StorageIterator<T> {
+	/// Iterate over the items of the map `item` of `module`.
+	pub fn new(module: &[u8], item: &[u8]) -> Self {
+		let prefix = storage_prefix(module, item);
+		Self {
+			prefix,
+			previous_key: prefix.to_vec(),
+			drain: false,
+			_phantom: Default::default(),
+		}
+	}
+
+	/// Remove the items from storage as they are iterated over.
+	pub fn drain(mut self) -> Self {
+		self.drain = true;
+		self
+	}
+}
+
+impl<T: Decode + Sized> Iterator for StorageIterator<T> {
+	type Item = (Vec<u8>, T);
+
+	fn next(&mut self) -> Option<(Vec<u8>, T)> {
+		loop {
+			let next = sp_io::storage::next_key(&self.previous_key)
+				.filter(|next| next.starts_with(&self.prefix))?;
+			let value = unhashed::get::<T>(&next);
+			if self.drain {
+				unhashed::kill(&next);
+			}
+			let hash = next[self.prefix.len()..].to_vec();
+			self.previous_key = next;
+			if let Some(value) = value {
+				return Some((hash, value))
+			}
+		}
+	}
+}
+
+/// Get the value of the item of the map `item` of `module` whose key hashes to `hash`.
+pub fn get_storage_value<T: Decode + Sized>(module: &[u8], item: &[u8], hash: &[u8]) -> Option<T> {
+	unhashed::get(&storage_key(module, item, hash))
+}
+
+/// Put `value` into the item of the map `item` of `module` whose key hashes to `hash`.
+pub fn put_storage_value<T: Encode>(module: &[u8], item: &[u8], hash: &[u8], value: T) {
+	unhashed::put(&storage_key(module, item, hash), &value);
+}
+
+fn storage_prefix(module: &[u8], item: &[u8]) -> [u8; 32] {
+	let mut prefix = [0u8; 32];
+	prefix[0..16].copy_from_slice(&Twox128::hash(module));
+	prefix[16..32].copy_from_slice(&Twox128::hash(item));
+	prefix
+}
+
+fn storage_key(module: &[u8], item: &[u8], hash: &[u8]) -> Vec<u8> {
+	let mut key = storage_prefix(module, item).to_vec();
+	key.extend_from_slice(hash);
+	key
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_io::TestExternalities;
+
+	#[test]
+	fn drained_items_are_yielded_with_their_hash() {
+		TestExternalities::default().execute_with(|| {
+			put_storage_value(b"Module", b"Map", &[1, 2], 3u32);
+			put_storage_value(b"Module", b"Map", &[4], 5u32);
+			put_storage_value(b"Module", b"Other", &[6], 7u32);
+
+			let items = StorageIterator::<u32>::new(b"Module", b"Map").drain().collect::<Vec<_>>();
+			assert_eq!(items, vec![(vec![1, 2], 3), (vec![4], 5)]);
+			assert_eq!(get_storage_value::<u32>(b"Module", b"Map", &[1, 2]), None);
+			assert_eq!(get_storage_value::<u32>(b"Module", b"Other", &[6]), Some(7));
+		});
+	}
+}
@@ -23,9 +23,36 @@ use crate::{traits::Len, hash::{Twox128, StorageHasher}};
 pub mod unhashed;
 pub mod hashed;
 pub mod child;
+pub mod migration;
 #[doc(hidden)]
 pub mod generator;
 
+/// Describes whether a storage transaction should be committed or rolled back.
+pub enum TransactionOutcome<R> {
+	/// Commit the transaction.
+	Commit(R),
+	/// Rollback the transaction.
+	Rollback(R),
+}
+
+/// Execute the supplied function in a new storage transaction.
+///
+/// All changes to storage performed by the supplied function are discarded if the returned
+/// outcome is `TransactionOutcome::Rollback`.
+///
+/// Transactions can be nested to any depth. Commits happen to the parent transaction.
+pub fn with_transaction<R>(f: impl FnOnce() -> TransactionOutcome<R>) -> R {
+	use sp_io::storage::{start_transaction, commit_transaction, rollback_transaction};
+	use TransactionOutcome::*;
+
+	start_transaction();
+
+	match f() {
+		Commit(res) => { commit_transaction(); res },
+		Rollback(res) => { rollback_transaction(); res },
+	}
+}
+
 /// A trait for working with macro-generated storage values under the substrate storage API.
 ///
 /// Details on implementation can be found at
@@ -477,7 +504,7 @@ pub trait StoragePrefixedMap<Value: FullCodec> {
 mod test {
 	use sp_core::hashing::twox_128;
 	use sp_io::TestExternalities;
-	use crate::storage::{unhashed, StoragePrefixedMap};
+	use crate::storage::{unhashed, StoragePrefixedMap, with_transaction, TransactionOutcome::*};
 
 	#[test]
 	fn prefixed_map_works() {
@@ -552,4 +579,40 @@ mod test {
 			assert_eq!(unhashed::get(&key_after[..]), Some(33u64));
 		});
 	}
+
+	#[test]
+	fn transactional_works() {
+		TestExternalities::default().execute_with(|| {
+			unhashed::put(b"key", &1u32);
+
+			let res = with_transaction(|| {
+				unhashed::put(b"key", &2u32);
+				unhashed::put(b"other", &3u32);
+
+				let inner = with_transaction(|| {
+					unhashed::put(b"key", &4u32);
+					Rollback(5u32)
+				});
+				assert_eq!(unhashed::get(b"key"), Some(2u32));
+
+				with_transaction(|| {
+					unhashed::kill(b"other");
+					Commit(())
+				});
+				assert_eq!(unhashed::get::<u32>(b"other"), None);
+
+				Rollback(inner)
+			});
+
+			assert_eq!(res, 5);
+			assert_eq!(unhashed::get(b"key"), Some(1u32));
+			assert_eq!(unhashed::get::<u32>(b"other"), None);
+
+			with_transaction(|| {
+				unhashed::put(b"key", &6u32);
+				Commit(())
+			});
+			assert_eq!(unhashed::get(b"key"), Some(6u32));
+		});
+	}
 }
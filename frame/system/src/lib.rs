@@ -472,6 +472,10 @@ decl_storage! {
 		/// the `EventIndex` then in case if the topic has the same contents on the next block
 		/// no notification will be triggered thus the event might be lost.
 		EventTopics get(fn event_topics): map T::Hash => Vec<(T::BlockNumber, EventIndex)>;
+
+		/// The `spec_version` of the runtime that last ran the runtime upgrade hooks, `None` if
+		/// they never ran.
+		pub LastRuntimeUpgrade get(fn last_runtime_upgrade): Option<u32>;
 	}
 	add_extra_genesis {
 		config(changes_trie_config): Option<ChangesTrieConfiguration>;
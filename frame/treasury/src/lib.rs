@@ -43,6 +43,22 @@
 //! countdown period, the median of all declared tips is paid to the reported beneficiary, along
 //! with any finders fee, in case of a public (and bonded) original report.
 //!
+//! ### Bounty
+//!
+//! A Bounty Spending is a reward for a specified body of work - or specified set of objectives -
+//! that needs to be executed for a predefined Treasury amount to be paid out. A curator is assigned
+//! after the bounty is approved and funded by Council, to be delegated with the responsibility of
+//! assigning a payout address once the specified set of objectives is completed.
+//!
+//! After the Council has activated a bounty, it delegates the work that requires expertise to a
+//! curator in exchange of a deposit. Once the curator accepts the bounty, they get to close the
+//! active bounty. Closing the active bounty enacts a delayed payout to the payout address, the
+//! curator fee and the return of the curator deposit. The delay allows for intervention through
+//! regular democracy. The Council gets to unassign the curator, resulting in a new curator
+//! election. The Council also gets to cancel the bounty if deemed necessary before assigning a
+//! curator or once the bounty is active or payout is pending, resulting in the slash of the
+//! curator's deposit.
+//!
 //! ### Terminology
 //!
 //! - **Proposal:** A suggestion to allocate funds from the pot to a beneficiary.
@@ -63,6 +79,23 @@
 //! - **Finders Fee:** Some proportion of the tip amount that is paid to the reporter of the tip,
 //!   rather than the main beneficiary.
 //!
+//! Bounty:
+//! - **Bounty spending proposal:** A proposal to reward a predefined body of work upon completion
+//!   by the Treasury.
+//! - **Proposer:** An account proposing a bounty spending.
+//! - **Curator:** An account managing the bounty and assigning a payout address receiving the
+//!   reward for the completion of work.
+//! - **Deposit:** The amount held on deposit for placing a bounty proposal plus the amount held on
+//!   deposit per byte within the bounty description.
+//! - **Curator deposit:** The payment from a candidate willing to curate an approved bounty. The
+//!   deposit is returned when/if the bounty is completed.
+//! - **Bounty value:** The total amount that should be paid to the Payout Address if the bounty is
+//!   rewarded.
+//! - **Payout address:** The account to which the total or part of the bounty is assigned to.
+//! - **Payout Delay:** The delay period for which a bounty beneficiary needs to wait before
+//!   claiming.
+//! - **Curator fee:** The reserved upfront payment for a curator for work related to the bounty.
+//!
 //! ## Interface
 //!
 //! ### Dispatchable Functions
@@ -81,6 +114,19 @@
 //! - `tip` - Declare or redeclare an amount to tip for a particular reason.
 //! - `close_tip` - Close and pay out a tip.
 //!
+//! Bounty protocol:
+//! - `propose_bounty` - Propose a specific treasury amount to be earmarked for a predefined set of
+//!   tasks and stake the required deposit.
+//! - `approve_bounty` - Accept a specific treasury amount to be earmarked for a predefined body of
+//!   work.
+//! - `propose_curator` - Assign an account to a bounty as candidate curator.
+//! - `accept_curator` - Accept a bounty assignment from the Council, setting a curator deposit.
+//! - `extend_bounty_expiry` - Extend the expiry block number of the bounty and stay active.
+//! - `award_bounty` - Close and pay out the specified amount for the completed work.
+//! - `claim_bounty` - Claim a specific bounty amount from the Payout Address.
+//! - `unassign_curator` - Unassign an accepted curator from a specific earmark.
+//! - `close_bounty` - Cancel the earmark for a specific treasury amount and close the bounty.
+//!
 //! ## GenesisConfig
 //!
 //! The Treasury module depends on the [`GenesisConfig`](./struct.GenesisConfig.html).
@@ -99,7 +145,7 @@ use sp_runtime::{Permill, ModuleId, Percent, RuntimeDebug, traits::{
 	Zero, EnsureOrigin, StaticLookup, AccountIdConversion, Saturating, Hash, BadOrigin
 }};
 use frame_support::{weights::SimpleDispatchInfo, traits::Contains};
-use codec::{Encode, Decode};
+use codec::{Encode, Decode, Compact};
 use frame_system::{self as system, ensure_signed};
 
 type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
@@ -109,6 +155,9 @@ type NegativeImbalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_sy
 /// The treasury's module id, used for deriving its sovereign account ID.
 const MODULE_ID: ModuleId = ModuleId(*b"py/trsry");
 
+/// The maximum length of a tip reason or a bounty description.
+const MAX_SENSIBLE_REASON_LENGTH: usize = 16384;
+
 pub trait Trait: frame_system::Trait {
 	/// The staking balance.
 	type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
@@ -152,6 +201,25 @@ pub trait Trait: frame_system::Trait {
 
 	/// Percentage of spare funds (if any) that are burnt per spend period.
 	type Burn: Get<Permill>;
+
+	/// The amount held on deposit for placing a bounty proposal.
+	type BountyDepositBase: Get<BalanceOf<Self>>;
+
+	/// The amount held on deposit per byte within the bounty description.
+	type BountyDepositPerByte: Get<BalanceOf<Self>>;
+
+	/// The delay period for which a bounty beneficiary needs to wait before claiming the payout.
+	type BountyDepositPayoutDelay: Get<Self::BlockNumber>;
+
+	/// The period after which an active curator has to give an update or can be unassigned by
+	/// anyone.
+	type BountyUpdatePeriod: Get<Self::BlockNumber>;
+
+	/// Percentage of the curator fee that will be reserved upfront as deposit for bounty curator.
+	type BountyCuratorDeposit: Get<Permill>;
+
+	/// Minimum value for a bounty.
+	type BountyValueMinimum: Get<BalanceOf<Self>>;
 }
 
 /// An index of a proposal. Just a `u32`.
@@ -194,6 +262,59 @@ pub struct OpenTip<
 	tips: Vec<(AccountId, Balance)>,
 }
 
+/// An index of a bounty. Just a `u32`.
+pub type BountyIndex = u32;
+
+/// A bounty proposal.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Bounty<AccountId, Balance, BlockNumber> {
+	/// The account proposing it.
+	proposer: AccountId,
+	/// The (total) amount that should be paid if the bounty is rewarded.
+	value: Balance,
+	/// The curator fee. Included in value.
+	fee: Balance,
+	/// The deposit of curator.
+	curator_deposit: Balance,
+	/// The amount held on deposit (reserved) for making this proposal.
+	bond: Balance,
+	/// The status of this bounty.
+	status: BountyStatus<AccountId, BlockNumber>,
+}
+
+/// The status of a bounty proposal.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum BountyStatus<AccountId, BlockNumber> {
+	/// The bounty is proposed and waiting for approval.
+	Proposed,
+	/// The bounty is approved and waiting to become active at next spend period.
+	Approved,
+	/// The bounty is funded and waiting for curator assignment.
+	Funded,
+	/// A curator has been proposed by the `ApproveOrigin`. Waiting for acceptance from the
+	/// curator.
+	CuratorProposed {
+		/// The assigned curator of this bounty.
+		curator: AccountId,
+	},
+	/// The bounty is active and waiting to be awarded.
+	Active {
+		/// The curator of this bounty.
+		curator: AccountId,
+		/// An update from the curator is due by this block, else they are considered inactive.
+		update_due: BlockNumber,
+	},
+	/// The bounty is awarded and waiting to released after a delay.
+	PendingPayout {
+		/// The curator of this bounty.
+		curator: AccountId,
+		/// The beneficiary of the bounty.
+		beneficiary: AccountId,
+		/// When the bounty can be claimed.
+		unlock_at: BlockNumber,
+	},
+}
+
 decl_storage! {
 	trait Store for Module<T: Trait> as Treasury {
 		/// Number of proposals that have been made.
@@ -214,6 +335,20 @@ decl_storage! {
 		/// Simple preimage lookup from the reason's hash to the original data. Again, has an
 		/// insecure enumerable hash since the key is guaranteed to be the result of a secure hash.
 		pub Reasons get(fn reasons): map hasher(twox_64_concat) T::Hash => Option<Vec<u8>>;
+
+		/// Number of bounty proposals that have been made.
+		pub BountyCount get(fn bounty_count): BountyIndex;
+
+		/// Bounties that have been made.
+		pub Bounties get(fn bounties):
+			map hasher(twox_64_concat) BountyIndex
+			=> Option<Bounty<T::AccountId, BalanceOf<T>, T::BlockNumber>>;
+
+		/// The description of each bounty.
+		pub BountyDescriptions get(fn bounty_descriptions): map hasher(twox_64_concat) BountyIndex => Option<Vec<u8>>;
+
+		/// Bounty indices that have been approved but not yet funded.
+		pub BountyApprovals get(fn bounty_approvals): Vec<BountyIndex>;
 	}
 	add_extra_genesis {
 		build(|_config| {
@@ -255,6 +390,20 @@ decl_event!(
 		TipClosed(Hash, AccountId, Balance),
 		/// A tip suggestion has been retracted.
 		TipRetracted(Hash),
+		/// New bounty proposal.
+		BountyProposed(BountyIndex),
+		/// A bounty proposal was rejected; funds were slashed.
+		BountyRejected(BountyIndex, Balance),
+		/// A bounty proposal is funded and became active.
+		BountyBecameActive(BountyIndex),
+		/// A bounty is awarded to a beneficiary.
+		BountyAwarded(BountyIndex, AccountId),
+		/// A bounty is claimed by beneficiary.
+		BountyClaimed(BountyIndex, Balance, AccountId),
+		/// A bounty is cancelled.
+		BountyCanceled(BountyIndex),
+		/// A bounty expiry is extended.
+		BountyExtended(BountyIndex),
 	}
 );
 
@@ -277,6 +426,21 @@ decl_error! {
 		StillOpen,
 		/// The tip cannot be claimed/closed because it's still in the countdown period.
 		Premature,
+		/// No bounty at that index.
+		InvalidBountyIndex,
+		/// The bounty value is too low.
+		InvalidValue,
+		/// The bounty status is unexpected.
+		UnexpectedStatus,
+		/// Require bounty curator.
+		RequireCurator,
+		/// Invalid bounty fee.
+		InvalidFee,
+		/// A bounty payout is pending.
+		/// To cancel the bounty, you must unassign and slash the curator.
+		PendingPayout,
+		/// The bounty index does not fit into the account ID of a bounty account.
+		BountyIndexOverflow,
 	}
 }
 
@@ -307,6 +471,25 @@ decl_module! {
 		/// The amount held on deposit per byte within the tip report reason.
 		const TipReportDepositPerByte: BalanceOf<T> = T::TipReportDepositPerByte::get();
 
+		/// The amount held on deposit for placing a bounty proposal.
+		const BountyDepositBase: BalanceOf<T> = T::BountyDepositBase::get();
+
+		/// The amount held on deposit per byte within the bounty description.
+		const BountyDepositPerByte: BalanceOf<T> = T::BountyDepositPerByte::get();
+
+		/// The delay period for which a bounty beneficiary needs to wait before claiming the payout.
+		const BountyDepositPayoutDelay: T::BlockNumber = T::BountyDepositPayoutDelay::get();
+
+		/// The period after which an active curator has to give an update or can be unassigned.
+		const BountyUpdatePeriod: T::BlockNumber = T::BountyUpdatePeriod::get();
+
+		/// Percentage of the curator fee that will be reserved upfront as deposit for bounty
+		/// curator.
+		const BountyCuratorDeposit: Permill = T::BountyCuratorDeposit::get();
+
+		/// Minimum value for a bounty.
+		const BountyValueMinimum: BalanceOf<T> = T::BountyValueMinimum::get();
+
 		type Error = Error<T>;
 
 		fn deposit_event() = default;
@@ -399,7 +582,6 @@ decl_module! {
 		fn report_awesome(origin, reason: Vec<u8>, who: T::AccountId) {
 			let finder = ensure_signed(origin)?;
 
-			const MAX_SENSIBLE_REASON_LENGTH: usize = 16384;
 			ensure!(reason.len() <= MAX_SENSIBLE_REASON_LENGTH, Error::<T>::ReasonTooBig);
 
 			let reason_hash = T::Hashing::hash(&reason[..]);
@@ -542,6 +724,401 @@ decl_module! {
 			Self::payout_tip(tip);
 		}
 
+		/// Propose a new bounty.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// Payment: `BountyDepositBase` will be reserved from the origin account, as well as
+		/// `BountyDepositPerByte` for each byte in `description`. It will be unreserved upon
+		/// approval, or slashed when rejected.
+		///
+		/// - `value`: The total payment amount of this bounty, curator fee included.
+		/// - `description`: The description of this bounty.
+		///
+		/// # <weight>
+		/// - `O(D)` where `D` is the length of `description`.
+		/// - One balance operation.
+		/// - Two storage insertions (codec `O(D)`), one storage mutation.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
+		fn propose_bounty(origin, #[compact] value: BalanceOf<T>, description: Vec<u8>) {
+			let proposer = ensure_signed(origin)?;
+			ensure!(description.len() <= MAX_SENSIBLE_REASON_LENGTH, Error::<T>::ReasonTooBig);
+			ensure!(value >= T::BountyValueMinimum::get(), Error::<T>::InvalidValue);
+			let index = Self::bounty_count();
+			ensure!(Self::bounty_account_fits(index), Error::<T>::BountyIndexOverflow);
+
+			let bond = T::BountyDepositBase::get()
+				+ T::BountyDepositPerByte::get() * (description.len() as u32).into();
+			T::Currency::reserve(&proposer, bond)
+				.map_err(|_| Error::<T>::InsufficientProposersBalance)?;
+
+			BountyCount::put(index + 1);
+
+			let bounty = Bounty {
+				proposer,
+				value,
+				fee: Zero::zero(),
+				curator_deposit: Zero::zero(),
+				bond,
+				status: BountyStatus::Proposed,
+			};
+			<Bounties<T>>::insert(index, &bounty);
+			BountyDescriptions::insert(index, description);
+
+			Self::deposit_event(RawEvent::BountyProposed(index));
+		}
+
+		/// Approve a bounty proposal. At a later time, the bounty will be funded and become active
+		/// and the original deposit will be returned.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - Two DB changes.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		fn approve_bounty(origin, #[compact] bounty_id: BountyIndex) {
+			T::ApproveOrigin::ensure_origin(origin)?;
+
+			let mut bounty = Self::bounties(bounty_id).ok_or(Error::<T>::InvalidBountyIndex)?;
+			ensure!(bounty.status == BountyStatus::Proposed, Error::<T>::UnexpectedStatus);
+
+			bounty.status = BountyStatus::Approved;
+			<Bounties<T>>::insert(bounty_id, bounty);
+			BountyApprovals::mutate(|v| v.push(bounty_id));
+		}
+
+		/// Assign a curator to a funded bounty.
+		///
+		/// - `curator`: The proposed curator, who has to accept the assignment.
+		/// - `fee`: The part of the bounty value paid to the curator, which must be lower than
+		///   the bounty value.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - One DB change.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		fn propose_curator(
+			origin,
+			#[compact] bounty_id: BountyIndex,
+			curator: <T::Lookup as StaticLookup>::Source,
+			#[compact] fee: BalanceOf<T>,
+		) {
+			T::ApproveOrigin::ensure_origin(origin)?;
+			let curator = T::Lookup::lookup(curator)?;
+
+			let mut bounty = Self::bounties(bounty_id).ok_or(Error::<T>::InvalidBountyIndex)?;
+			ensure!(bounty.status == BountyStatus::Funded, Error::<T>::UnexpectedStatus);
+			ensure!(fee < bounty.value, Error::<T>::InvalidFee);
+
+			bounty.status = BountyStatus::CuratorProposed { curator };
+			bounty.fee = fee;
+			<Bounties<T>>::insert(bounty_id, bounty);
+		}
+
+		/// Unassign curator from a bounty.
+		///
+		/// This function can only be called by the `RejectOrigin` or a signed origin.
+		///
+		/// If this function is called by the `RejectOrigin`, we assume that the curator is
+		/// malicious or inactive. As a result, we will slash the curator when possible.
+		///
+		/// If the origin is the curator, we take this as a sign they are unable to do their job
+		/// and they willingly give up. We could slash them, but for now we allow them to recover
+		/// their deposit and exit without issue. (We may want to change this if it is abused.)
+		///
+		/// Finally, the origin can be anyone if and only if the curator is "inactive". This allows
+		/// anyone in the community to call out that a curator is not doing their due diligence, and
+		/// we should pick a new curator. In this case the curator should also be slashed.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - One DB change.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		fn unassign_curator(origin, #[compact] bounty_id: BountyIndex) {
+			let maybe_sender = ensure_signed(origin.clone())
+				.map(Some)
+				.or_else(|_| T::RejectOrigin::ensure_origin(origin).map(|_| None))?;
+
+			let mut bounty = Self::bounties(bounty_id).ok_or(Error::<T>::InvalidBountyIndex)?;
+
+			let slash_curator = |curator: &T::AccountId, curator_deposit: &mut BalanceOf<T>| {
+				let imbalance = T::Currency::slash_reserved(curator, *curator_deposit).0;
+				T::ProposalRejection::on_unbalanced(imbalance);
+				*curator_deposit = Zero::zero();
+			};
+
+			match bounty.status {
+				BountyStatus::Proposed | BountyStatus::Approved | BountyStatus::Funded => {
+					// No curator to unassign at this point.
+					Err(Error::<T>::UnexpectedStatus)?
+				},
+				BountyStatus::CuratorProposed { ref curator } => {
+					// A curator has been proposed, but not accepted yet.
+					// Either `RejectOrigin` or the proposed curator can unassign the curator.
+					ensure!(maybe_sender.map_or(true, |sender| sender == *curator), BadOrigin);
+				},
+				BountyStatus::Active { ref curator, ref update_due } => {
+					// The bounty is active.
+					match maybe_sender {
+						// If the `RejectOrigin` is calling this function, slash the curator.
+						None => slash_curator(curator, &mut bounty.curator_deposit),
+						Some(sender) => {
+							if sender != *curator {
+								// If the sender is not the curator, and the curator is inactive,
+								// slash the curator.
+								let block_number = system::Module::<T>::block_number();
+								ensure!(*update_due < block_number, Error::<T>::Premature);
+								slash_curator(curator, &mut bounty.curator_deposit);
+							} else {
+								// Else this is the curator, willingly giving up their role.
+								// Give back their deposit.
+								let _ = T::Currency::unreserve(curator, bounty.curator_deposit);
+								bounty.curator_deposit = Zero::zero();
+							}
+						},
+					}
+				},
+				BountyStatus::PendingPayout { ref curator, .. } => {
+					// The bounty is pending payout, so only the `RejectOrigin` can unassign a
+					// curator. By doing so, they claim the curator is acting maliciously, so we
+					// slash the curator.
+					ensure!(maybe_sender.is_none(), BadOrigin);
+					slash_curator(curator, &mut bounty.curator_deposit);
+				}
+			}
+
+			bounty.status = BountyStatus::Funded;
+			<Bounties<T>>::insert(bounty_id, bounty);
+		}
+
+		/// Accept the curator role for a bounty.
+		/// A deposit will be reserved from curator and refund upon successful payout.
+		///
+		/// May only be called from the curator.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - One balance operation.
+		/// - One DB change.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn accept_curator(origin, #[compact] bounty_id: BountyIndex) {
+			let signer = ensure_signed(origin)?;
+
+			let mut bounty = Self::bounties(bounty_id).ok_or(Error::<T>::InvalidBountyIndex)?;
+			match bounty.status {
+				BountyStatus::CuratorProposed { ref curator } => {
+					ensure!(signer == *curator, Error::<T>::RequireCurator);
+				},
+				_ => Err(Error::<T>::UnexpectedStatus)?,
+			}
+
+			let deposit = T::BountyCuratorDeposit::get() * bounty.fee;
+			T::Currency::reserve(&signer, deposit)?;
+			bounty.curator_deposit = deposit;
+
+			let update_due = system::Module::<T>::block_number() + T::BountyUpdatePeriod::get();
+			bounty.status = BountyStatus::Active { curator: signer, update_due };
+			<Bounties<T>>::insert(bounty_id, bounty);
+		}
+
+		/// Award bounty to a beneficiary account. The beneficiary will be able to claim the funds
+		/// after a delay.
+		///
+		/// The dispatch origin for this call must be the curator of this bounty.
+		///
+		/// - `bounty_id`: Bounty ID to award.
+		/// - `beneficiary`: The beneficiary account whom will receive the payout.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - One DB change.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn award_bounty(
+			origin,
+			#[compact] bounty_id: BountyIndex,
+			beneficiary: <T::Lookup as StaticLookup>::Source,
+		) {
+			let signer = ensure_signed(origin)?;
+			let beneficiary = T::Lookup::lookup(beneficiary)?;
+
+			let mut bounty = Self::bounties(bounty_id).ok_or(Error::<T>::InvalidBountyIndex)?;
+			match bounty.status {
+				BountyStatus::Active { ref curator, .. } => {
+					ensure!(signer == *curator, Error::<T>::RequireCurator);
+				},
+				_ => Err(Error::<T>::UnexpectedStatus)?,
+			}
+
+			let unlock_at = system::Module::<T>::block_number() + T::BountyDepositPayoutDelay::get();
+			bounty.status = BountyStatus::PendingPayout {
+				curator: signer,
+				beneficiary: beneficiary.clone(),
+				unlock_at,
+			};
+			<Bounties<T>>::insert(bounty_id, bounty);
+
+			Self::deposit_event(RawEvent::BountyAwarded(bounty_id, beneficiary));
+		}
+
+		/// Claim the payout from an awarded bounty after payout delay.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `bounty_id`: Bounty ID to claim.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - Up to four balance operations.
+		/// - Two DB removals.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn claim_bounty(origin, #[compact] bounty_id: BountyIndex) {
+			ensure_signed(origin)?;
+
+			let bounty = Self::bounties(bounty_id).ok_or(Error::<T>::InvalidBountyIndex)?;
+			let (curator, beneficiary) = match bounty.status {
+				BountyStatus::PendingPayout { curator, beneficiary, unlock_at } => {
+					ensure!(
+						system::Module::<T>::block_number() >= unlock_at,
+						Error::<T>::Premature,
+					);
+					(curator, beneficiary)
+				},
+				_ => Err(Error::<T>::UnexpectedStatus)?,
+			};
+
+			let bounty_account = Self::bounty_account_id(bounty_id);
+			let balance = T::Currency::free_balance(&bounty_account);
+			let imbalance = T::Currency::withdraw(
+				&bounty_account,
+				balance,
+				WithdrawReason::Transfer.into(),
+				AllowDeath,
+			)?;
+			let (fee, payout) = imbalance.split(bounty.fee.min(balance));
+			let payout_value = payout.peek();
+
+			let _ = T::Currency::unreserve(&curator, bounty.curator_deposit);
+			T::Currency::resolve_creating(&curator, fee);
+			T::Currency::resolve_creating(&beneficiary, payout);
+
+			<Bounties<T>>::remove(bounty_id);
+			BountyDescriptions::remove(bounty_id);
+
+			Self::deposit_event(RawEvent::BountyClaimed(bounty_id, payout_value, beneficiary));
+		}
+
+		/// Cancel a proposed or active bounty. All the funds will be sent to treasury and
+		/// the curator deposit will be unreserved if possible.
+		///
+		/// Only `RejectOrigin` is able to cancel a bounty.
+		///
+		/// - `bounty_id`: Bounty ID to cancel.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - Up to two balance operations.
+		/// - Two DB removals.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		fn close_bounty(origin, #[compact] bounty_id: BountyIndex) {
+			T::RejectOrigin::ensure_origin(origin)?;
+
+			let bounty = Self::bounties(bounty_id).ok_or(Error::<T>::InvalidBountyIndex)?;
+			match bounty.status {
+				BountyStatus::Proposed => {
+					// The reject origin would like to cancel a proposed bounty.
+					<Bounties<T>>::remove(bounty_id);
+					BountyDescriptions::remove(bounty_id);
+					let value = bounty.bond;
+					let imbalance = T::Currency::slash_reserved(&bounty.proposer, value).0;
+					T::ProposalRejection::on_unbalanced(imbalance);
+					Self::deposit_event(RawEvent::BountyRejected(bounty_id, value));
+					return Ok(())
+				},
+				BountyStatus::Approved => {
+					// For weight reasons, we don't allow a council to cancel in this phase.
+					// We ask for them to wait until it is funded before they can cancel.
+					Err(Error::<T>::UnexpectedStatus)?
+				},
+				BountyStatus::Funded | BountyStatus::CuratorProposed { .. } => {
+					// Nothing extra to do besides the removal of the bounty below.
+				},
+				BountyStatus::Active { ref curator, .. } => {
+					// Cancelled by the council, refund deposit of the working curator.
+					let _ = T::Currency::unreserve(curator, bounty.curator_deposit);
+				},
+				BountyStatus::PendingPayout { .. } => {
+					// Bounty is already pending payout. If the council wants to cancel this
+					// bounty, it should mean the curator was acting maliciously. So the council
+					// should first unassign the curator, slashing their deposit.
+					Err(Error::<T>::PendingPayout)?
+				}
+			}
+
+			let bounty_account = Self::bounty_account_id(bounty_id);
+			let balance = T::Currency::free_balance(&bounty_account);
+			let imbalance = T::Currency::withdraw(
+				&bounty_account,
+				balance,
+				WithdrawReason::Transfer.into(),
+				AllowDeath,
+			)?;
+			T::Currency::resolve_creating(&Self::account_id(), imbalance);
+
+			<Bounties<T>>::remove(bounty_id);
+			BountyDescriptions::remove(bounty_id);
+
+			Self::deposit_event(RawEvent::BountyCanceled(bounty_id));
+		}
+
+		/// Extend the expiry time of an active bounty.
+		///
+		/// The dispatch origin for this call must be the curator of this bounty.
+		///
+		/// - `bounty_id`: Bounty ID to extend.
+		/// - `_remark`: additional information.
+		///
+		/// # <weight>
+		/// - O(1).
+		/// - Limited storage reads.
+		/// - One DB change.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn extend_bounty_expiry(origin, #[compact] bounty_id: BountyIndex, _remark: Vec<u8>) {
+			let signer = ensure_signed(origin)?;
+
+			let mut bounty = Self::bounties(bounty_id).ok_or(Error::<T>::InvalidBountyIndex)?;
+			match bounty.status {
+				BountyStatus::Active { ref curator, ref mut update_due } => {
+					ensure!(signer == *curator, Error::<T>::RequireCurator);
+					let new_due = system::Module::<T>::block_number() + T::BountyUpdatePeriod::get();
+					*update_due = new_due.max(*update_due);
+				},
+				_ => Err(Error::<T>::UnexpectedStatus)?,
+			}
+			<Bounties<T>>::insert(bounty_id, bounty);
+
+			Self::deposit_event(RawEvent::BountyExtended(bounty_id));
+		}
+
 		fn on_finalize(n: T::BlockNumber) {
 			// Check to see if we should spend some funds!
 			if (n % T::SpendPeriod::get()).is_zero() {
@@ -562,6 +1139,21 @@ impl<T: Trait> Module<T> {
 		MODULE_ID.into_account()
 	}
 
+	/// The account ID of a bounty account, holding the funds of a funded bounty.
+	pub fn bounty_account_id(id: BountyIndex) -> T::AccountId {
+		// "modl" ++ "py/trsry" ++ "bt" is 14 bytes, followed by the compact encoded bounty index of
+		// up to 5 bytes. Account IDs shorter than 19 bytes (16 bytes in the tests) truncate larger
+		// indices, `propose_bounty` rejects those.
+		MODULE_ID.into_sub_account((*b"bt", Compact(id)))
+	}
+
+	/// Whether the account ID of bounty `id` holds the whole index, so that it is unique.
+	fn bounty_account_fits(id: BountyIndex) -> bool {
+		let account = Self::bounty_account_id(id);
+		<ModuleId as AccountIdConversion<T::AccountId>>::try_from_sub_account(&account)
+			== Some((MODULE_ID, (*b"bt", Compact(id))))
+	}
+
 	/// The needed bond for a proposal whose spend is `value`.
 	fn calculate_bond(value: BalanceOf<T>) -> BalanceOf<T> {
 		T::ProposalBondMinimum::get().max(T::ProposalBond::get() * value)
@@ -670,6 +1262,38 @@ impl<T: Trait> Module<T> {
 			});
 		});
 
+		BountyApprovals::mutate(|v| {
+			v.retain(|&index| {
+				<Bounties<T>>::mutate(index, |bounty| {
+					// Should always be true, but shouldn't panic if false or we're screwed.
+					if let Some(bounty) = bounty {
+						if bounty.value <= budget_remaining {
+							budget_remaining -= bounty.value;
+
+							bounty.status = BountyStatus::Funded;
+
+							// return their deposit.
+							let _ = T::Currency::unreserve(&bounty.proposer, bounty.bond);
+
+							// fund the bounty account
+							imbalance.subsume(T::Currency::deposit_creating(
+								&Self::bounty_account_id(index),
+								bounty.value,
+							));
+
+							Self::deposit_event(RawEvent::BountyBecameActive(index));
+							false
+						} else {
+							missed_any = true;
+							true
+						}
+					} else {
+						false
+					}
+				})
+			});
+		});
+
 		if !missed_any {
 			// burn some proportion of the remaining budget if we run a surplus.
 			let burn = (T::Burn::get() * budget_remaining).min(budget_remaining);
@@ -746,7 +1370,7 @@ mod tests {
 		type Call = ();
 		type Hash = H256;
 		type Hashing = BlakeTwo256;
-		type AccountId = u64;
+		type AccountId = u128;
 		type Lookup = IdentityLookup<Self::AccountId>;
 		type Header = Header;
 		type Event = ();
@@ -775,11 +1399,11 @@ mod tests {
 		type CreationFee = CreationFee;
 	}
 	pub struct TenToFourteen;
-	impl Contains<u64> for TenToFourteen {
-		fn contains(n: &u64) -> bool {
+	impl Contains<u128> for TenToFourteen {
+		fn contains(n: &u128) -> bool {
 			*n >= 10 && *n <= 14
 		}
-		fn sorted_members() -> Vec<u64> {
+		fn sorted_members() -> Vec<u128> {
 			vec![10, 11, 12, 13, 14]
 		}
 	}
@@ -792,11 +1416,17 @@ mod tests {
 		pub const TipFindersFee: Percent = Percent::from_percent(20);
 		pub const TipReportDepositBase: u64 = 1;
 		pub const TipReportDepositPerByte: u64 = 1;
+		pub const BountyDepositBase: u64 = 80;
+		pub const BountyDepositPerByte: u64 = 1;
+		pub const BountyDepositPayoutDelay: u64 = 3;
+		pub const BountyUpdatePeriod: u64 = 20;
+		pub const BountyCuratorDeposit: Permill = Permill::from_percent(50);
+		pub const BountyValueMinimum: u64 = 1;
 	}
 	impl Trait for Test {
 		type Currency = pallet_balances::Module<Test>;
-		type ApproveOrigin = frame_system::EnsureRoot<u64>;
-		type RejectOrigin = frame_system::EnsureRoot<u64>;
+		type ApproveOrigin = frame_system::EnsureRoot<u128>;
+		type RejectOrigin = frame_system::EnsureRoot<u128>;
 		type Tippers = TenToFourteen;
 		type TipCountdown = TipCountdown;
 		type TipFindersFee = TipFindersFee;
@@ -808,6 +1438,12 @@ mod tests {
 		type ProposalBondMinimum = ProposalBondMinimum;
 		type SpendPeriod = SpendPeriod;
 		type Burn = Burn;
+		type BountyDepositBase = BountyDepositBase;
+		type BountyDepositPerByte = BountyDepositPerByte;
+		type BountyDepositPayoutDelay = BountyDepositPayoutDelay;
+		type BountyUpdatePeriod = BountyUpdatePeriod;
+		type BountyCuratorDeposit = BountyCuratorDeposit;
+		type BountyValueMinimum = BountyValueMinimum;
 	}
 	type System = frame_system::Module<Test>;
 	type Balances = pallet_balances::Module<Test>;
@@ -818,7 +1454,6 @@ mod tests {
 		pallet_balances::GenesisConfig::<Test>{
 			// Total issuance will be 200 with treasury account initialized at ED.
 			balances: vec![(0, 100), (1, 98), (2, 1)],
-			vesting: vec![],
 		}.assimilate_storage(&mut t).unwrap();
 		GenesisConfig::default().assimilate_storage::<Test>(&mut t).unwrap();
 		t.into()
@@ -833,7 +1468,7 @@ mod tests {
 	}
 
 	fn tip_hash() -> H256 {
-		BlakeTwo256::hash_of(&(BlakeTwo256::hash(b"awesome.dot"), 3u64))
+		BlakeTwo256::hash_of(&(BlakeTwo256::hash(b"awesome.dot"), 3u128))
 	}
 
 	#[test]
@@ -882,7 +1517,7 @@ mod tests {
 			assert_ok!(Treasury::report_awesome(Origin::signed(0), b"awesome.dot".to_vec(), 0));
 			assert_eq!(Balances::reserved_balance(&0), 12);
 			assert_eq!(Balances::free_balance(&0), 88);
-			let h = BlakeTwo256::hash_of(&(BlakeTwo256::hash(b"awesome.dot"), 0u64));
+			let h = BlakeTwo256::hash_of(&(BlakeTwo256::hash(b"awesome.dot"), 0u128));
 			assert_ok!(Treasury::tip(Origin::signed(10), h.clone(), 10));
 			assert_ok!(Treasury::tip(Origin::signed(11), h.clone(), 10));
 			assert_ok!(Treasury::tip(Origin::signed(12), h.clone(), 10));
@@ -1144,7 +1779,6 @@ mod tests {
 		let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
 		pallet_balances::GenesisConfig::<Test>{
 			balances: vec![(0, 100), (1, 99), (2, 1)],
-			vesting: vec![],
 		}.assimilate_storage(&mut t).unwrap();
 		// Treasury genesis config is not build thus treasury account does not exist
 		let mut t: sp_io::TestExternalities = t.into();
@@ -1171,4 +1805,369 @@ mod tests {
 			assert_eq!(Balances::free_balance(&3), 99); // Balance of `3` has changed
 		});
 	}
+
+	#[test]
+	fn propose_bounty_works() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+
+			Balances::make_free_balance_be(&Treasury::account_id(), 101);
+			assert_eq!(Treasury::pot(), 100);
+
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 10, b"1234567890".to_vec()));
+
+			let deposit: u64 = 80 + 10;
+			assert_eq!(Balances::reserved_balance(&0), deposit);
+			assert_eq!(Balances::free_balance(&0), 100 - deposit);
+
+			assert_eq!(Treasury::bounties(0).unwrap(), Bounty {
+				proposer: 0,
+				fee: 0,
+				curator_deposit: 0,
+				value: 10,
+				bond: deposit,
+				status: BountyStatus::Proposed,
+			});
+			assert_eq!(Treasury::bounty_descriptions(0).unwrap(), b"1234567890".to_vec());
+			assert_eq!(Treasury::bounty_count(), 1);
+		});
+	}
+
+	#[test]
+	fn propose_bounty_validation_works() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+
+			Balances::make_free_balance_be(&Treasury::account_id(), 101);
+			assert_eq!(Treasury::pot(), 100);
+
+			assert_noop!(
+				Treasury::propose_bounty(Origin::signed(1), 0, [0; 17_000].to_vec()),
+				Error::<Test>::ReasonTooBig
+			);
+			assert_noop!(
+				Treasury::propose_bounty(Origin::signed(1), 0, b"12345678901234567890".to_vec()),
+				Error::<Test>::InvalidValue
+			);
+			assert_noop!(
+				Treasury::propose_bounty(Origin::signed(1), 10, b"12345678901234567890".to_vec()),
+				Error::<Test>::InsufficientProposersBalance
+			);
+		});
+	}
+
+	#[test]
+	fn bounty_index_must_fit_into_bounty_account() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+
+			// The 16 byte account IDs of the tests leave two bytes for the compact encoded index.
+			BountyCount::put(16_383);
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 10, b"12345".to_vec()));
+			assert_ne!(Treasury::bounty_account_id(16_383), Treasury::bounty_account_id(63));
+
+			assert_noop!(
+				Treasury::propose_bounty(Origin::signed(0), 10, b"12345".to_vec()),
+				Error::<Test>::BountyIndexOverflow
+			);
+		});
+	}
+
+	#[test]
+	fn close_bounty_works() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			Balances::make_free_balance_be(&Treasury::account_id(), 101);
+			assert_noop!(Treasury::close_bounty(Origin::ROOT, 0), Error::<Test>::InvalidBountyIndex);
+
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 10, b"12345".to_vec()));
+			assert_noop!(Treasury::close_bounty(Origin::signed(0), 0), BadOrigin);
+
+			assert_ok!(Treasury::close_bounty(Origin::ROOT, 0));
+
+			let deposit: u64 = 80 + 5;
+			assert_eq!(Balances::reserved_balance(&0), 0);
+			assert_eq!(Balances::free_balance(&0), 100 - deposit);
+
+			assert_eq!(Treasury::bounties(0), None);
+			assert_eq!(Treasury::bounty_descriptions(0), None);
+		});
+	}
+
+	#[test]
+	fn approve_bounty_works() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			Balances::make_free_balance_be(&Treasury::account_id(), 101);
+			assert_noop!(Treasury::approve_bounty(Origin::ROOT, 0), Error::<Test>::InvalidBountyIndex);
+
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 50, b"12345".to_vec()));
+
+			assert_ok!(Treasury::approve_bounty(Origin::ROOT, 0));
+
+			let deposit: u64 = 80 + 5;
+
+			assert_eq!(Treasury::bounties(0).unwrap(), Bounty {
+				proposer: 0,
+				fee: 0,
+				value: 50,
+				curator_deposit: 0,
+				bond: deposit,
+				status: BountyStatus::Approved,
+			});
+			assert_eq!(Treasury::bounty_approvals(), vec![0]);
+
+			assert_noop!(Treasury::close_bounty(Origin::ROOT, 0), Error::<Test>::UnexpectedStatus);
+			assert_noop!(Treasury::approve_bounty(Origin::ROOT, 0), Error::<Test>::UnexpectedStatus);
+
+			// deposit not returned yet
+			assert_eq!(Balances::reserved_balance(&0), deposit);
+			assert_eq!(Balances::free_balance(&0), 100 - deposit);
+
+			<Treasury as OnFinalize<u64>>::on_finalize(2);
+
+			// return deposit
+			assert_eq!(Balances::reserved_balance(&0), 0);
+			assert_eq!(Balances::free_balance(&0), 100);
+
+			assert_eq!(Treasury::bounties(0).unwrap(), Bounty {
+				proposer: 0,
+				fee: 0,
+				curator_deposit: 0,
+				value: 50,
+				bond: deposit,
+				status: BountyStatus::Funded,
+			});
+			assert_eq!(Treasury::bounty_approvals(), Vec::<BountyIndex>::new());
+			assert_eq!(Balances::free_balance(&Treasury::bounty_account_id(0)), 50);
+			assert_eq!(Treasury::pot(), 100 - 50 - 25); // burn 25
+		});
+	}
+
+	#[test]
+	fn assign_curator_works() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			Balances::make_free_balance_be(&Treasury::account_id(), 101);
+
+			assert_noop!(
+				Treasury::propose_curator(Origin::ROOT, 0, 4, 4),
+				Error::<Test>::InvalidBountyIndex
+			);
+
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 50, b"12345".to_vec()));
+			assert_ok!(Treasury::approve_bounty(Origin::ROOT, 0));
+
+			System::set_block_number(2);
+			<Treasury as OnFinalize<u64>>::on_finalize(2);
+
+			assert_noop!(
+				Treasury::propose_curator(Origin::ROOT, 0, 4, 50),
+				Error::<Test>::InvalidFee
+			);
+
+			assert_ok!(Treasury::propose_curator(Origin::ROOT, 0, 4, 4));
+
+			assert_eq!(Treasury::bounties(0).unwrap(), Bounty {
+				proposer: 0,
+				fee: 4,
+				curator_deposit: 0,
+				value: 50,
+				bond: 85,
+				status: BountyStatus::CuratorProposed {
+					curator: 4,
+				},
+			});
+
+			assert_noop!(Treasury::accept_curator(Origin::signed(1), 0), Error::<Test>::RequireCurator);
+
+			Balances::make_free_balance_be(&4, 10);
+
+			assert_ok!(Treasury::accept_curator(Origin::signed(4), 0));
+
+			assert_eq!(Treasury::bounties(0).unwrap(), Bounty {
+				proposer: 0,
+				fee: 4,
+				curator_deposit: 2,
+				value: 50,
+				bond: 85,
+				status: BountyStatus::Active {
+					curator: 4,
+					update_due: 22,
+				},
+			});
+
+			assert_eq!(Balances::free_balance(&4), 8);
+			assert_eq!(Balances::reserved_balance(&4), 2);
+		});
+	}
+
+	#[test]
+	fn unassign_curator_works() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			Balances::make_free_balance_be(&Treasury::account_id(), 101);
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 50, b"12345".to_vec()));
+			assert_ok!(Treasury::approve_bounty(Origin::ROOT, 0));
+
+			System::set_block_number(2);
+			<Treasury as OnFinalize<u64>>::on_finalize(2);
+
+			assert_ok!(Treasury::propose_curator(Origin::ROOT, 0, 4, 4));
+
+			assert_noop!(Treasury::unassign_curator(Origin::signed(1), 0), BadOrigin);
+
+			// the proposed curator can decline the role.
+			assert_ok!(Treasury::unassign_curator(Origin::signed(4), 0));
+
+			assert_eq!(Treasury::bounties(0).unwrap(), Bounty {
+				proposer: 0,
+				fee: 4,
+				curator_deposit: 0,
+				value: 50,
+				bond: 85,
+				status: BountyStatus::Funded,
+			});
+
+			assert_ok!(Treasury::propose_curator(Origin::ROOT, 0, 4, 4));
+
+			Balances::make_free_balance_be(&4, 10);
+
+			assert_ok!(Treasury::accept_curator(Origin::signed(4), 0));
+
+			// anyone else may only unassign an inactive curator.
+			assert_noop!(Treasury::unassign_curator(Origin::signed(1), 0), Error::<Test>::Premature);
+
+			System::set_block_number(23);
+
+			assert_ok!(Treasury::unassign_curator(Origin::signed(1), 0));
+
+			assert_eq!(Treasury::bounties(0).unwrap(), Bounty {
+				proposer: 0,
+				fee: 4,
+				curator_deposit: 0,
+				value: 50,
+				bond: 85,
+				status: BountyStatus::Funded,
+			});
+
+			// the inactive curator is slashed.
+			assert_eq!(Balances::free_balance(&4), 8);
+			assert_eq!(Balances::reserved_balance(&4), 0);
+		});
+	}
+
+	#[test]
+	fn extend_bounty_expiry_works() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			Balances::make_free_balance_be(&Treasury::account_id(), 101);
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 50, b"12345".to_vec()));
+			assert_ok!(Treasury::approve_bounty(Origin::ROOT, 0));
+
+			System::set_block_number(2);
+			<Treasury as OnFinalize<u64>>::on_finalize(2);
+
+			assert_ok!(Treasury::propose_curator(Origin::ROOT, 0, 4, 4));
+			Balances::make_free_balance_be(&4, 10);
+			assert_ok!(Treasury::accept_curator(Origin::signed(4), 0));
+
+			System::set_block_number(10);
+
+			assert_noop!(
+				Treasury::extend_bounty_expiry(Origin::signed(1), 0, Vec::new()),
+				Error::<Test>::RequireCurator
+			);
+			assert_ok!(Treasury::extend_bounty_expiry(Origin::signed(4), 0, Vec::new()));
+
+			assert_eq!(Treasury::bounties(0).unwrap().status, BountyStatus::Active {
+				curator: 4,
+				update_due: 30,
+			});
+
+			// the curator is not inactive anymore.
+			System::set_block_number(23);
+			assert_noop!(Treasury::unassign_curator(Origin::signed(1), 0), Error::<Test>::Premature);
+		});
+	}
+
+	#[test]
+	fn award_and_claim_bounty_works() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			Balances::make_free_balance_be(&Treasury::account_id(), 101);
+			Balances::make_free_balance_be(&4, 10);
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 50, b"12345".to_vec()));
+			assert_ok!(Treasury::approve_bounty(Origin::ROOT, 0));
+
+			System::set_block_number(2);
+			<Treasury as OnFinalize<u64>>::on_finalize(2);
+
+			assert_ok!(Treasury::propose_curator(Origin::ROOT, 0, 4, 4));
+			assert_ok!(Treasury::accept_curator(Origin::signed(4), 0));
+
+			assert_eq!(Balances::free_balance(&4), 8); // inital 10 - 2 deposit
+
+			assert_noop!(Treasury::award_bounty(Origin::signed(1), 0, 3), Error::<Test>::RequireCurator);
+
+			assert_ok!(Treasury::award_bounty(Origin::signed(4), 0, 3));
+
+			assert_eq!(Treasury::bounties(0).unwrap(), Bounty {
+				proposer: 0,
+				fee: 4,
+				curator_deposit: 2,
+				value: 50,
+				bond: 85,
+				status: BountyStatus::PendingPayout {
+					curator: 4,
+					beneficiary: 3,
+					unlock_at: 5,
+				},
+			});
+
+			assert_noop!(Treasury::close_bounty(Origin::ROOT, 0), Error::<Test>::PendingPayout);
+			assert_noop!(Treasury::claim_bounty(Origin::signed(1), 0), Error::<Test>::Premature);
+
+			System::set_block_number(5);
+
+			assert_ok!(Treasury::claim_bounty(Origin::signed(1), 0));
+
+			assert_eq!(Balances::free_balance(&4), 14); // initial 10 + fee 4
+			assert_eq!(Balances::reserved_balance(&4), 0);
+			assert_eq!(Balances::free_balance(&3), 46);
+			assert_eq!(Balances::free_balance(&Treasury::bounty_account_id(0)), 0);
+
+			assert_eq!(Treasury::bounties(0), None);
+			assert_eq!(Treasury::bounty_descriptions(0), None);
+		});
+	}
+
+	#[test]
+	fn cancel_funded_bounty_refunds_treasury() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			Balances::make_free_balance_be(&Treasury::account_id(), 101);
+			Balances::make_free_balance_be(&4, 10);
+			assert_ok!(Treasury::propose_bounty(Origin::signed(0), 50, b"12345".to_vec()));
+			assert_ok!(Treasury::approve_bounty(Origin::ROOT, 0));
+
+			System::set_block_number(2);
+			<Treasury as OnFinalize<u64>>::on_finalize(2);
+
+			assert_ok!(Treasury::propose_curator(Origin::ROOT, 0, 4, 4));
+			assert_ok!(Treasury::accept_curator(Origin::signed(4), 0));
+			assert_eq!(Treasury::pot(), 25);
+
+			assert_ok!(Treasury::close_bounty(Origin::ROOT, 0));
+
+			// the working curator gets their deposit back.
+			assert_eq!(Balances::free_balance(&4), 10);
+			assert_eq!(Balances::reserved_balance(&4), 0);
+
+			assert_eq!(Balances::free_balance(&Treasury::bounty_account_id(0)), 0);
+			assert_eq!(Treasury::pot(), 75);
+			assert_eq!(Treasury::bounties(0), None);
+			assert_eq!(Treasury::bounty_descriptions(0), None);
+		});
+	}
 }
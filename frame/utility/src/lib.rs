@@ -26,7 +26,8 @@
 //! - Batch dispatch: A stateless operation, allowing any origin to execute multiple calls in a
 //!   single dispatch. This can be useful to amalgamate proposals, combining `set_code` with
 //!   corresponding `set_storage`s, for efficient multiple payouts with just a single signature
-//!   verify, or in combination with one of the other two dispatch functionality.
+//!   verify, or in combination with one of the other two dispatch functionality. Batches may be
+//!   atomic, reverting all of their calls if any one of them fails, and may be nested.
 //! - Pseudonymal dispatch: A stateless operation, allowing a signed origin to execute a call from
 //!   an alternative signed origin. Each account has 2**16 possible "pseudonyms" (alternative
 //!   account IDs) and these can be stacked. This can be useful as a key management tool, where you
@@ -44,7 +45,12 @@
 //! ### Dispatchable Functions
 //!
 //! #### For batch dispatch
-//! * `batch` - Dispatch multiple calls from the sender's origin.
+//! * `batch` - Dispatch multiple calls from the sender's origin, stopping at the first failure.
+//! * `batch_all` - Dispatch multiple calls from the sender's origin, reverting all of them if any
+//!   one fails.
+//!
+//! Calls dispatched by this module from any origin other than root must pass the `IsCallable`
+//! filter.
 //!
 //! #### For pseudonymal dispatch
 //! * `as_sub` - Dispatch a call from a secondary ("sub") signed origin.
@@ -66,10 +72,11 @@ use codec::{Encode, Decode};
 use sp_core::TypeId;
 use sp_io::hashing::blake2_256;
 use frame_support::{decl_module, decl_event, decl_error, decl_storage, Parameter, ensure, RuntimeDebug};
-use frame_support::{traits::{Get, ReservableCurrency, Currency}, weights::{
+use frame_support::{traits::{Get, ReservableCurrency, Currency, Filter}, weights::{
 	GetDispatchInfo, ClassifyDispatch, WeighData, Weight, DispatchClass, PaysFee
 }};
-use frame_system::{self as system, ensure_signed};
+use frame_support::storage::{with_transaction, TransactionOutcome};
+use frame_system::{self as system, ensure_signed, ensure_root};
 use sp_runtime::{DispatchError, DispatchResult, traits::Dispatchable};
 
 type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
@@ -98,6 +105,13 @@ pub trait Trait: frame_system::Trait {
 
 	/// The maximum amount of signatories allowed in the multisig.
 	type MaxSignatories: Get<u16>;
+
+	/// Is a given call allowed to be dispatched through this module by an origin other than root?
+	///
+	/// This keeps calls which are otherwise filtered out, e.g. by a transaction extension or
+	/// another module's own filter, from being slipped through inside a batch or a pseudonymal
+	/// or multisig dispatch.
+	type IsCallable: Filter<<Self as Trait>::Call>;
 }
 
 /// A global extrinsic index, formed as the extrinsic index within a block, together with that
@@ -159,6 +173,8 @@ decl_error! {
 		WrongTimepoint,
 		/// A timepoint was given, yet no multisig operation is underway.
 		UnexpectedTimepoint,
+		/// A call was given which may not be dispatched through this module.
+		Uncallable,
 	}
 }
 
@@ -196,7 +212,7 @@ impl<Call> Passthrough<Call> {
 }
 impl<Call: GetDispatchInfo> WeighData<(&u16, &Box<Call>)> for Passthrough<Call> {
 	fn weigh_data(&self, (_, call): (&u16, &Box<Call>)) -> Weight {
-		call.get_dispatch_info().weight + 10_000
+		call.get_dispatch_info().weight.saturating_add(10_000)
 	}
 }
 impl<Call: GetDispatchInfo> ClassifyDispatch<(&u16, &Box<Call>)> for Passthrough<Call> {
@@ -222,7 +238,7 @@ impl<Call: GetDispatchInfo> WeighData<(&Vec<Call>,)> for BatchPassthrough<Call>
 	fn weigh_data(&self, (calls,): (&Vec<Call>,)) -> Weight {
 		calls.iter()
 			.map(|call| call.get_dispatch_info().weight)
-			.fold(10_000, |a, n| a + n)
+			.fold(10_000, |a: Weight, n| a.saturating_add(n))
 	}
 }
 impl<Call: GetDispatchInfo> ClassifyDispatch<(&Vec<Call>,)> for BatchPassthrough<Call> {
@@ -256,7 +272,7 @@ impl<Call: GetDispatchInfo, AccountId, Timepoint> WeighData<(&u16, &Vec<AccountI
 for MultiPassthrough<Call, AccountId, Timepoint>
 {
 	fn weigh_data(&self, (_, sigs, _, call): (&u16, &Vec<AccountId>, &Timepoint, &Box<Call>)) -> Weight {
-		call.get_dispatch_info().weight + 10_000 * (sigs.len() as u32 + 1)
+		call.get_dispatch_info().weight.saturating_add(10_000 * (sigs.len() as u32 + 1))
 	}
 }
 impl<Call: GetDispatchInfo, AccountId, Timepoint> ClassifyDispatch<(&u16, &Vec<AccountId>, &Timepoint, &Box<Call>)>
@@ -323,9 +339,11 @@ decl_module! {
 
 		/// Send a batch of dispatch calls.
 		///
-		/// This will execute until the first one fails and then stop.
+		/// This will execute until the first one fails and then stop. The effects of the calls
+		/// made before the failing one are kept.
 		///
-		/// May be called from any origin.
+		/// May be called from any origin. Unless the origin is root, every call must pass the
+		/// `IsCallable` filter.
 		///
 		/// - `calls`: The calls to be dispatched from the same origin.
 		///
@@ -341,8 +359,9 @@ decl_module! {
 		/// event is deposited.
 		#[weight = <BatchPassthrough<<T as Trait>::Call>>::new()]
 		fn batch(origin, calls: Vec<<T as Trait>::Call>) {
+			let is_root = ensure_root(origin.clone()).is_ok();
 			for (index, call) in calls.into_iter().enumerate() {
-				let result = call.dispatch(origin.clone());
+				let result = Self::dispatch_filtered(origin.clone(), call, is_root);
 				if let Err(e) = result {
 					Self::deposit_event(Event::<T>::BatchInterrupted(index as u32, e));
 					return Ok(());
@@ -351,9 +370,42 @@ decl_module! {
 			Self::deposit_event(Event::<T>::BatchCompleted);
 		}
 
+		/// Send a batch of dispatch calls and atomically execute them.
+		///
+		/// The whole batch is reverted if any of the calls fails, in which case the error of the
+		/// failing call is returned.
+		///
+		/// May be called from any origin. Unless the origin is root, every call must pass the
+		/// `IsCallable` filter.
+		///
+		/// - `calls`: The calls to be dispatched from the same origin.
+		///
+		/// # <weight>
+		/// - The sum of the weights of the `calls`.
+		/// - One storage transaction.
+		/// - One event.
+		/// # </weight>
+		///
+		/// If all calls were successful, then the `BatchCompleted` event is deposited.
+		#[weight = <BatchPassthrough<<T as Trait>::Call>>::new()]
+		fn batch_all(origin, calls: Vec<<T as Trait>::Call>) -> DispatchResult {
+			let is_root = ensure_root(origin.clone()).is_ok();
+			with_transaction(|| {
+				for call in calls.into_iter() {
+					if let Err(e) = Self::dispatch_filtered(origin.clone(), call, is_root) {
+						return TransactionOutcome::Rollback(Err(e));
+					}
+				}
+				TransactionOutcome::Commit(Ok(()))
+			})?;
+			Self::deposit_event(Event::<T>::BatchCompleted);
+			Ok(())
+		}
+
 		/// Send a call through an indexed pseudonym of the sender.
 		///
-		/// The dispatch origin for this call must be _Signed_.
+		/// The dispatch origin for this call must be _Signed_, and `call` must pass the
+		/// `IsCallable` filter.
 		///
 		/// # <weight>
 		/// - The weight of the `call`.
@@ -361,6 +413,7 @@ decl_module! {
 		#[weight = <Passthrough<<T as Trait>::Call>>::new()]
 		fn as_sub(origin, index: u16, call: Box<<T as Trait>::Call>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			ensure!(T::IsCallable::filter(&call), Error::<T>::Uncallable);
 			let pseudonym = Self::sub_account_id(who, index);
 			call.dispatch(frame_system::RawOrigin::Signed(pseudonym).into())
 		}
@@ -382,7 +435,7 @@ decl_module! {
 		/// - `maybe_timepoint`: If this is the first approval, then this must be `None`. If it is
 		/// not the first approval, then it must be `Some`, with the timepoint (block number and
 		/// transaction index) of the first approval transaction.
-		/// - `call`: The call to be executed.
+		/// - `call`: The call to be executed. Must pass the `IsCallable` filter.
 		///
 		/// NOTE: Unless this is the final approval, you will generally want to use
 		/// `approve_as_multi` instead, since it only requires a hash of the call.
@@ -414,6 +467,7 @@ decl_module! {
 			call: Box<<T as Trait>::Call>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			ensure!(T::IsCallable::filter(&call), Error::<T>::Uncallable);
 			ensure!(threshold >= 1, Error::<T>::ZeroThreshold);
 			let max_sigs = T::MaxSignatories::get() as usize;
 			ensure!(!other_signatories.is_empty(), Error::<T>::TooFewSignatories);
@@ -619,6 +673,14 @@ impl<T: Trait> Module<T> {
 		}
 	}
 
+	/// Dispatch `call` from `origin`, first checking it against `IsCallable` unless `is_root`.
+	fn dispatch_filtered(origin: T::Origin, call: <T as Trait>::Call, is_root: bool) -> DispatchResult {
+		if !is_root && !T::IsCallable::filter(&call) {
+			Err(Error::<T>::Uncallable)?
+		}
+		call.dispatch(origin)
+	}
+
 	/// Check that signatories is sorted and doesn't contain sender, then insert sender.
 	fn ensure_sorted_and_insert(other_signatories: Vec<T::AccountId>, who: T::AccountId)
 		-> Result<Vec<T::AccountId>, DispatchError>
@@ -665,6 +727,7 @@ mod tests {
 	}
 	impl_outer_dispatch! {
 		pub enum Call for Test where origin: Origin {
+			frame_system::System,
 			pallet_balances::Balances,
 			utility::Utility,
 		}
@@ -721,6 +784,16 @@ mod tests {
 		pub const MultisigDepositFactor: u64 = 1;
 		pub const MaxSignatories: u16 = 3;
 	}
+	pub struct TestIsCallable;
+	impl Filter<Call> for TestIsCallable {
+		fn filter(c: &Call) -> bool {
+			match *c {
+				Call::Balances(_) => true,
+				Call::Utility(_) => true,
+				_ => false,
+			}
+		}
+	}
 	impl Trait for Test {
 		type Event = TestEvent;
 		type Call = Call;
@@ -728,11 +801,13 @@ mod tests {
 		type MultisigDepositBase = MultisigDepositBase;
 		type MultisigDepositFactor = MultisigDepositFactor;
 		type MaxSignatories = MaxSignatories;
+		type IsCallable = TestIsCallable;
 	}
 	type System = frame_system::Module<Test>;
 	type Balances = pallet_balances::Module<Test>;
 	type Utility = Module<Test>;
 
+	use frame_system::Call as SystemCall;
 	use pallet_balances::Call as BalancesCall;
 	use pallet_balances::Error as BalancesError;
 
@@ -740,7 +815,6 @@ mod tests {
 		let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
 		pallet_balances::GenesisConfig::<Test> {
 			balances: vec![(1, 10), (2, 10), (3, 10), (4, 10), (5, 10)],
-			vesting: vec![],
 		}.assimilate_storage(&mut t).unwrap();
 		t.into()
 	}
@@ -1066,4 +1140,137 @@ mod tests {
 			assert_eq!(Balances::free_balance(2), 15);
 		});
 	}
+
+	#[test]
+	fn batch_weight_calculation_doesnt_overflow() {
+		let big_call = Call::System(SystemCall::fill_block());
+		assert_eq!(big_call.get_dispatch_info().weight, Weight::max_value());
+
+		let batch_call = Call::Utility(crate::Call::batch(vec![big_call.clone(), big_call.clone()]));
+		assert_eq!(batch_call.get_dispatch_info().weight, Weight::max_value());
+
+		let batch_all_call = Call::Utility(crate::Call::batch_all(vec![big_call.clone(), big_call]));
+		assert_eq!(batch_all_call.get_dispatch_info().weight, Weight::max_value());
+	}
+
+	#[test]
+	fn batch_weight_sums_inner_calls() {
+		let call = Call::Balances(BalancesCall::transfer(2, 5));
+		let call_weight = call.get_dispatch_info().weight;
+		let nested = Call::Utility(crate::Call::batch(vec![call.clone(), call.clone()]));
+		let batch_all_call = Call::Utility(crate::Call::batch_all(vec![call, nested]));
+		assert_eq!(batch_all_call.get_dispatch_info().weight, 10_000 + call_weight + (10_000 + 2 * call_weight));
+	}
+
+	#[test]
+	fn batch_all_works() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Balances::free_balance(1), 10);
+			assert_eq!(Balances::free_balance(2), 10);
+			assert_ok!(
+				Utility::batch_all(Origin::signed(1), vec![
+					Call::Balances(BalancesCall::transfer(2, 5)),
+					Call::Balances(BalancesCall::transfer(2, 5))
+				]),
+			);
+			assert_eq!(Balances::free_balance(1), 0);
+			assert_eq!(Balances::free_balance(2), 20);
+			expect_event(RawEvent::BatchCompleted);
+		});
+	}
+
+	#[test]
+	fn batch_all_revert() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			assert_eq!(Balances::free_balance(1), 10);
+			assert_eq!(Balances::free_balance(2), 10);
+			assert_noop!(
+				Utility::batch_all(Origin::signed(1), vec![
+					Call::Balances(BalancesCall::transfer(2, 5)),
+					Call::Balances(BalancesCall::transfer(2, 10)),
+					Call::Balances(BalancesCall::transfer(2, 5)),
+				]),
+				BalancesError::<Test, _>::InsufficientBalance
+			);
+			assert_eq!(Balances::free_balance(1), 10);
+			assert_eq!(Balances::free_balance(2), 10);
+			assert!(System::events().is_empty());
+		});
+	}
+
+	#[test]
+	fn nested_batches_work() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(
+				Utility::batch(Origin::signed(1), vec![
+					Call::Balances(BalancesCall::transfer(2, 5)),
+					Call::Utility(crate::Call::batch_all(vec![
+						Call::Balances(BalancesCall::transfer(2, 1)),
+						Call::Balances(BalancesCall::transfer(2, 10)),
+					])),
+					Call::Balances(BalancesCall::transfer(2, 5)),
+				]),
+			);
+			// Only the call before the failing atomic batch went through.
+			assert_eq!(Balances::free_balance(1), 5);
+			assert_eq!(Balances::free_balance(2), 15);
+			let err = DispatchError::from(BalancesError::<Test, _>::InsufficientBalance).stripped();
+			expect_event(RawEvent::BatchInterrupted(1, err));
+
+			assert_ok!(
+				Utility::batch_all(Origin::signed(1), vec![
+					Call::Utility(crate::Call::batch_all(vec![
+						Call::Balances(BalancesCall::transfer(2, 1)),
+						Call::Balances(BalancesCall::transfer(2, 1)),
+					])),
+					Call::Balances(BalancesCall::transfer(2, 1)),
+				]),
+			);
+			assert_eq!(Balances::free_balance(1), 2);
+			assert_eq!(Balances::free_balance(2), 18);
+		});
+	}
+
+	#[test]
+	fn uncallable_calls_are_filtered_for_non_root_origins() {
+		new_test_ext().execute_with(|| {
+			let remark = Call::System(SystemCall::remark(vec![]));
+
+			assert_ok!(Utility::batch(Origin::signed(1), vec![
+				Call::Balances(BalancesCall::transfer(2, 5)),
+				remark.clone(),
+			]));
+			let err = DispatchError::from(Error::<Test>::Uncallable).stripped();
+			expect_event(RawEvent::BatchInterrupted(1, err));
+			assert_eq!(Balances::free_balance(1), 5);
+
+			assert_noop!(
+				Utility::batch_all(Origin::signed(1), vec![
+					Call::Balances(BalancesCall::transfer(2, 5)),
+					remark.clone(),
+				]),
+				Error::<Test>::Uncallable,
+			);
+			// Nesting a batch does not get around the filter.
+			assert_noop!(
+				Utility::batch_all(Origin::signed(1), vec![
+					Call::Utility(crate::Call::batch_all(vec![remark.clone()])),
+				]),
+				Error::<Test>::Uncallable,
+			);
+			assert_noop!(
+				Utility::as_sub(Origin::signed(1), 0, Box::new(remark.clone())),
+				Error::<Test>::Uncallable,
+			);
+			assert_noop!(
+				Utility::as_multi(Origin::signed(1), 2, vec![2, 3], None, Box::new(remark)),
+				Error::<Test>::Uncallable,
+			);
+
+			// Root is not restricted.
+			assert_ok!(Utility::batch_all(Origin::ROOT, vec![Call::System(SystemCall::set_heap_pages(8))]));
+			expect_event(RawEvent::BatchCompleted);
+		});
+	}
 }
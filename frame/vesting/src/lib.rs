@@ -0,0 +1,835 @@
+// Copyright 2019-2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Vesting Module
+//!
+//! - [`vesting::Trait`](./trait.Trait.html)
+//! - [`Call`](./enum.Call.html)
+//!
+//! ## Overview
+//!
+//! A simple module providing a means of placing a linear curve on an account's locked balance. This
+//! module ensures that there is a lock in place preventing the balance to drop below the *unvested*
+//! amount for any reason other than transaction fee payment.
+//!
+//! As the amount vested increases over time, the amount unvested reduces. However, locks remain in
+//! place and explicit action is needed on behalf of the user to ensure that the amount locked is
+//! equivalent to the amount remaining to be vested. This is done through a dispatchable function,
+//! either `vest` (in typical case where the sender is calling on their own behalf) or `vest_other`
+//! in case the sender is calling on another account's behalf.
+//!
+//! An account may have up to `MaxVestingSchedules` schedules at once. Each of them vests
+//! independently and the lock covers the sum of their unvested amounts. Since anyone may add a
+//! schedule to an account with `vested_transfer`, the account can free up slots by merging two of
+//! its schedules into one with `merge_schedules`.
+//!
+//! Vesting schedules used to be kept by the Balances module. Its `on_runtime_upgrade` moves them
+//! into this module's storage and locks them under this module's lock identifier.
+//!
+//! ## Interface
+//!
+//! This module implements the `VestingSchedule` trait.
+//!
+//! ### Dispatchable Functions
+//!
+//! - `vest` - Update the lock, reducing it in line with the amount "vested" so far.
+//! - `vest_other` - Update the lock of another account, reducing it in line with the amount
+//!   "vested" so far.
+//! - `vested_transfer` - Transfer some funds to another account and place them under a new vesting
+//!   schedule.
+//! - `merge_schedules` - Merge two vesting schedules of the sender into one.
+//!
+//! [`Call`]: ./enum.Call.html
+//! [`Trait`]: ./trait.Trait.html
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_std::prelude::*;
+use codec::{Encode, Decode};
+use sp_runtime::{DispatchResult, RuntimeDebug, traits::{
+	StaticLookup, Zero, One, SimpleArithmetic, Saturating, Convert, Bounded,
+}};
+use frame_support::{decl_module, decl_event, decl_storage, decl_error, ensure};
+use frame_support::traits::{
+	Currency, LockableCurrency, VestingSchedule, WithdrawReason, LockIdentifier, Get,
+	ExistenceRequirement,
+};
+use frame_support::weights::SimpleDispatchInfo;
+use frame_system::{self as system, ensure_signed};
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+pub trait Trait: frame_system::Trait {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The currency trait.
+	type Currency: LockableCurrency<Self::AccountId, Moment=Self::BlockNumber>;
+
+	/// Convert the block number into a balance.
+	type BlockNumberToBalance: Convert<Self::BlockNumber, BalanceOf<Self>>;
+
+	/// The minimum amount to be transferred to create a new vesting schedule.
+	type MinVestedTransfer: Get<BalanceOf<Self>>;
+
+	/// The maximum number of vesting schedules an account may have at any one time.
+	type MaxVestingSchedules: Get<u32>;
+}
+
+const VESTING_ID: LockIdentifier = *b"vesting ";
+
+/// Struct to encode the vesting schedule of an individual account.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct VestingInfo<Balance, BlockNumber> {
+	/// Locked amount at the start of the schedule.
+	pub locked: Balance,
+	/// Amount that gets unlocked every block after `starting_block`.
+	pub per_block: Balance,
+	/// Starting block for unlocking(vesting).
+	pub starting_block: BlockNumber,
+}
+
+impl<
+	Balance: SimpleArithmetic + Copy,
+	BlockNumber: SimpleArithmetic + Copy,
+> VestingInfo<Balance, BlockNumber> {
+	/// Amount locked at block `n`.
+	pub fn locked_at<
+		BlockNumberToBalance: Convert<BlockNumber, Balance>
+	>(&self, n: BlockNumber) -> Balance {
+		// Number of blocks that count toward vesting
+		// Saturating to 0 when n < starting_block
+		let vested_block_count = n.saturating_sub(self.starting_block);
+		let vested_block_count = BlockNumberToBalance::convert(vested_block_count);
+		// Return amount that is still locked in vesting
+		if let Some(x) = vested_block_count.checked_mul(&self.per_block) {
+			self.locked.max(x) - x
+		} else {
+			Zero::zero()
+		}
+	}
+
+	/// The block, converted into a balance, at which the schedule is fully vested, or the
+	/// maximum value if it never is.
+	pub fn ending_block_as_balance<
+		BlockNumberToBalance: Convert<BlockNumber, Balance>
+	>(&self) -> Balance {
+		if self.per_block.is_zero() {
+			return Balance::max_value()
+		}
+		let mut duration = self.locked / self.per_block;
+		if !(self.locked % self.per_block).is_zero() {
+			duration = duration.saturating_add(One::one());
+		}
+		BlockNumberToBalance::convert(self.starting_block).saturating_add(duration)
+	}
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Vesting {
+		/// Information regarding the vesting of a given account.
+		pub Vesting get(fn vesting):
+			map hasher(blake2_256) T::AccountId => Vec<VestingInfo<BalanceOf<T>, T::BlockNumber>>;
+	}
+	add_extra_genesis {
+		config(vesting): Vec<(T::AccountId, T::BlockNumber, T::BlockNumber, BalanceOf<T>)>;
+		// ^^ begin, length, amount liquid at genesis
+		build(|config: &GenesisConfig<T>| {
+			// Generate initial vesting configuration
+			// * who - Account which we are generating vesting configuration for
+			// * begin - Block when the account will start to vest
+			// * length - Number of blocks from `begin` until fully vested
+			// * liquid - Number of units which can be spent before vesting begins
+			for &(ref who, begin, length, liquid) in config.vesting.iter() {
+				let balance = T::Currency::free_balance(who);
+				assert!(!balance.is_zero(), "Currencies must be init'd before vesting");
+				assert!(!Vesting::<T>::exists(who), "Duplicate vesting schedule in genesis");
+				// Total genesis `balance` minus `liquid` equals funds locked for vesting
+				let locked = balance.saturating_sub(liquid);
+				let length_as_balance = T::BlockNumberToBalance::convert(length);
+				// Number of units unlocked per block after `begin`
+				let per_block = locked / length_as_balance.max(sp_runtime::traits::One::one());
+
+				Vesting::<T>::insert(who, vec![VestingInfo {
+					locked,
+					per_block,
+					starting_block: begin,
+				}]);
+				let reasons = WithdrawReason::Transfer | WithdrawReason::Reserve;
+				T::Currency::set_lock(VESTING_ID, who, locked, T::BlockNumber::max_value(), reasons);
+			}
+		});
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where AccountId = <T as frame_system::Trait>::AccountId, Balance = BalanceOf<T> {
+		/// The amount vested has been updated. This could indicate more funds are available. The
+		/// balance given is the amount which is left unvested (and thus locked).
+		VestingUpdated(AccountId, Balance),
+		/// An account (given) has become fully vested. No further vesting can happen.
+		VestingCompleted(AccountId),
+	}
+);
+
+decl_error! {
+	/// Error for the vesting module.
+	pub enum Error for Module<T: Trait> {
+		/// The account given is not vesting.
+		NotVesting,
+		/// The account already has the maximum number of vesting schedules.
+		AtMaxVestingSchedules,
+		/// Amount being transferred is too low to create a vesting schedule.
+		AmountLow,
+		/// The schedules to merge are the same or do not exist.
+		ScheduleIndexOutOfBounds,
+		/// The vesting schedule would never unlock any funds.
+		InvalidScheduleParams,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		/// The minimum amount to be transferred to create a new vesting schedule.
+		const MinVestedTransfer: BalanceOf<T> = T::MinVestedTransfer::get();
+
+		/// The maximum number of vesting schedules an account may have at any one time.
+		const MaxVestingSchedules: u32 = T::MaxVestingSchedules::get();
+
+		fn deposit_event() = default;
+
+		/// Unlock any vested funds of the sender account.
+		///
+		/// The dispatch origin for this call must be _Signed_ and the sender must have funds still
+		/// locked under this module.
+		///
+		/// Emits either `VestingCompleted` or `VestingUpdated`.
+		///
+		/// # <weight>
+		/// - `O(S)` where `S` is the number of vesting schedules of the sender, which is bounded
+		///   by `MaxVestingSchedules`.
+		/// - One storage read and write of `Vesting`.
+		/// - One balance-lock operation.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		fn vest(origin) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::update_lock(who)
+		}
+
+		/// Unlock any vested funds of a `target` account.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `target`: The account whose vested funds should be unlocked. Must have funds still
+		/// locked under this module.
+		///
+		/// Emits either `VestingCompleted` or `VestingUpdated`.
+		///
+		/// # <weight>
+		/// - `O(S)` where `S` is the number of vesting schedules of `target`, which is bounded
+		///   by `MaxVestingSchedules`.
+		/// - One storage read and write of `Vesting`.
+		/// - One balance-lock operation.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		fn vest_other(origin, target: <T::Lookup as StaticLookup>::Source) -> DispatchResult {
+			ensure_signed(origin)?;
+			Self::update_lock(T::Lookup::lookup(target)?)
+		}
+
+		/// Create a vested transfer.
+		///
+		/// The dispatch origin for this call must be _Signed_.
+		///
+		/// - `target`: The account that should be transferred the vested funds.
+		/// - `schedule`: The vesting schedule attached to the transfer. `schedule.locked` is the
+		///   amount transferred, and must be at least `MinVestedTransfer`. `schedule.per_block`
+		///   must not be zero, or the funds would never vest.
+		///
+		/// Emits `VestingUpdated` or, if the schedule is already fully vested, `VestingCompleted`.
+		///
+		/// # <weight>
+		/// - `O(S)` where `S` is the number of vesting schedules of `target`, which is bounded
+		///   by `MaxVestingSchedules`.
+		/// - One balance transfer.
+		/// - One storage read and write of `Vesting`.
+		/// - One balance-lock operation.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		fn vested_transfer(
+			origin,
+			target: <T::Lookup as StaticLookup>::Source,
+			schedule: VestingInfo<BalanceOf<T>, T::BlockNumber>,
+		) -> DispatchResult {
+			let transactor = ensure_signed(origin)?;
+			ensure!(schedule.locked >= T::MinVestedTransfer::get(), Error::<T>::AmountLow);
+			ensure!(!schedule.per_block.is_zero(), Error::<T>::InvalidScheduleParams);
+
+			let who = T::Lookup::lookup(target)?;
+			ensure!(Self::can_add_schedule(&who), Error::<T>::AtMaxVestingSchedules);
+
+			T::Currency::transfer(
+				&transactor,
+				&who,
+				schedule.locked,
+				ExistenceRequirement::AllowDeath,
+			)?;
+
+			Self::add_vesting_schedule(&who, schedule.locked, schedule.per_block, schedule.starting_block)
+		}
+
+		/// Merge two vesting schedules of the sender into one, freeing up a slot for another.
+		///
+		/// The dispatch origin for this call must be _Signed_; only the account holding the
+		/// schedules may merge them.
+		///
+		/// - `schedule1_index`, `schedule2_index`: The indices of the two schedules to merge.
+		///
+		/// The merged schedule locks what both schedules still lock, starts vesting at the latest
+		/// of their starting blocks and the current block and is fully vested when the later of
+		/// the two would have been, so no funds are unlocked sooner than before.
+		///
+		/// Emits either `VestingCompleted` or `VestingUpdated`.
+		///
+		/// # <weight>
+		/// - `O(S)` where `S` is the number of vesting schedules of the sender, which is bounded
+		///   by `MaxVestingSchedules`.
+		/// - One storage read and write of `Vesting`.
+		/// - One balance-lock operation.
+		/// - One event.
+		/// # </weight>
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		fn merge_schedules(origin, schedule1_index: u32, schedule2_index: u32) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut schedules = Self::vesting(&who);
+			let (first, second) = (schedule1_index as usize, schedule2_index as usize);
+			ensure!(
+				first != second && first < schedules.len() && second < schedules.len(),
+				Error::<T>::ScheduleIndexOutOfBounds,
+			);
+
+			let merged = Self::merge_vesting_info(schedules[first], schedules[second]);
+			schedules.remove(first.max(second));
+			schedules.remove(first.min(second));
+			schedules.push(merged);
+			Vesting::<T>::insert(&who, schedules);
+			Self::update_lock(who)
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// (Re)set or remove the module's currency lock on `who`'s account in accordance with their
+	/// current unvested amount, dropping any schedules which have fully vested.
+	fn update_lock(who: T::AccountId) -> DispatchResult {
+		let schedules = Self::vesting(&who);
+		ensure!(!schedules.is_empty(), Error::<T>::NotVesting);
+
+		let now = <frame_system::Module<T>>::block_number();
+		let mut locked_now: BalanceOf<T> = Zero::zero();
+		let mut remaining = Vec::with_capacity(schedules.len());
+		for schedule in schedules {
+			let locked = schedule.locked_at::<T::BlockNumberToBalance>(now);
+			if !locked.is_zero() {
+				locked_now = locked_now.saturating_add(locked);
+				remaining.push(schedule);
+			}
+		}
+
+		if remaining.is_empty() {
+			T::Currency::remove_lock(VESTING_ID, &who);
+			Vesting::<T>::remove(&who);
+			Self::deposit_event(RawEvent::VestingCompleted(who));
+		} else {
+			let reasons = WithdrawReason::Transfer | WithdrawReason::Reserve;
+			T::Currency::set_lock(VESTING_ID, &who, locked_now, T::BlockNumber::max_value(), reasons);
+			Vesting::<T>::insert(&who, remaining);
+			Self::deposit_event(RawEvent::VestingUpdated(who, locked_now));
+		}
+		Ok(())
+	}
+
+	/// Merge two vesting schedules into one that never unlocks funds sooner than both would.
+	fn merge_vesting_info(
+		schedule1: VestingInfo<BalanceOf<T>, T::BlockNumber>,
+		schedule2: VestingInfo<BalanceOf<T>, T::BlockNumber>,
+	) -> VestingInfo<BalanceOf<T>, T::BlockNumber> {
+		let now = <frame_system::Module<T>>::block_number();
+		let locked = schedule1.locked_at::<T::BlockNumberToBalance>(now)
+			.saturating_add(schedule2.locked_at::<T::BlockNumberToBalance>(now));
+		let starting_block = now.max(schedule1.starting_block).max(schedule2.starting_block);
+		let ending_block = schedule1.ending_block_as_balance::<T::BlockNumberToBalance>()
+			.max(schedule2.ending_block_as_balance::<T::BlockNumberToBalance>());
+
+		let per_block = if ending_block == BalanceOf::<T>::max_value() {
+			Zero::zero()
+		} else {
+			let duration = ending_block
+				.saturating_sub(T::BlockNumberToBalance::convert(starting_block))
+				.max(One::one());
+			(locked / duration).max(One::one())
+		};
+		VestingInfo { locked, per_block, starting_block }
+	}
+
+	/// Whether `who` has room for another vesting schedule.
+	fn can_add_schedule(who: &T::AccountId) -> bool {
+		Self::vesting(who).len() < T::MaxVestingSchedules::get() as usize
+	}
+}
+
+impl<T: Trait> VestingSchedule<T::AccountId> for Module<T> {
+	type Moment = T::BlockNumber;
+	type Currency = T::Currency;
+
+	/// Get the amount that is currently being vested and cannot be transferred out of this account.
+	fn vesting_balance(who: &T::AccountId) -> Option<BalanceOf<T>> {
+		let schedules = Self::vesting(who);
+		if schedules.is_empty() {
+			return None
+		}
+		let now = <frame_system::Module<T>>::block_number();
+		let locked_now = schedules.iter().fold(Zero::zero(), |acc: BalanceOf<T>, schedule|
+			acc.saturating_add(schedule.locked_at::<T::BlockNumberToBalance>(now))
+		);
+		Some(T::Currency::free_balance(who).min(locked_now))
+	}
+
+	/// Adds a vesting schedule to a given account.
+	///
+	/// If the account has already reached `MaxVestingSchedules`, an `Err` is returned and nothing
+	/// is updated.
+	///
+	/// On success, a linearly reducing amount of funds will be locked. In order to realise any
+	/// reduction of the lock over time as it diminishes, the account owner must use `vest` or
+	/// `vest_other`.
+	///
+	/// Is a no-op if the amount to be vested is zero.
+	fn add_vesting_schedule(
+		who: &T::AccountId,
+		locked: BalanceOf<T>,
+		per_block: BalanceOf<T>,
+		starting_block: T::BlockNumber
+	) -> DispatchResult {
+		if locked.is_zero() { return Ok(()) }
+		ensure!(Self::can_add_schedule(who), Error::<T>::AtMaxVestingSchedules);
+
+		Vesting::<T>::mutate(who, |schedules| schedules.push(VestingInfo {
+			locked,
+			per_block,
+			starting_block,
+		}));
+		Self::update_lock(who.clone())
+	}
+
+	/// Remove all vesting schedules for a given account and unlock its funds.
+	fn remove_vesting_schedule(who: &T::AccountId) {
+		Vesting::<T>::remove(who);
+		T::Currency::remove_lock(VESTING_ID, who);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use frame_support::{
+		assert_ok, assert_noop, impl_outer_origin, parameter_types, weights::Weight,
+		storage::migration::put_storage_value,
+	};
+	use sp_core::H256;
+	// The testing primitives are very useful for avoiding having to work with signatures
+	// or public keys. `u64` is used as the `AccountId` and no `Signature`s are required.
+	use sp_runtime::{
+		Perbill, testing::Header,
+		traits::{BlakeTwo256, IdentityLookup, ConvertInto, OnRuntimeUpgrade},
+	};
+	use sp_io::hashing::blake2_256;
+
+	impl_outer_origin! {
+		pub enum Origin for Test  where system = frame_system {}
+	}
+
+	// For testing the module, we construct most of a mock runtime. This means
+	// first constructing a configuration type (`Test`) which `impl`s each of the
+	// configuration traits of modules we want to use.
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::one();
+	}
+	impl frame_system::Trait for Test {
+		type Origin = Origin;
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Call = ();
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<Self::AccountId>;
+		type Header = Header;
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type MaximumBlockWeight = MaximumBlockWeight;
+		type MaximumBlockLength = MaximumBlockLength;
+		type AvailableBlockRatio = AvailableBlockRatio;
+		type Version = ();
+		type ModuleToIndex = ();
+	}
+	parameter_types! {
+		pub const ExistentialDeposit: u64 = 1;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+	}
+	impl pallet_balances::Trait for Test {
+		type Balance = u64;
+		type OnFreeBalanceZero = ();
+		type OnReapAccount = System;
+		type OnNewAccount = ();
+		type Event = ();
+		type TransferPayment = ();
+		type DustRemoval = ();
+		type ExistentialDeposit = ExistentialDeposit;
+		type TransferFee = TransferFee;
+		type CreationFee = CreationFee;
+	}
+	parameter_types! {
+		pub const MinVestedTransfer: u64 = 10;
+		pub const MaxVestingSchedules: u32 = 3;
+	}
+	impl Trait for Test {
+		type Event = ();
+		type Currency = Balances;
+		type BlockNumberToBalance = ConvertInto;
+		type MinVestedTransfer = MinVestedTransfer;
+		type MaxVestingSchedules = MaxVestingSchedules;
+	}
+	type System = frame_system::Module<Test>;
+	type Balances = pallet_balances::Module<Test>;
+	type Vesting = Module<Test>;
+	type BalancesError = pallet_balances::Error<Test, pallet_balances::DefaultInstance>;
+
+	// This function basically just builds a genesis storage key/value store according to
+	// our desired mockup.
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+		pallet_balances::GenesisConfig::<Test> {
+			balances: vec![(1, 100), (2, 200), (3, 300), (4, 400), (12, 100)],
+		}.assimilate_storage(&mut t).unwrap();
+		GenesisConfig::<Test> {
+			vesting: vec![
+				(1, 0, 10, 50),
+				(2, 10, 20, 0),
+				(12, 10, 20, 60),
+			],
+		}.assimilate_storage(&mut t).unwrap();
+		let mut ext = sp_io::TestExternalities::new(t);
+		ext.execute_with(|| System::set_block_number(1));
+		ext
+	}
+
+	fn schedule(locked: u64, per_block: u64, starting_block: u64) -> VestingInfo<u64, u64> {
+		VestingInfo { locked, per_block, starting_block }
+	}
+
+	#[test]
+	fn check_vesting_status() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Vesting::vesting(&1), vec![schedule(50, 5, 0)]); // Vesting over 10 blocks
+			assert_eq!(Vesting::vesting(&2), vec![schedule(200, 10, 10)]); // Vesting over 20 blocks
+			assert_eq!(Vesting::vesting(&12), vec![schedule(40, 2, 10)]); // Vesting over 20 blocks
+			assert_eq!(Vesting::vesting_balance(&3), None);
+
+			// Account 1 has only 5 units vested from their illiquid 50 units at block 1
+			assert_eq!(Vesting::vesting_balance(&1), Some(45));
+			// Account 2 has their full balance locked
+			assert_eq!(Vesting::vesting_balance(&2), Some(200));
+			// Account 12 has only their illiquid funds locked
+			assert_eq!(Vesting::vesting_balance(&12), Some(40));
+
+			System::set_block_number(10);
+
+			// Account 1 has fully vested by block 10
+			assert_eq!(Vesting::vesting_balance(&1), Some(0));
+			// Account 2 has started vesting by block 10
+			assert_eq!(Vesting::vesting_balance(&2), Some(200));
+			// Account 12 has started vesting by block 10
+			assert_eq!(Vesting::vesting_balance(&12), Some(40));
+
+			System::set_block_number(30);
+
+			assert_eq!(Vesting::vesting_balance(&1), Some(0)); // Account 1 is still fully vested, and not negative
+			assert_eq!(Vesting::vesting_balance(&2), Some(0)); // Account 2 has fully vested by block 30
+			assert_eq!(Vesting::vesting_balance(&12), Some(0)); // Account 12 has fully vested by block 30
+
+			// Once vested, the schedules are removed along with the lock.
+			assert_ok!(Vesting::vest(Some(1).into()));
+			assert_ok!(Vesting::vest(Some(2).into()));
+			assert_ok!(Vesting::vest_other(Some(3).into(), 12));
+			assert_eq!(Vesting::vesting_balance(&1), None);
+			assert_eq!(Vesting::vesting_balance(&2), None);
+			assert_eq!(Vesting::vesting_balance(&12), None);
+			assert!(Balances::locks(&2).is_empty());
+			assert_noop!(Vesting::vest(Some(2).into()), Error::<Test>::NotVesting);
+		});
+	}
+
+	#[test]
+	fn unvested_balance_should_not_transfer() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Balances::free_balance(&1), 100);
+			// Account 1 has only 5 units vested at block 1 (plus 50 unvested)
+			assert_eq!(Vesting::vesting_balance(&1), Some(45));
+			assert_noop!(
+				Balances::transfer(Some(1).into(), 2, 56),
+				BalancesError::LiquidityRestrictions,
+			); // Account 1 cannot send more than vested amount
+		});
+	}
+
+	#[test]
+	fn vested_balance_should_transfer() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Balances::free_balance(&1), 100);
+			// Account 1 has only 5 units vested at block 1 (plus 50 unvested)
+			assert_eq!(Vesting::vesting_balance(&1), Some(45));
+			// The lock only shrinks once the account has vested.
+			assert_noop!(
+				Balances::transfer(Some(1).into(), 2, 55),
+				BalancesError::LiquidityRestrictions,
+			);
+			assert_ok!(Vesting::vest(Some(1).into()));
+			assert_ok!(Balances::transfer(Some(1).into(), 2, 55));
+		});
+	}
+
+	#[test]
+	fn vested_balance_should_transfer_using_vest_other() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Vesting::vesting_balance(&1), Some(45));
+			assert_ok!(Vesting::vest_other(Some(2).into(), 1));
+			assert_ok!(Balances::transfer(Some(1).into(), 2, 55));
+			assert_noop!(Vesting::vest_other(Some(2).into(), 3), Error::<Test>::NotVesting);
+		});
+	}
+
+	#[test]
+	fn extra_balance_should_transfer() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Balances::transfer(Some(3).into(), 1, 100));
+			assert_ok!(Balances::transfer(Some(3).into(), 2, 100));
+
+			assert_eq!(Balances::free_balance(&1), 200); // Account 1 has 100 more free balance than normal
+			assert_eq!(Balances::free_balance(&2), 300); // Account 2 has 100 more free balance than normal
+
+			// Account 1 has only 5 units vested at block 1 (plus 150 unvested)
+			assert_eq!(Vesting::vesting_balance(&1), Some(45));
+			assert_ok!(Vesting::vest(Some(1).into()));
+			assert_ok!(Balances::transfer(Some(1).into(), 3, 155)); // Account 1 can send extra units gained
+
+			// Account 2 has no units vested at block 1, but gained 100
+			assert_eq!(Vesting::vesting_balance(&2), Some(200));
+			assert_ok!(Vesting::vest(Some(2).into()));
+			assert_ok!(Balances::transfer(Some(2).into(), 3, 100)); // Account 2 can send extra units gained
+		});
+	}
+
+	#[test]
+	fn liquid_funds_should_transfer_with_delayed_vesting() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Balances::free_balance(&12), 100);
+			// Account 12 has liquid funds
+			assert_eq!(Vesting::vesting_balance(&12), Some(40));
+			// Account 12 has delayed vesting
+			assert_eq!(Vesting::vesting(&12), vec![schedule(40, 2, 10)]);
+
+			// Account 12 can still send liquid funds
+			assert_ok!(Balances::transfer(Some(12).into(), 3, 60));
+		});
+	}
+
+	#[test]
+	fn vested_transfer_works() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Balances::free_balance(&3), 300);
+			assert_eq!(Balances::free_balance(&4), 400);
+			assert_eq!(Vesting::vesting_balance(&4), None);
+
+			let new_schedule = schedule(100, 10, 10);
+			assert_ok!(Vesting::vested_transfer(Some(3).into(), 4, new_schedule));
+			assert_eq!(Balances::free_balance(&3), 200);
+			assert_eq!(Balances::free_balance(&4), 500);
+			assert_eq!(Vesting::vesting(&4), vec![new_schedule]);
+			assert_eq!(Vesting::vesting_balance(&4), Some(100));
+
+			// Only the funds which arrived vesting are locked.
+			assert_noop!(
+				Balances::transfer(Some(4).into(), 3, 401),
+				BalancesError::LiquidityRestrictions,
+			);
+			assert_ok!(Balances::transfer(Some(4).into(), 3, 400));
+
+			System::set_block_number(15);
+			assert_eq!(Vesting::vesting_balance(&4), Some(50));
+
+			System::set_block_number(20);
+			assert_ok!(Vesting::vest(Some(4).into()));
+			assert_eq!(Vesting::vesting_balance(&4), None);
+			assert_ok!(Balances::transfer(Some(4).into(), 3, 100));
+		});
+	}
+
+	#[test]
+	fn vested_transfer_correctly_fails() {
+		new_test_ext().execute_with(|| {
+			// The transfer must be at least `MinVestedTransfer`.
+			assert_noop!(
+				Vesting::vested_transfer(Some(3).into(), 4, schedule(9, 1, 10)),
+				Error::<Test>::AmountLow,
+			);
+			// The sender must be able to pay for it.
+			assert_noop!(
+				Vesting::vested_transfer(Some(3).into(), 4, schedule(301, 1, 10)),
+				BalancesError::InsufficientBalance,
+			);
+			// The sender's own vesting lock is respected.
+			assert_noop!(
+				Vesting::vested_transfer(Some(2).into(), 4, schedule(10, 1, 10)),
+				BalancesError::LiquidityRestrictions,
+			);
+
+			// Account 1 already has one schedule, so it can take two more.
+			assert_ok!(Vesting::vested_transfer(Some(3).into(), 1, schedule(10, 1, 10)));
+			assert_ok!(Vesting::vested_transfer(Some(3).into(), 1, schedule(10, 1, 10)));
+			assert_noop!(
+				Vesting::vested_transfer(Some(3).into(), 1, schedule(10, 1, 10)),
+				Error::<Test>::AtMaxVestingSchedules,
+			);
+			assert_eq!(Balances::free_balance(&3), 280);
+		});
+	}
+
+	#[test]
+	fn multiple_schedules_vest_independently() {
+		new_test_ext().execute_with(|| {
+			let second = schedule(20, 1, 1);
+			assert_ok!(Vesting::vested_transfer(Some(3).into(), 1, second));
+			assert_eq!(Vesting::vesting(&1), vec![schedule(50, 5, 0), second]);
+			assert_eq!(Balances::free_balance(&1), 120);
+			assert_eq!(Vesting::vesting_balance(&1), Some(65));
+
+			System::set_block_number(11);
+			// The first schedule has fully vested; 10 units of the second remain.
+			assert_eq!(Vesting::vesting_balance(&1), Some(10));
+			assert_ok!(Vesting::vest(Some(1).into()));
+			assert_eq!(Vesting::vesting(&1), vec![second]);
+			assert_noop!(
+				Balances::transfer(Some(1).into(), 2, 111),
+				BalancesError::LiquidityRestrictions,
+			);
+			assert_ok!(Balances::transfer(Some(1).into(), 2, 110));
+		});
+	}
+
+	#[test]
+	fn merging_schedules_frees_a_slot() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Vesting::vested_transfer(Some(3).into(), 1, schedule(20, 1, 1)));
+			assert_ok!(Vesting::vested_transfer(Some(3).into(), 1, schedule(10, 1, 10)));
+			assert_noop!(
+				Vesting::vested_transfer(Some(3).into(), 1, schedule(10, 1, 10)),
+				Error::<Test>::AtMaxVestingSchedules,
+			);
+			assert_eq!(Vesting::vesting_balance(&1), Some(75));
+
+			assert_noop!(
+				Vesting::merge_schedules(Some(1).into(), 1, 1),
+				Error::<Test>::ScheduleIndexOutOfBounds,
+			);
+			assert_noop!(
+				Vesting::merge_schedules(Some(1).into(), 1, 3),
+				Error::<Test>::ScheduleIndexOutOfBounds,
+			);
+			// Only the account itself can merge its schedules.
+			assert_noop!(
+				Vesting::merge_schedules(Some(3).into(), 1, 2),
+				Error::<Test>::ScheduleIndexOutOfBounds,
+			);
+
+			// 30 units are left to vest from block 10 until block 21, when the second schedule
+			// would have fully vested.
+			assert_ok!(Vesting::merge_schedules(Some(1).into(), 2, 1));
+			assert_eq!(Vesting::vesting(&1), vec![schedule(50, 5, 0), schedule(30, 2, 10)]);
+			assert_eq!(Vesting::vesting_balance(&1), Some(75));
+			assert_ok!(Vesting::vested_transfer(Some(3).into(), 1, schedule(10, 1, 10)));
+
+			System::set_block_number(20);
+			assert_eq!(Vesting::vesting_balance(&1), Some(10));
+		});
+	}
+
+	#[test]
+	fn balances_schedules_are_migrated_on_upgrade() {
+		new_test_ext().execute_with(|| {
+			let hash = |who: u64| who.using_encoded(blake2_256);
+			put_storage_value(b"Balances", b"Vesting", &hash(1), schedule(30, 1, 0));
+			put_storage_value(b"Balances", b"Vesting", &hash(4), schedule(100, 10, 0));
+			put_storage_value(b"Balances", b"IsUpgraded", &[], false);
+
+			<Balances as OnRuntimeUpgrade>::on_runtime_upgrade();
+
+			assert_eq!(Vesting::vesting(&1), vec![schedule(30, 1, 0), schedule(50, 5, 0)]);
+			assert_eq!(Vesting::vesting(&4), vec![schedule(100, 10, 0)]);
+			// 45 + 29 units remain locked for account 1 and 90 for account 4 at block 1.
+			assert_eq!(Vesting::vesting_balance(&1), Some(74));
+			assert_noop!(
+				Balances::transfer(Some(1).into(), 3, 27),
+				BalancesError::LiquidityRestrictions,
+			);
+			assert_noop!(
+				Balances::transfer(Some(4).into(), 3, 311),
+				BalancesError::LiquidityRestrictions,
+			);
+			assert_ok!(Balances::transfer(Some(4).into(), 3, 310));
+
+			// The lock follows the migrated schedules as they vest.
+			System::set_block_number(11);
+			assert_ok!(Vesting::vest(Some(1).into()));
+			assert_eq!(Vesting::vesting(&1), vec![schedule(30, 1, 0)]);
+			assert_ok!(Balances::transfer(Some(1).into(), 3, 81));
+		});
+	}
+
+	#[test]
+	fn vested_transfer_rejects_schedules_that_never_vest() {
+		new_test_ext().execute_with(|| {
+			assert_noop!(
+				Vesting::vested_transfer(Some(3).into(), 4, schedule(10, 0, 10)),
+				Error::<Test>::InvalidScheduleParams,
+			);
+			assert_eq!(Vesting::vesting(&4), vec![]);
+		});
+	}
+}
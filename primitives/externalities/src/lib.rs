@@ -202,6 +202,24 @@ pub trait Externalities: ExtensionStore {
 	///
 	/// Returns the SCALE encoded hash.
 	fn storage_changes_root(&mut self, parent: &[u8]) -> Result<Option<Vec<u8>>, ()>;
+
+	/// Start a new nested storage transaction.
+	///
+	/// All changes made to storage from now on can either be undone with
+	/// `storage_rollback_transaction` or kept with `storage_commit_transaction`. Every started
+	/// transaction must be closed by exactly one call to either of these.
+	fn storage_start_transaction(&mut self);
+
+	/// Undo all storage changes made since the innermost open transaction was started, and close
+	/// it.
+	///
+	/// Returns an error if there is no open transaction.
+	fn storage_rollback_transaction(&mut self) -> Result<(), ()>;
+
+	/// Close the innermost open storage transaction, keeping all changes made within it.
+	///
+	/// Returns an error if there is no open transaction.
+	fn storage_commit_transaction(&mut self) -> Result<(), ()>;
 }
 
 /// Extension for the [`Externalities`] trait.
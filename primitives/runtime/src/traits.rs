@@ -343,6 +343,15 @@ pub trait OnInitialize<BlockNumber> {
 	fn on_initialize(_n: BlockNumber) {}
 }
 
+/// The runtime upgrade trait. Implementing this lets you express what should happen for your
+/// module when the runtime is upgraded, e.g. migrating its storage to a new layout.
+#[impl_for_tuples(30)]
+pub trait OnRuntimeUpgrade {
+	/// The runtime has been upgraded. Called at the beginning of the first block executed by the
+	/// new runtime, before `on_initialize`.
+	fn on_runtime_upgrade() {}
+}
+
 /// Off-chain computation trait.
 ///
 /// Implementing this trait on a module allows you to perform long-running tasks
@@ -37,12 +37,14 @@ use codec::Encode;
 #[derive(Debug)]
 pub struct BasicExternalities {
 	inner: Storage,
+	/// Snapshots of `inner` taken when each of the open storage transactions was started.
+	transactions: Vec<Storage>,
 }
 
 impl BasicExternalities {
 	/// Create a new instance of `BasicExternalities`
 	pub fn new(inner: Storage) -> Self {
-		BasicExternalities { inner }
+		BasicExternalities { inner, transactions: Vec::new() }
 	}
 
 	/// Insert key/value
@@ -62,10 +64,10 @@ impl BasicExternalities {
 		storage: &mut sp_core::storage::Storage,
 		f: impl FnOnce() -> R,
 	) -> R {
-		let mut ext = Self { inner: Storage {
+		let mut ext = Self::new(Storage {
 			top: std::mem::replace(&mut storage.top, Default::default()),
 			children: std::mem::replace(&mut storage.children, Default::default()),
-		}};
+		});
 
 		let r = ext.execute_with(f);
 
@@ -103,10 +105,10 @@ impl Default for BasicExternalities {
 
 impl From<BTreeMap<StorageKey, StorageValue>> for BasicExternalities {
 	fn from(hashmap: BTreeMap<StorageKey, StorageValue>) -> Self {
-		BasicExternalities { inner: Storage {
+		BasicExternalities::new(Storage {
 			top: hashmap,
 			children: Default::default(),
-		}}
+		})
 	}
 }
 
@@ -298,6 +300,19 @@ impl Externalities for BasicExternalities {
 	fn storage_changes_root(&mut self, _parent: &[u8]) -> Result<Option<Vec<u8>>, ()> {
 		Ok(None)
 	}
+
+	fn storage_start_transaction(&mut self) {
+		self.transactions.push(self.inner.clone());
+	}
+
+	fn storage_rollback_transaction(&mut self) -> Result<(), ()> {
+		self.inner = self.transactions.pop().ok_or(())?;
+		Ok(())
+	}
+
+	fn storage_commit_transaction(&mut self) -> Result<(), ()> {
+		self.transactions.pop().map(|_| ()).ok_or(())
+	}
 }
 
 impl sp_externalities::ExtensionStore for BasicExternalities {
@@ -366,6 +381,29 @@ mod tests {
 		assert_eq!(ext.child_storage(child(), CHILD_INFO_1, b"doe"), None);
 	}
 
+	#[test]
+	fn transactions_work() {
+		let mut ext = BasicExternalities::default();
+		ext.set_storage(b"doe".to_vec(), b"reindeer".to_vec());
+
+		ext.storage_start_transaction();
+		ext.set_storage(b"dog".to_vec(), b"puppy".to_vec());
+		ext.storage_start_transaction();
+		ext.clear_storage(b"doe");
+		assert_eq!(ext.storage_rollback_transaction(), Ok(()));
+		assert_eq!(ext.storage(b"doe"), Some(b"reindeer".to_vec()));
+		assert_eq!(ext.storage_commit_transaction(), Ok(()));
+		assert_eq!(ext.storage(b"dog"), Some(b"puppy".to_vec()));
+
+		ext.storage_start_transaction();
+		ext.set_storage(b"dog".to_vec(), b"cat".to_vec());
+		assert_eq!(ext.storage_rollback_transaction(), Ok(()));
+		assert_eq!(ext.storage(b"dog"), Some(b"puppy".to_vec()));
+
+		assert_eq!(ext.storage_commit_transaction(), Err(()));
+		assert_eq!(ext.storage_rollback_transaction(), Err(()));
+	}
+
 	#[test]
 	fn basic_externalities_is_empty() {
 		// Make sure no values are set by default in `BasicExternalities`.
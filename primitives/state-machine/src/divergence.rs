@@ -0,0 +1,75 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Reporting of discrepancies between native and wasm execution.
+//!
+//! When executing with [`ExecutionStrategy::Both`](crate::ExecutionStrategy::Both) and a
+//! [`DivergenceReporterExt`] is registered, a mismatch between the native and the wasm run is
+//! handed to the reporter instead of the consensus failure handler, and the wasm result is used.
+
+use crate::overlayed_changes::{StorageCollection, ChildStorageCollection};
+
+/// A discrepancy observed between the native and the wasm execution of a runtime call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionDivergence {
+	/// The runtime method that was called.
+	pub method: String,
+	/// The SCALE encoded call data.
+	pub call_data: Vec<u8>,
+	/// The SCALE encoded result of the native execution, or the error as string.
+	pub native_result: Result<Vec<u8>, String>,
+	/// The SCALE encoded result of the wasm execution, or the error as string.
+	pub wasm_result: Result<Vec<u8>, String>,
+	/// Top level storage changes made by the native execution.
+	pub native_changes: StorageCollection,
+	/// Top level storage changes made by the wasm execution.
+	pub wasm_changes: StorageCollection,
+	/// Child storage changes made by the native execution.
+	pub native_child_changes: ChildStorageCollection,
+	/// Child storage changes made by the wasm execution.
+	pub wasm_child_changes: ChildStorageCollection,
+}
+
+impl ExecutionDivergence {
+	/// Returns `true` if the results of both executions differ.
+	pub fn results_differ(&self) -> bool {
+		self.native_result != self.wasm_result
+	}
+
+	/// Returns `true` if the storage changes of both executions differ.
+	pub fn changes_differ(&self) -> bool {
+		self.native_changes != self.wasm_changes
+			|| self.native_child_changes != self.wasm_child_changes
+	}
+}
+
+/// Something that records native/wasm execution divergences.
+pub trait DivergenceReporter: Send + Sync {
+	/// Report the given divergence.
+	fn report(&self, divergence: ExecutionDivergence);
+}
+
+sp_externalities::decl_extension! {
+	/// The divergence reporter extension to register/retrieve from the externalities.
+	pub struct DivergenceReporterExt(Box<dyn DivergenceReporter>);
+}
+
+impl DivergenceReporterExt {
+	/// Creates a new instance of `Self`.
+	pub fn new<T: DivergenceReporter + 'static>(inner: T) -> Self {
+		Self(Box::new(inner))
+	}
+}
@@ -582,6 +582,22 @@ where
 
 		root.map(|r| r.map(|o| o.encode()))
 	}
+
+	fn storage_start_transaction(&mut self) {
+		trace!(target: "state-trace", "{:04x}: StartTransaction", self.id);
+		self.overlay.start_transaction();
+	}
+
+	fn storage_rollback_transaction(&mut self) -> Result<(), ()> {
+		trace!(target: "state-trace", "{:04x}: RollbackTransaction", self.id);
+		self.mark_dirty();
+		self.overlay.rollback_transaction()
+	}
+
+	fn storage_commit_transaction(&mut self) -> Result<(), ()> {
+		trace!(target: "state-trace", "{:04x}: CommitTransaction", self.id);
+		self.overlay.commit_transaction()
+	}
 }
 
 impl<'a, H, B, N> sp_externalities::ExtensionStore for Ext<'a, H, N, B>
@@ -627,6 +643,7 @@ mod tests {
 			].into_iter().collect(),
 			committed: Default::default(),
 			collect_extrinsics: true,
+			transactions: Default::default(),
 		}
 	}
 
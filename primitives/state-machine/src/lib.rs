@@ -18,7 +18,7 @@
 
 #![warn(missing_docs)]
 
-use std::{fmt, result, collections::HashMap, panic::UnwindSafe, marker::PhantomData};
+use std::{fmt, result, collections::HashMap, panic::UnwindSafe, marker::PhantomData, any::TypeId};
 use log::{warn, trace};
 use hash_db::Hasher;
 use codec::{Decode, Encode, Codec};
@@ -32,6 +32,7 @@ use sp_externalities::Extensions;
 pub mod backend;
 mod in_memory_backend;
 mod changes_trie;
+mod divergence;
 mod error;
 mod ext;
 mod testing;
@@ -73,6 +74,7 @@ pub use proving_backend::{
 pub use trie_backend_essence::{TrieBackendStorage, Storage};
 pub use trie_backend::TrieBackend;
 pub use error::{Error, ExecutionError};
+pub use divergence::{ExecutionDivergence, DivergenceReporter, DivergenceReporterExt};
 pub use in_memory_backend::InMemory as InMemoryBackend;
 pub use stats::{UsageInfo, UsageUnit};
 
@@ -95,6 +97,9 @@ pub enum ExecutionStrategy {
 	/// Use the given wasm module.
 	AlwaysWasm,
 	/// Run with both the wasm and the native variant (if compatible). Report any discrepancy as an error.
+	///
+	/// If a [`DivergenceReporterExt`] is registered, discrepancies in either the result or the
+	/// storage changes are reported to it instead and the wasm result is used.
 	Both,
 	/// First native, then if that fails or is not possible, wasm.
 	NativeElseWasm,
@@ -175,6 +180,14 @@ fn always_untrusted_wasm<E, R: Decode>() -> ExecutionManager<DefaultHandler<R, E
 	ExecutionManager::AlwaysWasm(BackendTrustLevel::Untrusted)
 }
 
+/// Convert a call result into its SCALE encoded form, stringifying the error.
+fn encode_call_result<R: Encode, E: fmt::Display>(result: &CallResult<R, E>) -> Result<Vec<u8>, String> {
+	match result {
+		Ok(r) => Ok(r.as_encoded().into_owned()),
+		Err(e) => Err(e.to_string()),
+	}
+}
+
 /// The substrate state machine.
 pub struct StateMachine<'a, B, H, N, Exec>
 	where
@@ -325,19 +338,52 @@ impl<'a, B, H, N, Exec> StateMachine<'a, B, H, N, Exec> where
 		let (result, was_native) = self.execute_aux(true, native_call.take());
 
 		if was_native {
-			self.overlay.prospective = orig_prospective.clone();
+			let native_prospective = std::mem::replace(
+				&mut self.overlay.prospective,
+				orig_prospective,
+			);
 			let (wasm_result, _) = self.execute_aux(
 				false,
 				native_call,
 			);
 
-			if (result.is_ok() && wasm_result.is_ok()
+			let results_match = (result.is_ok() && wasm_result.is_ok()
 				&& result.as_ref().ok() == wasm_result.as_ref().ok())
-				|| result.is_err() && wasm_result.is_err()
-			{
-				result
-			} else {
-				on_consensus_failure(wasm_result, result)
+				|| result.is_err() && wasm_result.is_err();
+
+			let reporter = self.extensions.get_mut(TypeId::of::<DivergenceReporterExt>())
+				.and_then(|ext| ext.downcast_mut::<DivergenceReporterExt>());
+
+			match reporter {
+				Some(reporter) => {
+					let (native_changes, native_child_changes) =
+						native_prospective.storage_collections();
+					let (wasm_changes, wasm_child_changes) =
+						self.overlay.prospective.storage_collections();
+
+					let divergence = ExecutionDivergence {
+						method: self.method.into(),
+						call_data: self.call_data.to_vec(),
+						native_result: encode_call_result(&result),
+						wasm_result: encode_call_result(&wasm_result),
+						native_changes,
+						wasm_changes,
+						native_child_changes,
+						wasm_child_changes,
+					};
+
+					if divergence.results_differ() || divergence.changes_differ() {
+						warn!(
+							"Native and wasm execution of {} diverged. Using wasm.",
+							self.method,
+						);
+						reporter.report(divergence);
+					}
+
+					wasm_result
+				},
+				None if results_match => result,
+				None => on_consensus_failure(wasm_result, result),
 			}
 		} else {
 			result
@@ -846,6 +892,59 @@ mod tests {
 		assert!(consensus_failed);
 	}
 
+	#[test]
+	fn dual_execution_strategy_reports_divergence() {
+		struct Reporter(std::sync::Arc<parking_lot::Mutex<Vec<ExecutionDivergence>>>);
+
+		impl DivergenceReporter for Reporter {
+			fn report(&self, divergence: ExecutionDivergence) {
+				self.0.lock().push(divergence);
+			}
+		}
+
+		let reported = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+		let mut extensions = Extensions::new();
+		extensions.register(DivergenceReporterExt::new(Reporter(reported.clone())));
+
+		let mut consensus_failed = false;
+		let backend = trie_backend::tests::test_trie();
+		let mut overlayed_changes = Default::default();
+
+		let mut state_machine = StateMachine::new(
+			&backend,
+			changes_trie::disabled_state::<_, u64>(),
+			&mut overlayed_changes,
+			&DummyCodeExecutor {
+				change_changes_trie_config: false,
+				native_available: true,
+				native_succeeds: true,
+				fallback_succeeds: false,
+			},
+			"test",
+			&[1, 2],
+			extensions,
+		);
+
+		assert!(
+			state_machine.execute_using_consensus_failure_handler::<_, NeverNativeValue, fn() -> _>(
+				ExecutionManager::Both(|we, _ne| {
+					consensus_failed = true;
+					we
+				}),
+				None,
+			).is_err()
+		);
+		assert!(!consensus_failed);
+
+		let reported = reported.lock();
+		assert_eq!(reported.len(), 1);
+		assert_eq!(reported[0].method, "test");
+		assert_eq!(reported[0].call_data, vec![1, 2]);
+		assert_eq!(reported[0].native_result, Ok(vec![66]));
+		assert_eq!(reported[0].wasm_result, Err("0".to_string()));
+		assert!(!reported[0].changes_differ());
+	}
+
 	#[test]
 	fn prove_execution_and_proof_check_works() {
 		let executor = DummyCodeExecutor {
@@ -57,8 +57,25 @@ pub struct OverlayedChanges {
 	pub(crate) committed: OverlayedChangeSet,
 	/// True if extrinsiscs stats must be collected.
 	pub(crate) collect_extrinsics: bool,
+	/// The prospective values overwritten within each currently open storage transaction,
+	/// innermost last.
+	pub(crate) transactions: Vec<TransactionLayer>,
 }
 
+/// The prospective values a storage transaction overwrote, as they were before it first changed
+/// them, so that they can be restored if the transaction is rolled back.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct TransactionLayer {
+	/// Previous top level values. `None` if the key had no prospective value.
+	top: TransactionValues,
+	/// Previous child storage values, together with the child info of the child storage. The
+	/// child info is `None` if the child storage had no prospective changes.
+	children: HashMap<StorageKey, (Option<OwnedChildInfo>, TransactionValues)>,
+}
+
+/// Storage values overwritten by a storage transaction.
+type TransactionValues = BTreeMap<StorageKey, Option<OverlayedValue>>;
+
 /// The storage value, used inside OverlayedChanges.
 #[derive(Debug, Default, Clone)]
 #[cfg_attr(test, derive(PartialEq))]
@@ -187,6 +204,24 @@ impl OverlayedChangeSet {
 		self.top.clear();
 		self.children.clear();
 	}
+
+	/// Collect the values of the change set, ignoring extrinsic indices.
+	///
+	/// Child storages are ordered by their storage key.
+	pub fn storage_collections(&self) -> (StorageCollection, ChildStorageCollection) {
+		let top = self.top.iter()
+			.map(|(k, v)| (k.clone(), v.value.clone()))
+			.collect();
+		let mut children: ChildStorageCollection = self.children.iter()
+			.map(|(sk, (changes, _))| (
+				sk.clone(),
+				changes.iter().map(|(k, v)| (k.clone(), v.value.clone())).collect(),
+			))
+			.collect();
+		children.sort_by(|a, b| a.0.cmp(&b.0));
+
+		(top, children)
+	}
 }
 
 impl OverlayedChanges {
@@ -233,6 +268,7 @@ impl OverlayedChanges {
 	/// `None` can be used to delete a value specified by the given key.
 	pub(crate) fn set_storage(&mut self, key: StorageKey, val: Option<StorageValue>) {
 		let extrinsic_index = self.extrinsic_index();
+		self.note_top_change(&key);
 		let entry = self.prospective.top.entry(key).or_default();
 		entry.value = val;
 
@@ -253,6 +289,7 @@ impl OverlayedChanges {
 		val: Option<StorageValue>,
 	) {
 		let extrinsic_index = self.extrinsic_index();
+		self.note_child_changes(&storage_key, &[&key]);
 		let map_entry = self.prospective.children.entry(storage_key)
 			.or_insert_with(|| (Default::default(), child_info.to_owned()));
 		let updatable = map_entry.1.try_update(child_info);
@@ -279,6 +316,10 @@ impl OverlayedChanges {
 		child_info: ChildInfo,
 	) {
 		let extrinsic_index = self.extrinsic_index();
+		if !self.transactions.is_empty() {
+			let keys = self.child_keys(storage_key, |_| true);
+			self.note_child_changes(storage_key, &keys.iter().collect::<Vec<_>>());
+		}
 		let map_entry = self.prospective.children.entry(storage_key.to_vec())
 			.or_insert_with(|| (Default::default(), child_info.to_owned()));
 		let updatable = map_entry.1.try_update(child_info);
@@ -318,6 +359,14 @@ impl OverlayedChanges {
 	/// [`discard_prospective`]: #method.discard_prospective
 	pub(crate) fn clear_prefix(&mut self, prefix: &[u8]) {
 		let extrinsic_index = self.extrinsic_index();
+		if !self.transactions.is_empty() {
+			let keys: BTreeSet<StorageKey> = self.prospective.top.keys()
+				.chain(self.committed.top.keys())
+				.filter(|key| key.starts_with(prefix))
+				.cloned()
+				.collect();
+			keys.iter().for_each(|key| self.note_top_change(key));
+		}
 
 		// Iterate over all prospective and mark all keys that share
 		// the given prefix as removed (None).
@@ -354,6 +403,10 @@ impl OverlayedChanges {
 		prefix: &[u8],
 	) {
 		let extrinsic_index = self.extrinsic_index();
+		if !self.transactions.is_empty() {
+			let keys = self.child_keys(storage_key, |key| key.starts_with(prefix));
+			self.note_child_changes(storage_key, &keys.iter().collect::<Vec<_>>());
+		}
 		let map_entry = self.prospective.children.entry(storage_key.to_vec())
 			.or_insert_with(|| (Default::default(), child_info.to_owned()));
 		let updatable = map_entry.1.try_update(child_info);
@@ -388,12 +441,18 @@ impl OverlayedChanges {
 	}
 
 	/// Discard prospective changes to state.
+	///
+	/// Any open storage transactions are discarded as well.
 	pub fn discard_prospective(&mut self) {
 		self.prospective.clear();
+		self.transactions.clear();
 	}
 
 	/// Commit prospective changes to state.
+	///
+	/// Any open storage transactions are closed, keeping their changes.
 	pub fn commit_prospective(&mut self) {
+		self.transactions.clear();
 		if self.committed.is_empty() {
 			mem::swap(&mut self.prospective, &mut self.committed);
 		} else {
@@ -424,6 +483,109 @@ impl OverlayedChanges {
 		}
 	}
 
+	/// Start a new nested storage transaction.
+	///
+	/// All prospective changes made from now on can be undone with `rollback_transaction`, or
+	/// kept with `commit_transaction`. Every transaction must be closed by exactly one of these.
+	pub fn start_transaction(&mut self) {
+		self.transactions.push(Default::default());
+	}
+
+	/// Undo all prospective changes made since the innermost open storage transaction was
+	/// started, and close it.
+	///
+	/// Returns an error if there is no open transaction.
+	pub fn rollback_transaction(&mut self) -> Result<(), ()> {
+		let layer = self.transactions.pop().ok_or(())?;
+		for (key, value) in layer.top {
+			match value {
+				Some(value) => self.prospective.top.insert(key, value),
+				None => self.prospective.top.remove(&key),
+			};
+		}
+		for (storage_key, (child_info, values)) in layer.children {
+			let child_info = match child_info {
+				Some(child_info) => child_info,
+				None => {
+					self.prospective.children.remove(&storage_key);
+					continue
+				},
+			};
+			if let Some(map_entry) = self.prospective.children.get_mut(&storage_key) {
+				for (key, value) in values {
+					match value {
+						Some(value) => map_entry.0.insert(key, value),
+						None => map_entry.0.remove(&key),
+					};
+				}
+				map_entry.1 = child_info;
+			}
+		}
+		Ok(())
+	}
+
+	/// Close the innermost open storage transaction, keeping the changes made within it.
+	///
+	/// Returns an error if there is no open transaction.
+	pub fn commit_transaction(&mut self) -> Result<(), ()> {
+		let layer = self.transactions.pop().ok_or(())?;
+		// The outer transaction keeps the values from before it changed them first.
+		if let Some(outer) = self.transactions.last_mut() {
+			for (key, value) in layer.top {
+				outer.top.entry(key).or_insert(value);
+			}
+			for (storage_key, (child_info, values)) in layer.children {
+				let outer_child = outer.children.entry(storage_key)
+					.or_insert_with(|| (child_info, Default::default()));
+				for (key, value) in values {
+					outer_child.1.entry(key).or_insert(value);
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Record the prospective value of `key` in the innermost open storage transaction, unless it
+	/// already changed it.
+	fn note_top_change(&mut self, key: &[u8]) {
+		if let Some(layer) = self.transactions.last_mut() {
+			if !layer.top.contains_key(key) {
+				layer.top.insert(key.to_vec(), self.prospective.top.get(key).cloned());
+			}
+		}
+	}
+
+	/// Record the prospective values of `keys` of the child storage `storage_key`, and its child
+	/// info, in the innermost open storage transaction, unless it already changed them.
+	fn note_child_changes(&mut self, storage_key: &[u8], keys: &[&StorageKey]) {
+		if let Some(layer) = self.transactions.last_mut() {
+			let prospective = self.prospective.children.get(storage_key);
+			let layer_child = layer.children.entry(storage_key.to_vec())
+				.or_insert_with(|| (prospective.map(|c| c.1.clone()), Default::default()));
+			for key in keys {
+				if !layer_child.1.contains_key(*key) {
+					let value = prospective.and_then(|c| c.0.get(*key)).cloned();
+					layer_child.1.insert((*key).clone(), value);
+				}
+			}
+		}
+	}
+
+	/// The keys of the child storage `storage_key` with prospective or committed changes that
+	/// satisfy `filter`.
+	fn child_keys(
+		&self,
+		storage_key: &[u8],
+		filter: impl Fn(&StorageKey) -> bool,
+	) -> BTreeSet<StorageKey> {
+		self.prospective.children.get(storage_key).into_iter()
+			.chain(self.committed.children.get(storage_key))
+			.flat_map(|(changes, _)| changes.keys())
+			.filter(|key| filter(key))
+			.cloned()
+			.collect()
+	}
+
 	/// Consume `OverlayedChanges` and take committed set.
 	///
 	/// Panics:
@@ -873,4 +1035,70 @@ mod tests {
 		assert_eq!(next_to_40.0.to_vec(), vec![50]);
 		assert_eq!(next_to_40.1.value, Some(vec![50]));
 	}
+
+	#[test]
+	fn nested_transactions_work() {
+		let mut overlay = OverlayedChanges::default();
+		overlay.set_storage(vec![1], Some(vec![1]));
+
+		overlay.start_transaction();
+		overlay.set_storage(vec![1], Some(vec![2]));
+		overlay.set_storage(vec![2], Some(vec![2]));
+
+		overlay.start_transaction();
+		overlay.set_storage(vec![2], None);
+		overlay.set_storage(vec![3], Some(vec![3]));
+		assert_eq!(overlay.storage(&[2]).unwrap(), None);
+		assert_eq!(overlay.rollback_transaction(), Ok(()));
+
+		// Only the changes of the inner transaction are undone.
+		assert_eq!(overlay.storage(&[1]).unwrap(), Some(&[2][..]));
+		assert_eq!(overlay.storage(&[2]).unwrap(), Some(&[2][..]));
+		assert!(overlay.storage(&[3]).is_none());
+
+		overlay.start_transaction();
+		overlay.set_storage(vec![3], Some(vec![3]));
+		assert_eq!(overlay.commit_transaction(), Ok(()));
+		assert_eq!(overlay.rollback_transaction(), Ok(()));
+
+		// Rolling back the outer transaction undoes the committed inner one too.
+		assert_eq!(overlay.storage(&[1]).unwrap(), Some(&[1][..]));
+		assert!(overlay.storage(&[2]).is_none());
+		assert!(overlay.storage(&[3]).is_none());
+
+		assert_eq!(overlay.rollback_transaction(), Err(()));
+		assert_eq!(overlay.commit_transaction(), Err(()));
+	}
+
+	#[test]
+	fn transactions_undo_cleared_prefixes_and_child_storage() {
+		let child_info = ChildInfo::new_default(b"uniqueid");
+		let child = b"Child1".to_vec();
+		let other_child = b"Child2".to_vec();
+		let mut overlay = OverlayedChanges::default();
+		overlay.set_storage(vec![1, 1], Some(vec![1]));
+		overlay.commit_prospective();
+		overlay.set_storage(vec![1, 2], Some(vec![2]));
+		overlay.set_child_storage(child.clone(), child_info, vec![1], Some(vec![1]));
+
+		overlay.start_transaction();
+		overlay.clear_prefix(&[1]);
+		overlay.set_child_storage(child.clone(), child_info, vec![1], Some(vec![2]));
+		overlay.clear_child_storage(&child, child_info);
+		overlay.set_child_storage(other_child, child_info, vec![1], None);
+		assert_eq!(overlay.storage(&[1, 1]).unwrap(), None);
+		assert_eq!(overlay.child_storage(&child, &[1]).unwrap(), None);
+
+		overlay.start_transaction();
+		overlay.set_storage(vec![1, 3], Some(vec![3]));
+		assert_eq!(overlay.commit_transaction(), Ok(()));
+		assert_eq!(overlay.rollback_transaction(), Ok(()));
+
+		assert_eq!(overlay.storage(&[1, 1]).unwrap(), Some(&[1][..]));
+		assert_eq!(overlay.storage(&[1, 2]).unwrap(), Some(&[2][..]));
+		assert!(overlay.storage(&[1, 3]).is_none());
+		assert_eq!(overlay.child_storage(&child, &[1]).unwrap(), Some(&[1][..]));
+		assert_eq!(overlay.prospective.top.len(), 1);
+		assert_eq!(overlay.prospective.children.len(), 1);
+	}
 }